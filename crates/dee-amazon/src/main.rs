@@ -1,10 +1,13 @@
-use std::fs;
-use std::path::PathBuf;
-
 use clap::{Args, Parser, Subcommand};
+use dee_core::http::{Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, report, Config, ConfigError, ErrorCode, Format, OkItem, OkList,
+    OkMessage,
+};
 use regex::Regex;
-use reqwest::blocking::Client;
 use reqwest::Url;
+use schemars::JsonSchema;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +35,11 @@ struct GlobalArgs {
     quiet: bool,
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -78,7 +86,7 @@ struct ShowFlags {
     json: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
 struct AppConfig {
     #[serde(default)]
     user_agent: Option<String>,
@@ -86,33 +94,7 @@ struct AppConfig {
     base_url: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct OkList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct OkItem<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
-struct OkMessage {
-    ok: bool,
-    message: String,
-}
-
-#[derive(Debug, Serialize)]
-struct ErrorJson {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ProductItem {
     id: String,
     title: String,
@@ -130,8 +112,8 @@ struct ProductItem {
 
 #[derive(Debug, thiserror::Error)]
 enum AppError {
-    #[error("Configuration directory not found")]
-    ConfigMissing,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
     #[error("HTTP request failed")]
@@ -140,39 +122,48 @@ enum AppError {
     ApiError(String),
     #[error("Parse failed")]
     ParseFailed,
-    #[error("JSON serialization failed")]
-    Serialize,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
-            Self::ConfigMissing => "CONFIG_MISSING",
+            Self::Config(err) => err.code(),
             Self::InvalidArgument(_) => "INVALID_ARGUMENT",
             Self::RequestFailed => "REQUEST_FAILED",
             Self::ApiError(_) => "API_ERROR",
             Self::ParseFailed => "PARSE_FAILED",
-            Self::Serialize => "SERIALIZE",
         }
     }
 }
 
 type AppResult<T> = Result<T, AppError>;
 
+/// `DEE_AMAZON_USER_AGENT` and `DEE_AMAZON_BASE_URL` override the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-amazon")
+        .env_override("user_agent")
+        .env_override("base_url")
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<ProductItem>("search")
+        .message("config set")
+        .item::<AppConfig>("config show")
+        .list::<String>("config path")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = dispatch(&cli) {
-        if cli.global.json {
-            print_json(&ErrorJson {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -191,7 +182,7 @@ fn cmd_search(args: &SearchArgs, global: &GlobalArgs) -> AppResult<()> {
         ));
     }
 
-    let config = load_config_or_default()?;
+    let config = config_file().load()?;
     let base_url = args
         .base_url
         .clone()
@@ -210,7 +201,10 @@ fn cmd_search(args: &SearchArgs, global: &GlobalArgs) -> AppResult<()> {
         urlencoding::encode(args.query.trim())
     );
 
-    let client = Client::new();
+    let client = Http::new("dee-amazon", env!("CARGO_PKG_VERSION"))
+        .args(&global.http)
+        .blocking()
+        .map_err(|_| AppError::RequestFailed)?;
     let response = client
         .get(url)
         .header("User-Agent", user_agent)
@@ -225,12 +219,8 @@ fn cmd_search(args: &SearchArgs, global: &GlobalArgs) -> AppResult<()> {
     let html = response.text().map_err(|_| AppError::RequestFailed)?;
     let items = parse_products(&html, args.limit)?;
 
-    if global.json {
-        print_json(&OkList {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(global.output, global.json) {
+        print_list(&items, format);
         return Ok(());
     }
 
@@ -383,7 +373,7 @@ fn cmd_config(args: &ConfigArgs, global: &GlobalArgs) -> AppResult<()> {
 }
 
 fn cmd_config_set(args: &ConfigSetArgs, global: &GlobalArgs) -> AppResult<()> {
-    let mut config = load_config_or_default()?;
+    let mut config = config_file().load_file()?;
 
     match args.key.as_str() {
         "amazon.user-agent" => {
@@ -402,7 +392,7 @@ fn cmd_config_set(args: &ConfigSetArgs, global: &GlobalArgs) -> AppResult<()> {
         }
     }
 
-    save_config(&config)?;
+    config_file().save(&config)?;
 
     if global.json || args.output.json {
         print_json(&OkMessage {
@@ -428,7 +418,7 @@ fn ensure_not_empty(value: &str) -> AppResult<()> {
 }
 
 fn cmd_config_show(json: bool) -> AppResult<()> {
-    let config = load_config_or_default()?;
+    let config = config_file().load()?;
 
     if json {
         print_json(&OkItem {
@@ -444,7 +434,7 @@ fn cmd_config_show(json: bool) -> AppResult<()> {
 }
 
 fn cmd_config_path(json: bool) -> AppResult<()> {
-    let path = config_path()?;
+    let path = config_file().path();
     let rendered = path.display().to_string();
 
     if json {
@@ -459,93 +449,3 @@ fn cmd_config_path(json: bool) -> AppResult<()> {
 
     Ok(())
 }
-
-fn config_path() -> AppResult<PathBuf> {
-    let base = dirs::config_dir().ok_or(AppError::ConfigMissing)?;
-    Ok(base.join("dee-amazon").join("config.toml"))
-}
-
-fn load_config_or_default() -> AppResult<AppConfig> {
-    match load_config() {
-        Ok(config) => Ok(config),
-        Err(AppError::InvalidArgument(_)) => Ok(AppConfig::default()),
-        Err(other) => Err(other),
-    }
-}
-
-fn load_config() -> AppResult<AppConfig> {
-    let path = config_path()?;
-    if !path.exists() {
-        return Err(AppError::InvalidArgument(
-            "config file not found".to_string(),
-        ));
-    }
-
-    let raw = fs::read_to_string(path)
-        .map_err(|_| AppError::InvalidArgument("failed to read config".to_string()))?;
-    toml::from_str(&raw).map_err(|_| AppError::InvalidArgument("invalid config TOML".to_string()))
-}
-
-fn save_config(config: &AppConfig) -> AppResult<()> {
-    let path = config_path()?;
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|_| AppError::InvalidArgument("failed to create config dir".to_string()))?;
-    }
-
-    let raw = toml::to_string(config)
-        .map_err(|_| AppError::InvalidArgument("failed to encode config".to_string()))?;
-    fs::write(path, raw)
-        .map_err(|_| AppError::InvalidArgument("failed to write config".to_string()))
-}
-
-fn print_json<T: Serialize>(value: &T) {
-    if let Ok(rendered) = serde_json::to_string(value) {
-        println!("{rendered}");
-    } else {
-        let payload = ErrorJson {
-            ok: false,
-            error: "JSON serialization failed".to_string(),
-            code: AppError::Serialize.code().to_string(),
-        };
-        if let Ok(rendered) = serde_json::to_string(&payload) {
-            println!("{rendered}");
-        } else {
-            println!(
-                "{{\"ok\":false,\"error\":\"JSON serialization failed\",\"code\":\"SERIALIZE\"}}"
-            );
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
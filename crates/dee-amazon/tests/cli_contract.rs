@@ -110,3 +110,34 @@ fn quiet_path_output_is_non_empty() {
         .success()
         .stdout(predicate::str::contains("dee-amazon"));
 }
+
+#[test]
+fn search_output_csv_and_json_errors() {
+    let home = TempDir::new().expect("temp dir");
+    let (base, handle) = start_mock_amazon();
+
+    let mut search = cmd();
+    with_temp_home(&mut search, &home);
+    search
+        .args(["search", "keyboard", "--base-url", &base, "--output", "csv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("id,title,"))
+        .stdout(predicate::str::contains("B001,Test Keyboard,"));
+    handle.join().expect("server thread should finish");
+
+    let mut search = cmd();
+    with_temp_home(&mut search, &home);
+    search
+        .args([
+            "search",
+            "keyboard",
+            "--base-url",
+            "notaurl",
+            "--output",
+            "jsonl",
+        ])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("\"code\":\"INVALID_ARGUMENT\""));
+}
@@ -0,0 +1,29 @@
+CREATE TABLE papers (
+  id TEXT PRIMARY KEY,
+  version_id TEXT NOT NULL,
+  title TEXT NOT NULL,
+  authors TEXT NOT NULL DEFAULT '[]',
+  year INTEGER NOT NULL DEFAULT 0,
+  published TEXT NOT NULL DEFAULT '',
+  abstract_text TEXT NOT NULL DEFAULT '',
+  url TEXT NOT NULL DEFAULT '',
+  citations INTEGER NOT NULL DEFAULT 0,
+  categories TEXT NOT NULL DEFAULT '[]',
+  doi TEXT,
+  journal_ref TEXT,
+  added_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE TABLE paper_tags (
+  paper_id TEXT NOT NULL REFERENCES papers(id) ON DELETE CASCADE,
+  tag TEXT NOT NULL,
+  PRIMARY KEY (paper_id, tag)
+);
+
+CREATE TABLE paper_notes (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  paper_id TEXT NOT NULL REFERENCES papers(id) ON DELETE CASCADE,
+  body TEXT NOT NULL,
+  created_at TEXT NOT NULL
+);
@@ -0,0 +1,14 @@
+CREATE TABLE alerts (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  name TEXT NOT NULL,
+  query TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  last_run TEXT NOT NULL DEFAULT ''
+);
+
+CREATE TABLE alert_seen (
+  alert_id INTEGER NOT NULL REFERENCES alerts(id) ON DELETE CASCADE,
+  paper_id TEXT NOT NULL,
+  first_seen TEXT NOT NULL,
+  PRIMARY KEY (alert_id, paper_id)
+);
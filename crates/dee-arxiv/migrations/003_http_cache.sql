@@ -0,0 +1,5 @@
+CREATE TABLE http_cache (
+  key TEXT PRIMARY KEY,
+  body TEXT NOT NULL,
+  fetched_at INTEGER NOT NULL
+);
@@ -0,0 +1,4 @@
+CREATE TABLE host_requests (
+  host TEXT PRIMARY KEY,
+  last_request_ms INTEGER NOT NULL
+);
@@ -0,0 +1,215 @@
+use dee_core::cache::CacheArgs;
+use dee_core::ErrorCode;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    db, fetch_arxiv, library, print_json, print_list, query, AlertsArgs, AlertsCommand, AppError,
+    Format, GlobalArgs, OkMessage, PageArgs, PaperItem,
+};
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct NewPaper {
+    alert_id: i64,
+    alert: String,
+    #[serde(flatten)]
+    paper: PaperItem,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct AlertError {
+    alert_id: i64,
+    error: String,
+    code: String,
+}
+
+/// `alerts add --json`
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AlertAdded {
+    ok: bool,
+    message: String,
+    id: i64,
+    query: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RunReport {
+    ok: bool,
+    /// New papers across all alerts
+    count: usize,
+    /// Alerts that ran
+    checked: usize,
+    items: Vec<NewPaper>,
+    errors: Vec<AlertError>,
+}
+
+pub fn cmd_alerts(args: &AlertsArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    match &args.command {
+        AlertsCommand::Add(input) => {
+            let query = query::build(&input.filter)?;
+            let name = input
+                .name
+                .as_deref()
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .unwrap_or(&query)
+                .to_string();
+
+            let conn = db::open()?;
+            let id = db::insert_alert(&conn, &name, &query)?;
+
+            if out.json {
+                print_json(&AlertAdded {
+                    ok: true,
+                    message: "Alert added".to_string(),
+                    id,
+                    query,
+                });
+            } else if out.quiet {
+                println!("{id}");
+            } else {
+                println!("Alert {id} added: {query}");
+            }
+            Ok(())
+        }
+        AlertsCommand::List(input) => {
+            let conn = db::open()?;
+            let items = db::list_alerts(&conn)?;
+            if let Some(format) = Format::resolve(input.output, out.json) {
+                print_list(&items, format);
+            } else {
+                for alert in items {
+                    if out.quiet {
+                        println!("{}", alert.id);
+                    } else {
+                        let last_run = if alert.last_run.is_empty() {
+                            "never"
+                        } else {
+                            alert.last_run.as_str()
+                        };
+                        println!("{}  {}  last_run={last_run}", alert.id, alert.name);
+                        if alert.name != alert.query {
+                            println!("  {}", alert.query);
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+        AlertsCommand::Remove(input) => {
+            let conn = db::open()?;
+            if !db::remove_alert(&conn, input.id)? {
+                return Err(AppError::AlertNotFound(input.id));
+            }
+            if out.json {
+                print_json(&OkMessage {
+                    ok: true,
+                    message: "Alert removed".to_string(),
+                });
+            } else if !out.quiet {
+                println!("Alert {} removed", input.id);
+            }
+            Ok(())
+        }
+        AlertsCommand::Run(input) => {
+            if input.limit == 0 || input.limit > 100 {
+                return Err(AppError::InvalidArgument(
+                    "--limit must be between 1 and 100".to_string(),
+                ));
+            }
+
+            let mut conn = db::open()?;
+            let mut alerts = db::list_alerts(&conn)?;
+            if !input.ids.is_empty() {
+                if let Some(missing) = input
+                    .ids
+                    .iter()
+                    .find(|id| !alerts.iter().any(|a| a.id == **id))
+                {
+                    return Err(AppError::AlertNotFound(*missing));
+                }
+                alerts.retain(|alert| input.ids.contains(&alert.id));
+            }
+
+            // Alerts exist to spot new submissions, so never replay a cached page
+            let fresh = GlobalArgs {
+                cache: CacheArgs {
+                    no_cache: true,
+                    ..out.cache
+                },
+                ..out.clone()
+            };
+            let page = PageArgs {
+                start: 0,
+                fetch_all: false,
+                max: input.limit,
+            };
+            let mut report = RunReport {
+                ok: true,
+                count: 0,
+                checked: 0,
+                items: Vec::new(),
+                errors: Vec::new(),
+            };
+            for alert in &alerts {
+                let fetched = fetch_arxiv(
+                    &alert.query,
+                    input.limit,
+                    &page,
+                    Some("submittedDate"),
+                    &fresh,
+                );
+                let papers = match fetched {
+                    Ok(papers) => papers,
+                    Err(err) => {
+                        report.errors.push(AlertError {
+                            alert_id: alert.id,
+                            error: err.to_string(),
+                            code: err.code().to_string(),
+                        });
+                        continue;
+                    }
+                };
+
+                let keys: Vec<String> = papers
+                    .iter()
+                    .map(|paper| library::library_key(&paper.id))
+                    .collect();
+                let fresh = db::mark_seen(&mut conn, alert.id, &keys)?;
+                report.checked += 1;
+                for (paper, is_new) in papers.into_iter().zip(fresh) {
+                    if is_new {
+                        report.items.push(NewPaper {
+                            alert_id: alert.id,
+                            alert: alert.name.clone(),
+                            paper,
+                        });
+                    }
+                }
+            }
+            report.count = report.items.len();
+            report.ok = report.errors.is_empty();
+
+            if out.json {
+                print_json(&report);
+            } else if out.quiet {
+                for item in &report.items {
+                    println!("{}", item.paper.id);
+                }
+            } else {
+                for item in &report.items {
+                    println!("[{}] {}", item.alert, item.paper.title);
+                    println!("  {}", item.paper.url);
+                }
+                for err in &report.errors {
+                    eprintln!("error: alert {}: {}", err.alert_id, err.error);
+                }
+                println!("{} new papers from {} alerts", report.count, report.checked);
+            }
+            if !report.ok {
+                std::process::exit(dee_core::exit::FAILURE);
+            }
+            Ok(())
+        }
+    }
+}
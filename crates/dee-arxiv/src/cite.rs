@@ -0,0 +1,333 @@
+//! BibTeX and RIS entries built from arXiv Atom metadata.
+
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::PaperItem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CiteFormat {
+    Bibtex,
+    Ris,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CiteItem {
+    pub id: String,
+    pub format: CiteFormat,
+    pub text: String,
+}
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Render one entry per paper. BibTeX keys that would collide get `a`, `b`, ... suffixes.
+pub fn render(items: &[PaperItem], format: CiteFormat) -> Vec<CiteItem> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    items
+        .iter()
+        .map(|item| {
+            let text = match format {
+                CiteFormat::Bibtex => {
+                    let base = cite_key(item);
+                    let n = seen.entry(base.clone()).or_insert(0);
+                    let key = if *n == 0 {
+                        base
+                    } else {
+                        format!("{base}{}", suffix(*n))
+                    };
+                    *n += 1;
+                    bibtex(item, &key)
+                }
+                CiteFormat::Ris => ris(item),
+            };
+            CiteItem {
+                id: item.id.clone(),
+                format,
+                text,
+            }
+        })
+        .collect()
+}
+
+fn suffix(n: usize) -> String {
+    let mut n = n - 1;
+    let mut out = Vec::new();
+    loop {
+        out.push(b'a' + (n % 26) as u8);
+        n /= 26;
+        if n == 0 {
+            break;
+        }
+        n -= 1;
+    }
+    out.reverse();
+    String::from_utf8(out).expect("ascii letters")
+}
+
+/// arXiv id without the `vN` version suffix, e.g. `1706.03762v7` -> `1706.03762`.
+pub fn eprint(id: &str) -> &str {
+    match id.rfind('v') {
+        Some(pos)
+            if pos > 0
+                && pos + 1 < id.len()
+                && id[pos + 1..].chars().all(|c| c.is_ascii_digit()) =>
+        {
+            &id[..pos]
+        }
+        _ => id,
+    }
+}
+
+fn ascii_word(input: &str) -> String {
+    input
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// `<first author surname><year><first title word>`, e.g. `vaswani2017attention`.
+fn cite_key(item: &PaperItem) -> String {
+    let surname = item
+        .authors
+        .first()
+        .and_then(|name| name.split_whitespace().last())
+        .map(ascii_word)
+        .unwrap_or_default();
+    let word = item
+        .title
+        .split_whitespace()
+        .map(ascii_word)
+        .find(|w| {
+            w.len() > 3 && !matches!(w.as_str(), "with" | "from" | "into" | "over" | "towards")
+        })
+        .unwrap_or_default();
+    let surname = if surname.is_empty() {
+        "arxiv".to_string()
+    } else {
+        surname
+    };
+    let year = if item.year > 0 {
+        item.year.to_string()
+    } else {
+        String::new()
+    };
+    format!("{surname}{year}{word}")
+}
+
+/// `Ashish Vaswani` -> `Vaswani, Ashish`. Single-word names are kept as-is.
+fn surname_first(name: &str) -> String {
+    let parts: Vec<&str> = name.split_whitespace().collect();
+    match parts.split_last() {
+        Some((last, given)) if !given.is_empty() => format!("{last}, {}", given.join(" ")),
+        _ => parts.join(" "),
+    }
+}
+
+/// Make field text safe inside `{...}`. arXiv titles and abstracts often carry LaTeX
+/// math, so `$`, `_`, `^` and backslashes are left alone; bare `&`, `%`, `#` are escaped,
+/// and braces are escaped only when they do not balance.
+fn bibtex_escape(input: &str) -> String {
+    let mut depth: i64 = 0;
+    let mut balanced = true;
+    let mut prev = '\0';
+    for c in input.chars() {
+        if prev != '\\' {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        if depth < 0 {
+            balanced = false;
+        }
+        prev = c;
+    }
+    balanced &= depth == 0;
+
+    let mut out = String::with_capacity(input.len());
+    let mut prev = '\0';
+    for c in input.chars() {
+        let escape =
+            prev != '\\' && (matches!(c, '&' | '%' | '#') || (!balanced && matches!(c, '{' | '}')));
+        if escape {
+            out.push('\\');
+        }
+        out.push(c);
+        prev = c;
+    }
+    out
+}
+
+fn month(published: &str) -> Option<usize> {
+    published
+        .get(5..7)
+        .and_then(|m| m.parse::<usize>().ok())
+        .filter(|m| (1..=12).contains(m))
+}
+
+fn abs_url(item: &PaperItem) -> String {
+    format!("https://arxiv.org/abs/{}", eprint(&item.id))
+}
+
+pub fn bibtex(item: &PaperItem, key: &str) -> String {
+    let mut fields: Vec<(&str, String)> = vec![
+        ("title", format!("{{{}}}", bibtex_escape(&item.title))),
+        (
+            "author",
+            format!(
+                "{{{}}}",
+                item.authors
+                    .iter()
+                    .map(|a| bibtex_escape(&surname_first(a)))
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            ),
+        ),
+    ];
+    if item.year > 0 {
+        fields.push(("year", format!("{{{}}}", item.year)));
+    }
+    if let Some(m) = month(&item.published) {
+        fields.push(("month", MONTHS[m - 1].to_string()));
+    }
+    fields.push(("eprint", format!("{{{}}}", eprint(&item.id))));
+    fields.push(("archivePrefix", "{arXiv}".to_string()));
+    if let Some(primary) = item.categories.first() {
+        fields.push(("primaryClass", format!("{{{primary}}}")));
+    }
+    if let Some(doi) = &item.doi {
+        fields.push(("doi", format!("{{{doi}}}")));
+    }
+    if let Some(journal) = &item.journal_ref {
+        fields.push(("note", format!("{{{}}}", bibtex_escape(journal))));
+    }
+    fields.push(("url", format!("{{{}}}", abs_url(item))));
+
+    let mut out = format!("@misc{{{key},\n");
+    for (idx, (name, value)) in fields.iter().enumerate() {
+        let comma = if idx + 1 < fields.len() { "," } else { "" };
+        out.push_str(&format!("  {name:<13} = {value}{comma}\n"));
+    }
+    out.push('}');
+    out
+}
+
+pub fn ris(item: &PaperItem) -> String {
+    let mut lines = vec![("TY", "UNPB".to_string()), ("TI", item.title.clone())];
+    for author in &item.authors {
+        lines.push(("AU", surname_first(author)));
+    }
+    if item.year > 0 {
+        lines.push(("PY", item.year.to_string()));
+    }
+    if let Some(date) = item
+        .published
+        .get(0..10)
+        .filter(|_| month(&item.published).is_some())
+    {
+        lines.push(("DA", date.replace('-', "/")));
+    }
+    if !item.abstract_text.is_empty() {
+        lines.push(("AB", item.abstract_text.clone()));
+    }
+    for category in &item.categories {
+        lines.push(("KW", category.clone()));
+    }
+    if let Some(doi) = &item.doi {
+        lines.push(("DO", doi.clone()));
+    }
+    if let Some(journal) = &item.journal_ref {
+        lines.push(("N1", journal.clone()));
+    }
+    lines.push(("PB", "arXiv".to_string()));
+    lines.push(("M1", format!("arXiv:{}", eprint(&item.id))));
+    lines.push(("UR", abs_url(item)));
+
+    let mut out = String::new();
+    for (tag, value) in lines {
+        out.push_str(&format!("{tag}  - {value}\n"));
+    }
+    out.push_str("ER  - ");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paper() -> PaperItem {
+        PaperItem {
+            id: "1706.03762v7".to_string(),
+            title: "Attention Is All You Need".to_string(),
+            authors: vec!["Ashish Vaswani".to_string(), "Noam Shazeer".to_string()],
+            year: 2017,
+            published: "2017-06-12T17:57:34Z".to_string(),
+            abstract_text: "The dominant sequence transduction models...".to_string(),
+            url: "http://arxiv.org/abs/1706.03762v7".to_string(),
+            citations: 0,
+            categories: vec!["cs.CL".to_string(), "cs.LG".to_string()],
+            doi: None,
+            journal_ref: None,
+        }
+    }
+
+    #[test]
+    fn bibtex_entry_is_well_formed() {
+        let entry = &render(&[paper()], CiteFormat::Bibtex)[0].text;
+        assert!(entry.starts_with("@misc{vaswani2017attention,\n"));
+        assert!(entry.contains("author        = {Vaswani, Ashish and Shazeer, Noam},"));
+        assert!(entry.contains("month         = jun,"));
+        assert!(entry.contains("eprint        = {1706.03762},"));
+        assert!(entry.contains("primaryClass  = {cs.CL},"));
+        assert!(entry.ends_with("url           = {https://arxiv.org/abs/1706.03762}\n}"));
+        assert_eq!(entry.matches('{').count(), entry.matches('}').count());
+    }
+
+    #[test]
+    fn duplicate_keys_get_suffixes() {
+        let entries = render(&[paper(), paper(), paper()], CiteFormat::Bibtex);
+        assert!(entries[1].text.starts_with("@misc{vaswani2017attentiona,"));
+        assert!(entries[2].text.starts_with("@misc{vaswani2017attentionb,"));
+        assert_eq!(suffix(27), "aa");
+    }
+
+    #[test]
+    fn escapes_specials_but_keeps_math() {
+        assert_eq!(
+            bibtex_escape("Q&A at 100% on $O(n^2)$"),
+            r"Q\&A at 100\% on $O(n^2)$"
+        );
+        assert_eq!(
+            bibtex_escape(r"Already \& fine {GNN}"),
+            r"Already \& fine {GNN}"
+        );
+        assert_eq!(bibtex_escape("Broken } brace"), r"Broken \} brace");
+    }
+
+    #[test]
+    fn ris_entry_has_tags_and_terminator() {
+        let mut item = paper();
+        item.doi = Some("10.1000/xyz".to_string());
+        let entry = ris(&item);
+        assert!(entry.starts_with("TY  - UNPB\nTI  - Attention Is All You Need\n"));
+        assert!(entry.contains("AU  - Vaswani, Ashish\nAU  - Shazeer, Noam\n"));
+        assert!(entry.contains("DA  - 2017/06/12\n"));
+        assert!(entry.contains("DO  - 10.1000/xyz\n"));
+        assert!(entry.ends_with("UR  - https://arxiv.org/abs/1706.03762\nER  - "));
+    }
+
+    #[test]
+    fn version_suffix_is_stripped() {
+        assert_eq!(eprint("2312.12345v2"), "2312.12345");
+        assert_eq!(eprint("hep-th/9901001v1"), "hep-th/9901001");
+        assert_eq!(eprint("2312.12345"), "2312.12345");
+    }
+}
@@ -0,0 +1,480 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use rusqlite_migration::{Migrations, M};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{AppError, PaperItem};
+
+/// A saved paper with its tags and notes. `id` is the versioned id that was saved;
+/// the library itself is keyed on the unversioned id.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct LibItem {
+    #[serde(flatten)]
+    pub paper: PaperItem,
+    pub tags: Vec<String>,
+    pub notes: Vec<Note>,
+    pub added_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Note {
+    pub id: i64,
+    pub text: String,
+    pub created_at: String,
+}
+
+/// A saved query re-run by `alerts run`. `query` is the composed arXiv search query.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Alert {
+    pub id: i64,
+    pub name: String,
+    pub query: String,
+    pub created_at: String,
+    /// Empty until the first run
+    pub last_run: String,
+}
+
+pub fn db_path() -> Result<PathBuf, AppError> {
+    let base = dirs::data_dir().ok_or(AppError::DataDirMissing)?;
+    Ok(base.join("dee-arxiv").join("library.db"))
+}
+
+fn migrations() -> Migrations<'static> {
+    Migrations::new(vec![
+        M::up(include_str!("../migrations/001_initial.sql")),
+        M::up(include_str!("../migrations/002_alerts.sql")),
+        M::up(include_str!("../migrations/003_http_cache.sql")),
+        M::up(include_str!("../migrations/004_host_requests.sql")),
+        // Responses moved to the shared cache dir (`dee_core::cache`)
+        M::up(include_str!("../migrations/005_drop_http_cache.sql")),
+        // Request spacing moved to the shared limiter (`dee_core::ratelimit`)
+        M::up(include_str!("../migrations/006_drop_host_requests.sql")),
+    ])
+}
+
+pub fn open() -> Result<Connection, AppError> {
+    let path = db_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|_| AppError::DataDirMissing)?;
+    }
+    init(Connection::open(path).map_err(|_| AppError::Database)?)
+}
+
+fn init(mut conn: Connection) -> Result<Connection, AppError> {
+    conn.execute_batch("PRAGMA foreign_keys = ON;")
+        .map_err(|_| AppError::Database)?;
+    migrations()
+        .to_latest(&mut conn)
+        .map_err(|_| AppError::Database)?;
+    Ok(conn)
+}
+
+/// Save a paper (refreshing its metadata if already saved) and add `tags`.
+/// Returns true when the paper was new.
+pub fn upsert_paper(
+    conn: &mut Connection,
+    key: &str,
+    item: &PaperItem,
+    tags: &[String],
+) -> Result<bool, AppError> {
+    let now = Utc::now().to_rfc3339();
+    let authors = serde_json::to_string(&item.authors).map_err(|_| AppError::Database)?;
+    let categories = serde_json::to_string(&item.categories).map_err(|_| AppError::Database)?;
+
+    let tx = conn.transaction().map_err(|_| AppError::Database)?;
+    let existed = tx
+        .query_row("SELECT 1 FROM papers WHERE id = ?1", params![key], |_| {
+            Ok(())
+        })
+        .optional()
+        .map_err(|_| AppError::Database)?
+        .is_some();
+    tx.execute(
+        "INSERT INTO papers
+         (id, version_id, title, authors, year, published, abstract_text, url, citations,
+          categories, doi, journal_ref, added_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?13)
+         ON CONFLICT(id) DO UPDATE SET
+           version_id = excluded.version_id,
+           title = excluded.title,
+           authors = excluded.authors,
+           year = excluded.year,
+           published = excluded.published,
+           abstract_text = excluded.abstract_text,
+           url = excluded.url,
+           citations = excluded.citations,
+           categories = excluded.categories,
+           doi = excluded.doi,
+           journal_ref = excluded.journal_ref,
+           updated_at = excluded.updated_at",
+        params![
+            key,
+            item.id,
+            item.title,
+            authors,
+            item.year,
+            item.published,
+            item.abstract_text,
+            item.url,
+            item.citations,
+            categories,
+            item.doi,
+            item.journal_ref,
+            now
+        ],
+    )
+    .map_err(|_| AppError::Database)?;
+    for tag in tags {
+        tx.execute(
+            "INSERT OR IGNORE INTO paper_tags (paper_id, tag) VALUES (?1, ?2)",
+            params![key, tag],
+        )
+        .map_err(|_| AppError::Database)?;
+    }
+    tx.commit().map_err(|_| AppError::Database)?;
+    Ok(!existed)
+}
+
+/// Returns false when `key` was not in the library.
+pub fn remove_paper(conn: &Connection, key: &str) -> Result<bool, AppError> {
+    let removed = conn
+        .execute("DELETE FROM papers WHERE id = ?1", params![key])
+        .map_err(|_| AppError::Database)?;
+    Ok(removed > 0)
+}
+
+pub fn remove_tags(conn: &Connection, key: &str, tags: &[String]) -> Result<(), AppError> {
+    for tag in tags {
+        conn.execute(
+            "DELETE FROM paper_tags WHERE paper_id = ?1 AND tag = ?2",
+            params![key, tag],
+        )
+        .map_err(|_| AppError::Database)?;
+    }
+    Ok(())
+}
+
+pub fn add_note(conn: &Connection, key: &str, text: &str) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT INTO paper_notes (paper_id, body, created_at) VALUES (?1, ?2, ?3)",
+        params![key, text, Utc::now().to_rfc3339()],
+    )
+    .map_err(|_| AppError::Database)?;
+    Ok(())
+}
+
+pub fn clear_notes(conn: &Connection, key: &str) -> Result<(), AppError> {
+    conn.execute("DELETE FROM paper_notes WHERE paper_id = ?1", params![key])
+        .map_err(|_| AppError::Database)?;
+    Ok(())
+}
+
+const SELECT_PAPERS: &str = "SELECT id, version_id, title, authors, year, published, abstract_text,
+        url, citations, categories, doi, journal_ref, added_at
+     FROM papers";
+
+pub fn get_paper(conn: &Connection, key: &str) -> Result<Option<LibItem>, AppError> {
+    let sql = format!("{SELECT_PAPERS} WHERE id = ?1");
+    let mut items = query_papers(conn, &sql, &[key.to_string()])?;
+    Ok(items.pop())
+}
+
+/// Newest first, optionally limited to papers carrying `tag`.
+pub fn list_papers(conn: &Connection, tag: Option<&str>) -> Result<Vec<LibItem>, AppError> {
+    match tag {
+        Some(tag) => {
+            let sql = format!(
+                "{SELECT_PAPERS}
+                 WHERE id IN (SELECT paper_id FROM paper_tags WHERE tag = ?1)
+                 ORDER BY added_at DESC, id"
+            );
+            query_papers(conn, &sql, &[tag.to_string()])
+        }
+        None => {
+            let sql = format!("{SELECT_PAPERS} ORDER BY added_at DESC, id");
+            query_papers(conn, &sql, &[])
+        }
+    }
+}
+
+/// Papers whose title, abstract, or notes contain every term (case-insensitive).
+pub fn search_papers(conn: &Connection, terms: &[String]) -> Result<Vec<LibItem>, AppError> {
+    let haystack = "(title || ' ' || abstract_text || ' ' ||
+        COALESCE((SELECT group_concat(body, ' ') FROM paper_notes WHERE paper_id = papers.id), ''))";
+    let clauses: Vec<String> = (1..=terms.len())
+        .map(|idx| format!("{haystack} LIKE ?{idx} ESCAPE '\\'"))
+        .collect();
+    let sql = format!(
+        "{SELECT_PAPERS} WHERE {} ORDER BY added_at DESC, id",
+        clauses.join(" AND ")
+    );
+    let patterns: Vec<String> = terms
+        .iter()
+        .map(|term| {
+            let escaped = term
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{escaped}%")
+        })
+        .collect();
+    query_papers(conn, &sql, &patterns)
+}
+
+fn query_papers(conn: &Connection, sql: &str, args: &[String]) -> Result<Vec<LibItem>, AppError> {
+    let mut stmt = conn.prepare(sql).map_err(|_| AppError::Database)?;
+    let rows = stmt
+        .query_map(params_from_iter(args.iter()), row_to_item)
+        .map_err(|_| AppError::Database)?;
+    let mut items = Vec::new();
+    for row in rows {
+        let (key, mut item) = row.map_err(|_| AppError::Database)?;
+        item.tags = load_tags(conn, &key)?;
+        item.notes = load_notes(conn, &key)?;
+        items.push(item);
+    }
+    Ok(items)
+}
+
+fn row_to_item(row: &Row<'_>) -> rusqlite::Result<(String, LibItem)> {
+    let key: String = row.get(0)?;
+    let authors: String = row.get(3)?;
+    let categories: String = row.get(9)?;
+    let paper = PaperItem {
+        id: row.get(1)?,
+        title: row.get(2)?,
+        authors: serde_json::from_str(&authors).unwrap_or_default(),
+        year: row.get(4)?,
+        published: row.get(5)?,
+        abstract_text: row.get(6)?,
+        url: row.get(7)?,
+        citations: row.get(8)?,
+        categories: serde_json::from_str(&categories).unwrap_or_default(),
+        doi: row.get(10)?,
+        journal_ref: row.get(11)?,
+    };
+    Ok((
+        key,
+        LibItem {
+            paper,
+            tags: Vec::new(),
+            notes: Vec::new(),
+            added_at: row.get(12)?,
+        },
+    ))
+}
+
+fn load_tags(conn: &Connection, key: &str) -> Result<Vec<String>, AppError> {
+    let mut stmt = conn
+        .prepare("SELECT tag FROM paper_tags WHERE paper_id = ?1 ORDER BY tag")
+        .map_err(|_| AppError::Database)?;
+    let rows = stmt
+        .query_map(params![key], |row| row.get(0))
+        .map_err(|_| AppError::Database)?;
+    rows.collect::<Result<_, _>>()
+        .map_err(|_| AppError::Database)
+}
+
+fn load_notes(conn: &Connection, key: &str) -> Result<Vec<Note>, AppError> {
+    let mut stmt = conn
+        .prepare("SELECT id, body, created_at FROM paper_notes WHERE paper_id = ?1 ORDER BY id")
+        .map_err(|_| AppError::Database)?;
+    let rows = stmt
+        .query_map(params![key], |row| {
+            Ok(Note {
+                id: row.get(0)?,
+                text: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        })
+        .map_err(|_| AppError::Database)?;
+    rows.collect::<Result<_, _>>()
+        .map_err(|_| AppError::Database)
+}
+
+pub fn insert_alert(conn: &Connection, name: &str, query: &str) -> Result<i64, AppError> {
+    conn.execute(
+        "INSERT INTO alerts (name, query, created_at) VALUES (?1, ?2, ?3)",
+        params![name, query, Utc::now().to_rfc3339()],
+    )
+    .map_err(|_| AppError::Database)?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn list_alerts(conn: &Connection) -> Result<Vec<Alert>, AppError> {
+    let mut stmt = conn
+        .prepare("SELECT id, name, query, created_at, last_run FROM alerts ORDER BY id")
+        .map_err(|_| AppError::Database)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(Alert {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                query: row.get(2)?,
+                created_at: row.get(3)?,
+                last_run: row.get(4)?,
+            })
+        })
+        .map_err(|_| AppError::Database)?;
+    rows.collect::<Result<_, _>>()
+        .map_err(|_| AppError::Database)
+}
+
+/// Returns false when no alert has this id.
+pub fn remove_alert(conn: &Connection, id: i64) -> Result<bool, AppError> {
+    let removed = conn
+        .execute("DELETE FROM alerts WHERE id = ?1", params![id])
+        .map_err(|_| AppError::Database)?;
+    Ok(removed > 0)
+}
+
+/// Record `paper_ids` as seen by the alert and stamp its run time.
+/// Returns, per id, whether it had not been seen before.
+pub fn mark_seen(
+    conn: &mut Connection,
+    alert_id: i64,
+    paper_ids: &[String],
+) -> Result<Vec<bool>, AppError> {
+    let now = Utc::now().to_rfc3339();
+    let tx = conn.transaction().map_err(|_| AppError::Database)?;
+    let mut fresh = Vec::with_capacity(paper_ids.len());
+    for paper_id in paper_ids {
+        let inserted = tx
+            .execute(
+                "INSERT OR IGNORE INTO alert_seen (alert_id, paper_id, first_seen)
+                 VALUES (?1, ?2, ?3)",
+                params![alert_id, paper_id, now],
+            )
+            .map_err(|_| AppError::Database)?;
+        fresh.push(inserted > 0);
+    }
+    tx.execute(
+        "UPDATE alerts SET last_run = ?2 WHERE id = ?1",
+        params![alert_id, now],
+    )
+    .map_err(|_| AppError::Database)?;
+    tx.commit().map_err(|_| AppError::Database)?;
+    Ok(fresh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paper(id: &str, title: &str) -> PaperItem {
+        PaperItem {
+            id: id.to_string(),
+            title: title.to_string(),
+            authors: vec!["Ada Lovelace".to_string()],
+            year: 2024,
+            published: "2024-01-02T00:00:00Z".to_string(),
+            abstract_text: "We study 100% of the graphs.".to_string(),
+            url: format!("http://arxiv.org/abs/{id}"),
+            citations: 3,
+            categories: vec!["cs.LG".to_string()],
+            doi: None,
+            journal_ref: None,
+        }
+    }
+
+    fn memory() -> Connection {
+        init(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn upsert_refreshes_and_merges_tags() {
+        let mut conn = memory();
+        let tags = vec!["gnn".to_string()];
+        assert!(upsert_paper(
+            &mut conn,
+            "2401.00001",
+            &paper("2401.00001v1", "Old"),
+            &tags
+        )
+        .unwrap());
+        let more = vec!["to-read".to_string()];
+        assert!(!upsert_paper(
+            &mut conn,
+            "2401.00001",
+            &paper("2401.00001v2", "New"),
+            &more
+        )
+        .unwrap());
+
+        let item = get_paper(&conn, "2401.00001").unwrap().unwrap();
+        assert_eq!(item.paper.id, "2401.00001v2");
+        assert_eq!(item.paper.title, "New");
+        assert_eq!(item.tags, vec!["gnn", "to-read"]);
+        assert_eq!(item.paper.authors, vec!["Ada Lovelace"]);
+    }
+
+    #[test]
+    fn search_covers_notes_and_escapes_wildcards() {
+        let mut conn = memory();
+        upsert_paper(&mut conn, "a", &paper("a", "Graph transformers"), &[]).unwrap();
+        upsert_paper(&mut conn, "b", &paper("b", "Diffusion models"), &[]).unwrap();
+        add_note(&conn, "b", "Compare with GRAPH baselines").unwrap();
+
+        let hits = search_papers(&conn, &["graph".to_string()]).unwrap();
+        assert_eq!(hits.len(), 2);
+        let hits = search_papers(&conn, &["graph".to_string(), "baselines".to_string()]).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].notes[0].text, "Compare with GRAPH baselines");
+        assert_eq!(search_papers(&conn, &["0%".to_string()]).unwrap().len(), 2);
+        assert!(search_papers(&conn, &["_x".to_string()])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn alerts_report_only_unseen_papers() {
+        let mut conn = memory();
+        let id = insert_alert(&conn, "gnn", "all:gnn").unwrap();
+        let ids = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            mark_seen(&mut conn, id, &ids(&["a", "b"])).unwrap(),
+            vec![true, true]
+        );
+        assert_eq!(
+            mark_seen(&mut conn, id, &ids(&["c", "a"])).unwrap(),
+            vec![true, false]
+        );
+        assert!(!list_alerts(&conn).unwrap()[0].last_run.is_empty());
+
+        // Another alert tracks its own history
+        let other = insert_alert(&conn, "other", "all:other").unwrap();
+        assert_eq!(
+            mark_seen(&mut conn, other, &ids(&["a"])).unwrap(),
+            vec![true]
+        );
+
+        assert!(remove_alert(&conn, id).unwrap());
+        assert!(!remove_alert(&conn, id).unwrap());
+        let left: i64 = conn
+            .query_row("SELECT COUNT(*) FROM alert_seen", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(left, 1);
+    }
+
+    #[test]
+    fn remove_cascades_to_tags_and_notes() {
+        let mut conn = memory();
+        upsert_paper(&mut conn, "a", &paper("a", "T"), &["x".to_string()]).unwrap();
+        add_note(&conn, "a", "note").unwrap();
+        assert!(remove_paper(&conn, "a").unwrap());
+        assert!(!remove_paper(&conn, "a").unwrap());
+        let orphans: i64 = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM paper_tags) + (SELECT COUNT(*) FROM paper_notes)",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(orphans, 0);
+        assert!(list_papers(&conn, Some("x")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+//! One place for outgoing requests: a response cache, per-host rate limits, and retries.
+//!
+//! Successful response bodies are stored in the shared cache dir (`dee_core::cache`)
+//! under the request (method, URL, and body) and reused for `CACHE_TTL_SECS`.
+//! `--no-cache` skips the lookup but still stores the fresh response; `--offline`
+//! answers only from the cache, at any age.
+//!
+//! Requests to rate-limited hosts wait for their turn in the shared per-host budgets
+//! (`dee_core::ratelimit`), so runs in a script stay under the limit together. 429 and 503 answers are retried with backoff,
+//! `--retries` times, so the client's own immediate retries are turned off.
+
+use std::thread;
+use std::time::Duration;
+
+use dee_core::cache::Cache;
+use dee_core::http::{retry_after, retry_delay, Http};
+use dee_core::ratelimit::Limiter;
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::StatusCode;
+
+use crate::{AppError, GlobalArgs};
+
+/// How long a cached response is served without `--offline`
+const CACHE_TTL_SECS: u64 = 60 * 60;
+/// Retries after the first 429/503 answer, without `--retries`
+const MAX_RETRIES: u32 = 3;
+/// First retry delay; doubles on each further retry
+const BACKOFF: Duration = Duration::from_secs(2);
+
+pub fn cache() -> Cache {
+    Cache::new("dee-arxiv").ttl_secs(CACHE_TTL_SECS)
+}
+
+fn settings(out: &GlobalArgs) -> Http {
+    Http::new("dee-arxiv", env!("CARGO_PKG_VERSION")).args(&out.http)
+}
+
+pub fn client(out: &GlobalArgs) -> Result<Client, AppError> {
+    settings(out)
+        .blocking_builder()
+        .retry(reqwest::retry::never())
+        .build()
+        .map_err(|_| AppError::RequestFailed)
+}
+
+/// Body of a successful response. A 404 is `NotFound`, an unreachable host or timeout is
+/// `Network`, a 429/503 that outlasts the retries is `RateLimited`, and any other error
+/// status is `RequestFailed`.
+pub fn send(request: RequestBuilder, out: &GlobalArgs) -> Result<String, AppError> {
+    let target = describe(&request)?;
+
+    let cache = cache();
+    if let Some(hit) = cache.lookup::<String>(&target.key, &out.cache)? {
+        tracing::debug!("cached {} ({}s old)", target.label, hit.age_secs);
+        return Ok(hit.value);
+    }
+
+    let limiter = Limiter::load()?;
+
+    let mut attempt = 0;
+    let resp = loop {
+        wait_turn(&limiter, &target.host);
+        tracing::debug!("{}", target.label);
+        let retry = request.try_clone().ok_or(AppError::RequestFailed)?;
+        let resp = retry
+            .send()
+            .map_err(|err| network_error(&err, &target.host, out))?;
+
+        let status = resp.status();
+        if !matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+        ) {
+            break resp;
+        }
+        if attempt == out.http.retries.unwrap_or(MAX_RETRIES) {
+            return Err(AppError::RateLimited(target.host));
+        }
+        let delay = retry_delay(BACKOFF, attempt, retry_after(resp.headers()));
+        tracing::debug!(
+            "{} answered {}, retrying in {}s",
+            target.host,
+            status.as_u16(),
+            delay.as_secs()
+        );
+        thread::sleep(delay);
+        attempt += 1;
+    };
+
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Err(AppError::NotFound);
+    }
+    let body = resp
+        .error_for_status()
+        .map_err(|_| AppError::RequestFailed)?
+        .text()
+        .map_err(|err| network_error(&err, &target.host, out))?;
+
+    cache.put(&target.key, &body);
+    Ok(body)
+}
+
+struct Target {
+    /// `GET <url>`, for `--verbose` and errors
+    label: String,
+    /// The label plus any POST body
+    key: String,
+    host: String,
+}
+
+fn describe(request: &RequestBuilder) -> Result<Target, AppError> {
+    let built = request
+        .try_clone()
+        .ok_or(AppError::RequestFailed)?
+        .build()
+        .map_err(|_| AppError::RequestFailed)?;
+    let label = format!("{} {}", built.method(), built.url());
+    let key = match built.body().and_then(|b| b.as_bytes()) {
+        Some(body) => format!("{label}\n{}", String::from_utf8_lossy(body)),
+        None => label.clone(),
+    };
+    let host = built.url().host_str().unwrap_or_default().to_string();
+    Ok(Target { label, key, host })
+}
+
+fn network_error(err: &reqwest::Error, host: &str, out: &GlobalArgs) -> AppError {
+    if err.is_timeout() {
+        AppError::Network(format!(
+            "{host} did not answer within {}s",
+            settings(out).timeout().as_secs()
+        ))
+    } else if err.is_connect() {
+        AppError::Network(format!("could not connect to {host}"))
+    } else {
+        AppError::Network(format!("connection to {host} failed"))
+    }
+}
+
+/// Sleep until the host's budget has room for another request.
+fn wait_turn(limiter: &Limiter, host: &str) {
+    let wait = limiter.reserve(host);
+    if wait.is_zero() {
+        return;
+    }
+    tracing::debug!("waiting {:.1}s before calling {host}", wait.as_secs_f64());
+    thread::sleep(wait);
+}
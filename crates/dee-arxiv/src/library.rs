@@ -0,0 +1,181 @@
+use crate::{
+    cite, db, enrich_citations, fetch_paper, print_json, print_list, AppError, Format, GlobalArgs,
+    LibArgs, LibCommand, OkItem, OkMessage,
+};
+
+/// Library key for a paper: the unversioned arXiv id, accepting `arXiv:` and abs-URL forms.
+pub fn library_key(paper_id: &str) -> String {
+    let id = paper_id.trim();
+    let id = id
+        .strip_prefix("arXiv:")
+        .or_else(|| id.strip_prefix("arxiv:"))
+        .unwrap_or(id);
+    let id = ["https://arxiv.org/abs/", "http://arxiv.org/abs/"]
+        .iter()
+        .find_map(|prefix| id.strip_prefix(prefix))
+        .unwrap_or(id);
+    cite::eprint(id).to_string()
+}
+
+fn clean_tags(tags: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = tags
+        .iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+fn saved(conn: &rusqlite::Connection, key: &str) -> Result<db::LibItem, AppError> {
+    db::get_paper(conn, key)?.ok_or_else(|| AppError::NotInLibrary(key.to_string()))
+}
+
+pub fn cmd_lib(args: &LibArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    match &args.command {
+        LibCommand::Add(input) => {
+            let key = library_key(&input.paper_id);
+            if key.is_empty() {
+                return Err(AppError::InvalidArgument(
+                    "paper id must not be empty".to_string(),
+                ));
+            }
+            let note = input.note.as_deref().map(str::trim).unwrap_or("");
+
+            let mut item = fetch_paper(&input.paper_id, out)?;
+            enrich_citations(std::slice::from_mut(&mut item), out)?;
+
+            let mut conn = db::open()?;
+            let created = db::upsert_paper(&mut conn, &key, &item, &clean_tags(&input.tags))?;
+            if !note.is_empty() {
+                db::add_note(&conn, &key, note)?;
+            }
+            let saved = saved(&conn, &key)?;
+
+            if out.json {
+                print_json(&OkItem {
+                    ok: true,
+                    item: saved,
+                });
+            } else if out.quiet {
+                println!("{key}");
+            } else {
+                let verb = if created { "Saved" } else { "Updated" };
+                println!("{verb} {key}: {}", saved.paper.title);
+            }
+            Ok(())
+        }
+        LibCommand::Remove(input) => {
+            let key = library_key(&input.paper_id);
+            let conn = db::open()?;
+            let tags = clean_tags(&input.tags);
+            let message = if tags.is_empty() {
+                if !db::remove_paper(&conn, &key)? {
+                    return Err(AppError::NotInLibrary(key));
+                }
+                format!("Removed {key} from the library")
+            } else {
+                saved(&conn, &key)?;
+                db::remove_tags(&conn, &key, &tags)?;
+                format!("Removed tags {} from {key}", tags.join(", "))
+            };
+
+            if out.json {
+                print_json(&OkMessage { ok: true, message });
+            } else if !out.quiet {
+                println!("{message}");
+            }
+            Ok(())
+        }
+        LibCommand::List(input) => {
+            let conn = db::open()?;
+            let tag = input.tag.as_deref().map(|t| t.trim().to_lowercase());
+            let items = db::list_papers(&conn, tag.as_deref())?;
+            print_items(items, input.output, out);
+            Ok(())
+        }
+        LibCommand::Note(input) => {
+            let key = library_key(&input.paper_id);
+            let text = input.text.as_deref().map(str::trim).unwrap_or("");
+            if text.is_empty() && !input.clear {
+                return Err(AppError::InvalidArgument(
+                    "provide note text, or --clear to delete notes".to_string(),
+                ));
+            }
+
+            let conn = db::open()?;
+            saved(&conn, &key)?;
+            if input.clear {
+                db::clear_notes(&conn, &key)?;
+            }
+            if !text.is_empty() {
+                db::add_note(&conn, &key, text)?;
+            }
+            let saved = saved(&conn, &key)?;
+
+            if out.json {
+                print_json(&OkItem {
+                    ok: true,
+                    item: saved,
+                });
+            } else if !out.quiet {
+                println!("{key}: {} note(s)", saved.notes.len());
+            }
+            Ok(())
+        }
+        LibCommand::Search(input) => {
+            let terms: Vec<String> = input
+                .terms
+                .iter()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect();
+            if terms.is_empty() {
+                return Err(AppError::InvalidArgument(
+                    "search terms must not be empty".to_string(),
+                ));
+            }
+            let conn = db::open()?;
+            let items = db::search_papers(&conn, &terms)?;
+            print_items(items, input.output, out);
+            Ok(())
+        }
+    }
+}
+
+fn print_items(items: Vec<db::LibItem>, output: Option<Format>, out: &GlobalArgs) {
+    if let Some(format) = Format::resolve(output, out.json) {
+        print_list(&items, format);
+    } else if out.quiet {
+        for item in items {
+            println!("{}", library_key(&item.paper.id));
+        }
+    } else {
+        for item in items {
+            println!("{} ({})", item.paper.title, item.paper.year);
+            println!("  {}", item.paper.id);
+            if !item.tags.is_empty() {
+                println!("  tags: {}", item.tags.join(", "));
+            }
+            for note in &item.notes {
+                println!("  note: {}", note.text);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_drop_versions_and_prefixes() {
+        assert_eq!(library_key(" 1706.03762v7 "), "1706.03762");
+        assert_eq!(library_key("arXiv:1706.03762"), "1706.03762");
+        assert_eq!(
+            library_key("https://arxiv.org/abs/hep-th/9901001v2"),
+            "hep-th/9901001"
+        );
+    }
+}
@@ -1,17 +1,43 @@
+mod alerts;
+mod cite;
+mod db;
+mod http;
+mod library;
+mod output;
+mod query;
+mod s2;
+mod sources;
+
+use std::collections::HashSet;
+
+use cite::CiteFormat;
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::cache::{CacheArgs, CacheCommand, CacheError};
+use dee_core::http::{base_url, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, report, Config, ConfigError, ErrorCode, Format, OkItem, OkList,
+    OkMessage,
+};
+use output::OutputFormat;
 use quick_xml::de::from_str;
-use reqwest::blocking::Client;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sources::Source;
 
-const ARXIV_API: &str = "https://export.arxiv.org/api/query";
-const S2_API: &str = "https://api.semanticscholar.org/graph/v1/paper/search";
+const ARXIV_API: &str = "https://export.arxiv.org/api";
+/// Results per request when paging with --fetch-all
+const PAGE_SIZE: usize = 100;
+/// The API refuses offsets beyond this
+const MAX_OFFSET: usize = 30_000;
+const MAX_FETCH_ALL: usize = 2_000;
 
 #[derive(Debug, Parser)]
 #[command(
     name = "dee-arxiv",
     version,
     about = "Academic paper search CLI",
-    after_help = "EXAMPLES:\n  dee-arxiv search \"graph neural networks\" --limit 10 --json\n  dee-arxiv get 2312.12345 --json\n  dee-arxiv author \"Yann LeCun\" --limit 5 --json"
+    after_help = "EXAMPLES:\n  dee-arxiv search \"graph neural networks\" --limit 10 --json\n  dee-arxiv search --title transformer --category cs.CL --from 2024-01-01 --exclude survey --json\n  dee-arxiv get 2312.12345 --json\n  dee-arxiv author \"Yann LeCun\" --limit 5 --json\n  dee-arxiv search \"dependency parsing\" --source openalex --sort citations --json\n  dee-arxiv doi 10.18653/v1/N19-1423 --json\n  dee-arxiv cite 1706.03762 --format bibtex >> refs.bib\n  dee-arxiv search --category cs.LG --from 2024-06-01 --limit 50 --output md >> reading.md\n  dee-arxiv lib add 1706.03762 --tag transformers --json\n  dee-arxiv citations 1706.03762 --limit 20 --sort citations --json\n  dee-arxiv related 1706.03762 --limit 20 --json\n  dee-arxiv alerts add \"graph neural networks\" --category cs.LG\n  dee-arxiv alerts run --json\n  dee-arxiv config set category cs.LG\n  dee-arxiv config set s2.api_key <KEY>\n  dee-arxiv cache clear"
 )]
 struct Cli {
     #[command(flatten)]
@@ -28,6 +54,10 @@ struct GlobalArgs {
     quiet: bool,
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+    #[command(flatten)]
+    cache: CacheArgs,
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -35,6 +65,27 @@ enum Commands {
     Search(SearchArgs),
     Get(GetArgs),
     Author(AuthorArgs),
+    /// Look up a journal or conference paper by DOI (Crossref)
+    Doi(DoiArgs),
+    /// Print a BibTeX or RIS entry for a paper
+    Cite(CiteArgs),
+    /// Papers citing a paper (Semantic Scholar)
+    Citations(GraphArgs),
+    /// Papers a paper cites (Semantic Scholar)
+    References(GraphArgs),
+    /// Papers similar to a paper, most similar first (Semantic Scholar)
+    Related(RelatedArgs),
+    /// Local reading list with tags and notes
+    Lib(LibArgs),
+    /// Saved searches that report papers not seen on earlier runs
+    Alerts(AlertsArgs),
+    /// Defaults for search flags and the Semantic Scholar API key
+    Config(ConfigArgs),
+    /// Manage cached API responses
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -45,13 +96,71 @@ enum SortBy {
 
 #[derive(Debug, Args)]
 struct SearchArgs {
-    query: String,
-    #[arg(long, default_value_t = 10)]
-    limit: usize,
+    #[command(flatten)]
+    filter: QueryArgs,
+    /// Results to return, 1-100 (default: config `limit`, else 10)
+    #[arg(long)]
+    limit: Option<usize>,
     #[arg(long, value_enum, default_value_t = SortBy::Date)]
     sort: SortBy,
+    /// Search arXiv, or OpenAlex/Crossref for journals and proceedings
+    #[arg(long, value_enum, default_value_t = Source::Arxiv)]
+    source: Source,
+    #[command(flatten)]
+    page: PageArgs,
+    /// Print results as citation entries instead of a summary
+    #[arg(long, value_enum)]
+    format: Option<CiteFormat>,
+    /// Print results as markdown, CSV, JSON, JSON lines, YAML or a table (includes citation counts)
+    #[arg(long, value_enum, conflicts_with = "format")]
+    output: Option<OutputFormat>,
+}
+
+// Search terms shared by `search` and `alerts add`; see `query::build`.
+#[derive(Debug, Clone, Args)]
+struct QueryArgs {
+    /// Free text matched against all fields
+    query: Option<String>,
+    /// Title contains (repeatable; multi-word values are phrases)
     #[arg(long)]
-    category: Option<String>,
+    title: Vec<String>,
+    /// Abstract contains (repeatable)
+    #[arg(long = "abstract")]
+    abstract_text: Vec<String>,
+    /// Author name (repeatable)
+    #[arg(long)]
+    author: Vec<String>,
+    /// Any field contains (repeatable)
+    #[arg(long)]
+    all: Vec<String>,
+    /// arXiv category such as cs.LG (repeatable; any of them matches)
+    #[arg(long)]
+    category: Vec<String>,
+    /// Drop papers matching this term; prefix a field like `au:Smith` to narrow it (repeatable)
+    #[arg(long)]
+    exclude: Vec<String>,
+    /// Match any of the title/abstract/author/all terms instead of all of them
+    #[arg(long)]
+    or: bool,
+    /// Submitted on or after this date (YYYY-MM-DD)
+    #[arg(long)]
+    from: Option<String>,
+    /// Submitted on or before this date (YYYY-MM-DD)
+    #[arg(long)]
+    to: Option<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct PageArgs {
+    /// Skip this many results (0-based offset into arXiv's ordering)
+    #[arg(long, default_value_t = 0)]
+    start: usize,
+    /// Page through results 100 at a time, 3 seconds apart, until --max (ignores --limit)
+    #[arg(long)]
+    fetch_all: bool,
+    /// Most results to collect with --fetch-all
+    #[arg(long, default_value_t = 500, requires = "fetch_all")]
+    max: usize,
 }
 
 #[derive(Debug, Args)]
@@ -59,11 +168,197 @@ struct GetArgs {
     paper_id: String,
 }
 
+#[derive(Debug, Args)]
+struct DoiArgs {
+    /// DOI, with or without a `doi:` or https://doi.org/ prefix
+    doi: String,
+}
+
 #[derive(Debug, Args)]
 struct AuthorArgs {
     name: String,
+    /// Results to return, 1-100 (default: config `limit`, else 10)
+    #[arg(long)]
+    limit: Option<usize>,
+    #[command(flatten)]
+    page: PageArgs,
+    /// Print results as citation entries instead of a summary
+    #[arg(long, value_enum)]
+    format: Option<CiteFormat>,
+    /// Print results as markdown, CSV, JSON, JSON lines, YAML or a table (includes citation counts)
+    #[arg(long, value_enum, conflicts_with = "format")]
+    output: Option<OutputFormat>,
+}
+
+#[derive(Debug, Args)]
+struct GraphArgs {
+    /// arXiv id, Semantic Scholar id, or a prefixed id such as DOI:10.1000/xyz
+    paper_id: String,
     #[arg(long, default_value_t = 10)]
     limit: usize,
+    /// Skip this many results
+    #[arg(long, default_value_t = 0)]
+    start: usize,
+    /// Order of the returned page (`citations` sorts by citation count)
+    #[arg(long, value_enum, default_value_t = SortBy::Date)]
+    sort: SortBy,
+    /// Print results as markdown, CSV, JSON, JSON lines, YAML or a table
+    #[arg(long, value_enum)]
+    output: Option<OutputFormat>,
+}
+
+#[derive(Debug, Args)]
+struct RelatedArgs {
+    /// arXiv id, Semantic Scholar id, or a prefixed id such as DOI:10.1000/xyz
+    paper_id: String,
+    #[arg(long, default_value_t = 10)]
+    limit: usize,
+    /// Print results as markdown, CSV, JSON, JSON lines, YAML or a table
+    #[arg(long, value_enum)]
+    output: Option<OutputFormat>,
+}
+
+#[derive(Debug, Args)]
+struct CiteArgs {
+    paper_id: String,
+    #[arg(long, value_enum, default_value_t = CiteFormat::Bibtex)]
+    format: CiteFormat,
+}
+
+#[derive(Debug, Args)]
+struct LibArgs {
+    #[command(subcommand)]
+    command: LibCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum LibCommand {
+    /// Save a paper (or refresh it and add tags if already saved)
+    Add(LibAddArgs),
+    /// Delete a saved paper with its notes, or only the given tags
+    Remove(LibRemoveArgs),
+    /// List saved papers, newest first
+    List(LibListArgs),
+    /// Add a note to a saved paper
+    Note(LibNoteArgs),
+    /// Search titles, abstracts, and notes of saved papers
+    Search(LibSearchArgs),
+}
+
+#[derive(Debug, Args)]
+struct LibAddArgs {
+    paper_id: String,
+    /// Tag to attach (repeatable or comma-separated)
+    #[arg(long = "tag", value_delimiter = ',')]
+    tags: Vec<String>,
+    /// Note to attach
+    #[arg(long)]
+    note: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct LibRemoveArgs {
+    paper_id: String,
+    /// Only remove these tags and keep the paper
+    #[arg(long = "tag", value_delimiter = ',')]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+struct LibListArgs {
+    /// Only papers with this tag
+    #[arg(long)]
+    tag: Option<String>,
+    /// Print the list as JSON, JSON lines, YAML, CSV or a table
+    #[arg(long, value_enum)]
+    output: Option<Format>,
+}
+
+#[derive(Debug, Args)]
+struct LibNoteArgs {
+    paper_id: String,
+    text: Option<String>,
+    /// Delete the paper's existing notes first
+    #[arg(long)]
+    clear: bool,
+}
+
+#[derive(Debug, Args)]
+struct LibSearchArgs {
+    /// Terms that must all appear (case-insensitive)
+    #[arg(required = true)]
+    terms: Vec<String>,
+    /// Print the list as JSON, JSON lines, YAML, CSV or a table
+    #[arg(long, value_enum)]
+    output: Option<Format>,
+}
+
+#[derive(Debug, Args)]
+struct ConfigArgs {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    /// Set a key: category, limit, output, s2.api_key (an empty value clears it)
+    Set(ConfigSetArgs),
+    Show,
+    Path,
+}
+
+#[derive(Debug, Args)]
+struct ConfigSetArgs {
+    key: String,
+    value: String,
+}
+
+#[derive(Debug, Args)]
+struct AlertsArgs {
+    #[command(subcommand)]
+    command: AlertsCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum AlertsCommand {
+    /// Save a search; takes the same terms and filters as `search`
+    Add(Box<AlertAddArgs>),
+    /// List saved alerts
+    List(AlertListArgs),
+    /// Delete an alert and its seen-paper history
+    Remove(AlertIdArgs),
+    /// Re-run alerts and report papers not seen before
+    Run(AlertRunArgs),
+}
+
+#[derive(Debug, Args)]
+struct AlertAddArgs {
+    #[command(flatten)]
+    filter: QueryArgs,
+    /// Label shown in reports (default: the composed query)
+    #[arg(long)]
+    name: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct AlertListArgs {
+    /// Print the list as JSON, JSON lines, YAML, CSV or a table
+    #[arg(long, value_enum)]
+    output: Option<Format>,
+}
+
+#[derive(Debug, Args)]
+struct AlertIdArgs {
+    id: i64,
+}
+
+#[derive(Debug, Args)]
+struct AlertRunArgs {
+    /// Only run these alerts (default: all)
+    ids: Vec<i64>,
+    /// Newest submissions to check per alert
+    #[arg(long, default_value_t = 50)]
+    limit: usize,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -72,57 +367,132 @@ enum AppError {
     InvalidArgument(String),
     #[error("HTTP request failed")]
     RequestFailed,
+    #[error("Network error: {0}")]
+    Network(String),
+    #[error("Rate limited by {0}; try again later")]
+    RateLimited(String),
     #[error("No paper found")]
     NotFound,
     #[error("Response parse failed")]
     ParseFailed,
+    #[error("Paper '{0}' is not in the library")]
+    NotInLibrary(String),
+    #[error("Alert {0} not found")]
+    AlertNotFound(i64),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+    #[error("Unknown config key: {0}")]
+    InvalidConfigKey(String),
+    #[error("Data directory not found")]
+    DataDirMissing,
+    #[error("Database operation failed")]
+    Database,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
-            Self::InvalidArgument(_) => "INVALID_ARGUMENT",
+            Self::InvalidArgument(_) | Self::InvalidConfigKey(_) => "INVALID_ARGUMENT",
             Self::RequestFailed => "REQUEST_FAILED",
-            Self::NotFound => "NOT_FOUND",
+            Self::Network(_) => "NETWORK_ERROR",
+            Self::RateLimited(_) => "RATE_LIMITED",
+            Self::NotFound | Self::NotInLibrary(_) | Self::AlertNotFound(_) => "NOT_FOUND",
             Self::ParseFailed => "PARSE_FAILED",
+            Self::Config(err) => err.code(),
+            Self::Cache(err) => err.code(),
+            Self::DataDirMissing | Self::Database => "DATABASE_ERROR",
         }
     }
 }
 
-#[derive(Debug, Serialize)]
-struct OkList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct AppConfig {
+    /// arXiv category added to `search` when no `--category` is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_category: Option<String>,
+    /// `--limit` for `search` and `author` when the flag is omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_limit: Option<usize>,
+    /// `--output` for plain-text `search` and `author` runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_output: Option<OutputFormat>,
+    /// Semantic Scholar API key, sent as `x-api-key` for higher rate limits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    s2_api_key: Option<String>,
+    /// Replaces `https://export.arxiv.org/api`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    arxiv_base_url: Option<String>,
+    /// Replaces `https://api.semanticscholar.org`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    s2_base_url: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct OkItem<T> {
-    ok: bool,
-    item: T,
-}
+impl AppConfig {
+    /// The arXiv query endpoint.
+    fn arxiv_query_url(&self) -> String {
+        format!(
+            "{}/query",
+            base_url(self.arxiv_base_url.as_deref(), ARXIV_API)
+        )
+    }
+
+    fn s2(&self) -> s2::Api<'_> {
+        s2::Api::new(self.s2_api_key.as_deref(), self.s2_base_url.as_deref())
+    }
 
-#[derive(Debug, Serialize)]
-struct ErrorJson {
-    ok: bool,
-    error: String,
-    code: String,
+    /// `--limit`, else the configured default, else 10. arXiv pages hold 1-100 results.
+    fn limit(&self, flag: Option<usize>) -> Result<usize, AppError> {
+        let limit = flag.or(self.default_limit).unwrap_or(10);
+        if limit == 0 || limit > 100 {
+            return Err(AppError::InvalidArgument(
+                "--limit must be between 1 and 100".to_string(),
+            ));
+        }
+        Ok(limit)
+    }
+
+    /// `--output`, else the configured format. The default only applies to plain-text
+    /// runs so `--json`, `--quiet`, and `--format` keep their usual output.
+    fn output(
+        &self,
+        flag: Option<OutputFormat>,
+        cite: Option<CiteFormat>,
+        out: &GlobalArgs,
+    ) -> Option<OutputFormat> {
+        if flag.is_some() || cite.is_some() || out.json || out.quiet {
+            flag
+        } else {
+            self.default_output
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 struct PaperItem {
     id: String,
     title: String,
     authors: Vec<String>,
     year: i32,
+    /// First submission timestamp (RFC 3339)
+    published: String,
     abstract_text: String,
     url: String,
     citations: i64,
+    /// Primary category first
     categories: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    doi: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    journal_ref: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ArxivFeed {
+    /// `opensearch:totalResults`: hits for the whole query, not just this page
+    #[serde(rename = "totalResults")]
+    total_results: Option<usize>,
     #[serde(rename = "entry", default)]
     entries: Vec<ArxivEntry>,
 }
@@ -139,6 +509,10 @@ struct ArxivEntry {
     links: Vec<ArxivLink>,
     #[serde(rename = "category", default)]
     categories: Vec<ArxivCategory>,
+    #[serde(rename = "primary_category")]
+    primary_category: Option<ArxivCategory>,
+    doi: Option<String>,
+    journal_ref: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -160,34 +534,40 @@ struct ArxivCategory {
     term: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct S2SearchResponse {
-    #[serde(default)]
-    data: Vec<S2Paper>,
-}
-
-#[derive(Debug, Deserialize)]
-struct S2Paper {
-    #[serde(default)]
-    #[serde(rename = "citationCount")]
-    citation_count: Option<i64>,
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<PaperItem>("search")
+        .item::<PaperItem>("get")
+        .list::<PaperItem>("author")
+        .item::<PaperItem>("doi")
+        .item::<cite::CiteItem>("cite")
+        .list::<PaperItem>("citations")
+        .list::<PaperItem>("references")
+        .list::<PaperItem>("related")
+        .item::<db::LibItem>("lib add")
+        .message("lib remove")
+        .list::<db::LibItem>("lib list")
+        .item::<db::LibItem>("lib note")
+        .list::<db::LibItem>("lib search")
+        .custom::<alerts::AlertAdded>("alerts add")
+        .list::<db::Alert>("alerts list")
+        .message("alerts remove")
+        .custom::<alerts::RunReport>("alerts run")
+        .message("config set")
+        .item::<ConfigView>("config show")
+        .item::<ConfigPath>("config path")
 }
 
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     let result = dispatch(&cli);
     if let Err(err) = result {
-        if cli.global.json {
-            print_json(&ErrorJson {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        // `--output` is per command, so look for `--output json|jsonl` in argv
+        report(&err, dee_core::cli::wants_json());
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -196,29 +576,167 @@ fn dispatch(cli: &Cli) -> Result<(), AppError> {
         Commands::Search(args) => cmd_search(args, &cli.global),
         Commands::Get(args) => cmd_get(args, &cli.global),
         Commands::Author(args) => cmd_author(args, &cli.global),
+        Commands::Doi(args) => cmd_doi(args, &cli.global),
+        Commands::Cite(args) => cmd_cite(args, &cli.global),
+        Commands::Citations(args) => cmd_graph(args, s2::Direction::Citations, &cli.global),
+        Commands::References(args) => cmd_graph(args, s2::Direction::References, &cli.global),
+        Commands::Related(args) => cmd_related(args, &cli.global),
+        Commands::Lib(args) => library::cmd_lib(args, &cli.global),
+        Commands::Alerts(args) => alerts::cmd_alerts(args, &cli.global),
+        Commands::Config(args) => cmd_config(args, &cli.global),
+        Commands::Cache { command } => Ok(dee_core::cache::run(
+            &http::cache(),
+            *command,
+            cli.global.json,
+        )?),
     }
 }
 
 fn cmd_search(args: &SearchArgs, out: &GlobalArgs) -> Result<(), AppError> {
-    if args.limit == 0 || args.limit > 100 {
+    let cfg = config_file().load()?;
+    let limit = cfg.limit(args.limit)?;
+    let output = cfg.output(args.output, args.format, out);
+
+    if args.source != Source::Arxiv {
+        return search_other(args, limit, output, out);
+    }
+
+    let mut filter = args.filter.clone();
+    if filter.category.is_empty() {
+        filter.category.extend(cfg.default_category);
+    }
+    let query = query::build(&filter)?;
+
+    let mut items = fetch_arxiv(&query, limit, &args.page, Some("submittedDate"), out)?;
+
+    if matches!(args.sort, SortBy::Citations) {
+        enrich_citations(&mut items, out)?;
+        items.sort_by_key(|item| std::cmp::Reverse(item.citations));
+    } else if output.is_some() {
+        enrich_citations(&mut items, out)?;
+    }
+
+    if let Some(format) = args.format {
+        print_citations(&items, format, out);
+    } else if let Some(format) = output {
+        print_export(&items, format, out);
+    } else {
+        print_papers(items, out);
+    }
+
+    Ok(())
+}
+
+/// `search --source openalex|crossref`: one page, sorted and counted by the source itself.
+fn search_other(
+    args: &SearchArgs,
+    limit: usize,
+    output: Option<OutputFormat>,
+    out: &GlobalArgs,
+) -> Result<(), AppError> {
+    if args.page.fetch_all {
         return Err(AppError::InvalidArgument(
-            "--limit must be between 1 and 100".to_string(),
+            "--fetch-all only works with --source arxiv".to_string(),
         ));
     }
+    if args.format.is_some() {
+        return Err(AppError::InvalidArgument(
+            "--format only works with --source arxiv".to_string(),
+        ));
+    }
+
+    let items = sources::search(
+        args.source,
+        &args.filter,
+        limit,
+        args.page.start,
+        &args.sort,
+        out,
+    )?;
 
-    let mut query = format!("all:{}", args.query.trim());
-    if let Some(cat) = &args.category {
-        query.push_str("+AND+cat:");
-        query.push_str(cat.trim());
+    match output {
+        Some(format) => print_export(&items, format, out),
+        None => print_papers(items, out),
     }
+    Ok(())
+}
 
-    let mut items = fetch_arxiv(&query, args.limit, Some("submittedDate"), out.verbose)?;
+fn cmd_graph(args: &GraphArgs, direction: s2::Direction, out: &GlobalArgs) -> Result<(), AppError> {
+    if args.limit == 0 || args.limit > s2::MAX_GRAPH_LIMIT {
+        return Err(AppError::InvalidArgument(format!(
+            "--limit must be between 1 and {}",
+            s2::MAX_GRAPH_LIMIT
+        )));
+    }
 
-    if matches!(args.sort, SortBy::Citations) {
-        enrich_citations(&mut items, out.verbose)?;
-        items.sort_by(|a, b| b.citations.cmp(&a.citations));
+    let cfg = config_file().load()?;
+    let mut items = s2::fetch_graph(
+        &args.paper_id,
+        direction,
+        args.limit,
+        args.start,
+        &cfg.s2(),
+        out,
+    )?;
+    match args.sort {
+        SortBy::Citations => items.sort_by_key(|item| std::cmp::Reverse(item.citations)),
+        SortBy::Date => items
+            .sort_by(|a, b| (b.year, b.published.as_str()).cmp(&(a.year, a.published.as_str()))),
+    }
+
+    match args.output {
+        Some(format) => print_export(&items, format, out),
+        None => print_papers(items, out),
+    }
+    Ok(())
+}
+
+fn cmd_related(args: &RelatedArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    if args.limit == 0 || args.limit > s2::MAX_RELATED_LIMIT {
+        return Err(AppError::InvalidArgument(format!(
+            "--limit must be between 1 and {}",
+            s2::MAX_RELATED_LIMIT
+        )));
     }
 
+    let cfg = config_file().load()?;
+    let items = s2::fetch_related(&args.paper_id, args.limit, &cfg.s2(), out)?;
+
+    match args.output {
+        Some(format) => print_export(&items, format, out),
+        None => print_papers(items, out),
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct ExportItem {
+    format: OutputFormat,
+    data: String,
+    count: usize,
+}
+
+fn print_export(items: &[PaperItem], format: OutputFormat, out: &GlobalArgs) {
+    if let Some(format) = format.shared() {
+        print_list(items, format);
+        return;
+    }
+    let data = output::render(items, format);
+    if out.json {
+        print_json(&OkItem {
+            ok: true,
+            item: ExportItem {
+                format,
+                data,
+                count: items.len(),
+            },
+        });
+    } else {
+        print!("{data}");
+    }
+}
+
+fn print_papers(items: Vec<PaperItem>, out: &GlobalArgs) {
     if out.json {
         print_json(&OkList {
             ok: true,
@@ -234,20 +752,12 @@ fn cmd_search(args: &SearchArgs, out: &GlobalArgs) -> Result<(), AppError> {
             println!("  citations={} year={}", item.citations, item.year);
         }
     }
-
-    Ok(())
 }
 
 fn cmd_get(args: &GetArgs, out: &GlobalArgs) -> Result<(), AppError> {
-    let query = format!("id_list={}", urlencoding::encode(&args.paper_id));
-    let url = format!("{}?{}", ARXIV_API, query);
-    let feed = fetch_feed(&url, out.verbose)?;
-    let entry = feed.entries.into_iter().next().ok_or(AppError::NotFound)?;
-    let mut item = map_entry(entry);
+    let mut item = fetch_paper(&args.paper_id, out)?;
 
-    let mut one = vec![item.clone()];
-    enrich_citations(&mut one, out.verbose)?;
-    item.citations = one[0].citations;
+    enrich_citations(std::slice::from_mut(&mut item), out)?;
 
     if out.json {
         print_json(&OkItem { ok: true, item });
@@ -264,17 +774,44 @@ fn cmd_get(args: &GetArgs, out: &GlobalArgs) -> Result<(), AppError> {
     Ok(())
 }
 
-fn cmd_author(args: &AuthorArgs, out: &GlobalArgs) -> Result<(), AppError> {
-    if args.limit == 0 || args.limit > 100 {
-        return Err(AppError::InvalidArgument(
-            "--limit must be between 1 and 100".to_string(),
-        ));
+fn cmd_doi(args: &DoiArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    let item = sources::fetch_doi(&args.doi, out)?;
+
+    if out.json {
+        print_json(&OkItem { ok: true, item });
+    } else if out.quiet {
+        println!("{}", item.id);
+    } else {
+        println!("{}", item.title);
+        println!("doi: {}", item.id);
+        println!("year: {}", item.year);
+        if let Some(venue) = &item.journal_ref {
+            println!("venue: {venue}");
+        }
+        println!("citations: {}", item.citations);
+        println!("url: {}", item.url);
     }
 
+    Ok(())
+}
+
+fn cmd_author(args: &AuthorArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    let cfg = config_file().load()?;
+    let limit = cfg.limit(args.limit)?;
+    let output = cfg.output(args.output, args.format, out);
+
     let query = format!("au:{}", args.name.trim());
-    let items = fetch_arxiv(&query, args.limit, Some("submittedDate"), out.verbose)?;
+    let mut items = fetch_arxiv(&query, limit, &args.page, Some("submittedDate"), out)?;
 
-    if out.json {
+    if output.is_some() {
+        enrich_citations(&mut items, out)?;
+    }
+
+    if let Some(format) = args.format {
+        print_citations(&items, format, out);
+    } else if let Some(format) = output {
+        print_export(&items, format, out);
+    } else if out.json {
         print_json(&OkList {
             ok: true,
             count: items.len(),
@@ -291,17 +828,107 @@ fn cmd_author(args: &AuthorArgs, out: &GlobalArgs) -> Result<(), AppError> {
     Ok(())
 }
 
+fn cmd_cite(args: &CiteArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    let item = fetch_paper(&args.paper_id, out)?;
+    let entry = cite::render(std::slice::from_ref(&item), args.format)
+        .pop()
+        .ok_or(AppError::NotFound)?;
+
+    if out.json {
+        print_json(&OkItem {
+            ok: true,
+            item: entry,
+        });
+    } else {
+        println!("{}", entry.text);
+    }
+
+    Ok(())
+}
+
+fn print_citations(items: &[PaperItem], format: CiteFormat, out: &GlobalArgs) {
+    let entries = cite::render(items, format);
+    if out.json {
+        print_json(&OkList {
+            ok: true,
+            count: entries.len(),
+            items: entries,
+        });
+    } else {
+        let texts: Vec<&str> = entries.iter().map(|e| e.text.as_str()).collect();
+        println!("{}", texts.join("\n\n"));
+    }
+}
+
 fn fetch_arxiv(
     search_query: &str,
     limit: usize,
+    page: &PageArgs,
     sort_by: Option<&str>,
-    verbose: bool,
+    out: &GlobalArgs,
 ) -> Result<Vec<PaperItem>, AppError> {
+    if page.start > MAX_OFFSET {
+        return Err(AppError::InvalidArgument(format!(
+            "--start must be at most {MAX_OFFSET}"
+        )));
+    }
+    let api = config_file().load()?.arxiv_query_url();
+    if !page.fetch_all {
+        let url = page_url(&api, search_query, page.start, limit, sort_by);
+        let feed = fetch_feed(&url, out)?;
+        return Ok(feed.entries.into_iter().map(map_entry).collect());
+    }
+    if page.max == 0 || page.max > MAX_FETCH_ALL {
+        return Err(AppError::InvalidArgument(format!(
+            "--max must be between 1 and {MAX_FETCH_ALL}"
+        )));
+    }
+
+    let mut items = Vec::new();
+    // New submissions can shift results between pages; keep the first copy of each paper
+    let mut seen = HashSet::new();
+    let mut offset = page.start;
+    loop {
+        let want = PAGE_SIZE.min(page.max - items.len());
+        // `http::send` keeps consecutive pages 3 seconds apart
+        let feed = fetch_feed(&page_url(&api, search_query, offset, want, sort_by), out)?;
+        let got = feed.entries.len();
+        if let Some(total) = feed.total_results {
+            tracing::debug!("{got} results at offset {offset} of {total}");
+        }
+        for entry in feed.entries {
+            let item = map_entry(entry);
+            if seen.insert(item.id.clone()) {
+                items.push(item);
+            }
+        }
+        offset += got;
+
+        let exhausted = match feed.total_results {
+            Some(total) => offset >= total,
+            None => got < want,
+        };
+        if got == 0 || exhausted || items.len() >= page.max || offset >= MAX_OFFSET {
+            break;
+        }
+    }
+    items.truncate(page.max);
+    Ok(items)
+}
+
+fn page_url(
+    api: &str,
+    search_query: &str,
+    start: usize,
+    max_results: usize,
+    sort_by: Option<&str>,
+) -> String {
     let mut url = format!(
-        "{}?search_query={}&start=0&max_results={}",
-        ARXIV_API,
+        "{}?search_query={}&start={}&max_results={}",
+        api,
         urlencoding::encode(search_query),
-        limit
+        start,
+        max_results
     );
 
     if let Some(sort) = sort_by {
@@ -309,35 +936,46 @@ fn fetch_arxiv(
         url.push_str(sort);
         url.push_str("&sortOrder=descending");
     }
-
-    let feed = fetch_feed(&url, verbose)?;
-    Ok(feed.entries.into_iter().map(map_entry).collect())
+    url
 }
 
-fn fetch_feed(url: &str, verbose: bool) -> Result<ArxivFeed, AppError> {
-    if verbose {
-        eprintln!("debug: GET {url}");
-    }
-
-    let client = Client::builder()
-        .user_agent("dee-arxiv/0.1.0 (https://dee.ink)")
-        .build()
-        .map_err(|_| AppError::RequestFailed)?;
-
-    let text = client
-        .get(url)
-        .send()
-        .map_err(|_| AppError::RequestFailed)?
-        .error_for_status()
-        .map_err(|_| AppError::RequestFailed)?
-        .text()
-        .map_err(|_| AppError::ParseFailed)?;
+/// Look up one paper by arXiv id (with or without a version suffix).
+fn fetch_paper(paper_id: &str, out: &GlobalArgs) -> Result<PaperItem, AppError> {
+    let url = format!(
+        "{}?id_list={}",
+        config_file().load()?.arxiv_query_url(),
+        urlencoding::encode(paper_id.trim())
+    );
+    let feed = fetch_feed(&url, out)?;
+    Ok(map_entry(first_paper(feed)?))
+}
 
+fn fetch_feed(url: &str, out: &GlobalArgs) -> Result<ArxivFeed, AppError> {
+    let text = http::send(http::client(out)?.get(url), out)?;
     from_str(&text).map_err(|_| AppError::ParseFailed)
 }
 
+/// arXiv answers a malformed `id_list` with a single entry whose id points at
+/// `/api/errors`; treat that as a bad argument rather than a paper.
+fn first_paper(feed: ArxivFeed) -> Result<ArxivEntry, AppError> {
+    let entry = feed.entries.into_iter().next().ok_or(AppError::NotFound)?;
+    if entry.id.contains("/api/errors") {
+        return Err(AppError::InvalidArgument(format!(
+            "arXiv rejected the id: {}",
+            normalize_whitespace(&entry.summary)
+        )));
+    }
+    Ok(entry)
+}
+
 fn map_entry(entry: ArxivEntry) -> PaperItem {
-    let id = entry.id.rsplit('/').next().unwrap_or(&entry.id).to_string();
+    // Old-style ids contain a slash (`hep-th/9901001v1`), so keep everything after `/abs/`
+    let id = entry
+        .id
+        .split_once("/abs/")
+        .map(|(_, id)| id)
+        .unwrap_or_else(|| entry.id.rsplit('/').next().unwrap_or(&entry.id))
+        .to_string();
     let year = entry
         .published
         .get(0..4)
@@ -350,107 +988,204 @@ fn map_entry(entry: ArxivEntry) -> PaperItem {
         .and_then(|x| x.href.clone())
         .unwrap_or_default();
 
+    let mut categories: Vec<String> = entry
+        .categories
+        .into_iter()
+        .filter_map(|c| c.term)
+        .collect();
+    if let Some(primary) = entry.primary_category.and_then(|c| c.term) {
+        categories.retain(|c| *c != primary);
+        categories.insert(0, primary);
+    }
+
     PaperItem {
         id,
         title: normalize_whitespace(&entry.title),
         authors: entry.authors.into_iter().map(|a| a.name).collect(),
         year,
+        published: entry.published,
         abstract_text: normalize_whitespace(&entry.summary),
         url,
         citations: 0,
-        categories: entry
-            .categories
-            .into_iter()
-            .filter_map(|c| c.term)
-            .collect(),
+        categories,
+        doi: entry.doi.map(|d| d.trim().to_string()),
+        journal_ref: entry.journal_ref.map(|j| normalize_whitespace(&j)),
     }
 }
 
-fn enrich_citations(items: &mut [PaperItem], verbose: bool) -> Result<(), AppError> {
-    if items.is_empty() {
-        return Ok(());
-    }
-
-    let client = Client::builder()
-        .user_agent("dee-arxiv/0.1.0 (https://dee.ink)")
-        .build()
-        .map_err(|_| AppError::RequestFailed)?;
-
-    for item in items {
-        let url = format!(
-            "{}?query={}&limit=1&fields=citationCount",
-            S2_API,
-            urlencoding::encode(&item.title)
-        );
-
-        if verbose {
-            eprintln!("debug: GET {url}");
-        }
-
-        let res = client.get(&url).send();
-        let Ok(resp) = res else {
-            continue;
-        };
-        if !resp.status().is_success() {
-            continue;
-        }
-        let Ok(parsed) = resp.json::<S2SearchResponse>() else {
-            continue;
-        };
-        let citations = parsed
-            .data
-            .first()
-            .and_then(|x| x.citation_count)
-            .unwrap_or(0);
-        item.citations = citations;
-    }
+/// Fill `citations` from Semantic Scholar, using the configured API key when present.
+fn enrich_citations(items: &mut [PaperItem], out: &GlobalArgs) -> Result<(), AppError> {
+    let cfg = config_file().load()?;
+    s2::enrich_citations(items, &cfg.s2(), out)
+}
 
-    Ok(())
+/// `DEE_ARXIV_<KEY>` overrides any key in the file, e.g. `DEE_ARXIV_S2_API_KEY`.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-arxiv").env_overrides(&[
+        "default_category",
+        "default_limit",
+        "default_output",
+        "s2_api_key",
+        "arxiv_base_url",
+        "s2_base_url",
+    ])
 }
 
 fn normalize_whitespace(input: &str) -> String {
     input.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(text) => println!("{text}"),
-        Err(_) => {
-            println!(
-                "{{\"ok\":false,\"error\":\"serialization failed\",\"code\":\"INTERNAL_ERROR\"}}"
-            );
-        }
-    }
+/// `config show --json`: the key itself is never printed
+#[derive(Debug, Serialize, JsonSchema)]
+struct ConfigView {
+    category: Option<String>,
+    limit: Option<usize>,
+    output: Option<&'static str>,
+    s2_api_key_set: bool,
 }
 
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
+#[derive(Debug, Serialize, JsonSchema)]
+struct ConfigPath {
+    path: String,
 }
 
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
+fn cmd_config(args: &ConfigArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    match &args.command {
+        ConfigCommand::Set(input) => {
+            let mut cfg = config_file().load_file()?;
+            let value = input.value.trim();
+            let set = (!value.is_empty()).then(|| value.to_string());
+            match input.key.as_str() {
+                "category" => {
+                    if value.contains(char::is_whitespace) {
+                        return Err(AppError::InvalidArgument(
+                            "category must be a single arXiv category such as cs.LG".to_string(),
+                        ));
+                    }
+                    cfg.default_category = set;
+                }
+                "limit" => {
+                    cfg.default_limit = set
+                        .map(|v| match v.parse::<usize>() {
+                            Ok(n) if (1..=100).contains(&n) => Ok(n),
+                            _ => Err(AppError::InvalidArgument(
+                                "limit must be a number between 1 and 100".to_string(),
+                            )),
+                        })
+                        .transpose()?;
+                }
+                "output" => {
+                    cfg.default_output = set
+                        .map(|v| {
+                            OutputFormat::from_str(&v, true).map_err(|_| {
+                                AppError::InvalidArgument(
+                                    "output must be md, csv, json, jsonl, yaml or table"
+                                        .to_string(),
+                                )
+                            })
+                        })
+                        .transpose()?;
+                }
+                "s2.api_key" | "s2_api_key" => cfg.s2_api_key = set,
+                "arxiv.base_url" | "arxiv_base_url" => cfg.arxiv_base_url = set,
+                "s2.base_url" | "s2_base_url" => cfg.s2_base_url = set,
+                other => return Err(AppError::InvalidConfigKey(other.to_string())),
+            }
+            config_file().save(&cfg)?;
 
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
+            if out.json {
+                print_json(&OkMessage {
+                    ok: true,
+                    message: "Config updated".to_string(),
+                });
+            } else if !out.quiet {
+                println!("Config updated");
+            }
+            Ok(())
+        }
+        ConfigCommand::Show => {
+            let cfg = config_file().load()?;
+            let output = cfg.default_output.map(OutputFormat::name);
+            if out.json {
+                print_json(&OkItem {
+                    ok: true,
+                    item: ConfigView {
+                        category: cfg.default_category.clone(),
+                        limit: cfg.default_limit,
+                        output,
+                        s2_api_key_set: cfg.s2_api_key.is_some(),
+                    },
+                });
+            } else {
+                let key_set = cfg
+                    .s2_api_key
+                    .as_deref()
+                    .map(|_| "set")
+                    .unwrap_or("missing");
+                println!(
+                    "category: {}",
+                    cfg.default_category.as_deref().unwrap_or("")
+                );
+                match cfg.default_limit {
+                    Some(limit) => println!("limit: {limit}"),
+                    None => println!("limit: "),
+                }
+                println!("output: {}", output.unwrap_or(""));
+                println!("s2.api_key: {key_set}");
+            }
+            Ok(())
         }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
+        ConfigCommand::Path => {
+            let path = config_file().path();
+            if out.json {
+                print_json(&OkItem {
+                    ok: true,
+                    item: ConfigPath {
+                        path: path.display().to_string(),
+                    },
                 });
-                println!("{payload}");
             } else {
-                let _ = err.print();
+                println!("{}", path.display());
             }
-            std::process::exit(2);
+            Ok(())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title type="html">ArXiv Query</title>
+  <opensearch:totalResults xmlns:opensearch="http://a9.com/-/spec/opensearch/1.1/">1</opensearch:totalResults>
+  <entry>
+    <id>http://arxiv.org/abs/hep-th/9901001v2</id>
+    <published>1999-01-04T12:00:00Z</published>
+    <title>A Sample
+      Paper</title>
+    <summary>  Some abstract.  </summary>
+    <author><name>Jane Q. Doe</name></author>
+    <arxiv:doi xmlns:arxiv="http://arxiv.org/schemas/atom">10.1000/sample</arxiv:doi>
+    <link href="http://arxiv.org/abs/hep-th/9901001v2" rel="alternate" type="text/html"/>
+    <arxiv:journal_ref xmlns:arxiv="http://arxiv.org/schemas/atom">Phys. Rev. D 1 (1999)</arxiv:journal_ref>
+    <arxiv:primary_category xmlns:arxiv="http://arxiv.org/schemas/atom" term="hep-th" scheme="http://arxiv.org/schemas/atom"/>
+    <category term="gr-qc" scheme="http://arxiv.org/schemas/atom"/>
+    <category term="hep-th" scheme="http://arxiv.org/schemas/atom"/>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn maps_atom_entry_metadata() {
+        let feed: ArxivFeed = from_str(FEED).expect("feed parses");
+        assert_eq!(feed.total_results, Some(1));
+        let item = map_entry(feed.entries.into_iter().next().expect("one entry"));
+        assert_eq!(item.id, "hep-th/9901001v2");
+        assert_eq!(item.title, "A Sample Paper");
+        assert_eq!(item.year, 1999);
+        assert_eq!(item.categories, vec!["hep-th", "gr-qc"]);
+        assert_eq!(item.doi.as_deref(), Some("10.1000/sample"));
+        assert_eq!(item.journal_ref.as_deref(), Some("Phys. Rev. D 1 (1999)"));
+    }
+}
@@ -0,0 +1,170 @@
+//! Markdown and CSV renderings of paper lists for pasting into notes and spreadsheets.
+//! The other `--output` formats come from `dee_core::format`.
+
+use clap::ValueEnum;
+use dee_core::Format;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::PaperItem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Md,
+    /// Curated columns (id, title, authors, year, url, citations)
+    Csv,
+    Json,
+    Jsonl,
+    Yaml,
+    Table,
+}
+
+impl OutputFormat {
+    /// The shared `dee_core` format, for everything but `md` and `csv`.
+    pub fn shared(self) -> Option<Format> {
+        match self {
+            Self::Md | Self::Csv => None,
+            Self::Json => Some(Format::Json),
+            Self::Jsonl => Some(Format::Jsonl),
+            Self::Yaml => Some(Format::Yaml),
+            Self::Table => Some(Format::Table),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Md => "md",
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Jsonl => "jsonl",
+            Self::Yaml => "yaml",
+            Self::Table => "table",
+        }
+    }
+}
+
+/// Authors shown in markdown before collapsing to "et al."
+const MD_AUTHORS: usize = 3;
+
+/// `md` or `csv` text; the shared formats are printed by `dee_core::print_list`.
+pub fn render(items: &[PaperItem], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Csv => csv(items),
+        _ => markdown(items),
+    }
+}
+
+fn md_escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '[' | ']' | '*' | '_' | '`' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn markdown(items: &[PaperItem]) -> String {
+    let mut out = String::new();
+    for item in items {
+        let mut authors = item
+            .authors
+            .iter()
+            .take(MD_AUTHORS)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+        if item.authors.len() > MD_AUTHORS {
+            authors.push_str(" et al.");
+        }
+        let year = if item.year > 0 {
+            format!(" ({})", item.year)
+        } else {
+            String::new()
+        };
+        let noun = if item.citations == 1 {
+            "citation"
+        } else {
+            "citations"
+        };
+        out.push_str(&format!(
+            "- [{}]({}) — {}{year} · {} {noun}\n",
+            md_escape(&item.title),
+            item.url,
+            md_escape(&authors),
+            item.citations
+        ));
+    }
+    out
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv(items: &[PaperItem]) -> String {
+    let mut out = String::from("id,title,authors,year,url,citations\n");
+    for item in items {
+        let fields = [
+            csv_escape(&item.id),
+            csv_escape(&item.title),
+            csv_escape(&item.authors.join("; ")),
+            item.year.to_string(),
+            csv_escape(&item.url),
+            item.citations.to_string(),
+        ];
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paper(title: &str, authors: &[&str], citations: i64) -> PaperItem {
+        PaperItem {
+            id: "2401.00001v1".to_string(),
+            title: title.to_string(),
+            authors: authors.iter().map(|a| a.to_string()).collect(),
+            year: 2024,
+            published: String::new(),
+            abstract_text: String::new(),
+            url: "http://arxiv.org/abs/2401.00001v1".to_string(),
+            citations,
+            categories: Vec::new(),
+            doi: None,
+            journal_ref: None,
+        }
+    }
+
+    #[test]
+    fn markdown_links_titles_and_shortens_author_lists() {
+        let items = [
+            paper("Graphs [and] more", &["A", "B", "C", "D"], 12),
+            paper("Solo", &["Ada"], 1),
+        ];
+        assert_eq!(
+            render(&items, OutputFormat::Md),
+            "- [Graphs \\[and\\] more](http://arxiv.org/abs/2401.00001v1) — A, B, C et al. (2024) · 12 citations\n\
+             - [Solo](http://arxiv.org/abs/2401.00001v1) — Ada (2024) · 1 citation\n"
+        );
+    }
+
+    #[test]
+    fn csv_quotes_fields_that_need_it() {
+        let items = [paper("Rust, \"fast\" and safe", &["A", "B"], 0)];
+        assert_eq!(
+            render(&items, OutputFormat::Csv),
+            "id,title,authors,year,url,citations\n\
+             2401.00001v1,\"Rust, \"\"fast\"\" and safe\",A; B,2024,http://arxiv.org/abs/2401.00001v1,0\n"
+        );
+    }
+}
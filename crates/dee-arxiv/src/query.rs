@@ -0,0 +1,196 @@
+//! Compose arXiv `search_query` strings from the search flags.
+//!
+//! Field terms (`ti:`, `abs:`, `au:`, `all:`) are ANDed, or ORed with `--or`. Repeated
+//! `--category` values are ORed with each other and ANDed with the rest, `--exclude`
+//! terms become `ANDNOT`, and `--from`/`--to` add a `submittedDate` range.
+
+use chrono::NaiveDate;
+
+use crate::{AppError, QueryArgs};
+
+const FIELD_PREFIXES: [&str; 9] = ["ti", "abs", "au", "cat", "all", "co", "jr", "rn", "id"];
+
+/// `field:value`, quoting multi-word values so arXiv treats them as a phrase.
+fn term(field: &str, value: &str) -> Option<String> {
+    let value = value.replace('"', "");
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    if value.is_empty() {
+        None
+    } else if value.contains(' ') {
+        Some(format!("{field}:\"{value}\""))
+    } else {
+        Some(format!("{field}:{value}"))
+    }
+}
+
+/// An `--exclude` value may name its own field (`au:Smith`); otherwise it matches `all:`.
+fn exclude_term(value: &str) -> Option<String> {
+    match value.trim().split_once(':') {
+        Some((field, rest)) if FIELD_PREFIXES.contains(&field) => term(field, rest),
+        _ => term("all", value),
+    }
+}
+
+fn group(terms: &[String], op: &str) -> String {
+    if terms.len() == 1 {
+        terms[0].clone()
+    } else {
+        format!("({})", terms.join(&format!(" {op} ")))
+    }
+}
+
+fn parse_date(flag: &str, value: &str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| AppError::InvalidArgument(format!("{flag} must be a date like 2024-01-31")))
+}
+
+/// Parsed `--from`/`--to`, rejecting a range that ends before it starts.
+pub fn date_range(args: &QueryArgs) -> Result<(Option<NaiveDate>, Option<NaiveDate>), AppError> {
+    let from = args
+        .from
+        .as_deref()
+        .map(|v| parse_date("--from", v))
+        .transpose()?;
+    let to = args
+        .to
+        .as_deref()
+        .map(|v| parse_date("--to", v))
+        .transpose()?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(AppError::InvalidArgument(
+                "--from must not be after --to".to_string(),
+            ));
+        }
+    }
+    Ok((from, to))
+}
+
+pub fn build(args: &QueryArgs) -> Result<String, AppError> {
+    let mut fields = Vec::new();
+    // The positional query keeps its original meaning: the raw text under `all:`
+    if let Some(query) = args.query.as_deref().map(str::trim) {
+        if !query.is_empty() {
+            fields.push(format!("all:{query}"));
+        }
+    }
+    let flagged = [
+        ("ti", &args.title),
+        ("abs", &args.abstract_text),
+        ("au", &args.author),
+        ("all", &args.all),
+    ];
+    for (field, values) in flagged {
+        fields.extend(values.iter().filter_map(|v| term(field, v)));
+    }
+    let categories: Vec<String> = args
+        .category
+        .iter()
+        .filter_map(|c| term("cat", c))
+        .collect();
+
+    if fields.is_empty() && categories.is_empty() {
+        return Err(AppError::InvalidArgument(
+            "provide a query or at least one of --title, --abstract, --author, --all, --category"
+                .to_string(),
+        ));
+    }
+
+    let mut parts = Vec::new();
+    if !fields.is_empty() {
+        parts.push(group(&fields, if args.or { "OR" } else { "AND" }));
+    }
+    if !categories.is_empty() {
+        parts.push(group(&categories, "OR"));
+    }
+
+    let (from, to) = date_range(args)?;
+    if from.is_some() || to.is_some() {
+        let from = from
+            .map(|d| d.format("%Y%m%d0000").to_string())
+            .unwrap_or_else(|| "199101010000".to_string());
+        let to = to
+            .map(|d| d.format("%Y%m%d2359").to_string())
+            .unwrap_or_else(|| "999912312359".to_string());
+        parts.push(format!("submittedDate:[{from} TO {to}]"));
+    }
+
+    let mut query = parts.join(" AND ");
+    for excluded in args.exclude.iter().filter_map(|v| exclude_term(v)) {
+        query.push_str(" ANDNOT ");
+        query.push_str(&excluded);
+    }
+    Ok(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> QueryArgs {
+        QueryArgs {
+            query: None,
+            title: Vec::new(),
+            abstract_text: Vec::new(),
+            author: Vec::new(),
+            all: Vec::new(),
+            category: Vec::new(),
+            exclude: Vec::new(),
+            or: false,
+            from: None,
+            to: None,
+        }
+    }
+
+    #[test]
+    fn positional_query_is_unchanged() {
+        let mut a = args();
+        a.query = Some("graph neural networks".to_string());
+        a.category = vec!["cs.LG".to_string()];
+        assert_eq!(
+            build(&a).unwrap(),
+            "all:graph neural networks AND cat:cs.LG"
+        );
+    }
+
+    #[test]
+    fn composes_fields_categories_and_exclusions() {
+        let mut a = args();
+        a.title = vec!["graph neural".to_string()];
+        a.author = vec!["Kipf".to_string()];
+        a.category = vec!["cs.LG".to_string(), "stat.ML".to_string()];
+        a.exclude = vec!["survey".to_string(), "au:Smith".to_string()];
+        assert_eq!(
+            build(&a).unwrap(),
+            "(ti:\"graph neural\" AND au:Kipf) AND (cat:cs.LG OR cat:stat.ML) ANDNOT all:survey ANDNOT au:Smith"
+        );
+
+        a.or = true;
+        assert!(build(&a)
+            .unwrap()
+            .starts_with("(ti:\"graph neural\" OR au:Kipf)"));
+    }
+
+    #[test]
+    fn date_range_fills_open_ends() {
+        let mut a = args();
+        a.abstract_text = vec!["diffusion".to_string()];
+        a.from = Some("2024-01-01".to_string());
+        assert_eq!(
+            build(&a).unwrap(),
+            "abs:diffusion AND submittedDate:[202401010000 TO 999912312359]"
+        );
+
+        a.to = Some("2023-12-31".to_string());
+        assert!(build(&a).is_err());
+        a.to = Some("31/12/2024".to_string());
+        assert!(build(&a).is_err());
+    }
+
+    #[test]
+    fn needs_a_positive_term() {
+        let mut a = args();
+        a.exclude = vec!["survey".to_string()];
+        assert!(build(&a).is_err());
+    }
+}
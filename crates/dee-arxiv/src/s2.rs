@@ -0,0 +1,359 @@
+//! Semantic Scholar Graph API: citation counts and citation graph lookups normalized
+//! into `PaperItem`. Every call sends the configured API key, if any, as `x-api-key`.
+
+use dee_core::http::base_url;
+use reqwest::blocking::RequestBuilder;
+use serde::Deserialize;
+
+use crate::{cite, http, library, normalize_whitespace, AppError, GlobalArgs, PaperItem};
+
+const S2_BASE: &str = "https://api.semanticscholar.org";
+const GRAPH_PATH: &str = "/graph/v1/paper";
+const RECOMMEND_PATH: &str = "/recommendations/v1/papers/forpaper";
+const PAPER_FIELDS: &str =
+    "title,authors,year,publicationDate,abstract,url,citationCount,externalIds";
+/// Largest page the citations/references endpoints accept
+pub const MAX_GRAPH_LIMIT: usize = 1000;
+/// Most recommendations returned per request
+pub const MAX_RELATED_LIMIT: usize = 500;
+/// Most ids the batch endpoint takes per request
+const BATCH_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Direction {
+    /// Papers that cite the given paper
+    Citations,
+    /// Papers the given paper cites
+    References,
+}
+
+impl Direction {
+    fn path(self) -> &'static str {
+        match self {
+            Self::Citations => "citations",
+            Self::References => "references",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphResponse {
+    #[serde(default)]
+    data: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphEdge {
+    #[serde(rename = "citingPaper")]
+    citing: Option<S2Paper>,
+    #[serde(rename = "citedPaper")]
+    cited: Option<S2Paper>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecommendResponse {
+    #[serde(rename = "recommendedPapers", default)]
+    recommended: Vec<S2Paper>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct S2Paper {
+    #[serde(rename = "paperId")]
+    paper_id: Option<String>,
+    title: Option<String>,
+    #[serde(default)]
+    authors: Vec<S2Author>,
+    year: Option<i32>,
+    #[serde(rename = "publicationDate")]
+    publication_date: Option<String>,
+    #[serde(rename = "abstract")]
+    abstract_text: Option<String>,
+    url: Option<String>,
+    #[serde(rename = "citationCount")]
+    citation_count: Option<i64>,
+    #[serde(rename = "externalIds")]
+    external_ids: Option<ExternalIds>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CitationCount {
+    #[serde(rename = "citationCount")]
+    citation_count: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct S2Author {
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExternalIds {
+    #[serde(rename = "ArXiv")]
+    arxiv: Option<String>,
+    #[serde(rename = "DOI")]
+    doi: Option<String>,
+}
+
+/// Identifier for the S2 API. arXiv ids (any version, `arXiv:` or abs-URL form) become
+/// `arXiv:<id>`; S2 paper ids and prefixed ids such as `DOI:...` pass through.
+pub fn paper_ref(input: &str) -> String {
+    let input = input.trim();
+    let passthrough = ["DOI:", "CorpusId:", "PMID:", "ACL:", "MAG:", "URL:"];
+    if passthrough.iter().any(|prefix| input.starts_with(prefix))
+        || (input.len() == 40 && input.chars().all(|c| c.is_ascii_hexdigit()))
+    {
+        return input.to_string();
+    }
+    format!("arXiv:{}", library::library_key(input))
+}
+
+/// Escape an id for the URL path, keeping the `:` and `/` that S2 expects raw in
+/// `arXiv:...` and `DOI:10.x/...` ids.
+fn path_id(id: &str) -> String {
+    urlencoding::encode(id)
+        .replace("%3A", ":")
+        .replace("%2F", "/")
+}
+
+/// Convert an S2 record. Papers on arXiv keep their arXiv id; others use the S2 paper id.
+/// Records without a title (S2 returns stubs for unresolved references) are dropped.
+pub fn to_item(paper: S2Paper) -> Option<PaperItem> {
+    let title = normalize_whitespace(paper.title.as_deref()?);
+    if title.is_empty() {
+        return None;
+    }
+    let (arxiv, doi) = match paper.external_ids {
+        Some(ids) => (ids.arxiv, ids.doi),
+        None => (None, None),
+    };
+    let id = arxiv.clone().or(paper.paper_id).unwrap_or_default();
+    let url = match &arxiv {
+        Some(arxiv) => format!("https://arxiv.org/abs/{arxiv}"),
+        None => paper.url.unwrap_or_default(),
+    };
+    Some(PaperItem {
+        id,
+        title,
+        authors: paper.authors.into_iter().filter_map(|a| a.name).collect(),
+        year: paper.year.unwrap_or(0),
+        published: paper.publication_date.unwrap_or_default(),
+        abstract_text: paper
+            .abstract_text
+            .map(|a| normalize_whitespace(&a))
+            .unwrap_or_default(),
+        url,
+        citations: paper.citation_count.unwrap_or(0),
+        categories: Vec::new(),
+        doi,
+        journal_ref: None,
+    })
+}
+
+/// Where Semantic Scholar is reached and the key sent along, if any.
+pub struct Api<'a> {
+    api_key: Option<&'a str>,
+    base: String,
+}
+
+impl<'a> Api<'a> {
+    /// `configured` replaces `https://api.semanticscholar.org`.
+    pub fn new(api_key: Option<&'a str>, configured: Option<&str>) -> Self {
+        Self {
+            api_key,
+            base: base_url(configured, S2_BASE),
+        }
+    }
+
+    fn with_key(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.api_key {
+            Some(key) if !key.trim().is_empty() => request.header("x-api-key", key.trim()),
+            _ => request,
+        }
+    }
+}
+
+/// Set `citations` on arXiv results with one batch POST per 500 papers, matched on
+/// `arXiv:<id>` rather than by title. Best effort: papers S2 does not know, or a failed
+/// request, leave the count at 0 (`--verbose` reports failures).
+pub fn enrich_citations(
+    items: &mut [PaperItem],
+    api: &Api,
+    out: &GlobalArgs,
+) -> Result<(), AppError> {
+    if items.is_empty() {
+        return Ok(());
+    }
+
+    let client = http::client(out)?;
+    let url = format!("{}{GRAPH_PATH}/batch?fields=citationCount", api.base);
+    for chunk in items.chunks_mut(BATCH_SIZE) {
+        let ids: Vec<String> = chunk
+            .iter()
+            .map(|item| format!("arXiv:{}", cite::eprint(&item.id)))
+            .collect();
+
+        let request = client.post(&url).json(&serde_json::json!({ "ids": ids }));
+        let counts = http::send(api.with_key(request), out).and_then(|body| {
+            serde_json::from_str::<Vec<Option<CitationCount>>>(&body)
+                .map_err(|_| AppError::ParseFailed)
+        });
+        match counts {
+            Ok(counts) => apply_counts(chunk, counts),
+            Err(err) => {
+                tracing::debug!("citation lookup failed: {err}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The batch response lists one entry (or null) per requested id, in request order.
+fn apply_counts(items: &mut [PaperItem], counts: Vec<Option<CitationCount>>) {
+    for (item, count) in items.iter_mut().zip(counts) {
+        item.citations = count.and_then(|c| c.citation_count).unwrap_or(0);
+    }
+}
+
+/// One page of citing or cited papers for `paper_id`.
+pub fn fetch_graph(
+    paper_id: &str,
+    direction: Direction,
+    limit: usize,
+    offset: usize,
+    api: &Api,
+    out: &GlobalArgs,
+) -> Result<Vec<PaperItem>, AppError> {
+    let url = format!(
+        "{}{}/{}/{}?fields={}&limit={}&offset={}",
+        api.base,
+        GRAPH_PATH,
+        path_id(&paper_ref(paper_id)),
+        direction.path(),
+        PAPER_FIELDS,
+        limit,
+        offset
+    );
+    let body = http::send(api.with_key(http::client(out)?.get(&url)), out)?;
+    let parsed: GraphResponse = serde_json::from_str(&body).map_err(|_| AppError::ParseFailed)?;
+
+    Ok(parsed
+        .data
+        .into_iter()
+        .filter_map(|edge| match direction {
+            Direction::Citations => edge.citing,
+            Direction::References => edge.cited,
+        })
+        .filter_map(to_item)
+        .collect())
+}
+
+/// Papers similar to `paper_id`, most relevant first, from S2's recommendation pool of
+/// recent papers.
+pub fn fetch_related(
+    paper_id: &str,
+    limit: usize,
+    api: &Api,
+    out: &GlobalArgs,
+) -> Result<Vec<PaperItem>, AppError> {
+    let url = format!(
+        "{}{}/{}?fields={}&limit={}",
+        api.base,
+        RECOMMEND_PATH,
+        path_id(&paper_ref(paper_id)),
+        PAPER_FIELDS,
+        limit
+    );
+    let body = http::send(api.with_key(http::client(out)?.get(&url)), out)?;
+    let parsed: RecommendResponse =
+        serde_json::from_str(&body).map_err(|_| AppError::ParseFailed)?;
+    Ok(parsed.recommended.into_iter().filter_map(to_item).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_s2_identifiers() {
+        assert_eq!(paper_ref("1706.03762v7"), "arXiv:1706.03762");
+        assert_eq!(paper_ref("arXiv:1706.03762"), "arXiv:1706.03762");
+        assert_eq!(
+            paper_ref("DOI:10.18653/v1/N19-1423"),
+            "DOI:10.18653/v1/N19-1423"
+        );
+        assert_eq!(path_id("DOI:10.1/a b"), "DOI:10.1/a%20b");
+        let s2 = "204e3073870fae3d05bcbc2f6a8e263d9b72e776";
+        assert_eq!(paper_ref(s2), s2);
+    }
+
+    #[test]
+    fn batch_counts_align_with_request_order() {
+        let paper = |id: &str| PaperItem {
+            id: id.to_string(),
+            title: String::new(),
+            authors: Vec::new(),
+            year: 0,
+            published: String::new(),
+            abstract_text: String::new(),
+            url: String::new(),
+            citations: 99,
+            categories: Vec::new(),
+            doi: None,
+            journal_ref: None,
+        };
+        let mut items = vec![paper("a"), paper("b"), paper("c")];
+        let counts: Vec<Option<CitationCount>> = serde_json::from_str(
+            r#"[{"paperId":"x","citationCount":12},null,{"paperId":"z","citationCount":null}]"#,
+        )
+        .unwrap();
+        apply_counts(&mut items, counts);
+        let got: Vec<i64> = items.iter().map(|i| i.citations).collect();
+        assert_eq!(got, vec![12, 0, 0]);
+    }
+
+    #[test]
+    fn maps_citing_papers_and_drops_stubs() {
+        let body = r#"{"offset":0,"data":[
+            {"citingPaper":{"paperId":"abc","title":"A  Follow-up","authors":[{"authorId":"1","name":"Ada Lovelace"}],
+              "year":2024,"publicationDate":"2024-03-01","abstract":null,"url":"https://www.semanticscholar.org/paper/abc",
+              "citationCount":7,"externalIds":{"ArXiv":"2403.00001","DOI":"10.1/x"}}},
+            {"citingPaper":{"paperId":"def","title":"Journal Paper","authors":[],"year":null,
+              "url":"https://www.semanticscholar.org/paper/def","citationCount":null,"externalIds":{}}},
+            {"citingPaper":{"paperId":null,"title":null}}
+        ]}"#;
+        let parsed: GraphResponse = serde_json::from_str(body).unwrap();
+        let items: Vec<PaperItem> = parsed
+            .data
+            .into_iter()
+            .filter_map(|edge| edge.citing)
+            .filter_map(to_item)
+            .collect();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].id, "2403.00001");
+        assert_eq!(items[0].title, "A Follow-up");
+        assert_eq!(items[0].url, "https://arxiv.org/abs/2403.00001");
+        assert_eq!(items[0].doi.as_deref(), Some("10.1/x"));
+        assert_eq!(items[0].citations, 7);
+        assert_eq!(items[1].id, "def");
+        assert_eq!(items[1].year, 0);
+    }
+
+    #[test]
+    fn keeps_recommendation_order() {
+        let body = r#"{"recommendedPapers":[
+            {"paperId":"b","title":"Second Best","authors":[],"citationCount":3,"externalIds":{}},
+            {"paperId":"a","title":"Closest Match","authors":[],"citationCount":900,
+              "externalIds":{"ArXiv":"2401.00002"}}
+        ]}"#;
+        let parsed: RecommendResponse = serde_json::from_str(body).unwrap();
+        let ids: Vec<String> = parsed
+            .recommended
+            .into_iter()
+            .filter_map(to_item)
+            .map(|item| item.id)
+            .collect();
+        assert_eq!(ids, vec!["b", "2401.00002"]);
+    }
+}
@@ -0,0 +1,571 @@
+//! OpenAlex and Crossref backends for venues outside arXiv (journals, conference
+//! proceedings, the ACL Anthology), normalized into `PaperItem`.
+
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::{
+    http, normalize_whitespace, query, AppError, GlobalArgs, PaperItem, QueryArgs, SortBy,
+};
+
+const OPENALEX_WORKS: &str = "https://api.openalex.org/works";
+const CROSSREF_WORKS: &str = "https://api.crossref.org/works";
+/// Deepest offset Crossref serves without cursor paging
+const CROSSREF_MAX_OFFSET: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Source {
+    Arxiv,
+    Openalex,
+    Crossref,
+}
+
+impl Source {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Arxiv => "arxiv",
+            Self::Openalex => "openalex",
+            Self::Crossref => "crossref",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAlexPage {
+    #[serde(default)]
+    results: Vec<OpenAlexWork>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAlexWork {
+    id: Option<String>,
+    doi: Option<String>,
+    display_name: Option<String>,
+    publication_year: Option<i32>,
+    publication_date: Option<String>,
+    #[serde(default)]
+    authorships: Vec<OpenAlexAuthorship>,
+    /// Abstracts ship as `word -> [positions]` rather than text
+    abstract_inverted_index: Option<BTreeMap<String, Vec<usize>>>,
+    cited_by_count: Option<i64>,
+    primary_location: Option<OpenAlexLocation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAlexAuthorship {
+    author: Option<OpenAlexAuthor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAlexAuthor {
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAlexLocation {
+    landing_page_url: Option<String>,
+    source: Option<OpenAlexSource>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAlexSource {
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrossrefEnvelope<T> {
+    message: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrossrefPage {
+    #[serde(default)]
+    items: Vec<CrossrefWork>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CrossrefWork {
+    #[serde(rename = "DOI")]
+    doi: Option<String>,
+    #[serde(default)]
+    title: Vec<String>,
+    #[serde(default)]
+    author: Vec<CrossrefAuthor>,
+    issued: Option<CrossrefDate>,
+    #[serde(rename = "abstract")]
+    abstract_text: Option<String>,
+    #[serde(rename = "URL")]
+    url: Option<String>,
+    is_referenced_by_count: Option<i64>,
+    #[serde(default)]
+    container_title: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrossrefAuthor {
+    given: Option<String>,
+    family: Option<String>,
+    /// Organisations as authors have only a name
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CrossrefDate {
+    #[serde(default)]
+    date_parts: Vec<Vec<Option<i32>>>,
+}
+
+/// Bare DOI from `10.x/...`, `doi:10.x/...`, or a doi.org URL.
+pub fn normalize_doi(input: &str) -> String {
+    let doi = input.trim();
+    let doi = ["https://doi.org/", "http://doi.org/", "https://dx.doi.org/"]
+        .iter()
+        .find_map(|prefix| doi.strip_prefix(prefix))
+        .unwrap_or(doi);
+    let doi = doi
+        .strip_prefix("doi:")
+        .or_else(|| doi.strip_prefix("DOI:"))
+        .unwrap_or(doi);
+    doi.trim().to_string()
+}
+
+/// OpenAlex and Crossref only understand free text, title, author, and date terms, and page
+/// one request at a time; reject the arXiv-only flags rather than silently dropping them.
+fn check_filter(source: Source, filter: &QueryArgs) -> Result<(), AppError> {
+    let unsupported = [
+        ("--category", !filter.category.is_empty()),
+        ("--exclude", !filter.exclude.is_empty()),
+        ("--or", filter.or),
+        (
+            "--abstract",
+            source == Source::Crossref && !filter.abstract_text.is_empty(),
+        ),
+    ];
+    match unsupported.iter().find(|(_, used)| *used) {
+        Some((flag, _)) => Err(AppError::InvalidArgument(format!(
+            "{flag} is not supported with --source {}",
+            source.name()
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn free_text(filter: &QueryArgs) -> String {
+    let mut words: Vec<&str> = Vec::new();
+    words.extend(filter.query.as_deref());
+    words.extend(filter.all.iter().map(String::as_str));
+    normalize_whitespace(&words.join(" "))
+}
+
+/// OpenAlex filter values are comma-separated, so commas inside a term must go.
+fn filter_value(value: &str) -> String {
+    normalize_whitespace(&value.replace([',', '|'], " "))
+}
+
+fn openalex_url(
+    filter: &QueryArgs,
+    limit: usize,
+    start: usize,
+    sort: &SortBy,
+) -> Result<String, AppError> {
+    if !start.is_multiple_of(limit) {
+        return Err(AppError::InvalidArgument(
+            "--start must be a multiple of --limit with --source openalex".to_string(),
+        ));
+    }
+
+    let mut filters = Vec::new();
+    let flagged = [
+        ("title.search", &filter.title),
+        ("abstract.search", &filter.abstract_text),
+        ("raw_author_name.search", &filter.author),
+    ];
+    for (name, values) in flagged {
+        for value in values.iter().map(|v| filter_value(v)) {
+            if !value.is_empty() {
+                filters.push(format!("{name}:{value}"));
+            }
+        }
+    }
+    let text = free_text(filter);
+    if text.is_empty() && filters.is_empty() {
+        return Err(AppError::InvalidArgument(
+            "provide a query or at least one of --title, --abstract, --author, --all".to_string(),
+        ));
+    }
+    let (from, to) = query::date_range(filter)?;
+    if let Some(from) = from {
+        filters.push(format!("from_publication_date:{from}"));
+    }
+    if let Some(to) = to {
+        filters.push(format!("to_publication_date:{to}"));
+    }
+
+    let sort = match sort {
+        SortBy::Date => "publication_date:desc",
+        SortBy::Citations => "cited_by_count:desc",
+    };
+    let mut url = format!(
+        "{OPENALEX_WORKS}?per-page={limit}&page={}&sort={sort}",
+        start / limit + 1
+    );
+    if !text.is_empty() {
+        url.push_str("&search=");
+        url.push_str(&urlencoding::encode(&text));
+    }
+    if !filters.is_empty() {
+        url.push_str("&filter=");
+        url.push_str(&urlencoding::encode(&filters.join(",")));
+    }
+    Ok(url)
+}
+
+fn crossref_url(
+    filter: &QueryArgs,
+    limit: usize,
+    start: usize,
+    sort: &SortBy,
+) -> Result<String, AppError> {
+    if start > CROSSREF_MAX_OFFSET {
+        return Err(AppError::InvalidArgument(format!(
+            "--start must be at most {CROSSREF_MAX_OFFSET} with --source crossref"
+        )));
+    }
+
+    // Crossref has no title-only query; titles join the bibliographic text
+    let mut words = vec![free_text(filter)];
+    words.extend(filter.title.iter().cloned());
+    let text = normalize_whitespace(&words.join(" "));
+    let authors = normalize_whitespace(&filter.author.join(" "));
+    if text.is_empty() && authors.is_empty() {
+        return Err(AppError::InvalidArgument(
+            "provide a query or at least one of --title, --author, --all".to_string(),
+        ));
+    }
+
+    let (from, to) = query::date_range(filter)?;
+    let mut filters = Vec::new();
+    if let Some(from) = from {
+        filters.push(format!("from-pub-date:{from}"));
+    }
+    if let Some(to) = to {
+        filters.push(format!("until-pub-date:{to}"));
+    }
+
+    let sort = match sort {
+        SortBy::Date => "published",
+        SortBy::Citations => "is-referenced-by-count",
+    };
+    let mut url = format!("{CROSSREF_WORKS}?rows={limit}&offset={start}&sort={sort}&order=desc");
+    if !text.is_empty() {
+        url.push_str("&query.bibliographic=");
+        url.push_str(&urlencoding::encode(&text));
+    }
+    if !authors.is_empty() {
+        url.push_str("&query.author=");
+        url.push_str(&urlencoding::encode(&authors));
+    }
+    if !filters.is_empty() {
+        url.push_str("&filter=");
+        url.push_str(&urlencoding::encode(&filters.join(",")));
+    }
+    Ok(url)
+}
+
+/// One page of results from OpenAlex or Crossref. Citation counts come from the source.
+pub fn search(
+    source: Source,
+    filter: &QueryArgs,
+    limit: usize,
+    start: usize,
+    sort: &SortBy,
+    out: &GlobalArgs,
+) -> Result<Vec<PaperItem>, AppError> {
+    check_filter(source, filter)?;
+    match source {
+        Source::Arxiv => Err(AppError::InvalidArgument(
+            "arXiv searches go through the arXiv API".to_string(),
+        )),
+        Source::Openalex => {
+            let url = openalex_url(filter, limit, start, sort)?;
+            let page: OpenAlexPage = get_json(&url, out)?;
+            Ok(page.results.into_iter().filter_map(openalex_item).collect())
+        }
+        Source::Crossref => {
+            let url = crossref_url(filter, limit, start, sort)?;
+            let page: CrossrefEnvelope<CrossrefPage> = get_json(&url, out)?;
+            Ok(page
+                .message
+                .items
+                .into_iter()
+                .filter_map(crossref_item)
+                .collect())
+        }
+    }
+}
+
+/// Look up one work on Crossref by DOI.
+pub fn fetch_doi(doi: &str, out: &GlobalArgs) -> Result<PaperItem, AppError> {
+    let doi = normalize_doi(doi);
+    if !doi.starts_with("10.") || !doi.contains('/') {
+        return Err(AppError::InvalidArgument(
+            "DOI must look like 10.1000/xyz".to_string(),
+        ));
+    }
+    let url = format!("{CROSSREF_WORKS}/{}", urlencoding::encode(&doi));
+    let work: CrossrefEnvelope<CrossrefWork> = get_json(&url, out)?;
+    crossref_item(work.message).ok_or(AppError::NotFound)
+}
+
+fn get_json<T: serde::de::DeserializeOwned>(url: &str, out: &GlobalArgs) -> Result<T, AppError> {
+    let body = http::send(http::client(out)?.get(url), out)?;
+    serde_json::from_str(&body).map_err(|_| AppError::ParseFailed)
+}
+
+/// Rebuild abstract text from OpenAlex's inverted index.
+fn invert_abstract(index: BTreeMap<String, Vec<usize>>) -> String {
+    let mut words: Vec<(usize, String)> = index
+        .into_iter()
+        .flat_map(|(word, positions)| positions.into_iter().map(move |p| (p, word.clone())))
+        .collect();
+    words.sort();
+    words
+        .into_iter()
+        .map(|(_, word)| word)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Works are keyed by DOI when they have one, otherwise by OpenAlex id (`W2741809807`).
+fn openalex_item(work: OpenAlexWork) -> Option<PaperItem> {
+    let title = normalize_whitespace(work.display_name.as_deref()?);
+    if title.is_empty() {
+        return None;
+    }
+    let doi = work
+        .doi
+        .as_deref()
+        .map(normalize_doi)
+        .filter(|d| !d.is_empty());
+    let openalex_id = work
+        .id
+        .as_deref()
+        .map(|id| id.rsplit('/').next().unwrap_or(id).to_string())
+        .unwrap_or_default();
+    let (landing, venue) = match work.primary_location {
+        Some(loc) => (
+            loc.landing_page_url,
+            loc.source.and_then(|s| s.display_name),
+        ),
+        None => (None, None),
+    };
+    let url = landing
+        .or_else(|| doi.as_ref().map(|d| format!("https://doi.org/{d}")))
+        .or(work.id)
+        .unwrap_or_default();
+
+    Some(PaperItem {
+        id: doi.clone().unwrap_or(openalex_id),
+        title,
+        authors: work
+            .authorships
+            .into_iter()
+            .filter_map(|a| a.author.and_then(|a| a.display_name))
+            .collect(),
+        year: work.publication_year.unwrap_or(0),
+        published: work.publication_date.unwrap_or_default(),
+        abstract_text: work
+            .abstract_inverted_index
+            .map(invert_abstract)
+            .unwrap_or_default(),
+        url,
+        citations: work.cited_by_count.unwrap_or(0),
+        categories: Vec::new(),
+        doi,
+        journal_ref: venue.map(|v| normalize_whitespace(&v)),
+    })
+}
+
+/// Crossref abstracts are JATS XML (`<jats:p>...`); keep the text only.
+fn strip_tags(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut in_tag = false;
+    for c in input.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                out.push(' ');
+            }
+            '>' => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    normalize_whitespace(&out)
+}
+
+fn crossref_item(work: CrossrefWork) -> Option<PaperItem> {
+    let doi = work
+        .doi
+        .map(|d| normalize_doi(&d))
+        .filter(|d| !d.is_empty())?;
+    let title = normalize_whitespace(work.title.first()?);
+    if title.is_empty() {
+        return None;
+    }
+    let parts: Vec<i32> = work
+        .issued
+        .and_then(|d| d.date_parts.into_iter().next())
+        .unwrap_or_default()
+        .into_iter()
+        .map_while(|p| p)
+        .collect();
+    let published = match parts.as_slice() {
+        [y, m, d, ..] => format!("{y:04}-{m:02}-{d:02}"),
+        [y, m] => format!("{y:04}-{m:02}"),
+        [y] => format!("{y:04}"),
+        [] => String::new(),
+    };
+
+    Some(PaperItem {
+        id: doi.clone(),
+        title,
+        authors: work
+            .author
+            .into_iter()
+            .filter_map(|a| match (a.given, a.family) {
+                (Some(given), Some(family)) => Some(format!("{given} {family}")),
+                (None, Some(family)) => Some(family),
+                _ => a.name,
+            })
+            .collect(),
+        year: parts.first().copied().unwrap_or(0),
+        published,
+        abstract_text: work
+            .abstract_text
+            .map(|a| strip_tags(&a))
+            .unwrap_or_default(),
+        url: work.url.unwrap_or_else(|| format!("https://doi.org/{doi}")),
+        citations: work.is_referenced_by_count.unwrap_or(0),
+        categories: Vec::new(),
+        doi: Some(doi),
+        journal_ref: work
+            .container_title
+            .first()
+            .map(|v| normalize_whitespace(v)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter() -> QueryArgs {
+        QueryArgs {
+            query: None,
+            title: Vec::new(),
+            abstract_text: Vec::new(),
+            author: Vec::new(),
+            all: Vec::new(),
+            category: Vec::new(),
+            exclude: Vec::new(),
+            or: false,
+            from: None,
+            to: None,
+        }
+    }
+
+    #[test]
+    fn normalizes_doi_forms() {
+        assert_eq!(
+            normalize_doi(" https://doi.org/10.18653/v1/N19-1423"),
+            "10.18653/v1/N19-1423"
+        );
+        assert_eq!(normalize_doi("doi:10.1000/xyz"), "10.1000/xyz");
+        assert_eq!(normalize_doi("10.1000/xyz"), "10.1000/xyz");
+    }
+
+    #[test]
+    fn builds_source_urls_and_rejects_arxiv_only_flags() {
+        let mut f = filter();
+        f.query = Some("dependency parsing".to_string());
+        f.title = vec!["neural, graph".to_string()];
+        f.from = Some("2020-01-01".to_string());
+        assert_eq!(
+            openalex_url(&f, 25, 50, &SortBy::Citations).unwrap(),
+            "https://api.openalex.org/works?per-page=25&page=3&sort=cited_by_count:desc\
+             &search=dependency%20parsing\
+             &filter=title.search%3Aneural%20graph%2Cfrom_publication_date%3A2020-01-01"
+        );
+        assert!(openalex_url(&f, 25, 10, &SortBy::Date).is_err());
+
+        f.author = vec!["Manning".to_string()];
+        assert_eq!(
+            crossref_url(&f, 10, 0, &SortBy::Date).unwrap(),
+            "https://api.crossref.org/works?rows=10&offset=0&sort=published&order=desc\
+             &query.bibliographic=dependency%20parsing%20neural%2C%20graph\
+             &query.author=Manning&filter=from-pub-date%3A2020-01-01"
+        );
+
+        f.category = vec!["cs.CL".to_string()];
+        assert!(check_filter(Source::Openalex, &f).is_err());
+        let mut f = filter();
+        f.abstract_text = vec!["parsing".to_string()];
+        assert!(check_filter(Source::Openalex, &f).is_ok());
+        assert!(check_filter(Source::Crossref, &f).is_err());
+    }
+
+    #[test]
+    fn maps_openalex_work() {
+        let body = r#"{"meta":{"count":1},"results":[{
+            "id":"https://openalex.org/W2963403868","doi":"https://doi.org/10.18653/v1/n19-1423",
+            "display_name":"BERT: Pre-training of  Deep Bidirectional Transformers",
+            "publication_year":2019,"publication_date":"2019-06-01",
+            "authorships":[{"author":{"display_name":"Jacob Devlin"}},{"author":{"display_name":"Ming-Wei Chang"}}],
+            "abstract_inverted_index":{"We":[0],"introduce":[1],"BERT.":[3],"a":[2]},
+            "cited_by_count":70000,
+            "primary_location":{"landing_page_url":"https://aclanthology.org/N19-1423","source":{"display_name":"NAACL"}}
+        },{"id":"https://openalex.org/W1","doi":null,"display_name":null}]}"#;
+        let page: OpenAlexPage = serde_json::from_str(body).unwrap();
+        let items: Vec<PaperItem> = page.results.into_iter().filter_map(openalex_item).collect();
+
+        assert_eq!(items.len(), 1);
+        let item = &items[0];
+        assert_eq!(item.id, "10.18653/v1/n19-1423");
+        assert_eq!(
+            item.title,
+            "BERT: Pre-training of Deep Bidirectional Transformers"
+        );
+        assert_eq!(item.authors, vec!["Jacob Devlin", "Ming-Wei Chang"]);
+        assert_eq!(item.abstract_text, "We introduce a BERT.");
+        assert_eq!(item.url, "https://aclanthology.org/N19-1423");
+        assert_eq!(item.citations, 70000);
+        assert_eq!(item.journal_ref.as_deref(), Some("NAACL"));
+    }
+
+    #[test]
+    fn maps_crossref_work() {
+        let body = r#"{"status":"ok","message":{
+            "DOI":"10.1038/nature14539","title":["Deep learning"],
+            "author":[{"given":"Yann","family":"LeCun"},{"family":"Bengio"},{"name":"Google Brain"}],
+            "issued":{"date-parts":[[2015,5,27]]},
+            "abstract":"<jats:p>Deep learning allows\n models.</jats:p>",
+            "URL":"https://doi.org/10.1038/nature14539","is-referenced-by-count":50000,
+            "container-title":["Nature"]
+        }}"#;
+        let work: CrossrefEnvelope<CrossrefWork> = serde_json::from_str(body).unwrap();
+        let item = crossref_item(work.message).unwrap();
+
+        assert_eq!(item.id, "10.1038/nature14539");
+        assert_eq!(item.authors, vec!["Yann LeCun", "Bengio", "Google Brain"]);
+        assert_eq!(item.year, 2015);
+        assert_eq!(item.published, "2015-05-27");
+        assert_eq!(item.abstract_text, "Deep learning allows models.");
+        assert_eq!(item.citations, 50000);
+        assert_eq!(item.journal_ref.as_deref(), Some("Nature"));
+    }
+}
@@ -0,0 +1,93 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn with_home(dir: &TempDir) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+    cmd.env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .env("XDG_DATA_HOME", dir.path().join("data"));
+    cmd
+}
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let out = cmd.output().unwrap();
+    serde_json::from_slice(&out.stdout).expect("valid json")
+}
+
+#[test]
+fn add_list_remove_roundtrip() {
+    let home = TempDir::new().unwrap();
+
+    let added = json(with_home(&home).args([
+        "alerts",
+        "add",
+        "graph neural networks",
+        "--category",
+        "cs.LG",
+        "--name",
+        "gnn",
+        "--json",
+    ]));
+    assert_eq!(added["ok"], true);
+    assert_eq!(added["query"], "all:graph neural networks AND cat:cs.LG");
+    let id = added["id"].as_i64().expect("numeric id");
+
+    let listed = json(with_home(&home).args(["alerts", "list", "--json"]));
+    assert_eq!(listed["count"], 1);
+    assert_eq!(listed["items"][0]["name"], "gnn");
+    assert_eq!(listed["items"][0]["last_run"], "");
+
+    let csv = with_home(&home)
+        .args(["alerts", "list", "--output", "csv"])
+        .output()
+        .unwrap();
+    let csv = String::from_utf8(csv.stdout).unwrap();
+    assert!(csv.starts_with("id,name,query,"), "{csv}");
+    assert!(csv.contains(",gnn,"), "{csv}");
+
+    let removed = json(with_home(&home).args(["alerts", "remove", &id.to_string(), "--json"]));
+    assert_eq!(removed["ok"], true);
+
+    let listed = json(with_home(&home).args(["alerts", "list", "--json"]));
+    assert_eq!(listed["count"], 0);
+}
+
+#[test]
+fn run_without_alerts_reports_nothing() {
+    let home = TempDir::new().unwrap();
+    let report = json(with_home(&home).args(["alerts", "run", "--json"]));
+    assert_eq!(report["ok"], true);
+    assert_eq!(report["count"], 0);
+    assert_eq!(report["checked"], 0);
+}
+
+#[test]
+fn failed_alerts_fail_the_run() {
+    let home = TempDir::new().unwrap();
+    json(with_home(&home).args(["alerts", "add", "transformers", "--json"]));
+
+    let out = with_home(&home)
+        .env("DEE_ARXIV_ARXIV_BASE_URL", "http://127.0.0.1:9")
+        .args(["alerts", "run", "--retries", "0", "--json"])
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).expect("valid json");
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(report["ok"], false);
+    assert_eq!(report["checked"], 0);
+    assert_eq!(report["errors"][0]["code"], "NETWORK_ERROR");
+}
+
+#[test]
+fn unknown_alert_is_not_found_and_empty_query_is_invalid() {
+    let home = TempDir::new().unwrap();
+
+    let parsed = json(with_home(&home).args(["alerts", "run", "42", "--json"]));
+    assert_eq!(parsed["code"], "NOT_FOUND");
+
+    let parsed = json(with_home(&home).args(["alerts", "remove", "42", "--json"]));
+    assert_eq!(parsed["code"], "NOT_FOUND");
+
+    let parsed = json(with_home(&home).args(["alerts", "add", "--exclude", "survey", "--json"]));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
@@ -0,0 +1,59 @@
+use assert_cmd::Command;
+use dee_test_support::matchers::{method, path, query_param};
+use dee_test_support::{fixture, json, Mock, MockApi, ResponseTemplate, Sandbox};
+
+fn bin(sandbox: &Sandbox, api: &MockApi) -> Command {
+    let mut cmd = sandbox.command(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+    cmd.env("DEE_ARXIV_ARXIV_BASE_URL", api.url("/arxiv"))
+        .env("DEE_ARXIV_S2_BASE_URL", api.url("/s2"))
+        .env_remove("DEE_ARXIV_S2_API_KEY");
+    cmd
+}
+
+fn mount_paper(api: &MockApi) {
+    api.mount(
+        Mock::given(method("GET"))
+            .and(path("/arxiv/query"))
+            .and(query_param("id_list", "1706.03762"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(fixture("arxiv/attention.xml"), "application/atom+xml"),
+            ),
+    );
+}
+
+#[test]
+fn get_fills_citations_from_semantic_scholar() {
+    let api = MockApi::start();
+    mount_paper(&api);
+    api.post_json("/s2/graph/v1/paper/batch", &fixture("arxiv/s2_batch.json"));
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(
+        bin(&sandbox, &api)
+            .env("DEE_ARXIV_S2_API_KEY", "s2-test")
+            .args(["get", "1706.03762", "--json"]),
+    );
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["item"]["id"], "1706.03762v7");
+    assert_eq!(parsed["item"]["title"], "Attention Is All You Need");
+    assert_eq!(parsed["item"]["categories"][0], "cs.CL");
+    assert_eq!(parsed["item"]["citations"], 152000);
+
+    let requests = api.requests();
+    let batch = &requests[1];
+    assert_eq!(batch.headers.get("x-api-key").unwrap(), "s2-test");
+    let body: serde_json::Value = serde_json::from_slice(&batch.body).unwrap();
+    assert_eq!(body["ids"][0], "arXiv:1706.03762");
+}
+
+#[test]
+fn citation_lookup_failure_leaves_the_count_at_zero() {
+    let api = MockApi::start();
+    mount_paper(&api);
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["get", "1706.03762", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["item"]["citations"], 0);
+}
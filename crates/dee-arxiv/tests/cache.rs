@@ -0,0 +1,84 @@
+use assert_cmd::Command;
+use dee_test_support::seed_cache;
+use tempfile::TempDir;
+
+fn with_home(dir: &TempDir) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+    cmd.env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .env("XDG_DATA_HOME", dir.path().join("data"))
+        .env("XDG_CACHE_HOME", dir.path().join("cache"));
+    cmd
+}
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let out = cmd.output().unwrap();
+    serde_json::from_slice(&out.stdout).expect("valid json")
+}
+
+const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <entry>
+    <id>http://arxiv.org/abs/1706.03762v7</id>
+    <published>2017-06-12T17:57:34Z</published>
+    <title>Attention Is All You Need</title>
+    <summary>The dominant sequence transduction models...</summary>
+    <author><name>Ashish Vaswani</name></author>
+    <link href="http://arxiv.org/abs/1706.03762v7" rel="alternate" type="text/html"/>
+  </entry>
+</feed>"#;
+
+#[test]
+fn offline_miss_is_reported() {
+    let home = TempDir::new().unwrap();
+
+    let got = json(with_home(&home).args(["get", "1706.03762", "--offline", "--json"]));
+    assert_eq!(got["ok"], false);
+    assert_eq!(got["code"], "OFFLINE_MISS");
+
+    let searched = json(with_home(&home).args(["search", "rust", "--offline", "--json"]));
+    assert_eq!(searched["code"], "OFFLINE_MISS");
+}
+
+#[test]
+fn offline_serves_cached_responses() {
+    let home = TempDir::new().unwrap();
+    // Older than the TTL: still served with --offline
+    seed_cache(
+        &home.path().join("cache"),
+        "dee-arxiv",
+        "GET https://export.arxiv.org/api/query?id_list=1706.03762",
+        &serde_json::json!(FEED),
+    );
+
+    let got = json(with_home(&home).args(["get", "1706.03762", "--offline", "--json"]));
+    assert_eq!(got["ok"], true);
+    assert_eq!(got["item"]["id"], "1706.03762v7");
+    assert_eq!(got["item"]["title"], "Attention Is All You Need");
+    // The citation lookup was never cached, so the count stays at zero
+    assert_eq!(got["item"]["citations"], 0);
+    assert_eq!(got["stale"], true);
+    assert!(got["cache_age_secs"].as_u64().unwrap() > 1_000_000);
+}
+
+#[test]
+fn offline_and_no_cache_conflict() {
+    let home = TempDir::new().unwrap();
+    let out = with_home(&home)
+        .args(["get", "1706.03762", "--offline", "--no-cache", "--json"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn cache_clear_reports_what_it_removed() {
+    let home = TempDir::new().unwrap();
+    let cleared = json(with_home(&home).args(["cache", "clear", "--json"]));
+    assert_eq!(cleared["ok"], true);
+    assert_eq!(cleared["item"]["removed"], 0);
+}
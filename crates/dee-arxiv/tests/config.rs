@@ -0,0 +1,78 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn with_home(dir: &TempDir) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+    cmd.env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .env("XDG_DATA_HOME", dir.path().join("data"));
+    cmd
+}
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let out = cmd.output().unwrap();
+    serde_json::from_slice(&out.stdout).expect("valid json")
+}
+
+#[test]
+fn set_show_and_clear_defaults() {
+    let home = TempDir::new().unwrap();
+
+    for (key, value) in [
+        ("category", "cs.LG"),
+        ("limit", "25"),
+        ("output", "md"),
+        ("s2.api_key", "secret"),
+    ] {
+        let set = json(with_home(&home).args(["config", "set", key, value, "--json"]));
+        assert_eq!(set["ok"], true, "{key}");
+    }
+
+    let shown = json(with_home(&home).args(["config", "show", "--json"]));
+    assert_eq!(shown["item"]["category"], "cs.LG");
+    assert_eq!(shown["item"]["limit"], 25);
+    assert_eq!(shown["item"]["output"], "md");
+    assert_eq!(shown["item"]["s2_api_key_set"], true);
+    assert!(!shown.to_string().contains("secret"));
+
+    let path = json(with_home(&home).args(["config", "path", "--json"]));
+    let path = path["item"]["path"].as_str().unwrap();
+    let saved = std::fs::read_to_string(path).unwrap();
+    assert!(saved.contains("default_category = \"cs.LG\""));
+
+    json(with_home(&home).args(["config", "set", "category", "", "--json"]));
+    let shown = json(with_home(&home).args(["config", "show", "--json"]));
+    assert!(shown["item"]["category"].is_null());
+}
+
+#[test]
+fn rejects_unknown_keys_and_bad_values() {
+    let home = TempDir::new().unwrap();
+
+    for (key, value) in [
+        ("colour", "blue"),
+        ("limit", "500"),
+        ("output", "xlsx"),
+        ("category", "cs LG"),
+    ] {
+        let set = json(with_home(&home).args(["config", "set", key, value, "--json"]));
+        assert_eq!(set["ok"], false, "{key}");
+        assert_eq!(set["code"], "INVALID_ARGUMENT", "{key}");
+    }
+}
+
+#[test]
+fn configured_limit_is_validated_before_searching() {
+    let home = TempDir::new().unwrap();
+    let config_dir = home.path().join("config").join("dee-arxiv");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), "default_limit = 0\n").unwrap();
+
+    let searched = json(with_home(&home).args(["search", "rust", "--json"]));
+    assert_eq!(searched["code"], "INVALID_ARGUMENT");
+
+    // An explicit flag wins over the configured value, so the run gets as far as the cache
+    let searched =
+        json(with_home(&home).args(["search", "rust", "--limit", "5", "--offline", "--json"]));
+    assert_eq!(searched["code"], "OFFLINE_MISS");
+}
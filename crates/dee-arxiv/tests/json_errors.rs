@@ -11,3 +11,140 @@ fn emits_json_error_for_invalid_limit() {
     assert_eq!(parsed["ok"], false);
     assert_eq!(parsed["code"], "INVALID_ARGUMENT");
 }
+
+#[test]
+fn emits_json_error_for_unknown_cite_format() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+    cmd.args(["cite", "1706.03762", "--format", "endnote", "--json"]);
+
+    let out = cmd.assert().code(2).get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+    assert_eq!(parsed["ok"], false);
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn emits_json_error_for_search_without_terms_or_bad_dates() {
+    for args in [
+        vec!["search", "--exclude", "survey", "--json"],
+        vec!["search", "--title", "gnn", "--from", "2024/01/01", "--json"],
+    ] {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+        cmd.args(&args);
+
+        let out = cmd.assert().failure().get_output().stdout.clone();
+        let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+        assert_eq!(parsed["ok"], false);
+        assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+    }
+}
+
+#[test]
+fn emits_json_error_for_bad_paging_flags() {
+    for args in [
+        vec!["search", "rust", "--max", "50", "--json"],
+        vec!["search", "rust", "--fetch-all", "--max", "0", "--json"],
+        vec!["author", "Hinton", "--start", "40000", "--json"],
+    ] {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+        cmd.args(&args);
+
+        let out = cmd.assert().failure().get_output().stdout.clone();
+        let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+        assert_eq!(parsed["code"], "INVALID_ARGUMENT", "{args:?}");
+    }
+}
+
+#[test]
+fn emits_json_error_for_invalid_graph_limit() {
+    for command in ["references", "related"] {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+        cmd.args([command, "1706.03762", "--limit", "5000", "--json"]);
+
+        let out = cmd.assert().failure().get_output().stdout.clone();
+        let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+        assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+    }
+}
+
+#[test]
+fn emits_json_error_for_unreadable_config() {
+    let home = tempfile::tempdir().unwrap();
+    let config_dir = home.path().join("config").join("dee-arxiv");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), "s2_api_key = [unterminated").unwrap();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+    cmd.env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join("config"))
+        .args(["references", "1706.03762", "--json"]);
+
+    let out = cmd.assert().failure().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+    assert_eq!(parsed["code"], "CONFIG_INVALID");
+}
+
+#[test]
+fn output_and_cite_format_conflict() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+    cmd.args([
+        "search", "rust", "--output", "md", "--format", "bibtex", "--json",
+    ]);
+
+    let out = cmd.assert().code(2).get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn emits_json_error_for_arxiv_only_flags_and_bad_doi() {
+    for args in [
+        vec![
+            "search",
+            "parsing",
+            "--source",
+            "crossref",
+            "--category",
+            "cs.CL",
+            "--json",
+        ],
+        vec![
+            "search",
+            "parsing",
+            "--source",
+            "openalex",
+            "--fetch-all",
+            "--json",
+        ],
+        vec![
+            "search", "parsing", "--source", "openalex", "--limit", "20", "--start", "5", "--json",
+        ],
+        vec!["doi", "not-a-doi", "--json"],
+    ] {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+        cmd.args(&args);
+
+        let out = cmd.assert().failure().get_output().stdout.clone();
+        let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+        assert_eq!(parsed["ok"], false);
+        assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+    }
+}
+
+#[test]
+fn emits_json_error_for_out_of_range_timeout() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+    cmd.args(["search", "rust", "--timeout-secs", "0", "--json"]);
+
+    let out = cmd.assert().code(2).get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
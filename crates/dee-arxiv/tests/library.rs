@@ -0,0 +1,46 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn with_home(dir: &TempDir) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-arxiv"));
+    cmd.env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .env("XDG_DATA_HOME", dir.path().join("data"));
+    cmd
+}
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let out = cmd.output().unwrap();
+    serde_json::from_slice(&out.stdout).expect("valid json")
+}
+
+#[test]
+fn empty_library_lists_and_searches_nothing() {
+    let home = TempDir::new().unwrap();
+
+    let listed = json(with_home(&home).args(["lib", "list", "--json"]));
+    assert_eq!(listed["ok"], true);
+    assert_eq!(listed["count"], 0);
+
+    let found = json(with_home(&home).args(["lib", "search", "graph", "--json"]));
+    assert_eq!(found["count"], 0);
+}
+
+#[test]
+fn unknown_paper_is_not_found() {
+    let home = TempDir::new().unwrap();
+
+    let removed = json(with_home(&home).args(["lib", "remove", "1706.03762", "--json"]));
+    assert_eq!(removed["ok"], false);
+    assert_eq!(removed["code"], "NOT_FOUND");
+
+    let noted = json(with_home(&home).args(["lib", "note", "1706.03762", "read later", "--json"]));
+    assert_eq!(noted["code"], "NOT_FOUND");
+}
+
+#[test]
+fn note_without_text_is_invalid_argument() {
+    let home = TempDir::new().unwrap();
+    let parsed = json(with_home(&home).args(["lib", "note", "1706.03762", "--json"]));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
@@ -3,7 +3,10 @@ use std::path::PathBuf;
 
 use chrono::{DateTime, SecondsFormat, Utc};
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::schema::{self, Schemas};
+use dee_core::{print_json, print_list, report, ErrorCode, Format, OkItem, OkList};
 use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Parser)]
@@ -35,6 +38,10 @@ struct GlobalFlags {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -206,7 +213,7 @@ struct InteractionListArgs {
     limit: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct ContactItem {
     id: i64,
     name: String,
@@ -228,7 +235,7 @@ struct ContactItem {
     updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct InteractionItem {
     id: i64,
     contact_id: i64,
@@ -238,7 +245,7 @@ struct InteractionItem {
     created_at: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ContactDetails {
     contact: ContactItem,
     interaction_count: usize,
@@ -252,20 +259,7 @@ struct CsvItem {
     count: usize,
 }
 
-#[derive(Debug, Serialize)]
-struct ListResponse<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct ItemResponse<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ActionResponse {
     ok: bool,
     message: String,
@@ -275,13 +269,6 @@ struct ActionResponse {
     count: Option<usize>,
 }
 
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 struct ExportBundle {
     contacts: Vec<ContactItem>,
@@ -306,7 +293,7 @@ enum AppError {
     Parse,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
             Self::DataDirMissing => "CONFIG_MISSING",
@@ -322,20 +309,32 @@ impl AppError {
 
 type AppResult<T> = Result<T, AppError>;
 
+/// What each command prints under `--json`, for `--schema`. `export --format csv`
+/// prints `{ok,item:{format,data,count}}` instead.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .custom::<ActionResponse>("add")
+        .list::<ContactItem>("list")
+        .list::<ContactItem>("search")
+        .item::<ContactDetails>("show")
+        .custom::<ActionResponse>("edit")
+        .custom::<ActionResponse>("delete")
+        .custom::<ActionResponse>("import")
+        .list::<ContactItem>("export")
+        .custom::<ActionResponse>("interaction add")
+        .list::<InteractionItem>("interaction list")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = run(&cli) {
-        if cli.global.json {
-            print_json(&ErrorResponse {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -347,9 +346,7 @@ fn run(cli: &Cli) -> AppResult<()> {
         fs::create_dir_all(parent).map_err(|_| AppError::Database)?;
     }
 
-    if cli.global.verbose {
-        eprintln!("[dee-contacts] db_path={}", db_path.display());
-    }
+    tracing::debug!("db_path={}", db_path.display());
 
     let conn = Connection::open(db_path).map_err(|_| AppError::Database)?;
     init_db(&conn)?;
@@ -576,7 +573,7 @@ fn cmd_show(conn: &Connection, input: &str, global: &GlobalFlags) -> AppResult<(
     };
 
     if global.json {
-        print_json(&ItemResponse {
+        print_json(&OkItem {
             ok: true,
             item: details,
         });
@@ -671,7 +668,7 @@ fn cmd_export(conn: &Connection, args: &ExportArgs, global: &GlobalFlags) -> App
     match args.format {
         TransferFormat::Json => {
             if global.json {
-                print_json(&ListResponse {
+                print_json(&OkList {
                     ok: true,
                     count: contacts.len(),
                     items: contacts,
@@ -690,7 +687,7 @@ fn cmd_export(conn: &Connection, args: &ExportArgs, global: &GlobalFlags) -> App
         TransferFormat::Csv => {
             let csv = contacts_to_csv(&contacts);
             if global.json {
-                print_json(&ItemResponse {
+                print_json(&OkItem {
                     ok: true,
                     item: CsvItem {
                         format: "csv".to_string(),
@@ -736,12 +733,8 @@ fn cmd_interaction(
             let contact_id = resolve_contact_id(conn, &cmd.contact)?;
             let items = list_interactions(conn, contact_id, cmd.limit)?;
 
-            if global.json {
-                print_json(&ListResponse {
-                    ok: true,
-                    count: items.len(),
-                    items,
-                });
+            if let Some(format) = Format::resolve(global.output, global.json) {
+                print_list(&items, format);
             } else if global.quiet {
                 println!("{}", items.len());
             } else if items.is_empty() {
@@ -900,12 +893,8 @@ fn parse_interaction_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Interactio
 }
 
 fn print_contact_list(items: Vec<ContactItem>, global: &GlobalFlags) -> AppResult<()> {
-    if global.json {
-        print_json(&ListResponse {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(global.output, global.json) {
+        print_list(&items, format);
         return Ok(());
     }
 
@@ -1169,47 +1158,3 @@ fn empty_to_none(value: &str) -> Option<&str> {
         Some(value)
     }
 }
-
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(text) => println!("{text}"),
-        Err(_) => {
-            println!(
-                "{{\"ok\":false,\"error\":\"JSON serialization failed\",\"code\":\"SERIALIZE\"}}"
-            );
-            std::process::exit(1);
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -0,0 +1,359 @@
+//! Responses kept on disk, the same way for every tool: one JSON file per key under
+//! `<cache dir>/<tool>/` (`~/.cache/dee-hn/` on Linux, honouring `XDG_CACHE_HOME`).
+//!
+//! A key is the request URL, minus any credentials. Entries younger than the TTL are
+//! served; older ones stay until the size cap pushes them out, so a tool can still
+//! fall back to them. Writes are best effort: an unwritable cache dir means no cache.
+//!
+//! `--offline` answers from the cache alone, at any age. Whatever is served that way
+//! is recorded, and the JSON envelopes then carry `"stale":true` and `cache_age_secs`
+//! (the oldest entry used). Nothing cached is [`CacheError::OfflineMiss`].
+//!
+//! ```no_run
+//! # fn fetch(url: &str) -> Vec<u64> { Vec::new() }
+//! let cache = dee_core::cache::Cache::new("dee-example").ttl_secs(300);
+//! let url = "https://example.com/top.json";
+//! let ids: Vec<u64> = match cache.get(url) {
+//!     Some(hit) => hit.value,
+//!     None => {
+//!         let ids = fetch(url);
+//!         cache.put(url, &ids);
+//!         ids
+//!     }
+//! };
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::{Args, Subcommand};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ErrorCode;
+use crate::output::{print_json, OkItem};
+
+/// How long an entry is served when the tool sets no TTL
+pub const DEFAULT_TTL_SECS: u64 = 60 * 60;
+/// Size of a tool's cache dir before the oldest entries are dropped
+pub const DEFAULT_MAX_BYTES: u64 = 50 * 1024 * 1024;
+
+/// `--no-cache` and `--offline`, for `#[command(flatten)]` next to `--json`.
+#[derive(Debug, Clone, Copy, Default, Args)]
+pub struct CacheArgs {
+    /// Skip cached responses (fresh ones are still cached)
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+    /// Answer only from cached responses, however old; never touch the network
+    #[arg(long, global = true, conflicts_with = "no_cache")]
+    pub offline: bool,
+}
+
+/// `<tool> cache clear|path`.
+#[derive(Debug, Clone, Copy, Subcommand)]
+pub enum CacheCommand {
+    /// Delete every cached response
+    Clear,
+    /// Print the cache directory
+    Path,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CacheError {
+    #[error("Could not clear cache {path}: {message}")]
+    Clear { path: String, message: String },
+    #[error("Nothing cached for {key}; run once without --offline")]
+    OfflineMiss { key: String },
+}
+
+impl ErrorCode for CacheError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Clear { .. } => "CACHE_CLEAR_FAILED",
+            Self::OfflineMiss { .. } => "OFFLINE_MISS",
+        }
+    }
+}
+
+/// Oldest entry served without the network in this run, in seconds
+static SERVED_STALE: Mutex<Option<u64>> = Mutex::new(None);
+
+/// The `stale` marker added to JSON envelopes once cached data stood in for the network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Stale {
+    pub stale: bool,
+    pub cache_age_secs: u64,
+}
+
+/// One tool's cache.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+    max_bytes: u64,
+}
+
+/// A cached value and when it was fetched.
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    pub value: T,
+    /// Unix seconds
+    pub fetched_at: i64,
+    pub age_secs: u64,
+}
+
+/// What `cache clear` removed.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Cleared {
+    pub path: String,
+    pub removed: usize,
+    pub bytes: u64,
+}
+
+/// Where `cache path` says the entries live.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CachePath {
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Entry {
+    key: String,
+    /// Unix seconds
+    fetched_at: i64,
+    body: serde_json::Value,
+}
+
+impl Cache {
+    /// `tool` is the binary name, which is also the directory name.
+    pub fn new(tool: &str) -> Self {
+        Self::in_dir(dir(tool))
+    }
+
+    /// A cache rooted somewhere else, for tests.
+    pub fn in_dir(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl: Duration::from_secs(DEFAULT_TTL_SECS),
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+
+    pub fn ttl_secs(mut self, secs: u64) -> Self {
+        self.ttl = Duration::from_secs(secs);
+        self
+    }
+
+    pub fn max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = bytes;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The entry for `key` while it is younger than the TTL.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<Cached<T>> {
+        self.get_stale(key)
+            .filter(|hit| hit.age_secs < self.ttl.as_secs())
+    }
+
+    /// The entry for `key` at any age.
+    pub fn get_stale<T: DeserializeOwned>(&self, key: &str) -> Option<Cached<T>> {
+        let content = std::fs::read_to_string(self.path(key)).ok()?;
+        let entry: Entry = serde_json::from_str(&content).ok()?;
+        // Two keys with the same hash: treat as a miss
+        if entry.key != key {
+            return None;
+        }
+        Some(Cached {
+            value: serde_json::from_value(entry.body).ok()?,
+            fetched_at: entry.fetched_at,
+            age_secs: now().saturating_sub(entry.fetched_at).max(0) as u64,
+        })
+    }
+
+    /// What to serve for `key` before fetching: a fresh entry, any entry under
+    /// `--offline` (recorded with [`served_stale`]), nothing under `--no-cache`.
+    /// `Ok(None)` means fetch; `--offline` with nothing cached is an error.
+    pub fn lookup<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        args: &CacheArgs,
+    ) -> Result<Option<Cached<T>>, CacheError> {
+        if args.offline {
+            let hit = self.get_stale(key).ok_or_else(|| CacheError::OfflineMiss {
+                key: key.to_string(),
+            })?;
+            served_stale(hit.age_secs);
+            Ok(Some(hit))
+        } else if args.no_cache {
+            Ok(None)
+        } else {
+            Ok(self.get(key))
+        }
+    }
+
+    /// Store `value` under `key`, then drop the oldest entries past the size cap.
+    pub fn put<T: Serialize>(&self, key: &str, value: &T) {
+        self.put_at(key, value, now());
+    }
+
+    /// [`put`](Self::put) with the fetch time given, in Unix seconds, so an entry
+    /// can be back-dated (tests seed stale entries this way).
+    pub fn put_at<T: Serialize>(&self, key: &str, value: &T, fetched_at: i64) {
+        let Ok(body) = serde_json::to_value(value) else {
+            return;
+        };
+        let entry = Entry {
+            key: key.to_string(),
+            fetched_at,
+            body,
+        };
+        let Ok(content) = serde_json::to_string(&entry) else {
+            return;
+        };
+        if std::fs::create_dir_all(&self.dir).is_err() {
+            return;
+        }
+        // Write then rename, so a concurrent reader never sees half a file
+        let path = self.path(key);
+        let tmp = path.with_extension("json.tmp");
+        if std::fs::write(&tmp, content).is_ok() && std::fs::rename(&tmp, &path).is_ok() {
+            self.prune();
+        }
+    }
+
+    /// Delete every entry. A missing dir is an empty cache.
+    pub fn clear(&self) -> Result<Cleared, CacheError> {
+        let mut cleared = Cleared {
+            path: self.dir.display().to_string(),
+            removed: 0,
+            bytes: 0,
+        };
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(cleared),
+            Err(err) => return Err(self.clear_error(&err)),
+        };
+        for file in entries.flatten().filter(|file| is_entry(&file.path())) {
+            let bytes = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+            std::fs::remove_file(file.path()).map_err(|err| self.clear_error(&err))?;
+            cleared.removed += 1;
+            cleared.bytes += bytes;
+        }
+        Ok(cleared)
+    }
+
+    fn clear_error(&self, err: &std::io::Error) -> CacheError {
+        CacheError::Clear {
+            path: self.dir.display().to_string(),
+            message: err.to_string(),
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.json", fnv1a(key.as_bytes())))
+    }
+
+    /// Remove the least recently written entries until the dir fits the cap.
+    fn prune(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut files: Vec<(SystemTime, u64, PathBuf)> = entries
+            .flatten()
+            .filter(|file| is_entry(&file.path()))
+            .filter_map(|file| {
+                let meta = file.metadata().ok()?;
+                Some((meta.modified().ok()?, meta.len(), file.path()))
+            })
+            .collect();
+        let mut total: u64 = files.iter().map(|(_, bytes, _)| bytes).sum();
+        if total <= self.max_bytes {
+            return;
+        }
+        files.sort();
+        for (_, bytes, path) in files {
+            if total <= self.max_bytes {
+                break;
+            }
+            if std::fs::remove_file(path).is_ok() {
+                total -= bytes;
+            }
+        }
+    }
+}
+
+/// `<cache dir>/<tool>` (`~/.cache/<tool>` on Linux), or `./<tool>`.
+pub fn dir(tool: &str) -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(tool)
+}
+
+/// Record that data `age_secs` old was served instead of a fetch: under `--offline`,
+/// or as a fallback when the network failed.
+pub fn served_stale(age_secs: u64) {
+    let mut served = SERVED_STALE.lock().unwrap_or_else(|err| err.into_inner());
+    *served = Some(served.map_or(age_secs, |oldest| oldest.max(age_secs)));
+}
+
+/// The marker for this run's output, once anything stale was served.
+pub fn stale() -> Option<Stale> {
+    let served = SERVED_STALE.lock().unwrap_or_else(|err| err.into_inner());
+    served.map(|cache_age_secs| Stale {
+        stale: true,
+        cache_age_secs,
+    })
+}
+
+/// Run `cache clear` or `cache path`, printing the result.
+pub fn run(cache: &Cache, command: CacheCommand, json: bool) -> Result<(), CacheError> {
+    match command {
+        CacheCommand::Clear => {
+            let cleared = cache.clear()?;
+            if json {
+                print_json(&OkItem::new(cleared));
+            } else {
+                println!(
+                    "Removed {} cached responses ({} bytes) from {}",
+                    cleared.removed, cleared.bytes, cleared.path
+                );
+            }
+        }
+        CacheCommand::Path => {
+            let path = CachePath {
+                path: cache.dir().display().to_string(),
+            };
+            if json {
+                print_json(&OkItem::new(path));
+            } else {
+                println!("{}", path.path);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_entry(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// FNV-1a: stable across Rust releases, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
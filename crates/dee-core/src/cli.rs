@@ -0,0 +1,106 @@
+//! Argument parsing with the exit codes and JSON errors every tool uses.
+
+use clap::error::ErrorKind;
+use clap::{Arg, ArgMatches, Command, Parser};
+
+use crate::log::{self, with_logging};
+use crate::output::{print_json, ErrorJson, OkList};
+use crate::profile::{self, PROFILE_ARG};
+
+/// Exit code for usage errors (bad flags, missing arguments).
+pub const USAGE_EXIT_CODE: i32 = crate::exit::INVALID_ARGUMENT;
+
+/// Flag that prints the tool's man page instead of running it.
+pub const GENERATE_MAN_FLAG: &str = "--generate-man";
+
+/// Flag that prints the tool's read-only commands as MCP tools instead of running it.
+pub const MCP_TOOLS_FLAG: &str = "--mcp-tools";
+
+/// Parse the process arguments. `--help` and `--version` print and exit `0`,
+/// and so do [`GENERATE_MAN_FLAG`] and [`MCP_TOOLS_FLAG`] as the only argument.
+/// Usage errors exit [`USAGE_EXIT_CODE`]; with `--json`/`-j` anywhere on the
+/// command line (or `--output json|jsonl`) they print
+/// `{ok:false,error,code:"INVALID_ARGUMENT"}` on stdout. Every tool also takes
+/// `--profile <NAME>` (see [`with_profile`]) and `--log-level`/`--log-format`
+/// (see [`crate::log`]), and logging is set up before this returns.
+pub fn parse<C: Parser>() -> C {
+    exit_on_generate_man(|| with_logging(with_profile(C::command())));
+    exit_on_mcp_tools(C::command);
+    let mut matches = with_logging(with_profile(C::command()))
+        .try_get_matches()
+        .unwrap_or_else(|err| exit_on_parse_error(err));
+    select_profile(&matches);
+    log::init(&matches);
+    C::from_arg_matches_mut(&mut matches)
+        .unwrap_or_else(|err| exit_on_parse_error(err.format(&mut C::command())))
+}
+
+/// `command` with the global `--profile <NAME>` flag. Hand-built commands add
+/// it here and call [`select_profile`] on the matches.
+pub fn with_profile(command: Command) -> Command {
+    command.arg(
+        Arg::new(PROFILE_ARG)
+            .long("profile")
+            .value_name("NAME")
+            .global(true)
+            .help("Profile from ~/.config/dee/profiles.toml [env: DEE_PROFILE]"),
+    )
+}
+
+/// Use the `--profile` in `matches`, if any, for this process.
+pub fn select_profile(matches: &ArgMatches) {
+    if let Some(name) = matches.get_one::<String>(PROFILE_ARG) {
+        profile::select(name);
+    }
+}
+
+/// Print the man page of `command()` and exit `0` when the process was run as
+/// `<tool> --generate-man`. For tools that build their clap command by hand.
+pub fn exit_on_generate_man(command: impl FnOnce() -> Command) {
+    if only_arg(GENERATE_MAN_FLAG) {
+        print!("{}", crate::man::render(&command()));
+        std::process::exit(0);
+    }
+}
+
+/// Print `{ok,count,items}` with the [`crate::mcp::tools`] of `command()` and exit
+/// `0` when the process was run as `<tool> --mcp-tools`.
+pub fn exit_on_mcp_tools(command: impl FnOnce() -> Command) {
+    if only_arg(MCP_TOOLS_FLAG) {
+        print_json(&OkList::new(crate::mcp::tools(&command())));
+        std::process::exit(0);
+    }
+}
+
+fn only_arg(flag: &str) -> bool {
+    let mut args = std::env::args().skip(1);
+    args.next().as_deref() == Some(flag) && args.next().is_none()
+}
+
+pub fn exit_on_parse_error(err: clap::Error) -> ! {
+    match err.kind() {
+        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
+            let _ = err.print();
+            std::process::exit(0);
+        }
+        _ => {
+            if wants_json() {
+                print_json(&ErrorJson::new(err.to_string().trim(), "INVALID_ARGUMENT"));
+            } else {
+                let _ = err.print();
+            }
+            std::process::exit(USAGE_EXIT_CODE);
+        }
+    }
+}
+
+/// Whether `--json`, `-j` or `--output json|jsonl` was passed, for errors raised
+/// before parsing succeeds.
+pub fn wants_json() -> bool {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter().enumerate().any(|(i, arg)| match arg.as_str() {
+        "--json" | "-j" | "--output=json" | "--output=jsonl" => true,
+        "--output" => matches!(args.get(i + 1).map(String::as_str), Some("json" | "jsonl")),
+        _ => false,
+    })
+}
@@ -0,0 +1,268 @@
+//! `~/.config/<tool>/config.toml`, read into the tool's own config struct.
+//!
+//! ```no_run
+//! # #[derive(Default, serde::Serialize, serde::Deserialize)]
+//! # struct AppConfig { api_key: Option<String> }
+//! let config = dee_core::Config::<AppConfig>::new("dee-example").env_override("api_key");
+//! // DEE_EXAMPLE_API_KEY, when set, wins over the file
+//! let loaded = config.load()?;
+//! # Ok::<(), dee_core::ConfigError>(())
+//! ```
+
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::ErrorCode;
+use crate::profile;
+use crate::secrets::{self, SecretsBackend, SecretsError, BACKEND_KEY};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Invalid config {path}: {message}")]
+    Invalid { path: PathBuf, message: String },
+    #[error("Could not write config {path}: {message}")]
+    Write { path: PathBuf, message: String },
+    #[error("No profile `{name}` in {path}")]
+    UnknownProfile { name: String, path: PathBuf },
+    #[error(transparent)]
+    Secret(#[from] SecretsError),
+}
+
+impl ErrorCode for ConfigError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Invalid { .. } => "CONFIG_INVALID",
+            Self::Write { .. } => "CONFIG_WRITE_FAILED",
+            Self::UnknownProfile { .. } => "PROFILE_NOT_FOUND",
+            Self::Secret(err) => err.code(),
+        }
+    }
+}
+
+/// A tool's TOML config file.
+///
+/// The file lives at `<config dir>/<tool>/config.toml` (`$XDG_CONFIG_HOME` when
+/// set on Linux), or at `$DEE_<TOOL>_CONFIG`. A missing file is the default
+/// config. Keys registered with [`Config::env_override`] are read from
+/// `DEE_<TOOL>_<KEY>` on [`Config::load`], never written back by [`Config::save`];
+/// so are the keys of the active [`crate::profile`], which rank below them.
+/// Keys registered with [`Config::secret`] live in the OS keyring instead of the
+/// file when the file says `secrets_backend = "keyring"`.
+#[derive(Debug, Clone)]
+pub struct Config<T> {
+    tool: String,
+    overrides: Vec<&'static str>,
+    secrets: Vec<&'static str>,
+    _config: PhantomData<fn() -> T>,
+}
+
+impl<T: Serialize + DeserializeOwned + Default> Config<T> {
+    /// `tool` is the binary name, e.g. `dee-amazon`.
+    pub fn new(tool: &str) -> Self {
+        Self {
+            tool: tool.to_string(),
+            overrides: Vec::new(),
+            secrets: Vec::new(),
+            _config: PhantomData,
+        }
+    }
+
+    /// Let `DEE_<TOOL>_<KEY>` replace the top-level `key`. The text is taken
+    /// as a string when `key` holds one; otherwise as a TOML literal (`25`,
+    /// `true`, `["a", "b"]`) or a comma-separated list (`EUR,GBP`).
+    pub fn env_override(mut self, key: &'static str) -> Self {
+        self.overrides.push(key);
+        self
+    }
+
+    /// [`Config::env_override`] for each of `keys`.
+    pub fn env_overrides(self, keys: &[&'static str]) -> Self {
+        keys.iter()
+            .fold(self, |config, key| config.env_override(key))
+    }
+
+    /// Keep the top-level string `key` in the OS keyring under the `keyring`
+    /// backend. A value still in the file is used as is, and moved to the
+    /// keyring on the next [`Config::save`].
+    pub fn secret(mut self, key: &'static str) -> Self {
+        self.secrets.push(key);
+        self
+    }
+
+    pub fn path(&self) -> PathBuf {
+        if let Some(path) = std::env::var_os(self.env_name("CONFIG")) {
+            return PathBuf::from(path);
+        }
+        config_dir(&self.tool).join("config.toml")
+    }
+
+    /// The config with the active profile and env overrides applied.
+    pub fn load(&self) -> Result<T, ConfigError> {
+        let path = self.path();
+        let mut table = read_table(&path)?;
+        self.read_secrets(&path, &mut table)?;
+        if let Some(profile) = profile::table(&self.tool)? {
+            table.extend(profile);
+        }
+        for key in &self.overrides {
+            if let Ok(text) = std::env::var(self.env_name(key)) {
+                table.insert((*key).to_string(), env_value::<T>(key, text));
+            }
+        }
+        T::deserialize(table).map_err(|err| invalid(&path, err.message()))
+    }
+
+    /// The config as stored (file and keyring), for editing and saving back.
+    pub fn load_file(&self) -> Result<T, ConfigError> {
+        let path = self.path();
+        let mut table = read_table(&path)?;
+        self.read_secrets(&path, &mut table)?;
+        T::deserialize(table).map_err(|err| invalid(&path, err.message()))
+    }
+
+    pub fn save(&self, config: &T) -> Result<(), ConfigError> {
+        let path = self.path();
+        let write_err = |message: String| ConfigError::Write {
+            path: path.clone(),
+            message,
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| write_err(err.to_string()))?;
+        }
+        let content = match self.without_secrets(&path, config)? {
+            Some(table) => toml::to_string_pretty(&table),
+            None => toml::to_string_pretty(config),
+        }
+        .map_err(|err| write_err(err.to_string()))?;
+        std::fs::write(&path, content).map_err(|err| write_err(err.to_string()))
+    }
+
+    /// Fill secrets missing from the file from the keyring.
+    fn read_secrets(&self, path: &Path, table: &mut toml::Table) -> Result<(), ConfigError> {
+        if self.secrets.is_empty() || backend(path, table)? != SecretsBackend::Keyring {
+            return Ok(());
+        }
+        for key in &self.secrets {
+            if is_blank(table.get(*key)) {
+                if let Some(value) = secrets::get(&self.tool, key)? {
+                    table.insert((*key).to_string(), toml::Value::String(value));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Under the `keyring` backend: store set secrets in the keyring and return
+    /// the rest of `config`. `None` means the whole config goes in the file.
+    fn without_secrets(&self, path: &Path, config: &T) -> Result<Option<toml::Table>, ConfigError> {
+        if self.secrets.is_empty() {
+            return Ok(None);
+        }
+        let mut table = toml::Table::try_from(config).map_err(|err| ConfigError::Write {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })?;
+        if backend(path, &table)? != SecretsBackend::Keyring {
+            return Ok(None);
+        }
+        for key in &self.secrets {
+            if let Some(toml::Value::String(value)) = table.remove(*key) {
+                if !value.is_empty() {
+                    secrets::set(&self.tool, key, &value)?;
+                }
+            }
+        }
+        Ok(Some(table))
+    }
+
+    /// `DEE_<TOOL>_<SUFFIX>`: `dee-amazon` and `api_key` give `DEE_AMAZON_API_KEY`.
+    fn env_name(&self, suffix: &str) -> String {
+        format!("{}_{}", self.tool, suffix)
+            .to_ascii_uppercase()
+            .replace('-', "_")
+    }
+}
+
+/// The value `key` gets from env text: the string itself when `T` takes a
+/// string there, else the first reading of it that `T` accepts.
+fn env_value<T: DeserializeOwned>(key: &str, text: String) -> toml::Value {
+    let accepts = |value: &toml::Value| {
+        let mut table = toml::Table::new();
+        table.insert(key.to_string(), value.clone());
+        T::deserialize(table).is_ok()
+    };
+    let literal = format!("value = {text}")
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"));
+    let list = toml::Value::Array(
+        text.split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| toml::Value::String(item.to_string()))
+            .collect(),
+    );
+    let string = toml::Value::String(text);
+    if accepts(&string) {
+        return string;
+    }
+    // Nothing fits: keep the string so the error names what was expected
+    literal
+        .into_iter()
+        .chain([list])
+        .find(accepts)
+        .unwrap_or(string)
+}
+
+/// `<config dir>/<tool>`, or `./<tool>` when the platform has none.
+pub fn config_dir(tool: &str) -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(tool)
+}
+
+/// `<data dir>/<tool>` (`~/.local/share/<tool>` on Linux), or `./<tool>`.
+pub fn data_dir(tool: &str) -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(tool)
+}
+
+pub(crate) fn read_table(path: &Path) -> Result<toml::Table, ConfigError> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => content
+            .parse::<toml::Table>()
+            .map_err(|err| invalid(path, err.message())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(toml::Table::new()),
+        Err(err) => Err(invalid(path, &err.to_string())),
+    }
+}
+
+fn backend(path: &Path, table: &toml::Table) -> Result<SecretsBackend, ConfigError> {
+    match table.get(BACKEND_KEY) {
+        None => Ok(SecretsBackend::Plaintext),
+        Some(toml::Value::String(value)) => {
+            value.parse().map_err(|err: String| invalid(path, &err))
+        }
+        Some(_) => Err(invalid(path, "secrets_backend must be a string")),
+    }
+}
+
+/// A missing or empty string: tools default unset keys to `""` or leave them out.
+fn is_blank(value: Option<&toml::Value>) -> bool {
+    match value {
+        None => true,
+        Some(toml::Value::String(value)) => value.is_empty(),
+        Some(_) => false,
+    }
+}
+
+fn invalid(path: &Path, message: &str) -> ConfigError {
+    ConfigError::Invalid {
+        path: path.to_path_buf(),
+        message: message.to_string(),
+    }
+}
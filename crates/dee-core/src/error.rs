@@ -0,0 +1,43 @@
+//! Error codes and how errors reach the user.
+
+use std::fmt::Display;
+
+use crate::exit;
+use crate::output::{print_json, ErrorJson};
+
+/// An error with a stable, machine-readable `code` such as `NOT_FOUND`.
+pub trait ErrorCode: Display {
+    fn code(&self) -> &'static str;
+
+    /// The process exit status for this error, [`exit::for_code`] of its code
+    /// unless the tool knows better.
+    fn exit_code(&self) -> i32 {
+        exit::for_code(self.code())
+    }
+}
+
+impl<E: ErrorCode + ?Sized> ErrorCode for &E {
+    fn code(&self) -> &'static str {
+        (**self).code()
+    }
+
+    fn exit_code(&self) -> i32 {
+        (**self).exit_code()
+    }
+}
+
+/// Print an error: `{ok:false,error,code}` on stdout with `--json`, else
+/// `error: <message>` on stderr. The caller exits with `err.exit_code()`.
+pub fn report<E: ErrorCode + ?Sized>(err: &E, json: bool) {
+    report_code(&err.to_string(), err.code(), json);
+}
+
+/// [`report`] for errors that carry no [`ErrorCode`], such as `anyhow::Error`
+/// classified by the caller, who exits with [`exit::for_code`].
+pub fn report_code(message: &str, code: &str, json: bool) {
+    if json {
+        print_json(&ErrorJson::new(message, code));
+    } else {
+        eprintln!("error: {message}");
+    }
+}
@@ -0,0 +1,52 @@
+//! Exit statuses, the same in every tool, so scripts can branch on `$?`
+//! instead of parsing the JSON `code`.
+//!
+//! ```no_run
+//! # use dee_core::{exit, report_code};
+//! report_code("No such post", "NOT_FOUND", false);
+//! std::process::exit(exit::for_code("NOT_FOUND")); // 3
+//! ```
+
+/// Success
+pub const OK: i32 = 0;
+/// Any failure without a more specific status below
+pub const FAILURE: i32 = 1;
+/// Bad flags or values, including clap usage errors
+pub const INVALID_ARGUMENT: i32 = 2;
+/// The thing asked for does not exist (or is not cached, under `--offline`)
+pub const NOT_FOUND: i32 = 3;
+/// Credentials are missing or were rejected
+pub const AUTH: i32 = 4;
+/// The network or the remote service failed
+pub const NETWORK: i32 = 5;
+/// The command worked and a threshold was crossed: a cert about to expire,
+/// an alert condition not met
+pub const THRESHOLD: i32 = 6;
+
+/// The exit status for an error `code`. Codes outside the shared vocabulary
+/// exit [`FAILURE`]; `AUTH_*` codes exit [`AUTH`].
+pub fn for_code(code: &str) -> i32 {
+    match code {
+        "INVALID_ARGUMENT"
+        | "INVALID_INPUT"
+        | "MISSING_ARGUMENT"
+        | "INVALID_LANGUAGE"
+        | "UNSUPPORTED_FORMAT"
+        | "UNSUPPORTED_ECOSYSTEM"
+        | "CONFIRM_REQUIRED"
+        | "AMBIGUOUS" => INVALID_ARGUMENT,
+        "NOT_FOUND" | "OFFLINE_MISS" | "PROFILE_NOT_FOUND" | "UNKNOWN_TOOL" | "NOT_INSTALLED"
+        | "NO_ACTIVE_SESSION" => NOT_FOUND,
+        "NETWORK_ERROR"
+        | "REQUEST_FAILED"
+        | "HTTP_STATUS"
+        | "API_ERROR"
+        | "RATE_LIMITED"
+        | "RESOLVE_FAILED"
+        | "TLS_HANDSHAKE_FAILED"
+        | "WHOIS_LOOKUP_FAILED" => NETWORK,
+        "EXPIRING_SOON" => THRESHOLD,
+        code if code.starts_with("AUTH_") => AUTH,
+        _ => FAILURE,
+    }
+}
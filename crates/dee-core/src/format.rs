@@ -0,0 +1,262 @@
+//! `--output json|jsonl|yaml|csv|table` for list-returning commands.
+//!
+//! `json` and `yaml` print the whole `{ok,count,items}` envelope (with the
+//! `stale` marker after `--offline`); `jsonl`, `csv`
+//! and `table` print the items alone. Columns for `csv` and `table` follow the
+//! item's field order; nested values are written as compact JSON.
+//! [`render_columns`] keeps a chosen set of fields, in the chosen order, for
+//! a tool's `--columns` flag.
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+use crate::error::{report_code, ErrorCode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// `{ok,count,items}` on one line
+    Json,
+    /// One JSON object per item, per line
+    Jsonl,
+    /// The `json` envelope as YAML
+    Yaml,
+    /// A header row, then one row per item
+    Csv,
+    /// Aligned columns
+    Table,
+}
+
+impl Format {
+    /// `--output` when given, else `json` under `--json`. `None` means the
+    /// tool's own human output.
+    pub fn resolve(output: Option<Format>, json: bool) -> Option<Format> {
+        output.or(json.then_some(Format::Json))
+    }
+
+    /// Whether errors should be printed as JSON under this format.
+    pub fn is_json(self) -> bool {
+        matches!(self, Format::Json | Format::Jsonl)
+    }
+
+    /// Whether errors go out as JSON: under `--json` or `--output json|jsonl`.
+    pub fn json_errors(output: Option<Format>, json: bool) -> bool {
+        json || output.is_some_and(Format::is_json)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FormatError {
+    #[error("Could not render output: {0}")]
+    Render(String),
+    #[error("Unknown column `{column}`; available: {available}")]
+    UnknownColumn { column: String, available: String },
+}
+
+impl ErrorCode for FormatError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Render(_) => "SERIALIZE",
+            Self::UnknownColumn { .. } => "INVALID_ARGUMENT",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    ok: bool,
+    count: usize,
+    items: &'a [T],
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    stale: Option<crate::cache::Stale>,
+}
+
+pub fn render_list<T: Serialize>(items: &[T], format: Format) -> Result<String, FormatError> {
+    let envelope = Envelope {
+        ok: true,
+        count: items.len(),
+        items,
+        stale: crate::cache::stale(),
+    };
+    match format {
+        Format::Json => serde_json::to_string(&envelope)
+            .map(|text| text + "\n")
+            .map_err(|err| FormatError::Render(err.to_string())),
+        Format::Yaml => {
+            serde_yaml::to_string(&envelope).map_err(|err| FormatError::Render(err.to_string()))
+        }
+        Format::Jsonl => items.iter().try_fold(String::new(), |mut out, item| {
+            out +=
+                &serde_json::to_string(item).map_err(|err| FormatError::Render(err.to_string()))?;
+            out.push('\n');
+            Ok(out)
+        }),
+        Format::Csv => {
+            let (header, rows) = rows(items)?;
+            Ok(std::iter::once(header)
+                .chain(rows)
+                .filter(|row| !row.is_empty())
+                .map(|row| {
+                    row.iter()
+                        .map(|cell| csv_cell(cell))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                        + "\n"
+                })
+                .collect())
+        }
+        Format::Table => {
+            let (header, rows) = rows(items)?;
+            Ok(table(&header, &rows))
+        }
+    }
+}
+
+/// [`render_list`] with only `columns`, in that order; empty keeps every field.
+/// Every name must be a field of some item (`value` for items that are not
+/// maps), else [`FormatError::UnknownColumn`].
+pub fn render_columns<T: Serialize>(
+    items: &[T],
+    format: Format,
+    columns: &[String],
+) -> Result<String, FormatError> {
+    if columns.is_empty() {
+        return render_list(items, format);
+    }
+    let (available, _) = rows(items)?;
+    if let Some(column) = columns.iter().find(|column| !available.contains(column)) {
+        return Err(FormatError::UnknownColumn {
+            column: column.clone(),
+            available: available.join(", "),
+        });
+    }
+    let values = items
+        .iter()
+        .map(serde_yaml::to_value)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| FormatError::Render(err.to_string()))?;
+    let selected: Vec<Value> = values
+        .into_iter()
+        .map(|value| match value {
+            Value::Mapping(map) => Value::Mapping(
+                columns
+                    .iter()
+                    .map(|column| {
+                        let key = Value::String(column.clone());
+                        let cell = map.get(&key).cloned().unwrap_or(Value::Null);
+                        (key, cell)
+                    })
+                    .collect::<Mapping>(),
+            ),
+            other => other,
+        })
+        .collect();
+    render_list(&selected, format)
+}
+
+/// Print `items` in `format` on stdout. A failure prints a `SERIALIZE` error
+/// instead, as [`crate::print_json`] does.
+pub fn print_list<T: Serialize>(items: &[T], format: Format) {
+    match render_list(items, format) {
+        Ok(text) => print!("{text}"),
+        Err(err) => report_code(&err.to_string(), err.code(), format.is_json()),
+    }
+}
+
+/// Column names in first-seen order, and one row of cells per item. Items that
+/// are not maps get a single `value` column.
+fn rows<T: Serialize>(items: &[T]) -> Result<(Vec<String>, Vec<Vec<String>>), FormatError> {
+    let values = items
+        .iter()
+        .map(serde_yaml::to_value)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| FormatError::Render(err.to_string()))?;
+
+    let mut header: Vec<String> = Vec::new();
+    for value in &values {
+        match value {
+            Value::Mapping(map) => {
+                for key in map.keys() {
+                    let key = cell(key);
+                    if !header.contains(&key) {
+                        header.push(key);
+                    }
+                }
+            }
+            _ if !header.iter().any(|name| name == "value") => header.push("value".to_string()),
+            _ => {}
+        }
+    }
+
+    let rows = values
+        .iter()
+        .map(|value| {
+            header
+                .iter()
+                .map(|name| match value {
+                    Value::Mapping(map) => map.get(name.as_str()).map(cell).unwrap_or_default(),
+                    other if name == "value" => cell(other),
+                    _ => String::new(),
+                })
+                .collect()
+        })
+        .collect();
+    Ok((header, rows))
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        Value::Tagged(tagged) => cell(&tagged.value),
+        nested => serde_json::to_string(nested).unwrap_or_default(),
+    }
+}
+
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+fn table(header: &[String], rows: &[Vec<String>]) -> String {
+    if rows.is_empty() {
+        return String::new();
+    }
+    // Cells stay on one line so every row is one line
+    let flat = |cell: &String| cell.replace(['\n', '\r'], " ");
+    let lines: Vec<Vec<String>> =
+        std::iter::once(header.iter().map(|name| name.to_uppercase()).collect())
+            .chain(rows.iter().map(|row| row.iter().map(flat).collect()))
+            .collect();
+    let widths: Vec<usize> = (0..header.len())
+        .map(|col| {
+            lines
+                .iter()
+                .map(|line| line[col].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let mut out = String::new();
+    for line in &lines {
+        let mut text = String::new();
+        for (col, value) in line.iter().enumerate() {
+            if col > 0 {
+                text.push_str("  ");
+            }
+            text.push_str(value);
+            text.extend(std::iter::repeat_n(
+                ' ',
+                widths[col] - value.chars().count(),
+            ));
+        }
+        out.push_str(text.trim_end());
+        out.push('\n');
+    }
+    out
+}
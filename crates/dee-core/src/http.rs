@@ -0,0 +1,184 @@
+//! Outgoing HTTP, the same for every tool: one User-Agent, `--timeout-secs`,
+//! `--retries`, and the standard proxy variables.
+//!
+//! ```no_run
+//! # #[derive(clap::Parser)]
+//! # struct Cli { #[command(flatten)] http: dee_core::http::HttpArgs }
+//! # let cli: Cli = dee_core::cli::parse();
+//! let http = dee_core::http::Http::new("dee-example", env!("CARGO_PKG_VERSION")).args(&cli.http);
+//! let client = http.blocking()?;
+//! # Ok::<(), reqwest::Error>(())
+//! ```
+//!
+//! Proxies come from `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and `NO_PROXY`
+//! (upper or lower case). Retries are sent straight away, for connection
+//! failures and `502`/`503`/`504` answers to `GET` and `HEAD` requests only,
+//! so a POST is never sent twice.
+//!
+//! Every API root a tool calls can be moved with a `*_base_url` config key
+//! (`DEE_<TOOL>_<API>_BASE_URL` in the env), read through [`base_url`].
+//!
+//! Tools that retry `429`/`503` answers themselves wait [`retry_delay`]
+//! between attempts, honouring the server's [`retry_after`].
+
+use std::time::Duration;
+
+use clap::Args;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+
+/// `--timeout-secs` when neither the flag nor the tool sets one
+pub const DEFAULT_TIMEOUT_SECS: u64 = 20;
+/// `--retries` when neither the flag nor the tool sets one
+pub const DEFAULT_RETRIES: u32 = 2;
+/// Longest `Retry-After` we are willing to honour
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// The network flags, for `#[command(flatten)]` next to `--json`.
+#[derive(Debug, Clone, Copy, Default, Args)]
+pub struct HttpArgs {
+    /// Give up on a request after this many seconds
+    #[arg(long, global = true, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..=600))]
+    pub timeout_secs: Option<u64>,
+    /// Retries after a connection failure or a 502/503/504 answer
+    #[arg(long, global = true, value_name = "N", value_parser = clap::value_parser!(u32).range(0..=10))]
+    pub retries: Option<u32>,
+}
+
+/// Client settings for one tool. Build it once and make clients from it.
+#[derive(Debug, Clone)]
+pub struct Http {
+    user_agent: String,
+    timeout: Duration,
+    retries: u32,
+}
+
+impl Http {
+    /// `tool` is the binary name: the User-Agent is `dee-hn/0.1.0 (https://dee.ink)`.
+    pub fn new(tool: &str, version: &str) -> Self {
+        Self {
+            user_agent: format!("{tool}/{version} (https://dee.ink)"),
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            retries: DEFAULT_RETRIES,
+        }
+    }
+
+    /// The tool's own default timeout, for when `--timeout-secs` is not given.
+    pub fn timeout_secs(mut self, secs: u64) -> Self {
+        self.timeout = Duration::from_secs(secs);
+        self
+    }
+
+    /// Apply `--timeout-secs` and `--retries`.
+    pub fn args(mut self, args: &HttpArgs) -> Self {
+        if let Some(secs) = args.timeout_secs {
+            self.timeout = Duration::from_secs(secs);
+        }
+        if let Some(retries) = args.retries {
+            self.retries = retries;
+        }
+        self
+    }
+
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// An async client builder with these settings, for tools that add more.
+    pub fn builder(&self) -> reqwest::ClientBuilder {
+        reqwest::Client::builder()
+            .user_agent(&self.user_agent)
+            .timeout(self.timeout)
+            .retry(self.retry_policy())
+    }
+
+    pub fn client(&self) -> reqwest::Result<reqwest::Client> {
+        self.builder().build()
+    }
+
+    /// A blocking client builder with these settings, for tools that add more.
+    pub fn blocking_builder(&self) -> reqwest::blocking::ClientBuilder {
+        reqwest::blocking::Client::builder()
+            .user_agent(&self.user_agent)
+            .timeout(self.timeout)
+            .retry(self.retry_policy())
+    }
+
+    pub fn blocking(&self) -> reqwest::Result<reqwest::blocking::Client> {
+        self.blocking_builder().build()
+    }
+
+    fn retry_policy(&self) -> reqwest::retry::Builder {
+        reqwest::retry::for_host(AnyHost)
+            .max_retries_per_request(self.retries)
+            .classify_fn(|req_rep| {
+                let idempotent = matches!(*req_rep.method(), Method::GET | Method::HEAD);
+                let transient = match req_rep.status() {
+                    Some(status) => is_transient(status),
+                    None => req_rep
+                        .error()
+                        .and_then(|err| err.downcast_ref::<reqwest::Error>())
+                        .is_some_and(reqwest::Error::is_connect),
+                };
+                if idempotent && transient {
+                    req_rep.retryable()
+                } else {
+                    req_rep.success()
+                }
+            })
+    }
+}
+
+/// The API root to call: `configured` (a tool's `*_base_url` config key or
+/// env var) when set, else `default`, without a trailing `/`. This is how
+/// tests and proxies point a tool at another host.
+pub fn base_url(configured: Option<&str>, default: &str) -> String {
+    configured
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .unwrap_or(default)
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// The `Retry-After` of a response, when given in seconds.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// The wait before retry `attempt + 1`: `first`, doubled per earlier retry, or
+/// the server's `retry_after` (at most [`MAX_RETRY_AFTER`]) when that is longer.
+pub fn retry_delay(first: Duration, attempt: u32, retry_after: Option<Duration>) -> Duration {
+    let backoff = first.saturating_mul(1 << attempt.min(16));
+    retry_after
+        .map(|after| after.min(MAX_RETRY_AFTER))
+        .map_or(backoff, |after| after.max(backoff))
+}
+
+/// A retry scope covering every host.
+struct AnyHost;
+
+impl PartialEq<&str> for AnyHost {
+    fn eq(&self, _host: &&str) -> bool {
+        true
+    }
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
@@ -0,0 +1,52 @@
+//! The `--interval` of watch loops: `90s`, `15m`, `1h`, `2d`, or a bare
+//! number of seconds, and never less than [`MIN`].
+//!
+//! ```
+//! # use std::time::Duration;
+//! assert_eq!(dee_core::interval::parse("15m")?, Duration::from_secs(900));
+//! # Ok::<(), dee_core::interval::IntervalError>(())
+//! ```
+
+use std::time::Duration;
+
+use crate::error::ErrorCode;
+
+/// The shortest interval accepted, so a loop cannot hammer an API.
+pub const MIN: Duration = Duration::from_secs(60);
+
+#[derive(Debug, thiserror::Error)]
+pub enum IntervalError {
+    #[error("invalid --interval '{0}'. Use a number with s, m, h, or d (e.g. 30m, 1h)")]
+    Invalid(String),
+    #[error("--interval must be at least 1m")]
+    TooShort,
+}
+
+impl ErrorCode for IntervalError {
+    fn code(&self) -> &'static str {
+        "INVALID_ARGUMENT"
+    }
+}
+
+/// Parse an interval. Values too large to count in seconds are invalid.
+pub fn parse(raw: &str) -> Result<Duration, IntervalError> {
+    let invalid = || IntervalError::Invalid(raw.to_string());
+    let trimmed = raw.trim().to_ascii_lowercase();
+    let (number, unit) = match trimmed.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => trimmed.split_at(idx),
+        None => (trimmed.as_str(), "s"),
+    };
+    let value: u64 = number.parse().map_err(|_| invalid())?;
+    let scale = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        _ => return Err(invalid()),
+    };
+    let interval = Duration::from_secs(value.checked_mul(scale).ok_or_else(invalid)?);
+    if interval < MIN {
+        return Err(IntervalError::TooShort);
+    }
+    Ok(interval)
+}
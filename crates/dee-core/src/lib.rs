@@ -0,0 +1,46 @@
+//! The conventions every dee.ink CLI follows, in one place:
+//!
+//! - [`Config`]: `~/.config/<tool>/config.toml` with `DEE_<TOOL>_*` env overrides
+//! - [`profile`]: named profiles in `~/.config/dee/profiles.toml`, picked with `--profile` or `DEE_PROFILE`
+//! - [`secrets`]: credentials in the OS keyring under `secrets_backend = "keyring"`
+//! - [`OkList`], [`OkItem`], [`OkMessage`], [`ErrorJson`]: the JSON envelopes
+//! - [`ErrorCode`]: the `code` of an error, and [`report`] to print it
+//! - [`exit`]: the exit status for each kind of error, shared by every tool
+//! - [`cli::parse`]: clap parsing with usage errors as JSON under `--json`
+//! - [`log`]: `tracing` diagnostics on stderr, with `--log-level`, `-v` and `--log-format text|json`
+//! - [`Format`]: `--output json|jsonl|yaml|csv|table` for lists, via [`print_list`]
+//! - [`pipe`]: `--stdin-json`, another tool's `--json` output as a command's inputs
+//! - [`cache`]: responses on disk under the user cache dir, with a TTL, a size cap, `--no-cache` and `cache clear`
+//! - [`ratelimit`]: request budgets per API host, shared across tools and runs
+//! - [`interval`]: the `--interval` of watch loops, `30m`, `1h`, `2d`
+//! - `http` (feature `http`): reqwest clients with `--timeout-secs`, `--retries` and proxy support
+//! - [`style`]: colour for human output, off in pipes and under `NO_COLOR`
+//! - [`man::render`]: the roff man page behind `<tool> --generate-man`
+//! - [`mcp::tools`]: the read-only commands behind `<tool> --mcp-tools`, for `dee mcp-serve`
+//! - [`schema`]: the JSON Schema of each command's output, behind `<tool> <command> --schema`
+
+pub mod cache;
+pub mod cli;
+pub mod config;
+pub mod error;
+pub mod exit;
+pub mod format;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod interval;
+pub mod log;
+pub mod man;
+pub mod mcp;
+pub mod output;
+pub mod pipe;
+pub mod profile;
+pub mod ratelimit;
+pub mod schema;
+pub mod secrets;
+pub mod style;
+
+pub use config::{Config, ConfigError};
+pub use error::{report, report_code, ErrorCode};
+pub use format::{print_list, Format};
+pub use output::{print_json, ErrorJson, OkItem, OkList, OkMessage};
+pub use secrets::SecretsBackend;
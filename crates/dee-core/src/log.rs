@@ -0,0 +1,185 @@
+//! Diagnostics on stderr through `tracing`, the same for every tool, so an
+//! agent can keep them apart from the payload on stdout.
+//!
+//! Tools log with `tracing::debug!`, `tracing::warn!` and friends, never
+//! `eprintln!("debug: ...")`. [`crate::cli::parse`] adds two global flags and
+//! sets the subscriber up before the tool runs:
+//!
+//! - `--log-level off|error|warn|info|debug|trace` (or `DEE_LOG_LEVEL`);
+//!   default `warn`. A tool's `-v/--verbose` means `debug`.
+//! - `--log-format text|json` (or `DEE_LOG_FORMAT`). `text` prints
+//!   `debug: GET https://...`; `json` prints one object per line:
+//!   `{"timestamp":"...","level":"DEBUG","message":"GET https://...","target":"dee_hn"}`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgMatches, Command};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+
+pub use tracing::level_filters::LevelFilter;
+
+/// Env var with the level when `--log-level` (and `-v`) are not given
+pub const LOG_LEVEL_ENV: &str = "DEE_LOG_LEVEL";
+
+/// Env var with the format when `--log-format` is not given
+pub const LOG_FORMAT_ENV: &str = "DEE_LOG_FORMAT";
+
+/// The `--log-level` argument id
+pub const LOG_LEVEL_ARG: &str = "log_level";
+
+/// The `--log-format` argument id
+pub const LOG_FORMAT_ARG: &str = "log_format";
+
+/// The `-v/--verbose` argument id every tool declares
+pub const VERBOSE_ARG: &str = "verbose";
+
+const LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+/// How each log line is written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `debug: message key=value`
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "unknown log format '{other}' (expected text or json)"
+            )),
+        }
+    }
+}
+
+/// `command` with the global `--log-level` and `--log-format` flags.
+/// Hand-built commands add them here and call [`init`] on the matches.
+pub fn with_logging(command: Command) -> Command {
+    command
+        .arg(
+            Arg::new(LOG_LEVEL_ARG)
+                .long("log-level")
+                .value_name("LEVEL")
+                .value_parser(PossibleValuesParser::new(LEVELS))
+                .global(true)
+                .help("Diagnostics on stderr from this level up; -v means debug [env: DEE_LOG_LEVEL] [default: warn]"),
+        )
+        .arg(
+            Arg::new(LOG_FORMAT_ARG)
+                .long("log-format")
+                .value_name("FORMAT")
+                .value_parser(PossibleValuesParser::new(["text", "json"]))
+                .global(true)
+                .help("Diagnostics as text lines or JSON objects [env: DEE_LOG_FORMAT] [default: text]"),
+        )
+}
+
+/// Send `tracing` events to stderr as `matches` asks. Only the first call in a
+/// process takes effect.
+pub fn init(matches: &ArgMatches) {
+    let flag = matches
+        .try_get_one::<String>(LOG_LEVEL_ARG)
+        .ok()
+        .flatten()
+        .map(String::as_str);
+    let verbose = matches
+        .try_get_one::<bool>(VERBOSE_ARG)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    let env_level = std::env::var(LOG_LEVEL_ENV).ok();
+    let level = level(flag, verbose, env_level.as_deref());
+
+    let format = matches
+        .try_get_one::<String>(LOG_FORMAT_ARG)
+        .ok()
+        .flatten()
+        .cloned()
+        .or_else(|| std::env::var(LOG_FORMAT_ENV).ok())
+        .and_then(|text| text.parse().ok())
+        .unwrap_or_default();
+
+    let _ = tracing::subscriber::set_global_default(subscriber(level, format, std::io::stderr));
+}
+
+/// The level in effect: `--log-level`, else `debug` under `-v`, else
+/// `DEE_LOG_LEVEL`, else `warn`. Unknown names count as not given.
+pub fn level(flag: Option<&str>, verbose: bool, env: Option<&str>) -> LevelFilter {
+    let parse = |text: &str| LevelFilter::from_str(text.trim()).ok();
+    flag.and_then(parse)
+        .or(verbose.then_some(LevelFilter::DEBUG))
+        .or_else(|| env.and_then(parse))
+        .unwrap_or(LevelFilter::WARN)
+}
+
+/// A subscriber writing events up to `level` to `writer` in `format`. Events
+/// from dependencies (reqwest, hyper, ...) stop at `warn` unless `level` is
+/// `trace`.
+pub fn subscriber<W>(
+    level: LevelFilter,
+    format: LogFormat,
+    writer: W,
+) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let others = if level == LevelFilter::TRACE {
+        level
+    } else {
+        level.min(LevelFilter::WARN)
+    };
+    let targets = Targets::new()
+        .with_target("dee", level)
+        .with_default(others);
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(writer);
+    match format {
+        LogFormat::Text => Box::new(builder.event_format(TextLine).finish().with(targets)),
+        LogFormat::Json => Box::new(
+            builder
+                .json()
+                .flatten_event(true)
+                .with_current_span(false)
+                .with_span_list(false)
+                .finish()
+                .with(targets),
+        ),
+    }
+}
+
+/// `debug: message key=value`, the shape tools printed by hand before.
+struct TextLine;
+
+impl<S, N> FormatEvent<S, N> for TextLine
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let level = event.metadata().level().as_str().to_ascii_lowercase();
+        write!(writer, "{level}: ")?;
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
@@ -0,0 +1,215 @@
+//! Man pages (roff) rendered from a tool's clap definition.
+//!
+//! Every tool answers `<tool> --generate-man` through [`crate::cli::parse`];
+//! `cargo xtask man` collects the pages into `target/man`.
+
+use clap::{Arg, Command};
+
+/// The man page for `cmd`, section 1: synopsis, description, options, one
+/// subsection per subcommand, then the `after_help` blocks (`EXAMPLES:` ...).
+pub fn render(cmd: &Command) -> String {
+    let mut cmd = cmd.clone();
+    cmd.build();
+
+    let name = cmd.get_name().to_string();
+    let version = cmd.get_version().unwrap_or_default().to_string();
+    let mut out = format!(
+        ".TH \"{}\" \"1\" \"\" \"{} {}\" \"dee.ink\"\n",
+        name.to_uppercase(),
+        name,
+        version
+    );
+
+    out.push_str(".SH NAME\n");
+    match cmd.get_about() {
+        Some(about) => out.push_str(&format!("{name} \\- {}\n", escape(&about.to_string()))),
+        None => out.push_str(&format!("{name}\n")),
+    }
+
+    out.push_str(".SH SYNOPSIS\n");
+    out.push_str(&format!("{}\n", synopsis(&mut cmd)));
+
+    if let Some(long) = cmd.get_long_about() {
+        out.push_str(".SH DESCRIPTION\n");
+        out.push_str(&paragraphs(&long.to_string()));
+    }
+
+    let options: Vec<&Arg> = visible_args(&cmd).collect();
+    if !options.is_empty() {
+        out.push_str(".SH OPTIONS\n");
+        for arg in options {
+            out.push_str(&option(arg));
+        }
+    }
+
+    let mut commands = String::new();
+    for sub in cmd.get_subcommands().filter(|sub| visible_command(sub)) {
+        subcommand(&mut commands, &name, sub);
+    }
+    if !commands.is_empty() {
+        out.push_str(".SH COMMANDS\n");
+        out.push_str(&commands);
+    }
+
+    if let Some(after) = cmd.get_after_help() {
+        out.push_str(&after_help(&after.to_string()));
+    }
+
+    if !version.is_empty() {
+        out.push_str(&format!(".SH VERSION\nv{version}\n"));
+    }
+    out
+}
+
+fn synopsis(cmd: &mut Command) -> String {
+    let usage = cmd.render_usage().to_string();
+    let usage = usage.trim_start_matches("Usage:").trim();
+    let (bin, rest) = usage.split_once(' ').unwrap_or((usage, ""));
+    format!("\\fB{}\\fR {}", escape(bin), escape(rest))
+        .trim_end()
+        .to_string()
+}
+
+fn subcommand(out: &mut String, parent: &str, cmd: &Command) {
+    let path = format!("{parent} {}", cmd.get_name());
+    out.push_str(&format!(".SS \"{}\"\n", escape(&path)));
+    if let Some(about) = cmd.get_long_about().or(cmd.get_about()) {
+        out.push_str(&paragraphs(&about.to_string()));
+    }
+    // Global flags and `--help` are listed once, under OPTIONS
+    for arg in visible_args(cmd)
+        .filter(|arg| !arg.is_global_set() && !matches!(arg.get_id().as_str(), "help" | "version"))
+    {
+        out.push_str(&option(arg));
+    }
+    for sub in cmd.get_subcommands().filter(|sub| visible_command(sub)) {
+        subcommand(out, &path, sub);
+    }
+}
+
+fn visible_command(cmd: &Command) -> bool {
+    !cmd.is_hide_set() && cmd.get_name() != "help"
+}
+
+fn visible_args(cmd: &Command) -> impl Iterator<Item = &Arg> {
+    cmd.get_arguments().filter(|arg| !arg.is_hide_set())
+}
+
+/// One `.TP` entry: the flag spellings with their value, then the help text.
+fn option(arg: &Arg) -> String {
+    let value = arg
+        .get_value_names()
+        .map(|names| {
+            names
+                .iter()
+                .map(|name| format!("<{name}>"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_else(|| format!("<{}>", arg.get_id()));
+    let takes_value = arg.get_action().takes_values();
+
+    let mut head = Vec::new();
+    if let Some(short) = arg.get_short() {
+        head.push(format!("\\fB\\-{short}\\fR"));
+    }
+    if let Some(long) = arg.get_long() {
+        head.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+    }
+    let mut head = head.join(", ");
+    if arg.is_positional() {
+        head = format!("\\fI{}\\fR", escape(&value));
+    } else if takes_value {
+        head.push_str(&format!(" \\fI{}\\fR", escape(&value)));
+    }
+
+    let mut body = arg
+        .get_long_help()
+        .or(arg.get_help())
+        .map(|help| help.to_string())
+        .unwrap_or_default();
+    let possible: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    if takes_value && !possible.is_empty() {
+        body.push_str(&format!(" [possible values: {}]", possible.join(", ")));
+    }
+    let defaults: Vec<String> = arg
+        .get_default_values()
+        .iter()
+        .map(|value| value.to_string_lossy().into_owned())
+        .collect();
+    if takes_value && !defaults.is_empty() {
+        body.push_str(&format!(" [default: {}]", defaults.join(", ")));
+    }
+
+    let body = body.trim();
+    if body.is_empty() {
+        format!(".TP\n{head}\n")
+    } else {
+        format!(".TP\n{head}\n{}\n", escape(body))
+    }
+}
+
+/// `after_help` text: each unindented `HEADING:` line starts a section, and
+/// the lines under it are kept as written.
+fn after_help(text: &str) -> String {
+    let mut out = String::new();
+    let mut open = false;
+    for line in text.lines() {
+        let heading = line
+            .strip_suffix(':')
+            .filter(|head| !head.starts_with(' ') && !head.is_empty())
+            .filter(|head| head.chars().all(|c| c.is_ascii_uppercase() || c == ' '));
+        if let Some(heading) = heading {
+            if open {
+                out.push_str(".fi\n");
+            }
+            out.push_str(&format!(".SH {heading}\n.nf\n"));
+            open = true;
+        } else if open || !line.trim().is_empty() {
+            if !open {
+                out.push_str(".SH NOTES\n.nf\n");
+                open = true;
+            }
+            out.push_str(&line_text(line.strip_prefix("  ").unwrap_or(line)));
+            out.push('\n');
+        }
+    }
+    if open {
+        out.push_str(".fi\n");
+    }
+    out
+}
+
+fn paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .map(|para| {
+            para.trim()
+                .lines()
+                .map(line_text)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .filter(|para| !para.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n.PP\n")
+        + "\n"
+}
+
+/// A line of text that roff must not read as a request.
+fn line_text(line: &str) -> String {
+    let line = escape(line);
+    if line.starts_with('.') || line.starts_with('\'') {
+        format!("\\&{line}")
+    } else {
+        line
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
@@ -0,0 +1,333 @@
+//! MCP (Model Context Protocol) tool definitions derived from a tool's clap command.
+//!
+//! Every tool answers `<tool> --mcp-tools` through [`crate::cli::parse`] with the
+//! list of its read-only commands: one [`McpTool`] per leaf subcommand, with a JSON
+//! Schema for its arguments. `dee mcp-serve` collects them and turns each MCP call
+//! back into a command line with [`McpTool::argv`].
+//!
+//! Read-only means the leaf's name is one of [`READ_COMMANDS`] and no part of its
+//! path is `config`, `cache` or `help`. Anything else (add, delete, set, run, ...) is left out.
+
+use std::any::TypeId;
+
+use clap::{Arg, ArgAction, Command};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// Leaf command names that only read: they may fetch, but never change local state.
+pub const READ_COMMANDS: &[&str] = &[
+    "alerts",
+    "article",
+    "ask",
+    "author",
+    "best",
+    "calc",
+    "categories",
+    "check",
+    "citations",
+    "cite",
+    "comments",
+    "contains",
+    "convert",
+    "current",
+    "deps",
+    "diff",
+    "doi",
+    "explore",
+    "fit",
+    "forecast",
+    "geocode",
+    "get",
+    "history",
+    "info",
+    "interest",
+    "issues",
+    "item",
+    "jobs",
+    "latest",
+    "links",
+    "list",
+    "list-all",
+    "markdown",
+    "me",
+    "metadata",
+    "national",
+    "new",
+    "ping",
+    "plan",
+    "prices",
+    "pricing",
+    "quote",
+    "recommend",
+    "references",
+    "related",
+    "releases",
+    "repo",
+    "reviews",
+    "route",
+    "search",
+    "show",
+    "split",
+    "status",
+    "streak",
+    "subreddit",
+    "summary",
+    "system",
+    "table",
+    "text",
+    "top",
+    "user",
+    "versions",
+    "watchlist",
+];
+
+/// Groups never exposed, whatever their leaves are called. `help` is the
+/// subcommand clap generates.
+const SKIPPED_GROUPS: &[&str] = &["config", "cache", "help"];
+
+/// Output and help flags: the server always asks for `--json`.
+const SKIPPED_ARGS: &[&str] = &["json", "quiet", "verbose", "output", "help", "version"];
+
+/// One MCP tool: a leaf subcommand of a dee tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpTool {
+    /// Subcommand path joined with `_`: `top`, `lib_list`
+    pub name: String,
+    /// Subcommand path: `["lib", "list"]`
+    pub command: Vec<String>,
+    pub description: String,
+    /// JSON Schema of the arguments object
+    pub input_schema: Value,
+    /// How each property maps to the command line
+    pub args: Vec<McpArg>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpArg {
+    /// Property name in `input_schema`: the clap id, `-` for `_`
+    pub name: String,
+    /// `--limit`, or `None` for a positional
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flag: Option<String>,
+    pub kind: ArgKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArgKind {
+    /// `--flag` when `true`
+    Switch,
+    /// `--flag <value>` or one positional value
+    Value,
+    /// `--flag <value>` repeated, or several positional values
+    List,
+}
+
+/// The read-only leaf commands of `cmd`, in definition order.
+pub fn tools(cmd: &Command) -> Vec<McpTool> {
+    let mut cmd = cmd.clone();
+    cmd.build();
+    let about = cmd.get_about().map(ToString::to_string).unwrap_or_default();
+    let mut tools = Vec::new();
+    if cmd.has_subcommands() {
+        for sub in cmd.get_subcommands() {
+            collect(sub, &mut Vec::new(), &about, &mut tools);
+        }
+    } else {
+        tools.push(tool(&cmd, Vec::new(), &about));
+    }
+    tools
+}
+
+fn collect(cmd: &Command, path: &mut Vec<String>, about: &str, tools: &mut Vec<McpTool>) {
+    let name = cmd.get_name();
+    if cmd.is_hide_set() || SKIPPED_GROUPS.contains(&name) {
+        return;
+    }
+    path.push(name.to_string());
+    if cmd.has_subcommands() {
+        for sub in cmd.get_subcommands() {
+            collect(sub, path, about, tools);
+        }
+    } else if READ_COMMANDS.contains(&name) {
+        tools.push(tool(cmd, path.clone(), about));
+    }
+    path.pop();
+}
+
+fn tool(cmd: &Command, command: Vec<String>, about: &str) -> McpTool {
+    let description = cmd
+        .get_about()
+        .map(ToString::to_string)
+        .filter(|text| !text.is_empty())
+        .unwrap_or_else(|| format!("{about}: {}", command.join(" ")));
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    let mut args = Vec::new();
+    for arg in cmd.get_arguments() {
+        let id = arg.get_id().as_str();
+        if arg.is_hide_set() || SKIPPED_ARGS.contains(&id) {
+            continue;
+        }
+        let Some(kind) = kind(arg) else {
+            continue;
+        };
+        let flag = if arg.is_positional() {
+            None
+        } else if let Some(long) = arg.get_long() {
+            Some(format!("--{long}"))
+        } else if let Some(short) = arg.get_short() {
+            Some(format!("-{short}"))
+        } else {
+            continue;
+        };
+        let name = id.replace('_', "-");
+        properties.insert(name.clone(), schema(arg, kind));
+        if arg.is_required_set() {
+            required.push(Value::String(name.clone()));
+        }
+        args.push(McpArg { name, flag, kind });
+    }
+    McpTool {
+        name: command.join("_"),
+        command,
+        description,
+        input_schema: json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        }),
+        args,
+    }
+}
+
+/// `None` for actions a caller cannot set (counts, help, version).
+fn kind(arg: &Arg) -> Option<ArgKind> {
+    match arg.get_action() {
+        ArgAction::SetTrue | ArgAction::SetFalse => Some(ArgKind::Switch),
+        ArgAction::Append => Some(ArgKind::List),
+        ArgAction::Set if arg.get_num_args().is_some_and(|n| n.max_values() > 1) => {
+            Some(ArgKind::List)
+        }
+        ArgAction::Set => Some(ArgKind::Value),
+        _ => None,
+    }
+}
+
+fn schema(arg: &Arg, kind: ArgKind) -> Value {
+    let mut value = match kind {
+        ArgKind::Switch => json!({ "type": "boolean" }),
+        ArgKind::Value => value_schema(arg),
+        ArgKind::List => json!({ "type": "array", "items": value_schema(arg) }),
+    };
+    if let Some(help) = arg.get_help() {
+        value["description"] = Value::String(help.to_string());
+    }
+    if let [default] = arg.get_default_values() {
+        if kind == ArgKind::Value {
+            let default = default.to_string_lossy();
+            value["default"] = match value["type"].as_str() {
+                Some("integer") => default.parse::<i64>().map(Value::from).ok(),
+                Some("number") => default.parse::<f64>().map(Value::from).ok(),
+                _ => None,
+            }
+            .unwrap_or_else(|| Value::String(default.to_string()));
+        }
+    }
+    value
+}
+
+/// The schema of one value, from the arg's possible values or parsed type.
+fn value_schema(arg: &Arg) -> Value {
+    let choices: Vec<Value> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| Value::String(value.get_name().to_string()))
+        .collect();
+    if !choices.is_empty() {
+        return json!({ "type": "string", "enum": choices });
+    }
+    let parsed = arg.get_value_parser().type_id();
+    let integers = [
+        TypeId::of::<u8>(),
+        TypeId::of::<u16>(),
+        TypeId::of::<u32>(),
+        TypeId::of::<u64>(),
+        TypeId::of::<usize>(),
+        TypeId::of::<i8>(),
+        TypeId::of::<i16>(),
+        TypeId::of::<i32>(),
+        TypeId::of::<i64>(),
+        TypeId::of::<isize>(),
+    ];
+    if integers.iter().any(|id| parsed == *id) {
+        json!({ "type": "integer" })
+    } else if parsed == TypeId::of::<f64>() || parsed == TypeId::of::<f32>() {
+        json!({ "type": "number" })
+    } else if parsed == TypeId::of::<bool>() {
+        json!({ "type": "boolean" })
+    } else {
+        json!({ "type": "string" })
+    }
+}
+
+impl McpTool {
+    /// The command line for a call with `arguments`, after the binary name:
+    /// subcommand path, options, `--json`, then `--` and the positionals.
+    pub fn argv(&self, arguments: &Map<String, Value>) -> Result<Vec<String>, String> {
+        if let Some(unknown) = arguments
+            .keys()
+            .find(|key| !self.args.iter().any(|arg| &arg.name == *key))
+        {
+            return Err(format!("Unknown argument `{unknown}` for {}", self.name));
+        }
+        let mut argv = self.command.clone();
+        let mut positionals = Vec::new();
+        for arg in &self.args {
+            let Some(value) = arguments.get(&arg.name).filter(|value| !value.is_null()) else {
+                continue;
+            };
+            let values = match (arg.kind, value) {
+                (ArgKind::Switch, Value::Bool(true)) => {
+                    argv.extend(arg.flag.clone());
+                    continue;
+                }
+                (ArgKind::Switch, Value::Bool(false)) => continue,
+                (ArgKind::Switch, _) => {
+                    return Err(format!("`{}` must be true or false", arg.name));
+                }
+                (ArgKind::List, Value::Array(items)) => items
+                    .iter()
+                    .map(|item| scalar(&arg.name, item))
+                    .collect::<Result<Vec<_>, _>>()?,
+                (_, value) => vec![scalar(&arg.name, value)?],
+            };
+            match &arg.flag {
+                Some(flag) => {
+                    for value in values {
+                        argv.push(flag.clone());
+                        argv.push(value);
+                    }
+                }
+                None => positionals.extend(values),
+            }
+        }
+        argv.push("--json".to_string());
+        if !positionals.is_empty() {
+            argv.push("--".to_string());
+            argv.extend(positionals);
+        }
+        Ok(argv)
+    }
+}
+
+fn scalar(name: &str, value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Number(number) => Ok(number.to_string()),
+        Value::Bool(flag) => Ok(flag.to_string()),
+        _ => Err(format!("`{name}` must be a string, number or boolean")),
+    }
+}
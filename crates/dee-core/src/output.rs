@@ -0,0 +1,98 @@
+//! The JSON envelopes from FRAMEWORK.md: `{ok,count,items}`, `{ok,item}`,
+//! `{ok,message}` and `{ok,error,code}`.
+//!
+//! Success envelopes printed after cached data stood in for the network also get
+//! `"stale":true` and `cache_age_secs` (see [`crate::cache`]).
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct OkList<T> {
+    pub ok: bool,
+    pub count: usize,
+    pub items: Vec<T>,
+}
+
+impl<T> OkList<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        Self {
+            ok: true,
+            count: items.len(),
+            items,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct OkItem<T> {
+    pub ok: bool,
+    pub item: T,
+}
+
+impl<T> OkItem<T> {
+    pub fn new(item: T) -> Self {
+        Self { ok: true, item }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct OkMessage {
+    pub ok: bool,
+    pub message: String,
+}
+
+impl OkMessage {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ErrorJson {
+    pub ok: bool,
+    pub error: String,
+    pub code: String,
+}
+
+impl ErrorJson {
+    pub fn new(error: impl Into<String>, code: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            error: error.into(),
+            code: code.into(),
+        }
+    }
+}
+
+/// One line of JSON on stdout. A value that cannot be serialized prints a
+/// `SERIALIZE` error instead, so stdout always holds a parseable envelope.
+pub fn print_json<T: Serialize + ?Sized>(value: &T) {
+    let text = serde_json::to_value(value).and_then(|mut value| {
+        mark_stale(&mut value);
+        serde_json::to_string(&value)
+    });
+    match text {
+        Ok(text) => println!("{text}"),
+        Err(_) => {
+            println!(r#"{{"ok":false,"error":"Internal serialization error","code":"SERIALIZE"}}"#)
+        }
+    }
+}
+
+/// Add the [`crate::cache::Stale`] marker to an `{ok:true,...}` envelope, for
+/// tools that print JSON some other way than [`print_json`].
+pub fn mark_stale(value: &mut serde_json::Value) {
+    let Some(stale) = crate::cache::stale() else {
+        return;
+    };
+    if let Some(envelope) = value.as_object_mut() {
+        if envelope.get("ok") == Some(&serde_json::Value::Bool(true)) {
+            envelope.insert("stale".to_string(), stale.stale.into());
+            envelope.insert("cache_age_secs".to_string(), stale.cache_age_secs.into());
+        }
+    }
+}
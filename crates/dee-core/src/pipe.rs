@@ -0,0 +1,164 @@
+//! `--stdin-json`: one tool's `--json` output as another tool's inputs, so
+//! commands chain without `jq`:
+//!
+//! ```text
+//! dee-porkbun domains list-all --json | dee-ssl check --stdin-json --field domain
+//! dee-hn top --json | dee-qr generate-batch --stdin-json --field url --out-dir codes/
+//! ```
+//!
+//! Stdin may hold an `OkList` envelope (`{"ok":true,"items":[...]}`), an
+//! `OkItem` (`{"ok":true,"item":{...}}`), a bare JSON array or object, or
+//! `--output jsonl` lines. `--field` picks a value out of each item; dots
+//! reach into nested objects (`location.city`). Items that are plain strings
+//! or numbers are used as they are. An upstream `{"ok":false,...}` is
+//! [`PipeError::Upstream`], so a failed first command fails the second.
+//!
+//! ```no_run
+//! # use dee_core::pipe::PipeArgs;
+//! # let pipe = PipeArgs::default();
+//! let domains: Vec<String> = match pipe.values("domain")? {
+//!     Some(domains) => domains,
+//!     None => vec!["example.com".to_string()],
+//! };
+//! # Ok::<(), dee_core::pipe::PipeError>(())
+//! ```
+
+use std::io::Read;
+
+use clap::Args;
+use serde_json::Value;
+
+use crate::error::ErrorCode;
+
+/// `--stdin-json` and `--field`, for `#[command(flatten)]` on a command that
+/// takes its inputs from another tool.
+#[derive(Debug, Clone, Default, Args)]
+pub struct PipeArgs {
+    /// Read the inputs from another dee tool's JSON output on stdin
+    #[arg(long)]
+    pub stdin_json: bool,
+    /// Field of each piped item to use (dots for nested fields)
+    #[arg(long, value_name = "NAME", requires = "stdin_json")]
+    pub field: Option<String>,
+}
+
+impl PipeArgs {
+    /// The piped values, reading `--field` or else `default_field` from each
+    /// item; `None` without `--stdin-json`.
+    pub fn values(&self, default_field: &str) -> Result<Option<Vec<String>>, PipeError> {
+        if !self.stdin_json {
+            return Ok(None);
+        }
+        let field = self.field.as_deref().unwrap_or(default_field);
+        read_values(std::io::stdin().lock(), field).map(Some)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PipeError {
+    #[error("Could not read stdin: {0}")]
+    Read(String),
+    #[error("Nothing on stdin; pipe another tool's --json output in")]
+    Empty,
+    #[error("Stdin is not JSON or JSON lines: {0}")]
+    NotJson(String),
+    #[error("The piped command failed ({code}): {error}")]
+    Upstream { error: String, code: String },
+    #[error("Piped item {index} has no `{field}` field")]
+    MissingField { index: usize, field: String },
+    #[error("Piped item {index}: `{field}` is not a string or number")]
+    NotScalar { index: usize, field: String },
+}
+
+impl ErrorCode for PipeError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Read(_) => "STDIN_READ_FAILED",
+            Self::Upstream { .. } => "UPSTREAM_FAILED",
+            Self::Empty | Self::NotJson(_) | Self::MissingField { .. } | Self::NotScalar { .. } => {
+                "INVALID_INPUT"
+            }
+        }
+    }
+}
+
+/// The `field` values of the items in `reader`, in order.
+pub fn read_values(mut reader: impl Read, field: &str) -> Result<Vec<String>, PipeError> {
+    let mut text = String::new();
+    reader
+        .read_to_string(&mut text)
+        .map_err(|err| PipeError::Read(err.to_string()))?;
+    parse_values(&text, field)
+}
+
+/// The `field` values of the items in `text`, in order.
+pub fn parse_values(text: &str, field: &str) -> Result<Vec<String>, PipeError> {
+    if text.trim().is_empty() {
+        return Err(PipeError::Empty);
+    }
+    items(text)?
+        .iter()
+        .enumerate()
+        .map(|(index, item)| value_of(item, field, index))
+        .collect()
+}
+
+/// The items of an envelope, array, object, or JSON lines.
+fn items(text: &str) -> Result<Vec<Value>, PipeError> {
+    let value = match serde_json::from_str::<Value>(text) {
+        Ok(value) => value,
+        Err(whole) => {
+            return text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect::<Result<_, _>>()
+                .map_err(|_| PipeError::NotJson(whole.to_string()));
+        }
+    };
+    match value {
+        Value::Array(items) => Ok(items),
+        Value::Object(mut object) => {
+            if object.get("ok") == Some(&Value::Bool(false)) {
+                let text = |key: &str| {
+                    object
+                        .get(key)
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                return Err(PipeError::Upstream {
+                    error: text("error"),
+                    code: text("code"),
+                });
+            }
+            if let Some(Value::Array(items)) = object.remove("items") {
+                return Ok(items);
+            }
+            Ok(vec![object.remove("item").unwrap_or(Value::Object(object))])
+        }
+        scalar => Ok(vec![scalar]),
+    }
+}
+
+fn value_of(item: &Value, field: &str, index: usize) -> Result<String, PipeError> {
+    let found = if item.is_object() {
+        field
+            .split('.')
+            .try_fold(item, |value, key| value.get(key))
+            .ok_or_else(|| PipeError::MissingField {
+                index,
+                field: field.to_string(),
+            })?
+    } else {
+        item
+    };
+    match found {
+        Value::String(text) => Ok(text.clone()),
+        Value::Number(number) => Ok(number.to_string()),
+        _ => Err(PipeError::NotScalar {
+            index,
+            field: field.to_string(),
+        }),
+    }
+}
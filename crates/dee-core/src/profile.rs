@@ -0,0 +1,143 @@
+//! Named profiles in `~/.config/dee/profiles.toml`: one switch for the
+//! credentials and defaults of every tool.
+//!
+//! ```toml
+//! [work.dee-porkbun]
+//! api_key = "pk1_work"
+//! secret_key = "sk1_work"
+//!
+//! [personal.dee-porkbun]
+//! api_key = "pk1_home"
+//! secret_key = "sk1_home"
+//!
+//! [personal.dee-rates]
+//! base = "EUR"
+//! ```
+//!
+//! `--profile work` (on any tool parsed with [`crate::cli::parse`]) or
+//! `DEE_PROFILE=work` picks one. [`crate::Config::load`] then lays the profile's
+//! table for the tool over its `config.toml`; `DEE_<TOOL>_*` overrides still win.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::config::{config_dir, read_table, ConfigError};
+
+/// Env var naming the profile when `--profile` is not given
+pub const PROFILE_ENV: &str = "DEE_PROFILE";
+
+/// Env var pointing at another profiles file, mostly for tests
+pub const PROFILES_FILE_ENV: &str = "DEE_PROFILES_FILE";
+
+/// The `--profile` argument id
+pub const PROFILE_ARG: &str = "profile";
+
+static SELECTED: OnceLock<String> = OnceLock::new();
+
+/// Use profile `name` for the rest of the process, as `--profile` does. The
+/// first call wins.
+pub fn select(name: impl Into<String>) {
+    let _ = SELECTED.set(name.into());
+}
+
+/// The profile in use: `--profile`, else `DEE_PROFILE`, else none.
+pub fn active() -> Option<String> {
+    SELECTED.get().cloned().or_else(|| {
+        std::env::var(PROFILE_ENV)
+            .ok()
+            .filter(|name| !name.is_empty())
+    })
+}
+
+/// `<config dir>/dee/profiles.toml`, or `$DEE_PROFILES_FILE`.
+pub fn path() -> PathBuf {
+    match std::env::var_os(PROFILES_FILE_ENV) {
+        Some(path) => PathBuf::from(path),
+        None => config_dir("dee").join("profiles.toml"),
+    }
+}
+
+/// Every profile with a table for `tool`, by name, with its keys.
+pub fn for_tool(tool: &str) -> Result<Vec<(String, toml::Table)>, ConfigError> {
+    let path = path();
+    let mut found = Vec::new();
+    for (name, profile) in read_table(&path)? {
+        match profile.get(tool) {
+            Some(toml::Value::Table(keys)) => found.push((name, keys.clone())),
+            Some(_) => {
+                return Err(ConfigError::Invalid {
+                    path,
+                    message: format!("`{name}.{tool}` must be a table"),
+                })
+            }
+            None => {}
+        }
+    }
+    Ok(found)
+}
+
+/// Set `key` in profile `name`'s table for `tool`, creating the file, the
+/// profile and the table as needed. The file is rewritten, so comments in it
+/// are lost.
+pub fn set_key(name: &str, tool: &str, key: &str, value: toml::Value) -> Result<(), ConfigError> {
+    let path = path();
+    let mut profiles = read_table(&path)?;
+    let invalid = |message: String| ConfigError::Invalid {
+        path: path.clone(),
+        message,
+    };
+    let profile = match profiles
+        .entry(name)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+    {
+        toml::Value::Table(profile) => profile,
+        _ => return Err(invalid(format!("profile `{name}` must be a table"))),
+    };
+    let keys = match profile
+        .entry(tool)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+    {
+        toml::Value::Table(keys) => keys,
+        _ => return Err(invalid(format!("`{name}.{tool}` must be a table"))),
+    };
+    keys.insert(key.to_string(), value);
+
+    let write_err = |message: String| ConfigError::Write {
+        path: path.clone(),
+        message,
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| write_err(err.to_string()))?;
+    }
+    let content = toml::to_string_pretty(&profiles).map_err(|err| write_err(err.to_string()))?;
+    std::fs::write(&path, content).map_err(|err| write_err(err.to_string()))
+}
+
+/// The active profile's keys for `tool`: `None` without a profile or when the
+/// profile leaves the tool out. A profile missing from the file is an error,
+/// so a typo never silently falls back to the default credentials.
+pub(crate) fn table(tool: &str) -> Result<Option<toml::Table>, ConfigError> {
+    let Some(name) = active() else {
+        return Ok(None);
+    };
+    let path = path();
+    let mut profiles = read_table(&path)?;
+    let profile = match profiles.remove(&name) {
+        Some(toml::Value::Table(profile)) => profile,
+        Some(_) => {
+            return Err(ConfigError::Invalid {
+                path,
+                message: format!("profile `{name}` must be a table"),
+            })
+        }
+        None => return Err(ConfigError::UnknownProfile { name, path }),
+    };
+    match profile.get(tool) {
+        None => Ok(None),
+        Some(toml::Value::Table(keys)) => Ok(Some(keys.clone())),
+        Some(_) => Err(ConfigError::Invalid {
+            path,
+            message: format!("`{name}.{tool}` must be a table"),
+        }),
+    }
+}
@@ -0,0 +1,261 @@
+//! Request budgets per API host, shared by every tool and every run: a token
+//! bucket per host, kept under `<cache dir>/dee/ratelimit/`, so a script of
+//! many `dee-*` calls stays under the provider's limit.
+//!
+//! Hosts with a published limit have a budget built in ([`DEFAULT_BUDGETS`]).
+//! `~/.config/dee/ratelimit.toml` changes them or adds more; `requests = 0`
+//! turns a host's limit off:
+//!
+//! ```toml
+//! ["hn.algolia.com"]
+//! requests = 100
+//! per_secs = 60
+//!
+//! ["api.porkbun.com"]
+//! requests = 0
+//! ```
+//!
+//! A budget of `requests` per `per_secs` allows a burst of `requests`, then
+//! one request every `per_secs / requests` seconds. Call [`wait`] before each
+//! request; async tools sleep for [`reserve`]'s answer instead.
+//!
+//! ```no_run
+//! let waited = dee_core::ratelimit::wait("https://export.arxiv.org/api/query?id_list=2101.00001")?;
+//! # Ok::<(), dee_core::ConfigError>(())
+//! ```
+//!
+//! The bucket files are locked while they are read and written, so runs in
+//! parallel queue up too. When they cannot be written the budget holds for
+//! this run only.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{config_dir, read_table, ConfigError};
+
+/// Env var pointing at another budgets file, mostly for tests
+pub const LIMITS_FILE_ENV: &str = "DEE_RATELIMIT_FILE";
+
+/// Env var pointing at another directory for the buckets, mostly for tests
+pub const STATE_DIR_ENV: &str = "DEE_RATELIMIT_DIR";
+
+/// Budgets for hosts that publish one. arXiv asks for one request every 3
+/// seconds; Semantic Scholar allows about one a second; Algolia's HN API
+/// 10,000 an hour; Yelp Fusion 5,000 a day; ip-api's free endpoint 45 a
+/// minute; crates.io's crawler policy one a second. Porkbun publishes none, so
+/// its budget is a conservative one a second.
+pub const DEFAULT_BUDGETS: &[(&str, Budget)] = &[
+    ("export.arxiv.org", Budget::new(1, 3)),
+    ("api.semanticscholar.org", Budget::new(1, 1)),
+    ("hn.algolia.com", Budget::new(10_000, 3600)),
+    ("api.yelp.com", Budget::new(5000, 86_400)),
+    ("api.porkbun.com", Budget::new(1, 1)),
+    ("ip-api.com", Budget::new(45, 60)),
+    ("crates.io", Budget::new(1, 1)),
+];
+
+/// `requests` per `per_secs` seconds for one host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Budget {
+    /// `0` means no limit
+    pub requests: u32,
+    #[serde(default = "one")]
+    pub per_secs: u64,
+}
+
+fn one() -> u64 {
+    1
+}
+
+impl Budget {
+    pub const fn new(requests: u32, per_secs: u64) -> Self {
+        Self { requests, per_secs }
+    }
+
+    /// Tokens added per second
+    fn rate(self) -> f64 {
+        f64::from(self.requests) / self.per_secs.max(1) as f64
+    }
+}
+
+/// A host's bucket as stored between runs.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+struct Bucket {
+    /// Below zero when requests are queued behind the last one
+    tokens: f64,
+    /// When `tokens` was computed, in ms since the epoch
+    at_ms: u64,
+}
+
+/// Buckets of this run, for when the files cannot be used
+static IN_RUN: Mutex<Option<HashMap<String, Bucket>>> = Mutex::new(None);
+
+/// The budgets in effect and where the buckets live.
+#[derive(Debug, Clone)]
+pub struct Limiter {
+    budgets: BTreeMap<String, Budget>,
+    dir: PathBuf,
+}
+
+impl Limiter {
+    /// The built-in budgets with `ratelimit.toml` laid over them.
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = path();
+        let mut budgets: BTreeMap<String, Budget> = DEFAULT_BUDGETS
+            .iter()
+            .map(|(host, budget)| (host.to_string(), *budget))
+            .collect();
+        for (host, value) in read_table(&path)? {
+            let budget = value
+                .try_into::<Budget>()
+                .map_err(|err| ConfigError::Invalid {
+                    path: path.clone(),
+                    message: format!("`{host}`: {}", err.message()),
+                })?;
+            budgets.insert(host.to_ascii_lowercase(), budget);
+        }
+        Ok(Self {
+            budgets,
+            dir: state_dir(),
+        })
+    }
+
+    /// The budget for `host`, if it has a limit.
+    pub fn budget(&self, host: &str) -> Option<Budget> {
+        self.budgets
+            .get(&host.to_ascii_lowercase())
+            .copied()
+            .filter(|budget| budget.requests > 0)
+    }
+
+    /// Take a request from `target`'s budget (a URL or a bare host) and return
+    /// how long to wait before sending it: zero when there is budget left.
+    pub fn reserve(&self, target: &str) -> Duration {
+        let host = host_of(target);
+        let Some(budget) = self.budget(&host) else {
+            return Duration::ZERO;
+        };
+        let now = now_ms();
+        let path = self.dir.join(&host);
+        match reserve_in_file(&path, budget, now) {
+            Ok(wait) => wait,
+            Err(_) => {
+                let mut buckets = IN_RUN.lock().unwrap_or_else(|err| err.into_inner());
+                let bucket = buckets
+                    .get_or_insert_with(HashMap::new)
+                    .entry(host)
+                    .or_insert_with(|| full(budget, now));
+                take(bucket, budget, now)
+            }
+        }
+    }
+}
+
+/// [`Limiter::reserve`] with the budgets from [`Limiter::load`].
+pub fn reserve(target: &str) -> Result<Duration, ConfigError> {
+    Ok(Limiter::load()?.reserve(target))
+}
+
+/// [`reserve`], then sleep that long. Returns the time slept.
+pub fn wait(target: &str) -> Result<Duration, ConfigError> {
+    let wait = reserve(target)?;
+    if !wait.is_zero() {
+        std::thread::sleep(wait);
+    }
+    Ok(wait)
+}
+
+/// `<config dir>/dee/ratelimit.toml`, or `$DEE_RATELIMIT_FILE`.
+pub fn path() -> PathBuf {
+    match std::env::var_os(LIMITS_FILE_ENV) {
+        Some(path) => PathBuf::from(path),
+        None => config_dir("dee").join("ratelimit.toml"),
+    }
+}
+
+fn state_dir() -> PathBuf {
+    match std::env::var_os(STATE_DIR_ENV) {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::cache_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("dee")
+            .join("ratelimit"),
+    }
+}
+
+/// The lowercase host of a URL, or `target` itself when it is a bare host.
+pub fn host_of(target: &str) -> String {
+    let rest = target.split_once("://").map_or(target, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        // IPv6 literal: keep the brackets off and the colons in
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    host.to_ascii_lowercase()
+}
+
+fn reserve_in_file(path: &Path, budget: Budget, now: u64) -> std::io::Result<Duration> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    file.lock()?;
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    // An empty or damaged file starts a full bucket
+    let mut bucket = serde_json::from_str(&text).unwrap_or(full(budget, now));
+    let wait = take(&mut bucket, budget, now);
+    write_bucket(&mut file, &bucket)?;
+    file.unlock()?;
+    Ok(wait)
+}
+
+fn write_bucket(file: &mut File, bucket: &Bucket) -> std::io::Result<()> {
+    let text = serde_json::to_string(bucket).map_err(std::io::Error::other)?;
+    file.set_len(0)?;
+    file.rewind()?;
+    file.write_all(text.as_bytes())
+}
+
+fn full(budget: Budget, now: u64) -> Bucket {
+    Bucket {
+        tokens: f64::from(budget.requests),
+        at_ms: now,
+    }
+}
+
+/// Refill `bucket` up to `now`, take one token, and return how long until
+/// that token was due. A bucket from a bigger budget is cut down to this one.
+fn take(bucket: &mut Bucket, budget: Budget, now: u64) -> Duration {
+    let capacity = f64::from(budget.requests);
+    let rate = budget.rate();
+    let elapsed = now.saturating_sub(bucket.at_ms) as f64 / 1000.0;
+    bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity) - 1.0;
+    bucket.at_ms = now.max(bucket.at_ms);
+    if bucket.tokens >= 0.0 {
+        Duration::ZERO
+    } else {
+        Duration::from_secs_f64(-bucket.tokens / rate)
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
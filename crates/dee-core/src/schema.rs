@@ -0,0 +1,173 @@
+//! JSON Schemas of what each command prints, behind `<tool> <command> --schema`,
+//! so agents can validate output instead of guessing its shape.
+//!
+//! A tool lists its commands and their payload types once; the schemas come from
+//! the same serde types that print the output:
+//!
+//! ```no_run
+//! # #[derive(serde::Serialize, schemars::JsonSchema)] struct Story { id: u64 }
+//! use dee_core::schema::{self, Schemas};
+//!
+//! fn schemas() -> Schemas {
+//!     Schemas::new()
+//!         .list::<Story>("top")
+//!         .item::<Story>("item")
+//!         .message("config set")
+//!         .cache()
+//! }
+//!
+//! schema::exit_on_schema(schemas); // before dee_core::cli::parse
+//! ```
+//!
+//! `dee-hn item --schema` then prints `{ok,item:{command,success,error}}` and
+//! `dee-hn --schema` every command as `{ok,count,items}`. Both always print JSON.
+
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, Schema};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::cache::{CachePath, Cleared};
+use crate::output::{print_json, ErrorJson, OkItem, OkList, OkMessage};
+
+/// Flag that prints the schemas instead of running the command.
+pub const SCHEMA_FLAG: &str = "--schema";
+
+/// The output of one command: `success` on exit `0`, `error` otherwise.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandSchema {
+    /// Subcommand path as typed: `top`, `config set`
+    pub command: String,
+    pub success: Value,
+    pub error: Value,
+}
+
+/// Every command of a tool with its output schema.
+#[derive(Debug, Default)]
+pub struct Schemas {
+    commands: Vec<CommandSchema>,
+}
+
+impl Schemas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `command` prints `{ok,count,items}` of `T`.
+    pub fn list<T: JsonSchema>(self, command: &str) -> Self {
+        self.custom::<OkList<T>>(command)
+    }
+
+    /// `command` prints `{ok,item}` with a `T`.
+    pub fn item<T: JsonSchema>(self, command: &str) -> Self {
+        self.custom::<OkItem<T>>(command)
+    }
+
+    /// `command` prints `{ok,message}`.
+    pub fn message(self, command: &str) -> Self {
+        self.custom::<OkMessage>(command)
+    }
+
+    /// `command` prints `T` as is, for envelopes with extra fields.
+    pub fn custom<T: JsonSchema>(mut self, command: &str) -> Self {
+        self.commands.push(CommandSchema {
+            command: command.to_string(),
+            success: with_stale(schema_of::<T>()),
+            error: schema_of::<ErrorJson>().to_value(),
+        });
+        self
+    }
+
+    /// `cache clear` and `cache path`, for tools with [`crate::cache::CacheCommand`].
+    pub fn cache(self) -> Self {
+        self.item::<Cleared>("cache clear")
+            .item::<CachePath>("cache path")
+    }
+
+    /// The schema of `command`, e.g. `"config set"`.
+    pub fn get(&self, command: &str) -> Option<&CommandSchema> {
+        self.commands.iter().find(|entry| entry.command == command)
+    }
+
+    pub fn commands(&self) -> &[CommandSchema] {
+        &self.commands
+    }
+
+    /// The command named by `args` (the command line without the program),
+    /// skipping flags, their values and positionals: the longest run of words
+    /// that is a command, else the tool's default command, registered as `""`.
+    pub fn find(&self, args: &[String]) -> Option<&CommandSchema> {
+        let words: Vec<&str> = args
+            .iter()
+            .map(String::as_str)
+            .filter(|arg| !arg.starts_with('-'))
+            .collect();
+        (0..words.len())
+            .find_map(|start| {
+                (start + 1..=words.len())
+                    .rev()
+                    .find_map(|end| self.get(&words[start..end].join(" ")))
+            })
+            .or_else(|| self.get(""))
+    }
+}
+
+/// Print the schemas and exit when `--schema` is on the command line: one
+/// command's as `{ok,item}`, or every command's as `{ok,count,items}` when the
+/// line names none. A command without a schema is a `NOT_FOUND` error.
+pub fn exit_on_schema(schemas: impl FnOnce() -> Schemas) {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.iter().any(|arg| arg == SCHEMA_FLAG) {
+        return;
+    }
+    let schemas = schemas();
+    let commands: Vec<&String> = args.iter().filter(|arg| !arg.starts_with('-')).collect();
+    if commands.is_empty() {
+        print_json(&OkList::new(schemas.commands().to_vec()));
+        std::process::exit(crate::exit::OK);
+    }
+    match schemas.find(&args) {
+        Some(entry) => {
+            print_json(&OkItem::new(entry));
+            std::process::exit(crate::exit::OK);
+        }
+        None => {
+            let message = format!(
+                "No schema for `{}`. Run with only {SCHEMA_FLAG} for every command",
+                commands
+                    .iter()
+                    .map(|word| word.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            );
+            print_json(&ErrorJson::new(message, "NOT_FOUND"));
+            std::process::exit(crate::exit::NOT_FOUND);
+        }
+    }
+}
+
+/// As serialized: fields skipped when empty are optional.
+fn schema_of<T: JsonSchema>() -> Schema {
+    SchemaSettings::default()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<T>()
+}
+
+/// Success envelopes may carry the cache's `stale` marker (see [`crate::output`]).
+fn with_stale(schema: Schema) -> Value {
+    let mut value = schema.to_value();
+    if let Some(properties) = value.get_mut("properties").and_then(Value::as_object_mut) {
+        if properties.contains_key("ok") {
+            properties.insert(
+                "stale".to_string(),
+                json!({"type": "boolean", "description": "Cached data stood in for the network"}),
+            );
+            properties.insert(
+                "cache_age_secs".to_string(),
+                json!({"type": "integer", "minimum": 0, "description": "Age of that data"}),
+            );
+        }
+    }
+    value
+}
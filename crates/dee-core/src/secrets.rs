@@ -0,0 +1,221 @@
+//! Credentials in the OS keychain instead of the config file.
+//!
+//! A tool marks its credential keys with [`crate::Config::secret`]; the user
+//! opts in with `secrets_backend = "keyring"` in that tool's config. Entries
+//! are stored under service `<tool>` and account `<key>`, through the
+//! platform's own CLI: `security` on macOS, `secret-tool` (libsecret) elsewhere
+//! on Unix. Windows has no keyring backend yet.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::str::FromStr;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ErrorCode;
+
+/// Config key selecting where a tool's secrets live.
+pub const BACKEND_KEY: &str = "secrets_backend";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretsBackend {
+    /// In the config file, as written
+    #[default]
+    Plaintext,
+    /// In the OS keychain; the config file keeps everything else
+    Keyring,
+}
+
+impl SecretsBackend {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Plaintext => "plaintext",
+            Self::Keyring => "keyring",
+        }
+    }
+}
+
+impl FromStr for SecretsBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "plaintext" => Ok(Self::Plaintext),
+            "keyring" => Ok(Self::Keyring),
+            other => Err(format!(
+                "unknown secrets_backend `{other}`; expected plaintext|keyring"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecretsError {
+    #[error("OS keyring is not available: {0}. Set secrets_backend to plaintext")]
+    Unavailable(String),
+    #[error("Keyring {action} failed for {service}/{account}: {message}")]
+    Failed {
+        action: &'static str,
+        service: String,
+        account: String,
+        message: String,
+    },
+}
+
+impl ErrorCode for SecretsError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Unavailable(_) => "KEYRING_UNAVAILABLE",
+            Self::Failed { .. } => "KEYRING_FAILED",
+        }
+    }
+}
+
+/// The stored secret, or `None` when there is no entry.
+pub fn get(service: &str, account: &str) -> Result<Option<String>, SecretsError> {
+    let output = backend::lookup(service, account)?;
+    if output.status.success() {
+        let value = String::from_utf8_lossy(&output.stdout)
+            .trim_end_matches(['\n', '\r'])
+            .to_string();
+        return Ok((!value.is_empty()).then_some(value));
+    }
+    if backend::is_missing(&output) {
+        return Ok(None);
+    }
+    Err(failed("lookup", service, account, &output))
+}
+
+/// Store `value`, replacing any existing entry.
+pub fn set(service: &str, account: &str, value: &str) -> Result<(), SecretsError> {
+    let output = backend::store(service, account, value)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(failed("store", service, account, &output))
+    }
+}
+
+fn failed(action: &'static str, service: &str, account: &str, output: &Output) -> SecretsError {
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    SecretsError::Failed {
+        action,
+        service: service.to_string(),
+        account: account.to_string(),
+        message: if stderr.is_empty() {
+            format!("exited with {}", output.status)
+        } else {
+            stderr
+        },
+    }
+}
+
+/// Run `program`, feeding `stdin` when given. A program that cannot be started
+/// means there is no keyring to talk to.
+#[cfg_attr(windows, allow(dead_code))]
+fn run(program: &str, args: &[&str], stdin: Option<&str>) -> Result<Output, SecretsError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| SecretsError::Unavailable(format!("could not run `{program}`: {err}")))?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        // The program may exit before reading; its status reports that
+        let _ = pipe.write_all(input.as_bytes());
+    }
+    child
+        .wait_with_output()
+        .map_err(|err| SecretsError::Unavailable(format!("`{program}` failed: {err}")))
+}
+
+#[cfg(target_os = "macos")]
+mod backend {
+    use super::*;
+
+    pub fn lookup(service: &str, account: &str) -> Result<Output, SecretsError> {
+        run(
+            "security",
+            &["find-generic-password", "-s", service, "-a", account, "-w"],
+            None,
+        )
+    }
+
+    // `security` only takes the password as an argument
+    pub fn store(service: &str, account: &str, value: &str) -> Result<Output, SecretsError> {
+        run(
+            "security",
+            &[
+                "add-generic-password",
+                "-U",
+                "-s",
+                service,
+                "-a",
+                account,
+                "-w",
+                value,
+            ],
+            None,
+        )
+    }
+
+    /// `errSecItemNotFound`
+    pub fn is_missing(output: &Output) -> bool {
+        output.status.code() == Some(44)
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod backend {
+    use super::*;
+
+    pub fn lookup(service: &str, account: &str) -> Result<Output, SecretsError> {
+        run(
+            "secret-tool",
+            &["lookup", "service", service, "account", account],
+            None,
+        )
+    }
+
+    pub fn store(service: &str, account: &str, value: &str) -> Result<Output, SecretsError> {
+        let label = format!("{service} {account}");
+        run(
+            "secret-tool",
+            &[
+                "store", "--label", &label, "service", service, "account", account,
+            ],
+            Some(value),
+        )
+    }
+
+    /// `secret-tool lookup` exits 1 without a message when nothing matches.
+    pub fn is_missing(output: &Output) -> bool {
+        output.status.code() == Some(1) && output.stderr.is_empty()
+    }
+}
+
+#[cfg(windows)]
+mod backend {
+    use super::*;
+
+    const UNSUPPORTED: &str = "no keyring backend on Windows";
+
+    pub fn lookup(_service: &str, _account: &str) -> Result<Output, SecretsError> {
+        Err(SecretsError::Unavailable(UNSUPPORTED.to_string()))
+    }
+
+    pub fn store(_service: &str, _account: &str, _value: &str) -> Result<Output, SecretsError> {
+        Err(SecretsError::Unavailable(UNSUPPORTED.to_string()))
+    }
+
+    pub fn is_missing(_output: &Output) -> bool {
+        false
+    }
+}
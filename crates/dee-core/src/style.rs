@@ -0,0 +1,92 @@
+//! Colour for human output, the same way in every tool.
+//!
+//! Styles apply only when stdout is a terminal: never in a pipe or a file, never
+//! under `NO_COLOR` (any non-empty value) or `TERM=dumb`. `CLICOLOR_FORCE` turns
+//! them on regardless. JSON and `--quiet` output are never styled; keep these
+//! calls on the human path.
+//!
+//! ```
+//! use dee_core::style;
+//! println!("{}", style::header("Certificate"));
+//! println!("  expires {}", style::expiry("2026-01-01 (12 days)", 12));
+//! println!("  {}", style::dim("issuer: Let's Encrypt"));
+//! ```
+
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+use owo_colors::OwoColorize;
+pub use owo_colors::Style;
+
+/// Days left at or below which [`expiry`] is red
+pub const EXPIRY_CRITICAL_DAYS: i64 = 7;
+/// Days left at or below which [`expiry`] is yellow
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Whether this process styles its output.
+pub fn enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        if !var("NO_COLOR").is_empty() {
+            false
+        } else if !matches!(var("CLICOLOR_FORCE").as_str(), "" | "0") {
+            true
+        } else {
+            var("TERM") != "dumb" && std::io::stdout().is_terminal()
+        }
+    })
+}
+
+/// `text` in `style` when styling is on, else as is.
+pub fn paint(text: impl Display, style: Style) -> String {
+    if enabled() {
+        text.style(style).to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Titles and section headings
+pub fn header(text: impl Display) -> String {
+    paint(text, Style::new().bold())
+}
+
+/// Secondary details: ids, dates, URLs, counts
+pub fn dim(text: impl Display) -> String {
+    paint(text, Style::new().dimmed())
+}
+
+/// A healthy status: valid, installed, up
+pub fn good(text: impl Display) -> String {
+    paint(text, Style::new().green())
+}
+
+/// Needs attention soon
+pub fn warn(text: impl Display) -> String {
+    paint(text, Style::new().yellow())
+}
+
+/// Broken, expired, failed
+pub fn bad(text: impl Display) -> String {
+    paint(text, Style::new().red())
+}
+
+/// `text` coloured by how soon something expires: red once `days_left` is
+/// [`EXPIRY_CRITICAL_DAYS`] or less (or negative), yellow within
+/// [`EXPIRY_WARNING_DAYS`], green beyond.
+pub fn expiry(text: impl Display, days_left: i64) -> String {
+    expiry_within(text, days_left, EXPIRY_WARNING_DAYS)
+}
+
+/// [`expiry`] with the tool's own warning window, e.g. `--warn-days`.
+pub fn expiry_within(text: impl Display, days_left: i64, warning_days: i64) -> String {
+    if days_left <= EXPIRY_CRITICAL_DAYS {
+        bad(text)
+    } else if days_left <= warning_days {
+        warn(text)
+    } else {
+        good(text)
+    }
+}
@@ -0,0 +1,82 @@
+use dee_core::cache::{Cache, CacheArgs};
+use dee_core::format::render_list;
+use dee_core::{ErrorCode, Format};
+
+#[test]
+fn serves_fresh_entries_and_keeps_stale_ones() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::in_dir(dir.path().join("dee-demo"));
+    assert!(cache.get::<Vec<u64>>("https://example.com/a").is_none());
+
+    cache.put("https://example.com/a", &vec![1_u64, 2, 3]);
+    let hit = cache.get::<Vec<u64>>("https://example.com/a").unwrap();
+    assert_eq!(hit.value, [1, 2, 3]);
+    assert!(hit.age_secs < 5);
+    assert!(cache.get::<Vec<u64>>("https://example.com/b").is_none());
+
+    let expired = cache.clone().ttl_secs(0);
+    assert!(expired.get::<Vec<u64>>("https://example.com/a").is_none());
+    assert_eq!(
+        expired
+            .get_stale::<Vec<u64>>("https://example.com/a")
+            .unwrap()
+            .value,
+        [1, 2, 3]
+    );
+
+    cache.put_at("https://example.com/old", &"old", 0);
+    assert!(cache.get::<String>("https://example.com/old").is_none());
+    let hit = cache
+        .get_stale::<String>("https://example.com/old")
+        .unwrap();
+    assert_eq!(hit.fetched_at, 0);
+    assert!(hit.age_secs > 1_000_000);
+}
+
+#[test]
+fn size_cap_drops_the_oldest_and_clear_empties() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::in_dir(dir.path()).max_bytes(300);
+    let body = "x".repeat(100);
+    for n in 0..5 {
+        cache.put(&format!("key-{n}"), &body);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    assert!(cache.get::<String>("key-0").is_none());
+    assert!(cache.get::<String>("key-4").is_some());
+
+    let cleared = cache.clear().unwrap();
+    assert!(cleared.removed >= 1);
+    assert!(cache.get::<String>("key-4").is_none());
+    assert_eq!(cache.clear().unwrap().removed, 0);
+}
+
+#[test]
+fn offline_serves_any_age_and_marks_output_stale() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::in_dir(dir.path()).ttl_secs(0);
+    let offline = CacheArgs {
+        no_cache: false,
+        offline: true,
+    };
+    cache.put("https://example.com/a", &"cached");
+    assert!(cache
+        .lookup::<String>("https://example.com/a", &CacheArgs::default())
+        .unwrap()
+        .is_none());
+
+    let hit = cache
+        .lookup::<String>("https://example.com/a", &offline)
+        .unwrap()
+        .unwrap();
+    assert_eq!(hit.value, "cached");
+    let err = cache
+        .lookup::<String>("https://example.com/b", &offline)
+        .unwrap_err();
+    assert_eq!(err.code(), "OFFLINE_MISS");
+
+    let json = render_list(&["a"], Format::Json).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed["stale"], true);
+    assert!(parsed["cache_age_secs"].is_u64());
+}
@@ -0,0 +1,91 @@
+use dee_core::{Config, ErrorCode};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+struct AppConfig {
+    api_key: Option<String>,
+    #[serde(default)]
+    limit: u32,
+}
+
+// One test, since the config location comes from the process environment
+#[test]
+fn load_save_and_env_overrides() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested").join("config.toml");
+    std::env::set_var("DEE_CORE_TEST_CONFIG", &path);
+    let config = Config::<AppConfig>::new("dee-core-test").env_override("api_key");
+    assert_eq!(config.path(), path);
+
+    // Missing file: the default
+    assert_eq!(config.load().unwrap(), AppConfig::default());
+
+    let saved = AppConfig {
+        api_key: Some("from-file".to_string()),
+        limit: 5,
+    };
+    config.save(&saved).unwrap();
+    assert_eq!(config.load().unwrap(), saved);
+
+    std::env::set_var("DEE_CORE_TEST_API_KEY", "from-env");
+    assert_eq!(config.load().unwrap().api_key.as_deref(), Some("from-env"));
+    // The override is not part of the file
+    assert_eq!(config.load_file().unwrap(), saved);
+    std::env::remove_var("DEE_CORE_TEST_API_KEY");
+
+    std::fs::write(&path, "limit = \"many\"").unwrap();
+    let err = config.load().unwrap_err();
+    assert_eq!(err.code(), "CONFIG_INVALID");
+    assert!(err.to_string().contains("config.toml"), "{err}");
+}
+
+#[test]
+fn unknown_secrets_backend_is_invalid() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "secrets_backend = \"vault\"").unwrap();
+    std::env::set_var("DEE_CORE_SECRETS_TEST_CONFIG", &path);
+    let config = Config::<AppConfig>::new("dee-core-secrets-test").secret("api_key");
+
+    let err = config.load().unwrap_err();
+    assert_eq!(err.code(), "CONFIG_INVALID");
+    assert!(err.to_string().contains("plaintext|keyring"), "{err}");
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct TypedConfig {
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(default)]
+    limit: Option<u32>,
+    #[serde(default)]
+    sandbox: bool,
+    #[serde(default)]
+    favorites: Vec<String>,
+}
+
+#[test]
+fn env_overrides_take_the_key_type() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("DEE_CORE_TYPED_TEST_CONFIG", dir.path().join("config.toml"));
+    std::env::set_var("DEE_CORE_TYPED_TEST_TOKEN", "12345");
+    std::env::set_var("DEE_CORE_TYPED_TEST_LIMIT", "25");
+    std::env::set_var("DEE_CORE_TYPED_TEST_SANDBOX", "true");
+    std::env::set_var("DEE_CORE_TYPED_TEST_FAVORITES", "EUR, GBP");
+    let config = Config::<TypedConfig>::new("dee-core-typed-test").env_overrides(&[
+        "token",
+        "limit",
+        "sandbox",
+        "favorites",
+    ]);
+
+    let loaded = config.load().unwrap();
+    assert_eq!(loaded.token.as_deref(), Some("12345"));
+    assert_eq!(loaded.limit, Some(25));
+    assert!(loaded.sandbox);
+    assert_eq!(loaded.favorites, ["EUR", "GBP"]);
+
+    std::env::set_var("DEE_CORE_TYPED_TEST_LIMIT", "lots");
+    let err = config.load().unwrap_err();
+    assert_eq!(err.code(), "CONFIG_INVALID");
+}
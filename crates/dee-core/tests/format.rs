@@ -0,0 +1,117 @@
+use dee_core::format::{render_columns, render_list, FormatError};
+use dee_core::ErrorCode;
+use dee_core::Format;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Story {
+    title: String,
+    score: u32,
+    tags: Vec<&'static str>,
+    url: Option<String>,
+}
+
+fn stories() -> Vec<Story> {
+    vec![
+        Story {
+            title: "Rust 2.0".to_string(),
+            score: 120,
+            tags: vec!["rust"],
+            url: Some("https://example.com".to_string()),
+        },
+        Story {
+            title: "Say \"hi\", world".to_string(),
+            score: 7,
+            tags: vec![],
+            url: None,
+        },
+    ]
+}
+
+#[test]
+fn every_format_renders_the_same_items() {
+    let items = stories();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&render_list(&items, Format::Json).unwrap()).unwrap();
+    assert_eq!(json["ok"], true);
+    assert_eq!(json["count"], 2);
+    assert_eq!(json["items"][1]["score"], 7);
+
+    let jsonl = render_list(&items, Format::Jsonl).unwrap();
+    let lines: Vec<serde_json::Value> = jsonl
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["title"], "Rust 2.0");
+
+    let yaml = render_list(&items, Format::Yaml).unwrap();
+    assert!(yaml.starts_with("ok: true\ncount: 2\nitems:\n"));
+
+    assert_eq!(
+        render_list(&items, Format::Csv).unwrap(),
+        "title,score,tags,url\n\
+         Rust 2.0,120,\"[\"\"rust\"\"]\",https://example.com\n\
+         \"Say \"\"hi\"\", world\",7,[],\n"
+    );
+
+    assert_eq!(
+        render_list(&items, Format::Table).unwrap(),
+        "TITLE            SCORE  TAGS      URL\n\
+         Rust 2.0         120    [\"rust\"]  https://example.com\n\
+         Say \"hi\", world  7      []\n"
+    );
+}
+
+#[test]
+fn scalar_items_and_empty_lists() {
+    assert_eq!(
+        render_list(&["EUR", "USD"], Format::Csv).unwrap(),
+        "value\nEUR\nUSD\n"
+    );
+    let empty: [u8; 0] = [];
+    assert_eq!(render_list(&empty, Format::Table).unwrap(), "");
+    assert_eq!(
+        render_list(&empty, Format::Json).unwrap(),
+        "{\"ok\":true,\"count\":0,\"items\":[]}\n"
+    );
+}
+
+#[test]
+fn columns_pick_and_order_fields() {
+    let items = stories();
+    let columns = ["score".to_string(), "title".to_string()];
+
+    assert_eq!(
+        render_columns(&items, Format::Csv, &columns).unwrap(),
+        "score,title\n120,Rust 2.0\n7,\"Say \"\"hi\"\", world\"\n"
+    );
+    assert_eq!(
+        render_columns(&items, Format::Jsonl, &columns[..1]).unwrap(),
+        "{\"score\":120}\n{\"score\":7}\n"
+    );
+    assert_eq!(
+        render_columns(&items, Format::Table, &[]).unwrap(),
+        render_list(&items, Format::Table).unwrap()
+    );
+
+    let err = render_columns(&items, Format::Csv, &["points".to_string()]).unwrap_err();
+    assert!(matches!(err, FormatError::UnknownColumn { .. }));
+    assert_eq!(err.code(), "INVALID_ARGUMENT");
+    assert_eq!(
+        err.to_string(),
+        "Unknown column `points`; available: title, score, tags, url"
+    );
+}
+
+#[test]
+fn output_wins_over_json() {
+    assert_eq!(Format::resolve(None, false), None);
+    assert_eq!(Format::resolve(None, true), Some(Format::Json));
+    assert_eq!(Format::resolve(Some(Format::Csv), true), Some(Format::Csv));
+    assert!(Format::Jsonl.is_json());
+    assert!(!Format::Table.is_json());
+    assert!(Format::json_errors(Some(Format::Jsonl), false));
+    assert!(!Format::json_errors(Some(Format::Csv), false));
+}
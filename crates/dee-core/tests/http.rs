@@ -0,0 +1,118 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+use std::time::Duration;
+
+use dee_core::http::{base_url, retry_after, retry_delay, Http, HttpArgs, MAX_RETRY_AFTER};
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+/// Answer with `statuses` in turn (the last one repeats) and record each
+/// request's method and User-Agent.
+fn serve(statuses: &'static [u16]) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&seen);
+    std::thread::spawn(move || {
+        for (n, stream) in listener.incoming().enumerate() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut agent = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("user-agent:") {
+                    agent = value.trim().to_string();
+                }
+            }
+            let method = request_line.split_whitespace().next().unwrap_or_default();
+            log.lock().unwrap().push(format!("{method} {agent}"));
+            let status = statuses[n.min(statuses.len() - 1)];
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status} X\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok"
+            );
+        }
+    });
+    (url, seen)
+}
+
+#[test]
+fn retries_transient_answers_to_gets() {
+    let (url, seen) = serve(&[503, 502, 200]);
+    let http = Http::new("dee-core-test", "1.0.0");
+
+    let response = http.blocking().unwrap().get(&url).send().unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        *seen.lock().unwrap(),
+        ["GET dee-core-test/1.0.0 (https://dee.ink)"; 3]
+    );
+}
+
+#[test]
+fn retries_flag_caps_attempts_and_posts_are_sent_once() {
+    let (url, seen) = serve(&[503]);
+    let args = HttpArgs {
+        timeout_secs: Some(5),
+        retries: Some(1),
+    };
+    let http = Http::new("dee-core-test", "1.0.0").args(&args);
+    assert_eq!(http.timeout().as_secs(), 5);
+    let client = http.blocking().unwrap();
+
+    assert_eq!(client.get(&url).send().unwrap().status(), 503);
+    assert_eq!(seen.lock().unwrap().len(), 2);
+
+    assert_eq!(client.post(&url).send().unwrap().status(), 503);
+    assert_eq!(seen.lock().unwrap().len(), 3);
+}
+
+#[test]
+fn base_url_prefers_the_configured_root() {
+    let default = "https://api.example.com/v1";
+    assert_eq!(base_url(None, default), default);
+    assert_eq!(base_url(Some("  "), default), default);
+    assert_eq!(
+        base_url(Some("http://127.0.0.1:8080/v1/"), default),
+        "http://127.0.0.1:8080/v1"
+    );
+}
+
+#[test]
+fn backoff_doubles_and_respects_retry_after() {
+    let first = Duration::from_secs(2);
+    assert_eq!(retry_delay(first, 0, None), Duration::from_secs(2));
+    assert_eq!(retry_delay(first, 2, None), Duration::from_secs(8));
+    assert_eq!(
+        retry_delay(first, 0, Some(Duration::from_secs(30))),
+        Duration::from_secs(30)
+    );
+    assert_eq!(
+        retry_delay(first, 1, Some(Duration::from_secs(1))),
+        Duration::from_secs(4)
+    );
+    assert_eq!(
+        retry_delay(first, 0, Some(Duration::from_secs(3600))),
+        MAX_RETRY_AFTER
+    );
+}
+
+#[test]
+fn retry_after_reads_seconds_only() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(retry_after(&headers), None);
+    headers.insert(RETRY_AFTER, HeaderValue::from_static(" 7 "));
+    assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+    headers.insert(
+        RETRY_AFTER,
+        HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+    );
+    assert_eq!(retry_after(&headers), None);
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use dee_core::interval::{self, IntervalError};
+use dee_core::ErrorCode;
+
+#[test]
+fn parses_units_and_bare_seconds() {
+    assert_eq!(interval::parse("1h").unwrap(), Duration::from_secs(3600));
+    assert_eq!(interval::parse("15M").unwrap(), Duration::from_secs(900));
+    assert_eq!(interval::parse(" 120 ").unwrap(), Duration::from_secs(120));
+    assert_eq!(interval::parse("2d").unwrap(), Duration::from_secs(172_800));
+}
+
+#[test]
+fn rejects_short_malformed_and_overflowing_intervals() {
+    assert!(matches!(
+        interval::parse("30s"),
+        Err(IntervalError::TooShort)
+    ));
+    for raw in ["1w", "h", "", "-5m", "999999999999999999d"] {
+        let err = interval::parse(raw).unwrap_err();
+        assert!(matches!(err, IntervalError::Invalid(_)), "{raw}");
+        assert_eq!(err.code(), "INVALID_ARGUMENT");
+    }
+}
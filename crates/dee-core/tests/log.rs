@@ -0,0 +1,93 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use dee_core::log::{level, subscriber, LevelFilter, LogFormat};
+
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Buffer {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+fn capture(level: LevelFilter, format: LogFormat) -> String {
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    tracing::subscriber::with_default(subscriber(level, format, move || writer.clone()), || {
+        tracing::trace!(target: "dee_test", "not shown");
+        tracing::debug!(target: "dee_test", url = "https://example.com/a", "GET");
+        tracing::debug!(target: "hyper_util::client", "starting new connection");
+        tracing::warn!(target: "dee_test", "cache is full");
+    });
+    buffer.text()
+}
+
+#[test]
+fn flag_then_verbose_then_env_then_warn() {
+    assert_eq!(
+        level(Some("trace"), true, Some("error")),
+        LevelFilter::TRACE
+    );
+    assert_eq!(level(None, true, Some("error")), LevelFilter::DEBUG);
+    assert_eq!(level(None, false, Some("info")), LevelFilter::INFO);
+    assert_eq!(level(None, false, Some("loud")), LevelFilter::WARN);
+    assert_eq!(level(Some("off"), true, None), LevelFilter::OFF);
+    assert_eq!(level(None, false, None), LevelFilter::WARN);
+}
+
+#[test]
+fn text_lines_start_with_the_level() {
+    assert_eq!(
+        capture(LevelFilter::DEBUG, LogFormat::Text),
+        "debug: GET url=\"https://example.com/a\"\nwarn: cache is full\n"
+    );
+    assert_eq!(
+        capture(LevelFilter::WARN, LogFormat::Text),
+        "warn: cache is full\n"
+    );
+    assert_eq!(capture(LevelFilter::OFF, LogFormat::Text), "");
+}
+
+#[test]
+fn dependencies_show_only_at_trace() {
+    let text = capture(LevelFilter::TRACE, LogFormat::Text);
+    assert!(text.contains("trace: not shown\n"), "{text}");
+    assert!(text.contains("debug: starting new connection\n"), "{text}");
+}
+
+#[test]
+fn json_is_one_object_per_line() {
+    let text = capture(LevelFilter::DEBUG, LogFormat::Json);
+    let lines: Vec<serde_json::Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("JSON line"))
+        .collect();
+
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["level"], "DEBUG");
+    assert_eq!(lines[0]["message"], "GET");
+    assert_eq!(lines[0]["url"], "https://example.com/a");
+    assert!(lines[0]["timestamp"].is_string());
+    assert_eq!(lines[1]["level"], "WARN");
+    assert_eq!(lines[1]["message"], "cache is full");
+}
+
+#[test]
+fn formats_parse_by_name() {
+    assert_eq!("JSON".parse(), Ok(LogFormat::Json));
+    assert_eq!("text".parse(), Ok(LogFormat::Text));
+    assert!("xml".parse::<LogFormat>().is_err());
+}
@@ -0,0 +1,51 @@
+use clap::{Arg, ArgAction, Command};
+use dee_core::man::render;
+
+fn tool() -> Command {
+    Command::new("dee-demo")
+        .version("1.2.3")
+        .about("Demo tool")
+        .long_about("dee-demo - Demo tool.\n\n.Lines starting with a dot stay text.")
+        .after_help("EXAMPLES:\n  dee-demo list --limit 5\n  dee-demo list --json")
+        .arg(
+            Arg::new("json")
+                .short('j')
+                .long("json")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Output as JSON"),
+        )
+        .subcommand(
+            Command::new("list").about("List things").arg(
+                Arg::new("limit")
+                    .long("limit")
+                    .default_value("10")
+                    .help("Max results"),
+            ),
+        )
+        .subcommand(Command::new("secret").hide(true))
+}
+
+#[test]
+fn renders_header_sections_and_options() {
+    let page = render(&tool());
+
+    assert!(page.starts_with(".TH \"DEE-DEMO\" \"1\" \"\" \"dee-demo 1.2.3\" \"dee.ink\"\n"));
+    assert!(page.contains(".SH NAME\ndee-demo \\- Demo tool\n"));
+    assert!(page.contains(".SH SYNOPSIS\n\\fBdee\\-demo\\fR"));
+    assert!(page.contains("\\&.Lines starting with a dot stay text."));
+    assert!(page.contains(".TP\n\\fB\\-j\\fR, \\fB\\-\\-json\\fR\nOutput as JSON\n"));
+    assert!(page.contains(".SS \"dee\\-demo list\"\nList things\n"));
+    assert!(page.contains("\\fB\\-\\-limit\\fR \\fI<limit>\\fR\nMax results [default: 10]\n"));
+    assert!(page.contains(".SH EXAMPLES\n.nf\ndee\\-demo list \\-\\-limit 5\n"));
+    assert!(page.ends_with(".SH VERSION\nv1.2.3\n"));
+}
+
+#[test]
+fn skips_hidden_and_help_commands_and_repeated_globals() {
+    let page = render(&tool());
+
+    assert!(!page.contains("secret"));
+    assert!(!page.contains(".SS \"dee\\-demo help\""));
+    assert_eq!(page.matches("\\-\\-json\\fR\n").count(), 1);
+}
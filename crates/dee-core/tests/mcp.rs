@@ -0,0 +1,101 @@
+use clap::{Arg, ArgAction, Command};
+use dee_core::mcp::tools;
+use serde_json::json;
+
+fn tool() -> Command {
+    Command::new("dee-demo")
+        .about("Demo tool")
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .global(true)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no_cache")
+                .long("no-cache")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Skip cached responses"),
+        )
+        .subcommand(
+            Command::new("search")
+                .about("Search things")
+                .arg(Arg::new("query").required(true).help("Search terms"))
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .value_parser(clap::value_parser!(u32))
+                        .default_value("10"),
+                )
+                .arg(Arg::new("tag").long("tag").action(ArgAction::Append))
+                .arg(
+                    Arg::new("sort")
+                        .long("sort")
+                        .value_parser(["date", "score"]),
+                ),
+        )
+        .subcommand(Command::new("add").arg(Arg::new("name").required(true)))
+        .subcommand(Command::new("config").subcommand(Command::new("show")))
+        .subcommand(Command::new("lib").subcommand(Command::new("list")))
+}
+
+#[test]
+fn exposes_read_only_leaves_with_schemas() {
+    let tools = tools(&tool());
+    let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+    assert_eq!(names, ["search", "lib_list"]);
+
+    let search = &tools[0];
+    assert_eq!(search.description, "Search things");
+    let schema = &search.input_schema;
+    assert_eq!(schema["required"], json!(["query"]));
+    assert_eq!(
+        schema["properties"]["query"],
+        json!({ "type": "string", "description": "Search terms" })
+    );
+    assert_eq!(
+        schema["properties"]["limit"],
+        json!({ "type": "integer", "default": 10 })
+    );
+    assert_eq!(schema["properties"]["tag"]["type"], "array");
+    assert_eq!(
+        schema["properties"]["sort"]["enum"],
+        json!(["date", "score"])
+    );
+    assert_eq!(schema["properties"]["no-cache"]["type"], "boolean");
+    assert!(schema["properties"].get("json").is_none());
+
+    assert_eq!(tools[1].description, "Demo tool: lib list");
+}
+
+#[test]
+fn arguments_become_a_command_line() {
+    let tools = tools(&tool());
+    let search = &tools[0];
+    let arguments = json!({
+        "query": "-rust",
+        "limit": 5,
+        "tag": ["a", "b"],
+        "no-cache": true,
+    });
+    assert_eq!(
+        search.argv(arguments.as_object().unwrap()).unwrap(),
+        [
+            "search",
+            "--limit",
+            "5",
+            "--tag",
+            "a",
+            "--tag",
+            "b",
+            "--no-cache",
+            "--json",
+            "--",
+            "-rust"
+        ]
+    );
+
+    let unknown = json!({ "query": "x", "page": 2 });
+    assert!(search.argv(unknown.as_object().unwrap()).is_err());
+}
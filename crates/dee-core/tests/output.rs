@@ -0,0 +1,48 @@
+use dee_core::{ErrorCode, ErrorJson, OkItem, OkList, OkMessage};
+
+#[derive(Debug, thiserror::Error)]
+#[error("Contact not found")]
+struct NotFound;
+
+impl ErrorCode for NotFound {
+    fn code(&self) -> &'static str {
+        "NOT_FOUND"
+    }
+}
+
+#[test]
+fn envelopes_match_the_contract() {
+    let list = serde_json::to_value(OkList::new(vec![1, 2, 3])).unwrap();
+    assert_eq!(
+        list,
+        serde_json::json!({"ok": true, "count": 3, "items": [1, 2, 3]})
+    );
+
+    let item = serde_json::to_value(OkItem::new("x")).unwrap();
+    assert_eq!(item, serde_json::json!({"ok": true, "item": "x"}));
+
+    let message = serde_json::to_value(OkMessage::new("Contact added")).unwrap();
+    assert_eq!(
+        message,
+        serde_json::json!({"ok": true, "message": "Contact added"})
+    );
+
+    let err = &NotFound;
+    let error = serde_json::to_value(ErrorJson::new(err.to_string(), err.code())).unwrap();
+    assert_eq!(
+        error,
+        serde_json::json!({"ok": false, "error": "Contact not found", "code": "NOT_FOUND"})
+    );
+}
+
+#[test]
+fn error_codes_map_to_shared_exit_statuses() {
+    use dee_core::exit;
+
+    assert_eq!(NotFound.exit_code(), exit::NOT_FOUND);
+    assert_eq!(exit::for_code("INVALID_ARGUMENT"), 2);
+    assert_eq!(exit::for_code("AUTH_MISSING"), 4);
+    assert_eq!(exit::for_code("REQUEST_FAILED"), 5);
+    assert_eq!(exit::for_code("EXPIRING_SOON"), 6);
+    assert_eq!(exit::for_code("DATABASE_ERROR"), exit::FAILURE);
+}
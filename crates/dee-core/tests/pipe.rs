@@ -0,0 +1,57 @@
+use dee_core::pipe::{parse_values, read_values};
+use dee_core::{exit, ErrorCode};
+
+#[test]
+fn fields_come_from_every_output_shape() {
+    let list = r#"{"ok":true,"count":2,"items":[{"domain":"a.com"},{"domain":"b.com"}]}"#;
+    assert_eq!(parse_values(list, "domain").unwrap(), ["a.com", "b.com"]);
+
+    let item = r#"{"ok":true,"item":{"domain":"a.com","port":443}}"#;
+    assert_eq!(parse_values(item, "port").unwrap(), ["443"]);
+
+    let jsonl = "{\"url\":\"https://a.example\"}\n\n{\"url\":\"https://b.example\"}\n";
+    assert_eq!(
+        read_values(jsonl.as_bytes(), "url").unwrap(),
+        ["https://a.example", "https://b.example"]
+    );
+
+    let nested = r#"[{"location":{"city":"Oslo"}}]"#;
+    assert_eq!(parse_values(nested, "location.city").unwrap(), ["Oslo"]);
+
+    // Plain values need no field
+    assert_eq!(
+        parse_values(r#"["a.com", 7]"#, "domain").unwrap(),
+        ["a.com", "7"]
+    );
+    assert!(parse_values(r#"{"ok":true,"items":[]}"#, "domain")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn bad_input_is_an_error() {
+    let err = parse_values(r#"[{"domain":"a.com"},{"name":"b"}]"#, "domain").unwrap_err();
+    assert_eq!(err.code(), "INVALID_INPUT");
+    assert_eq!(err.exit_code(), exit::INVALID_ARGUMENT);
+    assert!(err.to_string().contains("item 1"), "{err}");
+
+    let err = parse_values(r#"[{"domain":["a.com"]}]"#, "domain").unwrap_err();
+    assert_eq!(err.code(), "INVALID_INPUT");
+
+    assert_eq!(
+        parse_values(" \n", "domain").unwrap_err().code(),
+        "INVALID_INPUT"
+    );
+    assert_eq!(
+        parse_values("domain\na.com\n", "domain")
+            .unwrap_err()
+            .code(),
+        "INVALID_INPUT"
+    );
+
+    // A failed first command fails the second
+    let upstream = r#"{"ok":false,"error":"Auth missing","code":"AUTH_MISSING"}"#;
+    let err = parse_values(upstream, "domain").unwrap_err();
+    assert_eq!(err.code(), "UPSTREAM_FAILED");
+    assert!(err.to_string().contains("AUTH_MISSING"), "{err}");
+}
@@ -0,0 +1,60 @@
+use dee_core::{profile, Config, ErrorCode};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+struct AppConfig {
+    api_key: Option<String>,
+    #[serde(default)]
+    limit: u32,
+}
+
+// One test, since the profile comes from the process environment
+#[test]
+fn the_active_profile_overlays_the_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.toml");
+    let profiles = dir.path().join("profiles.toml");
+    std::env::set_var("DEE_PROFILE_TEST_CONFIG", &config_path);
+    std::env::set_var(profile::PROFILES_FILE_ENV, &profiles);
+    std::fs::write(&config_path, "api_key = \"personal\"\nlimit = 5\n").unwrap();
+    std::fs::write(
+        &profiles,
+        "[work.dee-profile-test]\napi_key = \"work\"\n\n[empty]\n",
+    )
+    .unwrap();
+    let config = Config::<AppConfig>::new("dee-profile-test").env_override("api_key");
+
+    // No profile: the file alone
+    assert_eq!(config.load().unwrap().api_key.as_deref(), Some("personal"));
+
+    std::env::set_var(profile::PROFILE_ENV, "work");
+    assert_eq!(profile::active().as_deref(), Some("work"));
+    let loaded = config.load().unwrap();
+    assert_eq!(loaded.api_key.as_deref(), Some("work"));
+    // Keys the profile leaves out come from the file
+    assert_eq!(loaded.limit, 5);
+    // Never saved back
+    assert_eq!(
+        config.load_file().unwrap().api_key.as_deref(),
+        Some("personal")
+    );
+
+    // Env overrides still win
+    std::env::set_var("DEE_PROFILE_TEST_API_KEY", "from-env");
+    assert_eq!(config.load().unwrap().api_key.as_deref(), Some("from-env"));
+    std::env::remove_var("DEE_PROFILE_TEST_API_KEY");
+
+    // A profile without this tool changes nothing
+    std::env::set_var(profile::PROFILE_ENV, "empty");
+    assert_eq!(config.load().unwrap().api_key.as_deref(), Some("personal"));
+
+    std::env::set_var(profile::PROFILE_ENV, "wrok");
+    let err = config.load().unwrap_err();
+    assert_eq!(err.code(), "PROFILE_NOT_FOUND");
+    assert_eq!(err.exit_code(), dee_core::exit::NOT_FOUND);
+    assert!(err.to_string().contains("`wrok`"), "{err}");
+
+    // `--profile` beats the environment
+    profile::select("work");
+    assert_eq!(config.load().unwrap().api_key.as_deref(), Some("work"));
+}
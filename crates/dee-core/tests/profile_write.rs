@@ -0,0 +1,29 @@
+use dee_core::profile;
+
+// One test, since the profiles file comes from the process environment
+#[test]
+fn keys_are_written_into_the_named_profile() {
+    let dir = tempfile::tempdir().unwrap();
+    let profiles = dir.path().join("dee").join("profiles.toml");
+    std::env::set_var(profile::PROFILES_FILE_ENV, &profiles);
+
+    assert!(profile::for_tool("dee-porkbun").unwrap().is_empty());
+
+    profile::set_key("work", "dee-porkbun", "api_key", "pk1_work".into()).unwrap();
+    profile::set_key("work", "dee-porkbun", "secret_key", "sk1_work".into()).unwrap();
+    profile::set_key("home", "dee-rates", "base", "EUR".into()).unwrap();
+    profile::set_key("home", "dee-porkbun", "api_key", "pk1_home".into()).unwrap();
+
+    let found = profile::for_tool("dee-porkbun").unwrap();
+    let names: Vec<&str> = found.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["home", "work"]);
+    assert_eq!(found[1].1["secret_key"].as_str(), Some("sk1_work"));
+
+    let text = std::fs::read_to_string(&profiles).unwrap();
+    let table: toml::Table = text.parse().unwrap();
+    assert_eq!(table["home"]["dee-rates"]["base"].as_str(), Some("EUR"));
+
+    std::fs::write(&profiles, "work = \"oops\"\n").unwrap();
+    let err = profile::set_key("work", "dee-porkbun", "api_key", "x".into()).unwrap_err();
+    assert!(err.to_string().contains("must be a table"), "{err}");
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use dee_core::ratelimit::{self, Budget, Limiter};
+use dee_core::ErrorCode;
+
+#[test]
+fn hosts_come_from_urls() {
+    assert_eq!(
+        ratelimit::host_of("https://export.arxiv.org/api/query?id_list=1"),
+        "export.arxiv.org"
+    );
+    assert_eq!(
+        ratelimit::host_of("http://user:pw@HN.Algolia.com:443/api"),
+        "hn.algolia.com"
+    );
+    assert_eq!(ratelimit::host_of("[::1]:8080"), "::1");
+    assert_eq!(ratelimit::host_of("api.yelp.com"), "api.yelp.com");
+}
+
+// One test, since the files come from the process environment
+#[test]
+fn budgets_are_shared_between_runs_and_configurable() {
+    let dir = tempfile::tempdir().unwrap();
+    let limits = dir.path().join("ratelimit.toml");
+    std::env::set_var(ratelimit::LIMITS_FILE_ENV, &limits);
+    std::env::set_var(ratelimit::STATE_DIR_ENV, dir.path().join("buckets"));
+
+    // Built in until the file says otherwise
+    let limiter = Limiter::load().unwrap();
+    assert_eq!(limiter.budget("export.arxiv.org"), Some(Budget::new(1, 3)));
+    assert_eq!(limiter.budget("example.com"), None);
+    assert_eq!(limiter.reserve("https://example.com/a"), Duration::ZERO);
+
+    std::fs::write(
+        &limits,
+        "[\"api.example.com\"]\nrequests = 2\nper_secs = 1\n\n[\"export.arxiv.org\"]\nrequests = 0\n",
+    )
+    .unwrap();
+    let limiter = Limiter::load().unwrap();
+    assert_eq!(limiter.budget("export.arxiv.org"), None);
+
+    // A burst of two, then one every half second
+    let url = "https://api.example.com/v1/items";
+    assert_eq!(limiter.reserve(url), Duration::ZERO);
+    assert_eq!(limiter.reserve(url), Duration::ZERO);
+    // The next run sees the same bucket
+    let wait = Limiter::load().unwrap().reserve(url);
+    assert!(
+        wait > Duration::from_millis(300) && wait <= Duration::from_millis(500),
+        "{wait:?}"
+    );
+    // and queues behind it
+    let wait = ratelimit::reserve(url).unwrap();
+    assert!(wait > Duration::from_millis(800), "{wait:?}");
+
+    std::fs::write(&limits, "[\"api.example.com\"]\nrequests = \"many\"\n").unwrap();
+    let err = Limiter::load().unwrap_err();
+    assert_eq!(err.code(), "CONFIG_INVALID");
+    assert!(err.to_string().contains("api.example.com"), "{err}");
+}
@@ -0,0 +1,83 @@
+use dee_core::schema::Schemas;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Serialize, JsonSchema)]
+struct Story {
+    id: u64,
+    title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+}
+
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<Story>("top")
+        .item::<Story>("item")
+        .message("config set")
+        .cache()
+}
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(String::from).collect()
+}
+
+#[test]
+fn commands_are_found_among_flags_and_positionals() {
+    let schemas = schemas();
+    let find = |line: &str| {
+        schemas
+            .find(&args(line))
+            .map(|entry| entry.command.as_str())
+    };
+    assert_eq!(find("top --limit 5 --schema"), Some("top"));
+    assert_eq!(find("item 8863 --schema"), Some("item"));
+    assert_eq!(
+        find("--profile work config set --schema"),
+        Some("config set")
+    );
+    assert_eq!(find("cache clear --schema"), Some("cache clear"));
+    assert_eq!(find("config --schema"), None);
+    assert_eq!(schemas.commands().len(), 5);
+
+    // A tool whose bare invocation is a command registers it as ""
+    let schemas = Schemas::new().item::<Story>("").list::<Story>("history");
+    assert_eq!(
+        schemas
+            .find(&args("example.com --schema"))
+            .map(|entry| entry.command.as_str()),
+        Some("")
+    );
+    assert_eq!(
+        schemas
+            .find(&args("history example.com --schema"))
+            .map(|entry| entry.command.as_str()),
+        Some("history")
+    );
+}
+
+#[test]
+fn schemas_describe_the_envelopes() {
+    let schemas = schemas();
+    let top = &schemas.get("top").unwrap().success;
+    let properties = top["properties"].as_object().unwrap();
+    for key in ["ok", "count", "items", "stale", "cache_age_secs"] {
+        assert!(properties.contains_key(key), "{key} in {top}");
+    }
+    let story = &top["$defs"]["Story"];
+    assert_eq!(story["required"], serde_json::json!(["id", "title"]));
+    assert!(story["properties"]["url"].is_object());
+
+    let message = &schemas.get("config set").unwrap().success;
+    assert_eq!(message["properties"]["message"]["type"], "string");
+
+    let error = &schemas.get("item").unwrap().error;
+    let required: Vec<&str> = error["required"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|key| key.as_str())
+        .collect();
+    assert_eq!(required, ["ok", "error", "code"]);
+    assert!(error["properties"].get("stale").is_none());
+}
@@ -0,0 +1,15 @@
+use dee_core::style;
+
+// One test per binary: `enabled` is decided once per process
+#[test]
+fn clicolor_force_styles_and_expiry_picks_the_colour() {
+    std::env::remove_var("NO_COLOR");
+    std::env::set_var("CLICOLOR_FORCE", "1");
+    assert!(style::enabled());
+
+    assert_eq!(style::header("Title"), "\u{1b}[1mTitle\u{1b}[0m");
+    assert!(style::expiry("expired", -3).starts_with("\u{1b}[31m"));
+    assert!(style::expiry("soon", 20).starts_with("\u{1b}[33m"));
+    assert!(style::expiry("later", 200).starts_with("\u{1b}[32m"));
+    assert!(style::expiry_within("soon", 45, 60).starts_with("\u{1b}[33m"));
+}
@@ -1,4 +1,6 @@
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::http::HttpArgs;
+use dee_core::Format;
 
 #[derive(Debug, Parser)]
 #[command(
@@ -26,6 +28,13 @@ pub struct GlobalFlags {
 
     #[arg(short = 'v', long, global = true)]
     pub verbose: bool,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    pub output: Option<Format>,
+
+    #[command(flatten)]
+    pub http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -1,3 +1,4 @@
+use dee_core::ErrorCode;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -16,8 +17,8 @@ pub enum AppError {
     Database,
 }
 
-impl AppError {
-    pub fn code(&self) -> &'static str {
+impl ErrorCode for AppError {
+    fn code(&self) -> &'static str {
         match self {
             Self::DataDirMissing => "CONFIG_MISSING",
             Self::InvalidArgument(_) => "INVALID_ARGUMENT",
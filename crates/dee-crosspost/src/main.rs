@@ -8,7 +8,7 @@ use std::env;
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
-use clap::{CommandFactory, Parser};
+use clap::CommandFactory;
 use cli::{
     AuthArgs, AuthCommand, Cli, Commands, Platform, PostArgs, QueueArgs, QueueCommand, RunArgs,
     ScheduleArgs,
@@ -18,21 +18,40 @@ use db::{
     get_token_expiry, mark_target_result, queue_cancel, queue_list, queue_show, schedule_job,
     set_job_running, upsert_token, PostDraft,
 };
+use dee_core::http::{Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{print_list, report, ErrorCode, Format, OkItem};
 use error::AppError;
 use models::{
-    ActionResponse, AuthStatusItem, ErrorResponse, ItemResponse, ListResponse, PostPlatformResult,
-    PostResponse, RunResponse,
+    ActionResponse, AuthStatusItem, PostPlatformResult, PostResponse, QueueDetail, QueueItem,
+    RunResponse,
 };
 use providers::{post_to_platform, PostRequest};
 use reqwest::Client;
 
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .custom::<ActionResponse>("auth set-token")
+        .list::<AuthStatusItem>("auth status")
+        .custom::<ActionResponse>("auth logout")
+        .custom::<ActionResponse>("auth login")
+        .custom::<PostResponse>("post")
+        .custom::<ActionResponse>("schedule")
+        .list::<QueueItem>("queue list")
+        .item::<QueueDetail>("queue show")
+        .custom::<ActionResponse>("queue cancel")
+        .custom::<RunResponse>("run")
+}
+
 #[tokio::main]
 async fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = run(cli).await {
-        print_error(&err);
-        std::process::exit(1);
+        report(&err, dee_core::cli::wants_json());
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -41,15 +60,31 @@ async fn run(cli: Cli) -> Result<(), AppError> {
     let conn = connect(&path)?;
 
     match cli.command {
-        Commands::Auth(args) => handle_auth(&conn, args, cli.global.json),
-        Commands::Post(args) => handle_post(&conn, args, cli.global.json).await,
+        Commands::Auth(args) => handle_auth(&conn, args, cli.global.json, cli.global.output),
+        Commands::Post(args) => {
+            handle_post(&conn, &client(&cli.global.http)?, args, cli.global.json).await
+        }
         Commands::Schedule(args) => handle_schedule(&conn, args, cli.global.json),
-        Commands::Queue(args) => handle_queue(&conn, args, cli.global.json),
-        Commands::Run(args) => handle_run(&conn, args, cli.global.json).await,
+        Commands::Queue(args) => handle_queue(&conn, args, cli.global.json, cli.global.output),
+        Commands::Run(args) => {
+            handle_run(&conn, &client(&cli.global.http)?, args, cli.global.json).await
+        }
     }
 }
 
-fn handle_auth(conn: &rusqlite::Connection, args: AuthArgs, json: bool) -> Result<(), AppError> {
+fn client(http: &HttpArgs) -> Result<Client, AppError> {
+    Http::new("dee-crosspost", env!("CARGO_PKG_VERSION"))
+        .args(http)
+        .client()
+        .map_err(|e| AppError::RequestFailed(format!("http client: {e}")))
+}
+
+fn handle_auth(
+    conn: &rusqlite::Connection,
+    args: AuthArgs,
+    json: bool,
+    output: Option<Format>,
+) -> Result<(), AppError> {
     match args.command {
         AuthCommand::SetToken(args) => {
             if args.token.trim().is_empty() {
@@ -94,15 +129,7 @@ fn handle_auth(conn: &rusqlite::Connection, args: AuthArgs, json: bool) -> Resul
                     expires_at,
                 });
             }
-            print_json_or_text(
-                json,
-                &ListResponse {
-                    ok: true,
-                    count: items.len(),
-                    items,
-                },
-                "auth status listed",
-            );
+            print_list_or_text(Format::resolve(output, json), &items, "auth status listed");
             Ok(())
         }
         AuthCommand::Logout(args) => {
@@ -151,11 +178,11 @@ fn handle_auth(conn: &rusqlite::Connection, args: AuthArgs, json: bool) -> Resul
 
 async fn handle_post(
     conn: &rusqlite::Connection,
+    client: &Client,
     args: PostArgs,
     json: bool,
 ) -> Result<(), AppError> {
     validate_targets(&args.to)?;
-    let client = Client::new();
     let req = PostRequest {
         text: args.text,
         media: args.media,
@@ -237,25 +264,22 @@ fn handle_schedule(
     Ok(())
 }
 
-fn handle_queue(conn: &rusqlite::Connection, args: QueueArgs, json: bool) -> Result<(), AppError> {
+fn handle_queue(
+    conn: &rusqlite::Connection,
+    args: QueueArgs,
+    json: bool,
+    output: Option<Format>,
+) -> Result<(), AppError> {
     match args.command {
         QueueCommand::List(args) => {
             let status = args.status.map(|s| s.as_str().to_string());
             let items = queue_list(conn, status.as_deref())?;
-            print_json_or_text(
-                json,
-                &ListResponse {
-                    ok: true,
-                    count: items.len(),
-                    items,
-                },
-                "queue listed",
-            );
+            print_list_or_text(Format::resolve(output, json), &items, "queue listed");
             Ok(())
         }
         QueueCommand::Show(args) => {
             let item = queue_show(conn, &args.id)?.ok_or(AppError::NotFound)?;
-            print_json_or_text(json, &ItemResponse { ok: true, item }, "queue item");
+            print_json_or_text(json, &OkItem { ok: true, item }, "queue item");
             Ok(())
         }
         QueueCommand::Cancel(args) => {
@@ -279,6 +303,7 @@ fn handle_queue(conn: &rusqlite::Connection, args: QueueArgs, json: bool) -> Res
 
 async fn handle_run(
     conn: &rusqlite::Connection,
+    client: &Client,
     args: RunArgs,
     json: bool,
 ) -> Result<(), AppError> {
@@ -294,13 +319,13 @@ async fn handle_run(
     }
 
     if args.once {
-        let response = run_once(conn).await?;
+        let response = run_once(conn, client).await?;
         print_json_or_text(json, &response, "run once completed");
         return Ok(());
     }
 
     loop {
-        let response = run_once(conn).await?;
+        let response = run_once(conn, client).await?;
         if !json {
             println!(
                 "jobs_processed={} targets_sent={} targets_failed={}",
@@ -328,9 +353,8 @@ async fn handle_run(
     Ok(())
 }
 
-async fn run_once(conn: &rusqlite::Connection) -> Result<RunResponse, AppError> {
+async fn run_once(conn: &rusqlite::Connection, client: &Client) -> Result<RunResponse, AppError> {
     let jobs = due_jobs(conn, Utc::now())?;
-    let client = Client::new();
 
     let mut jobs_processed = 0usize;
     let mut targets_sent = 0usize;
@@ -349,7 +373,7 @@ async fn run_once(conn: &rusqlite::Connection) -> Result<RunResponse, AppError>
             let platform = parse_platform(target)?;
             let resolved = resolve_token(conn, platform);
             let outcome = match resolved {
-                Ok(token) => post_to_platform(&client, platform, &token, &req).await,
+                Ok(token) => post_to_platform(client, platform, &token, &req).await,
                 Err(err) => Err(err),
             };
 
@@ -449,6 +473,13 @@ fn all_platforms() -> [Platform; 5] {
     ]
 }
 
+fn print_list_or_text<T: serde::Serialize>(format: Option<Format>, items: &[T], fallback: &str) {
+    match format {
+        Some(format) => print_list(items, format),
+        None => println!("{fallback}"),
+    }
+}
+
 fn print_json_or_text<T: serde::Serialize>(json: bool, value: &T, fallback: &str) {
     if json {
         let line = serde_json::to_string(value).unwrap_or_else(|_| {
@@ -460,56 +491,7 @@ fn print_json_or_text<T: serde::Serialize>(json: bool, value: &T, fallback: &str
     }
 }
 
-fn print_error(err: &AppError) {
-    let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-    if wants_json {
-        let line = serde_json::to_string(&ErrorResponse {
-            ok: false,
-            error: err.to_string(),
-            code: err.code().to_string(),
-        })
-        .unwrap_or_else(|_| {
-            r#"{"ok":false,"error":"unexpected error","code":"UNEXPECTED"}"#.to_string()
-        });
-        println!("{line}");
-    } else {
-        eprintln!("error: {err}");
-    }
-}
-
 #[allow(dead_code)]
 fn _verify_help_builds() {
     Cli::command();
 }
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -1,13 +1,7 @@
+use schemars::JsonSchema;
 use serde::Serialize;
 
-#[derive(Debug, Serialize)]
-pub struct ErrorResponse {
-    pub ok: bool,
-    pub error: String,
-    pub code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct ActionResponse {
     pub ok: bool,
     pub message: String,
@@ -15,20 +9,7 @@ pub struct ActionResponse {
     pub id: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct ListResponse<T> {
-    pub ok: bool,
-    pub count: usize,
-    pub items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct ItemResponse<T> {
-    pub ok: bool,
-    pub item: T,
-}
-
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct AuthStatusItem {
     pub platform: String,
     pub configured: bool,
@@ -37,7 +18,7 @@ pub struct AuthStatusItem {
     pub expires_at: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct PostPlatformResult {
     pub platform: String,
     pub status: String,
@@ -49,7 +30,7 @@ pub struct PostPlatformResult {
     pub error: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct QueueItem {
     pub id: String,
     pub run_at: String,
@@ -58,7 +39,7 @@ pub struct QueueItem {
     pub targets: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct QueueDetail {
     pub id: String,
     pub run_at: String,
@@ -73,14 +54,14 @@ pub struct QueueDetail {
     pub targets: Vec<PostPlatformResult>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct PostResponse {
     pub ok: bool,
     pub count: usize,
     pub results: Vec<PostPlatformResult>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct RunResponse {
     pub ok: bool,
     pub jobs_processed: usize,
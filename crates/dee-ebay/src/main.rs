@@ -1,10 +1,14 @@
-use std::fs;
-use std::path::PathBuf;
-
 use base64::Engine as _;
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::http::{Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, report, Config, ConfigError, ErrorCode, Format, OkItem, OkList,
+    OkMessage,
+};
 use reqwest::blocking::Client;
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Parser)]
@@ -31,6 +35,11 @@ struct GlobalArgs {
     quiet: bool,
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -97,7 +106,7 @@ struct ShowFlags {
     json: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
 struct AppConfig {
     #[serde(default)]
     client_id: Option<String>,
@@ -107,33 +116,7 @@ struct AppConfig {
     sandbox: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
-struct OkList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct OkItem<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
-struct OkMessage {
-    ok: bool,
-    message: String,
-}
-
-#[derive(Debug, Serialize)]
-struct ErrorJson {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct SearchItem {
     id: String,
     title: String,
@@ -191,8 +174,8 @@ struct BrowseSeller {
 
 #[derive(Debug, thiserror::Error)]
 enum AppError {
-    #[error("Configuration directory not found")]
-    ConfigMissing,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
     #[error("Missing eBay credentials. Set ebay.client-id and ebay.client-secret")]
     AuthMissing,
     #[error("Unknown config key: {0}")]
@@ -205,40 +188,50 @@ enum AppError {
     ApiError(String),
     #[error("Response parse failed")]
     ParseFailed,
-    #[error("JSON serialization failed")]
-    Serialize,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
-            Self::ConfigMissing => "CONFIG_MISSING",
+            Self::Config(err) => err.code(),
             Self::AuthMissing => "AUTH_MISSING",
             Self::InvalidConfigKey(_) | Self::InvalidArgument(_) => "INVALID_ARGUMENT",
             Self::RequestFailed => "REQUEST_FAILED",
             Self::ApiError(_) => "API_ERROR",
             Self::ParseFailed => "PARSE_FAILED",
-            Self::Serialize => "SERIALIZE",
         }
     }
 }
 
 type AppResult<T> = Result<T, AppError>;
 
+/// `DEE_EBAY_CLIENT_ID`, `DEE_EBAY_CLIENT_SECRET` and `DEE_EBAY_SANDBOX` override the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-ebay")
+        .env_override("client_id")
+        .env_override("client_secret")
+        .env_override("sandbox")
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<SearchItem>("search")
+        .message("config set")
+        .item::<AppConfig>("config show")
+        .list::<String>("config path")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = dispatch(&cli) {
-        if cli.global.json {
-            print_json(&ErrorJson {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -261,7 +254,7 @@ fn cmd_search(args: &SearchArgs, global: &GlobalArgs) -> AppResult<()> {
         ));
     }
 
-    let config = load_config_or_default()?;
+    let config = config_file().load()?;
     let client_id = config.client_id.ok_or(AppError::AuthMissing)?;
     let client_secret = config.client_secret.ok_or(AppError::AuthMissing)?;
     let sandbox = config.sandbox.unwrap_or(false);
@@ -272,7 +265,11 @@ fn cmd_search(args: &SearchArgs, global: &GlobalArgs) -> AppResult<()> {
         "https://api.ebay.com"
     };
 
-    let token = fetch_access_token(auth_base, &client_id, &client_secret)?;
+    let client = Http::new("dee-ebay", env!("CARGO_PKG_VERSION"))
+        .args(&global.http)
+        .blocking()
+        .map_err(|_| AppError::RequestFailed)?;
+    let token = fetch_access_token(&client, auth_base, &client_id, &client_secret)?;
 
     let mut url = format!(
         "{auth_base}/buy/browse/v1/item_summary/search?q={}&limit={}",
@@ -285,7 +282,6 @@ fn cmd_search(args: &SearchArgs, global: &GlobalArgs) -> AppResult<()> {
         url.push_str(sort.as_api_value());
     }
 
-    let client = Client::new();
     let response = client
         .get(url)
         .header(AUTHORIZATION, format!("Bearer {token}"))
@@ -318,12 +314,8 @@ fn cmd_search(args: &SearchArgs, global: &GlobalArgs) -> AppResult<()> {
         })
         .collect::<Vec<_>>();
 
-    if global.json {
-        print_json(&OkList {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(global.output, global.json) {
+        print_list(&items, format);
         return Ok(());
     }
 
@@ -348,7 +340,12 @@ fn cmd_search(args: &SearchArgs, global: &GlobalArgs) -> AppResult<()> {
     Ok(())
 }
 
-fn fetch_access_token(base: &str, client_id: &str, client_secret: &str) -> AppResult<String> {
+fn fetch_access_token(
+    client: &Client,
+    base: &str,
+    client_id: &str,
+    client_secret: &str,
+) -> AppResult<String> {
     let endpoint = format!("{base}/identity/v1/oauth2/token");
     let basic = base64::engine::general_purpose::STANDARD.encode(format!(
         "{}:{}",
@@ -356,7 +353,6 @@ fn fetch_access_token(base: &str, client_id: &str, client_secret: &str) -> AppRe
         client_secret.trim()
     ));
 
-    let client = Client::new();
     let response = client
         .post(endpoint)
         .header(AUTHORIZATION, format!("Basic {basic}"))
@@ -392,7 +388,7 @@ fn cmd_config(args: &ConfigArgs, global: &GlobalArgs) -> AppResult<()> {
 }
 
 fn cmd_config_set(args: &ConfigSetArgs, global: &GlobalArgs) -> AppResult<()> {
-    let mut config = load_config_or_default()?;
+    let mut config = config_file().load_file()?;
 
     match args.key.as_str() {
         "ebay.client-id" => {
@@ -420,7 +416,7 @@ fn cmd_config_set(args: &ConfigSetArgs, global: &GlobalArgs) -> AppResult<()> {
         other => return Err(AppError::InvalidConfigKey(other.to_string())),
     }
 
-    save_config(&config)?;
+    config_file().save(&config)?;
 
     if global.json || args.output.json {
         print_json(&OkMessage {
@@ -437,7 +433,7 @@ fn cmd_config_set(args: &ConfigSetArgs, global: &GlobalArgs) -> AppResult<()> {
 }
 
 fn cmd_config_show(json: bool) -> AppResult<()> {
-    let config = load_config_or_default()?;
+    let config = config_file().load()?;
 
     if json {
         print_json(&OkItem {
@@ -454,7 +450,7 @@ fn cmd_config_show(json: bool) -> AppResult<()> {
 }
 
 fn cmd_config_path(json: bool) -> AppResult<()> {
-    let path = config_path()?;
+    let path = config_file().path();
     let rendered = path.display().to_string();
 
     if json {
@@ -469,93 +465,3 @@ fn cmd_config_path(json: bool) -> AppResult<()> {
 
     Ok(())
 }
-
-fn config_path() -> AppResult<PathBuf> {
-    let base = dirs::config_dir().ok_or(AppError::ConfigMissing)?;
-    Ok(base.join("dee-ebay").join("config.toml"))
-}
-
-fn load_config_or_default() -> AppResult<AppConfig> {
-    match load_config() {
-        Ok(config) => Ok(config),
-        Err(AppError::InvalidArgument(_)) => Ok(AppConfig::default()),
-        Err(other) => Err(other),
-    }
-}
-
-fn load_config() -> AppResult<AppConfig> {
-    let path = config_path()?;
-    if !path.exists() {
-        return Err(AppError::InvalidArgument(
-            "config file not found".to_string(),
-        ));
-    }
-
-    let raw = fs::read_to_string(path)
-        .map_err(|_| AppError::InvalidArgument("failed to read config".to_string()))?;
-    toml::from_str(&raw).map_err(|_| AppError::InvalidArgument("invalid config TOML".to_string()))
-}
-
-fn save_config(config: &AppConfig) -> AppResult<()> {
-    let path = config_path()?;
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|_| AppError::InvalidArgument("failed to create config dir".to_string()))?;
-    }
-
-    let raw = toml::to_string(config)
-        .map_err(|_| AppError::InvalidArgument("failed to encode config".to_string()))?;
-    fs::write(path, raw)
-        .map_err(|_| AppError::InvalidArgument("failed to write config".to_string()))
-}
-
-fn print_json<T: Serialize>(value: &T) {
-    if let Ok(rendered) = serde_json::to_string(value) {
-        println!("{rendered}");
-    } else {
-        let payload = ErrorJson {
-            ok: false,
-            error: "JSON serialization failed".to_string(),
-            code: AppError::Serialize.code().to_string(),
-        };
-        if let Ok(rendered) = serde_json::to_string(&payload) {
-            println!("{rendered}");
-        } else {
-            println!(
-                "{{\"ok\":false,\"error\":\"JSON serialization failed\",\"code\":\"SERIALIZE\"}}"
-            );
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
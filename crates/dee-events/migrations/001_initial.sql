@@ -0,0 +1,36 @@
+CREATE TABLE events (
+  provider TEXT NOT NULL,
+  event_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  description TEXT NOT NULL DEFAULT '',
+  start TEXT NOT NULL DEFAULT '',
+  end TEXT NOT NULL DEFAULT '',
+  status TEXT NOT NULL DEFAULT '',
+  url TEXT NOT NULL DEFAULT '',
+  city TEXT NOT NULL DEFAULT '',
+  venue TEXT NOT NULL DEFAULT '',
+  first_seen TEXT NOT NULL,
+  last_seen TEXT NOT NULL,
+  PRIMARY KEY (provider, event_id)
+);
+
+CREATE TABLE saved_searches (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  name TEXT NOT NULL UNIQUE,
+  provider TEXT NOT NULL DEFAULT '',
+  city TEXT NOT NULL,
+  query TEXT NOT NULL DEFAULT '',
+  category TEXT NOT NULL DEFAULT '',
+  result_limit INTEGER NOT NULL,
+  dates TEXT NOT NULL DEFAULT '{}',
+  created_at TEXT NOT NULL,
+  last_run TEXT NOT NULL DEFAULT ''
+);
+
+CREATE TABLE saved_search_seen (
+  search_id INTEGER NOT NULL REFERENCES saved_searches(id) ON DELETE CASCADE,
+  provider TEXT NOT NULL,
+  event_id TEXT NOT NULL,
+  first_seen TEXT NOT NULL,
+  PRIMARY KEY (search_id, provider, event_id)
+);
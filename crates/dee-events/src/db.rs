@@ -0,0 +1,244 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite_migration::{Migrations, M};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{AppError, DateArgs, EventItem, Provider};
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SavedSearch {
+    pub id: i64,
+    pub name: String,
+    /// Empty when the search follows the configured/default provider
+    pub provider: String,
+    pub city: String,
+    pub query: String,
+    pub category: String,
+    pub limit: usize,
+    pub dates: DateArgs,
+    pub created_at: String,
+    pub last_run: String,
+}
+
+impl SavedSearch {
+    pub fn provider(&self) -> Option<Provider> {
+        match self.provider.as_str() {
+            "ticketmaster" => Some(Provider::Ticketmaster),
+            "seatgeek" => Some(Provider::Seatgeek),
+            "eventbrite" => Some(Provider::Eventbrite),
+            _ => None,
+        }
+    }
+}
+
+pub fn db_path() -> Result<PathBuf, AppError> {
+    let base = dirs::data_dir().ok_or(AppError::DataDirMissing)?;
+    Ok(base.join("dee-events").join("events.db"))
+}
+
+fn migrations() -> Migrations<'static> {
+    Migrations::new(vec![M::up(include_str!("../migrations/001_initial.sql"))])
+}
+
+pub fn open() -> Result<Connection, AppError> {
+    let path = db_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|_| AppError::DataDirMissing)?;
+    }
+    let mut conn = Connection::open(path).map_err(|_| AppError::Database)?;
+    conn.execute_batch("PRAGMA foreign_keys = ON;")
+        .map_err(|_| AppError::Database)?;
+    migrations()
+        .to_latest(&mut conn)
+        .map_err(|_| AppError::Database)?;
+    Ok(conn)
+}
+
+/// Insert or refresh the cached copy of each event.
+pub fn upsert_events(
+    conn: &mut Connection,
+    provider: Provider,
+    items: &[EventItem],
+) -> Result<(), AppError> {
+    let now = Utc::now().to_rfc3339();
+    let tx = conn.transaction().map_err(|_| AppError::Database)?;
+    for item in items {
+        tx.execute(
+            "INSERT INTO events
+             (provider, event_id, name, description, start, end, status, url, city, venue, first_seen, last_seen)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?11)
+             ON CONFLICT(provider, event_id) DO UPDATE SET
+               name = excluded.name,
+               description = excluded.description,
+               start = excluded.start,
+               end = excluded.end,
+               status = excluded.status,
+               url = excluded.url,
+               city = excluded.city,
+               venue = excluded.venue,
+               last_seen = excluded.last_seen",
+            params![
+                provider.as_str(),
+                item.id,
+                item.name,
+                item.description,
+                item.start,
+                item.end,
+                item.status,
+                item.url,
+                item.city,
+                item.venue,
+                now
+            ],
+        )
+        .map_err(|_| AppError::Database)?;
+    }
+    tx.commit().map_err(|_| AppError::Database)
+}
+
+pub fn insert_saved(conn: &Connection, saved: &SavedSearch) -> Result<i64, AppError> {
+    let exists: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM saved_searches WHERE name = ?1",
+            params![saved.name],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|_| AppError::Database)?;
+    if exists.is_some() {
+        return Err(AppError::InvalidArgument(format!(
+            "saved search '{}' already exists; remove it first",
+            saved.name
+        )));
+    }
+
+    let dates = serde_json::to_string(&saved.dates).map_err(|_| AppError::Database)?;
+    conn.execute(
+        "INSERT INTO saved_searches
+         (name, provider, city, query, category, result_limit, dates, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            saved.name,
+            saved.provider,
+            saved.city,
+            saved.query,
+            saved.category,
+            saved.limit as i64,
+            dates,
+            saved.created_at
+        ],
+    )
+    .map_err(|_| AppError::Database)?;
+    Ok(conn.last_insert_rowid())
+}
+
+const SAVED_COLUMNS: &str =
+    "id, name, provider, city, query, category, result_limit, dates, created_at, last_run";
+
+fn map_saved(row: &rusqlite::Row<'_>) -> rusqlite::Result<SavedSearch> {
+    let limit: i64 = row.get(6)?;
+    let dates: String = row.get(7)?;
+    Ok(SavedSearch {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        provider: row.get(2)?,
+        city: row.get(3)?,
+        query: row.get(4)?,
+        category: row.get(5)?,
+        limit: limit.max(1) as usize,
+        dates: serde_json::from_str(&dates).unwrap_or_default(),
+        created_at: row.get(8)?,
+        last_run: row.get(9)?,
+    })
+}
+
+pub fn list_saved(conn: &Connection) -> Result<Vec<SavedSearch>, AppError> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {SAVED_COLUMNS} FROM saved_searches ORDER BY name"
+        ))
+        .map_err(|_| AppError::Database)?;
+    let rows = stmt
+        .query_map([], map_saved)
+        .map_err(|_| AppError::Database)?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|_| AppError::Database)
+}
+
+pub fn get_saved(conn: &Connection, name: &str) -> Result<SavedSearch, AppError> {
+    conn.query_row(
+        &format!("SELECT {SAVED_COLUMNS} FROM saved_searches WHERE name = ?1"),
+        params![name],
+        map_saved,
+    )
+    .optional()
+    .map_err(|_| AppError::Database)?
+    .ok_or_else(|| AppError::SavedNotFound(name.to_string()))
+}
+
+pub fn delete_saved(conn: &Connection, name: &str) -> Result<(), AppError> {
+    let removed = conn
+        .execute("DELETE FROM saved_searches WHERE name = ?1", params![name])
+        .map_err(|_| AppError::Database)?;
+    if removed == 0 {
+        return Err(AppError::SavedNotFound(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Return, per item, whether the saved search has not seen it yet. Records nothing.
+pub fn unseen(
+    conn: &Connection,
+    search_id: i64,
+    provider: Provider,
+    items: &[EventItem],
+) -> Result<Vec<bool>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT EXISTS(SELECT 1 FROM saved_search_seen
+             WHERE search_id = ?1 AND provider = ?2 AND event_id = ?3)",
+        )
+        .map_err(|_| AppError::Database)?;
+    items
+        .iter()
+        .map(|item| {
+            stmt.query_row(params![search_id, provider.as_str(), item.id], |row| {
+                row.get::<_, bool>(0)
+            })
+            .map(|seen| !seen)
+            .map_err(|_| AppError::Database)
+        })
+        .collect()
+}
+
+/// Record `items` as seen by the saved search and return, per item, whether it was new.
+pub fn mark_seen<'a>(
+    conn: &mut Connection,
+    search_id: i64,
+    provider: Provider,
+    items: impl IntoIterator<Item = &'a EventItem>,
+) -> Result<Vec<bool>, AppError> {
+    let now = Utc::now().to_rfc3339();
+    let tx = conn.transaction().map_err(|_| AppError::Database)?;
+    let mut fresh = Vec::new();
+    for item in items {
+        let inserted = tx
+            .execute(
+                "INSERT OR IGNORE INTO saved_search_seen (search_id, provider, event_id, first_seen)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![search_id, provider.as_str(), item.id, now],
+            )
+            .map_err(|_| AppError::Database)?;
+        fresh.push(inserted > 0);
+    }
+    tx.execute(
+        "UPDATE saved_searches SET last_run = ?1 WHERE id = ?2",
+        params![now, search_id],
+    )
+    .map_err(|_| AppError::Database)?;
+    tx.commit().map_err(|_| AppError::Database)?;
+    Ok(fresh)
+}
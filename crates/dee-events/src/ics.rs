@@ -0,0 +1,179 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+use crate::{EventItem, Provider};
+
+/// Render events as an RFC 5545 VCALENDAR document (CRLF line endings, folded lines).
+pub fn render_calendar(items: &[EventItem], provider: Provider) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//dee.ink//dee-events//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+    ];
+
+    for item in items {
+        let Some(start) = ics_time(&item.start) else {
+            // An event without a start cannot be placed on a calendar
+            continue;
+        };
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!(
+            "UID:{}@{}.dee-events",
+            escape_text(&item.id),
+            provider.as_str()
+        ));
+        lines.push(format!("DTSTAMP:{stamp}"));
+        lines.push(start.property("DTSTART"));
+        if let Some(end) = ics_time(&item.end) {
+            lines.push(end.property("DTEND"));
+        }
+        lines.push(format!("SUMMARY:{}", escape_text(&item.name)));
+
+        let location = [item.venue.as_str(), item.city.as_str()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !location.is_empty() {
+            lines.push(format!("LOCATION:{}", escape_text(&location)));
+        }
+        if !item.url.is_empty() {
+            lines.push(format!("URL:{}", item.url));
+        }
+        if !item.description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape_text(&item.description)));
+        }
+        if item.status.eq_ignore_ascii_case("cancelled")
+            || item.status.eq_ignore_ascii_case("canceled")
+        {
+            lines.push("STATUS:CANCELLED".to_string());
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&fold_line(&line));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+enum IcsTime {
+    Utc(String),
+    Date(String),
+}
+
+impl IcsTime {
+    fn property(&self, name: &str) -> String {
+        match self {
+            Self::Utc(value) => format!("{name}:{value}"),
+            Self::Date(value) => format!("{name};VALUE=DATE:{value}"),
+        }
+    }
+}
+
+/// Providers hand back RFC 3339 timestamps, naive UTC timestamps, or bare dates.
+fn ics_time(raw: &str) -> Option<IcsTime> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Some(IcsTime::Utc(
+            dt.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string(),
+        ));
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S") {
+        return Some(IcsTime::Utc(dt.format("%Y%m%dT%H%M%SZ").to_string()));
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .ok()
+        .map(|d| IcsTime::Date(d.format("%Y%m%d").to_string()))
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold content lines at 75 octets without splitting UTF-8 characters.
+fn fold_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + line.len() / 74 * 3);
+    let mut width = 0;
+    for ch in line.chars() {
+        let len = ch.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += len;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(start: &str, end: &str) -> EventItem {
+        EventItem {
+            id: "42".to_string(),
+            name: "Jazz, Blues; Night".to_string(),
+            description: "line one\nline two".to_string(),
+            start: start.to_string(),
+            end: end.to_string(),
+            status: "onsale".to_string(),
+            url: "https://example.com/e/42".to_string(),
+            city: "Austin".to_string(),
+            venue: "The Parish".to_string(),
+        }
+    }
+
+    #[test]
+    fn renders_vevent_with_escaped_text() {
+        let cal = render_calendar(
+            &[event("2024-07-01T18:00:00Z", "2024-07-01T21:00:00Z")],
+            Provider::Ticketmaster,
+        );
+        assert!(cal.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(cal.ends_with("END:VCALENDAR\r\n"));
+        assert!(cal.contains("UID:42@ticketmaster.dee-events\r\n"));
+        assert!(cal.contains("DTSTART:20240701T180000Z\r\n"));
+        assert!(cal.contains("DTEND:20240701T210000Z\r\n"));
+        assert!(cal.contains("SUMMARY:Jazz\\, Blues\\; Night\r\n"));
+        assert!(cal.contains("LOCATION:The Parish\\, Austin\r\n"));
+        assert!(cal.contains("DESCRIPTION:line one\\nline two\r\n"));
+    }
+
+    #[test]
+    fn date_only_start_uses_value_date_and_skips_missing_start() {
+        let cal = render_calendar(
+            &[event("2024-07-01", ""), event("", "")],
+            Provider::Seatgeek,
+        );
+        assert!(cal.contains("DTSTART;VALUE=DATE:20240701\r\n"));
+        assert!(!cal.contains("DTEND"));
+        assert_eq!(cal.matches("BEGIN:VEVENT").count(), 1);
+    }
+
+    #[test]
+    fn folds_long_lines_at_75_octets() {
+        let folded = fold_line(&"x".repeat(160));
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].len(), 75);
+        assert!(parts[1].starts_with(' '));
+        assert!(parts.iter().all(|p| p.len() <= 75));
+    }
+}
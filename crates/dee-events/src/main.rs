@@ -1,19 +1,35 @@
-use std::fs;
-use std::path::PathBuf;
-
-use anyhow::{Context, Result};
-use clap::{Args, Parser, Subcommand};
-use reqwest::blocking::Client;
+mod db;
+mod ics;
+mod providers;
+mod watch;
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::http::HttpArgs;
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, report, Config, ConfigError, ErrorCode, Format, OkItem, OkMessage,
+    SecretsBackend,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-const EVENTBRITE_BASE: &str = "https://www.eventbriteapi.com/v3";
+use providers::{Api, SearchQuery};
+
+/// Page size used when walking pages with `search --all`.
+const ALL_PAGE_SIZE: usize = 50;
+/// Hard stop for `search --all`, independent of `--max`.
+const ALL_MAX_PAGES: u32 = 40;
 
 #[derive(Debug, Parser)]
 #[command(
     name = "dee-events",
     version,
     about = "Local events search CLI",
-    after_help = "EXAMPLES:\n  dee-events search \"San Francisco\" --query tech --limit 10 --json\n  dee-events show 1234567890 --json\n  dee-events config set eventbrite.token <TOKEN>"
+    after_help = "EXAMPLES:\n  dee-events search \"San Francisco\" --query tech --limit 10 --json\n  dee-events search Austin --provider seatgeek --category concert --json\n  dee-events search Austin --start 2024-07-01 --end 2024-07-31 --json\n  dee-events search Austin --weekend --json\n  dee-events search Austin --query jazz --page 2 --json\n  dee-events search Austin --all --max 300 --json\n  dee-events search Austin --weekend --output ics > weekend.ics\n  dee-events saved add sf-tech --city \"San Francisco\" --query tech\n  dee-events saved run sf-tech --new-only --json\n  dee-events categories --provider seatgeek --json\n  dee-events show G5vYZ9HjWQ2Nq --provider ticketmaster --json\n  dee-events config set ticketmaster.api_key <KEY>\n  dee-events config set provider seatgeek"
 )]
 struct Cli {
     #[command(flatten)]
@@ -30,31 +46,227 @@ struct GlobalArgs {
     quiet: bool,
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
 enum Commands {
     Search(SearchArgs),
     Show(ShowArgs),
+    /// List the provider's categories and the values --category accepts
+    Categories(CategoriesArgs),
+    /// Manage and run saved searches
+    Saved(SavedArgs),
+    /// Re-run saved searches on an interval and notify about new events
+    Watch(WatchArgs),
     Config(ConfigArgs),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum Provider {
+    Ticketmaster,
+    Seatgeek,
+    Eventbrite,
+}
+
+impl Provider {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Ticketmaster => "ticketmaster",
+            Self::Seatgeek => "seatgeek",
+            Self::Eventbrite => "eventbrite",
+        }
+    }
+
+    fn credential_key(self) -> &'static str {
+        match self {
+            Self::Ticketmaster => "ticketmaster.api_key",
+            Self::Seatgeek => "seatgeek.client_id",
+            Self::Eventbrite => "eventbrite.token",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Json,
+    Jsonl,
+    Yaml,
+    Csv,
+    Table,
+    Ics,
+}
+
+impl OutputFormat {
+    /// The shared `dee_core` format; `None` for `ics`.
+    fn shared(self) -> Option<Format> {
+        match self {
+            Self::Json => Some(Format::Json),
+            Self::Jsonl => Some(Format::Jsonl),
+            Self::Yaml => Some(Format::Yaml),
+            Self::Csv => Some(Format::Csv),
+            Self::Table => Some(Format::Table),
+            Self::Ics => None,
+        }
+    }
+}
+
 #[derive(Debug, Args)]
 struct SearchArgs {
     city: String,
     #[arg(long)]
     query: Option<String>,
-    #[arg(long)]
+    /// Eventbrite date keyword (e.g. this_week); prefer --start/--end
+    #[arg(long, conflicts_with_all = ["start", "end", "today", "tomorrow", "weekend"])]
     date: Option<String>,
+    #[command(flatten)]
+    dates: DateArgs,
     #[arg(long)]
     category: Option<String>,
+    /// Results per page
     #[arg(long, default_value_t = 20)]
     limit: usize,
+    /// Page number to fetch (1-based)
+    #[arg(long, default_value_t = 1, conflicts_with = "all")]
+    page: u32,
+    /// Walk all pages and merge results (deduplicated by event id)
+    #[arg(long)]
+    all: bool,
+    /// Maximum number of events to collect with --all
+    #[arg(long, default_value_t = 200, requires = "all")]
+    max: usize,
+    /// Output format: json, jsonl, yaml, csv, table, or `ics` for a VCALENDAR document
+    #[arg(long, value_enum)]
+    output: Option<OutputFormat>,
+    /// Event provider (defaults to config `provider`, then the first with credentials)
+    #[arg(long, value_enum)]
+    provider: Option<Provider>,
+}
+
+/// Date window flags shared by `search` and `saved add`; resolved relative to the day they run.
+#[derive(Debug, Clone, Default, Args, Serialize, Deserialize, JsonSchema)]
+struct DateArgs {
+    /// First day to include (YYYY-MM-DD, today, tomorrow)
+    #[arg(long, conflicts_with_all = ["today", "tomorrow", "weekend"])]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start: Option<String>,
+    /// Last day to include (YYYY-MM-DD, today, tomorrow)
+    #[arg(long, conflicts_with_all = ["today", "tomorrow", "weekend"])]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    /// Only events happening today
+    #[arg(long, conflicts_with_all = ["tomorrow", "weekend"])]
+    #[serde(default)]
+    today: bool,
+    /// Only events happening tomorrow
+    #[arg(long, conflicts_with = "weekend")]
+    #[serde(default)]
+    tomorrow: bool,
+    /// Only events this weekend (Saturday and Sunday)
+    #[arg(long)]
+    #[serde(default)]
+    weekend: bool,
 }
 
 #[derive(Debug, Args)]
 struct ShowArgs {
     event_id: String,
+    /// Provider the event id belongs to
+    #[arg(long, value_enum)]
+    provider: Option<Provider>,
+}
+
+#[derive(Debug, Args)]
+struct CategoriesArgs {
+    /// Provider whose taxonomy to list
+    #[arg(long, value_enum)]
+    provider: Option<Provider>,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, value_enum)]
+    output: Option<Format>,
+}
+
+#[derive(Debug, Args)]
+struct SavedArgs {
+    #[command(subcommand)]
+    command: SavedCommand,
+}
+
+#[derive(Debug, Args)]
+struct WatchArgs {
+    /// Time between cycles: a number with s, m, h, or d (minimum 1m)
+    #[arg(long, default_value = "1h")]
+    interval: String,
+    /// Only watch these saved searches (repeatable; default: all)
+    #[arg(long)]
+    name: Vec<String>,
+    /// POST each new event as JSON to this URL (overrides notify.webhook)
+    #[arg(long)]
+    webhook: Option<String>,
+    /// Run this shell command per new event with the event JSON on stdin (overrides notify.command)
+    #[arg(long)]
+    notify_cmd: Option<String>,
+    /// Also notify for events found on a saved search's first run
+    #[arg(long)]
+    notify_initial: bool,
+    /// Run a single cycle and exit
+    #[arg(long)]
+    once: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum SavedCommand {
+    /// Save a named search
+    Add(SavedAddArgs),
+    /// List saved searches
+    List(SavedListArgs),
+    /// Delete a saved search and its seen-event history
+    Remove(SavedNameArgs),
+    /// Run a saved search, flagging events not seen in previous runs
+    Run(SavedRunArgs),
+}
+
+#[derive(Debug, Args)]
+struct SavedAddArgs {
+    name: String,
+    #[arg(long)]
+    city: String,
+    #[arg(long)]
+    query: Option<String>,
+    #[arg(long)]
+    category: Option<String>,
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+    #[command(flatten)]
+    dates: DateArgs,
+    /// Pin the search to a provider instead of following config
+    #[arg(long, value_enum)]
+    provider: Option<Provider>,
+}
+
+#[derive(Debug, Args)]
+struct SavedNameArgs {
+    name: String,
+}
+
+#[derive(Debug, Args)]
+struct SavedListArgs {
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, value_enum)]
+    output: Option<Format>,
+}
+
+#[derive(Debug, Args)]
+struct SavedRunArgs {
+    name: String,
+    /// Only report events not seen in previous runs
+    #[arg(long)]
+    new_only: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, value_enum)]
+    output: Option<Format>,
 }
 
 #[derive(Debug, Args)]
@@ -84,73 +296,135 @@ struct ShowFlags {
     json: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
 struct AppConfig {
+    #[serde(default)]
+    provider: Option<Provider>,
+    /// Eventbrite private token (key kept as `token` for existing configs)
     #[serde(default)]
     token: Option<String>,
+    #[serde(default)]
+    ticketmaster_key: Option<String>,
+    #[serde(default)]
+    seatgeek_client_id: Option<String>,
+    #[serde(default)]
+    notify_webhook: Option<String>,
+    #[serde(default)]
+    notify_command: Option<String>,
+    /// `plaintext` (default) or `keyring`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secrets_backend: Option<SecretsBackend>,
+    /// API roots instead of the public ones, e.g. a mock server in tests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    eventbrite_base_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ticketmaster_base_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seatgeek_base_url: Option<String>,
+}
+
+impl AppConfig {
+    fn credential(&self, provider: Provider) -> Option<&str> {
+        let value = match provider {
+            Provider::Ticketmaster => self.ticketmaster_key.as_deref(),
+            Provider::Seatgeek => self.seatgeek_client_id.as_deref(),
+            Provider::Eventbrite => self.token.as_deref(),
+        };
+        value.filter(|x| !x.trim().is_empty())
+    }
+
+    /// How to call `provider`; `AuthMissing` without its credential.
+    fn api(&self, provider: Provider) -> Result<Api<'_>, AppError> {
+        let credential = self
+            .credential(provider)
+            .ok_or(AppError::AuthMissing(provider))?;
+        let base_url = match provider {
+            Provider::Ticketmaster => self.ticketmaster_base_url.as_deref(),
+            Provider::Seatgeek => self.seatgeek_base_url.as_deref(),
+            Provider::Eventbrite => self.eventbrite_base_url.as_deref(),
+        };
+        Ok(Api::new(provider, credential, base_url))
+    }
+
+    /// Explicit flag, then configured provider, then the first provider with credentials.
+    fn resolve_provider(&self, flag: Option<Provider>) -> Provider {
+        flag.or(self.provider).unwrap_or_else(|| {
+            [
+                Provider::Ticketmaster,
+                Provider::Seatgeek,
+                Provider::Eventbrite,
+            ]
+            .into_iter()
+            .find(|p| self.credential(*p).is_some())
+            .unwrap_or(Provider::Ticketmaster)
+        })
+    }
+}
+
+/// `DEE_EVENTS_<KEY>` overrides any key in the file (`DEE_EVENTS_TOKEN`,
+/// `DEE_EVENTS_TICKETMASTER_KEY`, `DEE_EVENTS_PROVIDER`, ...). With `secrets_backend = "keyring"` the three credentials
+/// live in the OS keyring instead of the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-events")
+        .env_override("token")
+        .env_override("ticketmaster_key")
+        .env_override("seatgeek_client_id")
+        .env_override("provider")
+        .env_override("notify_webhook")
+        .env_override("notify_command")
+        .env_override("eventbrite_base_url")
+        .env_override("ticketmaster_base_url")
+        .env_override("seatgeek_base_url")
+        .secret("token")
+        .secret("ticketmaster_key")
+        .secret("seatgeek_client_id")
 }
 
 #[derive(Debug, thiserror::Error)]
 enum AppError {
-    #[error("Configuration directory not found")]
-    ConfigMissing,
-    #[error("Missing Eventbrite token. Set eventbrite.token via config set")]
-    AuthMissing,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error("Missing {} credentials. Set {} via config set", .0.as_str(), .0.credential_key())]
+    AuthMissing(Provider),
     #[error("Unknown config key: {0}")]
     InvalidConfigKey(String),
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
     #[error("HTTP request failed")]
     RequestFailed,
-    #[error("Eventbrite API returned an error")]
+    #[error("Event provider API returned an error")]
     ApiError,
     #[error("No item found")]
     NotFound,
     #[error("Response parse failed")]
     ParseFailed,
+    #[error("Data directory not found")]
+    DataDirMissing,
+    #[error("Database operation failed")]
+    Database,
+    #[error("Saved search '{0}' not found")]
+    SavedNotFound(String),
+    #[error("Notification failed: {0}")]
+    NotifyFailed(String),
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
-            Self::ConfigMissing => "CONFIG_MISSING",
-            Self::AuthMissing => "AUTH_MISSING",
+            Self::Config(err) => err.code(),
+            Self::AuthMissing(_) => "AUTH_MISSING",
             Self::InvalidConfigKey(_) | Self::InvalidArgument(_) => "INVALID_ARGUMENT",
             Self::RequestFailed => "REQUEST_FAILED",
             Self::ApiError => "API_ERROR",
-            Self::NotFound => "NOT_FOUND",
+            Self::NotFound | Self::SavedNotFound(_) => "NOT_FOUND",
             Self::ParseFailed => "PARSE_FAILED",
+            Self::DataDirMissing | Self::Database => "DATABASE_ERROR",
+            Self::NotifyFailed(_) => "NOTIFY_FAILED",
         }
     }
 }
 
-#[derive(Debug, Serialize)]
-struct OkList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct OkItem<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
-struct OkMessage {
-    ok: bool,
-    message: String,
-}
-
-#[derive(Debug, Serialize)]
-struct ErrorJson {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct EventItem {
     id: String,
     name: String,
@@ -163,71 +437,57 @@ struct EventItem {
     venue: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct EventSearchResponse {
-    events: Vec<EventNode>,
-}
-
-#[derive(Debug, Deserialize)]
-struct EventNode {
-    id: String,
-    #[serde(default)]
-    name: TextNode,
-    #[serde(default)]
-    description: TextNode,
-    #[serde(default)]
-    start: DateNode,
-    #[serde(default)]
-    end: DateNode,
-    #[serde(default)]
-    status: String,
-    #[serde(default)]
-    url: String,
-    #[serde(default)]
-    venue: VenueNode,
-}
-
-#[derive(Debug, Deserialize, Default)]
-struct TextNode {
-    #[serde(default)]
-    text: String,
+#[derive(Debug, Serialize, JsonSchema)]
+struct SavedRunItem {
+    #[serde(flatten)]
+    event: EventItem,
+    /// True when no previous run of this saved search returned the event
+    new: bool,
 }
 
-#[derive(Debug, Deserialize, Default)]
-struct DateNode {
-    #[serde(default)]
-    utc: String,
+/// `saved add --json`
+#[derive(Debug, Serialize, JsonSchema)]
+struct SavedAdded {
+    ok: bool,
+    message: String,
+    id: i64,
 }
 
-#[derive(Debug, Deserialize, Default)]
-struct VenueNode {
-    #[serde(default)]
+#[derive(Debug, Serialize, JsonSchema)]
+struct CategoryItem {
+    id: String,
     name: String,
-    #[serde(default)]
-    address: AddressNode,
-}
-
-#[derive(Debug, Deserialize, Default)]
-struct AddressNode {
-    #[serde(default)]
-    localized_area_display: String,
+    /// Name of the parent category, empty for top-level entries
+    parent: String,
+    /// Value to pass to `search --category`
+    filter_value: String,
+}
+
+/// What each command prints under `--json`, for `--schema`. `watch` prints one
+/// report per cycle.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<EventItem>("search")
+        .item::<EventItem>("show")
+        .list::<CategoryItem>("categories")
+        .custom::<SavedAdded>("saved add")
+        .list::<db::SavedSearch>("saved list")
+        .message("saved remove")
+        .list::<SavedRunItem>("saved run")
+        .custom::<watch::CycleReport>("watch")
+        .message("config set")
+        .item::<AppConfig>("config show")
 }
 
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     let result = dispatch(&cli);
     if let Err(err) = result {
-        if cli.global.json {
-            print_json(&ErrorJson {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        // `--output` is per command, so look for `--output json|jsonl` in argv
+        report(&err, dee_core::cli::wants_json());
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -235,6 +495,9 @@ fn dispatch(cli: &Cli) -> Result<(), AppError> {
     match &cli.command {
         Commands::Search(args) => cmd_search(args, &cli.global),
         Commands::Show(args) => cmd_show(args, &cli.global),
+        Commands::Categories(args) => cmd_categories(args, &cli.global),
+        Commands::Saved(args) => cmd_saved(args, &cli.global),
+        Commands::Watch(args) => watch::cmd_watch(args, &cli.global),
         Commands::Config(args) => cmd_config(args),
     }
 }
@@ -246,36 +509,49 @@ fn cmd_search(args: &SearchArgs, out: &GlobalArgs) -> Result<(), AppError> {
         ));
     }
 
-    let mut url = format!(
-        "{}/events/search/?location.address={}&expand=venue&page=1",
-        EVENTBRITE_BASE,
-        urlencoding::encode(&args.city)
-    );
-
-    if let Some(query) = &args.query {
-        url.push_str("&q=");
-        url.push_str(&urlencoding::encode(query));
-    }
-    if let Some(date) = &args.date {
-        url.push_str("&start_date.keyword=");
-        url.push_str(&urlencoding::encode(date));
+    if args.page == 0 {
+        return Err(AppError::InvalidArgument(
+            "--page must be 1 or greater".to_string(),
+        ));
     }
-    if let Some(category) = &args.category {
-        url.push_str("&categories=");
-        url.push_str(&urlencoding::encode(category));
+    if args.max == 0 || args.max > 1000 {
+        return Err(AppError::InvalidArgument(
+            "--max must be between 1 and 1000".to_string(),
+        ));
     }
-
-    let mut body: EventSearchResponse = get_json(&url, out.verbose)?;
-    body.events.truncate(args.limit);
-
-    let items: Vec<EventItem> = body.events.into_iter().map(map_event).collect();
-
-    if out.json {
-        print_json(&OkList {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    let (start, end) = resolve_date_range(&args.dates, Local::now().date_naive())?;
+
+    let cfg = config_file().load()?;
+    let provider = cfg.resolve_provider(args.provider);
+    let api = cfg.api(provider)?;
+    tracing::debug!("provider {}", provider.as_str());
+
+    let mut query = SearchQuery {
+        city: args.city.clone(),
+        query: args.query.clone(),
+        date: args.date.clone(),
+        start,
+        end,
+        category: args.category.clone(),
+        limit: args.limit,
+        page: args.page,
+    };
+    let items = if args.all {
+        query.limit = ALL_PAGE_SIZE;
+        search_all(&api, query, args.max, out)?
+    } else {
+        let mut page = providers::search(&api, &query, out)?;
+        page.items.truncate(args.limit);
+        page.items
+    };
+    cache_events(provider, &items);
+
+    if args.output == Some(OutputFormat::Ics) {
+        print!("{}", ics::render_calendar(&items, provider));
+    } else if let Some(format) =
+        Format::resolve(args.output.and_then(OutputFormat::shared), out.json)
+    {
+        print_list(&items, format);
     } else if out.quiet {
         println!("{}", items.len());
     } else {
@@ -291,11 +567,258 @@ fn cmd_search(args: &SearchArgs, out: &GlobalArgs) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Search results are cached as a side effect; a broken database must not fail the search.
+fn cache_events(provider: Provider, items: &[EventItem]) {
+    let result = db::open().and_then(|mut conn| db::upsert_events(&mut conn, provider, items));
+    if let Err(err) = result {
+        tracing::debug!("failed to cache events: {err}");
+    }
+}
+
+fn cmd_saved(args: &SavedArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    match &args.command {
+        SavedCommand::Add(input) => {
+            if input.limit == 0 || input.limit > 50 {
+                return Err(AppError::InvalidArgument(
+                    "--limit must be between 1 and 50".to_string(),
+                ));
+            }
+            // Validate the window now so a typo fails at save time, not on every run
+            resolve_date_range(&input.dates, Local::now().date_naive())?;
+
+            let conn = db::open()?;
+            let saved = db::SavedSearch {
+                id: 0,
+                name: input.name.clone(),
+                provider: input
+                    .provider
+                    .map(Provider::as_str)
+                    .unwrap_or("")
+                    .to_string(),
+                city: input.city.clone(),
+                query: input.query.clone().unwrap_or_default(),
+                category: input.category.clone().unwrap_or_default(),
+                limit: input.limit,
+                dates: input.dates.clone(),
+                created_at: chrono::Utc::now().to_rfc3339(),
+                last_run: String::new(),
+            };
+            let id = db::insert_saved(&conn, &saved)?;
+
+            if out.json {
+                print_json(&SavedAdded {
+                    ok: true,
+                    message: "Saved search added".to_string(),
+                    id,
+                });
+            } else if !out.quiet {
+                println!("Saved search '{}' added", input.name);
+            }
+            Ok(())
+        }
+        SavedCommand::List(input) => {
+            let conn = db::open()?;
+            let items = db::list_saved(&conn)?;
+            if let Some(format) = Format::resolve(input.output, out.json) {
+                print_list(&items, format);
+            } else {
+                for item in items {
+                    if out.quiet {
+                        println!("{}", item.name);
+                    } else {
+                        let last_run = if item.last_run.is_empty() {
+                            "never"
+                        } else {
+                            item.last_run.as_str()
+                        };
+                        println!("{}  city={}  last_run={last_run}", item.name, item.city);
+                    }
+                }
+            }
+            Ok(())
+        }
+        SavedCommand::Remove(input) => {
+            let conn = db::open()?;
+            db::delete_saved(&conn, &input.name)?;
+            if out.json {
+                print_json(&OkMessage {
+                    ok: true,
+                    message: "Saved search removed".to_string(),
+                });
+            } else if !out.quiet {
+                println!("Saved search '{}' removed", input.name);
+            }
+            Ok(())
+        }
+        SavedCommand::Run(input) => {
+            let mut conn = db::open()?;
+            let saved = db::get_saved(&conn, &input.name)?;
+            let cfg = config_file().load()?;
+            let mut items = run_saved(&mut conn, &saved, &cfg, out, true)?;
+            if input.new_only {
+                items.retain(|item| item.new);
+            }
+
+            if let Some(format) = Format::resolve(input.output, out.json) {
+                print_list(&items, format);
+            } else if out.quiet {
+                println!("{}", items.len());
+            } else {
+                for item in items {
+                    let marker = if item.new { "[new] " } else { "" };
+                    println!("{marker}{} ({})", item.event.name, item.event.id);
+                    println!("  {}", item.event.start);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Execute a saved search, cache the results, and flag events this search has not returned before.
+/// With `record` the results are also marked seen; otherwise the caller marks them.
+fn run_saved(
+    conn: &mut rusqlite::Connection,
+    saved: &db::SavedSearch,
+    cfg: &AppConfig,
+    out: &GlobalArgs,
+    record: bool,
+) -> Result<Vec<SavedRunItem>, AppError> {
+    let (start, end) = resolve_date_range(&saved.dates, Local::now().date_naive())?;
+    let provider = cfg.resolve_provider(saved.provider());
+    let api = cfg.api(provider)?;
+    tracing::debug!(
+        "running saved search '{}' via {}",
+        saved.name,
+        provider.as_str()
+    );
+
+    let query = SearchQuery {
+        city: saved.city.clone(),
+        query: Some(saved.query.clone()).filter(|q| !q.is_empty()),
+        date: None,
+        start,
+        end,
+        category: Some(saved.category.clone()).filter(|c| !c.is_empty()),
+        limit: saved.limit,
+        page: 1,
+    };
+    let mut page = providers::search(&api, &query, out)?;
+    page.items.truncate(saved.limit);
+
+    db::upsert_events(conn, provider, &page.items)?;
+    let fresh = if record {
+        db::mark_seen(conn, saved.id, provider, &page.items)?
+    } else {
+        db::unseen(conn, saved.id, provider, &page.items)?
+    };
+
+    Ok(page
+        .items
+        .into_iter()
+        .zip(fresh)
+        .map(|(event, new)| SavedRunItem { event, new })
+        .collect())
+}
+
+/// Walk provider pages from page 1, merging results by event id until `max` events are
+/// collected, the provider reports no more pages, or a page adds nothing new.
+fn search_all(
+    api: &Api,
+    mut query: SearchQuery,
+    max: usize,
+    out: &GlobalArgs,
+) -> Result<Vec<EventItem>, AppError> {
+    let mut seen = HashSet::new();
+    let mut items = Vec::new();
+
+    for page in 1..=ALL_MAX_PAGES {
+        query.page = page;
+        let result = providers::search(api, &query, out)?;
+        let before = items.len();
+        for item in result.items {
+            if items.len() >= max {
+                break;
+            }
+            if seen.insert(item.id.clone()) {
+                items.push(item);
+            }
+        }
+        tracing::debug!(
+            "page {page} added {} events ({} total)",
+            items.len() - before,
+            items.len()
+        );
+        if !result.has_more || items.len() >= max || items.len() == before {
+            break;
+        }
+    }
+
+    Ok(items)
+}
+
+/// Turn the date flags into an inclusive (start, end) day range relative to `today`.
+fn resolve_date_range(
+    args: &DateArgs,
+    today: NaiveDate,
+) -> Result<(Option<NaiveDate>, Option<NaiveDate>), AppError> {
+    if args.today {
+        return Ok((Some(today), Some(today)));
+    }
+    if args.tomorrow {
+        let tomorrow = today + Duration::days(1);
+        return Ok((Some(tomorrow), Some(tomorrow)));
+    }
+    if args.weekend {
+        let saturday = match today.weekday() {
+            Weekday::Sat => today,
+            Weekday::Sun => today - Duration::days(1),
+            other => today + Duration::days(5 - i64::from(other.num_days_from_monday())),
+        };
+        let start = saturday.max(today);
+        return Ok((Some(start), Some(saturday + Duration::days(1))));
+    }
+
+    let start = args
+        .start
+        .as_deref()
+        .map(|raw| parse_day(raw, "--start", today))
+        .transpose()?;
+    let end = args
+        .end
+        .as_deref()
+        .map(|raw| parse_day(raw, "--end", today))
+        .transpose()?;
+
+    if let (Some(s), Some(e)) = (start, end) {
+        if e < s {
+            return Err(AppError::InvalidArgument(format!(
+                "--end ({e}) must not be before --start ({s})"
+            )));
+        }
+    }
+
+    Ok((start, end))
+}
+
+fn parse_day(raw: &str, flag: &str, today: NaiveDate) -> Result<NaiveDate, AppError> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "today" => Ok(today),
+        "tomorrow" => Ok(today + Duration::days(1)),
+        value => NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+            AppError::InvalidArgument(format!(
+                "invalid {flag} date '{raw}'. Use YYYY-MM-DD, today, or tomorrow"
+            ))
+        }),
+    }
+}
+
 fn cmd_show(args: &ShowArgs, out: &GlobalArgs) -> Result<(), AppError> {
-    let url = format!("{}/events/{}/?expand=venue", EVENTBRITE_BASE, args.event_id);
+    let cfg = config_file().load()?;
+    let provider = cfg.resolve_provider(args.provider);
+    let api = cfg.api(provider)?;
 
-    let row: EventNode = get_json(&url, out.verbose)?;
-    let item = map_event(row);
+    let item = providers::show(&api, &args.event_id, out)?;
 
     if out.json {
         print_json(&OkItem { ok: true, item });
@@ -321,61 +844,67 @@ fn cmd_show(args: &ShowArgs, out: &GlobalArgs) -> Result<(), AppError> {
     Ok(())
 }
 
-fn get_json<T: for<'de> Deserialize<'de>>(url: &str, verbose: bool) -> Result<T, AppError> {
-    let cfg = load_config().map_err(|_| AppError::ConfigMissing)?;
-    let token = cfg
-        .token
-        .filter(|x| !x.trim().is_empty())
-        .ok_or(AppError::AuthMissing)?;
+fn cmd_categories(args: &CategoriesArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    let cfg = config_file().load()?;
+    let provider = cfg.resolve_provider(args.provider);
+    let api = cfg.api(provider)?;
 
-    if verbose {
-        eprintln!("debug: GET {url}");
-    }
-
-    let client = Client::builder()
-        .user_agent("dee-events/0.1.0 (https://dee.ink)")
-        .build()
-        .map_err(|_| AppError::RequestFailed)?;
+    let items = providers::categories(&api, out)?;
 
-    let response = client
-        .get(url)
-        .bearer_auth(token)
-        .send()
-        .map_err(|_| AppError::RequestFailed)?;
-
-    if response.status().as_u16() == 404 {
-        return Err(AppError::NotFound);
-    }
-    if !response.status().is_success() {
-        return Err(AppError::ApiError);
+    if let Some(format) = Format::resolve(args.output, out.json) {
+        print_list(&items, format);
+    } else if out.quiet {
+        for item in items {
+            println!("{}", item.filter_value);
+        }
+    } else {
+        for item in items {
+            if item.parent.is_empty() {
+                println!(
+                    "{} ({})  --category {}",
+                    item.name, item.id, item.filter_value
+                );
+            } else {
+                println!(
+                    "  {} ({})  --category {}",
+                    item.name, item.id, item.filter_value
+                );
+            }
+        }
     }
 
-    response.json().map_err(|_| AppError::ParseFailed)
-}
-
-fn map_event(row: EventNode) -> EventItem {
-    EventItem {
-        id: row.id,
-        name: row.name.text,
-        description: row.description.text,
-        start: row.start.utc,
-        end: row.end.utc,
-        status: row.status,
-        url: row.url,
-        city: row.venue.address.localized_area_display,
-        venue: row.venue.name,
-    }
+    Ok(())
 }
 
 fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
     match &args.command {
         ConfigCommand::Set(input) => {
-            let mut cfg = load_config().unwrap_or_default();
+            let mut cfg = config_file().load_file()?;
             match input.key.as_str() {
+                "provider" => {
+                    let provider = Provider::from_str(&input.value, true).map_err(|_| {
+                        AppError::InvalidArgument(format!(
+                            "unknown provider '{}'; expected ticketmaster, seatgeek, or eventbrite",
+                            input.value
+                        ))
+                    })?;
+                    cfg.provider = Some(provider);
+                }
                 "eventbrite.token" | "token" => cfg.token = Some(input.value.clone()),
+                "ticketmaster.api_key" => cfg.ticketmaster_key = Some(input.value.clone()),
+                "seatgeek.client_id" => cfg.seatgeek_client_id = Some(input.value.clone()),
+                "notify.webhook" => cfg.notify_webhook = Some(input.value.clone()),
+                "notify.command" => cfg.notify_command = Some(input.value.clone()),
+                "eventbrite.base_url" => cfg.eventbrite_base_url = Some(input.value.clone()),
+                "ticketmaster.base_url" => cfg.ticketmaster_base_url = Some(input.value.clone()),
+                "seatgeek.base_url" => cfg.seatgeek_base_url = Some(input.value.clone()),
+                "secrets_backend" => {
+                    cfg.secrets_backend =
+                        Some(input.value.parse().map_err(AppError::InvalidArgument)?)
+                }
                 other => return Err(AppError::InvalidConfigKey(other.to_string())),
             }
-            save_config(&cfg).map_err(|_| AppError::ConfigMissing)?;
+            config_file().save(&cfg)?;
 
             if input.output.json {
                 print_json(&OkMessage {
@@ -388,91 +917,41 @@ fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
             Ok(())
         }
         ConfigCommand::Show(flags) => {
-            let cfg = load_config().unwrap_or_default();
+            let cfg = config_file().load()?;
             if flags.json {
                 print_json(&OkItem {
                     ok: true,
                     item: cfg,
                 });
             } else {
-                let state = cfg.token.as_deref().map(|_| "set").unwrap_or("missing");
-                println!("token: {state}");
+                let provider = cfg.provider.map(Provider::as_str).unwrap_or("auto");
+                println!("provider: {provider}");
+                for p in [
+                    Provider::Ticketmaster,
+                    Provider::Seatgeek,
+                    Provider::Eventbrite,
+                ] {
+                    let state = cfg.credential(p).map(|_| "set").unwrap_or("missing");
+                    println!("{}: {state}", p.credential_key());
+                }
+                println!(
+                    "notify.webhook: {}",
+                    cfg.notify_webhook.as_deref().unwrap_or("")
+                );
+                println!(
+                    "notify.command: {}",
+                    cfg.notify_command.as_deref().unwrap_or("")
+                );
+                println!(
+                    "secrets_backend: {}",
+                    cfg.secrets_backend.unwrap_or_default().as_str()
+                );
             }
             Ok(())
         }
         ConfigCommand::Path => {
-            println!("{}", config_path().display());
+            println!("{}", config_file().path().display());
             Ok(())
         }
     }
 }
-
-fn config_path() -> PathBuf {
-    let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-    path.push("dee-events");
-    path.push("config.toml");
-    path
-}
-
-fn load_config() -> Result<AppConfig> {
-    let path = config_path();
-    if !path.exists() {
-        return Ok(AppConfig::default());
-    }
-
-    let content = fs::read_to_string(&path)
-        .with_context(|| format!("failed reading config at {}", path.display()))?;
-    toml::from_str(&content).context("failed parsing config")
-}
-
-fn save_config(cfg: &AppConfig) -> Result<()> {
-    let path = config_path();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    fs::write(&path, toml::to_string_pretty(cfg)?)?;
-    Ok(())
-}
-
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(text) => println!("{text}"),
-        Err(_) => {
-            println!(
-                "{{\"ok\":false,\"error\":\"serialization failed\",\"code\":\"INTERNAL_ERROR\"}}"
-            );
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -0,0 +1,671 @@
+use chrono::NaiveDate;
+use dee_core::http::{base_url, Http};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::Deserialize;
+
+use crate::{AppError, CategoryItem, EventItem, GlobalArgs, Provider};
+
+const EVENTBRITE_BASE: &str = "https://www.eventbriteapi.com/v3";
+const TICKETMASTER_BASE: &str = "https://app.ticketmaster.com/discovery/v2";
+const SEATGEEK_BASE: &str = "https://api.seatgeek.com/2";
+
+/// A provider with the credential and API root to call it with.
+#[derive(Debug)]
+pub struct Api<'a> {
+    pub provider: Provider,
+    pub credential: &'a str,
+    pub base_url: String,
+}
+
+impl<'a> Api<'a> {
+    /// `configured` replaces the provider's public API root, e.g. with a mock server.
+    pub fn new(provider: Provider, credential: &'a str, configured: Option<&str>) -> Self {
+        let default = match provider {
+            Provider::Eventbrite => EVENTBRITE_BASE,
+            Provider::Ticketmaster => TICKETMASTER_BASE,
+            Provider::Seatgeek => SEATGEEK_BASE,
+        };
+        Self {
+            provider,
+            credential,
+            base_url: base_url(configured, default),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    pub city: String,
+    pub query: Option<String>,
+    pub date: Option<String>,
+    /// Inclusive day range; either side may be open.
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+    pub category: Option<String>,
+    /// Page size requested from the provider (Eventbrite ignores this and always returns 50).
+    pub limit: usize,
+    /// 1-based page number; translated to each provider's own numbering.
+    pub page: u32,
+}
+
+#[derive(Debug)]
+pub struct SearchPage {
+    pub items: Vec<EventItem>,
+    pub has_more: bool,
+}
+
+pub fn search(api: &Api, q: &SearchQuery, out: &GlobalArgs) -> Result<SearchPage, AppError> {
+    match api.provider {
+        Provider::Eventbrite => eventbrite_search(api, q, out),
+        Provider::Ticketmaster => ticketmaster_search(api, q, out),
+        Provider::Seatgeek => seatgeek_search(api, q, out),
+    }
+}
+
+pub fn show(api: &Api, event_id: &str, out: &GlobalArgs) -> Result<EventItem, AppError> {
+    match api.provider {
+        Provider::Eventbrite => eventbrite_show(api, event_id, out),
+        Provider::Ticketmaster => ticketmaster_show(api, event_id, out),
+        Provider::Seatgeek => seatgeek_show(api, event_id, out),
+    }
+}
+
+pub fn categories(api: &Api, out: &GlobalArgs) -> Result<Vec<CategoryItem>, AppError> {
+    match api.provider {
+        Provider::Eventbrite => eventbrite_categories(api, out),
+        Provider::Ticketmaster => ticketmaster_categories(api, out),
+        Provider::Seatgeek => seatgeek_categories(api, out),
+    }
+}
+
+fn reject_date_keyword(provider: Provider, q: &SearchQuery) -> Result<(), AppError> {
+    if q.date.is_some() {
+        return Err(AppError::InvalidArgument(format!(
+            "--date keywords are only supported by the eventbrite provider, not {}; use --start/--end or --today/--tomorrow/--weekend",
+            provider.as_str()
+        )));
+    }
+    Ok(())
+}
+
+// --- Eventbrite ---
+
+#[derive(Debug, Deserialize)]
+struct EventbriteSearchResponse {
+    events: Vec<EventbriteEvent>,
+    #[serde(default)]
+    pagination: EventbritePagination,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct EventbritePagination {
+    #[serde(default)]
+    has_more_items: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventbriteEvent {
+    id: String,
+    #[serde(default)]
+    name: EventbriteText,
+    #[serde(default)]
+    description: EventbriteText,
+    #[serde(default)]
+    start: EventbriteDate,
+    #[serde(default)]
+    end: EventbriteDate,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    venue: EventbriteVenue,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct EventbriteText {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct EventbriteDate {
+    #[serde(default)]
+    utc: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct EventbriteVenue {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    address: EventbriteAddress,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct EventbriteAddress {
+    #[serde(default)]
+    localized_area_display: String,
+}
+
+fn eventbrite_search(api: &Api, q: &SearchQuery, out: &GlobalArgs) -> Result<SearchPage, AppError> {
+    let mut url = format!(
+        "{}/events/search/?location.address={}&expand=venue&page={}",
+        api.base_url,
+        urlencoding::encode(&q.city),
+        q.page
+    );
+
+    if let Some(query) = &q.query {
+        url.push_str("&q=");
+        url.push_str(&urlencoding::encode(query));
+    }
+    if let Some(date) = &q.date {
+        url.push_str("&start_date.keyword=");
+        url.push_str(&urlencoding::encode(date));
+    }
+    if let Some(start) = q.start {
+        url.push_str(&format!("&start_date.range_start={start}T00:00:00"));
+    }
+    if let Some(end) = q.end {
+        url.push_str(&format!("&start_date.range_end={end}T23:59:59"));
+    }
+    if let Some(category) = &q.category {
+        url.push_str("&categories=");
+        url.push_str(&urlencoding::encode(category));
+    }
+
+    let body: EventbriteSearchResponse =
+        get_json(client(out)?.get(&url).bearer_auth(api.credential), &url)?;
+    Ok(SearchPage {
+        items: body.events.into_iter().map(map_eventbrite).collect(),
+        has_more: body.pagination.has_more_items,
+    })
+}
+
+fn eventbrite_show(api: &Api, event_id: &str, out: &GlobalArgs) -> Result<EventItem, AppError> {
+    let url = format!(
+        "{}/events/{}/?expand=venue",
+        api.base_url,
+        urlencoding::encode(event_id)
+    );
+    let row: EventbriteEvent = get_json(client(out)?.get(&url).bearer_auth(api.credential), &url)?;
+    Ok(map_eventbrite(row))
+}
+
+fn map_eventbrite(row: EventbriteEvent) -> EventItem {
+    EventItem {
+        id: row.id,
+        name: row.name.text,
+        description: row.description.text,
+        start: row.start.utc,
+        end: row.end.utc,
+        status: row.status,
+        url: row.url,
+        city: row.venue.address.localized_area_display,
+        venue: row.venue.name,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct EventbriteCategoriesResponse {
+    #[serde(default)]
+    categories: Vec<EventbriteCategory>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventbriteCategory {
+    id: String,
+    #[serde(default)]
+    name: String,
+}
+
+/// Eventbrite filters by numeric category id.
+fn eventbrite_categories(api: &Api, out: &GlobalArgs) -> Result<Vec<CategoryItem>, AppError> {
+    let url = format!("{}/categories/", api.base_url);
+    let body: EventbriteCategoriesResponse =
+        get_json(client(out)?.get(&url).bearer_auth(api.credential), &url)?;
+    Ok(body
+        .categories
+        .into_iter()
+        .map(|c| CategoryItem {
+            filter_value: c.id.clone(),
+            id: c.id,
+            name: c.name,
+            parent: String::new(),
+        })
+        .collect())
+}
+
+// --- Ticketmaster Discovery ---
+
+#[derive(Debug, Deserialize)]
+struct TicketmasterSearchResponse {
+    #[serde(default, rename = "_embedded")]
+    embedded: Option<TicketmasterEvents>,
+    #[serde(default)]
+    page: TicketmasterPage,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TicketmasterPage {
+    #[serde(default)]
+    number: u32,
+    #[serde(default, rename = "totalPages")]
+    total_pages: u32,
+}
+
+/// Discovery API rejects requests where `size * page` reaches 1000.
+const TICKETMASTER_MAX_DEPTH: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+struct TicketmasterEvents {
+    #[serde(default)]
+    events: Vec<TicketmasterEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TicketmasterEvent {
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    info: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    dates: TicketmasterDates,
+    #[serde(default, rename = "_embedded")]
+    embedded: TicketmasterEventEmbedded,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TicketmasterDates {
+    #[serde(default)]
+    start: TicketmasterDate,
+    #[serde(default)]
+    end: TicketmasterDate,
+    #[serde(default)]
+    status: TicketmasterStatus,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TicketmasterDate {
+    #[serde(default, rename = "dateTime")]
+    date_time: String,
+    #[serde(default, rename = "localDate")]
+    local_date: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TicketmasterStatus {
+    #[serde(default)]
+    code: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TicketmasterEventEmbedded {
+    #[serde(default)]
+    venues: Vec<TicketmasterVenue>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TicketmasterVenue {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    city: TicketmasterCity,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TicketmasterCity {
+    #[serde(default)]
+    name: String,
+}
+
+fn ticketmaster_search(
+    api: &Api,
+    q: &SearchQuery,
+    out: &GlobalArgs,
+) -> Result<SearchPage, AppError> {
+    reject_date_keyword(Provider::Ticketmaster, q)?;
+
+    let zero_based = q.page.saturating_sub(1);
+    if (zero_based as usize + 1) * q.limit > TICKETMASTER_MAX_DEPTH {
+        return Err(AppError::InvalidArgument(format!(
+            "ticketmaster only serves the first {TICKETMASTER_MAX_DEPTH} results; lower --page or --limit"
+        )));
+    }
+
+    let mut url = format!(
+        "{}/events.json?city={}&size={}&page={}&sort=date,asc",
+        api.base_url,
+        urlencoding::encode(&q.city),
+        q.limit,
+        zero_based
+    );
+    if let Some(query) = &q.query {
+        url.push_str("&keyword=");
+        url.push_str(&urlencoding::encode(query));
+    }
+    if let Some(start) = q.start {
+        url.push_str(&format!("&startDateTime={start}T00:00:00Z"));
+    }
+    if let Some(end) = q.end {
+        url.push_str(&format!("&endDateTime={end}T23:59:59Z"));
+    }
+    if let Some(category) = &q.category {
+        url.push_str("&classificationName=");
+        url.push_str(&urlencoding::encode(category));
+    }
+
+    let request = client(out)?.get(&url).query(&[("apikey", api.credential)]);
+    let body: TicketmasterSearchResponse = get_json(request, &url)?;
+    let next_depth = (body.page.number as usize + 2) * q.limit;
+    Ok(SearchPage {
+        has_more: body.page.number + 1 < body.page.total_pages
+            && next_depth <= TICKETMASTER_MAX_DEPTH,
+        items: body
+            .embedded
+            .map(|e| e.events)
+            .unwrap_or_default()
+            .into_iter()
+            .map(map_ticketmaster)
+            .collect(),
+    })
+}
+
+fn ticketmaster_show(api: &Api, event_id: &str, out: &GlobalArgs) -> Result<EventItem, AppError> {
+    let url = format!(
+        "{}/events/{}.json",
+        api.base_url,
+        urlencoding::encode(event_id)
+    );
+    let request = client(out)?.get(&url).query(&[("apikey", api.credential)]);
+    let row: TicketmasterEvent = get_json(request, &url)?;
+    Ok(map_ticketmaster(row))
+}
+
+fn map_ticketmaster(row: TicketmasterEvent) -> EventItem {
+    let venue = row.embedded.venues.into_iter().next().unwrap_or_default();
+    let start = if row.dates.start.date_time.is_empty() {
+        row.dates.start.local_date
+    } else {
+        row.dates.start.date_time
+    };
+    let end = if row.dates.end.date_time.is_empty() {
+        row.dates.end.local_date
+    } else {
+        row.dates.end.date_time
+    };
+
+    EventItem {
+        id: row.id,
+        name: row.name,
+        description: row.info,
+        start,
+        end,
+        status: row.dates.status.code,
+        url: row.url,
+        city: venue.city.name,
+        venue: venue.name,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TicketmasterClassificationsResponse {
+    #[serde(default, rename = "_embedded")]
+    embedded: Option<TicketmasterClassifications>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TicketmasterClassifications {
+    #[serde(default)]
+    classifications: Vec<TicketmasterClassification>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TicketmasterClassification {
+    #[serde(default)]
+    segment: Option<TicketmasterSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TicketmasterSegment {
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default, rename = "_embedded")]
+    embedded: TicketmasterGenres,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TicketmasterGenres {
+    #[serde(default)]
+    genres: Vec<TicketmasterGenre>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TicketmasterGenre {
+    id: String,
+    #[serde(default)]
+    name: String,
+}
+
+/// Ticketmaster filters by classification name; segments are listed with their genres beneath.
+fn ticketmaster_categories(api: &Api, out: &GlobalArgs) -> Result<Vec<CategoryItem>, AppError> {
+    let url = format!("{}/classifications.json?size=100", api.base_url);
+    let request = client(out)?.get(&url).query(&[("apikey", api.credential)]);
+    let body: TicketmasterClassificationsResponse = get_json(request, &url)?;
+
+    let mut items = Vec::new();
+    let segments = body
+        .embedded
+        .map(|e| e.classifications)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|c| c.segment);
+    for segment in segments {
+        items.push(CategoryItem {
+            id: segment.id,
+            name: segment.name.clone(),
+            parent: String::new(),
+            filter_value: segment.name.clone(),
+        });
+        for genre in segment.embedded.genres {
+            items.push(CategoryItem {
+                id: genre.id,
+                filter_value: genre.name.clone(),
+                name: genre.name,
+                parent: segment.name.clone(),
+            });
+        }
+    }
+    Ok(items)
+}
+
+// --- SeatGeek ---
+
+#[derive(Debug, Deserialize)]
+struct SeatgeekSearchResponse {
+    #[serde(default)]
+    events: Vec<SeatgeekEvent>,
+    #[serde(default)]
+    meta: SeatgeekMeta,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct SeatgeekMeta {
+    #[serde(default)]
+    total: usize,
+    #[serde(default)]
+    page: usize,
+    #[serde(default)]
+    per_page: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct SeatgeekEvent {
+    id: u64,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    datetime_utc: String,
+    #[serde(default)]
+    enddatetime_utc: Option<String>,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    venue: SeatgeekVenue,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct SeatgeekVenue {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    city: String,
+}
+
+fn seatgeek_search(api: &Api, q: &SearchQuery, out: &GlobalArgs) -> Result<SearchPage, AppError> {
+    reject_date_keyword(Provider::Seatgeek, q)?;
+
+    let mut url = format!(
+        "{}/events?venue.city={}&per_page={}&page={}&sort=datetime_utc.asc",
+        api.base_url,
+        urlencoding::encode(&q.city),
+        q.limit,
+        q.page
+    );
+    if let Some(query) = &q.query {
+        url.push_str("&q=");
+        url.push_str(&urlencoding::encode(query));
+    }
+    // SeatGeek filters on venue-local time, which matches how people read "today"
+    if let Some(start) = q.start {
+        url.push_str(&format!("&datetime_local.gte={start}T00:00:00"));
+    }
+    if let Some(end) = q.end {
+        url.push_str(&format!("&datetime_local.lte={end}T23:59:59"));
+    }
+    if let Some(category) = &q.category {
+        url.push_str("&taxonomies.name=");
+        url.push_str(&urlencoding::encode(category));
+    }
+
+    let request = client(out)?
+        .get(&url)
+        .query(&[("client_id", api.credential)]);
+    let body: SeatgeekSearchResponse = get_json(request, &url)?;
+    Ok(SearchPage {
+        has_more: body.meta.page * body.meta.per_page < body.meta.total,
+        items: body.events.into_iter().map(map_seatgeek).collect(),
+    })
+}
+
+fn seatgeek_show(api: &Api, event_id: &str, out: &GlobalArgs) -> Result<EventItem, AppError> {
+    let url = format!("{}/events/{}", api.base_url, urlencoding::encode(event_id));
+    let request = client(out)?
+        .get(&url)
+        .query(&[("client_id", api.credential)]);
+    let row: SeatgeekEvent = get_json(request, &url)?;
+    Ok(map_seatgeek(row))
+}
+
+fn map_seatgeek(row: SeatgeekEvent) -> EventItem {
+    EventItem {
+        id: row.id.to_string(),
+        name: row.title,
+        description: row.description,
+        start: utc_suffix(row.datetime_utc),
+        end: row.enddatetime_utc.map(utc_suffix).unwrap_or_default(),
+        status: row.status,
+        url: row.url,
+        city: row.venue.city,
+        venue: row.venue.name,
+    }
+}
+
+/// SeatGeek returns naive UTC timestamps (`2026-03-01T03:00:00`); make the zone explicit.
+fn utc_suffix(ts: String) -> String {
+    if ts.is_empty() || ts.ends_with('Z') {
+        ts
+    } else {
+        format!("{ts}Z")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SeatgeekTaxonomiesResponse {
+    #[serde(default)]
+    taxonomies: Vec<SeatgeekTaxonomy>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SeatgeekTaxonomy {
+    id: u64,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    parent_id: Option<u64>,
+}
+
+/// SeatGeek filters by taxonomy name (`concert`, `nba`, ...).
+fn seatgeek_categories(api: &Api, out: &GlobalArgs) -> Result<Vec<CategoryItem>, AppError> {
+    let url = format!("{}/taxonomies", api.base_url);
+    let request = client(out)?
+        .get(&url)
+        .query(&[("client_id", api.credential)]);
+    let body: SeatgeekTaxonomiesResponse = get_json(request, &url)?;
+
+    let names: std::collections::HashMap<u64, String> = body
+        .taxonomies
+        .iter()
+        .map(|t| (t.id, t.name.clone()))
+        .collect();
+    Ok(body
+        .taxonomies
+        .into_iter()
+        .map(|t| CategoryItem {
+            id: t.id.to_string(),
+            parent: t
+                .parent_id
+                .and_then(|p| names.get(&p).cloned())
+                .unwrap_or_default(),
+            filter_value: t.name.clone(),
+            name: t.name,
+        })
+        .collect())
+}
+
+// --- HTTP ---
+
+fn client(out: &GlobalArgs) -> Result<Client, AppError> {
+    Http::new("dee-events", env!("CARGO_PKG_VERSION"))
+        .args(&out.http)
+        .blocking()
+        .map_err(|_| AppError::RequestFailed)
+}
+
+/// `url` is the request URL without credentials, used for the debug log only.
+fn get_json<T: for<'de> Deserialize<'de>>(
+    request: RequestBuilder,
+    url: &str,
+) -> Result<T, AppError> {
+    tracing::debug!("GET {url}");
+
+    let response = request.send().map_err(|_| AppError::RequestFailed)?;
+
+    if response.status().as_u16() == 404 {
+        return Err(AppError::NotFound);
+    }
+    if !response.status().is_success() {
+        return Err(AppError::ApiError);
+    }
+
+    response.json().map_err(|_| AppError::ParseFailed)
+}
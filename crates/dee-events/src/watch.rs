@@ -0,0 +1,245 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use dee_core::http::Http;
+use dee_core::ErrorCode;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    config_file, db, print_json, run_saved, AppConfig, AppError, EventItem, GlobalArgs, WatchArgs,
+};
+
+#[derive(Debug, Serialize)]
+struct Notification<'a> {
+    saved_search: &'a str,
+    event: &'a EventItem,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CycleReport {
+    ok: bool,
+    cycle: u64,
+    checked: usize,
+    new: usize,
+    notified: usize,
+    errors: Vec<CycleError>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct CycleError {
+    saved_search: String,
+    error: String,
+    code: String,
+}
+
+impl CycleError {
+    fn new(saved_search: &str, err: &AppError) -> Self {
+        Self {
+            saved_search: saved_search.to_string(),
+            error: err.to_string(),
+            code: err.code().to_string(),
+        }
+    }
+}
+
+pub fn cmd_watch(args: &WatchArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    let interval = dee_core::interval::parse(&args.interval)
+        .map_err(|err| AppError::InvalidArgument(err.to_string()))?;
+    let mut cycle = 0;
+
+    loop {
+        cycle += 1;
+        // Re-read config and saved searches each cycle so edits apply without a restart
+        let cfg = config_file().load()?;
+        let report = run_cycle(args, &cfg, cycle, out)?;
+
+        if out.json {
+            print_json(&report);
+        } else if !out.quiet {
+            println!(
+                "cycle {}: checked {} saved searches, {} new events, {} notified",
+                report.cycle, report.checked, report.new, report.notified
+            );
+            for err in &report.errors {
+                eprintln!("error: {}: {}", err.saved_search, err.error);
+            }
+        }
+
+        if args.once {
+            if report.errors.is_empty() {
+                return Ok(());
+            }
+            std::process::exit(dee_core::exit::FAILURE);
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+fn run_cycle(
+    args: &WatchArgs,
+    cfg: &AppConfig,
+    cycle: u64,
+    out: &GlobalArgs,
+) -> Result<CycleReport, AppError> {
+    let mut conn = db::open()?;
+    let searches = if args.name.is_empty() {
+        db::list_saved(&conn)?
+    } else {
+        args.name
+            .iter()
+            .map(|name| db::get_saved(&conn, name))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    if searches.is_empty() {
+        return Err(AppError::InvalidArgument(
+            "no saved searches to watch; add one with `saved add`".to_string(),
+        ));
+    }
+
+    let webhook = args
+        .webhook
+        .clone()
+        .or_else(|| cfg.notify_webhook.clone())
+        .filter(|w| !w.trim().is_empty());
+    let command = args
+        .notify_cmd
+        .clone()
+        .or_else(|| cfg.notify_command.clone())
+        .filter(|c| !c.trim().is_empty());
+
+    let mut report = CycleReport {
+        ok: true,
+        cycle,
+        checked: 0,
+        new: 0,
+        notified: 0,
+        errors: Vec::new(),
+    };
+
+    for saved in &searches {
+        report.checked += 1;
+        let items = match run_saved(&mut conn, saved, cfg, out, false) {
+            Ok(items) => items,
+            Err(err) => {
+                report.errors.push(CycleError::new(&saved.name, &err));
+                continue;
+            }
+        };
+
+        // The first run of a saved search only establishes the baseline
+        let baseline = saved.last_run.is_empty() && !args.notify_initial;
+        // Events stay unseen until every sink accepted them, so failures retry next cycle
+        let mut seen = Vec::with_capacity(items.len());
+        for item in &items {
+            if item.new {
+                report.new += 1;
+            }
+            if !item.new || baseline {
+                seen.push(&item.event);
+                continue;
+            }
+
+            let payload = Notification {
+                saved_search: &saved.name,
+                event: &item.event,
+            };
+            if webhook.is_none() && command.is_none() && !out.json && !out.quiet {
+                println!("[{}] {} ({})", saved.name, item.event.name, item.event.id);
+                println!("  {}", item.event.start);
+            }
+
+            let (used, errors) = deliver(&payload, webhook.as_deref(), command.as_deref(), out);
+            if errors.is_empty() {
+                report.notified += usize::from(used);
+                seen.push(&item.event);
+            }
+            report
+                .errors
+                .extend(errors.iter().map(|err| CycleError::new(&saved.name, err)));
+        }
+
+        let provider = cfg.resolve_provider(saved.provider());
+        if let Err(err) = db::mark_seen(&mut conn, saved.id, provider, seen) {
+            report.errors.push(CycleError::new(&saved.name, &err));
+        }
+        if baseline {
+            tracing::debug!(
+                "'{}' first run recorded {} events as baseline",
+                saved.name,
+                items.len()
+            );
+        }
+    }
+
+    report.ok = report.errors.is_empty();
+    Ok(report)
+}
+
+/// Send one notification to every configured sink. Each sink is tried even if another
+/// failed; returns whether any sink was used and the errors of those that failed.
+fn deliver(
+    payload: &Notification<'_>,
+    webhook: Option<&str>,
+    command: Option<&str>,
+    out: &GlobalArgs,
+) -> (bool, Vec<AppError>) {
+    let mut errors = Vec::new();
+    if let Some(url) = webhook {
+        if let Err(err) = post_webhook(url, payload, out) {
+            errors.push(err);
+        }
+    }
+    if let Some(cmd) = command {
+        if let Err(err) = run_command(cmd, payload) {
+            errors.push(err);
+        }
+    }
+    (webhook.is_some() || command.is_some(), errors)
+}
+
+fn post_webhook(url: &str, payload: &Notification<'_>, out: &GlobalArgs) -> Result<(), AppError> {
+    tracing::debug!("POST {url}");
+    let response = Http::new("dee-events", env!("CARGO_PKG_VERSION"))
+        .args(&out.http)
+        .blocking()
+        .map_err(|_| AppError::RequestFailed)?
+        .post(url)
+        .json(payload)
+        .send()
+        .map_err(|_| AppError::NotifyFailed("webhook request failed".to_string()))?;
+    if !response.status().is_success() {
+        return Err(AppError::NotifyFailed(format!(
+            "webhook returned HTTP {}",
+            response.status().as_u16()
+        )));
+    }
+    Ok(())
+}
+
+fn run_command(cmd: &str, payload: &Notification<'_>) -> Result<(), AppError> {
+    let body = serde_json::to_vec(payload).map_err(|_| AppError::ParseFailed)?;
+    tracing::debug!("running notify command: {cmd}");
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .env("DEE_EVENTS_SAVED_SEARCH", payload.saved_search)
+        .env("DEE_EVENTS_EVENT_ID", &payload.event.id)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| AppError::NotifyFailed(format!("failed to start notify command: {e}")))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A command that ignores stdin may close it early; that is not a failure
+        let _ = stdin.write_all(&body);
+    }
+    let status = child
+        .wait()
+        .map_err(|e| AppError::NotifyFailed(format!("notify command failed: {e}")))?;
+    if !status.success() {
+        return Err(AppError::NotifyFailed(format!(
+            "notify command exited with {status}"
+        )));
+    }
+    Ok(())
+}
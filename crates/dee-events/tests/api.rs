@@ -0,0 +1,171 @@
+use assert_cmd::Command;
+use dee_test_support::{fixture, json, MockApi, Sandbox};
+
+fn bin(sandbox: &Sandbox, api: &MockApi) -> Command {
+    let mut cmd = sandbox.command(assert_cmd::cargo::cargo_bin!("dee-events"));
+    cmd.env("DEE_EVENTS_TICKETMASTER_BASE_URL", api.url("/discovery/v2"))
+        .env("DEE_EVENTS_SEATGEEK_BASE_URL", api.url("/2"))
+        .env("DEE_EVENTS_EVENTBRITE_BASE_URL", api.url("/v3"))
+        .env("DEE_EVENTS_TICKETMASTER_KEY", "tm-test")
+        .env("DEE_EVENTS_SEATGEEK_CLIENT_ID", "sg-test")
+        .env("DEE_EVENTS_TOKEN", "eb-test")
+        .env_remove("DEE_EVENTS_PROVIDER");
+    cmd
+}
+
+#[test]
+fn ticketmaster_search_show_and_saved_runs() {
+    let api = MockApi::start();
+    api.get_json(
+        "/discovery/v2/events.json",
+        &fixture("events/ticketmaster_search.json"),
+    )
+    .get_json(
+        "/discovery/v2/events/G5vYZ9a1b2c3.json",
+        &fixture("events/ticketmaster_event.json"),
+    );
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args([
+        "search",
+        "Seattle",
+        "--query",
+        "music",
+        "--provider",
+        "ticketmaster",
+        "--json",
+    ]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][0]["venue"], "Paramount Theatre");
+    assert_eq!(parsed["items"][0]["start"], "2026-11-03T03:30:00Z");
+    assert_eq!(parsed["items"][1]["start"], "2026-12-12");
+    let query = api.requests()[0].url.query().unwrap().to_string();
+    assert!(query.contains("city=Seattle&size=20&page=0"), "{query}");
+    assert!(query.contains("keyword=music"), "{query}");
+    assert!(query.ends_with("apikey=tm-test"), "{query}");
+
+    let (code, parsed) = json(bin(&sandbox, &api).args([
+        "show",
+        "G5vYZ9a1b2c3",
+        "--provider",
+        "ticketmaster",
+        "--json",
+    ]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["item"]["description"], "All ages.");
+
+    bin(&sandbox, &api)
+        .args([
+            "saved",
+            "add",
+            "shows",
+            "--city",
+            "Seattle",
+            "--provider",
+            "ticketmaster",
+            "--json",
+        ])
+        .assert()
+        .success();
+    let (code, parsed) = json(bin(&sandbox, &api).args(["saved", "run", "shows", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][0]["new"], true);
+    let (_, parsed) =
+        json(bin(&sandbox, &api).args(["saved", "run", "shows", "--new-only", "--json"]));
+    assert_eq!(parsed["count"], 0);
+}
+
+#[test]
+fn seatgeek_categories_and_eventbrite_show() {
+    let api = MockApi::start();
+    api.get_json("/2/taxonomies", &fixture("events/seatgeek_taxonomies.json"))
+        .get_json(
+            "/v3/events/734829102/",
+            &fixture("events/eventbrite_event.json"),
+        );
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) =
+        json(bin(&sandbox, &api).args(["categories", "--provider", "seatgeek", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 3);
+    assert_eq!(parsed["items"][1]["name"], "nba");
+    assert_eq!(parsed["items"][1]["parent"], "sports");
+
+    let (code, parsed) =
+        json(bin(&sandbox, &api).args(["show", "734829102", "--provider", "eventbrite", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["item"]["name"], "Rust Meetup");
+    assert_eq!(parsed["item"]["city"], "Seattle, WA");
+    let request = api.requests().pop().unwrap();
+    assert_eq!(
+        request.headers.get("authorization").unwrap(),
+        "Bearer eb-test"
+    );
+}
+
+#[test]
+fn watch_keeps_events_unseen_until_notified() {
+    use dee_test_support::{matchers, Mock, ResponseTemplate};
+
+    let api = MockApi::start();
+    api.get_json(
+        "/discovery/v2/events.json",
+        &fixture("events/ticketmaster_search.json"),
+    )
+    .mount(
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/hooks/down"))
+            .respond_with(ResponseTemplate::new(503)),
+    )
+    .mount(
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/hooks/up"))
+            .respond_with(ResponseTemplate::new(204)),
+    );
+    let sandbox = Sandbox::new();
+    bin(&sandbox, &api)
+        .args(["saved", "add", "shows", "--city", "Seattle"])
+        .args(["--provider", "ticketmaster"])
+        .assert()
+        .success();
+    let log = sandbox.path().join("notified.log");
+    let notify_cmd = format!("echo $DEE_EVENTS_EVENT_ID >> {}", log.display());
+
+    // A failing webhook does not stop the command sink, but the events stay unseen
+    let (code, report) = json(bin(&sandbox, &api).args([
+        "watch",
+        "--once",
+        "--notify-initial",
+        "--webhook",
+        &api.url("/hooks/down"),
+        "--notify-cmd",
+        &notify_cmd,
+        "--json",
+    ]));
+    assert_eq!(code, Some(1));
+    assert_eq!(report["new"], 2);
+    assert_eq!(report["notified"], 0);
+    assert_eq!(report["errors"][0]["code"], "NOTIFY_FAILED");
+    assert_eq!(std::fs::read_to_string(&log).unwrap().lines().count(), 2);
+
+    let watch_up = || {
+        json(bin(&sandbox, &api).args([
+            "watch",
+            "--once",
+            "--webhook",
+            &api.url("/hooks/up"),
+            "--json",
+        ]))
+    };
+    let (code, report) = watch_up();
+    assert_eq!(code, Some(0));
+    assert_eq!(report["new"], 2);
+    assert_eq!(report["notified"], 2);
+
+    let (code, report) = watch_up();
+    assert_eq!(code, Some(0));
+    assert_eq!(report["new"], 0);
+}
@@ -11,3 +11,112 @@ fn emits_json_error_for_missing_auth() {
     assert_eq!(parsed["ok"], false);
     assert_eq!(parsed["code"], "AUTH_MISSING");
 }
+
+#[test]
+fn missing_auth_names_selected_provider_key() {
+    let home = tempfile::tempdir().unwrap();
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-events"));
+    cmd.env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join("config"))
+        .args(["search", "Austin", "--provider", "seatgeek", "--json"]);
+
+    let out = cmd.assert().failure().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+    assert_eq!(parsed["code"], "AUTH_MISSING");
+    assert!(parsed["error"]
+        .as_str()
+        .unwrap()
+        .contains("seatgeek.client_id"));
+}
+
+#[test]
+fn unknown_provider_in_config_is_invalid_argument() {
+    let home = tempfile::tempdir().unwrap();
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-events"));
+    cmd.env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join("config"))
+        .args(["config", "set", "provider", "meetup", "--json"]);
+
+    let out = cmd.assert().failure().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn end_before_start_is_invalid_argument() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-events"));
+    cmd.args([
+        "search",
+        "Austin",
+        "--start",
+        "2024-07-31",
+        "--end",
+        "2024-07-01",
+        "--json",
+    ]);
+
+    let out = cmd.assert().failure().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn malformed_start_date_is_invalid_argument() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-events"));
+    cmd.args(["search", "Austin", "--start", "07/01/2024", "--json"]);
+
+    let out = cmd.assert().failure().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn today_conflicts_with_weekend() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-events"));
+    cmd.args(["search", "Austin", "--today", "--weekend", "--json"]);
+
+    let out = cmd.assert().failure().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn page_zero_is_invalid_argument() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-events"));
+    cmd.args(["search", "Austin", "--page", "0", "--json"]);
+
+    let out = cmd.assert().failure().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn max_requires_all() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-events"));
+    cmd.args(["search", "Austin", "--max", "100", "--json"]);
+
+    let out = cmd.assert().failure().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn categories_without_credentials_is_auth_missing() {
+    let home = tempfile::tempdir().unwrap();
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-events"));
+    cmd.env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join("config"))
+        .args(["categories", "--provider", "ticketmaster", "--json"]);
+
+    let out = cmd.assert().failure().get_output().stdout.clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+
+    assert_eq!(parsed["code"], "AUTH_MISSING");
+}
@@ -0,0 +1,121 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn with_home(dir: &TempDir) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-events"));
+    cmd.env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .env("XDG_DATA_HOME", dir.path().join("data"));
+    cmd
+}
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let out = cmd.output().unwrap();
+    serde_json::from_slice(&out.stdout).expect("valid json")
+}
+
+#[test]
+fn add_list_remove_roundtrip() {
+    let home = TempDir::new().unwrap();
+
+    let added = json(with_home(&home).args([
+        "saved",
+        "add",
+        "sf-tech",
+        "--city",
+        "San Francisco",
+        "--query",
+        "tech",
+        "--weekend",
+        "--json",
+    ]));
+    assert_eq!(added["ok"], true);
+    assert!(added["id"].is_i64());
+
+    let listed = json(with_home(&home).args(["saved", "list", "--json"]));
+    assert_eq!(listed["count"], 1);
+    assert_eq!(listed["items"][0]["name"], "sf-tech");
+    assert_eq!(listed["items"][0]["dates"]["weekend"], true);
+
+    let removed = json(with_home(&home).args(["saved", "remove", "sf-tech", "--json"]));
+    assert_eq!(removed["ok"], true);
+
+    let listed = json(with_home(&home).args(["saved", "list", "--json"]));
+    assert_eq!(listed["count"], 0);
+}
+
+#[test]
+fn duplicate_name_is_invalid_argument() {
+    let home = TempDir::new().unwrap();
+    with_home(&home)
+        .args(["saved", "add", "dup", "--city", "Austin"])
+        .assert()
+        .success();
+
+    let parsed = json(with_home(&home).args(["saved", "add", "dup", "--city", "Austin", "--json"]));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn run_unknown_saved_search_is_not_found() {
+    let home = TempDir::new().unwrap();
+    let parsed = json(with_home(&home).args(["saved", "run", "nope", "--json"]));
+    assert_eq!(parsed["ok"], false);
+    assert_eq!(parsed["code"], "NOT_FOUND");
+}
+
+#[test]
+fn run_without_credentials_is_auth_missing() {
+    let home = TempDir::new().unwrap();
+    with_home(&home)
+        .args([
+            "saved",
+            "add",
+            "atx",
+            "--city",
+            "Austin",
+            "--provider",
+            "seatgeek",
+        ])
+        .assert()
+        .success();
+
+    let parsed = json(with_home(&home).args(["saved", "run", "atx", "--new-only", "--json"]));
+    assert_eq!(parsed["code"], "AUTH_MISSING");
+}
+
+#[test]
+fn watch_without_saved_searches_is_invalid_argument() {
+    let home = TempDir::new().unwrap();
+    let parsed = json(with_home(&home).args(["watch", "--once", "--json"]));
+    assert_eq!(parsed["ok"], false);
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn watch_rejects_short_interval() {
+    let home = TempDir::new().unwrap();
+    let parsed = json(with_home(&home).args(["watch", "--interval", "10s", "--json"]));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn watch_once_reports_per_search_errors() {
+    let home = TempDir::new().unwrap();
+    with_home(&home)
+        .args(["saved", "add", "austin", "--city", "Austin"])
+        .assert()
+        .success();
+
+    let out = with_home(&home)
+        .args(["watch", "--once", "--json"])
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).expect("valid json");
+    assert_eq!(report["ok"], false);
+    assert_eq!(report["cycle"], 1);
+    assert_eq!(report["checked"], 1);
+    assert_eq!(report["errors"][0]["saved_search"], "austin");
+    assert_eq!(report["errors"][0]["code"], "AUTH_MISSING");
+}
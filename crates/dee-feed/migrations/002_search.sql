@@ -0,0 +1,28 @@
+ALTER TABLE items ADD COLUMN content TEXT NOT NULL DEFAULT '';
+
+CREATE VIRTUAL TABLE items_fts USING fts5(
+  title,
+  summary,
+  content,
+  content='items',
+  content_rowid='id'
+);
+
+CREATE TRIGGER items_fts_insert AFTER INSERT ON items BEGIN
+  INSERT INTO items_fts (rowid, title, summary, content)
+  VALUES (new.id, new.title, new.summary, new.content);
+END;
+
+CREATE TRIGGER items_fts_delete AFTER DELETE ON items BEGIN
+  INSERT INTO items_fts (items_fts, rowid, title, summary, content)
+  VALUES ('delete', old.id, old.title, old.summary, old.content);
+END;
+
+CREATE TRIGGER items_fts_update AFTER UPDATE OF title, summary, content ON items BEGIN
+  INSERT INTO items_fts (items_fts, rowid, title, summary, content)
+  VALUES ('delete', old.id, old.title, old.summary, old.content);
+  INSERT INTO items_fts (rowid, title, summary, content)
+  VALUES (new.id, new.title, new.summary, new.content);
+END;
+
+INSERT INTO items_fts (items_fts) VALUES ('rebuild');
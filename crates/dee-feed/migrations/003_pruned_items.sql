@@ -0,0 +1,5 @@
+CREATE TABLE pruned_items (
+  feed_id INTEGER NOT NULL,
+  ext_id TEXT NOT NULL,
+  PRIMARY KEY (feed_id, ext_id)
+) WITHOUT ROWID;
@@ -0,0 +1,3 @@
+ALTER TABLE items ADD COLUMN starred INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_items_starred ON items(starred) WHERE starred = 1;
@@ -0,0 +1,3 @@
+ALTER TABLE items ADD COLUMN enclosure_url TEXT;
+ALTER TABLE items ADD COLUMN enclosure_type TEXT;
+ALTER TABLE items ADD COLUMN enclosure_length INTEGER;
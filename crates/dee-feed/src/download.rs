@@ -0,0 +1,286 @@
+//! Enclosures (podcast audio and the like): picked out of entries by `fetch`
+//! and saved to disk by `download`.
+//!
+//! A download goes to `<file>.part` first and is renamed when complete. A
+//! `.part` left by an interrupted run is continued with a `Range` request;
+//! servers that ignore ranges send the whole file and it starts over.
+
+use std::fs::{self, OpenOptions};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use dee_core::http::Http;
+use feed_rs::model::Entry;
+use reqwest::{header, StatusCode};
+use rusqlite::{params, Connection, OptionalExtension};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{AppError, DownloadArgs, GlobalFlags, TOOL};
+
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct Enclosure {
+    pub url: String,
+    /// MIME type as the feed declares it
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// Size in bytes as the feed declares it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<i64>,
+}
+
+#[derive(Serialize, JsonSchema, Debug)]
+pub struct Downloaded {
+    ok: bool,
+    id: i64,
+    url: String,
+    path: String,
+    /// Size of the saved file
+    bytes: u64,
+    /// Bytes already on disk from an earlier, interrupted run
+    resumed_from: u64,
+    /// The file was already complete; nothing was requested
+    already_present: bool,
+}
+
+/// The entry's audio or video enclosure, else the first media or
+/// `rel="enclosure"` link it has.
+pub fn from_entry(entry: &Entry) -> Option<Enclosure> {
+    let media = entry.media.iter().flat_map(|m| &m.content).filter_map(|c| {
+        Some(Enclosure {
+            url: c.url.as_ref()?.to_string(),
+            mime_type: c.content_type.as_ref().map(|t| t.to_string()),
+            length: c.size.and_then(|s| i64::try_from(s).ok()),
+        })
+    });
+    let links = entry
+        .links
+        .iter()
+        .filter(|l| l.rel.as_deref() == Some("enclosure"))
+        .map(|l| Enclosure {
+            url: l.href.clone(),
+            mime_type: l.media_type.clone(),
+            length: l.length.and_then(|s| i64::try_from(s).ok()),
+        });
+    let all: Vec<Enclosure> = media.chain(links).collect();
+    let playable = |e: &&Enclosure| {
+        e.mime_type
+            .as_deref()
+            .is_some_and(|t| t.starts_with("audio/") || t.starts_with("video/"))
+    };
+    all.iter().find(playable).or(all.first()).cloned()
+}
+
+pub async fn run(conn: &Connection, flags: &GlobalFlags, args: DownloadArgs) -> Result<()> {
+    let enclosure: Option<Option<String>> = conn
+        .query_row(
+            "SELECT enclosure_url FROM items WHERE id=?1",
+            params![args.item_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let url = enclosure
+        .ok_or_else(|| AppError::NotFound(format!("Item not found: {}", args.item_id)))?
+        .ok_or_else(|| AppError::NotFound(format!("Item {} has no enclosure", args.item_id)))?;
+
+    fs::create_dir_all(&args.out_dir)
+        .with_context(|| format!("Could not create {}", args.out_dir.display()))?;
+    let path = args.out_dir.join(file_name(args.item_id, &url));
+    let mut done = Downloaded {
+        ok: true,
+        id: args.item_id,
+        url: url.clone(),
+        path: path.display().to_string(),
+        bytes: 0,
+        resumed_from: 0,
+        already_present: false,
+    };
+    if let Ok(meta) = fs::metadata(&path) {
+        done.bytes = meta.len();
+        done.already_present = true;
+        return report(flags, &done);
+    }
+
+    let part = part_path(&path);
+    let mut offset = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+    // --timeout-secs bounds connecting and each read, not the whole transfer
+    let http = Http::new(TOOL, env!("CARGO_PKG_VERSION")).args(&flags.http);
+    let client = reqwest::Client::builder()
+        .user_agent(http.user_agent())
+        .connect_timeout(http.timeout())
+        .read_timeout(http.timeout())
+        .build()?;
+    let mut request = client.get(&url);
+    if offset > 0 {
+        request = request.header(header::RANGE, format!("bytes={offset}-"));
+    }
+    let mut response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed fetching {url}"))?;
+    match response.status() {
+        StatusCode::PARTIAL_CONTENT => done.resumed_from = offset,
+        // the .part already holds everything
+        StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => {
+            fs::rename(&part, &path)?;
+            done.bytes = offset;
+            done.resumed_from = offset;
+            return report(flags, &done);
+        }
+        status if status.is_success() => offset = 0,
+        status => {
+            return Err(AppError::RequestFailed(format!("Bad status from {url}: {status}")).into())
+        }
+    }
+
+    let total = response.content_length().map(|len| len + offset);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(offset > 0)
+        .truncate(offset == 0)
+        .open(&part)
+        .with_context(|| format!("Could not write {}", part.display()))?;
+    let mut progress = Progress::new(flags, total);
+    let mut written = offset;
+    while let Some(chunk) = response.chunk().await.with_context(|| {
+        format!(
+            "Download interrupted; run again to resume from {}",
+            part.display()
+        )
+    })? {
+        file.write_all(&chunk)?;
+        written += chunk.len() as u64;
+        progress.update(written);
+    }
+    file.flush()?;
+    progress.finish();
+    if let Some(total) = total.filter(|total| written < *total) {
+        return Err(AppError::RequestFailed(format!(
+            "Download ended at {written} of {total} bytes; run again to resume"
+        ))
+        .into());
+    }
+    fs::rename(&part, &path)?;
+    done.bytes = written;
+    report(flags, &done)
+}
+
+fn report(flags: &GlobalFlags, done: &Downloaded) -> Result<()> {
+    let text = if done.already_present {
+        format!("Already downloaded: {}", done.path)
+    } else {
+        format!("Saved {} ({} bytes)", done.path, done.bytes)
+    };
+    crate::output_q(flags, done, &text, &done.path);
+    Ok(())
+}
+
+/// `<item-id>-<last URL path segment>`: the id keeps the many podcasts whose
+/// files are all called `episode.mp3` apart.
+fn file_name(item_id: i64, url: &str) -> String {
+    let segment = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            u.path_segments()?
+                .rev()
+                .find(|s| !s.is_empty())
+                .map(str::to_string)
+        })
+        .map(|s| s.replace(['/', '\\', ':'], "_"))
+        .filter(|s| s != "." && s != "..")
+        .unwrap_or_else(|| "enclosure".to_string());
+    format!("{item_id}-{segment}")
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+/// A one-line counter on stderr, redrawn a few times a second; off for
+/// `--json`, `--quiet` and when stderr is not a terminal.
+struct Progress {
+    enabled: bool,
+    total: Option<u64>,
+    drawn: Option<Instant>,
+}
+
+impl Progress {
+    fn new(flags: &GlobalFlags, total: Option<u64>) -> Self {
+        Self {
+            enabled: !flags.json && !flags.quiet && std::io::stderr().is_terminal(),
+            total,
+            drawn: None,
+        }
+    }
+
+    fn update(&mut self, written: u64) {
+        if !self.enabled
+            || self
+                .drawn
+                .is_some_and(|at| at.elapsed() < Duration::from_millis(200))
+        {
+            return;
+        }
+        self.drawn = Some(Instant::now());
+        let mb = |bytes: u64| bytes as f64 / 1_048_576.0;
+        let line = match self.total {
+            Some(total) if total > 0 => format!(
+                "{:.1} / {:.1} MB  {:>3}%",
+                mb(written),
+                mb(total),
+                written * 100 / total
+            ),
+            _ => format!("{:.1} MB", mb(written)),
+        };
+        eprint!("\r  {line}   ");
+    }
+
+    fn finish(&self) {
+        if self.enabled && self.drawn.is_some() {
+            eprintln!();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_keep_the_url_name_behind_the_id() {
+        assert_eq!(
+            file_name(7, "https://cdn.test/show/ep-12.mp3?token=x"),
+            "7-ep-12.mp3"
+        );
+        assert_eq!(file_name(7, "https://cdn.test/audio/"), "7-audio");
+        assert_eq!(file_name(7, "https://cdn.test"), "7-enclosure");
+        assert_eq!(
+            part_path(Path::new("/tmp/7-ep.mp3")),
+            Path::new("/tmp/7-ep.mp3.part")
+        );
+    }
+
+    #[test]
+    fn prefers_audio_over_other_media() {
+        let xml = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/"><channel><title>t</title>
+<item><guid>1</guid><title>Episode</title>
+<media:content url="https://cdn.test/cover.jpg" type="image/jpeg"/>
+<enclosure url="https://cdn.test/ep.mp3" type="audio/mpeg" length="1234"/>
+</item></channel></rss>"#;
+        let feed = feed_rs::parser::parse(xml.as_bytes()).unwrap();
+        assert_eq!(
+            from_entry(&feed.entries[0]),
+            Some(Enclosure {
+                url: "https://cdn.test/ep.mp3".to_string(),
+                mime_type: Some("audio/mpeg".to_string()),
+                length: Some(1234),
+            })
+        );
+    }
+}
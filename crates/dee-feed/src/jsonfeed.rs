@@ -0,0 +1,207 @@
+//! JSON Feed (https://jsonfeed.org) 1.0 and 1.1: read by `fetch`, written by
+//! `export --format jsonfeed`.
+//!
+//! feed-rs reads JSON Feed too, but drops items' attachments into plain links
+//! and gives items without an `id` the same empty one, so this format is
+//! mapped here.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::download::Enclosure;
+use crate::{FeedDef, NewItem};
+
+const VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct JsonFeed {
+    version: String,
+    title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    home_page_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    feed_url: Option<String>,
+    items: Vec<JsonItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct JsonItem {
+    /// A string in the spec; some 1.0 feeds use numbers
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_html: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_published: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Attachment {
+    url: String,
+    mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_in_bytes: Option<i64>,
+}
+
+impl JsonFeed {
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+/// A `Content-Type` of `application/feed+json` (or any JSON), or a body that
+/// opens with `{` when the server sent none.
+pub fn detect(content_type: Option<&str>, body: &[u8]) -> bool {
+    match content_type {
+        Some(ct) if !ct.is_empty() => ct.contains("json"),
+        _ => body
+            .iter()
+            .find(|b| !b.is_ascii_whitespace())
+            .is_some_and(|b| *b == b'{'),
+    }
+}
+
+pub fn parse(body: &[u8]) -> Result<Vec<NewItem>> {
+    let feed: JsonFeed = serde_json::from_slice(body).context("Invalid JSON Feed")?;
+    if !feed.version.starts_with("https://jsonfeed.org/version/1") {
+        anyhow::bail!("Unsupported JSON Feed version: {}", feed.version);
+    }
+    Ok(feed
+        .items
+        .into_iter()
+        .filter_map(|item| {
+            let ext_id = match &item.id {
+                Value::String(id) if !id.is_empty() => id.clone(),
+                Value::Number(id) => id.to_string(),
+                _ => item.url.clone().or(item.external_url.clone())?,
+            };
+            let date = item
+                .date_published
+                .as_deref()
+                .or(item.date_modified.as_deref());
+            Some(NewItem {
+                ext_id,
+                title: item.title.unwrap_or_else(|| "Untitled".to_string()),
+                link: item.url.or(item.external_url).unwrap_or_default(),
+                summary: item.summary.unwrap_or_default(),
+                content: item.content_html.or(item.content_text).unwrap_or_default(),
+                published: date
+                    .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+                    .map(|d| d.with_timezone(&Utc).to_rfc3339())
+                    .unwrap_or_else(|| Utc::now().to_rfc3339()),
+                enclosure: item.attachments.into_iter().next().map(|a| Enclosure {
+                    url: a.url,
+                    mime_type: Some(a.mime_type),
+                    length: a.size_in_bytes,
+                }),
+            })
+        })
+        .collect())
+}
+
+/// The stored items of `feeds`, newest first, as one JSON Feed 1.1 document.
+/// A single feed keeps its name and URL; several are titled `dee-feed export`.
+pub fn export(conn: &Connection, feeds: &[FeedDef]) -> Result<JsonFeed> {
+    let placeholders = vec!["?"; feeds.len()].join(",");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT ext_id, title, url, summary, content, published, \
+         enclosure_url, enclosure_type, enclosure_length \
+         FROM items WHERE feed_id IN ({placeholders}) ORDER BY published DESC, id DESC"
+    ))?;
+    let items = stmt
+        .query_map(params_from_iter(feeds.iter().map(|f| f.id)), |row| {
+            let content: String = row.get(4)?;
+            let summary: String = row.get(3)?;
+            let url: String = row.get(2)?;
+            let enclosure: Option<String> = row.get(6)?;
+            Ok(JsonItem {
+                id: Value::String(row.get(0)?),
+                url: (!url.is_empty()).then_some(url),
+                external_url: None,
+                title: Some(row.get(1)?),
+                // the spec wants content_html or content_text on every item
+                content_text: content.is_empty().then(|| summary.clone()),
+                content_html: (!content.is_empty()).then_some(content),
+                summary: (!summary.is_empty()).then_some(summary),
+                date_published: Some(crate::normalize_iso(row.get(5)?)),
+                date_modified: None,
+                attachments: match enclosure {
+                    Some(url) => vec![Attachment {
+                        url,
+                        mime_type: row
+                            .get::<_, Option<String>>(7)?
+                            .unwrap_or_else(|| "application/octet-stream".to_string()),
+                        size_in_bytes: row.get(8)?,
+                    }],
+                    None => Vec::new(),
+                },
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let (title, feed_url) = match feeds {
+        [one] => (one.name.clone(), Some(one.url.clone())),
+        _ => ("dee-feed export".to_string(), None),
+    };
+    Ok(JsonFeed {
+        version: VERSION.to_string(),
+        title,
+        home_page_url: None,
+        feed_url,
+        items,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_1_1_items_with_attachments() {
+        let body = br#"{
+  "version": "https://jsonfeed.org/version/1.1",
+  "title": "Pod",
+  "authors": [{"name": "Dee"}],
+  "items": [
+    {"id": "a", "url": "https://pod.test/a", "title": "A", "content_text": "plain",
+     "date_published": "2026-03-01T10:00:00+02:00",
+     "attachments": [{"url": "https://pod.test/a.mp3", "mime_type": "audio/mpeg", "size_in_bytes": 99}]},
+    {"id": 7, "content_html": "<p>seven</p>", "summary": "7"},
+    {"id": "", "url": "https://pod.test/c", "content_text": ""}
+  ]
+}"#;
+        let items = parse(body).unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].ext_id, "a");
+        assert_eq!(items[0].content, "plain");
+        assert_eq!(items[0].published, "2026-03-01T08:00:00+00:00");
+        assert_eq!(items[0].enclosure.as_ref().unwrap().length, Some(99));
+        assert_eq!(items[1].ext_id, "7");
+        assert_eq!(items[1].title, "Untitled");
+        assert_eq!(items[2].ext_id, "https://pod.test/c");
+        assert!(parse(br#"{"version": "2", "title": "x", "items": []}"#).is_err());
+    }
+
+    #[test]
+    fn detects_by_type_then_body() {
+        assert!(detect(Some("application/feed+json; charset=utf-8"), b"<"));
+        assert!(!detect(Some("application/rss+xml"), b"{"));
+        assert!(detect(None, b"\n  {\"version\""));
+        assert!(!detect(Some(""), b"<?xml"));
+    }
+}
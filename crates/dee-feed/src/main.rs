@@ -1,15 +1,28 @@
-use anyhow::{anyhow, Context, Result};
-use chrono::{DateTime, Utc};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::cli::wants_json;
+use dee_core::config::{config_dir, data_dir};
+use dee_core::http::{Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{exit, print_json, print_list, report_code, style, Format, OkItem, OkList};
+use dee_core::{Config, ConfigError, ErrorCode};
 use feed_rs::parser;
 use rusqlite::{params, Connection, OptionalExtension};
 use rusqlite_migration::{Migrations, M};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
-use std::collections::HashSet;
+use serde_json::json;
 use std::fs;
 use std::path::PathBuf;
 
+mod download;
+mod jsonfeed;
+mod notify;
+mod opml;
+mod prune;
+mod rules;
+
 const TOOL: &str = "dee-feed";
 
 #[derive(Parser, Debug)]
@@ -17,7 +30,7 @@ const TOOL: &str = "dee-feed";
 #[command(version)]
 #[command(about = "dee-feed - RSS/Atom feed reader CLI")]
 #[command(
-    after_help = "EXAMPLES:\n  dee-feed add https://example.com/feed.xml --name \"Example\"\n  dee-feed list --json\n  dee-feed fetch --limit 20 --json\n  dee-feed read 1 --json\n  dee-feed export --format opml"
+    after_help = "EXAMPLES:\n  dee-feed add https://example.com/feed.xml --name \"Example\" --tag news\n  dee-feed list --tag news --json\n  dee-feed fetch --limit 20 --json\n  dee-feed fetch --notify-cmd 'jq -r .title | notify-send dee-feed'\n  dee-feed unread --json\n  dee-feed read 1 --json\n  dee-feed star 1 2\n  dee-feed download 1 --out-dir ~/Podcasts\n  dee-feed fetch --starred --json\n  dee-feed search \"rust async\" --unread --json\n  dee-feed prune --keep-days 90 --keep-per-feed 500\n  dee-feed export --format opml"
 )]
 struct Cli {
     #[command(flatten)]
@@ -30,11 +43,22 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     Add(AddArgs),
-    List,
+    List(ListArgs),
     Remove(RemoveArgs),
     Fetch(FetchArgs),
+    /// Full-text search over stored items
+    Search(SearchArgs),
+    /// Unread counts per feed from stored items, without fetching
+    Unread(UnreadArgs),
     Read(ReadArgs),
     MarkRead(MarkReadArgs),
+    /// Save an item's enclosure (podcast audio) to disk
+    Download(DownloadArgs),
+    /// Keep items for later; starred items are never pruned
+    Star(StarArgs),
+    Unstar(StarArgs),
+    /// Delete old read items and vacuum the database
+    Prune(PruneArgs),
     Export(ExportArgs),
     Import(ImportArgs),
     Config(ConfigArgs),
@@ -48,6 +72,11 @@ struct GlobalFlags {
     quiet: bool,
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Args, Debug)]
@@ -55,6 +84,16 @@ struct AddArgs {
     url: String,
     #[arg(long)]
     name: Option<String>,
+    /// Topic to group the feed under; repeatable
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+struct ListArgs {
+    /// Only feeds with this tag
+    #[arg(long)]
+    tag: Option<String>,
 }
 
 #[derive(Args, Debug)]
@@ -65,10 +104,38 @@ struct RemoveArgs {
 #[derive(Args, Debug)]
 struct FetchArgs {
     name_or_id: Option<String>,
+    /// Fetch every feed with this tag
+    #[arg(long, conflicts_with = "name_or_id")]
+    tag: Option<String>,
     #[arg(long, default_value_t = 20)]
     limit: usize,
     #[arg(long)]
     unread: bool,
+    /// Only starred items
+    #[arg(long)]
+    starred: bool,
+    /// Shell command run per new item, with the item as JSON on stdin
+    #[arg(long, value_name = "CMD")]
+    notify_cmd: Option<String>,
+    /// Webhook URL POSTed each new item as JSON
+    #[arg(long, value_name = "URL")]
+    notify_url: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct SearchArgs {
+    /// FTS5 query: words, "quoted phrases", prefix*, OR, NOT
+    query: String,
+    /// Only items from this feed (name or id)
+    #[arg(long)]
+    feed: Option<String>,
+    #[arg(long)]
+    unread: bool,
+    /// Only items published at or after this RFC 3339 time or YYYY-MM-DD date
+    #[arg(long)]
+    since: Option<String>,
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
 }
 
 #[derive(Args, Debug)]
@@ -78,21 +145,61 @@ struct ReadArgs {
 
 #[derive(Args, Debug)]
 struct MarkReadArgs {
-    name_or_id: String,
+    #[arg(required_unless_present = "tag")]
+    name_or_id: Option<String>,
+    /// Mark every feed with this tag
+    #[arg(long, conflicts_with = "name_or_id")]
+    tag: Option<String>,
     #[arg(long, default_value_t = false)]
     all: bool,
 }
 
+#[derive(Args, Debug)]
+struct UnreadArgs {
+    /// Only feeds with this tag
+    #[arg(long)]
+    tag: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct DownloadArgs {
+    item_id: i64,
+    /// Directory to save into; created if missing
+    #[arg(long, default_value = ".")]
+    out_dir: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct StarArgs {
+    #[arg(required = true)]
+    item_ids: Vec<i64>,
+}
+
+#[derive(Args, Debug)]
+struct PruneArgs {
+    /// Delete read items published more than this many days ago
+    #[arg(long)]
+    keep_days: Option<u32>,
+    /// Delete read items beyond the newest N of each feed
+    #[arg(long)]
+    keep_per_feed: Option<u32>,
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
 enum ExportFormat {
     Opml,
     Json,
+    /// The stored items as a JSON Feed 1.1 document
+    Jsonfeed,
 }
 
 #[derive(Args, Debug)]
 struct ExportArgs {
     #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
     format: ExportFormat,
+    /// With `--format jsonfeed`: only this feed's items (name or id)
+    #[arg(long)]
+    feed: Option<String>,
 }
 
 #[derive(Args, Debug)]
@@ -111,12 +218,45 @@ enum ConfigCommand {
     Show,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 struct FeedDef {
     id: i64,
     name: String,
     url: String,
     created_at: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+/// `{ok,message}` plus what the command touched
+#[derive(Serialize, JsonSchema, Debug)]
+struct ActionResponse {
+    ok: bool,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    item: Option<FeedDef>,
+}
+
+impl ActionResponse {
+    fn new(message: &str) -> Self {
+        Self {
+            ok: true,
+            message: message.to_string(),
+            id: None,
+            count: None,
+            item: None,
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema, Debug)]
+struct ConfigFile {
+    path: String,
+    content: String,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug)]
@@ -124,7 +264,18 @@ struct FeedConfig {
     feeds: Vec<FeedDef>,
 }
 
-#[derive(Serialize, Debug)]
+/// config.toml; only the sections read here
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct Settings {
+    #[serde(default)]
+    prune: Option<prune::Policy>,
+    #[serde(default)]
+    rules: Vec<rules::RuleDef>,
+    #[serde(default)]
+    notify: notify::Hooks,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
 struct FeedItem {
     id: i64,
     feed: String,
@@ -132,38 +283,165 @@ struct FeedItem {
     url: String,
     published: String,
     read: bool,
+    starred: bool,
     summary: String,
+    /// Attached media, e.g. a podcast episode; see `download`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enclosure: Option<download::Enclosure>,
+}
+
+/// An entry from a fetched feed, before it is stored
+struct NewItem {
+    ext_id: String,
+    title: String,
+    link: String,
+    summary: String,
+    content: String,
+    published: String,
+    enclosure: Option<download::Enclosure>,
+}
+
+impl NewItem {
+    fn from_entry(entry: feed_rs::model::Entry) -> Self {
+        let enclosure = download::from_entry(&entry);
+        let link = entry
+            .links
+            .iter()
+            .find(|l| l.rel.as_deref() != Some("enclosure"))
+            .or(entry.links.first())
+            .map(|l| l.href.clone())
+            .unwrap_or_default();
+        Self {
+            ext_id: entry.id,
+            title: entry
+                .title
+                .map(|t| t.content)
+                .unwrap_or_else(|| "Untitled".to_string()),
+            link,
+            summary: entry.summary.map(|s| s.content).unwrap_or_default(),
+            content: entry.content.and_then(|c| c.body).unwrap_or_default(),
+            published: entry
+                .published
+                .or(entry.updated)
+                .map(|d| d.to_rfc3339())
+                .unwrap_or_else(|| Utc::now().to_rfc3339()),
+            enclosure,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+struct UnreadCount {
+    feed: String,
+    unread: i64,
+    /// Newest unread item; null when everything is read
+    latest_published: Option<String>,
+}
+
+/// `unread --json`: the list envelope plus the unread total across feeds
+#[derive(Serialize, Debug, JsonSchema)]
+struct UnreadSummary {
+    ok: bool,
+    count: usize,
+    total: i64,
+    items: Vec<UnreadCount>,
+}
+
+/// What each command prints under `--json`, for `--schema`. `export --format
+/// opml` prints `{ok,count,opml}` instead, and `--format jsonfeed`
+/// `{ok,count,jsonfeed}`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .custom::<ActionResponse>("add")
+        .list::<FeedDef>("list")
+        .custom::<ActionResponse>("remove")
+        .list::<FeedItem>("fetch")
+        .list::<FeedItem>("search")
+        .custom::<UnreadSummary>("unread")
+        .item::<FeedItem>("read")
+        .custom::<ActionResponse>("mark-read")
+        .custom::<download::Downloaded>("download")
+        .custom::<ActionResponse>("star")
+        .custom::<ActionResponse>("unstar")
+        .custom::<prune::PruneResponse>("prune")
+        .list::<FeedDef>("export")
+        .custom::<ActionResponse>("import")
+        .item::<ConfigFile>("config show")
+}
+
+#[derive(Debug, thiserror::Error)]
+enum AppError {
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{0}")]
+    InvalidArgument(String),
+
+    #[error("Missing required argument: {0}")]
+    MissingArgument(String),
+
+    #[error("{0}")]
+    RequestFailed(String),
+
+    #[error("{0}")]
+    ConfigInvalid(String),
+}
+
+impl ErrorCode for AppError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::InvalidArgument(_) => "INVALID_ARGUMENT",
+            Self::MissingArgument(_) => "MISSING_ARGUMENT",
+            Self::RequestFailed(_) => "REQUEST_FAILED",
+            Self::ConfigInvalid(_) => "CONFIG_INVALID",
+        }
+    }
+}
+
+/// The error code for `err`; a transport failure anywhere in the chain is a network error.
+fn classify_error(err: &anyhow::Error) -> &'static str {
+    if let Some(err) = err.downcast_ref::<AppError>() {
+        return err.code();
+    }
+    if let Some(err) = err.downcast_ref::<ConfigError>() {
+        return err.code();
+    }
+    if err.chain().any(|cause| cause.is::<reqwest::Error>()) {
+        return "NETWORK_ERROR";
+    }
+    "RUNTIME_ERROR"
 }
 
 #[tokio::main]
 async fn main() {
+    schema::exit_on_schema(schemas);
     if let Err(err) = run().await {
-        let json_mode = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-        if json_mode {
-            println!(
-                "{}",
-                json!({"ok": false, "error": err.to_string(), "code": "RUNTIME_ERROR"})
-            );
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        let code = classify_error(&err);
+        report_code(&err.to_string(), code, wants_json());
+        std::process::exit(exit::for_code(code));
     }
 }
 
 async fn run() -> Result<()> {
-    let Cli { global, command } = parse_cli();
+    let Cli { global, command } = dee_core::cli::parse();
     let mut cfg = load_feeds()?;
     let mut conn = open_db()?;
 
     match command {
         Commands::Add(args) => cmd_add(&mut cfg, &global, args),
-        Commands::List => cmd_list(&cfg, &global),
+        Commands::List(args) => cmd_list(&cfg, &global, args),
         Commands::Remove(args) => cmd_remove(&mut cfg, &global, args),
         Commands::Fetch(args) => cmd_fetch(&cfg, &mut conn, &global, args).await,
+        Commands::Search(args) => cmd_search(&cfg, &mut conn, &global, args),
+        Commands::Unread(args) => cmd_unread(&cfg, &conn, &global, args),
         Commands::Read(args) => cmd_read(&cfg, &mut conn, &global, args),
         Commands::MarkRead(args) => cmd_mark_read(&cfg, &mut conn, &global, args),
-        Commands::Export(args) => cmd_export(&cfg, &global, args),
+        Commands::Download(args) => download::run(&conn, &global, args).await,
+        Commands::Star(args) => cmd_star(&mut conn, &global, args, true),
+        Commands::Unstar(args) => cmd_star(&mut conn, &global, args, false),
+        Commands::Prune(args) => cmd_prune(&mut conn, &global, args),
+        Commands::Export(args) => cmd_export(&cfg, &conn, &global, args),
         Commands::Import(args) => cmd_import(&mut cfg, &global, args),
         Commands::Config(args) => cmd_config(args, &global),
     }
@@ -172,40 +450,70 @@ async fn run() -> Result<()> {
 fn cmd_add(cfg: &mut FeedConfig, flags: &GlobalFlags, args: AddArgs) -> Result<()> {
     let next_id = cfg.feeds.iter().map(|f| f.id).max().unwrap_or(0) + 1;
     if cfg.feeds.iter().any(|f| f.url == args.url) {
-        return Err(anyhow!("Feed already exists: {}", args.url));
+        return Err(AppError::InvalidArgument(format!("Feed already exists: {}", args.url)).into());
     }
     let name = args.name.unwrap_or_else(|| format!("feed-{}", next_id));
+    let mut tags = Vec::new();
+    for tag in &args.tags {
+        let tag = normalize_tag(tag)?;
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
     let item = FeedDef {
         id: next_id,
         name,
         url: args.url,
         created_at: Utc::now().to_rfc3339(),
+        tags,
     };
     cfg.feeds.push(item.clone());
     save_feeds(cfg)?;
     output_q(
         flags,
-        json!({"ok": true, "message": "Feed added", "id": item.id, "item": item}),
+        &ActionResponse {
+            id: Some(item.id),
+            item: Some(item.clone()),
+            ..ActionResponse::new("Feed added")
+        },
         &format!("Added feed #{}", next_id),
         &format!("{}", next_id),
     );
     Ok(())
 }
 
-fn cmd_list(cfg: &FeedConfig, flags: &GlobalFlags) -> Result<()> {
-    if flags.json {
-        println!(
-            "{}",
-            json!({"ok": true, "count": cfg.feeds.len(), "items": cfg.feeds})
-        );
+fn cmd_list(cfg: &FeedConfig, flags: &GlobalFlags, args: ListArgs) -> Result<()> {
+    let feeds = match args.tag.as_deref() {
+        Some(tag) => {
+            let tag = normalize_tag(tag)?;
+            cfg.feeds
+                .iter()
+                .filter(|f| f.tags.contains(&tag))
+                .cloned()
+                .collect()
+        }
+        None => cfg.feeds.clone(),
+    };
+    if let Some(format) = Format::resolve(flags.output, flags.json) {
+        print_list(&feeds, format);
     } else if flags.quiet {
-        for f in &cfg.feeds {
+        for f in &feeds {
             println!("{}", f.id);
         }
     } else {
-        println!("{} feeds", cfg.feeds.len());
-        for f in &cfg.feeds {
-            println!("  {} {} ({})", f.id, f.name, f.url);
+        println!("{}", style::header(format!("{} feeds", feeds.len())));
+        for f in &feeds {
+            let tags = if f.tags.is_empty() {
+                String::new()
+            } else {
+                format!(" {}", style::dim(format!("#{}", f.tags.join(" #"))))
+            };
+            println!(
+                "  {} {} {}{tags}",
+                f.id,
+                f.name,
+                style::dim(format!("({})", f.url))
+            );
         }
     }
     Ok(())
@@ -217,7 +525,10 @@ fn cmd_remove(cfg: &mut FeedConfig, flags: &GlobalFlags, args: RemoveArgs) -> Re
     save_feeds(cfg)?;
     output_q(
         flags,
-        json!({"ok": true, "message": "Feed removed", "id": found.id}),
+        &ActionResponse {
+            id: Some(found.id),
+            ..ActionResponse::new("Feed removed")
+        },
         &format!("Removed {}", found.name),
         &format!("{}", found.id),
     );
@@ -230,39 +541,57 @@ async fn cmd_fetch(
     flags: &GlobalFlags,
     args: FetchArgs,
 ) -> Result<()> {
-    let scoped_feed_id: Option<i64>;
+    let scoped = args.name_or_id.is_some() || args.tag.is_some();
     let chosen = if let Some(target) = args.name_or_id.as_deref() {
-        let feed = resolve_feed(cfg, target)?;
-        scoped_feed_id = Some(feed.id);
-        vec![feed]
+        vec![resolve_feed(cfg, target)?]
+    } else if let Some(tag) = args.tag.as_deref() {
+        resolve_tag(cfg, tag)?
     } else {
-        scoped_feed_id = None;
         cfg.feeds.clone()
     };
 
+    let settings = load_settings()?;
+    let rules = rules::Rules::compile(&settings.rules)?;
+
     // Sync cache before inserts so JOIN works correctly
     sync_feeds_cache(conn, cfg)?;
 
-    let client = reqwest::Client::new();
+    let client = Http::new(TOOL, env!("CARGO_PKG_VERSION"))
+        .args(&flags.http)
+        .client()?;
+    // flags replace the configured hooks one by one
+    let hooks = notify::Hooks {
+        cmd: args.notify_cmd.clone().or(settings.notify.cmd),
+        url: args.notify_url.clone().or(settings.notify.url),
+    };
     for feed in &chosen {
-        match fetch_and_store_feed(&client, conn, feed).await {
-            Ok(()) => {}
+        match fetch_and_store_feed(&client, conn, feed, &rules).await {
+            Ok(fresh) if !hooks.is_empty() => hooks.send(&client, &fresh).await,
+            Ok(_) => {}
             Err(e) => {
-                if flags.verbose {
-                    eprintln!("warning: feed {} failed: {e}", feed.url);
-                }
+                tracing::debug!("warning: feed {} failed: {e}", feed.url);
                 // isolation: continue with remaining feeds
             }
         }
     }
+    if let Some(policy) = settings.prune {
+        match prune::run(conn, policy) {
+            Ok(pruned) => tracing::debug!("auto-prune: {}", pruned.deleted),
+            Err(e) => tracing::warn!("auto-prune skipped: {e}"),
+        }
+    }
 
     // Build query with optional feed_id and unread scopes
     let mut conditions = Vec::new();
     if args.unread {
         conditions.push("i.read = 0".to_string());
     }
-    if let Some(fid) = scoped_feed_id {
-        conditions.push(format!("i.feed_id = {fid}"));
+    if args.starred {
+        conditions.push("i.starred = 1".to_string());
+    }
+    if scoped {
+        let ids: Vec<String> = chosen.iter().map(|f| f.id.to_string()).collect();
+        conditions.push(format!("i.feed_id IN ({})", ids.join(",")));
     }
 
     let where_clause = if conditions.is_empty() {
@@ -272,38 +601,34 @@ async fn cmd_fetch(
     };
 
     let sql = format!(
-        "SELECT i.id, f.name, i.title, i.url, i.published, i.read, i.summary \
+        "SELECT i.id, f.name, i.title, i.url, i.published, i.read, i.summary, i.starred, \
+         i.enclosure_url, i.enclosure_type, i.enclosure_length \
          FROM items i JOIN feeds_cache f ON f.id=i.feed_id{where_clause} \
          ORDER BY i.published DESC LIMIT ?1"
     );
 
     let mut stmt = conn.prepare(&sql)?;
-    let rows = stmt.query_map(params![args.limit as i64], |row| {
-        Ok(FeedItem {
-            id: row.get(0)?,
-            feed: row.get(1)?,
-            title: row.get(2)?,
-            url: row.get(3)?,
-            published: normalize_iso(row.get::<_, String>(4)?),
-            read: row.get::<_, i64>(5)? == 1,
-            summary: row.get(6)?,
-        })
-    })?;
+    let rows = stmt.query_map(params![args.limit as i64], feed_item)?;
     let items: Vec<FeedItem> = rows.collect::<rusqlite::Result<Vec<_>>>()?;
 
-    if flags.json {
-        println!(
-            "{}",
-            json!({"ok": true, "count": items.len(), "items": items})
-        );
+    if let Some(format) = Format::resolve(flags.output, flags.json) {
+        print_list(&items, format);
     } else if flags.quiet {
         for item in &items {
             println!("{}", item.id);
         }
     } else {
-        println!("Fetched {} items", items.len());
+        println!(
+            "{}",
+            style::header(format!("Fetched {} items", items.len()))
+        );
         for item in &items {
-            println!("  [{}] {} ({})", item.id, item.title, item.published);
+            println!(
+                "  {} {} {}",
+                style::dim(format!("[{}]", item.id)),
+                item.title,
+                style::dim(format!("({})", item.published))
+            );
         }
     }
     Ok(())
@@ -313,48 +638,222 @@ async fn fetch_and_store_feed(
     client: &reqwest::Client,
     conn: &mut Connection,
     feed: &FeedDef,
-) -> Result<()> {
-    let body = client
+    rules: &rules::Rules,
+) -> Result<Vec<FeedItem>> {
+    let response = client
         .get(&feed.url)
         .send()
         .await
         .with_context(|| format!("Failed fetching {}", feed.url))?
         .error_for_status()
-        .with_context(|| format!("Bad status from {}", feed.url))?
+        .with_context(|| format!("Bad status from {}", feed.url))?;
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body = response
         .bytes()
         .await
         .context("Failed reading response body")?;
 
-    let parsed =
-        parser::parse(&body[..]).with_context(|| format!("Invalid feed XML: {}", feed.url))?;
+    let items = if jsonfeed::detect(content_type.as_deref(), &body) {
+        jsonfeed::parse(&body).with_context(|| format!("Invalid JSON Feed: {}", feed.url))?
+    } else {
+        parser::parse(&body[..])
+            .with_context(|| format!("Invalid feed XML: {}", feed.url))?
+            .entries
+            .into_iter()
+            .map(NewItem::from_entry)
+            .collect()
+    };
 
-    for entry in parsed.entries {
-        let ext_id = entry.id;
-        let title = entry
-            .title
-            .as_ref()
-            .map(|t| t.content.clone())
-            .unwrap_or_else(|| "Untitled".to_string());
-        let link = entry
-            .links
-            .first()
-            .map(|l| l.href.clone())
-            .unwrap_or_default();
-        let summary = entry
-            .summary
-            .as_ref()
-            .map(|s| s.content.clone())
-            .unwrap_or_default();
-        let published = entry
-            .published
-            .or(entry.updated)
-            .map(|d| d.to_rfc3339())
-            .unwrap_or_else(|| Utc::now().to_rfc3339());
-
-        conn.execute(
-            "INSERT OR IGNORE INTO items (feed_id, ext_id, title, url, summary, published, read) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0)",
-            params![feed.id, ext_id, title, link, summary, published],
+    // stored by this fetch and unread, for the notify hooks
+    let mut fresh = Vec::new();
+    for item in items {
+        let NewItem {
+            ext_id,
+            title,
+            link,
+            summary,
+            content,
+            published,
+            enclosure,
+        } = item;
+        let verdict = rules.verdict(feed, &title, &summary, &content);
+        if verdict.skip {
+            tracing::debug!("rule skipped {ext_id}");
+            continue;
+        }
+        let (enclosure_url, enclosure_type, enclosure_length) = match enclosure {
+            Some(e) => (Some(e.url), e.mime_type, e.length),
+            None => (None, None, None),
+        };
+        // items stored before enclosures were kept pick theirs up here
+        if enclosure_url.is_some() {
+            conn.execute(
+                "UPDATE items SET enclosure_url=?3, enclosure_type=?4, enclosure_length=?5 \
+                 WHERE feed_id=?1 AND ext_id=?2 AND enclosure_url IS NULL",
+                params![
+                    feed.id,
+                    ext_id,
+                    enclosure_url,
+                    enclosure_type,
+                    enclosure_length
+                ],
+            )?;
+        }
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO items (feed_id, ext_id, title, url, summary, content, published, read, starred, \
+             enclosure_url, enclosure_type, enclosure_length) \
+             SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12 \
+             WHERE NOT EXISTS (SELECT 1 FROM pruned_items WHERE feed_id=?1 AND ext_id=?2)",
+            params![
+                feed.id,
+                ext_id,
+                title,
+                link,
+                summary,
+                content,
+                published,
+                verdict.read,
+                verdict.starred,
+                enclosure_url,
+                enclosure_type,
+                enclosure_length
+            ],
         )?;
+        if inserted == 1 && !verdict.read {
+            fresh.push(FeedItem {
+                id: conn.last_insert_rowid(),
+                feed: feed.name.clone(),
+                title,
+                url: link,
+                published: normalize_iso(published),
+                read: false,
+                starred: verdict.starred,
+                summary,
+                enclosure: enclosure_url.map(|url| download::Enclosure {
+                    url,
+                    mime_type: enclosure_type,
+                    length: enclosure_length,
+                }),
+            });
+        }
+    }
+    Ok(fresh)
+}
+
+fn cmd_search(
+    cfg: &FeedConfig,
+    conn: &mut Connection,
+    flags: &GlobalFlags,
+    args: SearchArgs,
+) -> Result<()> {
+    let feed_id = match args.feed.as_deref() {
+        Some(target) => Some(resolve_feed(cfg, target)?.id),
+        None => None,
+    };
+    let since = args.since.as_deref().map(parse_since).transpose()?;
+    sync_feeds_cache(conn, cfg)?;
+
+    // Best match first; the filters ride along as nullable parameters
+    let mut stmt = conn.prepare(
+        "SELECT i.id, f.name, i.title, i.url, i.published, i.read, i.summary, i.starred, \
+         i.enclosure_url, i.enclosure_type, i.enclosure_length \
+         FROM items_fts JOIN items i ON i.id=items_fts.rowid \
+         JOIN feeds_cache f ON f.id=i.feed_id \
+         WHERE items_fts MATCH ?1 \
+         AND (?2 IS NULL OR i.feed_id=?2) \
+         AND (?3=0 OR i.read=0) \
+         AND (?4 IS NULL OR i.published>=?4) \
+         ORDER BY bm25(items_fts), i.published DESC LIMIT ?5",
+    )?;
+    let items = stmt
+        .query_map(
+            params![args.query, feed_id, args.unread, since, args.limit as i64],
+            feed_item,
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| match e {
+            // SQLITE_ERROR while matching is FTS5 rejecting the query syntax
+            rusqlite::Error::SqliteFailure(err, Some(msg))
+                if err.code == rusqlite::ErrorCode::Unknown =>
+            {
+                anyhow::Error::from(AppError::InvalidArgument(format!(
+                    "Invalid search query `{}`: {msg}",
+                    args.query
+                )))
+            }
+            e => e.into(),
+        })?;
+
+    if let Some(format) = Format::resolve(flags.output, flags.json) {
+        print_list(&items, format);
+    } else if flags.quiet {
+        for item in &items {
+            println!("{}", item.id);
+        }
+    } else {
+        println!("{}", style::header(format!("{} matches", items.len())));
+        for item in &items {
+            println!(
+                "  {} {} {}",
+                style::dim(format!("[{}]", item.id)),
+                item.title,
+                style::dim(format!("({}, {})", item.feed, item.published))
+            );
+        }
+    }
+    Ok(())
+}
+
+fn cmd_unread(
+    cfg: &FeedConfig,
+    conn: &Connection,
+    flags: &GlobalFlags,
+    args: UnreadArgs,
+) -> Result<()> {
+    let feeds = match args.tag.as_deref() {
+        Some(tag) => resolve_tag(cfg, tag)?,
+        None => cfg.feeds.clone(),
+    };
+    let mut stmt =
+        conn.prepare("SELECT COUNT(*), MAX(published) FROM items WHERE read=0 AND feed_id=?1")?;
+    let mut items = Vec::with_capacity(feeds.len());
+    for feed in &feeds {
+        let (unread, latest) = stmt.query_row(params![feed.id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+        })?;
+        items.push(UnreadCount {
+            feed: feed.name.clone(),
+            unread,
+            latest_published: latest.map(normalize_iso),
+        });
+    }
+    let total = items.iter().map(|c| c.unread).sum();
+
+    match Format::resolve(flags.output, flags.json) {
+        Some(Format::Json) => print_json(&UnreadSummary {
+            ok: true,
+            count: items.len(),
+            total,
+            items,
+        }),
+        Some(format) => print_list(&items, format),
+        None if flags.quiet => println!("{total}"),
+        None => {
+            println!("{}", style::header(format!("{total} unread")));
+            let width = items.iter().map(|c| c.feed.len()).max().unwrap_or(0);
+            for c in &items {
+                let latest = c
+                    .latest_published
+                    .as_deref()
+                    .map(|at| format!(" {}", style::dim(format!("(latest {at})"))))
+                    .unwrap_or_default();
+                println!("  {:<width$}  {:>5}{latest}", c.feed, c.unread);
+            }
+        }
     }
     Ok(())
 }
@@ -367,28 +866,20 @@ fn cmd_read(
 ) -> Result<()> {
     sync_feeds_cache(conn, cfg)?;
     let mut stmt = conn.prepare(
-        "SELECT i.id, COALESCE(f.name, ''), i.title, i.url, i.published, i.read, i.summary \
+        "SELECT i.id, COALESCE(f.name, ''), i.title, i.url, i.published, i.read, i.summary, i.starred, \
+         i.enclosure_url, i.enclosure_type, i.enclosure_length \
          FROM items i LEFT JOIN feeds_cache f ON f.id=i.feed_id WHERE i.id=?1",
     )?;
     let item: Option<FeedItem> = stmt
-        .query_row(params![args.item_id], |row| {
-            Ok(FeedItem {
-                id: row.get(0)?,
-                feed: row.get(1)?,
-                title: row.get(2)?,
-                url: row.get(3)?,
-                published: normalize_iso(row.get::<_, String>(4)?),
-                read: row.get::<_, i64>(5)? == 1,
-                summary: row.get(6)?,
-            })
-        })
+        .query_row(params![args.item_id], feed_item)
         .optional()?;
 
-    let mut item = item.ok_or_else(|| anyhow!("Item not found: {}", args.item_id))?;
+    let mut item =
+        item.ok_or_else(|| AppError::NotFound(format!("Item not found: {}", args.item_id)))?;
     conn.execute("UPDATE items SET read=1 WHERE id=?1", params![args.item_id])?;
     item.read = true;
 
-    output(flags, json!({"ok": true, "item": item}), format!("{}", args.item_id));
+    output(flags, &OkItem::new(item), format!("{}", args.item_id));
     Ok(())
 }
 
@@ -399,43 +890,116 @@ fn cmd_mark_read(
     args: MarkReadArgs,
 ) -> Result<()> {
     if !args.all {
-        return Err(anyhow!("Missing required argument: --all"));
+        return Err(AppError::MissingArgument("--all".into()).into());
+    }
+    let feeds = match (args.name_or_id.as_deref(), args.tag.as_deref()) {
+        (Some(target), _) => vec![resolve_feed(cfg, target)?],
+        (None, Some(tag)) => resolve_tag(cfg, tag)?,
+        (None, None) => {
+            return Err(AppError::MissingArgument("<name-or-id> or --tag".into()).into())
+        }
+    };
+    let mut count = 0;
+    for feed in &feeds {
+        count += conn.execute("UPDATE items SET read=1 WHERE feed_id=?1", params![feed.id])?;
     }
-    let feed = resolve_feed(cfg, &args.name_or_id)?;
-    let count = conn.execute("UPDATE items SET read=1 WHERE feed_id=?1", params![feed.id])?;
     output_q(
         flags,
-        json!({"ok": true, "message": "Marked items read", "count": count}),
+        &ActionResponse {
+            count: Some(count),
+            ..ActionResponse::new("Marked items read")
+        },
         &format!("Marked {} as read", count),
         &format!("{}", count),
     );
     Ok(())
 }
 
-fn cmd_export(cfg: &FeedConfig, flags: &GlobalFlags, args: ExportArgs) -> Result<()> {
+fn cmd_star(
+    conn: &mut Connection,
+    flags: &GlobalFlags,
+    args: StarArgs,
+    starred: bool,
+) -> Result<()> {
+    let tx = conn.transaction()?;
+    for id in &args.item_ids {
+        if tx.execute(
+            "UPDATE items SET starred=?1 WHERE id=?2",
+            params![starred, id],
+        )? == 0
+        {
+            return Err(AppError::NotFound(format!("Item not found: {id}")).into());
+        }
+    }
+    tx.commit()?;
+    let count = args.item_ids.len();
+    let message = if starred {
+        "Starred items"
+    } else {
+        "Unstarred items"
+    };
+    output_q(
+        flags,
+        &ActionResponse {
+            count: Some(count),
+            ..ActionResponse::new(message)
+        },
+        &format!("{message}: {count}"),
+        &format!("{count}"),
+    );
+    Ok(())
+}
+
+fn cmd_prune(conn: &mut Connection, flags: &GlobalFlags, args: PruneArgs) -> Result<()> {
+    // flags override config.toml limit by limit
+    let configured = load_settings()?.prune.unwrap_or_default();
+    let policy = prune::Policy {
+        keep_days: args.keep_days.or(configured.keep_days),
+        keep_per_feed: args.keep_per_feed.or(configured.keep_per_feed),
+    };
+    let pruned = prune::run(conn, policy)?;
+    output_q(
+        flags,
+        &pruned,
+        &pruned.message,
+        &format!("{}", pruned.deleted),
+    );
+    Ok(())
+}
+
+fn cmd_export(
+    cfg: &FeedConfig,
+    conn: &Connection,
+    flags: &GlobalFlags,
+    args: ExportArgs,
+) -> Result<()> {
+    if args.feed.is_some() && args.format != ExportFormat::Jsonfeed {
+        return Err(
+            AppError::InvalidArgument("--feed only applies to --format jsonfeed".into()).into(),
+        );
+    }
     match args.format {
+        ExportFormat::Jsonfeed => {
+            let feeds = match args.feed.as_deref() {
+                Some(target) => vec![resolve_feed(cfg, target)?],
+                None => cfg.feeds.clone(),
+            };
+            let document = jsonfeed::export(conn, &feeds)?;
+            if flags.json {
+                print_json(&json!({"ok": true, "count": document.len(), "jsonfeed": document}));
+            } else {
+                println!("{}", serde_json::to_string_pretty(&document)?);
+            }
+        }
         ExportFormat::Json => {
             output(
                 flags,
-                json!({"ok": true, "count": cfg.feeds.len(), "items": cfg.feeds}),
+                &OkList::new(cfg.feeds.clone()),
                 "Exported feeds".to_string(),
             );
         }
         ExportFormat::Opml => {
-            let body = cfg
-                .feeds
-                .iter()
-                .map(|f| {
-                    format!(
-                        "    <outline text=\"{}\" title=\"{}\" type=\"rss\" xmlUrl=\"{}\" />",
-                        xml_escape(&f.name),
-                        xml_escape(&f.name),
-                        xml_escape(&f.url)
-                    )
-                })
-                .collect::<Vec<_>>()
-                .join("\n");
-            let opml = format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n  <head><title>dee-feed export</title></head>\n  <body>\n{}\n  </body>\n</opml>", body);
+            let opml = opml::render(&cfg.feeds);
             if flags.json {
                 println!(
                     "{}",
@@ -452,31 +1016,34 @@ fn cmd_export(cfg: &FeedConfig, flags: &GlobalFlags, args: ExportArgs) -> Result
 fn cmd_import(cfg: &mut FeedConfig, flags: &GlobalFlags, args: ImportArgs) -> Result<()> {
     let data = fs::read_to_string(&args.file)
         .with_context(|| format!("Could not read file {}", args.file.display()))?;
-    let mut existing: HashSet<String> = cfg.feeds.iter().map(|f| f.url.clone()).collect();
-    let mut added = 0_i64;
-    for line in data.lines() {
-        if let Some(url) = parse_attr(line, "xmlUrl") {
-            if existing.contains(&url) {
-                continue;
+    let mut added = 0;
+    for sub in opml::parse(&data)? {
+        // already subscribed: only pick up the folders it sits in
+        if let Some(known) = cfg.feeds.iter_mut().find(|f| f.url == sub.url) {
+            for tag in sub.tags {
+                if !known.tags.contains(&tag) {
+                    known.tags.push(tag);
+                }
             }
-            let next_id = cfg.feeds.iter().map(|f| f.id).max().unwrap_or(0) + 1;
-            let name = parse_attr(line, "title")
-                .or_else(|| parse_attr(line, "text"))
-                .unwrap_or_else(|| format!("feed-{}", next_id));
-            cfg.feeds.push(FeedDef {
-                id: next_id,
-                name,
-                url: url.clone(),
-                created_at: Utc::now().to_rfc3339(),
-            });
-            existing.insert(url);
-            added += 1;
+            continue;
         }
+        let next_id = cfg.feeds.iter().map(|f| f.id).max().unwrap_or(0) + 1;
+        cfg.feeds.push(FeedDef {
+            id: next_id,
+            name: sub.name.unwrap_or_else(|| format!("feed-{}", next_id)),
+            url: sub.url,
+            created_at: Utc::now().to_rfc3339(),
+            tags: sub.tags,
+        });
+        added += 1;
     }
     save_feeds(cfg)?;
     output(
         flags,
-        json!({"ok": true, "message": "Import complete", "count": added}),
+        &ActionResponse {
+            count: Some(added),
+            ..ActionResponse::new("Import complete")
+        },
         format!("Imported {} feeds", added),
     );
     Ok(())
@@ -488,14 +1055,20 @@ fn cmd_config(args: ConfigArgs, global: &GlobalFlags) -> Result<()> {
             let cfg_path = config_path();
             if !cfg_path.exists() {
                 ensure_dirs()?;
-                fs::write(&cfg_path, "[general]\ndefault_format = \"table\"\n")?;
+                if let Some(parent) = cfg_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(
+                    &cfg_path,
+                    "[general]\ndefault_format = \"table\"\n\n# Prune read items after every fetch\n# [prune]\n# keep_days = 90\n# keep_per_feed = 500\n",
+                )?;
             }
             let content = fs::read_to_string(&cfg_path)?;
             if global.json {
-                println!(
-                    "{}",
-                    json!({"ok": true, "item": {"path": cfg_path.display().to_string(), "content": content}})
-                );
+                print_json(&OkItem::new(ConfigFile {
+                    path: cfg_path.display().to_string(),
+                    content,
+                }));
             } else {
                 println!("{}", content.trim_end());
             }
@@ -524,64 +1097,87 @@ fn resolve_feed(cfg: &FeedConfig, name_or_id: &str) -> Result<FeedDef> {
         .iter()
         .find(|f| f.name.to_lowercase().contains(&needle))
         .cloned();
-    fuzzy.ok_or_else(|| anyhow!("Feed not found: {name_or_id}"))
+    fuzzy.ok_or_else(|| AppError::NotFound(format!("Feed not found: {name_or_id}")).into())
+}
+
+/// Every feed tagged `tag`; an error when there are none, as for an unknown
+/// feed name.
+fn resolve_tag(cfg: &FeedConfig, tag: &str) -> Result<Vec<FeedDef>> {
+    let tag = normalize_tag(tag)?;
+    let feeds: Vec<FeedDef> = cfg
+        .feeds
+        .iter()
+        .filter(|f| f.tags.contains(&tag))
+        .cloned()
+        .collect();
+    if feeds.is_empty() {
+        return Err(AppError::NotFound(format!("No feeds tagged: {tag}")).into());
+    }
+    Ok(feeds)
+}
+
+/// Tags compare case-insensitively, so they are stored lower case.
+fn normalize_tag(raw: &str) -> Result<String> {
+    let tag = raw.trim().trim_start_matches('#').to_lowercase();
+    if tag.is_empty() || tag.contains(char::is_whitespace) {
+        return Err(AppError::InvalidArgument(format!(
+            "Invalid tag `{raw}`: expected a single word"
+        ))
+        .into());
+    }
+    Ok(tag)
 }
 
-fn output(flags: &GlobalFlags, payload: Value, text: String) {
+fn output<T: Serialize>(flags: &GlobalFlags, payload: &T, text: String) {
     output_q(flags, payload, &text, &text);
 }
 
-fn output_q(flags: &GlobalFlags, payload: Value, text: &str, quiet_text: &str) {
+fn output_q<T: Serialize>(flags: &GlobalFlags, payload: &T, text: &str, quiet_text: &str) {
     if flags.json {
-        println!("{payload}");
+        print_json(payload);
     } else if flags.quiet {
         println!("{quiet_text}");
     } else {
         println!("{text}");
     }
-    if flags.verbose {
-        eprintln!("debug: completed");
-    }
+    tracing::debug!("completed");
 }
 
 fn ensure_dirs() -> Result<()> {
-    let cfg_parent = config_dir()?;
-    let data_parent = data_dir()?;
-    fs::create_dir_all(cfg_parent)?;
-    fs::create_dir_all(data_parent)?;
+    fs::create_dir_all(config_dir(TOOL))?;
+    fs::create_dir_all(data_dir(TOOL))?;
     Ok(())
 }
 
-fn config_dir() -> Result<PathBuf> {
-    dirs::config_dir()
-        .map(|p| p.join(TOOL))
-        .ok_or_else(|| anyhow!("Could not resolve config directory"))
+fn feeds_path() -> PathBuf {
+    config_dir(TOOL).join("feeds.toml")
 }
 
-fn data_dir() -> Result<PathBuf> {
-    dirs::data_dir()
-        .map(|p| p.join(TOOL))
-        .ok_or_else(|| anyhow!("Could not resolve data directory"))
-}
+/// Sections of config.toml that `DEE_FEED_<SECTION>` can replace.
+const SETTINGS_KEYS: &[&str] = &["prune", "rules", "notify"];
 
-fn feeds_path() -> Result<PathBuf> {
-    Ok(config_dir()?.join("feeds.toml"))
+/// `DEE_FEED_<SECTION>` takes a TOML value, e.g.
+/// `DEE_FEED_NOTIFY='{ url = "https://example.com/hook" }'`.
+fn config_file() -> Config<Settings> {
+    Config::new(TOOL).env_overrides(SETTINGS_KEYS)
 }
 
 fn config_path() -> PathBuf {
-    dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(TOOL)
-        .join("config.toml")
+    config_file().path()
 }
 
-fn db_path() -> Result<PathBuf> {
-    Ok(data_dir()?.join("feed.db"))
+fn db_path() -> PathBuf {
+    data_dir(TOOL).join("feed.db")
+}
+
+/// The settings in effect: the file, the active profile, then env overrides.
+fn load_settings() -> Result<Settings> {
+    Ok(config_file().load()?)
 }
 
 fn load_feeds() -> Result<FeedConfig> {
     ensure_dirs()?;
-    let path = feeds_path()?;
+    let path = feeds_path();
     if !path.exists() {
         return Ok(FeedConfig::default());
     }
@@ -592,19 +1188,25 @@ fn load_feeds() -> Result<FeedConfig> {
 
 fn save_feeds(cfg: &FeedConfig) -> Result<()> {
     ensure_dirs()?;
-    let path = feeds_path()?;
+    let path = feeds_path();
     let toml_data = toml::to_string_pretty(cfg)?;
     fs::write(path, toml_data)?;
     Ok(())
 }
 
 fn migrations() -> Migrations<'static> {
-    Migrations::new(vec![M::up(include_str!("../migrations/001_initial.sql"))])
+    Migrations::new(vec![
+        M::up(include_str!("../migrations/001_initial.sql")),
+        M::up(include_str!("../migrations/002_search.sql")),
+        M::up(include_str!("../migrations/003_pruned_items.sql")),
+        M::up(include_str!("../migrations/004_starred.sql")),
+        M::up(include_str!("../migrations/005_enclosures.sql")),
+    ])
 }
 
 fn open_db() -> Result<Connection> {
     ensure_dirs()?;
-    let path = db_path()?;
+    let path = db_path();
     let mut conn = Connection::open(path)?;
     migrations().to_latest(&mut conn)?;
     Ok(conn)
@@ -623,12 +1225,45 @@ fn sync_feeds_cache(conn: &mut Connection, cfg: &FeedConfig) -> Result<()> {
     Ok(())
 }
 
-fn parse_attr(line: &str, name: &str) -> Option<String> {
-    let token = format!("{name}=\"");
-    let start = line.find(&token)? + token.len();
-    let rest = &line[start..];
-    let end = rest.find('"')?;
-    Some(rest[..end].to_string())
+/// Row order: id, feed name, title, url, published, read, summary, starred,
+/// enclosure url, type and length.
+fn feed_item(row: &rusqlite::Row<'_>) -> rusqlite::Result<FeedItem> {
+    Ok(FeedItem {
+        id: row.get(0)?,
+        feed: row.get(1)?,
+        title: row.get(2)?,
+        url: row.get(3)?,
+        published: normalize_iso(row.get::<_, String>(4)?),
+        read: row.get::<_, i64>(5)? == 1,
+        summary: row.get(6)?,
+        starred: row.get::<_, i64>(7)? == 1,
+        enclosure: row
+            .get::<_, Option<String>>(8)?
+            .map(|url| -> rusqlite::Result<_> {
+                Ok(download::Enclosure {
+                    url,
+                    mime_type: row.get(9)?,
+                    length: row.get(10)?,
+                })
+            })
+            .transpose()?,
+    })
+}
+
+/// `--since` as the RFC 3339 UTC form `published` is stored in, so the two
+/// compare as strings.
+fn parse_since(raw: &str) -> Result<String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(dt.with_timezone(&Utc).to_rfc3339());
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .map(|date| date.and_time(NaiveTime::MIN).and_utc().to_rfc3339())
+        .map_err(|_| {
+            AppError::InvalidArgument(format!(
+                "Invalid --since `{raw}`: expected RFC 3339 or YYYY-MM-DD"
+            ))
+            .into()
+        })
 }
 
 fn normalize_iso(input: String) -> String {
@@ -636,42 +1271,3 @@ fn normalize_iso(input: String) -> String {
         .map(|dt| dt.with_timezone(&Utc).to_rfc3339())
         .unwrap_or(input)
 }
-
-fn xml_escape(raw: &str) -> String {
-    raw.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('\"', "&quot;")
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
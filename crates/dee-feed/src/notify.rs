@@ -0,0 +1,79 @@
+//! Hooks run by `fetch` for each item it stores as new and unread: a shell
+//! command that gets the item as JSON on stdin, and/or a webhook URL that is
+//! POSTed the same JSON. ntfy, Slack incoming webhooks behind `jq`, a desktop
+//! notifier: anything that reads one JSON object works.
+//!
+//! A failing hook is logged and the fetch carries on.
+
+use std::process::Stdio;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::FeedItem;
+
+/// `[notify]` in config.toml, or `fetch --notify-cmd/--notify-url`
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Hooks {
+    pub cmd: Option<String>,
+    pub url: Option<String>,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.cmd.is_none() && self.url.is_none()
+    }
+
+    pub async fn send(&self, client: &reqwest::Client, items: &[FeedItem]) {
+        for item in items {
+            let Ok(body) = serde_json::to_vec(item) else {
+                continue;
+            };
+            if let Some(cmd) = &self.cmd {
+                if let Err(e) = run(cmd, &body).await {
+                    tracing::warn!("notify command failed for item {}: {e}", item.id);
+                }
+            }
+            if let Some(url) = &self.url {
+                let sent = client
+                    .post(url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status());
+                if let Err(e) = sent {
+                    tracing::warn!("notify webhook failed for item {}: {e}", item.id);
+                }
+            }
+        }
+    }
+}
+
+/// Run `cmd` with `stdin`. Its stdout is discarded so it cannot corrupt `--json` output.
+async fn run(cmd: &str, stdin: &[u8]) -> std::io::Result<()> {
+    let mut child = if cfg!(windows) {
+        Command::new("cmd")
+            .args(["/C", cmd])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()?
+    } else {
+        Command::new("sh")
+            .args(["-c", cmd])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()?
+    };
+    if let Some(mut pipe) = child.stdin.take() {
+        // a command that ignores stdin may close it early; that is fine
+        let _ = pipe.write_all(stdin).await;
+    }
+    let status = child.wait().await?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("exited with {status}")));
+    }
+    Ok(())
+}
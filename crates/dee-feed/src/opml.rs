@@ -0,0 +1,234 @@
+//! OPML for `import` and `export --format opml`.
+//!
+//! Folders (outlines without an `xmlUrl`) map to tags: a feed inside
+//! `Tech > Rust` is imported with the tags `tech` and `rust`, in that order.
+//! Export nests each feed under folders named after its tags in the same
+//! order, so an imported tree comes back out with the same shape.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::FeedDef;
+
+#[derive(Deserialize)]
+struct Opml {
+    body: Body,
+}
+
+#[derive(Deserialize)]
+struct Body {
+    #[serde(rename = "outline", default)]
+    outlines: Vec<Outline>,
+}
+
+#[derive(Deserialize)]
+struct Outline {
+    #[serde(rename = "@text")]
+    text: Option<String>,
+    #[serde(rename = "@title")]
+    title: Option<String>,
+    #[serde(rename = "@xmlUrl")]
+    xml_url: Option<String>,
+    #[serde(rename = "outline", default)]
+    children: Vec<Outline>,
+}
+
+/// A feed found in an OPML document.
+#[derive(Debug, PartialEq)]
+pub struct Subscription {
+    pub url: String,
+    pub name: Option<String>,
+    /// The enclosing folders, outermost first
+    pub tags: Vec<String>,
+}
+
+pub fn parse(xml: &str) -> Result<Vec<Subscription>> {
+    let opml: Opml = quick_xml::de::from_str(xml).context("Invalid OPML")?;
+    let mut found = Vec::new();
+    walk(&opml.body.outlines, &mut Vec::new(), &mut found);
+    Ok(found)
+}
+
+fn walk(outlines: &[Outline], folders: &mut Vec<String>, found: &mut Vec<Subscription>) {
+    for outline in outlines {
+        let label = outline.title.as_ref().or(outline.text.as_ref());
+        if let Some(url) = outline.xml_url.as_deref().map(str::trim) {
+            if !url.is_empty() {
+                found.push(Subscription {
+                    url: url.to_string(),
+                    name: label
+                        .map(|l| l.trim().to_string())
+                        .filter(|l| !l.is_empty()),
+                    tags: folders.clone(),
+                });
+            }
+            walk(&outline.children, folders, found);
+            continue;
+        }
+        let tag = outline
+            .text
+            .as_ref()
+            .or(outline.title.as_ref())
+            .and_then(|name| folder_tag(name))
+            .filter(|tag| !folders.contains(tag));
+        match tag {
+            Some(tag) => {
+                folders.push(tag);
+                walk(&outline.children, folders, found);
+                folders.pop();
+            }
+            None => walk(&outline.children, folders, found),
+        }
+    }
+}
+
+/// `Tech News` -> `tech-news`; tags are single lower-case words.
+fn folder_tag(name: &str) -> Option<String> {
+    let tag = name
+        .trim()
+        .trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+enum Node<'a> {
+    Feed(&'a FeedDef),
+    Folder(&'a str, Vec<Node<'a>>),
+}
+
+pub fn render(feeds: &[FeedDef]) -> String {
+    let mut root = Vec::new();
+    for feed in feeds {
+        insert(&mut root, &feed.tags, feed);
+    }
+    let mut body = String::new();
+    write_nodes(&mut body, &root, 2);
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n  <head><title>dee-feed export</title></head>\n  <body>\n{body}  </body>\n</opml>"
+    )
+}
+
+fn insert<'a>(nodes: &mut Vec<Node<'a>>, path: &'a [String], feed: &'a FeedDef) {
+    let Some((folder, rest)) = path.split_first() else {
+        nodes.push(Node::Feed(feed));
+        return;
+    };
+    let at = nodes
+        .iter()
+        .position(|node| matches!(node, Node::Folder(name, _) if name == folder))
+        .unwrap_or_else(|| {
+            nodes.push(Node::Folder(folder, Vec::new()));
+            nodes.len() - 1
+        });
+    if let Node::Folder(_, children) = &mut nodes[at] {
+        insert(children, rest, feed);
+    }
+}
+
+fn write_nodes(out: &mut String, nodes: &[Node], depth: usize) {
+    let indent = "  ".repeat(depth);
+    for node in nodes {
+        match node {
+            Node::Feed(f) => out.push_str(&format!(
+                "{indent}<outline text=\"{}\" title=\"{}\" type=\"rss\" xmlUrl=\"{}\" />\n",
+                xml_escape(&f.name),
+                xml_escape(&f.name),
+                xml_escape(&f.url)
+            )),
+            Node::Folder(name, children) => {
+                let name = xml_escape(name);
+                out.push_str(&format!(
+                    "{indent}<outline text=\"{name}\" title=\"{name}\">\n"
+                ));
+                write_nodes(out, children, depth + 1);
+                out.push_str(&format!("{indent}</outline>\n"));
+            }
+        }
+    }
+}
+
+fn xml_escape(raw: &str) -> String {
+    raw.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(id: i64, name: &str, tags: &[&str]) -> FeedDef {
+        FeedDef {
+            id,
+            name: name.to_string(),
+            url: format!("https://example.com/{id}.xml"),
+            created_at: String::new(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn nested_folders_become_tags() {
+        let xml = r#"<?xml version="1.0"?>
+<opml version="1.0"><head><title>x</title></head><body>
+  <outline text="Loose &amp; Co" xmlUrl="https://a.test/rss"/>
+  <outline text="Tech News">
+    <outline title="Rust Lang" text="Rust">
+      <outline text="This Week" title="TWiR" type="rss" xmlUrl=" https://b.test/rss "/>
+    </outline>
+    <outline text="HN" xmlUrl="https://c.test/rss"/>
+  </outline>
+  <outline text="empty folder"/>
+</body></opml>"#;
+        let found = parse(xml).unwrap();
+        let summary: Vec<(&str, Option<&str>, Vec<&str>)> = found
+            .iter()
+            .map(|s| {
+                (
+                    s.url.as_str(),
+                    s.name.as_deref(),
+                    s.tags.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("https://a.test/rss", Some("Loose & Co"), vec![]),
+                (
+                    "https://b.test/rss",
+                    Some("TWiR"),
+                    vec!["tech-news", "rust"]
+                ),
+                ("https://c.test/rss", Some("HN"), vec!["tech-news"]),
+            ]
+        );
+        assert!(parse("<html><body/></html>").is_ok_and(|f| f.is_empty()));
+        assert!(parse("not xml").is_err());
+    }
+
+    #[test]
+    fn export_round_trips_the_tree() {
+        let feeds = [
+            feed(1, "A & B", &["tech", "rust"]),
+            feed(2, "Loose", &[]),
+            feed(3, "HN", &["tech"]),
+            feed(4, "TWiR", &["tech", "rust"]),
+        ];
+        let xml = render(&feeds);
+        assert!(xml.contains(
+            "    <outline text=\"tech\" title=\"tech\">\n      <outline text=\"rust\" title=\"rust\">\n        <outline text=\"A &amp; B\""
+        ));
+        let back = parse(&xml).unwrap();
+        assert_eq!(back.len(), 4);
+        for f in &feeds {
+            let found = back.iter().find(|s| s.url == f.url).unwrap();
+            assert_eq!(found.name.as_deref(), Some(f.name.as_str()));
+            assert_eq!(found.tags, f.tags);
+        }
+    }
+}
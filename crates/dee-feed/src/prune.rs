@@ -0,0 +1,84 @@
+//! `prune` and the automatic prune after `fetch`: delete read, unstarred items
+//! past the retention limits, then vacuum so `feed.db` shrinks on disk.
+//!
+//! A pruned item's `(feed_id, ext_id)` is kept in `pruned_items` so the next
+//! fetch does not store it again as unread while the feed still lists it.
+
+use std::fs;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use rusqlite::{params, Connection};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{db_path, AppError};
+
+/// `[prune]` in config.toml; when present, `fetch` prunes with it.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy)]
+pub struct Policy {
+    pub keep_days: Option<u32>,
+    pub keep_per_feed: Option<u32>,
+}
+
+#[derive(Serialize, JsonSchema, Debug)]
+pub struct PruneResponse {
+    ok: bool,
+    pub message: String,
+    /// Read items deleted
+    pub deleted: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_days: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_per_feed: Option<u32>,
+    /// feed.db size before and after the vacuum, in bytes
+    size_before: u64,
+    size_after: u64,
+}
+
+/// Items matching ?1 (cutoff) or ?2 (per-feed cap); unread and starred items
+/// never match.
+const STALE: &str = "read=1 AND starred=0 AND ((?1 IS NOT NULL AND published < ?1) OR (?2 IS NOT NULL AND id IN ( \
+     SELECT id FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY feed_id ORDER BY published DESC, id DESC) AS rank FROM items) \
+     WHERE rank > ?2)))";
+
+pub fn run(conn: &mut Connection, policy: Policy) -> Result<PruneResponse> {
+    if policy.keep_days.is_none() && policy.keep_per_feed.is_none() {
+        return Err(AppError::MissingArgument(
+            "--keep-days and/or --keep-per-feed, or set them under [prune] in config.toml".into(),
+        )
+        .into());
+    }
+    let cutoff = policy
+        .keep_days
+        .map(|days| (Utc::now() - Duration::days(i64::from(days))).to_rfc3339());
+    let size_before = db_size();
+
+    let tx = conn.transaction()?;
+    tx.execute(
+        &format!("INSERT OR IGNORE INTO pruned_items (feed_id, ext_id) SELECT feed_id, ext_id FROM items WHERE {STALE}"),
+        params![cutoff, policy.keep_per_feed],
+    )?;
+    let deleted = tx.execute(
+        &format!("DELETE FROM items WHERE {STALE}"),
+        params![cutoff, policy.keep_per_feed],
+    )?;
+    tx.commit()?;
+    if deleted > 0 {
+        conn.execute_batch("INSERT INTO items_fts (items_fts) VALUES ('optimize'); VACUUM;")?;
+    }
+
+    Ok(PruneResponse {
+        ok: true,
+        message: format!("Pruned {deleted} read items"),
+        deleted,
+        keep_days: policy.keep_days,
+        keep_per_feed: policy.keep_per_feed,
+        size_before,
+        size_after: db_size(),
+    })
+}
+
+fn db_size() -> u64 {
+    fs::metadata(db_path()).map(|m| m.len()).unwrap_or(0)
+}
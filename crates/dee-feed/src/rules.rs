@@ -0,0 +1,178 @@
+//! `[[rules]]` in config.toml, applied by `fetch` to items it stores for the
+//! first time:
+//!
+//! ```toml
+//! [[rules]]
+//! feed = "HN"
+//! title_regex = "(?i)crypto"
+//! action = "mark_read"
+//! ```
+//!
+//! Every condition a rule sets must hold; a rule without conditions matches
+//! every item. All matching rules apply, so an item can be both starred and
+//! marked read, and `skip` wins over both.
+
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{AppError, FeedDef};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RuleDef {
+    /// Feed name (case-insensitive) or id
+    feed: Option<String>,
+    tag: Option<String>,
+    title_regex: Option<String>,
+    /// Matched against the summary and the content
+    content_regex: Option<String>,
+    action: Action,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Store the item as read
+    MarkRead,
+    /// Store the item starred
+    Star,
+    /// Do not store the item
+    Skip,
+}
+
+struct Rule {
+    feed: Option<String>,
+    tag: Option<String>,
+    title: Option<Regex>,
+    content: Option<Regex>,
+    action: Action,
+}
+
+pub struct Rules(Vec<Rule>);
+
+/// What the rules decided for one new item.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Verdict {
+    pub skip: bool,
+    pub read: bool,
+    pub starred: bool,
+}
+
+impl Rules {
+    pub fn compile(defs: &[RuleDef]) -> Result<Self> {
+        let regex = |n: usize, key: &str, pattern: &Option<String>| {
+            pattern
+                .as_deref()
+                .map(|p| {
+                    Regex::new(p).map_err(|e| {
+                        AppError::ConfigInvalid(format!("Invalid rule #{n}: {key}: {e}"))
+                    })
+                })
+                .transpose()
+        };
+        let mut rules = Vec::with_capacity(defs.len());
+        for (i, def) in defs.iter().enumerate() {
+            rules.push(Rule {
+                feed: def.feed.as_deref().map(str::to_lowercase),
+                tag: def.tag.as_deref().map(str::to_lowercase),
+                title: regex(i + 1, "title_regex", &def.title_regex)?,
+                content: regex(i + 1, "content_regex", &def.content_regex)?,
+                action: def.action,
+            });
+        }
+        Ok(Self(rules))
+    }
+
+    pub fn verdict(&self, feed: &FeedDef, title: &str, summary: &str, content: &str) -> Verdict {
+        let mut verdict = Verdict::default();
+        for rule in self.0.iter().filter(|rule| {
+            rule.feed
+                .as_ref()
+                .is_none_or(|f| *f == feed.name.to_lowercase() || *f == feed.id.to_string())
+                && rule.tag.as_ref().is_none_or(|t| feed.tags.contains(t))
+                && rule.title.as_ref().is_none_or(|re| re.is_match(title))
+                && rule
+                    .content
+                    .as_ref()
+                    .is_none_or(|re| re.is_match(summary) || re.is_match(content))
+        }) {
+            match rule.action {
+                Action::MarkRead => verdict.read = true,
+                Action::Star => verdict.starred = true,
+                Action::Skip => verdict.skip = true,
+            }
+        }
+        verdict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(toml: &str) -> Result<Rules> {
+        #[derive(Deserialize)]
+        struct File {
+            rules: Vec<RuleDef>,
+        }
+        Rules::compile(&toml::from_str::<File>(toml)?.rules)
+    }
+
+    fn feed(id: i64, name: &str, tags: &[&str]) -> FeedDef {
+        FeedDef {
+            id,
+            name: name.to_string(),
+            url: String::new(),
+            created_at: String::new(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn conditions_all_hold_and_actions_combine() {
+        let rules = rules(
+            r#"
+[[rules]]
+feed = "hn"
+title_regex = "(?i)crypto"
+action = "mark_read"
+
+[[rules]]
+tag = "rust"
+content_regex = "async"
+action = "star"
+
+[[rules]]
+feed = "3"
+action = "skip"
+"#,
+        )
+        .unwrap();
+        let hn = feed(1, "HN", &["rust"]);
+        let blog = feed(2, "blog", &[]);
+        let noise = feed(3, "noise", &[]);
+
+        assert_eq!(
+            rules.verdict(&hn, "Crypto winter", "", "async fn"),
+            Verdict {
+                read: true,
+                starred: true,
+                skip: false
+            }
+        );
+        assert_eq!(rules.verdict(&blog, "Crypto", "", ""), Verdict::default());
+        assert_eq!(rules.verdict(&hn, "Rust 2026", "", ""), Verdict::default());
+        assert!(rules.verdict(&noise, "", "", "").skip);
+    }
+
+    #[test]
+    fn bad_rules_are_rejected() {
+        let err = rules("[[rules]]\ntitle_regex = \"(\"\naction = \"skip\"\n")
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("rule #1: title_regex"));
+        assert!(rules("[[rules]]\naction = \"delete\"\n").is_err());
+        assert!(rules("[[rules]]\ntitle = \"x\"\naction = \"skip\"\n").is_err());
+    }
+}
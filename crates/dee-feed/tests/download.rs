@@ -0,0 +1,129 @@
+use dee_test_support::{json, matchers, Mock, MockApi, ResponseTemplate, Sandbox};
+use std::fs;
+
+const AUDIO: &[u8] = b"ID3-fake-episode-audio";
+
+/// A one-episode podcast whose enclosure is served by the same mock
+fn podcast() -> MockApi {
+    let api = MockApi::start();
+    let rss = format!(
+        r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Pod</title>
+<item><guid>ep1</guid><title>Episode 1</title><link>https://pod.test/1</link>
+<enclosure url="{}" type="audio/mpeg" length="{}"/></item>
+</channel></rss>"#,
+        api.url("/media/ep1.mp3"),
+        AUDIO.len()
+    );
+    api.mount(
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/media/ep1.mp3"))
+            .and(matchers::header("range", "bytes=4-"))
+            .respond_with(ResponseTemplate::new(206).set_body_bytes(&AUDIO[4..])),
+    );
+    api.mount(
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/media/ep1.mp3"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(AUDIO)),
+    );
+    api.mount(
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/feed.xml"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(rss, "application/rss+xml")),
+    );
+    api
+}
+
+fn feed(sandbox: &Sandbox, args: &[&str]) -> serde_json::Value {
+    let (code, parsed) = json(
+        sandbox
+            .command(assert_cmd::cargo::cargo_bin!("dee-feed"))
+            .args(args)
+            .arg("--json"),
+    );
+    assert_eq!(code, Some(0), "{parsed}");
+    parsed
+}
+
+/// fetch keeps the enclosure; download saves it once
+#[test]
+fn download_saves_the_enclosure() {
+    let api = podcast();
+    let sandbox = Sandbox::new();
+    feed(&sandbox, &["add", &api.url("/feed.xml"), "--name", "pod"]);
+
+    let fetched = feed(&sandbox, &["fetch"]);
+    let item = &fetched["items"][0];
+    assert_eq!(item["url"], serde_json::json!("https://pod.test/1"));
+    assert_eq!(item["enclosure"]["type"], serde_json::json!("audio/mpeg"));
+    assert_eq!(item["enclosure"]["length"], serde_json::json!(AUDIO.len()));
+
+    let out_dir = sandbox.path().join("Podcasts");
+    let out = out_dir.to_str().unwrap();
+    let saved = feed(&sandbox, &["download", "1", "--out-dir", out]);
+    assert_eq!(saved["bytes"], serde_json::json!(AUDIO.len()));
+    assert_eq!(saved["resumed_from"], serde_json::json!(0));
+    assert_eq!(fs::read(out_dir.join("1-ep1.mp3")).unwrap(), AUDIO);
+
+    let again = feed(&sandbox, &["download", "1", "--out-dir", out]);
+    assert_eq!(again["already_present"], serde_json::json!(true));
+}
+
+/// A .part left behind is continued with a range request
+#[test]
+fn download_resumes_a_partial_file() {
+    let api = podcast();
+    let sandbox = Sandbox::new();
+    feed(&sandbox, &["add", &api.url("/feed.xml"), "--name", "pod"]);
+    feed(&sandbox, &["fetch"]);
+
+    let out_dir = sandbox.path().join("Podcasts");
+    fs::create_dir_all(&out_dir).unwrap();
+    fs::write(out_dir.join("1-ep1.mp3.part"), &AUDIO[..4]).unwrap();
+
+    let saved = feed(
+        &sandbox,
+        &["download", "1", "--out-dir", out_dir.to_str().unwrap()],
+    );
+    assert_eq!(saved["resumed_from"], serde_json::json!(4));
+    assert_eq!(fs::read(out_dir.join("1-ep1.mp3")).unwrap(), AUDIO);
+    assert!(!out_dir.join("1-ep1.mp3.part").exists());
+}
+
+/// A missing item is NOT_FOUND; an unreachable enclosure is a network error
+#[test]
+fn download_failures_exit_with_their_code() {
+    let api = MockApi::start();
+    api.mount(
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/feed.xml"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Pod</title>
+<item><guid>ep1</guid><title>Episode 1</title>
+<enclosure url="http://127.0.0.1:9/ep1.mp3" type="audio/mpeg"/></item>
+</channel></rss>"#,
+                "application/rss+xml",
+            )),
+    );
+    let sandbox = Sandbox::new();
+    feed(&sandbox, &["add", &api.url("/feed.xml"), "--name", "pod"]);
+    feed(&sandbox, &["fetch"]);
+    let (code, missing) = json(
+        sandbox
+            .command(assert_cmd::cargo::cargo_bin!("dee-feed"))
+            .args(["download", "999", "--json"]),
+    );
+    assert_eq!(code, Some(3));
+    assert_eq!(missing["code"], serde_json::json!("NOT_FOUND"));
+
+    let out_dir = sandbox.path().join("Podcasts");
+    let (code, unreachable) = json(
+        sandbox
+            .command(assert_cmd::cargo::cargo_bin!("dee-feed"))
+            .args(["download", "1", "--retries", "0", "--json", "--out-dir"])
+            .arg(&out_dir),
+    );
+    assert_eq!(code, Some(5), "{unreachable}");
+    assert_eq!(unreachable["code"], serde_json::json!("NETWORK_ERROR"));
+}
@@ -0,0 +1,74 @@
+use dee_test_support::{json, matchers, Mock, MockApi, ResponseTemplate, Sandbox};
+
+const FEED: &str = r#"{
+  "version": "https://jsonfeed.org/version/1.1",
+  "title": "Notes",
+  "items": [
+    {"id": "2", "url": "https://notes.test/2", "title": "Second", "content_html": "<p>two</p>",
+     "date_published": "2026-03-02T09:00:00Z",
+     "attachments": [{"url": "https://notes.test/2.mp3", "mime_type": "audio/mpeg", "size_in_bytes": 10}]},
+    {"id": "1", "url": "https://notes.test/1", "title": "First", "summary": "one",
+     "content_text": "", "date_published": "2026-03-01T09:00:00Z"}
+  ]
+}"#;
+
+fn feed(sandbox: &Sandbox, args: &[&str]) -> serde_json::Value {
+    let (code, parsed) = json(
+        sandbox
+            .command(assert_cmd::cargo::cargo_bin!("dee-feed"))
+            .args(args)
+            .arg("--json"),
+    );
+    assert_eq!(code, Some(0), "{parsed}");
+    parsed
+}
+
+/// A JSON Feed is stored like any other, and exported back as JSON Feed 1.1
+#[test]
+fn json_feed_round_trip() {
+    let api = MockApi::start();
+    api.mount(
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/feed.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(FEED, "application/feed+json")),
+    );
+    let sandbox = Sandbox::new();
+    feed(
+        &sandbox,
+        &["add", &api.url("/feed.json"), "--name", "notes"],
+    );
+
+    let fetched = feed(&sandbox, &["fetch"]);
+    assert_eq!(fetched["count"], serde_json::json!(2));
+    assert_eq!(fetched["items"][0]["title"], serde_json::json!("Second"));
+    assert_eq!(
+        fetched["items"][0]["enclosure"]["url"],
+        serde_json::json!("https://notes.test/2.mp3")
+    );
+
+    let exported = feed(
+        &sandbox,
+        &["export", "--format", "jsonfeed", "--feed", "notes"],
+    );
+    assert_eq!(exported["count"], serde_json::json!(2));
+    let doc = &exported["jsonfeed"];
+    assert_eq!(
+        doc["version"],
+        serde_json::json!("https://jsonfeed.org/version/1.1")
+    );
+    assert_eq!(doc["title"], serde_json::json!("notes"));
+    assert_eq!(doc["items"][0]["id"], serde_json::json!("2"));
+    assert_eq!(
+        doc["items"][0]["content_html"],
+        serde_json::json!("<p>two</p>")
+    );
+    assert_eq!(
+        doc["items"][0]["attachments"][0]["mime_type"],
+        serde_json::json!("audio/mpeg")
+    );
+    assert_eq!(doc["items"][1]["content_text"], serde_json::json!("one"));
+    assert_eq!(
+        doc["items"][1]["date_published"],
+        serde_json::json!("2026-03-01T09:00:00+00:00")
+    );
+}
@@ -0,0 +1,143 @@
+use dee_test_support::{json, matchers, Mock, MockApi, ResponseTemplate, Sandbox};
+use std::fs;
+
+const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Fixture</title>
+<item><guid>b</guid><title>Second</title><pubDate>Thu, 02 Jan 2020 09:00:00 GMT</pubDate></item>
+<item><guid>a</guid><title>First</title><pubDate>Wed, 01 Jan 2020 09:00:00 GMT</pubDate></item>
+</channel></rss>"#;
+
+fn served() -> MockApi {
+    let api = MockApi::start();
+    api.mount(
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/feed.xml"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(RSS, "application/rss+xml")),
+    );
+    api.mount(
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/hook"))
+            .respond_with(ResponseTemplate::new(200)),
+    );
+    api
+}
+
+fn feed(sandbox: &Sandbox, args: &[&str]) -> serde_json::Value {
+    let (code, parsed) = json(
+        sandbox
+            .command(assert_cmd::cargo::cargo_bin!("dee-feed"))
+            .args(args)
+            .arg("--json"),
+    );
+    assert_eq!(code, Some(0), "{parsed}");
+    parsed
+}
+
+/// The webhook gets one POST per new item, and nothing for items seen before
+#[test]
+fn webhook_is_posted_each_new_item_once() {
+    let api = served();
+    let sandbox = Sandbox::new();
+    feed(
+        &sandbox,
+        &["add", &api.url("/feed.xml"), "--name", "fixture"],
+    );
+    let hook = api.url("/hook");
+
+    feed(&sandbox, &["fetch", "--notify-url", &hook]);
+    feed(&sandbox, &["fetch", "--notify-url", &hook]);
+
+    let posted: Vec<serde_json::Value> = api
+        .requests()
+        .iter()
+        .filter(|r| r.method.as_str() == "POST")
+        .map(|r| serde_json::from_slice(&r.body).unwrap())
+        .collect();
+    assert_eq!(posted.len(), 2);
+    assert_eq!(posted[0]["title"], serde_json::json!("Second"));
+    assert_eq!(posted[0]["feed"], serde_json::json!("fixture"));
+    assert_eq!(posted[1]["read"], serde_json::json!(false));
+}
+
+/// `[notify] cmd` gets each new unread item on stdin; rules that store an
+/// item as read keep it quiet, and a failing command does not fail the fetch
+#[cfg(unix)]
+#[test]
+fn configured_command_reads_items_on_stdin() {
+    let api = served();
+    let sandbox = Sandbox::new();
+    feed(
+        &sandbox,
+        &["add", &api.url("/feed.xml"), "--name", "fixture"],
+    );
+    let log = sandbox.path().join("notified.jsonl");
+    fs::write(
+        sandbox.path().join("config/dee-feed/config.toml"),
+        format!(
+            "[notify]\ncmd = \"cat >> '{}' && echo >> '{}'; exit 3\"\n\n[[rules]]\ntitle_regex = \"^First\"\naction = \"mark_read\"\n",
+            log.display(),
+            log.display()
+        ),
+    )
+    .unwrap();
+
+    let fetched = feed(&sandbox, &["fetch"]);
+    assert_eq!(fetched["count"], serde_json::json!(2));
+
+    let notified: Vec<serde_json::Value> = fs::read_to_string(&log)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(notified.len(), 1);
+    assert_eq!(notified[0]["title"], serde_json::json!("Second"));
+    assert_eq!(notified[0]["id"], fetched["items"][0]["id"]);
+}
+
+/// Whatever the command prints stays out of `fetch --json`
+#[cfg(unix)]
+#[test]
+fn command_output_does_not_reach_json_stdout() {
+    let api = served();
+    let sandbox = Sandbox::new();
+    feed(
+        &sandbox,
+        &["add", &api.url("/feed.xml"), "--name", "fixture"],
+    );
+    fs::write(
+        sandbox.path().join("config/dee-feed/config.toml"),
+        "[notify]\ncmd = \"echo notified; cat\"\n",
+    )
+    .unwrap();
+
+    let fetched = feed(&sandbox, &["fetch"]);
+    assert_eq!(fetched["count"], serde_json::json!(2));
+}
+
+/// `DEE_FEED_NOTIFY` replaces the `[notify]` section, like any other tool's env overrides
+#[test]
+fn notify_section_can_come_from_the_env() {
+    let api = served();
+    let sandbox = Sandbox::new();
+    feed(
+        &sandbox,
+        &["add", &api.url("/feed.xml"), "--name", "fixture"],
+    );
+
+    let (code, parsed) = json(
+        sandbox
+            .command(assert_cmd::cargo::cargo_bin!("dee-feed"))
+            .env(
+                "DEE_FEED_NOTIFY",
+                format!("{{ url = \"{}\" }}", api.url("/hook")),
+            )
+            .args(["fetch", "--json"]),
+    );
+    assert_eq!(code, Some(0), "{parsed}");
+    let posted = api
+        .requests()
+        .iter()
+        .filter(|r| r.method.as_str() == "POST")
+        .count();
+    assert_eq!(posted, 2);
+}
@@ -0,0 +1,129 @@
+use dee_test_support::{json, matchers, Mock, MockApi, ResponseTemplate, Sandbox};
+use std::fs;
+
+/// Three items, newest first, from 2020 so any `--keep-days` covers them
+const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Fixture</title>
+<item><guid>c</guid><title>Third</title><pubDate>Fri, 03 Jan 2020 09:00:00 GMT</pubDate></item>
+<item><guid>b</guid><title>Second</title><pubDate>Thu, 02 Jan 2020 09:00:00 GMT</pubDate></item>
+<item><guid>a</guid><title>First</title><pubDate>Wed, 01 Jan 2020 09:00:00 GMT</pubDate></item>
+</channel></rss>"#;
+
+fn served() -> MockApi {
+    let api = MockApi::start();
+    api.mount(
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/feed.xml"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(RSS, "application/rss+xml")),
+    );
+    api
+}
+
+fn feed(sandbox: &Sandbox, args: &[&str]) -> serde_json::Value {
+    let (code, parsed) = json(
+        sandbox
+            .command(assert_cmd::cargo::cargo_bin!("dee-feed"))
+            .args(args)
+            .arg("--json"),
+    );
+    assert_eq!(code, Some(0), "{parsed}");
+    parsed
+}
+
+fn fetched_titles(sandbox: &Sandbox) -> Vec<String> {
+    feed(sandbox, &["fetch"])["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap().to_string())
+        .collect()
+}
+
+/// Pruned read items are deleted and not stored again by the next fetch;
+/// unread items survive any limit
+#[test]
+fn prune_keeps_newest_per_feed_and_stays_pruned() {
+    let api = served();
+    let sandbox = Sandbox::new();
+    feed(
+        &sandbox,
+        &["add", &api.url("/feed.xml"), "--name", "fixture"],
+    );
+    assert_eq!(fetched_titles(&sandbox), ["Third", "Second", "First"]);
+    // Third and First read, Second left unread
+    feed(&sandbox, &["read", "1"]);
+    feed(&sandbox, &["read", "3"]);
+
+    let pruned = feed(&sandbox, &["prune", "--keep-per-feed", "1"]);
+    assert_eq!(pruned["deleted"], serde_json::json!(1));
+    assert_eq!(fetched_titles(&sandbox), ["Third", "Second"]);
+
+    let pruned = feed(&sandbox, &["prune", "--keep-days", "30"]);
+    assert_eq!(pruned["deleted"], serde_json::json!(1));
+    assert_eq!(fetched_titles(&sandbox), ["Second"]);
+}
+
+/// `[prune]` in config.toml runs after every fetch; without limits `prune`
+/// refuses
+#[test]
+fn config_prunes_on_fetch() {
+    let api = served();
+    let sandbox = Sandbox::new();
+    feed(
+        &sandbox,
+        &["add", &api.url("/feed.xml"), "--name", "fixture"],
+    );
+    fetched_titles(&sandbox);
+
+    let (code, parsed) = json(
+        sandbox
+            .command(assert_cmd::cargo::cargo_bin!("dee-feed"))
+            .args(["prune", "--json"]),
+    );
+    assert_eq!(code, Some(2));
+    assert_eq!(parsed["code"], serde_json::json!("MISSING_ARGUMENT"));
+
+    feed(&sandbox, &["mark-read", "fixture", "--all"]);
+    fs::write(
+        sandbox.path().join("config/dee-feed/config.toml"),
+        "[prune]\nkeep_days = 30\n",
+    )
+    .unwrap();
+    assert!(fetched_titles(&sandbox).is_empty());
+}
+
+/// Starred items list under `fetch --starred` and outlive any prune until
+/// unstarred
+#[test]
+fn starred_items_are_exempt_from_pruning() {
+    let api = served();
+    let sandbox = Sandbox::new();
+    feed(
+        &sandbox,
+        &["add", &api.url("/feed.xml"), "--name", "fixture"],
+    );
+    fetched_titles(&sandbox);
+
+    let starred = feed(&sandbox, &["star", "1", "3"]);
+    assert_eq!(starred["count"], serde_json::json!(2));
+    feed(&sandbox, &["mark-read", "fixture", "--all"]);
+    let listed = feed(&sandbox, &["fetch", "--starred"]);
+    assert_eq!(listed["count"], serde_json::json!(2));
+    assert_eq!(listed["items"][0]["starred"], serde_json::json!(true));
+
+    let pruned = feed(&sandbox, &["prune", "--keep-days", "30"]);
+    assert_eq!(pruned["deleted"], serde_json::json!(1));
+    assert_eq!(fetched_titles(&sandbox), ["Third", "First"]);
+
+    feed(&sandbox, &["unstar", "3"]);
+    feed(&sandbox, &["prune", "--keep-days", "30"]);
+    assert_eq!(fetched_titles(&sandbox), ["Third"]);
+
+    let (code, missing) = json(
+        sandbox
+            .command(assert_cmd::cargo::cargo_bin!("dee-feed"))
+            .args(["star", "99", "--json"]),
+    );
+    assert_eq!(code, Some(3));
+    assert_eq!(missing["code"], serde_json::json!("NOT_FOUND"));
+}
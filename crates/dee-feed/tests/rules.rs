@@ -0,0 +1,90 @@
+use dee_test_support::{json, matchers, Mock, MockApi, ResponseTemplate, Sandbox};
+use std::fs;
+
+const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Fixture</title>
+<item><guid>c</guid><title>Crypto winter is over</title><pubDate>Fri, 03 Jan 2020 09:00:00 GMT</pubDate></item>
+<item><guid>b</guid><title>Async Rust</title><description>tokio internals</description><pubDate>Thu, 02 Jan 2020 09:00:00 GMT</pubDate></item>
+<item><guid>a</guid><title>Sponsored: buy now</title><pubDate>Wed, 01 Jan 2020 09:00:00 GMT</pubDate></item>
+</channel></rss>"#;
+
+fn feed(sandbox: &Sandbox, args: &[&str]) -> (Option<i32>, serde_json::Value) {
+    json(
+        sandbox
+            .command(assert_cmd::cargo::cargo_bin!("dee-feed"))
+            .args(args)
+            .arg("--json"),
+    )
+}
+
+/// Rules decide how new items are stored: skipped, read or starred
+#[test]
+fn fetch_applies_rules_to_new_items() {
+    let api = MockApi::start();
+    api.mount(
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/feed.xml"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(RSS, "application/rss+xml")),
+    );
+    let sandbox = Sandbox::new();
+    feed(&sandbox, &["add", &api.url("/feed.xml"), "--name", "HN"]);
+    fs::write(
+        sandbox.path().join("config/dee-feed/config.toml"),
+        r#"[[rules]]
+feed = "hn"
+title_regex = "(?i)crypto"
+action = "mark_read"
+
+[[rules]]
+content_regex = "tokio"
+action = "star"
+
+[[rules]]
+title_regex = "^Sponsored:"
+action = "skip"
+"#,
+    )
+    .unwrap();
+
+    let (code, fetched) = feed(&sandbox, &["fetch"]);
+    assert_eq!(code, Some(0));
+    let items: Vec<(&str, bool, bool)> = fetched["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| {
+            (
+                item["title"].as_str().unwrap(),
+                item["read"].as_bool().unwrap(),
+                item["starred"].as_bool().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        items,
+        [
+            ("Crypto winter is over", true, false),
+            ("Async Rust", false, true)
+        ]
+    );
+
+    let (_, unread) = feed(&sandbox, &["fetch", "--unread"]);
+    assert_eq!(unread["count"], serde_json::json!(1));
+}
+
+/// A rule that does not compile stops the fetch before anything is stored
+#[test]
+fn invalid_rule_is_an_error() {
+    let sandbox = Sandbox::new();
+    feed(&sandbox, &["add", "http://127.0.0.1:9/feed.xml"]);
+    fs::write(
+        sandbox.path().join("config/dee-feed/config.toml"),
+        "[[rules]]\ntitle_regex = \"(\"\naction = \"skip\"\n",
+    )
+    .unwrap();
+
+    let (code, error) = feed(&sandbox, &["fetch"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(error["code"], serde_json::json!("CONFIG_INVALID"));
+    assert!(error["error"].as_str().unwrap().contains("rule #1"));
+}
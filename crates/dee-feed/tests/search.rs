@@ -0,0 +1,161 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use rusqlite::Connection;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn bin() -> Command {
+    Command::cargo_bin("dee-feed").unwrap()
+}
+
+fn with_home(dir: &TempDir) -> Command {
+    let mut cmd = bin();
+    cmd.env("HOME", dir.path());
+    cmd.env("XDG_CONFIG_HOME", dir.path().join("config"));
+    cmd.env("XDG_DATA_HOME", dir.path().join("data"));
+    cmd
+}
+
+fn db_path(home: &TempDir) -> PathBuf {
+    #[cfg(target_os = "macos")]
+    {
+        return home
+            .path()
+            .join("Library")
+            .join("Application Support")
+            .join("dee-feed")
+            .join("feed.db");
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        home.path().join("data").join("dee-feed").join("feed.db")
+    }
+}
+
+/// Two feeds with items stored the way `fetch` stores them
+fn seeded() -> TempDir {
+    let home = TempDir::new().unwrap();
+    for (url, name) in [
+        ("https://example.com/a.xml", "alpha"),
+        ("https://example.com/b.xml", "beta"),
+    ] {
+        with_home(&home)
+            .args(["add", url, "--name", name])
+            .assert()
+            .success();
+    }
+
+    let conn = Connection::open(db_path(&home)).unwrap();
+    for (feed_id, ext_id, title, summary, content, published, read) in [
+        (
+            1_i64,
+            "a1",
+            "Async Rust in practice",
+            "",
+            "",
+            "2026-03-01T09:00:00+00:00",
+            0_i64,
+        ),
+        (
+            1,
+            "a2",
+            "Weekly notes",
+            "tokio and rust tips",
+            "",
+            "2026-01-10T09:00:00+00:00",
+            1,
+        ),
+        (
+            2,
+            "b1",
+            "Gardening",
+            "",
+            "Why I rewrote my planner in Rust",
+            "2026-02-15T09:00:00+00:00",
+            0,
+        ),
+        (
+            2,
+            "b2",
+            "Cooking",
+            "soup",
+            "",
+            "2026-03-02T09:00:00+00:00",
+            0,
+        ),
+    ] {
+        conn.execute(
+            "INSERT INTO items (feed_id, ext_id, title, url, summary, content, published, read) VALUES (?1, ?2, ?3, '', ?4, ?5, ?6, ?7)",
+            rusqlite::params![feed_id, ext_id, title, summary, content, published, read],
+        )
+        .unwrap();
+    }
+    home
+}
+
+fn search_titles(home: &TempDir, args: &[&str]) -> Vec<String> {
+    let out = with_home(home)
+        .arg("search")
+        .args(args)
+        .arg("--json")
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stdout)
+    );
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["ok"], serde_json::json!(true));
+    let mut titles: Vec<String> = parsed["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap().to_string())
+        .collect();
+    titles.sort();
+    titles
+}
+
+/// Title, summary and content are all searched, and each filter narrows
+#[test]
+fn search_matches_every_text_column_and_filters() {
+    let home = seeded();
+
+    assert_eq!(
+        search_titles(&home, &["rust"]),
+        ["Async Rust in practice", "Gardening", "Weekly notes"]
+    );
+    assert_eq!(
+        search_titles(&home, &["rust", "--feed", "beta"]),
+        ["Gardening"]
+    );
+    assert_eq!(
+        search_titles(&home, &["rust", "--unread"]),
+        ["Async Rust in practice", "Gardening"]
+    );
+    assert_eq!(
+        search_titles(&home, &["rust", "--since", "2026-02-20"]),
+        ["Async Rust in practice"]
+    );
+    assert_eq!(search_titles(&home, &["plan*"]), ["Gardening"]);
+    assert!(search_titles(&home, &["kubernetes"]).is_empty());
+}
+
+/// A malformed FTS5 query is reported as a JSON error, not a panic
+#[test]
+fn search_bad_query_json_error() {
+    let home = seeded();
+
+    let out = with_home(&home)
+        .args(["search", "\"unterminated", "--json"])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&out.stdout).expect("error must be valid JSON on stdout");
+    assert_eq!(parsed["ok"], serde_json::json!(false));
+    assert!(parsed["error"].as_str().unwrap().contains("search query"));
+}
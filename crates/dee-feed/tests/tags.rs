@@ -0,0 +1,202 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use rusqlite::Connection;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn bin() -> Command {
+    Command::cargo_bin("dee-feed").unwrap()
+}
+
+fn with_home(dir: &TempDir) -> Command {
+    let mut cmd = bin();
+    cmd.env("HOME", dir.path());
+    cmd.env("XDG_CONFIG_HOME", dir.path().join("config"));
+    cmd.env("XDG_DATA_HOME", dir.path().join("data"));
+    cmd
+}
+
+fn db_path(home: &TempDir) -> PathBuf {
+    #[cfg(target_os = "macos")]
+    {
+        return home
+            .path()
+            .join("Library")
+            .join("Application Support")
+            .join("dee-feed")
+            .join("feed.db");
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        home.path().join("data").join("dee-feed").join("feed.db")
+    }
+}
+
+fn json(home: &TempDir, args: &[&str]) -> serde_json::Value {
+    let out = with_home(home).args(args).arg("--json").output().unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stdout)
+    );
+    serde_json::from_slice(&out.stdout).unwrap()
+}
+
+/// Three feeds, two tagged rust, each with one unread item. The URLs refuse
+/// connections so `fetch` only reads what is stored.
+fn tagged() -> TempDir {
+    let home = TempDir::new().unwrap();
+    for (port, name, tags) in [
+        ("9", "blog", &["rust", "news"][..]),
+        ("19", "forum", &["Rust"][..]),
+        ("29", "paper", &["news"][..]),
+    ] {
+        let url = format!("http://127.0.0.1:{port}/feed.xml");
+        let mut cmd = with_home(&home);
+        cmd.args(["add", &url, "--name", name]);
+        for tag in tags {
+            cmd.args(["--tag", tag]);
+        }
+        cmd.assert().success();
+    }
+    let conn = Connection::open(db_path(&home)).unwrap();
+    for feed_id in 1..=3_i64 {
+        conn.execute(
+            "INSERT INTO items (feed_id, ext_id, title, published) VALUES (?1, 'x', ?2, '2026-03-01T09:00:00+00:00')",
+            rusqlite::params![feed_id, format!("item {feed_id}")],
+        )
+        .unwrap();
+    }
+    home
+}
+
+fn names(value: &serde_json::Value, key: &str) -> Vec<String> {
+    let mut names: Vec<String> = value["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item[key].as_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Tags are stored lower case and `list --tag` filters on them
+#[test]
+fn list_filters_by_tag() {
+    let home = tagged();
+
+    let all = json(&home, &["list"]);
+    assert_eq!(all["items"][0]["tags"], serde_json::json!(["rust", "news"]));
+    assert_eq!(
+        names(&json(&home, &["list", "--tag", "RUST"]), "name"),
+        ["blog", "forum"]
+    );
+    assert_eq!(
+        names(&json(&home, &["list", "--tag", "news"]), "name"),
+        ["blog", "paper"]
+    );
+    assert_eq!(
+        json(&home, &["list", "--tag", "go"])["count"],
+        serde_json::json!(0)
+    );
+}
+
+/// `fetch --tag` and `mark-read --tag` cover every feed with the tag
+#[test]
+fn fetch_and_mark_read_by_tag() {
+    let home = tagged();
+    let fetch = [
+        "fetch",
+        "--tag",
+        "rust",
+        "--unread",
+        "--retries",
+        "0",
+        "--timeout-secs",
+        "1",
+    ];
+
+    assert_eq!(names(&json(&home, &fetch), "feed"), ["blog", "forum"]);
+
+    let marked = json(&home, &["mark-read", "--tag", "rust", "--all"]);
+    assert_eq!(marked["count"], serde_json::json!(2));
+    assert_eq!(json(&home, &fetch)["count"], serde_json::json!(0));
+
+    let out = with_home(&home)
+        .args(["mark-read", "--tag", "go", "--all", "--json"])
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    let error: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(error["ok"], serde_json::json!(false));
+}
+
+/// OPML folders become tags on import, also for feeds already subscribed,
+/// and export nests them back
+#[test]
+fn import_maps_folders_to_tags_and_export_nests_them() {
+    let home = tagged();
+    let file = home.path().join("subs.opml");
+    std::fs::write(
+        &file,
+        r#"<?xml version="1.0"?>
+<opml version="2.0"><body>
+  <outline text="Rust">
+    <outline text="Forum" xmlUrl="http://127.0.0.1:19/feed.xml"/>
+    <outline text="Async">
+      <outline text="Tokio blog" xmlUrl="https://tokio.rs/blog/index.xml"/>
+    </outline>
+  </outline>
+</body></opml>"#,
+    )
+    .unwrap();
+
+    let imported = json(&home, &["import", file.to_str().unwrap()]);
+    assert_eq!(imported["count"], serde_json::json!(1));
+    let listed = json(&home, &["list", "--tag", "async"]);
+    assert_eq!(listed["items"][0]["name"], serde_json::json!("Tokio blog"));
+    assert_eq!(
+        listed["items"][0]["tags"],
+        serde_json::json!(["rust", "async"])
+    );
+    let paper = json(&home, &["list", "--tag", "news"]);
+    assert_eq!(paper["count"], serde_json::json!(2));
+
+    let out = with_home(&home)
+        .args(["export", "--format", "opml"])
+        .output()
+        .unwrap();
+    let opml = String::from_utf8(out.stdout).unwrap();
+    assert!(opml.contains(
+        "<outline text=\"async\" title=\"async\">\n        <outline text=\"Tokio blog\""
+    ));
+}
+
+/// `unread` counts stored items per feed, with a total, without fetching
+#[test]
+fn unread_counts_per_feed() {
+    let home = tagged();
+    json(&home, &["read", "1"]);
+
+    let summary = json(&home, &["unread"]);
+    assert_eq!(summary["total"], serde_json::json!(2));
+    assert_eq!(summary["count"], serde_json::json!(3));
+    assert_eq!(summary["items"][0]["feed"], serde_json::json!("blog"));
+    assert_eq!(summary["items"][0]["unread"], serde_json::json!(0));
+    assert!(summary["items"][0]["latest_published"].is_null());
+    assert_eq!(
+        summary["items"][1]["latest_published"],
+        serde_json::json!("2026-03-01T09:00:00+00:00")
+    );
+
+    let news = json(&home, &["unread", "--tag", "news"]);
+    assert_eq!(news["total"], serde_json::json!(1));
+
+    let out = with_home(&home)
+        .args(["unread", "--quiet"])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "2");
+}
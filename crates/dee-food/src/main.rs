@@ -1,9 +1,13 @@
-use std::fs;
-use std::path::PathBuf;
-
-use anyhow::{Context, Result};
+use anyhow::Result;
 use clap::{Args, Parser, Subcommand, ValueEnum};
-use reqwest::blocking::Client;
+use dee_core::http::{base_url, Http, HttpArgs};
+use dee_core::ratelimit;
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, report, Config, ConfigError, ErrorCode, Format, OkItem, OkMessage,
+    SecretsBackend,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 const YELP_BASE: &str = "https://api.yelp.com/v3";
@@ -30,6 +34,11 @@ struct GlobalArgs {
     quiet: bool,
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -91,16 +100,30 @@ struct ShowFlags {
     json: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
 struct AppConfig {
     #[serde(default)]
     api_key: Option<String>,
+    /// `plaintext` (default) or `keyring`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secrets_backend: Option<SecretsBackend>,
+    /// Yelp API root instead of `YELP_BASE`, e.g. a mock server in tests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_url: Option<String>,
+}
+
+/// `DEE_FOOD_API_KEY` and `DEE_FOOD_BASE_URL` override the file. With
+/// `secrets_backend = "keyring"` the key lives in the OS keyring instead of the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-food")
+        .env_overrides(&["api_key", "base_url"])
+        .secret("api_key")
 }
 
 #[derive(Debug, thiserror::Error)]
 enum AppError {
-    #[error("Configuration directory not found")]
-    ConfigMissing,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
     #[error("Missing Yelp API key. Set yelp.api-key via config set")]
     AuthMissing,
     #[error("Unknown config key: {0}")]
@@ -117,10 +140,10 @@ enum AppError {
     ParseFailed,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
-            Self::ConfigMissing => "CONFIG_MISSING",
+            Self::Config(err) => err.code(),
             Self::AuthMissing => "AUTH_MISSING",
             Self::InvalidConfigKey(_) | Self::InvalidArgument(_) => "INVALID_ARGUMENT",
             Self::RequestFailed => "REQUEST_FAILED",
@@ -131,33 +154,7 @@ impl AppError {
     }
 }
 
-#[derive(Debug, Serialize)]
-struct OkList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct OkItem<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
-struct OkMessage {
-    ok: bool,
-    message: String,
-}
-
-#[derive(Debug, Serialize)]
-struct ErrorJson {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct BusinessItem {
     id: String,
     name: String,
@@ -169,7 +166,7 @@ struct BusinessItem {
     location: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ReviewItem {
     id: String,
     rating: i64,
@@ -234,21 +231,27 @@ struct YelpUser {
     name: String,
 }
 
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<BusinessItem>("search")
+        .item::<BusinessItem>("show")
+        .list::<ReviewItem>("reviews")
+        .message("config set")
+        .item::<AppConfig>("config show")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     let result = dispatch(&cli);
     if let Err(err) = result {
-        if cli.global.json {
-            print_json(&ErrorJson {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -275,28 +278,23 @@ fn cmd_search(args: &SearchArgs, out: &GlobalArgs) -> Result<(), AppError> {
         SortBy::Distance => "distance",
     };
 
-    let mut url = format!(
-        "{}/businesses/search?location={}&limit={}&sort_by={}",
-        YELP_BASE,
+    let mut path = format!(
+        "/businesses/search?location={}&limit={}&sort_by={}",
         urlencoding::encode(&args.location),
         args.limit,
         sort
     );
 
     if let Some(term) = &args.term {
-        url.push_str("&term=");
-        url.push_str(&urlencoding::encode(term));
+        path.push_str("&term=");
+        path.push_str(&urlencoding::encode(term));
     }
 
-    let rows: YelpSearchResponse = get_json(&url, out.verbose)?;
+    let rows: YelpSearchResponse = get_json(&path, out)?;
     let items: Vec<BusinessItem> = rows.businesses.into_iter().map(map_business).collect();
 
-    if out.json {
-        print_json(&OkList {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
     } else if out.quiet {
         println!("{}", items.len());
     } else {
@@ -313,8 +311,8 @@ fn cmd_search(args: &SearchArgs, out: &GlobalArgs) -> Result<(), AppError> {
 }
 
 fn cmd_show(args: &ShowArgs, out: &GlobalArgs) -> Result<(), AppError> {
-    let url = format!("{}/businesses/{}", YELP_BASE, args.business_id);
-    let raw: YelpBusiness = get_json(&url, out.verbose)?;
+    let path = format!("/businesses/{}", args.business_id);
+    let raw: YelpBusiness = get_json(&path, out)?;
     let item = map_business(raw);
 
     if out.json {
@@ -340,8 +338,8 @@ fn cmd_show(args: &ShowArgs, out: &GlobalArgs) -> Result<(), AppError> {
 }
 
 fn cmd_reviews(args: &ShowArgs, out: &GlobalArgs) -> Result<(), AppError> {
-    let url = format!("{}/businesses/{}/reviews", YELP_BASE, args.business_id);
-    let raw: YelpReviewsResponse = get_json(&url, out.verbose)?;
+    let path = format!("/businesses/{}/reviews", args.business_id);
+    let raw: YelpReviewsResponse = get_json(&path, out)?;
 
     let items: Vec<ReviewItem> = raw
         .reviews
@@ -356,12 +354,8 @@ fn cmd_reviews(args: &ShowArgs, out: &GlobalArgs) -> Result<(), AppError> {
         })
         .collect();
 
-    if out.json {
-        print_json(&OkList {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
     } else if out.quiet {
         println!("{}", items.len());
     } else {
@@ -376,24 +370,30 @@ fn cmd_reviews(args: &ShowArgs, out: &GlobalArgs) -> Result<(), AppError> {
     Ok(())
 }
 
-fn get_json<T: for<'de> Deserialize<'de>>(url: &str, verbose: bool) -> Result<T, AppError> {
-    let cfg = load_config().map_err(|_| AppError::ConfigMissing)?;
+/// GET `path` under the Yelp API root.
+fn get_json<T: for<'de> Deserialize<'de>>(path: &str, out: &GlobalArgs) -> Result<T, AppError> {
+    let cfg = config_file().load()?;
     let key = cfg
         .api_key
         .filter(|x| !x.trim().is_empty())
         .ok_or(AppError::AuthMissing)?;
+    let url = format!("{}{path}", base_url(cfg.base_url.as_deref(), YELP_BASE));
 
-    if verbose {
-        eprintln!("debug: GET {url}");
+    // Yelp allows 5,000 calls a day per key
+    let wait = ratelimit::reserve(&url)?;
+    if !wait.is_zero() {
+        tracing::debug!("waiting {:.1}s before calling Yelp", wait.as_secs_f64());
+        std::thread::sleep(wait);
     }
+    tracing::debug!("GET {url}");
 
-    let client = Client::builder()
-        .user_agent("dee-food/0.1.0 (https://dee.ink)")
-        .build()
+    let client = Http::new("dee-food", env!("CARGO_PKG_VERSION"))
+        .args(&out.http)
+        .blocking()
         .map_err(|_| AppError::RequestFailed)?;
 
     let response = client
-        .get(url)
+        .get(&url)
         .bearer_auth(key)
         .send()
         .map_err(|_| AppError::RequestFailed)?;
@@ -424,12 +424,17 @@ fn map_business(row: YelpBusiness) -> BusinessItem {
 fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
     match &args.command {
         ConfigCommand::Set(input) => {
-            let mut cfg = load_config().unwrap_or_default();
+            let mut cfg = config_file().load_file()?;
             match input.key.as_str() {
                 "yelp.api-key" | "api_key" => cfg.api_key = Some(input.value.clone()),
+                "yelp.base-url" | "base_url" => cfg.base_url = Some(input.value.clone()),
+                "secrets_backend" => {
+                    cfg.secrets_backend =
+                        Some(input.value.parse().map_err(AppError::InvalidArgument)?)
+                }
                 other => return Err(AppError::InvalidConfigKey(other.to_string())),
             }
-            save_config(&cfg).map_err(|_| AppError::ConfigMissing)?;
+            config_file().save(&cfg)?;
 
             if input.output.json {
                 print_json(&OkMessage {
@@ -442,7 +447,7 @@ fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
             Ok(())
         }
         ConfigCommand::Show(flags) => {
-            let cfg = load_config().unwrap_or_default();
+            let cfg = config_file().load()?;
             if flags.json {
                 print_json(&OkItem {
                     ok: true,
@@ -451,82 +456,16 @@ fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
             } else {
                 let state = cfg.api_key.as_deref().map(|_| "set").unwrap_or("missing");
                 println!("api_key: {state}");
+                println!(
+                    "secrets_backend: {}",
+                    cfg.secrets_backend.unwrap_or_default().as_str()
+                );
             }
             Ok(())
         }
         ConfigCommand::Path => {
-            println!("{}", config_path().display());
+            println!("{}", config_file().path().display());
             Ok(())
         }
     }
 }
-
-fn config_path() -> PathBuf {
-    let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-    path.push("dee-food");
-    path.push("config.toml");
-    path
-}
-
-fn load_config() -> Result<AppConfig> {
-    let path = config_path();
-    if !path.exists() {
-        return Ok(AppConfig::default());
-    }
-
-    let content = fs::read_to_string(&path)
-        .with_context(|| format!("failed reading config at {}", path.display()))?;
-    toml::from_str(&content).context("failed parsing config")
-}
-
-fn save_config(cfg: &AppConfig) -> Result<()> {
-    let path = config_path();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    fs::write(&path, toml::to_string_pretty(cfg)?)?;
-    Ok(())
-}
-
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(text) => println!("{text}"),
-        Err(_) => {
-            println!(
-                "{{\"ok\":false,\"error\":\"serialization failed\",\"code\":\"INTERNAL_ERROR\"}}"
-            );
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -0,0 +1,69 @@
+use assert_cmd::Command;
+use dee_test_support::{fixture, json, MockApi, Sandbox};
+
+fn bin(sandbox: &Sandbox, api: &MockApi) -> Command {
+    let mut cmd = sandbox.command(assert_cmd::cargo::cargo_bin!("dee-food"));
+    cmd.env("DEE_FOOD_BASE_URL", api.url("/v3"))
+        .env("DEE_FOOD_API_KEY", "yelp-test-key");
+    cmd
+}
+
+#[test]
+fn search_show_and_reviews() {
+    let api = MockApi::start();
+    api.get_json("/v3/businesses/search", &fixture("food/search.json"))
+        .get_json(
+            "/v3/businesses/sushi-kashiba-seattle",
+            &fixture("food/business.json"),
+        )
+        .get_json(
+            "/v3/businesses/sushi-kashiba-seattle/reviews",
+            &fixture("food/reviews.json"),
+        );
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args([
+        "search",
+        "Seattle, WA",
+        "--term",
+        "sushi",
+        "--sort",
+        "rating",
+        "--json",
+    ]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(
+        parsed["items"][0]["location"],
+        "86 Pine St, Ste 1, Seattle, WA 98101"
+    );
+    let request = &api.requests()[0];
+    assert_eq!(
+        request.url.query(),
+        Some("location=Seattle%2C%20WA&limit=20&sort_by=rating&term=sushi")
+    );
+    assert_eq!(
+        request.headers.get("authorization").unwrap(),
+        "Bearer yelp-test-key"
+    );
+
+    let (code, parsed) =
+        json(bin(&sandbox, &api).args(["show", "sushi-kashiba-seattle", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["item"]["phone"], "(206) 441-8844");
+
+    let (code, parsed) =
+        json(bin(&sandbox, &api).args(["reviews", "sushi-kashiba-seattle", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["items"][0]["user_name"], "Ana P.");
+}
+
+#[test]
+fn unknown_business_is_not_found() {
+    let api = MockApi::start();
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["show", "nowhere", "--json"]));
+    assert_eq!(code, Some(3));
+    assert_eq!(parsed["code"], "NOT_FOUND");
+}
@@ -1,19 +1,27 @@
-use std::fs;
-use std::path::PathBuf;
-
-use anyhow::{Context, Result};
+use anyhow::Result;
 use clap::{Args, Parser, Subcommand, ValueEnum};
-use reqwest::blocking::Client;
+use dee_core::cache::{Cache, CacheArgs, CacheCommand, CacheError};
+use dee_core::http::{base_url, Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, report, Config, ConfigError, ErrorCode, Format, OkItem, OkMessage,
+    SecretsBackend,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-const EIA_BASE: &str = "https://api.eia.gov/v2/petroleum/pri/gnd/data/";
+const EIA_BASE: &str = "https://api.eia.gov/v2";
+/// Weekly retail gasoline prices, under `EIA_BASE`
+const GAS_PRICES_PATH: &str = "/petroleum/pri/gnd/data/";
+/// EIA publishes once a week, on Mondays
+const CACHE_TTL_SECS: u64 = 6 * 60 * 60;
 
 #[derive(Debug, Parser)]
 #[command(
     name = "dee-gas",
     version,
     about = "Gas prices by US region/state",
-    after_help = "EXAMPLES:\n  dee-gas national --json\n  dee-gas prices --state CA --grade regular --json\n  dee-gas history --state TX --weeks 6 --json\n  dee-gas config set eia.api-key <KEY>"
+    after_help = "EXAMPLES:\n  dee-gas national --json\n  dee-gas prices --state CA --grade regular --json\n  dee-gas history --state TX --weeks 6 --json\n  dee-gas config set eia.api-key <KEY>\n  dee-gas national --no-cache\n  dee-gas cache clear"
 )]
 struct Cli {
     #[command(flatten)]
@@ -30,6 +38,13 @@ struct GlobalArgs {
     quiet: bool,
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    #[command(flatten)]
+    http: HttpArgs,
+    #[command(flatten)]
+    cache: CacheArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -38,6 +53,11 @@ enum Commands {
     National(OutOnlyArgs),
     History(HistoryArgs),
     Config(ConfigArgs),
+    /// Manage cached EIA responses
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -98,39 +118,19 @@ struct ShowFlags {
     json: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
 struct AppConfig {
     #[serde(default)]
     api_key: Option<String>,
+    /// `plaintext` (default) or `keyring`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secrets_backend: Option<SecretsBackend>,
+    /// EIA API root instead of `EIA_BASE`, e.g. a mock server in tests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_url: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct OkList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct OkItem<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
-struct OkMessage {
-    ok: bool,
-    message: String,
-}
-
-#[derive(Debug, Serialize)]
-struct ErrorJson {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct GasPoint {
     period: String,
     area: String,
@@ -140,10 +140,20 @@ struct GasPoint {
     units: String,
 }
 
+/// `DEE_GAS_API_KEY` and `DEE_GAS_BASE_URL` override the file. With
+/// `secrets_backend = "keyring"` the key lives in the OS keyring instead of the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-gas")
+        .env_overrides(&["api_key", "base_url"])
+        .secret("api_key")
+}
+
 #[derive(Debug, thiserror::Error)]
 enum AppError {
-    #[error("Configuration directory not found")]
-    ConfigMissing,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
     #[error("Missing EIA API key. Set eia.api-key via config set")]
     AuthMissing,
     #[error("Unknown config key: {0}")]
@@ -160,10 +170,11 @@ enum AppError {
     ParseFailed,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
-            Self::ConfigMissing => "CONFIG_MISSING",
+            Self::Config(err) => err.code(),
+            Self::Cache(err) => err.code(),
             Self::AuthMissing => "AUTH_MISSING",
             Self::InvalidConfigKey(_) | Self::InvalidArgument(_) => "INVALID_ARGUMENT",
             Self::RequestFailed => "REQUEST_FAILED",
@@ -189,7 +200,8 @@ struct EiaResponse {
 struct EiaRow {
     period: String,
     series: String,
-    #[serde(default)]
+    /// `area-name` in EIA v2 answers
+    #[serde(default, alias = "area-name")]
     area_name: Option<String>,
     #[serde(default)]
     units: Option<String>,
@@ -197,21 +209,28 @@ struct EiaRow {
     value: Option<f64>,
 }
 
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<GasPoint>("prices")
+        .item::<GasPoint>("national")
+        .list::<GasPoint>("history")
+        .message("config set")
+        .item::<AppConfig>("config show")
+        .cache()
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     let result = dispatch(&cli);
     if let Err(err) = result {
-        if cli.global.json {
-            print_json(&ErrorJson {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -221,6 +240,9 @@ fn dispatch(cli: &Cli) -> Result<(), AppError> {
         Commands::National(_) => cmd_national(&cli.global),
         Commands::History(args) => cmd_history(args, &cli.global),
         Commands::Config(args) => cmd_config(args),
+        Commands::Cache { command } => {
+            Ok(dee_core::cache::run(&cache(), *command, cli.global.json)?)
+        }
     }
 }
 
@@ -249,7 +271,7 @@ fn cmd_prices(args: &PricesArgs, out: &GlobalArgs) -> Result<(), AppError> {
     let mut items = Vec::new();
     for area in series_codes {
         let series = series_code(&area, &args.grade);
-        let mut rows = fetch_series(&series, 1, out.verbose)?;
+        let mut rows = fetch_series(&series, 1, out)?;
         if let Some(item) = rows.pop() {
             items.push(item);
         }
@@ -259,12 +281,8 @@ fn cmd_prices(args: &PricesArgs, out: &GlobalArgs) -> Result<(), AppError> {
         return Err(AppError::NotFound);
     }
 
-    if out.json {
-        print_json(&OkList {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
     } else if out.quiet {
         println!("{}", items.len());
     } else {
@@ -281,7 +299,7 @@ fn cmd_prices(args: &PricesArgs, out: &GlobalArgs) -> Result<(), AppError> {
 
 fn cmd_national(out: &GlobalArgs) -> Result<(), AppError> {
     let series = series_code("NUS", &Grade::Regular);
-    let mut rows = fetch_series(&series, 1, out.verbose)?;
+    let mut rows = fetch_series(&series, 1, out)?;
     let item = rows.pop().ok_or(AppError::NotFound)?;
 
     if out.json {
@@ -315,17 +333,13 @@ fn cmd_history(args: &HistoryArgs, out: &GlobalArgs) -> Result<(), AppError> {
     }
 
     let series = series_code(&area, &args.grade);
-    let items = fetch_series(&series, args.weeks, out.verbose)?;
+    let items = fetch_series(&series, args.weeks, out)?;
     if items.is_empty() {
         return Err(AppError::NotFound);
     }
 
-    if out.json {
-        print_json(&OkList {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
     } else if out.quiet {
         println!("{}", items.len());
     } else {
@@ -337,42 +351,60 @@ fn cmd_history(args: &HistoryArgs, out: &GlobalArgs) -> Result<(), AppError> {
     Ok(())
 }
 
-fn fetch_series(series: &str, length: usize, verbose: bool) -> Result<Vec<GasPoint>, AppError> {
-    let cfg = load_config().map_err(|_| AppError::ConfigMissing)?;
-    let api_key = cfg
-        .api_key
-        .filter(|x| !x.trim().is_empty())
-        .ok_or(AppError::AuthMissing)?;
-
-    let url = format!(
-        "{base}?api_key={api}&frequency=weekly&data[0]=value&facets[series][]={series}&sort[0][column]=period&sort[0][direction]=desc&length={length}",
-        base = EIA_BASE,
-        api = urlencoding::encode(&api_key),
+fn fetch_series(series: &str, length: usize, out: &GlobalArgs) -> Result<Vec<GasPoint>, AppError> {
+    let query = format!(
+        "frequency=weekly&data[0]=value&facets[series][]={series}&sort[0][column]=period&sort[0][direction]=desc&length={length}",
         series = urlencoding::encode(series),
         length = length
     );
+    let cfg = config_file().load()?;
+    let endpoint = format!(
+        "{}{GAS_PRICES_PATH}",
+        base_url(cfg.base_url.as_deref(), EIA_BASE)
+    );
+    // The cache key leaves the API key out
+    let key = format!("{endpoint}?{query}");
+
+    let cache = cache();
+    let (raw, fetched) = match cache.lookup::<serde_json::Value>(&key, &out.cache)? {
+        Some(hit) => {
+            tracing::debug!("cached {key} ({}s old)", hit.age_secs);
+            (hit.value, false)
+        }
+        None => {
+            // Only a fetch needs the key, so `--offline` works without one
+            let api_key = cfg
+                .api_key
+                .filter(|x| !x.trim().is_empty())
+                .ok_or(AppError::AuthMissing)?;
+            let url = format!(
+                "{endpoint}?api_key={api}&{query}",
+                api = urlencoding::encode(&api_key)
+            );
+            tracing::debug!("GET {key}");
+            let client = Http::new("dee-gas", env!("CARGO_PKG_VERSION"))
+                .args(&out.http)
+                .blocking()
+                .map_err(|_| AppError::RequestFailed)?;
+            let raw: serde_json::Value = client
+                .get(&url)
+                .send()
+                .map_err(|_| AppError::RequestFailed)?
+                .error_for_status()
+                .map_err(|_| AppError::RequestFailed)?
+                .json()
+                .map_err(|_| AppError::ParseFailed)?;
+            (raw, true)
+        }
+    };
 
-    if verbose {
-        eprintln!("debug: GET {url}");
-    }
-
-    let client = Client::builder()
-        .user_agent("dee-gas/0.1.0 (https://dee.ink)")
-        .build()
-        .map_err(|_| AppError::RequestFailed)?;
-
-    let body: EiaRoot = client
-        .get(&url)
-        .send()
-        .map_err(|_| AppError::RequestFailed)?
-        .error_for_status()
-        .map_err(|_| AppError::RequestFailed)?
-        .json()
-        .map_err(|_| AppError::ParseFailed)?;
-
+    let body: EiaRoot = serde_json::from_value(raw.clone()).map_err(|_| AppError::ParseFailed)?;
     if body.error.is_some() {
         return Err(AppError::ApiError);
     }
+    if fetched {
+        cache.put(&key, &raw);
+    }
 
     let response = body.response.ok_or(AppError::ParseFailed)?;
     let mut out = Vec::new();
@@ -397,6 +429,10 @@ fn fetch_series(series: &str, length: usize, verbose: bool) -> Result<Vec<GasPoi
     Ok(out)
 }
 
+fn cache() -> Cache {
+    Cache::new("dee-gas").ttl_secs(CACHE_TTL_SECS)
+}
+
 fn series_code(area_code: &str, grade: &Grade) -> String {
     let grade_code = match grade {
         Grade::Regular => "PTE",
@@ -429,12 +465,17 @@ fn extract_area_from_series(series: &str) -> String {
 fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
     match &args.command {
         ConfigCommand::Set(input) => {
-            let mut cfg = load_config().unwrap_or_default();
+            let mut cfg = config_file().load_file()?;
             match input.key.as_str() {
                 "eia.api-key" | "api_key" => cfg.api_key = Some(input.value.clone()),
+                "eia.base-url" | "base_url" => cfg.base_url = Some(input.value.clone()),
+                "secrets_backend" => {
+                    cfg.secrets_backend =
+                        Some(input.value.parse().map_err(AppError::InvalidArgument)?)
+                }
                 other => return Err(AppError::InvalidConfigKey(other.to_string())),
             }
-            save_config(&cfg).map_err(|_| AppError::ConfigMissing)?;
+            config_file().save(&cfg)?;
 
             if input.output.json {
                 print_json(&OkMessage {
@@ -447,7 +488,7 @@ fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
             Ok(())
         }
         ConfigCommand::Show(flags) => {
-            let cfg = load_config().unwrap_or_default();
+            let cfg = config_file().load()?;
             if flags.json {
                 print_json(&OkItem {
                     ok: true,
@@ -456,82 +497,16 @@ fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
             } else {
                 let state = cfg.api_key.as_deref().map(|_| "set").unwrap_or("missing");
                 println!("api_key: {state}");
+                println!(
+                    "secrets_backend: {}",
+                    cfg.secrets_backend.unwrap_or_default().as_str()
+                );
             }
             Ok(())
         }
         ConfigCommand::Path => {
-            println!("{}", config_path().display());
+            println!("{}", config_file().path().display());
             Ok(())
         }
     }
 }
-
-fn config_path() -> PathBuf {
-    let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-    path.push("dee-gas");
-    path.push("config.toml");
-    path
-}
-
-fn load_config() -> Result<AppConfig> {
-    let path = config_path();
-    if !path.exists() {
-        return Ok(AppConfig::default());
-    }
-
-    let content = fs::read_to_string(&path)
-        .with_context(|| format!("failed reading config at {}", path.display()))?;
-    toml::from_str(&content).context("failed parsing config")
-}
-
-fn save_config(cfg: &AppConfig) -> Result<()> {
-    let path = config_path();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    fs::write(&path, toml::to_string_pretty(cfg)?)?;
-    Ok(())
-}
-
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(text) => println!("{text}"),
-        Err(_) => {
-            println!(
-                "{{\"ok\":false,\"error\":\"serialization failed\",\"code\":\"INTERNAL_ERROR\"}}"
-            );
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -0,0 +1,66 @@
+use assert_cmd::Command;
+use dee_test_support::matchers::{method, path, query_param};
+use dee_test_support::{fixture, json, json_body, Mock, MockApi, Sandbox};
+
+const PATH: &str = "/v2/petroleum/pri/gnd/data/";
+
+fn bin(sandbox: &Sandbox, api: &MockApi) -> Command {
+    let mut cmd = sandbox.command(assert_cmd::cargo::cargo_bin!("dee-gas"));
+    cmd.env("DEE_GAS_BASE_URL", api.url("/v2"))
+        .env("DEE_GAS_API_KEY", "eia-test");
+    cmd
+}
+
+#[test]
+fn national_and_history_then_cached() {
+    let api = MockApi::start();
+    let mut latest: serde_json::Value =
+        serde_json::from_str(&fixture("gas/national.json")).unwrap();
+    latest["response"]["data"]
+        .as_array_mut()
+        .unwrap()
+        .truncate(1);
+    api.mount(
+        Mock::given(method("GET"))
+            .and(path(PATH))
+            .and(query_param("length", "1"))
+            .respond_with(json_body(200, &latest.to_string())),
+    );
+    api.get_json(PATH, &fixture("gas/national.json"));
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["national", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["item"]["price"], 3.118);
+    assert_eq!(parsed["item"]["area"], "U.S.");
+    assert_eq!(parsed["item"]["period"], "2026-10-12");
+    let query = api.requests()[0].url.query().unwrap().to_string();
+    assert!(
+        query.starts_with("api_key=eia-test&frequency=weekly"),
+        "{query}"
+    );
+    assert!(query.contains("EMM_EPMRR_PTE_NUS_DPG"), "{query}");
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["history", "--weeks", "2", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][1]["price"], 3.156);
+    assert_eq!(api.requests().len(), 2);
+
+    bin(&sandbox, &api)
+        .args(["national", "--json"])
+        .assert()
+        .success();
+    assert_eq!(api.requests().len(), 2);
+}
+
+#[test]
+fn api_errors_are_reported() {
+    let api = MockApi::start();
+    api.get_json(PATH, r#"{"error": "invalid series id", "code": 400}"#);
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["national", "--json"]));
+    assert_eq!(code, Some(5));
+    assert_eq!(parsed["code"], "API_ERROR");
+}
@@ -1,4 +1,5 @@
 use assert_cmd::Command;
+use dee_test_support::seed_cache;
 
 #[test]
 fn emits_json_error_for_missing_auth() {
@@ -11,3 +12,44 @@ fn emits_json_error_for_missing_auth() {
     assert_eq!(parsed["ok"], false);
     assert_eq!(parsed["code"], "AUTH_MISSING");
 }
+
+#[test]
+fn offline_serves_cached_prices_without_an_api_key() {
+    let home = tempfile::tempdir().unwrap();
+    let bin = || {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-gas"));
+        cmd.env("HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path().join("config"))
+            .env("XDG_CACHE_HOME", home.path().join("cache"));
+        cmd
+    };
+
+    let out = bin()
+        .args(["national", "--offline", "--json"])
+        .output()
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).expect("valid json");
+    assert_eq!(parsed["code"], "OFFLINE_MISS");
+
+    // The cache key is the URL minus the API key
+    let key = "https://api.eia.gov/v2/petroleum/pri/gnd/data/?frequency=weekly&data[0]=value&facets[series][]=EMM_EPMRR_PTE_NUS_DPG&sort[0][column]=period&sort[0][direction]=desc&length=1";
+    let body = serde_json::json!({
+        "response": { "data": [{
+            "period": "2026-01-05",
+            "series": "EMM_EPMRR_PTE_NUS_DPG",
+            "area_name": "U.S.",
+            "units": "$/GAL",
+            "value": 3.1
+        }] }
+    });
+    seed_cache(&home.path().join("cache"), "dee-gas", key, &body);
+
+    let out = bin()
+        .args(["national", "--offline", "--json"])
+        .output()
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).expect("valid json");
+    assert_eq!(parsed["ok"], true);
+    assert_eq!(parsed["item"]["price"], 3.1);
+    assert_eq!(parsed["stale"], true);
+}
@@ -0,0 +1,314 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use dee_core::cache::Cache;
+use dee_core::http::Http;
+use dee_core::ratelimit;
+use reqwest::blocking::Response;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::{AppError, GlobalArgs, Issue, IssueState, Release, Repo, SearchSort};
+
+const API_BASE: &str = "https://api.github.com";
+const API_VERSION: &str = "2022-11-28";
+/// Stars and issues move, but not by the minute
+const CACHE_TTL_SECS: u64 = 10 * 60;
+
+#[derive(Debug, Deserialize)]
+struct RepoRoot {
+    full_name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    stargazers_count: u64,
+    #[serde(default)]
+    forks_count: u64,
+    #[serde(default)]
+    open_issues_count: u64,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    license: Option<LicenseRoot>,
+    #[serde(default)]
+    topics: Vec<String>,
+    #[serde(default)]
+    default_branch: String,
+    #[serde(default)]
+    homepage: Option<String>,
+    #[serde(default)]
+    fork: bool,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    pushed_at: Option<String>,
+    html_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LicenseRoot {
+    /// `NOASSERTION` when GitHub cannot tell
+    #[serde(default)]
+    spdx_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchRoot {
+    items: Vec<RepoRoot>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueRoot {
+    number: u64,
+    title: String,
+    state: String,
+    #[serde(default)]
+    user: Option<UserRoot>,
+    #[serde(default)]
+    labels: Vec<LabelRoot>,
+    #[serde(default)]
+    comments: u64,
+    created_at: String,
+    updated_at: String,
+    html_url: String,
+    /// Present on pull requests, which the issues endpoint also lists
+    #[serde(default)]
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserRoot {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LabelRoot {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseRoot {
+    tag_name: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    author: Option<UserRoot>,
+    #[serde(default)]
+    published_at: Option<String>,
+    #[serde(default)]
+    assets: Vec<serde_json::Value>,
+    html_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorRoot {
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// `GITHUB_TEST_BASE_URL` replaces the API host, for tests.
+fn base() -> String {
+    std::env::var("GITHUB_TEST_BASE_URL").unwrap_or_else(|_| API_BASE.to_string())
+}
+
+pub fn repo(name: &str, token: Option<&str>, out: &GlobalArgs) -> Result<Repo, AppError> {
+    let url = format!("{}/repos/{name}", base());
+    let root: RepoRoot = fetch(&url, name, token, out)?;
+    Ok(repo_item(root))
+}
+
+/// Newest first. The endpoint mixes in pull requests, so a full page is
+/// asked for and cut to `limit` once they are left out.
+pub fn issues(
+    name: &str,
+    state: IssueState,
+    labels: &[&str],
+    limit: usize,
+    token: Option<&str>,
+    out: &GlobalArgs,
+) -> Result<Vec<Issue>, AppError> {
+    let mut url = format!(
+        "{}/repos/{name}/issues?state={}&sort=created&direction=desc&per_page=100",
+        base(),
+        state.as_str()
+    );
+    if !labels.is_empty() {
+        url.push_str("&labels=");
+        url.push_str(&urlencoding::encode(&labels.join(",")));
+    }
+    let roots: Vec<IssueRoot> = fetch(&url, name, token, out)?;
+    Ok(roots
+        .into_iter()
+        .filter(|issue| issue.pull_request.is_none())
+        .take(limit)
+        .map(|issue| Issue {
+            number: issue.number,
+            title: issue.title,
+            state: issue.state,
+            author: issue.user.map(|user| user.login),
+            labels: issue.labels.into_iter().map(|label| label.name).collect(),
+            comments: issue.comments,
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+            url: issue.html_url,
+        })
+        .collect())
+}
+
+pub fn releases(
+    name: &str,
+    limit: usize,
+    token: Option<&str>,
+    out: &GlobalArgs,
+) -> Result<Vec<Release>, AppError> {
+    let url = format!("{}/repos/{name}/releases?per_page={limit}", base());
+    let roots: Vec<ReleaseRoot> = fetch(&url, name, token, out)?;
+    Ok(roots
+        .into_iter()
+        .map(|release| Release {
+            tag: release.tag_name,
+            name: release.name.filter(|x| !x.trim().is_empty()),
+            draft: release.draft,
+            prerelease: release.prerelease,
+            author: release.author.map(|user| user.login),
+            published_at: release.published_at,
+            assets: release.assets.len(),
+            url: release.html_url,
+        })
+        .collect())
+}
+
+pub fn search(
+    query: &str,
+    sort: Option<SearchSort>,
+    limit: usize,
+    token: Option<&str>,
+    out: &GlobalArgs,
+) -> Result<Vec<Repo>, AppError> {
+    let mut url = format!(
+        "{}/search/repositories?q={}&per_page={limit}",
+        base(),
+        urlencoding::encode(query)
+    );
+    if let Some(sort) = sort {
+        url.push_str("&sort=");
+        url.push_str(sort.as_str());
+    }
+    let root: SearchRoot = fetch(&url, query, token, out)?;
+    Ok(root.items.into_iter().map(repo_item).collect())
+}
+
+fn repo_item(root: RepoRoot) -> Repo {
+    Repo {
+        full_name: root.full_name,
+        description: root.description.filter(|x| !x.trim().is_empty()),
+        stars: root.stargazers_count,
+        forks: root.forks_count,
+        open_issues: root.open_issues_count,
+        language: root.language,
+        license: root
+            .license
+            .and_then(|license| license.spdx_id)
+            .filter(|id| id != "NOASSERTION"),
+        topics: root.topics,
+        default_branch: root.default_branch,
+        homepage: root.homepage.filter(|x| !x.trim().is_empty()),
+        fork: root.fork,
+        archived: root.archived,
+        created_at: root.created_at,
+        pushed_at: root.pushed_at,
+        url: root.html_url,
+    }
+}
+
+/// The JSON at `url` as `T`, from the cache when fresh. The token never
+/// reaches the cache key. A 404 is `NotFound` for `what`.
+fn fetch<T: DeserializeOwned>(
+    url: &str,
+    what: &str,
+    token: Option<&str>,
+    out: &GlobalArgs,
+) -> Result<T, AppError> {
+    let cache = Cache::new("dee-github").ttl_secs(CACHE_TTL_SECS);
+    if let Some(hit) = cache.lookup::<serde_json::Value>(url, &out.cache)? {
+        tracing::debug!("cached {url} ({}s old)", hit.age_secs);
+        return serde_json::from_value(hit.value).map_err(|_| AppError::ParseFailed);
+    }
+
+    let wait = ratelimit::reserve(url)?;
+    if !wait.is_zero() {
+        tracing::debug!("waiting {:.1}s before calling GitHub", wait.as_secs_f64());
+        std::thread::sleep(wait);
+    }
+    tracing::debug!("GET {url}");
+
+    let client = Http::new("dee-github", env!("CARGO_PKG_VERSION"))
+        .args(&out.http)
+        .blocking()
+        .map_err(|_| AppError::RequestFailed)?;
+    let mut request = client
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", API_VERSION);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().map_err(|_| AppError::RequestFailed)?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(status_error(response, what, token.is_some()));
+    }
+    let raw: serde_json::Value = response.json().map_err(|_| AppError::ParseFailed)?;
+    let value = serde_json::from_value(raw.clone()).map_err(|_| AppError::ParseFailed)?;
+    cache.put(url, &raw);
+    Ok(value)
+}
+
+/// GitHub answers an exhausted quota with 403 or 429 and
+/// `x-ratelimit-remaining: 0`; other 403s are permission problems.
+fn status_error(response: Response, what: &str, authenticated: bool) -> AppError {
+    let status = response.status();
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let exhausted = header("x-ratelimit-remaining").as_deref() == Some("0");
+    let reset = header("x-ratelimit-reset").and_then(|value| value.parse::<u64>().ok());
+    let message = response
+        .json::<ErrorRoot>()
+        .ok()
+        .and_then(|body| body.message)
+        .unwrap_or_else(|| format!("HTTP {}", status.as_u16()));
+
+    match status {
+        StatusCode::NOT_FOUND => AppError::NotFound(what.to_string()),
+        StatusCode::UNAUTHORIZED => AppError::AuthInvalid,
+        StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+            if exhausted || status == StatusCode::TOO_MANY_REQUESTS =>
+        {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let mut hint = match reset {
+                Some(reset) => format!("resets in {}s", reset.saturating_sub(now)),
+                None => "try again shortly".to_string(),
+            };
+            if !authenticated {
+                hint.push_str("; set a token with `config set token` for 5,000 requests an hour");
+            }
+            AppError::RateLimited(hint)
+        }
+        StatusCode::UNPROCESSABLE_ENTITY => AppError::InvalidArgument(message),
+        _ => AppError::ApiError(message),
+    }
+}
@@ -0,0 +1,571 @@
+mod api;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::cache::{Cache, CacheArgs, CacheCommand, CacheError};
+use dee_core::http::HttpArgs;
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, report, Config, ConfigError, ErrorCode, Format, OkItem, OkMessage,
+    SecretsBackend,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "dee-github",
+    version,
+    about = "GitHub repositories, issues, releases and search",
+    after_help = "EXAMPLES:\n  dee-github repo rust-lang/rust --json\n  dee-github issues rust-lang/rust --state open --label bug --limit 5 --json\n  dee-github issues tokio-rs/tokio --label A-tokio --label C-bug --output table\n  dee-github releases BurntSushi/ripgrep --limit 3 --json\n  dee-github search \"terminal ui\" --language rust --sort stars --json\n  dee-github config set token <TOKEN>\n  dee-github config set secrets_backend keyring"
+)]
+struct Cli {
+    #[command(flatten)]
+    global: GlobalArgs,
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, Clone, Args)]
+pub(crate) struct GlobalArgs {
+    #[arg(short = 'j', long, global = true)]
+    json: bool,
+    #[arg(short = 'q', long, global = true)]
+    quiet: bool,
+    #[arg(short = 'v', long, global = true)]
+    pub(crate) verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    #[command(flatten)]
+    pub(crate) http: HttpArgs,
+    #[command(flatten)]
+    pub(crate) cache: CacheArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// Repository metadata: stars, forks, language, license
+    Repo(RepoArgs),
+    /// Issues of a repository, newest first (pull requests left out)
+    Issues(IssuesArgs),
+    /// Releases of a repository, newest first
+    Releases(ReleasesArgs),
+    /// Search repositories by keyword
+    Search(SearchArgs),
+    Config(ConfigArgs),
+    /// Manage cached API responses
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(Debug, Args)]
+struct RepoArgs {
+    /// owner/name, or a github.com URL
+    repo: String,
+}
+
+#[derive(Debug, Args)]
+struct IssuesArgs {
+    /// owner/name, or a github.com URL
+    repo: String,
+    #[arg(long, value_enum, default_value_t = IssueState::Open)]
+    state: IssueState,
+    /// Only issues with this label; repeat to require several
+    #[arg(long = "label", value_name = "LABEL")]
+    labels: Vec<String>,
+    /// Most issues to list (1-100)
+    #[arg(long, default_value_t = 30)]
+    limit: usize,
+}
+
+#[derive(Debug, Args)]
+struct ReleasesArgs {
+    /// owner/name, or a github.com URL
+    repo: String,
+    /// Most releases to list (1-100)
+    #[arg(long, default_value_t = 10)]
+    limit: usize,
+}
+
+#[derive(Debug, Args)]
+struct SearchArgs {
+    query: String,
+    /// Only repositories in this language
+    #[arg(long)]
+    language: Option<String>,
+    /// Order of results (default: best match)
+    #[arg(long, value_enum)]
+    sort: Option<SearchSort>,
+    /// Most results to return (1-100)
+    #[arg(long, default_value_t = 10)]
+    limit: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum IssueState {
+    Open,
+    Closed,
+    All,
+}
+
+impl IssueState {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Closed => "closed",
+            Self::All => "all",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum SearchSort {
+    Stars,
+    Forks,
+    Updated,
+}
+
+impl SearchSort {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Stars => "stars",
+            Self::Forks => "forks",
+            Self::Updated => "updated",
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct ConfigArgs {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    /// Set a key: token, secrets_backend
+    Set(ConfigSetArgs),
+    Show(ShowFlags),
+    Path,
+}
+
+#[derive(Debug, Args)]
+struct ConfigSetArgs {
+    key: String,
+    value: String,
+    #[command(flatten)]
+    output: ShowFlags,
+}
+
+#[derive(Debug, Args)]
+struct ShowFlags {
+    #[arg(short = 'j', long)]
+    json: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
+pub(crate) struct AppConfig {
+    /// Personal access token; optional, but lifts the limit from 60 to 5,000
+    /// requests an hour and reaches private repositories
+    #[serde(default)]
+    pub(crate) token: Option<String>,
+    /// `plaintext` (default) or `keyring`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secrets_backend: Option<SecretsBackend>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct Repo {
+    /// `owner/name`
+    pub(crate) full_name: String,
+    pub(crate) description: Option<String>,
+    pub(crate) stars: u64,
+    pub(crate) forks: u64,
+    /// Open issues and pull requests together, as GitHub counts them
+    pub(crate) open_issues: u64,
+    pub(crate) language: Option<String>,
+    /// SPDX id such as `MIT`
+    pub(crate) license: Option<String>,
+    pub(crate) topics: Vec<String>,
+    pub(crate) default_branch: String,
+    pub(crate) homepage: Option<String>,
+    pub(crate) fork: bool,
+    pub(crate) archived: bool,
+    pub(crate) created_at: Option<String>,
+    /// Last push to any branch
+    pub(crate) pushed_at: Option<String>,
+    pub(crate) url: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct Issue {
+    pub(crate) number: u64,
+    pub(crate) title: String,
+    /// `open` or `closed`
+    pub(crate) state: String,
+    pub(crate) author: Option<String>,
+    pub(crate) labels: Vec<String>,
+    pub(crate) comments: u64,
+    pub(crate) created_at: String,
+    pub(crate) updated_at: String,
+    pub(crate) url: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct Release {
+    pub(crate) tag: String,
+    pub(crate) name: Option<String>,
+    pub(crate) draft: bool,
+    pub(crate) prerelease: bool,
+    pub(crate) author: Option<String>,
+    /// `null` for drafts
+    pub(crate) published_at: Option<String>,
+    /// Uploaded files, not counting the source archives
+    pub(crate) assets: usize,
+    pub(crate) url: String,
+}
+
+/// `DEE_GITHUB_TOKEN` overrides the file. With `secrets_backend = "keyring"`
+/// the token lives in the OS keyring instead of the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-github")
+        .env_overrides(&["token"])
+        .secret("token")
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum AppError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+    #[error("Unknown config key: {0} (expected token or secrets_backend)")]
+    InvalidConfigKey(String),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("Not found on GitHub: {0}")]
+    NotFound(String),
+    #[error("GitHub rejected the token. Check it via config set token")]
+    AuthInvalid,
+    #[error("GitHub rate limit reached; {0}")]
+    RateLimited(String),
+    #[error("HTTP request failed")]
+    RequestFailed,
+    #[error("GitHub API returned an error: {0}")]
+    ApiError(String),
+    #[error("Response parse failed")]
+    ParseFailed,
+}
+
+impl ErrorCode for AppError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Config(err) => err.code(),
+            Self::Cache(err) => err.code(),
+            Self::InvalidConfigKey(_) | Self::InvalidArgument(_) => "INVALID_ARGUMENT",
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::AuthInvalid => "AUTH_INVALID",
+            Self::RateLimited(_) => "RATE_LIMITED",
+            Self::RequestFailed => "REQUEST_FAILED",
+            Self::ApiError(_) => "API_ERROR",
+            Self::ParseFailed => "PARSE_FAILED",
+        }
+    }
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .item::<Repo>("repo")
+        .list::<Issue>("issues")
+        .list::<Release>("releases")
+        .list::<Repo>("search")
+        .message("config set")
+        .item::<AppConfig>("config show")
+        .cache()
+}
+
+fn main() {
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
+
+    if let Err(err) = dispatch(&cli) {
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
+    }
+}
+
+fn dispatch(cli: &Cli) -> Result<(), AppError> {
+    match &cli.command {
+        Commands::Config(args) => return cmd_config(args),
+        Commands::Cache { command } => {
+            return Ok(dee_core::cache::run(
+                &Cache::new("dee-github"),
+                *command,
+                cli.global.json,
+            )?)
+        }
+        _ => {}
+    }
+
+    let config = config_file().load()?;
+    let token = config.token.as_deref().filter(|x| !x.trim().is_empty());
+    if token.is_none() {
+        tracing::debug!("no token; unauthenticated requests are limited to 60 an hour");
+    }
+    match &cli.command {
+        Commands::Repo(args) => cmd_repo(args, token, &cli.global),
+        Commands::Issues(args) => cmd_issues(args, token, &cli.global),
+        Commands::Releases(args) => cmd_releases(args, token, &cli.global),
+        Commands::Search(args) => cmd_search(args, token, &cli.global),
+        Commands::Config(_) | Commands::Cache { .. } => unreachable!("handled above"),
+    }
+}
+
+fn cmd_repo(args: &RepoArgs, token: Option<&str>, out: &GlobalArgs) -> Result<(), AppError> {
+    let item = api::repo(&repo_name(&args.repo)?, token, out)?;
+
+    if out.json {
+        print_json(&OkItem { ok: true, item });
+    } else if out.quiet {
+        println!("{}", item.full_name);
+    } else {
+        let mut flags = Vec::new();
+        if item.archived {
+            flags.push("archived");
+        }
+        if item.fork {
+            flags.push("fork");
+        }
+        let flags = if flags.is_empty() {
+            String::new()
+        } else {
+            format!("  ({})", flags.join(", "))
+        };
+        println!("{}{flags}", item.full_name);
+        if let Some(description) = &item.description {
+            println!("  {description}");
+        }
+        println!(
+            "  stars: {}  forks: {}  open issues: {}",
+            item.stars, item.forks, item.open_issues
+        );
+        for (label, value) in [
+            ("language", &item.language),
+            ("license", &item.license),
+            ("homepage", &item.homepage),
+            ("pushed", &item.pushed_at),
+        ] {
+            if let Some(value) = value {
+                println!("  {label}: {value}");
+            }
+        }
+        if !item.topics.is_empty() {
+            println!("  topics: {}", item.topics.join(", "));
+        }
+        println!("  {}", item.url);
+    }
+
+    Ok(())
+}
+
+fn cmd_issues(args: &IssuesArgs, token: Option<&str>, out: &GlobalArgs) -> Result<(), AppError> {
+    check_limit(args.limit)?;
+    let labels: Vec<&str> = args
+        .labels
+        .iter()
+        .map(|label| label.trim())
+        .filter(|label| !label.is_empty())
+        .collect();
+    let items = api::issues(
+        &repo_name(&args.repo)?,
+        args.state,
+        &labels,
+        args.limit,
+        token,
+        out,
+    )?;
+
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
+    } else if out.quiet {
+        for item in items {
+            println!("{}", item.number);
+        }
+    } else {
+        for item in items {
+            let state = if item.state == "closed" {
+                "  (closed)"
+            } else {
+                ""
+            };
+            println!("#{:<6} {}{state}", item.number, item.title);
+            if !item.labels.is_empty() {
+                println!("        [{}]", item.labels.join(", "));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_releases(
+    args: &ReleasesArgs,
+    token: Option<&str>,
+    out: &GlobalArgs,
+) -> Result<(), AppError> {
+    check_limit(args.limit)?;
+    let items = api::releases(&repo_name(&args.repo)?, args.limit, token, out)?;
+
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
+    } else {
+        for item in items {
+            if out.quiet {
+                println!("{}", item.tag);
+                continue;
+            }
+            let date = item.published_at.as_deref().unwrap_or("-");
+            let date = date.get(..10).unwrap_or(date);
+            let kind = if item.draft {
+                "  draft"
+            } else if item.prerelease {
+                "  pre-release"
+            } else {
+                ""
+            };
+            let name = item
+                .name
+                .as_deref()
+                .filter(|name| *name != item.tag)
+                .unwrap_or("");
+            println!("{:<24} {date}{kind}  {name}", item.tag);
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_search(args: &SearchArgs, token: Option<&str>, out: &GlobalArgs) -> Result<(), AppError> {
+    check_limit(args.limit)?;
+    let query = args.query.trim();
+    if query.is_empty() {
+        return Err(AppError::InvalidArgument(
+            "query must not be empty".to_string(),
+        ));
+    }
+    let query = match args.language.as_deref().map(str::trim) {
+        Some(language) if !language.is_empty() => format!("{query} language:{language}"),
+        _ => query.to_string(),
+    };
+    let items = api::search(&query, args.sort, args.limit, token, out)?;
+
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
+    } else {
+        for item in items {
+            if out.quiet {
+                println!("{}", item.full_name);
+                continue;
+            }
+            println!("{}  ★ {}", item.full_name, item.stars);
+            if let Some(description) = &item.description {
+                println!("  {description}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_limit(limit: usize) -> Result<(), AppError> {
+    if (1..=100).contains(&limit) {
+        Ok(())
+    } else {
+        Err(AppError::InvalidArgument(
+            "--limit must be between 1 and 100".to_string(),
+        ))
+    }
+}
+
+/// `owner/name` from `owner/name`, `github.com/owner/name` or a full URL.
+fn repo_name(text: &str) -> Result<String, AppError> {
+    let trimmed = text.trim().trim_end_matches('/');
+    let path = trimmed
+        .strip_prefix("https://")
+        .or_else(|| trimmed.strip_prefix("http://"))
+        .unwrap_or(trimmed);
+    let path = path.strip_prefix("github.com/").unwrap_or(path);
+    let path = path.strip_suffix(".git").unwrap_or(path);
+
+    let mut parts = path.split('/');
+    let valid = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(owner), Some(name), None) if valid(owner) && valid(name) => {
+            Ok(format!("{owner}/{name}"))
+        }
+        _ => Err(AppError::InvalidArgument(format!(
+            "expected owner/name, got '{text}'"
+        ))),
+    }
+}
+
+fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
+    match &args.command {
+        ConfigCommand::Set(input) => {
+            let mut cfg = config_file().load_file()?;
+            match input.key.as_str() {
+                "token" => cfg.token = Some(input.value.clone()),
+                "secrets_backend" => {
+                    cfg.secrets_backend =
+                        Some(input.value.parse().map_err(AppError::InvalidArgument)?)
+                }
+                other => return Err(AppError::InvalidConfigKey(other.to_string())),
+            }
+            config_file().save(&cfg)?;
+
+            if input.output.json {
+                print_json(&OkMessage {
+                    ok: true,
+                    message: "Config updated".to_string(),
+                });
+            } else {
+                println!("Config updated");
+            }
+            Ok(())
+        }
+        ConfigCommand::Show(flags) => {
+            let cfg = config_file().load()?;
+            if flags.json {
+                print_json(&OkItem {
+                    ok: true,
+                    item: cfg,
+                });
+            } else {
+                println!(
+                    "token: {}",
+                    cfg.token.as_deref().map(|_| "set").unwrap_or("missing")
+                );
+                println!(
+                    "secrets_backend: {}",
+                    cfg.secrets_backend.unwrap_or_default().as_str()
+                );
+            }
+            Ok(())
+        }
+        ConfigCommand::Path => {
+            println!("{}", config_file().path().display());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use assert_cmd::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::mpsc;
+
+fn bin(home: &Path, url: &str) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-github"));
+    cmd.env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("DEE_RATELIMIT_DIR", home.join("ratelimit"))
+        .env("GITHUB_TEST_BASE_URL", url)
+        .env_remove("DEE_GITHUB_TOKEN");
+    cmd
+}
+
+const REPO: &str = r#"{
+  "full_name": "octo/widget", "description": "Widgets for everyone", "stargazers_count": 1200,
+  "forks_count": 80, "open_issues_count": 14, "language": "Rust",
+  "license": {"key": "mit", "spdx_id": "MIT"}, "topics": ["cli", "widgets"],
+  "default_branch": "main", "homepage": "", "fork": false, "archived": false,
+  "created_at": "2020-01-01T00:00:00Z", "pushed_at": "2026-10-15T08:00:00Z",
+  "html_url": "https://github.com/octo/widget"
+}"#;
+
+const ISSUES: &str = r#"[
+  {"number": 42, "title": "Crash on start", "state": "open", "user": {"login": "ana"},
+   "labels": [{"name": "bug"}], "comments": 3, "created_at": "2026-10-14T00:00:00Z",
+   "updated_at": "2026-10-15T00:00:00Z", "html_url": "https://github.com/octo/widget/issues/42"},
+  {"number": 41, "title": "Fix crash", "state": "open", "user": {"login": "bo"},
+   "labels": [{"name": "bug"}], "comments": 0, "created_at": "2026-10-13T00:00:00Z",
+   "updated_at": "2026-10-13T00:00:00Z", "html_url": "https://github.com/octo/widget/pull/41",
+   "pull_request": {"url": "https://api.github.com/repos/octo/widget/pulls/41"}},
+  {"number": 40, "title": "Typo in docs", "state": "open", "user": null,
+   "labels": [], "comments": 1, "created_at": "2026-10-12T00:00:00Z",
+   "updated_at": "2026-10-12T00:00:00Z", "html_url": "https://github.com/octo/widget/issues/40"}
+]"#;
+
+const RELEASES: &str = r#"[
+  {"tag_name": "v2.0.0-rc.1", "name": "", "draft": false, "prerelease": true,
+   "author": {"login": "ana"}, "published_at": "2026-10-01T00:00:00Z", "assets": [{}, {}],
+   "html_url": "https://github.com/octo/widget/releases/tag/v2.0.0-rc.1"},
+  {"tag_name": "v1.0.0", "name": "Widget 1.0", "draft": false, "prerelease": false,
+   "author": {"login": "ana"}, "published_at": "2026-01-01T00:00:00Z", "assets": [],
+   "html_url": "https://github.com/octo/widget/releases/tag/v1.0.0"}
+]"#;
+
+/// Canned GitHub answers; each request's line and headers go down `requests`.
+fn serve() -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (sender, requests) = mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            let path = head.split_whitespace().nth(1).unwrap_or("").to_string();
+            let _ = sender.send(head.to_lowercase());
+            let (status, extra, body) = if path == "/repos/octo/widget" {
+                ("200 OK", "", REPO.to_string())
+            } else if path.starts_with("/repos/octo/widget/issues?") {
+                ("200 OK", "", ISSUES.to_string())
+            } else if path.starts_with("/repos/octo/widget/releases?") {
+                ("200 OK", "", RELEASES.to_string())
+            } else if path.starts_with("/search/repositories?") {
+                (
+                    "200 OK",
+                    "",
+                    format!(r#"{{"total_count":1,"items":[{REPO}]}}"#),
+                )
+            } else if path == "/repos/octo/limited" {
+                (
+                    "403 Forbidden",
+                    "x-ratelimit-remaining: 0\r\n",
+                    r#"{"message":"API rate limit exceeded"}"#.to_string(),
+                )
+            } else if path == "/repos/octo/private" {
+                (
+                    "401 Unauthorized",
+                    "",
+                    r#"{"message":"Bad credentials"}"#.to_string(),
+                )
+            } else {
+                (
+                    "404 Not Found",
+                    "",
+                    r#"{"message":"Not Found"}"#.to_string(),
+                )
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\n{extra}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    (format!("http://{addr}"), requests)
+}
+
+fn json(cmd: &mut Command) -> (Option<i32>, serde_json::Value) {
+    let out = cmd.output().unwrap();
+    let parsed = serde_json::from_slice(&out.stdout).expect("valid json on stdout");
+    (out.status.code(), parsed)
+}
+
+#[test]
+fn repo_accepts_names_and_urls() {
+    let (url, _) = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    let (code, parsed) = json(bin(home.path(), &url).args(["repo", "octo/widget", "--json"]));
+    assert_eq!(code, Some(0));
+    let item = &parsed["item"];
+    assert_eq!(item["full_name"], "octo/widget");
+    assert_eq!(item["stars"], 1200);
+    assert_eq!(item["license"], "MIT");
+    assert_eq!(item["homepage"], serde_json::Value::Null);
+    assert_eq!(item["topics"][1], "widgets");
+
+    let (code, _) =
+        json(bin(home.path(), &url).args(["repo", "https://github.com/octo/widget.git", "--json"]));
+    assert_eq!(code, Some(0));
+
+    let (code, parsed) = json(bin(home.path(), &url).args(["repo", "octo", "--json"]));
+    assert_eq!(code, Some(2));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    let (code, parsed) = json(bin(home.path(), &url).args(["repo", "octo/missing", "--json"]));
+    assert_eq!(code, Some(3));
+    assert_eq!(parsed["code"], "NOT_FOUND");
+}
+
+#[test]
+fn issues_leave_out_pull_requests() {
+    let (url, requests) = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    let (code, parsed) = json(bin(home.path(), &url).args([
+        "issues",
+        "octo/widget",
+        "--state",
+        "all",
+        "--label",
+        "bug",
+        "--label",
+        "good first issue",
+        "--json",
+    ]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][0]["number"], 42);
+    assert_eq!(parsed["items"][0]["author"], "ana");
+    assert_eq!(parsed["items"][0]["labels"][0], "bug");
+    assert_eq!(parsed["items"][1]["number"], 40);
+
+    let request = requests.recv().unwrap();
+    assert!(request.contains("state=all"), "{request}");
+    assert!(
+        request.contains("labels=bug%2cgood%20first%20issue"),
+        "{request}"
+    );
+
+    let (_, parsed) =
+        json(bin(home.path(), &url).args(["issues", "octo/widget", "--limit", "1", "--json"]));
+    assert_eq!(parsed["count"], 1);
+
+    let (code, parsed) =
+        json(bin(home.path(), &url).args(["issues", "octo/widget", "--limit", "0", "--json"]));
+    assert_eq!(code, Some(2));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn releases_and_search() {
+    let (url, requests) = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    let (_, parsed) = json(bin(home.path(), &url).args(["releases", "octo/widget", "--json"]));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][0]["tag"], "v2.0.0-rc.1");
+    assert_eq!(parsed["items"][0]["name"], serde_json::Value::Null);
+    assert_eq!(parsed["items"][0]["prerelease"], true);
+    assert_eq!(parsed["items"][0]["assets"], 2);
+    requests.recv().unwrap();
+
+    let (_, parsed) = json(bin(home.path(), &url).args([
+        "search",
+        "widgets",
+        "--language",
+        "rust",
+        "--sort",
+        "stars",
+        "--json",
+    ]));
+    assert_eq!(parsed["count"], 1);
+    assert_eq!(parsed["items"][0]["full_name"], "octo/widget");
+    let request = requests.recv().unwrap();
+    assert!(
+        request.contains("q=widgets%20language%3arust&per_page=10&sort=stars"),
+        "{request}"
+    );
+}
+
+#[test]
+fn token_is_sent_and_failures_are_classified() {
+    let (url, requests) = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    bin(home.path(), &url)
+        .args(["repo", "octo/widget", "--no-cache", "--json"])
+        .assert()
+        .success();
+    let request = requests.recv().unwrap();
+    assert!(!request.contains("authorization"), "{request}");
+    assert!(
+        request.contains("x-github-api-version: 2022-11-28"),
+        "{request}"
+    );
+
+    bin(home.path(), &url)
+        .args(["config", "set", "token", "ghp_test123"])
+        .assert()
+        .success();
+    bin(home.path(), &url)
+        .args(["repo", "octo/widget", "--no-cache", "--json"])
+        .assert()
+        .success();
+    let request = requests.recv().unwrap();
+    assert!(
+        request.contains("authorization: bearer ghp_test123"),
+        "{request}"
+    );
+
+    let (code, parsed) = json(bin(home.path(), &url).args(["repo", "octo/limited", "--json"]));
+    assert_eq!(code, Some(5));
+    assert_eq!(parsed["code"], "RATE_LIMITED");
+
+    let (code, parsed) = json(bin(home.path(), &url).args(["repo", "octo/private", "--json"]));
+    assert_eq!(code, Some(4));
+    assert_eq!(parsed["code"], "AUTH_INVALID");
+}
+
+#[test]
+fn offline_serves_the_cache() {
+    let (url, _) = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    let (code, parsed) =
+        json(bin(home.path(), &url).args(["repo", "octo/widget", "--offline", "--json"]));
+    assert_eq!(code, Some(3));
+    assert_eq!(parsed["code"], "OFFLINE_MISS");
+
+    bin(home.path(), &url)
+        .args(["repo", "octo/widget", "--json"])
+        .assert()
+        .success();
+    let (code, parsed) =
+        json(bin(home.path(), &url).args(["repo", "octo/widget", "--offline", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["stale"], true);
+    assert_eq!(parsed["item"]["forks"], 80);
+
+    let (_, parsed) =
+        json(bin(home.path(), &url).args(["config", "set", "color", "blue", "--json"]));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn debug_logs_go_to_stderr_as_json_lines() {
+    let (url, _) = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    let out = bin(home.path(), &url)
+        .args([
+            "repo",
+            "octo/widget",
+            "--json",
+            "--log-level",
+            "debug",
+            "--log-format",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["item"]["full_name"], "octo/widget");
+
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    let lines: Vec<serde_json::Value> = stderr
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("JSON log line"))
+        .collect();
+    assert!(lines.iter().any(|line| line["level"] == "DEBUG"
+        && line["message"] == format!("GET {url}/repos/octo/widget")));
+
+    let out = bin(home.path(), &url)
+        .args(["repo", "octo/widget", "--json", "-v"])
+        .env("DEE_LOG_LEVEL", "off")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        stderr
+            .lines()
+            .any(|line| line.starts_with("debug: cached ")),
+        "{stderr}"
+    );
+}
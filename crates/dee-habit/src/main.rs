@@ -3,7 +3,11 @@ use std::path::PathBuf;
 
 use chrono::{Duration, NaiveDate, SecondsFormat, Utc};
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::format::render_list;
+use dee_core::schema::{self, Schemas};
+use dee_core::{report, ErrorCode, Format, OkItem};
 use rusqlite::{params, Connection, OptionalExtension};
+use schemars::JsonSchema;
 use serde::Serialize;
 
 #[derive(Debug, Parser)]
@@ -35,6 +39,10 @@ struct GlobalFlags {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -88,20 +96,7 @@ impl CadenceArg {
     }
 }
 
-#[derive(Debug, Serialize)]
-struct ListResponse<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct ItemResponse<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ActionResponse {
     ok: bool,
     message: String,
@@ -109,13 +104,6 @@ struct ActionResponse {
     id: Option<i64>,
 }
 
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
 #[derive(Debug, Clone)]
 struct HabitRecord {
     id: i64,
@@ -124,7 +112,7 @@ struct HabitRecord {
     created_at: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct HabitListItem {
     id: i64,
     name: String,
@@ -136,7 +124,7 @@ struct HabitListItem {
     last_done_on: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct StreakItem {
     id: i64,
     name: String,
@@ -163,7 +151,7 @@ enum AppError {
     Serialize,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
             Self::DataDirMissing => "CONFIG_MISSING",
@@ -178,31 +166,26 @@ impl AppError {
 
 type AppResult<T> = Result<T, AppError>;
 
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .custom::<ActionResponse>("add")
+        .list::<HabitListItem>("list")
+        .custom::<ActionResponse>("done")
+        .item::<StreakItem>("streak")
+        .custom::<ActionResponse>("delete")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = run(&cli) {
-        if cli.global.json {
-            let payload = ErrorResponse {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            };
-
-            match serde_json::to_string(&payload) {
-                Ok(rendered) => println!("{rendered}"),
-                Err(_) => {
-                    let escaped = escape_json(&err.to_string());
-                    println!(
-                        "{{\"ok\":false,\"error\":\"{escaped}\",\"code\":\"{}\"}}",
-                        err.code()
-                    );
-                }
-            }
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -212,9 +195,7 @@ fn run(cli: &Cli) -> AppResult<()> {
         fs::create_dir_all(parent).map_err(|_| AppError::Database)?;
     }
 
-    if cli.global.verbose {
-        eprintln!("[dee-habit] db_path={}", db_path.display());
-    }
+    tracing::debug!("db_path={}", db_path.display());
 
     let conn = Connection::open(db_path).map_err(|_| AppError::Database)?;
     initialize_db(&conn)?;
@@ -307,12 +288,8 @@ fn cmd_list(conn: &Connection, global: &GlobalFlags) -> AppResult<()> {
         });
     }
 
-    if global.json {
-        write_json(&ListResponse {
-            ok: true,
-            count: items.len(),
-            items,
-        })
+    if let Some(format) = Format::resolve(global.output, global.json) {
+        write_list(&items, format)
     } else if global.quiet {
         println!("{}", items.len());
         Ok(())
@@ -384,7 +361,7 @@ fn cmd_streak(conn: &Connection, args: &HabitArg, global: &GlobalFlags) -> AppRe
     };
 
     if global.json {
-        write_json(&ItemResponse { ok: true, item })
+        write_json(&OkItem { ok: true, item })
     } else if global.quiet {
         println!("{current_streak}");
         Ok(())
@@ -639,16 +616,14 @@ fn write_json<T: Serialize>(value: &T) -> AppResult<()> {
     Ok(())
 }
 
-fn now_timestamp() -> String {
-    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+fn write_list<T: Serialize>(items: &[T], format: Format) -> AppResult<()> {
+    let rendered = render_list(items, format).map_err(|_| AppError::Serialize)?;
+    print!("{rendered}");
+    Ok(())
 }
 
-fn escape_json(value: &str) -> String {
-    value
-        .replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace('\n', "\\n")
-        .replace('\r', "\\r")
+fn now_timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
 }
 
 fn is_unique_violation(err: &rusqlite::Error, target: &str) -> bool {
@@ -657,35 +632,3 @@ fn is_unique_violation(err: &rusqlite::Error, target: &str) -> bool {
         _ => false,
     }
 }
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -1,18 +1,32 @@
 use anyhow::{anyhow, Context, Result};
 use chrono::{TimeZone, Utc};
 use clap::{Args, Parser, Subcommand};
+use dee_core::cache::{Cache, CacheArgs, CacheCommand, CacheError};
+use dee_core::format::render_list;
+use dee_core::http::{base_url, Http, HttpArgs};
+use dee_core::output::mark_stale;
+use dee_core::ratelimit::Limiter;
+use dee_core::schema::{self, Schemas};
+use dee_core::{exit, report_code, style, ConfigError, ErrorCode, Format, OkItem, OkList};
 use reqwest::Client;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 const HN_BASE: &str = "https://hacker-news.firebaseio.com/v0";
 const ALGOLIA_BASE: &str = "https://hn.algolia.com/api/v1";
+/// Env vars that move the two APIs, e.g. to a mock server in tests
+const HN_BASE_ENV: &str = "DEE_HN_BASE_URL";
+const ALGOLIA_BASE_ENV: &str = "DEE_HN_ALGOLIA_BASE_URL";
+/// Front pages move fast; five minutes keeps repeat runs cheap without going stale
+const CACHE_TTL_SECS: u64 = 5 * 60;
 
 #[derive(Parser, Debug)]
 #[command(
     name = "dee-hn",
     version,
     about = "Browse Hacker News stories, items, and comments",
-    after_help = "EXAMPLES:\n  dee-hn top --limit 10\n  dee-hn new --json\n  dee-hn search \"rust async\" --limit 5 --json\n  dee-hn item 8863 --json\n  dee-hn comments 8863 --depth 2 --json\n  dee-hn user pg --json"
+    after_help = "EXAMPLES:\n  dee-hn top --limit 10\n  dee-hn new --json\n  dee-hn search \"rust async\" --limit 5 --json\n  dee-hn item 8863 --json\n  dee-hn comments 8863 --depth 2 --json\n  dee-hn user pg --json\n  dee-hn top --no-cache\n  dee-hn cache clear"
 )]
 struct Cli {
     #[command(subcommand)]
@@ -26,6 +40,20 @@ struct Cli {
 
     #[arg(short, long, global = true, help = "Debug output to stderr")]
     verbose: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        help = "List format: json, jsonl, yaml, csv or table"
+    )]
+    output: Option<Format>,
+
+    #[command(flatten)]
+    http: HttpArgs,
+
+    #[command(flatten)]
+    cache: CacheArgs,
 }
 
 #[derive(Subcommand, Debug)]
@@ -41,6 +69,11 @@ enum Commands {
     Comments(CommentsArgs),
     /// Look up a Hacker News user profile
     User(UserArgs),
+    /// Manage cached API responses
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
 }
 
 #[derive(Args, Debug)]
@@ -108,27 +141,7 @@ struct AlgoliaHit {
     created_at_i: Option<i64>,
 }
 
-#[derive(Debug, Serialize)]
-struct JsonList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct JsonItem<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
-struct JsonError {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct StoryOut {
     id: u64,
     item_type: String,
@@ -141,7 +154,7 @@ struct StoryOut {
     url: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ItemOut {
     id: u64,
     item_type: String,
@@ -156,7 +169,7 @@ struct ItemOut {
     kids_count: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct CommentOut {
     id: u64,
     by: String,
@@ -178,7 +191,7 @@ struct HnUser {
     submitted: Vec<u64>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct UserOut {
     id: String,
     karma: i64,
@@ -189,70 +202,97 @@ struct UserOut {
 
 #[tokio::main]
 async fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
     let result = run(&cli).await;
 
     if let Err(err) = result {
-        if cli.json {
-            let payload = JsonError {
-                ok: false,
-                error: err.to_string(),
-                code: classify_error(&err),
-            };
-            match serde_json::to_string_pretty(&payload) {
-                Ok(s) => println!("{s}"),
-                Err(_) => println!("{{\"ok\":false,\"error\":\"serialization failure\",\"code\":\"INTERNAL_ERROR\"}}"),
-            }
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        let code = classify_error(&err);
+        report_code(
+            &err.to_string(),
+            code,
+            Format::json_errors(cli.output, cli.json),
+        );
+        std::process::exit(exit::for_code(code));
     }
 }
 
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<StoryOut>("top")
+        .list::<StoryOut>("new")
+        .list::<StoryOut>("best")
+        .list::<StoryOut>("ask")
+        .list::<StoryOut>("show")
+        .list::<StoryOut>("jobs")
+        .list::<StoryOut>("search")
+        .item::<ItemOut>("item")
+        .list::<CommentOut>("comments")
+        .item::<UserOut>("user")
+        .cache()
+}
+
+/// The HTTP client, response cache, and request budgets every request goes through.
+struct Api {
+    client: Client,
+    cache: Cache,
+    cache_args: CacheArgs,
+    limiter: Limiter,
+    /// `HN_BASE` unless `DEE_HN_BASE_URL` is set
+    hn_base: String,
+    /// `ALGOLIA_BASE` unless `DEE_HN_ALGOLIA_BASE_URL` is set
+    algolia_base: String,
+}
+
 async fn run(cli: &Cli) -> Result<()> {
-    let client = Client::builder()
-        .user_agent("dee-hn/0.1.0")
-        .build()
+    let client = Http::new("dee-hn", env!("CARGO_PKG_VERSION"))
+        .args(&cli.http)
+        .client()
         .context("failed to initialize HTTP client")?;
+    let api = Api {
+        client,
+        cache: Cache::new("dee-hn").ttl_secs(CACHE_TTL_SECS),
+        cache_args: cli.cache,
+        limiter: Limiter::load()?,
+        hn_base: base_url(std::env::var(HN_BASE_ENV).ok().as_deref(), HN_BASE),
+        algolia_base: base_url(
+            std::env::var(ALGOLIA_BASE_ENV).ok().as_deref(),
+            ALGOLIA_BASE,
+        ),
+    };
 
     match &cli.command {
-        Commands::Top(args) => list_stories(&client, "topstories", args.limit, cli).await,
-        Commands::New(args) => list_stories(&client, "newstories", args.limit, cli).await,
-        Commands::Best(args) => list_stories(&client, "beststories", args.limit, cli).await,
-        Commands::Ask(args) => list_stories(&client, "askstories", args.limit, cli).await,
-        Commands::Show(args) => list_stories(&client, "showstories", args.limit, cli).await,
-        Commands::Jobs(args) => list_stories(&client, "jobstories", args.limit, cli).await,
-        Commands::Search(args) => search_stories(&client, &args.query, args.limit, cli).await,
-        Commands::Item(args) => show_item(&client, args.id, cli).await,
-        Commands::Comments(args) => show_comments(&client, args.id, args.depth, cli).await,
-        Commands::User(args) => show_user(&client, &args.id, cli).await,
+        Commands::Top(args) => list_stories(&api, "topstories", args.limit, cli).await,
+        Commands::New(args) => list_stories(&api, "newstories", args.limit, cli).await,
+        Commands::Best(args) => list_stories(&api, "beststories", args.limit, cli).await,
+        Commands::Ask(args) => list_stories(&api, "askstories", args.limit, cli).await,
+        Commands::Show(args) => list_stories(&api, "showstories", args.limit, cli).await,
+        Commands::Jobs(args) => list_stories(&api, "jobstories", args.limit, cli).await,
+        Commands::Search(args) => search_stories(&api, &args.query, args.limit, cli).await,
+        Commands::Item(args) => show_item(&api, args.id, cli).await,
+        Commands::Comments(args) => show_comments(&api, args.id, args.depth, cli).await,
+        Commands::User(args) => show_user(&api, &args.id, cli).await,
+        Commands::Cache { command } => Ok(dee_core::cache::run(&api.cache, *command, cli.json)?),
     }
 }
 
-async fn list_stories(client: &Client, endpoint: &str, limit: usize, cli: &Cli) -> Result<()> {
-    let ids_url = format!("{HN_BASE}/{endpoint}.json");
-    let ids: Vec<u64> = client
-        .get(&ids_url)
-        .send()
-        .await
-        .with_context(|| format!("failed request to {ids_url}"))?
-        .error_for_status()
-        .with_context(|| format!("request failed for {ids_url}"))?
-        .json()
-        .await
-        .context("failed to decode story id list")?;
+async fn list_stories(api: &Api, endpoint: &str, limit: usize, cli: &Cli) -> Result<()> {
+    let ids_url = format!("{}/{endpoint}.json", api.hn_base);
+    let ids: Vec<u64> = api.get_json(&ids_url, "story id list").await?;
 
     let mut stories = Vec::new();
     for id in ids.into_iter().take(limit) {
-        let item = fetch_item(client, id).await?;
+        let item = fetch_item(api, id).await?;
         if item.item_type.as_deref() == Some("story") || endpoint == "jobstories" {
             stories.push(to_story_out(item));
         }
     }
 
-    if cli.json {
-        print_json(&JsonList {
+    if let Some(format) = cli.output {
+        print!("{}", render_list(&stories, format)?);
+    } else if cli.json {
+        print_json(&OkList {
             ok: true,
             count: stories.len(),
             items: stories,
@@ -268,39 +308,39 @@ async fn list_stories(client: &Client, endpoint: &str, limit: usize, cli: &Cli)
                 format!(" | {}", story.url)
             };
             println!(
-                "{} [{}] by {} | {} pts | {} comments | {}{}",
-                story.id,
-                story.item_type,
-                story.by,
-                story.score,
-                story.comments,
-                story.time,
-                url_part
+                "{}",
+                dim(
+                    cli,
+                    format!(
+                        "{} [{}] by {} | {} pts | {} comments | {}{}",
+                        story.id,
+                        story.item_type,
+                        story.by,
+                        story.score,
+                        story.comments,
+                        story.time,
+                        url_part
+                    )
+                )
             );
-            println!("  {}", story.title);
+            println!("  {}", title(cli, &story.title));
         }
     }
 
     Ok(())
 }
 
-async fn search_stories(client: &Client, query: &str, limit: usize, cli: &Cli) -> Result<()> {
-    let url = format!("{ALGOLIA_BASE}/search");
-    let response: AlgoliaResponse = client
-        .get(url)
-        .query(&[
+async fn search_stories(api: &Api, query: &str, limit: usize, cli: &Cli) -> Result<()> {
+    let url = reqwest::Url::parse_with_params(
+        &format!("{}/search", api.algolia_base),
+        &[
             ("query", query),
             ("tags", "story"),
             ("hitsPerPage", &limit.to_string()),
-        ])
-        .send()
-        .await
-        .context("failed request to Algolia search")?
-        .error_for_status()
-        .context("Algolia search request failed")?
-        .json()
-        .await
-        .context("failed to decode Algolia response")?;
+        ],
+    )
+    .context("failed to build Algolia search request")?;
+    let response: AlgoliaResponse = api.get_json(url.as_str(), "Algolia response").await?;
 
     let items: Vec<StoryOut> = response
         .hits
@@ -320,8 +360,10 @@ async fn search_stories(client: &Client, query: &str, limit: usize, cli: &Cli) -
         })
         .collect();
 
-    if cli.json {
-        print_json(&JsonList {
+    if let Some(format) = cli.output {
+        print!("{}", render_list(&items, format)?);
+    } else if cli.json {
+        print_json(&OkList {
             ok: true,
             count: items.len(),
             items,
@@ -332,12 +374,18 @@ async fn search_stories(client: &Client, query: &str, limit: usize, cli: &Cli) -
         }
         for story in items {
             println!(
-                "{} by {} | {} pts | {} comments | {}",
-                story.id, story.by, story.score, story.comments, story.time
+                "{}",
+                dim(
+                    cli,
+                    format!(
+                        "{} by {} | {} pts | {} comments | {}",
+                        story.id, story.by, story.score, story.comments, story.time
+                    )
+                )
             );
-            println!("  {}", story.title);
+            println!("  {}", title(cli, &story.title));
             if !story.url.is_empty() {
-                println!("  {}", story.url);
+                println!("  {}", dim(cli, &story.url));
             }
         }
     }
@@ -345,12 +393,12 @@ async fn search_stories(client: &Client, query: &str, limit: usize, cli: &Cli) -
     Ok(())
 }
 
-async fn show_item(client: &Client, id: u64, cli: &Cli) -> Result<()> {
-    let item = fetch_item(client, id).await?;
+async fn show_item(api: &Api, id: u64, cli: &Cli) -> Result<()> {
+    let item = fetch_item(api, id).await?;
     let out = to_item_out(item);
 
     if cli.json {
-        print_json(&JsonItem {
+        print_json(&OkItem {
             ok: true,
             item: out,
         })?;
@@ -375,15 +423,15 @@ async fn show_item(client: &Client, id: u64, cli: &Cli) -> Result<()> {
     Ok(())
 }
 
-async fn show_comments(client: &Client, id: u64, max_depth: usize, cli: &Cli) -> Result<()> {
-    let root = fetch_item(client, id).await?;
+async fn show_comments(api: &Api, id: u64, max_depth: usize, cli: &Cli) -> Result<()> {
+    let root = fetch_item(api, id).await?;
     let kids = root.kids.unwrap_or_default();
 
     let mut comments = Vec::new();
     let mut stack: Vec<(u64, usize)> = kids.into_iter().rev().map(|kid| (kid, 1usize)).collect();
 
     while let Some((comment_id, depth)) = stack.pop() {
-        let item = fetch_item(client, comment_id).await?;
+        let item = fetch_item(api, comment_id).await?;
         if item.item_type.as_deref() == Some("comment")
             && item.deleted != Some(true)
             && item.dead != Some(true)
@@ -406,8 +454,10 @@ async fn show_comments(client: &Client, id: u64, max_depth: usize, cli: &Cli) ->
         }
     }
 
-    if cli.json {
-        print_json(&JsonList {
+    if let Some(format) = cli.output {
+        print!("{}", render_list(&comments, format)?);
+    } else if cli.json {
+        print_json(&OkList {
             ok: true,
             count: comments.len(),
             items: comments,
@@ -418,7 +468,11 @@ async fn show_comments(client: &Client, id: u64, max_depth: usize, cli: &Cli) ->
         }
         for c in comments {
             let indent = "  ".repeat(c.depth.saturating_sub(1));
-            println!("{}#{} by {} at {}", indent, c.id, c.by, c.time);
+            println!(
+                "{}{}",
+                indent,
+                dim(cli, format!("#{} by {} at {}", c.id, c.by, c.time))
+            );
             println!("{}{}", indent, c.text.replace('\n', " "));
         }
     }
@@ -426,18 +480,9 @@ async fn show_comments(client: &Client, id: u64, max_depth: usize, cli: &Cli) ->
     Ok(())
 }
 
-async fn show_user(client: &Client, id: &str, cli: &Cli) -> Result<()> {
-    let url = format!("{HN_BASE}/user/{id}.json");
-    let maybe_user: Option<HnUser> = client
-        .get(&url)
-        .send()
-        .await
-        .with_context(|| format!("failed request to {url}"))?
-        .error_for_status()
-        .with_context(|| format!("request failed for {url}"))?
-        .json()
-        .await
-        .with_context(|| format!("failed to decode user {id}"))?;
+async fn show_user(api: &Api, id: &str, cli: &Cli) -> Result<()> {
+    let url = format!("{}/user/{id}.json", api.hn_base);
+    let maybe_user: Option<HnUser> = api.get_json(&url, &format!("user {id}")).await?;
 
     let user = maybe_user.ok_or_else(|| anyhow!("user {id} not found"))?;
     let out = UserOut {
@@ -449,7 +494,7 @@ async fn show_user(client: &Client, id: &str, cli: &Cli) -> Result<()> {
     };
 
     if cli.json {
-        print_json(&JsonItem {
+        print_json(&OkItem {
             ok: true,
             item: out,
         })?;
@@ -466,22 +511,52 @@ async fn show_user(client: &Client, id: &str, cli: &Cli) -> Result<()> {
     Ok(())
 }
 
-async fn fetch_item(client: &Client, id: u64) -> Result<HnItem> {
-    let url = format!("{HN_BASE}/item/{id}.json");
-    let maybe_item: Option<HnItem> = client
-        .get(&url)
-        .send()
-        .await
-        .with_context(|| format!("failed request to {url}"))?
-        .error_for_status()
-        .with_context(|| format!("request failed for {url}"))?
-        .json()
-        .await
-        .with_context(|| format!("failed to decode item {id}"))?;
+async fn fetch_item(api: &Api, id: u64) -> Result<HnItem> {
+    let url = format!("{}/item/{id}.json", api.hn_base);
+    let maybe_item: Option<HnItem> = api.get_json(&url, &format!("item {id}")).await?;
 
     maybe_item.ok_or_else(|| anyhow!("item {id} not found"))
 }
 
+impl Api {
+    /// `url` decoded as `T`, from the cache while it is fresh unless `--no-cache`,
+    /// and only from the cache with `--offline`. `what` names the response in
+    /// decode errors.
+    async fn get_json<T: DeserializeOwned>(&self, url: &str, what: &str) -> Result<T> {
+        let body = match self
+            .cache
+            .lookup::<serde_json::Value>(url, &self.cache_args)?
+        {
+            Some(hit) => {
+                tracing::debug!("cached {url} ({}s old)", hit.age_secs);
+                hit.value
+            }
+            None => {
+                // Algolia has a budget; the Firebase API has none
+                let wait = self.limiter.reserve(url);
+                if !wait.is_zero() {
+                    tracing::debug!("waiting {:.1}s before {url}", wait.as_secs_f64());
+                    tokio::time::sleep(wait).await;
+                }
+                let body: serde_json::Value = self
+                    .client
+                    .get(url)
+                    .send()
+                    .await
+                    .with_context(|| format!("failed request to {url}"))?
+                    .error_for_status()
+                    .with_context(|| format!("request failed for {url}"))?
+                    .json()
+                    .await
+                    .with_context(|| format!("failed to decode {what}"))?;
+                self.cache.put(url, &body);
+                body
+            }
+        };
+        serde_json::from_value(body).with_context(|| format!("failed to decode {what}"))
+    }
+}
+
 fn to_story_out(item: HnItem) -> StoryOut {
     StoryOut {
         id: item.id,
@@ -518,53 +593,47 @@ fn iso_time(ts: i64) -> String {
         .unwrap_or_else(|| "1970-01-01T00:00:00+00:00".to_owned())
 }
 
+/// Story titles stand out on a terminal; `--quiet` stays plain.
+fn title(cli: &Cli, text: &str) -> String {
+    if cli.quiet {
+        text.to_string()
+    } else {
+        style::header(text)
+    }
+}
+
+/// Ids, authors, scores and links recede on a terminal; `--quiet` stays plain.
+fn dim(cli: &Cli, text: impl std::fmt::Display) -> String {
+    if cli.quiet {
+        text.to_string()
+    } else {
+        style::dim(text)
+    }
+}
+
 fn print_json<T: Serialize>(value: &T) -> Result<()> {
-    let rendered = serde_json::to_string_pretty(value).context("failed to serialize JSON")?;
+    let mut value = serde_json::to_value(value).context("failed to serialize JSON")?;
+    mark_stale(&mut value);
+    let rendered = serde_json::to_string_pretty(&value).context("failed to serialize JSON")?;
     println!("{rendered}");
     Ok(())
 }
 
-fn classify_error(err: &anyhow::Error) -> String {
+fn classify_error(err: &anyhow::Error) -> &'static str {
+    if let Some(err) = err.downcast_ref::<CacheError>() {
+        return err.code();
+    }
+    if let Some(err) = err.downcast_ref::<ConfigError>() {
+        return err.code();
+    }
     let lower = err.to_string().to_lowercase();
     if lower.contains("not found") {
-        "NOT_FOUND".to_owned()
+        "NOT_FOUND"
     } else if lower.contains("request") || lower.contains("network") || lower.contains("timeout") {
-        "NETWORK_ERROR".to_owned()
+        "NETWORK_ERROR"
     } else if lower.contains("decode") || lower.contains("serialize") || lower.contains("json") {
-        "PARSE_ERROR".to_owned()
+        "PARSE_ERROR"
     } else {
-        "INTERNAL_ERROR".to_owned()
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
+        "INTERNAL_ERROR"
     }
 }
@@ -0,0 +1,69 @@
+use assert_cmd::Command;
+use dee_test_support::{fixture, json, MockApi, Sandbox};
+
+fn bin(sandbox: &Sandbox, api: &MockApi) -> Command {
+    let mut cmd = sandbox.command(assert_cmd::cargo::cargo_bin!("dee-hn"));
+    cmd.env("DEE_HN_BASE_URL", api.url("/v0"))
+        .env("DEE_HN_ALGOLIA_BASE_URL", api.url("/api/v1"));
+    cmd
+}
+
+fn hn_api() -> MockApi {
+    let api = MockApi::start();
+    api.get_json("/v0/topstories.json", &fixture("hn/topstories.json"));
+    for id in [8863, 8864, 8865] {
+        api.get_json(
+            &format!("/v0/item/{id}.json"),
+            &fixture(&format!("hn/item_{id}.json")),
+        );
+    }
+    api.get_json("/v0/user/pg.json", &fixture("hn/user_pg.json"))
+        .get_json("/api/v1/search", &fixture("hn/search.json"));
+    api
+}
+
+#[test]
+fn top_lists_only_stories() {
+    let api = hn_api();
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["top", "--limit", "3", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][0]["id"], 8863);
+    assert_eq!(parsed["items"][0]["by"], "dhouston");
+    assert_eq!(parsed["items"][0]["comments"], 71);
+    assert!(parsed["items"][1].get("url").is_none());
+}
+
+#[test]
+fn search_item_and_user() {
+    let api = hn_api();
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["search", "async rust", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 1);
+    assert_eq!(parsed["items"][0]["score"], 320);
+    let search = api
+        .requests()
+        .into_iter()
+        .find(|request| request.url.path() == "/api/v1/search")
+        .unwrap();
+    assert_eq!(
+        search.url.query(),
+        Some("query=async+rust&tags=story&hitsPerPage=20")
+    );
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["item", "8863", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(
+        parsed["item"]["title"],
+        "My YC app: Dropbox - Throw away your USB drive"
+    );
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["user", "pg", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["item"]["karma"], 155111);
+    assert_eq!(parsed["item"]["submissions"], 2);
+}
@@ -36,3 +36,16 @@ fn item_json_error_shape_on_stdout() {
 fn invalid_subcommand_exits_nonzero() {
     bin().arg("__not_a_valid_subcommand__").assert().failure();
 }
+
+#[test]
+fn offline_with_nothing_cached_is_offline_miss() {
+    let cache = tempfile::tempdir().unwrap();
+    let out = bin()
+        .env("XDG_CACHE_HOME", cache.path())
+        .args(["item", "8863", "--offline", "--json"])
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(3));
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], "OFFLINE_MISS");
+}
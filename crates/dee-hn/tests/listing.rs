@@ -21,3 +21,24 @@ fn help_includes_examples_with_dee_hn() {
 fn version_flag_succeeds() {
     bin().arg("--version").assert().success();
 }
+
+#[test]
+fn schema_describes_each_command() {
+    let out = bin()
+        .args(["top", "--limit", "5", "--schema"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let item = &parsed["item"];
+    assert_eq!(item["command"], "top");
+    assert!(item["success"]["properties"]["items"].is_object());
+    assert!(item["success"].to_string().contains("\"comments\""));
+    assert_eq!(item["error"]["properties"]["code"]["type"], "string");
+
+    let all: serde_json::Value =
+        serde_json::from_slice(&bin().arg("--schema").output().unwrap().stdout).unwrap();
+    assert!(all["count"].as_u64().unwrap() >= 10);
+
+    bin().args(["nope", "--schema"]).assert().code(3);
+}
@@ -2,7 +2,10 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::schema::{self, Schemas};
+use dee_core::{print_json, report, ErrorCode, OkItem};
 use printpdf::{BuiltinFont, Mm, Op, PdfDocument, PdfPage, PdfSaveOptions, Point, Pt, TextItem};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Parser)]
@@ -84,7 +87,7 @@ struct TemplateArgs {
     format: TemplateFormat,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct Party {
     name: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -95,14 +98,14 @@ struct Party {
     address: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct LineItem {
     description: String,
     quantity: f64,
     unit_price: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct InvoiceInput {
     invoice_number: String,
     issue_date: String,
@@ -117,7 +120,7 @@ struct InvoiceInput {
     tax_rate: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 struct ComputedLineItem {
     description: String,
     quantity: f64,
@@ -125,7 +128,7 @@ struct ComputedLineItem {
     line_total: f64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 struct InvoiceComputed {
     invoice_number: String,
     issue_date: String,
@@ -142,13 +145,7 @@ struct InvoiceComputed {
     notes: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct ItemResponse<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ActionResponse {
     ok: bool,
     message: String,
@@ -156,13 +153,6 @@ struct ActionResponse {
     path: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
 #[derive(Debug, thiserror::Error)]
 enum AppError {
     #[error("Invalid argument: {0}")]
@@ -175,7 +165,7 @@ enum AppError {
     Pdf,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
             Self::InvalidArgument(_) => "INVALID_ARGUMENT",
@@ -186,20 +176,22 @@ impl AppError {
     }
 }
 
+/// What each command prints under `--json`, for `--schema`. `generate --format
+/// json` without `--output` prints `{ok,item}` with the computed invoice.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .item::<InvoiceInput>("template")
+        .item::<InvoiceComputed>("calc")
+        .custom::<ActionResponse>("generate")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = run(&cli) {
-        if cli.global.json {
-            print_json(&ErrorResponse {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(&err, cli.global.json);
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -210,7 +202,7 @@ fn run(cli: &Cli) -> Result<(), AppError> {
             let input = load_input(&args.input)?;
             let computed = compute_invoice(input)?;
             if cli.global.json {
-                print_json(&ItemResponse {
+                print_json(&OkItem {
                     ok: true,
                     item: computed,
                 });
@@ -240,7 +232,7 @@ fn run(cli: &Cli) -> Result<(), AppError> {
                             &cli.global,
                         )
                     } else if cli.global.json {
-                        print_json(&ItemResponse {
+                        print_json(&OkItem {
                             ok: true,
                             item: computed,
                         });
@@ -271,7 +263,7 @@ fn run(cli: &Cli) -> Result<(), AppError> {
 fn cmd_template(args: &TemplateArgs, global: &GlobalFlags) -> Result<(), AppError> {
     let sample = sample_invoice();
     if global.json {
-        print_json(&ItemResponse {
+        print_json(&OkItem {
             ok: true,
             item: sample,
         });
@@ -521,47 +513,3 @@ fn print_action(message: &str, path: Option<String>, global: &GlobalFlags) -> Re
     }
     Ok(())
 }
-
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(text) => println!("{text}"),
-        Err(_) => {
-            println!(
-                "{{\"ok\":false,\"error\":\"JSON serialization failed\",\"code\":\"SERIALIZE\"}}"
-            );
-            std::process::exit(1);
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -0,0 +1,157 @@
+//! CIDR arithmetic for `contains` and `split`. Both families are handled as
+//! `u128`, with IPv4 in the low 32 bits.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// More subnets than this from one `split` is almost certainly a typo
+pub const MAX_SUBNETS: u32 = 65_536;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: u128,
+    prefix: u8,
+    v4: bool,
+}
+
+impl Cidr {
+    /// `10.0.0.0/8`, `2001:db8::/32`, or a bare address (a /32 or /128).
+    /// Host bits are cleared: `10.1.2.3/8` is `10.0.0.0/8`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (addr, prefix) = match text.trim().split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (text.trim(), None),
+        };
+        let addr: IpAddr = addr
+            .parse()
+            .map_err(|_| format!("not an IP address: {addr}"))?;
+        let v4 = addr.is_ipv4();
+        let bits = bits(v4);
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix| *prefix <= bits)
+                .ok_or_else(|| format!("prefix must be 0-{bits}: {text}"))?,
+            None => bits,
+        };
+        Ok(Self {
+            network: to_u128(addr) & mask(prefix, v4),
+            prefix,
+            v4,
+        })
+    }
+
+    pub fn bits(&self) -> u8 {
+        bits(self.v4)
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        ip.is_ipv4() == self.v4 && to_u128(ip) & mask(self.prefix, self.v4) == self.network
+    }
+
+    pub fn first(&self) -> IpAddr {
+        from_u128(self.network, self.v4)
+    }
+
+    pub fn last(&self) -> IpAddr {
+        from_u128(self.network | host_mask(self.prefix, self.v4), self.v4)
+    }
+
+    /// How many addresses the block holds, as decimal text: `::/0` holds 2^128.
+    pub fn addresses(&self) -> String {
+        match self.bits() - self.prefix {
+            128 => "340282366920938463463374607431768211456".to_string(),
+            host_bits => (1u128 << host_bits).to_string(),
+        }
+    }
+
+    /// The block cut into subnets of `prefix`, in order.
+    pub fn split(&self, prefix: u8) -> Result<Vec<Cidr>, String> {
+        if prefix < self.prefix || prefix > self.bits() {
+            return Err(format!(
+                "prefix must be {}-{} to split {self}",
+                self.prefix,
+                self.bits()
+            ));
+        }
+        let extra = prefix - self.prefix;
+        if extra == 0 {
+            return Ok(vec![*self]);
+        }
+        if u32::from(extra) > MAX_SUBNETS.ilog2() {
+            return Err(format!(
+                "{self} into /{prefix} is more than {MAX_SUBNETS} subnets"
+            ));
+        }
+        let step = 1u128 << (self.bits() - prefix);
+        Ok((0..1u128 << extra)
+            .map(|index| Self {
+                network: self.network + index * step,
+                prefix,
+                v4: self.v4,
+            })
+            .collect())
+    }
+
+    /// The prefix that cuts the block into at least `count` equal subnets.
+    pub fn prefix_for(&self, count: u32) -> Result<u8, String> {
+        if count == 0 || count > MAX_SUBNETS {
+            return Err(format!("--count must be 1-{MAX_SUBNETS}"));
+        }
+        let extra = count.next_power_of_two().ilog2() as u8;
+        let prefix = self.prefix + extra;
+        if prefix > self.bits() {
+            return Err(format!("{self} has too few addresses for {count} subnets"));
+        }
+        Ok(prefix)
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.first(), self.prefix)
+    }
+}
+
+fn bits(v4: bool) -> u8 {
+    if v4 {
+        32
+    } else {
+        128
+    }
+}
+
+fn full(v4: bool) -> u128 {
+    if v4 {
+        u128::from(u32::MAX)
+    } else {
+        u128::MAX
+    }
+}
+
+fn mask(prefix: u8, v4: bool) -> u128 {
+    if prefix == 0 {
+        return 0;
+    }
+    (u128::MAX << (bits(v4) - prefix)) & full(v4)
+}
+
+fn host_mask(prefix: u8, v4: bool) -> u128 {
+    full(v4) & !mask(prefix, v4)
+}
+
+fn to_u128(ip: IpAddr) -> u128 {
+    match ip {
+        IpAddr::V4(ip) => u128::from(u32::from(ip)),
+        IpAddr::V6(ip) => u128::from(ip),
+    }
+}
+
+fn from_u128(value: u128, v4: bool) -> IpAddr {
+    if v4 {
+        IpAddr::V4(Ipv4Addr::from(value as u32))
+    } else {
+        IpAddr::V6(Ipv6Addr::from(value))
+    }
+}
@@ -0,0 +1,488 @@
+mod cidr;
+mod providers;
+
+use std::net::IpAddr;
+
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use dee_core::cache::{Cache, CacheArgs, CacheCommand, CacheError};
+use dee_core::http::HttpArgs;
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, report, Config, ConfigError, ErrorCode, Format, OkItem, OkMessage,
+    SecretsBackend,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cidr::Cidr;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "dee-ip",
+    version,
+    about = "IP geolocation, ASN lookup and CIDR math",
+    after_help = "EXAMPLES:\n  dee-ip 8.8.8.8 --json\n  dee-ip 2606:4700:4700::1111 --provider ipinfo --json\n  dee-ip me --json\n  dee-ip me -q\n  dee-ip contains 10.0.0.0/8 10.42.0.7 --json\n  dee-ip split 192.168.0.0/24 --prefix 26 --json\n  dee-ip split 10.0.0.0/16 --count 6 --output table\n  dee-ip config set provider ipinfo\n  dee-ip config set ipinfo.token <TOKEN>",
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(flatten)]
+    global: GlobalArgs,
+    /// IP address to look up
+    ip: Option<String>,
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub(crate) struct GlobalArgs {
+    #[arg(short = 'j', long, global = true)]
+    json: bool,
+    #[arg(short = 'q', long, global = true)]
+    quiet: bool,
+    #[arg(short = 'v', long, global = true)]
+    pub(crate) verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    /// Geolocation provider (defaults to config `provider`, then ip-api)
+    #[arg(long, global = true, value_enum)]
+    provider: Option<Provider>,
+    #[command(flatten)]
+    pub(crate) http: HttpArgs,
+    #[command(flatten)]
+    pub(crate) cache: CacheArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// Look up your own public IP
+    Me,
+    /// Whether a CIDR block holds an address
+    Contains(ContainsArgs),
+    /// Cut a CIDR block into equal subnets
+    Split(SplitArgs),
+    Config(ConfigArgs),
+    /// Manage cached lookups
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Provider {
+    /// ip-api.com: free without a key (45 lookups a minute), pro with one
+    #[default]
+    IpApi,
+    /// ipinfo.io: works without a token, within a smaller quota
+    Ipinfo,
+}
+
+impl Provider {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::IpApi => "ip-api",
+            Self::Ipinfo => "ipinfo",
+        }
+    }
+
+    fn credential_key(self) -> &'static str {
+        match self {
+            Self::IpApi => "ip-api.key",
+            Self::Ipinfo => "ipinfo.token",
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct ContainsArgs {
+    /// Block such as 10.0.0.0/8 or 2001:db8::/32
+    cidr: String,
+    ip: String,
+}
+
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("size").required(true).args(["prefix", "count"])))]
+struct SplitArgs {
+    /// Block such as 10.0.0.0/16
+    cidr: String,
+    /// Prefix length of each subnet
+    #[arg(long)]
+    prefix: Option<u8>,
+    /// At least this many subnets, rounded up to a power of two
+    #[arg(long)]
+    count: Option<u32>,
+}
+
+#[derive(Debug, Args)]
+struct ConfigArgs {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    Set(ConfigSetArgs),
+    Show(ShowFlags),
+    Path,
+}
+
+#[derive(Debug, Args)]
+struct ConfigSetArgs {
+    key: String,
+    value: String,
+    #[command(flatten)]
+    output: ShowFlags,
+}
+
+#[derive(Debug, Args)]
+struct ShowFlags {
+    #[arg(short = 'j', long)]
+    json: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
+pub(crate) struct AppConfig {
+    #[serde(default)]
+    provider: Option<Provider>,
+    /// ip-api pro key; without one the free endpoint is used
+    #[serde(default)]
+    pub(crate) ip_api_key: Option<String>,
+    #[serde(default)]
+    pub(crate) ipinfo_token: Option<String>,
+    /// `plaintext` (default) or `keyring`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secrets_backend: Option<SecretsBackend>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct IpInfo {
+    pub(crate) ip: String,
+    /// `AS15169`
+    pub(crate) asn: Option<String>,
+    /// Holder of the ASN
+    pub(crate) as_name: Option<String>,
+    pub(crate) org: Option<String>,
+    /// ip-api only
+    pub(crate) isp: Option<String>,
+    /// ip-api only; ipinfo gives the code alone
+    pub(crate) country: Option<String>,
+    pub(crate) country_code: Option<String>,
+    pub(crate) region: Option<String>,
+    pub(crate) city: Option<String>,
+    pub(crate) latitude: Option<f64>,
+    pub(crate) longitude: Option<f64>,
+    pub(crate) timezone: Option<String>,
+    pub(crate) provider: Provider,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct Containment {
+    /// The block with host bits cleared
+    cidr: String,
+    ip: String,
+    contains: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct Subnet {
+    cidr: String,
+    first: String,
+    last: String,
+    /// Decimal text, since IPv6 counts pass 2^64
+    addresses: String,
+}
+
+/// `DEE_IP_<KEY>` overrides the file (`DEE_IP_PROVIDER`, `DEE_IP_IP_API_KEY`,
+/// `DEE_IP_IPINFO_TOKEN`). With `secrets_backend = "keyring"` the two
+/// credentials live in the OS keyring instead of the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-ip")
+        .env_overrides(&["provider", "ip_api_key", "ipinfo_token"])
+        .secret("ip_api_key")
+        .secret("ipinfo_token")
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum AppError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+    #[error("Unknown config key: {0}")]
+    InvalidConfigKey(String),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("{} rejected the credentials. Check {} via config set", .0.as_str(), .0.credential_key())]
+    AuthInvalid(Provider),
+    #[error("{} rate limit reached; try again shortly", .0.as_str())]
+    RateLimited(Provider),
+    #[error("HTTP request failed")]
+    RequestFailed,
+    #[error("Geolocation API returned an error: {0}")]
+    ApiError(String),
+    #[error("Response parse failed")]
+    ParseFailed,
+}
+
+impl ErrorCode for AppError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Config(err) => err.code(),
+            Self::Cache(err) => err.code(),
+            Self::InvalidConfigKey(_) | Self::InvalidArgument(_) => "INVALID_ARGUMENT",
+            Self::AuthInvalid(_) => "AUTH_INVALID",
+            Self::RateLimited(_) => "RATE_LIMITED",
+            Self::RequestFailed => "REQUEST_FAILED",
+            Self::ApiError(_) => "API_ERROR",
+            Self::ParseFailed => "PARSE_FAILED",
+        }
+    }
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .item::<IpInfo>("")
+        .item::<IpInfo>("me")
+        .item::<Containment>("contains")
+        .list::<Subnet>("split")
+        .message("config set")
+        .item::<AppConfig>("config show")
+        .cache()
+}
+
+fn main() {
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
+
+    let result = dispatch(&cli);
+    if let Err(err) = result {
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
+    }
+}
+
+fn dispatch(cli: &Cli) -> Result<(), AppError> {
+    match &cli.command {
+        Some(Commands::Me) => cmd_lookup(None, &cli.global),
+        Some(Commands::Contains(args)) => cmd_contains(args, &cli.global),
+        Some(Commands::Split(args)) => cmd_split(args, &cli.global),
+        Some(Commands::Config(args)) => cmd_config(args),
+        Some(Commands::Cache { command }) => Ok(dee_core::cache::run(
+            &Cache::new("dee-ip"),
+            *command,
+            cli.global.json,
+        )?),
+        None => {
+            let ip = cli.ip.as_deref().ok_or_else(|| {
+                AppError::InvalidArgument(
+                    "give an IP address, or run `dee-ip me` for your own".to_string(),
+                )
+            })?;
+            let ip = parse_ip(ip)?;
+            if !is_public(ip) {
+                return Err(AppError::InvalidArgument(format!(
+                    "{ip} is a private or reserved address with no public location"
+                )));
+            }
+            cmd_lookup(Some(ip), &cli.global)
+        }
+    }
+}
+
+fn cmd_lookup(ip: Option<IpAddr>, out: &GlobalArgs) -> Result<(), AppError> {
+    let config = config_file().load()?;
+    let provider = out.provider.or(config.provider).unwrap_or_default();
+    tracing::debug!("provider {}", provider.as_str());
+    let item = providers::lookup(provider, &config, ip, out)?;
+
+    if out.json {
+        print_json(&OkItem { ok: true, item });
+    } else if out.quiet {
+        println!("{}", item.ip);
+    } else {
+        let network = [item.asn.as_deref(), item.as_name.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!("{}  {network}", item.ip);
+        let place = [
+            item.city.as_deref(),
+            item.region.as_deref(),
+            item.country.as_deref().or(item.country_code.as_deref()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(", ");
+        if let (Some(lat), Some(lon)) = (item.latitude, item.longitude) {
+            println!("{place} ({lat:.4}, {lon:.4})");
+        } else if !place.is_empty() {
+            println!("{place}");
+        }
+        if let Some(timezone) = &item.timezone {
+            println!("timezone {timezone}");
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_contains(args: &ContainsArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    let cidr = Cidr::parse(&args.cidr).map_err(AppError::InvalidArgument)?;
+    let ip = parse_ip(&args.ip)?;
+    let item = Containment {
+        cidr: cidr.to_string(),
+        ip: ip.to_string(),
+        contains: cidr.contains(ip),
+    };
+
+    if out.json {
+        print_json(&OkItem { ok: true, item });
+    } else if out.quiet {
+        println!("{}", item.contains);
+    } else {
+        let verb = if item.contains {
+            "contains"
+        } else {
+            "does not contain"
+        };
+        println!("{} {verb} {}", item.cidr, item.ip);
+    }
+
+    Ok(())
+}
+
+fn cmd_split(args: &SplitArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    let cidr = Cidr::parse(&args.cidr).map_err(AppError::InvalidArgument)?;
+    let prefix = match (args.prefix, args.count) {
+        (Some(prefix), _) => prefix,
+        (None, Some(count)) => cidr.prefix_for(count).map_err(AppError::InvalidArgument)?,
+        (None, None) => unreachable!("clap requires --prefix or --count"),
+    };
+    let items: Vec<Subnet> = cidr
+        .split(prefix)
+        .map_err(AppError::InvalidArgument)?
+        .into_iter()
+        .map(|subnet| Subnet {
+            cidr: subnet.to_string(),
+            first: subnet.first().to_string(),
+            last: subnet.last().to_string(),
+            addresses: subnet.addresses(),
+        })
+        .collect();
+
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
+    } else if out.quiet {
+        println!("{}", items.len());
+    } else {
+        for item in items {
+            println!(
+                "{}  {} - {}  ({})",
+                item.cidr, item.first, item.last, item.addresses
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_ip(text: &str) -> Result<IpAddr, AppError> {
+    text.trim()
+        .parse()
+        .map_err(|_| AppError::InvalidArgument(format!("not an IP address: {text}")))
+}
+
+/// Whether a geolocation provider could know where `ip` is.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                // 100.64.0.0/10, carrier-grade NAT
+                || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public(IpAddr::V4(v4)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
+    match &args.command {
+        ConfigCommand::Set(input) => {
+            let mut cfg = config_file().load_file()?;
+            match input.key.as_str() {
+                "provider" => {
+                    cfg.provider = Some(
+                        Provider::from_str(&input.value, true)
+                            .map_err(AppError::InvalidArgument)?,
+                    )
+                }
+                "ip-api.key" | "ip_api_key" => cfg.ip_api_key = Some(input.value.clone()),
+                "ipinfo.token" | "ipinfo_token" => cfg.ipinfo_token = Some(input.value.clone()),
+                "secrets_backend" => {
+                    cfg.secrets_backend =
+                        Some(input.value.parse().map_err(AppError::InvalidArgument)?)
+                }
+                other => return Err(AppError::InvalidConfigKey(other.to_string())),
+            }
+            config_file().save(&cfg)?;
+
+            if input.output.json {
+                print_json(&OkMessage {
+                    ok: true,
+                    message: "Config updated".to_string(),
+                });
+            } else {
+                println!("Config updated");
+            }
+            Ok(())
+        }
+        ConfigCommand::Show(flags) => {
+            let cfg = config_file().load()?;
+            if flags.json {
+                print_json(&OkItem {
+                    ok: true,
+                    item: cfg,
+                });
+            } else {
+                let state =
+                    |value: &Option<String>| value.as_deref().map(|_| "set").unwrap_or("missing");
+                println!("provider: {}", cfg.provider.unwrap_or_default().as_str());
+                println!("ip_api_key: {}", state(&cfg.ip_api_key));
+                println!("ipinfo_token: {}", state(&cfg.ipinfo_token));
+                println!(
+                    "secrets_backend: {}",
+                    cfg.secrets_backend.unwrap_or_default().as_str()
+                );
+            }
+            Ok(())
+        }
+        ConfigCommand::Path => {
+            println!("{}", config_file().path().display());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use std::net::IpAddr;
+
+use dee_core::cache::{Cache, CacheError};
+use dee_core::http::Http;
+use dee_core::ratelimit;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use crate::{AppConfig, AppError, GlobalArgs, IpInfo, Provider};
+
+/// The free tier is plain HTTP only; keys go to the HTTPS pro endpoint
+const IP_API_BASE: &str = "http://ip-api.com/json";
+const IP_API_PRO_BASE: &str = "https://pro.ip-api.com/json";
+const IP_API_FIELDS: &str =
+    "status,message,query,country,countryCode,regionName,city,lat,lon,timezone,isp,org,as";
+const IPINFO_BASE: &str = "https://ipinfo.io";
+/// Where an address is registered rarely changes
+const CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IpApiRoot {
+    status: String,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    query: Option<String>,
+    #[serde(default)]
+    country: Option<String>,
+    #[serde(default)]
+    country_code: Option<String>,
+    #[serde(default)]
+    region_name: Option<String>,
+    #[serde(default)]
+    city: Option<String>,
+    #[serde(default)]
+    lat: Option<f64>,
+    #[serde(default)]
+    lon: Option<f64>,
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    isp: Option<String>,
+    #[serde(default)]
+    org: Option<String>,
+    /// `AS15169 Google LLC`
+    #[serde(default, rename = "as")]
+    autonomous_system: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IpinfoRoot {
+    ip: String,
+    #[serde(default)]
+    city: Option<String>,
+    #[serde(default)]
+    region: Option<String>,
+    /// Two-letter code
+    #[serde(default)]
+    country: Option<String>,
+    /// `37.4056,-122.0775`
+    #[serde(default)]
+    loc: Option<String>,
+    /// `AS15169 Google LLC`
+    #[serde(default)]
+    org: Option<String>,
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    bogon: bool,
+}
+
+/// What `provider` knows about `ip`, or about the caller when `ip` is `None`.
+pub fn lookup(
+    provider: Provider,
+    config: &AppConfig,
+    ip: Option<IpAddr>,
+    out: &GlobalArgs,
+) -> Result<IpInfo, AppError> {
+    match provider {
+        Provider::IpApi => ip_api_lookup(config, ip, out),
+        Provider::Ipinfo => ipinfo_lookup(config, ip, out),
+    }
+}
+
+fn ip_api_lookup(
+    config: &AppConfig,
+    ip: Option<IpAddr>,
+    out: &GlobalArgs,
+) -> Result<IpInfo, AppError> {
+    let path = ip.map(|ip| format!("/{ip}")).unwrap_or_default();
+    let api_key = config
+        .ip_api_key
+        .as_deref()
+        .filter(|x| !x.trim().is_empty());
+    let base = if api_key.is_some() {
+        IP_API_PRO_BASE
+    } else {
+        IP_API_BASE
+    };
+    // The cache key leaves the API key out
+    let cache_key = format!("{base}{path}?fields={IP_API_FIELDS}");
+    let url = match api_key {
+        Some(api_key) => format!("{cache_key}&key={api_key}"),
+        None => cache_key.clone(),
+    };
+
+    let raw = fetch(Provider::IpApi, &url, &cache_key, ip.is_some(), None, out)?;
+    let body: IpApiRoot = serde_json::from_value(raw).map_err(|_| AppError::ParseFailed)?;
+    if body.status != "success" {
+        let message = body.message.unwrap_or_else(|| "lookup failed".to_string());
+        return Err(match message.as_str() {
+            "private range" | "reserved range" | "invalid query" => {
+                AppError::InvalidArgument(message)
+            }
+            _ => AppError::ApiError(message),
+        });
+    }
+
+    let (asn, as_name) = split_as(body.autonomous_system.as_deref());
+    Ok(IpInfo {
+        ip: body
+            .query
+            .or_else(|| ip.map(|ip| ip.to_string()))
+            .ok_or(AppError::ParseFailed)?,
+        asn,
+        as_name,
+        org: body.org.filter(|x| !x.is_empty()),
+        isp: body.isp.filter(|x| !x.is_empty()),
+        country: body.country,
+        country_code: body.country_code,
+        region: body.region_name.filter(|x| !x.is_empty()),
+        city: body.city.filter(|x| !x.is_empty()),
+        latitude: body.lat,
+        longitude: body.lon,
+        timezone: body.timezone,
+        provider: Provider::IpApi,
+    })
+}
+
+fn ipinfo_lookup(
+    config: &AppConfig,
+    ip: Option<IpAddr>,
+    out: &GlobalArgs,
+) -> Result<IpInfo, AppError> {
+    let url = match ip {
+        Some(ip) => format!("{IPINFO_BASE}/{ip}/json"),
+        None => format!("{IPINFO_BASE}/json"),
+    };
+    // Optional: without a token ipinfo still answers, within a smaller quota
+    let token = config
+        .ipinfo_token
+        .as_deref()
+        .filter(|x| !x.trim().is_empty());
+
+    let raw = fetch(Provider::Ipinfo, &url, &url, ip.is_some(), token, out)?;
+    let body: IpinfoRoot = serde_json::from_value(raw).map_err(|_| AppError::ParseFailed)?;
+    if body.bogon {
+        return Err(AppError::InvalidArgument(format!(
+            "{} is not a public address",
+            body.ip
+        )));
+    }
+
+    let (asn, as_name) = split_as(body.org.as_deref());
+    let (latitude, longitude) = body
+        .loc
+        .as_deref()
+        .and_then(|loc| loc.split_once(','))
+        .map(|(lat, lon)| (lat.trim().parse().ok(), lon.trim().parse().ok()))
+        .unwrap_or((None, None));
+    Ok(IpInfo {
+        ip: body.ip,
+        asn,
+        org: as_name.clone(),
+        as_name,
+        isp: None,
+        country: None,
+        country_code: body.country,
+        region: body.region.filter(|x| !x.is_empty()),
+        city: body.city.filter(|x| !x.is_empty()),
+        latitude,
+        longitude,
+        timezone: body.timezone,
+        provider: Provider::Ipinfo,
+    })
+}
+
+/// The JSON at `url`; `key` is the same URL without credentials, for the
+/// cache and debug output. Only lookups of a given address are `cached`: the
+/// caller's own address can change, so `--offline` has nothing to serve it from.
+fn fetch(
+    provider: Provider,
+    url: &str,
+    key: &str,
+    cached: bool,
+    bearer: Option<&str>,
+    out: &GlobalArgs,
+) -> Result<serde_json::Value, AppError> {
+    let cache = Cache::new("dee-ip").ttl_secs(CACHE_TTL_SECS);
+    if cached {
+        if let Some(hit) = cache.lookup::<serde_json::Value>(key, &out.cache)? {
+            tracing::debug!("cached {key} ({}s old)", hit.age_secs);
+            return Ok(hit.value);
+        }
+    } else if out.cache.offline {
+        return Err(CacheError::OfflineMiss {
+            key: key.to_string(),
+        }
+        .into());
+    }
+
+    let wait = ratelimit::reserve(url)?;
+    if !wait.is_zero() {
+        tracing::debug!(
+            "waiting {:.1}s before calling {}",
+            wait.as_secs_f64(),
+            provider.as_str()
+        );
+        std::thread::sleep(wait);
+    }
+    tracing::debug!("GET {key}");
+
+    let client = Http::new("dee-ip", env!("CARGO_PKG_VERSION"))
+        .args(&out.http)
+        .blocking()
+        .map_err(|_| AppError::RequestFailed)?;
+    let mut request = client.get(url);
+    if let Some(token) = bearer {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().map_err(|_| AppError::RequestFailed)?;
+    match response.status() {
+        StatusCode::TOO_MANY_REQUESTS => return Err(AppError::RateLimited(provider)),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            return Err(AppError::AuthInvalid(provider))
+        }
+        status if !status.is_success() => {
+            return Err(AppError::ApiError(format!("HTTP {}", status.as_u16())))
+        }
+        _ => {}
+    }
+    let raw: serde_json::Value = response.json().map_err(|_| AppError::ParseFailed)?;
+
+    if cached {
+        cache.put(key, &raw);
+    }
+    Ok(raw)
+}
+
+/// `AS15169 Google LLC` as `("AS15169", "Google LLC")`.
+fn split_as(text: Option<&str>) -> (Option<String>, Option<String>) {
+    let Some(text) = text.map(str::trim).filter(|x| !x.is_empty()) else {
+        return (None, None);
+    };
+    let (first, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let is_asn = first.len() > 2
+        && first.starts_with("AS")
+        && first[2..].chars().all(|c| c.is_ascii_digit());
+    if !is_asn {
+        return (None, Some(text.to_string()));
+    }
+    let rest = rest.trim();
+    (
+        Some(first.to_string()),
+        (!rest.is_empty()).then(|| rest.to_string()),
+    )
+}
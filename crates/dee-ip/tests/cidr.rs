@@ -0,0 +1,65 @@
+use assert_cmd::Command;
+use serde_json::Value;
+
+fn run(args: &[&str]) -> (Option<i32>, Value) {
+    let out = Command::new(assert_cmd::cargo::cargo_bin!("dee-ip"))
+        .args(args)
+        .arg("--json")
+        .output()
+        .unwrap();
+    let parsed = serde_json::from_slice(&out.stdout).expect("valid json");
+    (out.status.code(), parsed)
+}
+
+#[test]
+fn contains_checks_both_families() {
+    let (code, parsed) = run(&["contains", "10.1.2.3/8", "10.42.0.7"]);
+    assert_eq!(code, Some(0));
+    // Host bits are cleared
+    assert_eq!(parsed["item"]["cidr"], "10.0.0.0/8");
+    assert_eq!(parsed["item"]["contains"], true);
+
+    let (_, parsed) = run(&["contains", "10.0.0.0/8", "11.0.0.1"]);
+    assert_eq!(parsed["item"]["contains"], false);
+
+    let (_, parsed) = run(&["contains", "2001:db8::/32", "2001:db8:ffff::1"]);
+    assert_eq!(parsed["item"]["contains"], true);
+
+    // Never across families
+    let (_, parsed) = run(&["contains", "0.0.0.0/0", "::1"]);
+    assert_eq!(parsed["item"]["contains"], false);
+
+    let (code, parsed) = run(&["contains", "10.0.0.0/33", "10.0.0.1"]);
+    assert_eq!(code, Some(2));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn split_cuts_equal_subnets() {
+    let (_, parsed) = run(&["split", "192.168.0.0/24", "--prefix", "26"]);
+    assert_eq!(parsed["count"], 4);
+    let last = &parsed["items"][3];
+    assert_eq!(last["cidr"], "192.168.0.192/26");
+    assert_eq!(last["first"], "192.168.0.192");
+    assert_eq!(last["last"], "192.168.0.255");
+    assert_eq!(last["addresses"], "64");
+
+    // Rounded up to a power of two
+    let (_, parsed) = run(&["split", "10.0.0.0/16", "--count", "6"]);
+    assert_eq!(parsed["count"], 8);
+    assert_eq!(parsed["items"][1]["cidr"], "10.0.32.0/19");
+
+    let (_, parsed) = run(&["split", "2001:db8::/48", "--prefix", "50"]);
+    assert_eq!(parsed["items"][1]["cidr"], "2001:db8:0:4000::/50");
+    assert_eq!(parsed["items"][0]["addresses"], "302231454903657293676544");
+
+    for args in [
+        ["split", "10.0.0.0/24", "--prefix", "20"],
+        ["split", "0.0.0.0/0", "--prefix", "24"],
+        ["split", "10.0.0.0/30", "--count", "8"],
+    ] {
+        let (code, parsed) = run(&args);
+        assert_eq!(code, Some(2), "{args:?}");
+        assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+    }
+}
@@ -0,0 +1,90 @@
+use assert_cmd::Command;
+use dee_test_support::seed_cache;
+use std::path::Path;
+
+fn bin(home: &Path) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-ip"));
+    cmd.env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env_remove("DEE_IP_PROVIDER")
+        .env_remove("DEE_IP_IP_API_KEY")
+        .env_remove("DEE_IP_IPINFO_TOKEN");
+    cmd
+}
+
+fn json(cmd: &mut Command) -> (Option<i32>, serde_json::Value) {
+    let out = cmd.output().unwrap();
+    let parsed = serde_json::from_slice(&out.stdout).expect("valid json");
+    (out.status.code(), parsed)
+}
+
+#[test]
+fn rejects_addresses_without_a_public_location() {
+    let home = tempfile::tempdir().unwrap();
+    for ip in [
+        "10.0.0.1",
+        "127.0.0.1",
+        "100.64.1.1",
+        "fd00::1",
+        "not-an-ip",
+    ] {
+        let (code, parsed) = json(bin(home.path()).args([ip, "--json"]));
+        assert_eq!(code, Some(2), "{ip}");
+        assert_eq!(parsed["ok"], false);
+        assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+    }
+
+    let (code, parsed) = json(bin(home.path()).arg("--json"));
+    assert_eq!(code, Some(2));
+    assert!(parsed["error"].as_str().unwrap().contains("dee-ip me"));
+}
+
+#[test]
+fn offline_serves_cached_lookups() {
+    let home = tempfile::tempdir().unwrap();
+
+    let (_, parsed) = json(bin(home.path()).args(["8.8.8.8", "--offline", "--json"]));
+    assert_eq!(parsed["code"], "OFFLINE_MISS");
+
+    let key = "http://ip-api.com/json/8.8.8.8?fields=status,message,query,country,countryCode,regionName,city,lat,lon,timezone,isp,org,as";
+    let body = serde_json::json!({
+        "status": "success",
+        "query": "8.8.8.8",
+        "country": "United States",
+        "countryCode": "US",
+        "regionName": "Virginia",
+        "city": "Ashburn",
+        "lat": 39.03,
+        "lon": -77.5,
+        "timezone": "America/New_York",
+        "isp": "Google LLC",
+        "org": "Google Public DNS",
+        "as": "AS15169 Google LLC"
+    });
+    seed_cache(&home.path().join("cache"), "dee-ip", key, &body);
+
+    let (code, parsed) = json(bin(home.path()).args(["8.8.8.8", "--offline", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["stale"], true);
+    let item = &parsed["item"];
+    assert_eq!(item["asn"], "AS15169");
+    assert_eq!(item["as_name"], "Google LLC");
+    assert_eq!(item["org"], "Google Public DNS");
+    assert_eq!(item["country_code"], "US");
+    assert_eq!(item["city"], "Ashburn");
+    assert_eq!(item["provider"], "ip-api");
+
+    // Another provider is another cache entry
+    let (_, parsed) =
+        json(bin(home.path()).args(["8.8.8.8", "--provider", "ipinfo", "--offline", "--json"]));
+    assert_eq!(parsed["code"], "OFFLINE_MISS");
+}
+
+#[test]
+fn offline_never_looks_up_the_callers_own_address() {
+    let home = tempfile::tempdir().unwrap();
+    let (code, parsed) = json(bin(home.path()).args(["me", "--offline", "--json"]));
+    assert_eq!(code, Some(3));
+    assert_eq!(parsed["code"], "OFFLINE_MISS");
+}
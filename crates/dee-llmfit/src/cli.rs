@@ -1,4 +1,5 @@
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::Format;
 
 #[derive(Debug, Clone, Args)]
 pub struct GlobalArgs {
@@ -13,6 +14,10 @@ pub struct GlobalArgs {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     pub verbose: bool,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    pub output: Option<Format>,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
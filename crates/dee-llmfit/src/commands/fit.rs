@@ -1,14 +1,15 @@
 use comfy_table::Table;
+use schemars::JsonSchema;
 use serde::Serialize;
 
 use crate::cli::{FitArgs, UseCaseArg};
 use crate::hardware::SystemSpecs;
 use crate::models::{ModelDatabase, UseCase};
-use crate::output::{AppResult, ListJson, OutputMode, print_json};
+use crate::output::{AppResult, OutputMode, print_list};
 use crate::scoring::{FitLevel, ModelFit, backend_compatible, rank_models_by_fit};
 
-#[derive(Debug, Serialize)]
-struct FitItem {
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FitItem {
     name: String,
     provider: String,
     parameter_count: String,
@@ -48,26 +49,20 @@ pub fn run(args: FitArgs, output: OutputMode) -> AppResult<()> {
         fits.truncate(args.limit);
     }
 
-    if output.verbose {
-        eprintln!(
-            "debug: fit candidates={} filters(perfect={}, all={}, use_case={})",
-            fits.len(),
-            args.perfect,
-            args.all,
-            args.use_case
-                .as_ref()
-                .map(use_case_arg_label)
-                .unwrap_or("none")
-        );
-    }
-
-    if output.json {
+    tracing::debug!(
+        "fit candidates={} filters(perfect={}, all={}, use_case={})",
+        fits.len(),
+        args.perfect,
+        args.all,
+        args.use_case
+            .as_ref()
+            .map(use_case_arg_label)
+            .unwrap_or("none")
+    );
+
+    if let Some(format) = output.list {
         let items = fits.iter().map(to_fit_item).collect::<Vec<_>>();
-        return print_json(&ListJson {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+        return print_list(&items, format);
     }
 
     if output.quiet {
@@ -1,14 +1,15 @@
 use comfy_table::Table;
+use schemars::JsonSchema;
 use serde::Serialize;
 
 use crate::cli::InfoArgs;
 use crate::hardware::SystemSpecs;
 use crate::models::ModelDatabase;
-use crate::output::{AppError, AppResult, ItemJson, OutputMode, print_json};
+use crate::output::{AppError, AppResult, OkItem, OutputMode, print_json};
 use crate::scoring::{ModelFit, backend_compatible};
 
-#[derive(Debug, Serialize)]
-struct InfoItem {
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct InfoItem {
     name: String,
     provider: String,
     parameter_count: String,
@@ -46,18 +47,16 @@ pub fn run(args: InfoArgs, output: OutputMode) -> AppResult<()> {
             .to_string()
     };
 
-    if output.verbose {
-        eprintln!(
-            "debug: info model='{}' fit={} runtime={} compatible={}",
-            model.name,
-            fit.fit_level.label(),
-            fit.runtime.label(),
-            compatible
-        );
-    }
+    tracing::debug!(
+        "info model='{}' fit={} runtime={} compatible={}",
+        model.name,
+        fit.fit_level.label(),
+        fit.runtime.label(),
+        compatible
+    );
 
     if output.json {
-        return print_json(&ItemJson {
+        return print_json(&OkItem {
             ok: true,
             item: InfoItem {
                 name: fit.model.name.clone(),
@@ -3,7 +3,7 @@ use comfy_table::Table;
 use crate::cli::PlanArgs;
 use crate::hardware::SystemSpecs;
 use crate::models::ModelDatabase;
-use crate::output::{AppError, AppResult, ItemJson, OutputMode, print_json};
+use crate::output::{AppError, AppResult, OkItem, OutputMode, print_json};
 use crate::scoring::{PlanRequest, estimate_model_plan};
 
 pub fn run(args: PlanArgs, output: OutputMode) -> AppResult<()> {
@@ -22,27 +22,22 @@ pub fn run(args: PlanArgs, output: OutputMode) -> AppResult<()> {
     let estimate =
         estimate_model_plan(model, &request, &system).map_err(AppError::InvalidArgument)?;
 
-    if output.verbose {
-        eprintln!(
-            "debug: plan model='{}' context={} quant='{}' target_tps={}",
-            estimate.model_name,
-            estimate.context,
-            estimate.quantization,
-            estimate
-                .target_tps
-                .map(|v| format!("{v:.1}"))
-                .unwrap_or_else(|| "none".to_string())
-        );
-    }
+    tracing::debug!(
+        "plan model='{}' context={} quant='{}' target_tps={}",
+        estimate.model_name,
+        estimate.context,
+        estimate.quantization,
+        estimate
+            .target_tps
+            .map(|v| format!("{v:.1}"))
+            .unwrap_or_else(|| "none".to_string())
+    );
 
     if output.json {
-        let mut value = serde_json::to_value(ItemJson {
+        return print_json(&OkItem {
             ok: true,
             item: estimate,
-        })
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-        strip_nulls(&mut value);
-        return print_json(&value);
+        });
     }
 
     if output.quiet {
@@ -174,26 +169,3 @@ fn map_selector_error(message: String) -> AppError {
         AppError::NotFound(message)
     }
 }
-
-fn strip_nulls(value: &mut serde_json::Value) {
-    match value {
-        serde_json::Value::Object(map) => {
-            let keys: Vec<String> = map
-                .iter()
-                .filter_map(|(k, v)| if v.is_null() { Some(k.clone()) } else { None })
-                .collect();
-            for key in keys {
-                map.remove(&key);
-            }
-            for child in map.values_mut() {
-                strip_nulls(child);
-            }
-        }
-        serde_json::Value::Array(items) => {
-            for item in items.iter_mut() {
-                strip_nulls(item);
-            }
-        }
-        _ => {}
-    }
-}
@@ -1,14 +1,15 @@
 use comfy_table::Table;
+use schemars::JsonSchema;
 use serde::Serialize;
 
 use crate::cli::{RecommendArgs, UseCaseArg};
 use crate::hardware::SystemSpecs;
 use crate::models::{ModelDatabase, UseCase};
-use crate::output::{AppResult, ListJson, OutputMode, print_json};
+use crate::output::{AppResult, OutputMode, print_list};
 use crate::scoring::{FitLevel, ModelFit, backend_compatible, rank_models_by_fit};
 
-#[derive(Debug, Serialize)]
-struct RecommendItem {
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RecommendItem {
     name: String,
     provider: String,
     parameter_count: String,
@@ -43,24 +44,18 @@ pub fn run(args: RecommendArgs, output: OutputMode) -> AppResult<()> {
         fits.truncate(args.limit);
     }
 
-    if output.verbose {
-        eprintln!(
-            "debug: recommend returned={} use_case={}",
-            fits.len(),
-            args.use_case
-                .as_ref()
-                .map(use_case_arg_label)
-                .unwrap_or("none")
-        );
-    }
+    tracing::debug!(
+        "recommend returned={} use_case={}",
+        fits.len(),
+        args.use_case
+            .as_ref()
+            .map(use_case_arg_label)
+            .unwrap_or("none")
+    );
 
-    if output.json {
+    if let Some(format) = output.list {
         let items = fits.iter().map(to_recommend_item).collect::<Vec<_>>();
-        return print_json(&ListJson {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+        return print_list(&items, format);
     }
 
     if output.quiet {
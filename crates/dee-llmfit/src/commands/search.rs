@@ -1,12 +1,13 @@
 use comfy_table::Table;
+use schemars::JsonSchema;
 use serde::Serialize;
 
 use crate::cli::SearchArgs;
 use crate::models::{LlmModel, ModelDatabase};
-use crate::output::{AppResult, ListJson, OutputMode, print_json};
+use crate::output::{AppResult, OutputMode, print_list};
 
-#[derive(Debug, Serialize)]
-struct SearchItem {
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SearchItem {
     name: String,
     provider: String,
     parameter_count: String,
@@ -24,24 +25,14 @@ pub fn run(args: SearchArgs, output: OutputMode) -> AppResult<()> {
         matches.truncate(args.limit);
     }
 
-    if output.verbose {
-        eprintln!(
-            "debug: search query='{}' matches={}",
-            args.query,
-            matches.len()
-        );
-    }
+    tracing::debug!("search query='{}' matches={}", args.query, matches.len());
 
-    if output.json {
+    if let Some(format) = output.list {
         let items = matches
             .iter()
             .map(|m| to_search_item(m))
             .collect::<Vec<_>>();
-        return print_json(&ListJson {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+        return print_list(&items, format);
     }
 
     if output.quiet {
@@ -1,11 +1,12 @@
 use comfy_table::Table;
+use schemars::JsonSchema;
 use serde::Serialize;
 
 use crate::hardware::SystemSpecs;
-use crate::output::{AppResult, ItemJson, OutputMode, print_json};
+use crate::output::{AppResult, OkItem, OutputMode, print_json};
 
-#[derive(Debug, Serialize)]
-struct GpuItem {
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GpuItem {
     name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     vram_gb: Option<f64>,
@@ -14,8 +15,8 @@ struct GpuItem {
     unified_memory: bool,
 }
 
-#[derive(Debug, Serialize)]
-struct SystemItem {
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SystemItem {
     total_ram_gb: f64,
     available_ram_gb: f64,
     cpu_cores: usize,
@@ -36,18 +37,16 @@ struct SystemItem {
 pub fn run(output: OutputMode) -> AppResult<()> {
     let system = SystemSpecs::detect();
 
-    if output.verbose {
-        eprintln!(
-            "debug: detected cpu={} cores={} gpu_count={} backend={}",
-            system.cpu_name,
-            system.total_cpu_cores,
-            system.gpu_count,
-            system.backend.label()
-        );
-    }
+    tracing::debug!(
+        "detected cpu={} cores={} gpu_count={} backend={}",
+        system.cpu_name,
+        system.total_cpu_cores,
+        system.gpu_count,
+        system.backend.label()
+    );
 
     if output.json {
-        return print_json(&ItemJson {
+        return print_json(&OkItem {
             ok: true,
             item: to_system_item(&system),
         });
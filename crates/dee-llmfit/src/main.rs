@@ -5,16 +5,29 @@ mod models;
 mod output;
 mod scoring;
 
-use clap::Parser;
 use cli::{Cli, Commands};
-use output::{OutputMode, print_error};
+use dee_core::schema::{self, Schemas};
+use dee_core::{ErrorCode, Format, report};
+use output::OutputMode;
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .item::<commands::system::SystemItem>("system")
+        .list::<commands::fit::FitItem>("fit")
+        .list::<commands::search::SearchItem>("search")
+        .item::<commands::info::InfoItem>("info")
+        .list::<commands::recommend::RecommendItem>("recommend")
+        .item::<scoring::PlanEstimate>("plan")
+}
 
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
     let output = OutputMode {
         json: cli.global.json,
         quiet: cli.global.quiet,
-        verbose: cli.global.verbose,
+        list: Format::resolve(cli.global.output, cli.global.json),
     };
 
     let result = match cli.command {
@@ -27,39 +40,7 @@ fn main() {
     };
 
     if let Err(err) = result {
-        print_error(&err, output.json);
-        std::process::exit(1);
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT",
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(1);
-        }
+        report(&err, Format::json_errors(cli.global.output, output.json));
+        std::process::exit(err.exit_code());
     }
 }
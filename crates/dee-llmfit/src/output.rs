@@ -1,10 +1,15 @@
+use dee_core::format::render_list;
+use dee_core::{ErrorCode, Format};
 use serde::Serialize;
 
+pub use dee_core::OkItem;
+
 #[derive(Debug, Clone, Copy)]
 pub struct OutputMode {
     pub json: bool,
     pub quiet: bool,
-    pub verbose: bool,
+    /// `--output`, or `json` under `--json`; list commands only
+    pub list: Option<Format>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -19,8 +24,8 @@ pub enum AppError {
     Internal(String),
 }
 
-impl AppError {
-    pub fn code(&self) -> &'static str {
+impl ErrorCode for AppError {
+    fn code(&self) -> &'static str {
         match self {
             Self::InvalidArgument(_) => "INVALID_ARGUMENT",
             Self::NotFound(_) => "NOT_FOUND",
@@ -38,46 +43,14 @@ impl From<anyhow::Error> for AppError {
 
 pub type AppResult<T> = Result<T, AppError>;
 
-#[derive(Serialize)]
-struct ErrorJson<'a> {
-    ok: bool,
-    error: &'a str,
-    code: &'a str,
-}
-
-#[derive(Serialize)]
-pub struct ListJson<T: Serialize> {
-    pub ok: bool,
-    pub count: usize,
-    pub items: Vec<T>,
-}
-
-#[derive(Serialize)]
-pub struct ItemJson<T: Serialize> {
-    pub ok: bool,
-    pub item: T,
-}
-
 pub fn print_json<T: Serialize>(value: &T) -> AppResult<()> {
     let out = serde_json::to_string(value).map_err(|e| AppError::Internal(e.to_string()))?;
     println!("{out}");
     Ok(())
 }
 
-pub fn print_error(err: &AppError, json: bool) {
-    if json {
-        let payload = ErrorJson {
-            ok: false,
-            error: &err.to_string(),
-            code: err.code(),
-        };
-        match serde_json::to_string(&payload) {
-            Ok(text) => println!("{text}"),
-            Err(_) => println!(
-                "{{\"ok\":false,\"error\":\"Internal serialization error\",\"code\":\"SERIALIZE\"}}"
-            ),
-        }
-    } else {
-        eprintln!("error: {err}");
-    }
+pub fn print_list<T: Serialize>(items: &[T], format: Format) -> AppResult<()> {
+    let out = render_list(items, format).map_err(|e| AppError::Internal(e.to_string()))?;
+    print!("{out}");
+    Ok(())
 }
@@ -16,7 +16,7 @@ impl InferenceRuntime {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum FitLevel {
     Perfect,
@@ -45,7 +45,7 @@ impl FitLevel {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum RunMode {
     Gpu,
@@ -623,14 +623,15 @@ pub struct PlanRequest {
     pub target_tps: Option<f64>,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
 pub struct HardwareEstimate {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub vram_gb: Option<f64>,
     pub ram_gb: f64,
     pub cpu_cores: usize,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum PlanRunPath {
     Gpu,
@@ -656,41 +657,49 @@ impl PlanRunPath {
     }
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
 pub struct PathEstimate {
     pub path: PlanRunPath,
     pub feasible: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub minimum: Option<HardwareEstimate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub recommended: Option<HardwareEstimate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub estimated_tps: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub fit_level: Option<FitLevel>,
     pub notes: Vec<String>,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
 pub struct UpgradeDelta {
     pub resource: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub add_gb: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub add_cores: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub target_fit: Option<FitLevel>,
     pub path: PlanRunPath,
     pub description: String,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
 pub struct PlanCurrentStatus {
     pub fit_level: FitLevel,
     pub run_mode: RunMode,
     pub estimated_tps: f64,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
 pub struct PlanEstimate {
     pub estimate_notice: String,
     pub model_name: String,
     pub provider: String,
     pub context: u32,
     pub quantization: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub target_tps: Option<f64>,
     pub minimum: HardwareEstimate,
     pub recommended: HardwareEstimate,
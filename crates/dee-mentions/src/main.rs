@@ -4,9 +4,14 @@ use std::path::PathBuf;
 
 use chrono::{SecondsFormat, TimeZone, Utc};
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::http::{Http, HttpArgs};
+use dee_core::ratelimit::Limiter;
+use dee_core::schema::{self, Schemas};
+use dee_core::{print_json, print_list, report, ConfigError, ErrorCode, Format};
 use futures::future::join_all;
 use reqwest::Client;
 use rusqlite::{params, Connection, OptionalExtension};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Parser)]
@@ -38,6 +43,13 @@ struct GlobalFlags {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -120,7 +132,7 @@ struct WatchRemoveArgs {
     id: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 struct MentionItem {
     source: String,
     query: String,
@@ -145,7 +157,7 @@ struct WatchItem {
     created_at: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 struct WatchItemOut {
     id: i64,
     query: String,
@@ -155,14 +167,7 @@ struct WatchItemOut {
     created_at: String,
 }
 
-#[derive(Debug, Serialize)]
-struct ListResponse<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ActionResponse {
     ok: bool,
     message: String,
@@ -170,13 +175,6 @@ struct ActionResponse {
     id: Option<i64>,
 }
 
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
 #[derive(Debug, thiserror::Error)]
 enum AppError {
     #[error("Data directory not found")]
@@ -189,9 +187,11 @@ enum AppError {
     RequestFailed,
     #[error("Database operation failed")]
     Database,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
             Self::DataDirMissing => "CONFIG_MISSING",
@@ -199,6 +199,7 @@ impl AppError {
             Self::NotFound => "NOT_FOUND",
             Self::RequestFailed => "REQUEST_FAILED",
             Self::Database => "DATABASE_ERROR",
+            Self::Config(err) => err.code(),
         }
     }
 }
@@ -260,21 +261,27 @@ struct RedditPost {
 #[derive(Debug)]
 struct SourceFailure;
 
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<MentionItem>("check")
+        .list::<MentionItem>("run")
+        .custom::<ActionResponse>("watch add")
+        .list::<WatchItemOut>("watch list")
+        .custom::<ActionResponse>("watch remove")
+}
+
 #[tokio::main(flavor = "multi_thread")]
 async fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = run(&cli).await {
-        if cli.global.json {
-            print_json(&ErrorResponse {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -286,17 +293,14 @@ async fn run(cli: &Cli) -> Result<(), AppError> {
         fs::create_dir_all(parent).map_err(|_| AppError::Database)?;
     }
 
-    if cli.global.verbose {
-        eprintln!("[dee-mentions] db_path={}", db_path.display());
-    }
+    tracing::debug!("db_path={}", db_path.display());
 
     let conn = Connection::open(db_path).map_err(|_| AppError::Database)?;
     init_db(&conn)?;
 
     match &cli.command {
         Commands::Check(args) => {
-            let items =
-                fetch_mentions(&args.query, &args.sources, args.limit, cli.global.verbose).await?;
+            let items = fetch_mentions(&args.query, &args.sources, args.limit, &cli.global).await?;
             print_mentions(items, &cli.global)
         }
         Commands::Run(args) => {
@@ -311,8 +315,7 @@ async fn run(cli: &Cli) -> Result<(), AppError> {
                 };
 
                 let mut found =
-                    fetch_mentions(&watch.query, &source_list, args.limit, cli.global.verbose)
-                        .await?;
+                    fetch_mentions(&watch.query, &source_list, args.limit, &cli.global).await?;
                 items.append(&mut found);
             }
 
@@ -351,7 +354,7 @@ async fn run(cli: &Cli) -> Result<(), AppError> {
                     items.push(row.map_err(|_| AppError::Database)?);
                 }
 
-                if cli.global.json {
+                if let Some(format) = Format::resolve(cli.global.output, cli.global.json) {
                     let out_items: Vec<WatchItemOut> = items
                         .iter()
                         .map(|item| WatchItemOut {
@@ -366,11 +369,7 @@ async fn run(cli: &Cli) -> Result<(), AppError> {
                             created_at: item.created_at.clone(),
                         })
                         .collect();
-                    print_json(&ListResponse {
-                        ok: true,
-                        count: out_items.len(),
-                        items: out_items,
-                    });
+                    print_list(&out_items, format);
                 } else if cli.global.quiet {
                     println!("{}", items.len());
                 } else if items.is_empty() {
@@ -529,13 +528,13 @@ async fn fetch_mentions(
     query: &str,
     sources: &[Source],
     limit: usize,
-    verbose: bool,
+    global: &GlobalFlags,
 ) -> Result<Vec<MentionItem>, AppError> {
-    let client = Client::builder()
-        .user_agent("dee-mentions/0.1")
-        .timeout(std::time::Duration::from_secs(20))
-        .build()
+    let client = Http::new("dee-mentions", env!("CARGO_PKG_VERSION"))
+        .args(&global.http)
+        .client()
         .map_err(|_| AppError::RequestFailed)?;
+    let limiter = Limiter::load()?;
 
     let unique_sources: HashSet<Source> = sources.iter().copied().collect();
     if unique_sources.is_empty() {
@@ -549,10 +548,11 @@ async fn fetch_mentions(
     for source in unique_sources {
         let c = client.clone();
         let q = query.to_string();
+        let limiter = &limiter;
         let task = async move {
             match source {
-                Source::Hn => fetch_hn_mentions(&c, &q, limit).await,
-                Source::Reddit => fetch_reddit_mentions(&c, &q, limit).await,
+                Source::Hn => fetch_hn_mentions(&c, limiter, &q, limit).await,
+                Source::Reddit => fetch_reddit_mentions(&c, limiter, &q, limit).await,
             }
         };
         tasks.push(task);
@@ -570,9 +570,7 @@ async fn fetch_mentions(
                 all_mentions.append(&mut items);
             }
             Err(_) => {
-                if verbose {
-                    eprintln!("[dee-mentions] source request failed");
-                }
+                tracing::debug!("source request failed");
             }
         }
     }
@@ -585,8 +583,23 @@ async fn fetch_mentions(
     Ok(all_mentions)
 }
 
+/// Sleep until `url`'s host has budget left (`dee_core::ratelimit`).
+async fn wait_turn(limiter: &Limiter, url: &str) {
+    let wait = limiter.reserve(url);
+    if wait.is_zero() {
+        return;
+    }
+    tracing::debug!(
+        "waiting {:.1}s before {}",
+        wait.as_secs_f64(),
+        dee_core::ratelimit::host_of(url)
+    );
+    tokio::time::sleep(wait).await;
+}
+
 async fn fetch_hn_mentions(
     client: &Client,
+    limiter: &Limiter,
     query: &str,
     limit: usize,
 ) -> Result<Vec<MentionItem>, SourceFailure> {
@@ -599,6 +612,7 @@ async fn fetch_hn_mentions(
         limit
     );
 
+    wait_turn(limiter, &url).await;
     let response = client.get(url).send().await.map_err(|_| SourceFailure)?;
     if !response.status().is_success() {
         return Err(SourceFailure);
@@ -633,6 +647,7 @@ async fn fetch_hn_mentions(
 
 async fn fetch_reddit_mentions(
     client: &Client,
+    limiter: &Limiter,
     query: &str,
     limit: usize,
 ) -> Result<Vec<MentionItem>, SourceFailure> {
@@ -645,6 +660,7 @@ async fn fetch_reddit_mentions(
         limit
     );
 
+    wait_turn(limiter, &url).await;
     let response = client
         .get(url)
         .header("User-Agent", "dee-mentions/0.1")
@@ -731,12 +747,8 @@ fn now_iso() -> String {
 }
 
 fn print_mentions(items: Vec<MentionItem>, global: &GlobalFlags) -> Result<(), AppError> {
-    if global.json {
-        print_json(&ListResponse {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(global.output, global.json) {
+        print_list(&items, format);
     } else if global.quiet {
         println!("{}", items.len());
     } else if items.is_empty() {
@@ -763,47 +775,3 @@ fn print_action(message: &str, id: Option<i64>, global: &GlobalFlags) -> Result<
     }
     Ok(())
 }
-
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(text) => println!("{text}"),
-        Err(_) => {
-            println!(
-                "{{\"ok\":false,\"error\":\"JSON serialization failed\",\"code\":\"SERIALIZE\"}}"
-            );
-            std::process::exit(1);
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -1,10 +1,20 @@
 use anyhow::{Context, Result};
 use clap::{Args, Parser, Subcommand};
+use dee_core::cache::{Cache, CacheArgs, CacheCommand, CacheError};
+use dee_core::format::render_list;
+use dee_core::http::{base_url, Http, HttpArgs};
+use dee_core::output::mark_stale;
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    exit, report_code, Config, ConfigError, ErrorCode, Format, OkItem, OkList, OkMessage,
+    SecretsBackend,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
 
-const API_MODELS_URL: &str = "https://openrouter.ai/api/v1/models";
+const API_BASE: &str = "https://openrouter.ai/api/v1";
+/// The model list changes a few times a day at most
+const CACHE_TTL_SECS: u64 = 60 * 60;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -12,7 +22,7 @@ const API_MODELS_URL: &str = "https://openrouter.ai/api/v1/models";
     version,
     about = "Search, filter, and inspect OpenRouter models",
     long_about = None,
-    after_help = "EXAMPLES:\n  dee-openrouter list --provider google\n  dee-openrouter list --free --limit 10 --json\n  dee-openrouter search gemini --json\n  dee-openrouter show google/gemini-2.5-pro --json\n  dee-openrouter config set openrouter.api-key sk-xxx\n  dee-openrouter config show --json\n  dee-openrouter config path"
+    after_help = "EXAMPLES:\n  dee-openrouter list --provider google\n  dee-openrouter list --free --limit 10 --json\n  dee-openrouter search gemini --json\n  dee-openrouter show google/gemini-2.5-pro --json\n  dee-openrouter config set openrouter.api-key sk-xxx\n  dee-openrouter config show --json\n  dee-openrouter config path\n  dee-openrouter list --no-cache\n  dee-openrouter cache clear"
 )]
 struct Cli {
     #[command(flatten)]
@@ -32,6 +42,11 @@ enum Commands {
     Search(SearchArgs),
     /// Manage configuration
     Config(ConfigArgs),
+    /// Manage the cached model list
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
 }
 
 #[derive(Args, Debug, Clone)]
@@ -45,6 +60,13 @@ struct OutputFlags {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long = "output", global = true, value_enum)]
+    format: Option<Format>,
+    #[command(flatten)]
+    http: HttpArgs,
+    #[command(flatten)]
+    cache: CacheArgs,
 }
 
 #[derive(Args, Debug)]
@@ -140,7 +162,7 @@ struct OpenRouterTopProvider {
     context_length: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 struct ModelItem {
     id: String,
     provider: String,
@@ -153,38 +175,14 @@ struct ModelItem {
     created_at: String,
 }
 
-#[derive(Debug, Serialize)]
-struct SuccessList<T: Serialize> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct SuccessItem<T: Serialize> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
-struct SuccessMsg {
-    ok: bool,
-    message: String,
-}
-
-#[derive(Debug, Serialize)]
-struct JsonError {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
 #[derive(Debug, thiserror::Error)]
 enum AppError {
     #[error("Model not found: {0}")]
     NotFound(String),
     #[error("Unknown config key: {0}")]
     UnknownKey(String),
+    #[error("{0}")]
+    InvalidValue(String),
 }
 
 /// Serializable config stored in ~/.config/dee-openrouter/config.toml
@@ -192,30 +190,44 @@ enum AppError {
 struct AppConfig {
     #[serde(default)]
     api_key: Option<String>,
+    /// `plaintext` (default) or `keyring`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secrets_backend: Option<SecretsBackend>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_url: Option<String>,
+}
+
+/// `DEE_OPENROUTER_API_KEY` and `DEE_OPENROUTER_BASE_URL` override the file. With `secrets_backend = "keyring"`
+/// the key lives in the OS keyring instead of the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-openrouter")
+        .env_overrides(&["api_key", "base_url"])
+        .secret("api_key")
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<ModelItem>("list")
+        .item::<ModelItem>("show")
+        .list::<ModelItem>("search")
+        .message("config set")
+        .item::<ConfigShowItem>("config show")
+        .item::<ConfigPathItem>("config path")
+        .cache()
 }
 
 #[tokio::main]
 async fn main() {
-    let cli = parse_cli();
-    let json_errors = cli.output.json;
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
+    let json_errors = Format::json_errors(cli.output.format, cli.output.json);
 
     let run = dispatch(cli).await;
     if let Err(err) = run {
-        if json_errors {
-            let payload = JsonError {
-                ok: false,
-                error: err.to_string(),
-                code: classify_error_code(&err).to_string(),
-            };
-            if let Ok(rendered) = serde_json::to_string_pretty(&payload) {
-                println!("{rendered}");
-            } else {
-                println!("{{\"ok\":false,\"error\":\"serialization failure\",\"code\":\"INTERNAL_ERROR\"}}");
-            }
-        } else {
-            eprintln!("{err:#}");
-        }
-        std::process::exit(1);
+        let code = classify_error_code(&err);
+        report_code(&format!("{err:#}"), code, json_errors);
+        std::process::exit(exit::for_code(code));
     }
 }
 
@@ -225,12 +237,15 @@ async fn dispatch(cli: Cli) -> Result<()> {
         Commands::Show(args) => handle_show(args, &cli.output).await,
         Commands::Search(args) => handle_search(args, &cli.output).await,
         Commands::Config(args) => handle_config(args, &cli.output),
+        Commands::Cache { command } => {
+            Ok(dee_core::cache::run(&cache(), command, cli.output.json)?)
+        }
     }
 }
 
 async fn handle_list(args: ListArgs, output: &OutputFlags) -> Result<()> {
-    let api_key = load_config().ok().and_then(|c| c.api_key);
-    let models = fetch_models(output.verbose, api_key.as_deref()).await?;
+    let cfg = config_file().load().unwrap_or_default();
+    let models = fetch_models(output, &cfg).await?;
     let provider_filter = args.provider.as_deref().map(str::to_lowercase);
 
     let mut items: Vec<ModelItem> = models
@@ -259,8 +274,11 @@ async fn handle_list(args: ListArgs, output: &OutputFlags) -> Result<()> {
         items.truncate(limit);
     }
 
-    if output.json {
-        print_json(&SuccessList {
+    if let Some(format) = output.format {
+        print!("{}", render_list(&items, format)?);
+        Ok(())
+    } else if output.json {
+        print_json(&OkList {
             ok: true,
             count: items.len(),
             items,
@@ -272,9 +290,9 @@ async fn handle_list(args: ListArgs, output: &OutputFlags) -> Result<()> {
 }
 
 async fn handle_show(args: ItemArgs, output: &OutputFlags) -> Result<()> {
-    let api_key = load_config().ok().and_then(|c| c.api_key);
+    let cfg = config_file().load().unwrap_or_default();
     let model_id = args.model_id.to_lowercase();
-    let item = fetch_models(output.verbose, api_key.as_deref())
+    let item = fetch_models(output, &cfg)
         .await?
         .into_iter()
         .map(normalize_model)
@@ -282,7 +300,7 @@ async fn handle_show(args: ItemArgs, output: &OutputFlags) -> Result<()> {
         .ok_or_else(|| anyhow::anyhow!(AppError::NotFound(args.model_id.clone())))?;
 
     if output.json {
-        print_json(&SuccessItem { ok: true, item })
+        print_json(&OkItem { ok: true, item })
     } else {
         if !output.quiet {
             println!("{}", item.id);
@@ -305,9 +323,9 @@ async fn handle_show(args: ItemArgs, output: &OutputFlags) -> Result<()> {
 }
 
 async fn handle_search(args: SearchArgs, output: &OutputFlags) -> Result<()> {
-    let api_key = load_config().ok().and_then(|c| c.api_key);
+    let cfg = config_file().load().unwrap_or_default();
     let q = args.query.to_lowercase();
-    let mut items: Vec<ModelItem> = fetch_models(output.verbose, api_key.as_deref())
+    let mut items: Vec<ModelItem> = fetch_models(output, &cfg)
         .await?
         .into_iter()
         .map(normalize_model)
@@ -322,8 +340,11 @@ async fn handle_search(args: SearchArgs, output: &OutputFlags) -> Result<()> {
         items.truncate(limit);
     }
 
-    if output.json {
-        print_json(&SuccessList {
+    if let Some(format) = output.format {
+        print!("{}", render_list(&items, format)?);
+        Ok(())
+    } else if output.json {
+        print_json(&OkList {
             ok: true,
             count: items.len(),
             items,
@@ -334,17 +355,34 @@ async fn handle_search(args: SearchArgs, output: &OutputFlags) -> Result<()> {
     }
 }
 
+#[derive(Serialize, JsonSchema)]
+struct ConfigShowItem {
+    path: String,
+    api_key_set: bool,
+    secrets_backend: SecretsBackend,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ConfigPathItem {
+    path: String,
+}
+
 fn handle_config(args: ConfigArgs, output: &OutputFlags) -> Result<()> {
     match args.command {
         ConfigCommand::Set(set_args) => {
-            if set_args.key != "openrouter.api-key" {
-                return Err(anyhow::anyhow!(AppError::UnknownKey(set_args.key)));
+            let mut cfg = config_file().load_file()?;
+            match set_args.key.as_str() {
+                "openrouter.api-key" => cfg.api_key = Some(set_args.value),
+                "openrouter.base-url" | "base_url" => cfg.base_url = Some(set_args.value),
+                "secrets_backend" => {
+                    cfg.secrets_backend =
+                        Some(set_args.value.parse().map_err(AppError::InvalidValue)?)
+                }
+                _ => return Err(anyhow::anyhow!(AppError::UnknownKey(set_args.key))),
             }
-            let mut cfg = load_config().unwrap_or_default();
-            cfg.api_key = Some(set_args.value);
-            save_config(&cfg)?;
+            config_file().save(&cfg)?;
             if output.json {
-                print_json(&SuccessMsg {
+                print_json(&OkMessage {
                     ok: true,
                     message: format!("Set {}", set_args.key),
                 })?;
@@ -354,44 +392,30 @@ fn handle_config(args: ConfigArgs, output: &OutputFlags) -> Result<()> {
             Ok(())
         }
         ConfigCommand::Show => {
-            let cfg = load_config().unwrap_or_default();
+            let cfg = config_file().load()?;
             if output.json {
-                #[derive(Serialize)]
-                struct ConfigShow {
-                    ok: bool,
-                    item: ConfigShowItem,
-                }
-                #[derive(Serialize)]
-                struct ConfigShowItem {
-                    path: String,
-                    api_key_set: bool,
-                }
-                print_json(&ConfigShow {
+                print_json(&OkItem {
                     ok: true,
                     item: ConfigShowItem {
-                        path: config_path().display().to_string(),
+                        path: config_file().path().display().to_string(),
                         api_key_set: cfg.api_key.is_some(),
+                        secrets_backend: cfg.secrets_backend.unwrap_or_default(),
                     },
                 })
             } else {
-                println!("path: {}", config_path().display());
+                println!("path: {}", config_file().path().display());
                 println!("api_key_set: {}", cfg.api_key.is_some());
+                println!(
+                    "secrets_backend: {}",
+                    cfg.secrets_backend.unwrap_or_default().as_str()
+                );
                 Ok(())
             }
         }
         ConfigCommand::Path => {
-            let path = config_path().display().to_string();
+            let path = config_file().path().display().to_string();
             if output.json {
-                #[derive(Serialize)]
-                struct ConfigPath {
-                    ok: bool,
-                    item: ConfigPathItem,
-                }
-                #[derive(Serialize)]
-                struct ConfigPathItem {
-                    path: String,
-                }
-                print_json(&ConfigPath {
+                print_json(&OkItem {
                     ok: true,
                     item: ConfigPathItem { path },
                 })?;
@@ -403,43 +427,27 @@ fn handle_config(args: ConfigArgs, output: &OutputFlags) -> Result<()> {
     }
 }
 
-fn config_path() -> PathBuf {
-    dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("dee-openrouter")
-        .join("config.toml")
-}
-
-fn load_config() -> Result<AppConfig> {
-    let path = config_path();
-    if !path.exists() {
-        return Ok(AppConfig::default());
-    }
-    let content = fs::read_to_string(&path)
-        .with_context(|| format!("failed to read config {}", path.display()))?;
-    toml::from_str(&content).context("invalid config.toml")
-}
-
-fn save_config(cfg: &AppConfig) -> Result<()> {
-    let path = config_path();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    let content = toml::to_string_pretty(cfg).context("failed to serialize config")?;
-    fs::write(&path, content).with_context(|| format!("failed to write config {}", path.display()))
+fn cache() -> Cache {
+    Cache::new("dee-openrouter").ttl_secs(CACHE_TTL_SECS)
 }
 
-async fn fetch_models(verbose: bool, api_key: Option<&str>) -> Result<Vec<OpenRouterModel>> {
-    if verbose {
-        eprintln!("Fetching models from {API_MODELS_URL}");
+/// The model list, from the cache while it is fresh unless `--no-cache`.
+async fn fetch_models(output: &OutputFlags, cfg: &AppConfig) -> Result<Vec<OpenRouterModel>> {
+    let url = format!("{}/models", base_url(cfg.base_url.as_deref(), API_BASE));
+    let cache = cache();
+    if let Some(hit) = cache.lookup::<OpenRouterResponse>(&url, &output.cache)? {
+        tracing::debug!("Using cached models ({}s old)", hit.age_secs);
+        return Ok(hit.value.data);
     }
+    tracing::debug!("Fetching models from {url}");
 
-    let client = reqwest::Client::new();
-    let mut req = client
-        .get(API_MODELS_URL)
-        .header("Accept", "application/json");
+    let client = Http::new("dee-openrouter", env!("CARGO_PKG_VERSION"))
+        .args(&output.http)
+        .client()
+        .context("failed to build HTTP client")?;
+    let mut req = client.get(&url).header("Accept", "application/json");
 
-    if let Some(key) = api_key {
+    if let Some(key) = &cfg.api_key {
         req = req.header("Authorization", format!("Bearer {key}"));
     }
 
@@ -454,10 +462,13 @@ async fn fetch_models(verbose: bool, api_key: Option<&str>) -> Result<Vec<OpenRo
         anyhow::bail!("OpenRouter API error: {status} - {body}");
     }
 
-    let parsed = response
-        .json::<OpenRouterResponse>()
+    let body = response
+        .json::<serde_json::Value>()
         .await
         .context("invalid OpenRouter API response")?;
+    let parsed: OpenRouterResponse =
+        serde_json::from_value(body.clone()).context("invalid OpenRouter API response")?;
+    cache.put(&url, &body);
 
     Ok(parsed.data)
 }
@@ -538,17 +549,31 @@ fn print_models_table(items: &[ModelItem], quiet: bool) {
 }
 
 fn print_json<T: Serialize>(value: &T) -> Result<()> {
-    let output = serde_json::to_string_pretty(value)?;
+    let mut value = serde_json::to_value(value)?;
+    mark_stale(&mut value);
+    let output = serde_json::to_string_pretty(&value)?;
     println!("{output}");
     Ok(())
 }
 
+impl ErrorCode for AppError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::UnknownKey(_) | Self::InvalidValue(_) => "INVALID_ARGUMENT",
+        }
+    }
+}
+
 fn classify_error_code(err: &anyhow::Error) -> &'static str {
     if let Some(app) = err.downcast_ref::<AppError>() {
-        return match app {
-            AppError::NotFound(_) => "NOT_FOUND",
-            AppError::UnknownKey(_) => "INVALID_ARGUMENT",
-        };
+        return app.code();
+    }
+    if let Some(config) = err.downcast_ref::<ConfigError>() {
+        return config.code();
+    }
+    if let Some(cache) = err.downcast_ref::<CacheError>() {
+        return cache.code();
     }
     if err.to_string().contains("OpenRouter API error") {
         "API_ERROR"
@@ -558,35 +583,3 @@ fn classify_error_code(err: &anyhow::Error) -> &'static str {
         "INTERNAL_ERROR"
     }
 }
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -0,0 +1,52 @@
+use assert_cmd::Command;
+use dee_test_support::{fixture, json, MockApi, Sandbox};
+
+fn bin(sandbox: &Sandbox, api: &MockApi) -> Command {
+    let mut cmd = sandbox.command(assert_cmd::cargo::cargo_bin!("dee-openrouter"));
+    cmd.env("DEE_OPENROUTER_BASE_URL", api.url("/api/v1"))
+        .env_remove("DEE_OPENROUTER_API_KEY");
+    cmd
+}
+
+#[test]
+fn list_free_then_show_from_the_cache() {
+    let api = MockApi::start();
+    api.get_json("/api/v1/models", &fixture("openrouter/models.json"));
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["list", "--free", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 1);
+    assert_eq!(
+        parsed["items"][0]["id"],
+        "meta-llama/llama-3.3-70b-instruct:free"
+    );
+    assert_eq!(parsed["items"][0]["provider"], "meta-llama");
+
+    let (code, parsed) =
+        json(bin(&sandbox, &api).args(["show", "google/gemini-2.5-pro", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["item"]["price_prompt_per_1m"], 1.25);
+    assert_eq!(parsed["item"]["context_length"], 1048576);
+    assert_eq!(api.requests().len(), 1);
+}
+
+#[test]
+fn api_key_is_sent_as_a_bearer_token() {
+    let api = MockApi::start();
+    api.get_json("/api/v1/models", &fixture("openrouter/models.json"));
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(
+        bin(&sandbox, &api)
+            .env("DEE_OPENROUTER_API_KEY", "sk-or-test")
+            .args(["search", "claude", "--json"]),
+    );
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["items"][0]["id"], "anthropic/claude-sonnet-4");
+    let requests = api.requests();
+    assert_eq!(
+        requests[0].headers.get("authorization").unwrap(),
+        "Bearer sk-or-test"
+    );
+}
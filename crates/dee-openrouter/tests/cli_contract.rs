@@ -62,3 +62,16 @@ fn config_path_subcommand_succeeds() {
         .success()
         .stdout(predicate::str::contains("config.toml"));
 }
+
+#[test]
+fn offline_without_a_cached_model_list_is_offline_miss() {
+    let cache = tempfile::tempdir().unwrap();
+    let out = bin()
+        .env("XDG_CACHE_HOME", cache.path())
+        .args(["list", "--offline", "--json"])
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(3));
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], "OFFLINE_MISS");
+}
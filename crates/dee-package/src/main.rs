@@ -1,15 +1,13 @@
 use clap::{Args, Parser, Subcommand};
+use dee_core::http::{Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{print_json, print_list, report, ErrorCode, Format, OkItem};
 use reqwest::blocking::{Client, Response};
 use reqwest::StatusCode;
+use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::time::Duration;
 
 const CRATES_API: &str = "https://crates.io/api/v1/crates";
-const USER_AGENT: &str = concat!(
-    "dee-package/",
-    env!("CARGO_PKG_VERSION"),
-    " (https://dee.ink)"
-);
 
 #[derive(Debug, Parser)]
 #[command(
@@ -33,6 +31,11 @@ struct GlobalArgs {
     quiet: bool,
     #[arg(short = 'v', long, global = true, help = "Debug output to stderr")]
     verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -102,7 +105,7 @@ enum AppError {
     Internal(String),
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
             Self::UnsupportedEcosystem(_) => "UNSUPPORTED_ECOSYSTEM",
@@ -116,27 +119,7 @@ impl AppError {
     }
 }
 
-#[derive(Debug, Serialize)]
-struct JsonList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct JsonItem<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
-struct JsonError {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 struct PackageSummary {
     ecosystem: String,
     name: String,
@@ -157,7 +140,7 @@ struct PackageSummary {
     source_url: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct PackageInfo {
     ecosystem: String,
     name: String,
@@ -185,7 +168,7 @@ struct PackageInfo {
     source_url: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct VersionInfo {
     ecosystem: String,
     name: String,
@@ -294,52 +277,31 @@ struct CrateVersion {
     crate_size: Option<u64>,
 }
 
-fn main() {
-    let cli = match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => {
-            if err.kind() == clap::error::ErrorKind::DisplayHelp
-                || err.kind() == clap::error::ErrorKind::DisplayVersion
-            {
-                err.exit();
-            }
-
-            if wants_json_from_args() {
-                print_json(&JsonError {
-                    ok: false,
-                    error: err.to_string().trim().to_string(),
-                    code: "INVALID_ARGUMENT".to_string(),
-                });
-                std::process::exit(1);
-            }
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<PackageSummary>("search")
+        .item::<PackageInfo>("info")
+        .item::<VersionInfo>("latest")
+}
 
-            err.exit();
-        }
-    };
+fn main() {
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = run(&cli) {
-        if cli.global.json {
-            print_json(&JsonError {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
-fn wants_json_from_args() -> bool {
-    std::env::args_os().any(|arg| arg == "--json" || arg == "-j")
-}
-
 fn run(cli: &Cli) -> Result<(), AppError> {
-    let client = Client::builder()
-        .user_agent(USER_AGENT)
-        .timeout(Duration::from_secs(20))
-        .build()
+    let client = Http::new("dee-package", env!("CARGO_PKG_VERSION"))
+        .args(&cli.global.http)
+        .blocking()
         .map_err(|err| AppError::Internal(err.to_string()))?;
 
     match &cli.command {
@@ -364,10 +326,7 @@ fn search_packages(client: &Client, args: &SearchArgs, out: &GlobalArgs) -> Resu
 
     match ecosystem {
         Ecosystem::CratesIo => {
-            verbose(
-                out,
-                &format!("GET {CRATES_API}?q={}&per_page={}", args.query, args.limit),
-            );
+            tracing::debug!("GET {CRATES_API}?q={}&per_page={}", args.query, args.limit);
             let response = client
                 .get(CRATES_API)
                 .query(&[
@@ -384,12 +343,8 @@ fn search_packages(client: &Client, args: &SearchArgs, out: &GlobalArgs) -> Resu
                 .map(|item| item.into_summary(ecosystem))
                 .collect();
 
-            if out.json {
-                print_json(&JsonList {
-                    ok: true,
-                    count: items.len(),
-                    items,
-                });
+            if let Some(format) = Format::resolve(out.output, out.json) {
+                print_list(&items, format);
             } else if out.quiet {
                 for item in items {
                     println!("{} {}", item.name, item.version);
@@ -417,11 +372,11 @@ fn search_packages(client: &Client, args: &SearchArgs, out: &GlobalArgs) -> Resu
 
 fn show_info(client: &Client, args: &PackageArgs, out: &GlobalArgs) -> Result<(), AppError> {
     let ecosystem = Ecosystem::parse(&args.ecosystem)?;
-    let response = fetch_crate(client, ecosystem, &args.name, out)?;
+    let response = fetch_crate(client, ecosystem, &args.name)?;
     let item = response.into_info(ecosystem);
 
     if out.json {
-        print_json(&JsonItem { ok: true, item });
+        print_json(&OkItem { ok: true, item });
     } else if out.quiet {
         println!("{} {}", item.name, item.latest_version);
     } else {
@@ -444,11 +399,11 @@ fn show_info(client: &Client, args: &PackageArgs, out: &GlobalArgs) -> Result<()
 
 fn show_latest(client: &Client, args: &PackageArgs, out: &GlobalArgs) -> Result<(), AppError> {
     let ecosystem = Ecosystem::parse(&args.ecosystem)?;
-    let response = fetch_crate(client, ecosystem, &args.name, out)?;
+    let response = fetch_crate(client, ecosystem, &args.name)?;
     let item = response.into_latest(ecosystem);
 
     if out.json {
-        print_json(&JsonItem { ok: true, item });
+        print_json(&OkItem { ok: true, item });
     } else if out.quiet {
         println!("{} {}", item.name, item.version);
     } else {
@@ -467,7 +422,6 @@ fn fetch_crate(
     client: &Client,
     ecosystem: Ecosystem,
     name: &str,
-    out: &GlobalArgs,
 ) -> Result<CrateResponse, AppError> {
     if name.trim().is_empty() {
         return Err(AppError::InvalidArgument(
@@ -479,7 +433,7 @@ fn fetch_crate(
         Ecosystem::CratesIo => {
             let encoded = urlencoding::encode(name.trim());
             let url = format!("{CRATES_API}/{encoded}");
-            verbose(out, &format!("GET {url}"));
+            tracing::debug!("GET {url}");
             let response = client
                 .get(&url)
                 .send()
@@ -511,22 +465,6 @@ fn parse_response<T: DeserializeOwned>(
         .map_err(|err| AppError::ParseFailed(err.to_string()))
 }
 
-fn verbose(out: &GlobalArgs, message: &str) {
-    if out.verbose && !out.quiet {
-        eprintln!("debug: {message}");
-    }
-}
-
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string_pretty(value) {
-        Ok(json) => println!("{json}"),
-        Err(_) => println!(
-            "{}",
-            r#"{"ok":false,"error":"serialization failure","code":"INTERNAL"}"#
-        ),
-    }
-}
-
 impl CrateSearchItem {
     fn into_summary(self, ecosystem: Ecosystem) -> PackageSummary {
         let name = if self.name.is_empty() {
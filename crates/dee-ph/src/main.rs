@@ -1,13 +1,16 @@
-use std::fs;
-use std::path::PathBuf;
-
-use anyhow::{Context, Result};
+use anyhow::Result;
 use clap::{Args, Parser, Subcommand, ValueEnum};
-use reqwest::blocking::Client;
+use dee_core::http::{base_url, Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, report, Config, ConfigError, ErrorCode, Format, OkItem, OkMessage,
+    SecretsBackend,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-const PH_API_URL: &str = "https://api.producthunt.com/v2/api/graphql";
+const PH_API_BASE: &str = "https://api.producthunt.com/v2/api";
 
 #[derive(Debug, Parser)]
 #[command(
@@ -31,6 +34,11 @@ struct GlobalArgs {
     quiet: bool,
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -98,39 +106,18 @@ struct ShowFlags {
     json: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
 struct AppConfig {
     #[serde(default)]
     api_key: Option<String>,
+    /// `plaintext` (default) or `keyring`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secrets_backend: Option<SecretsBackend>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_url: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct OkList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct OkItem<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
-struct OkMessage {
-    ok: bool,
-    message: String,
-}
-
-#[derive(Debug, Serialize)]
-struct ErrorJson {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ProductItem {
     id: String,
     slug: String,
@@ -143,10 +130,18 @@ struct ProductItem {
     created_at: String,
 }
 
+/// `DEE_PH_API_KEY` and `DEE_PH_BASE_URL` override the file. With `secrets_backend = "keyring"` the key
+/// lives in the OS keyring instead of the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-ph")
+        .env_overrides(&["api_key", "base_url"])
+        .secret("api_key")
+}
+
 #[derive(Debug, thiserror::Error)]
 enum AppError {
-    #[error("Configuration directory not found")]
-    ConfigMissing,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
     #[error("Missing Product Hunt API key. Set ph.api-key via config set")]
     AuthMissing,
     #[error("Unknown config key: {0}")]
@@ -163,10 +158,10 @@ enum AppError {
     ParseFailed,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
-            Self::ConfigMissing => "CONFIG_MISSING",
+            Self::Config(err) => err.code(),
             Self::AuthMissing => "AUTH_MISSING",
             Self::InvalidConfigKey(_) | Self::InvalidArgument(_) => "INVALID_ARGUMENT",
             Self::RequestFailed => "REQUEST_FAILED",
@@ -230,21 +225,27 @@ struct PostNode {
     created_at: String,
 }
 
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<ProductItem>("top")
+        .list::<ProductItem>("search")
+        .item::<ProductItem>("show")
+        .message("config set")
+        .item::<AppConfig>("config show")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     let result = dispatch(&cli);
     if let Err(err) = result {
-        if cli.global.json {
-            print_json(&ErrorJson {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -278,15 +279,11 @@ fn cmd_top(args: &TopArgs, out: &GlobalArgs) -> Result<(), AppError> {
 }"#;
 
     let vars = json!({"first": args.limit as i64, "order": order});
-    let data: TopData = gql_request(query, vars, out.verbose)?;
+    let data: TopData = gql_request(query, vars, out)?;
     let items = map_posts(data.posts.edges.into_iter().map(|x| x.node).collect());
 
-    if out.json {
-        print_json(&OkList {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
     } else if out.quiet {
         println!("{}", items.len());
     } else {
@@ -324,15 +321,11 @@ fn cmd_search(args: &SearchArgs, out: &GlobalArgs) -> Result<(), AppError> {
 }"#;
 
     let vars = json!({"query": args.topic, "first": args.limit as i64});
-    let data: SearchData = gql_request(query, vars, out.verbose)?;
+    let data: SearchData = gql_request(query, vars, out)?;
     let items = map_posts(data.posts.edges.into_iter().map(|x| x.node).collect());
 
-    if out.json {
-        print_json(&OkList {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
     } else if out.quiet {
         println!("{}", items.len());
     } else {
@@ -358,7 +351,7 @@ fn cmd_show(args: &ShowArgs, out: &GlobalArgs) -> Result<(), AppError> {
 }"#;
 
     let vars = json!({"slug": args.product_slug});
-    let data: ShowData = gql_request(query, vars, out.verbose)?;
+    let data: ShowData = gql_request(query, vars, out)?;
     let post = data.post.ok_or(AppError::NotFound)?;
     let item = map_post(post);
 
@@ -390,12 +383,17 @@ fn cmd_show(args: &ShowArgs, out: &GlobalArgs) -> Result<(), AppError> {
 fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
     match &args.command {
         ConfigCommand::Set(input) => {
-            let mut cfg = load_config().unwrap_or_default();
+            let mut cfg = config_file().load_file()?;
             match input.key.as_str() {
                 "ph.api-key" | "api_key" => cfg.api_key = Some(input.value.clone()),
+                "ph.base-url" | "base_url" => cfg.base_url = Some(input.value.clone()),
+                "secrets_backend" => {
+                    cfg.secrets_backend =
+                        Some(input.value.parse().map_err(AppError::InvalidArgument)?)
+                }
                 other => return Err(AppError::InvalidConfigKey(other.to_string())),
             }
-            save_config(&cfg).map_err(|_| AppError::ConfigMissing)?;
+            config_file().save(&cfg)?;
             if input.output.json {
                 print_json(&OkMessage {
                     ok: true,
@@ -407,7 +405,7 @@ fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
             Ok(())
         }
         ConfigCommand::Show(flags) => {
-            let cfg = load_config().unwrap_or_default();
+            let cfg = config_file().load()?;
             if flags.json {
                 print_json(&OkItem {
                     ok: true,
@@ -416,11 +414,15 @@ fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
             } else {
                 let key_set = cfg.api_key.as_deref().map(|_| "set").unwrap_or("missing");
                 println!("api_key: {key_set}");
+                println!(
+                    "secrets_backend: {}",
+                    cfg.secrets_backend.unwrap_or_default().as_str()
+                );
             }
             Ok(())
         }
         ConfigCommand::Path => {
-            println!("{}", config_path().display());
+            println!("{}", config_file().path().display());
             Ok(())
         }
     }
@@ -429,25 +431,24 @@ fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
 fn gql_request<T: for<'de> Deserialize<'de>>(
     query: &str,
     variables: serde_json::Value,
-    verbose: bool,
+    out: &GlobalArgs,
 ) -> Result<T, AppError> {
-    let cfg = load_config().map_err(|_| AppError::ConfigMissing)?;
+    let cfg = config_file().load()?;
     let token = cfg
         .api_key
         .filter(|x| !x.trim().is_empty())
         .ok_or(AppError::AuthMissing)?;
+    let url = format!("{}/graphql", base_url(cfg.base_url.as_deref(), PH_API_BASE));
 
-    if verbose {
-        eprintln!("debug: POST {PH_API_URL}");
-    }
+    tracing::debug!("POST {url}");
 
-    let client = Client::builder()
-        .user_agent("dee-ph/0.1.0 (https://dee.ink)")
-        .build()
+    let client = Http::new("dee-ph", env!("CARGO_PKG_VERSION"))
+        .args(&out.http)
+        .blocking()
         .map_err(|_| AppError::RequestFailed)?;
 
     let root: GqlRoot<T> = client
-        .post(PH_API_URL)
+        .post(&url)
         .bearer_auth(token)
         .json(&json!({"query": query, "variables": variables}))
         .send()
@@ -481,73 +482,3 @@ fn map_post(node: PostNode) -> ProductItem {
         created_at: node.created_at,
     }
 }
-
-fn config_path() -> PathBuf {
-    let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-    path.push("dee-ph");
-    path.push("config.toml");
-    path
-}
-
-fn load_config() -> Result<AppConfig> {
-    let path = config_path();
-    if !path.exists() {
-        return Ok(AppConfig::default());
-    }
-
-    let content = fs::read_to_string(&path)
-        .with_context(|| format!("failed reading config at {}", path.display()))?;
-    toml::from_str(&content).context("failed parsing config")
-}
-
-fn save_config(cfg: &AppConfig) -> Result<()> {
-    let path = config_path();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    fs::write(&path, toml::to_string_pretty(cfg)?)?;
-    Ok(())
-}
-
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(text) => println!("{text}"),
-        Err(_) => {
-            println!(
-                "{{\"ok\":false,\"error\":\"serialization failed\",\"code\":\"INTERNAL_ERROR\"}}"
-            );
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -0,0 +1,42 @@
+use assert_cmd::Command;
+use dee_test_support::{fixture, json, MockApi, Sandbox};
+
+fn bin(sandbox: &Sandbox, api: &MockApi) -> Command {
+    let mut cmd = sandbox.command(assert_cmd::cargo::cargo_bin!("dee-ph"));
+    cmd.env("DEE_PH_BASE_URL", api.url("/v2/api"))
+        .env("DEE_PH_API_KEY", "ph-test");
+    cmd
+}
+
+#[test]
+fn top_posts_the_query_with_the_token() {
+    let api = MockApi::start();
+    api.post_json("/v2/api/graphql", &fixture("ph/top.json"));
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["top", "--limit", "2", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][0]["slug"], "linear-4");
+    assert_eq!(parsed["items"][0]["votes_count"], 812);
+
+    let requests = api.requests();
+    assert_eq!(
+        requests[0].headers.get("authorization").unwrap(),
+        "Bearer ph-test"
+    );
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["variables"]["first"], 2);
+    assert_eq!(body["variables"]["order"], "VOTES");
+}
+
+#[test]
+fn graphql_errors_fail_the_command() {
+    let api = MockApi::start();
+    api.post_json("/v2/api/graphql", &fixture("ph/errors.json"));
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["top", "--json"]));
+    assert_ne!(code, Some(0));
+    assert_eq!(parsed["code"], "API_ERROR");
+}
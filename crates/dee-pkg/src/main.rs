@@ -0,0 +1,466 @@
+mod registries;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::cache::{Cache, CacheArgs, CacheCommand, CacheError};
+use dee_core::http::HttpArgs;
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, report, Config, ConfigError, ErrorCode, Format, OkItem, OkMessage,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "dee-pkg",
+    version,
+    about = "Package lookup across crates.io, npm and PyPI",
+    after_help = "EXAMPLES:\n  dee-pkg show serde --registry crates --json\n  dee-pkg show react --registry npm\n  dee-pkg show requests --registry pypi --json\n  dee-pkg versions tokio --limit 5 --json\n  dee-pkg deps express --registry npm --json\n  dee-pkg deps flask --registry pypi --version 3.0.0\n  dee-pkg search \"http client\" --registry crates --limit 5\n  dee-pkg search left-pad --registry npm --output table\n  dee-pkg config set registry npm"
+)]
+struct Cli {
+    #[command(flatten)]
+    global: GlobalArgs,
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, Clone, Args)]
+pub(crate) struct GlobalArgs {
+    #[arg(short = 'j', long, global = true)]
+    json: bool,
+    #[arg(short = 'q', long, global = true)]
+    quiet: bool,
+    #[arg(short = 'v', long, global = true)]
+    pub(crate) verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    /// Registry to query (defaults to config `registry`, then crates)
+    #[arg(short = 'r', long, global = true, value_enum)]
+    registry: Option<Registry>,
+    #[command(flatten)]
+    pub(crate) http: HttpArgs,
+    #[command(flatten)]
+    pub(crate) cache: CacheArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// Package metadata at its latest stable version
+    Show(PackageArgs),
+    /// Published versions, newest first
+    Versions(VersionsArgs),
+    /// Dependencies of one version (default: the latest)
+    Deps(DepsArgs),
+    /// Search a registry by keyword (crates and npm)
+    Search(SearchArgs),
+    Config(ConfigArgs),
+    /// Manage cached registry responses
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Registry {
+    /// crates.io (Rust)
+    #[default]
+    #[value(alias = "crates.io", alias = "cargo")]
+    Crates,
+    /// registry.npmjs.org (JavaScript)
+    Npm,
+    /// pypi.org (Python)
+    #[value(alias = "pip")]
+    Pypi,
+}
+
+impl Registry {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Crates => "crates",
+            Self::Npm => "npm",
+            Self::Pypi => "pypi",
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct PackageArgs {
+    /// Package name, e.g. serde, @types/node, requests
+    name: String,
+}
+
+#[derive(Debug, Args)]
+struct VersionsArgs {
+    name: String,
+    /// Most versions to list (1-1000)
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+}
+
+#[derive(Debug, Args)]
+struct DepsArgs {
+    name: String,
+    /// Exact version (default: the latest stable)
+    #[arg(long = "version", value_name = "VERSION")]
+    pkg_version: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct SearchArgs {
+    query: String,
+    /// Most results to return (1-100)
+    #[arg(long, default_value_t = 10)]
+    limit: usize,
+}
+
+#[derive(Debug, Args)]
+struct ConfigArgs {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    /// Set a key: registry
+    Set(ConfigSetArgs),
+    Show(ShowFlags),
+    Path,
+}
+
+#[derive(Debug, Args)]
+struct ConfigSetArgs {
+    key: String,
+    value: String,
+    #[command(flatten)]
+    output: ShowFlags,
+}
+
+#[derive(Debug, Args)]
+struct ShowFlags {
+    #[arg(short = 'j', long)]
+    json: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
+struct AppConfig {
+    /// Registry when `--registry` is not given
+    #[serde(default)]
+    registry: Option<Registry>,
+}
+
+/// One package, the same shape for every registry and for `show` and `search`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct Package {
+    pub(crate) registry: Registry,
+    pub(crate) name: String,
+    /// Latest stable version
+    pub(crate) version: String,
+    pub(crate) description: Option<String>,
+    /// SPDX expression where the registry has one
+    pub(crate) license: Option<String>,
+    pub(crate) homepage: Option<String>,
+    pub(crate) repository: Option<String>,
+    /// All-time downloads; crates.io only
+    pub(crate) downloads: Option<u64>,
+    /// When `version` was published; npm search gives the last publish
+    pub(crate) published_at: Option<String>,
+    /// The package's page on the registry website
+    pub(crate) url: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct Version {
+    pub(crate) registry: Registry,
+    pub(crate) name: String,
+    pub(crate) version: String,
+    pub(crate) published_at: Option<String>,
+    /// Yanked on crates.io or PyPI, deprecated on npm
+    pub(crate) yanked: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct Dependency {
+    pub(crate) registry: Registry,
+    /// The package depending on `name`
+    pub(crate) package: String,
+    pub(crate) version: String,
+    /// The dependency
+    pub(crate) name: String,
+    /// Version requirement as written, e.g. `^1.0`; empty for none
+    pub(crate) requirement: String,
+    /// `normal`, `dev`, `build` (crates) or `peer` (npm)
+    pub(crate) kind: String,
+    /// Optional feature (crates), optional dependency (npm) or extra (PyPI)
+    pub(crate) optional: bool,
+}
+
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-pkg").env_overrides(&["registry"])
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum AppError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+    #[error("Unknown config key: {0} (expected registry)")]
+    InvalidConfigKey(String),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("Package '{name}' not found on {}", .registry.as_str())]
+    NotFound { registry: Registry, name: String },
+    #[error("{} rate limit reached; try again shortly", .0.as_str())]
+    RateLimited(Registry),
+    #[error("HTTP request failed")]
+    RequestFailed,
+    #[error("HTTP request returned status {0}")]
+    HttpStatus(u16),
+    #[error("Response parse failed")]
+    ParseFailed,
+}
+
+impl ErrorCode for AppError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Config(err) => err.code(),
+            Self::Cache(err) => err.code(),
+            Self::InvalidConfigKey(_) | Self::InvalidArgument(_) => "INVALID_ARGUMENT",
+            Self::NotFound { .. } => "NOT_FOUND",
+            Self::RateLimited(_) => "RATE_LIMITED",
+            Self::RequestFailed => "REQUEST_FAILED",
+            Self::HttpStatus(_) => "HTTP_STATUS",
+            Self::ParseFailed => "PARSE_FAILED",
+        }
+    }
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .item::<Package>("show")
+        .list::<Version>("versions")
+        .list::<Dependency>("deps")
+        .list::<Package>("search")
+        .message("config set")
+        .item::<AppConfig>("config show")
+        .cache()
+}
+
+fn main() {
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
+
+    if let Err(err) = dispatch(&cli) {
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
+    }
+}
+
+fn dispatch(cli: &Cli) -> Result<(), AppError> {
+    match &cli.command {
+        Commands::Config(args) => return cmd_config(args),
+        Commands::Cache { command } => {
+            return Ok(dee_core::cache::run(
+                &Cache::new("dee-pkg"),
+                *command,
+                cli.global.json,
+            )?)
+        }
+        _ => {}
+    }
+
+    let config = config_file().load()?;
+    let registry = cli.global.registry.or(config.registry).unwrap_or_default();
+    match &cli.command {
+        Commands::Show(args) => cmd_show(registry, args, &cli.global),
+        Commands::Versions(args) => cmd_versions(registry, args, &cli.global),
+        Commands::Deps(args) => cmd_deps(registry, args, &cli.global),
+        Commands::Search(args) => cmd_search(registry, args, &cli.global),
+        Commands::Config(_) | Commands::Cache { .. } => unreachable!("handled above"),
+    }
+}
+
+fn cmd_show(registry: Registry, args: &PackageArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    let item = registries::show(registry, package_name(&args.name)?, out)?;
+
+    if out.json {
+        print_json(&OkItem { ok: true, item });
+    } else if out.quiet {
+        println!("{} {}", item.name, item.version);
+    } else {
+        println!("{} {}  ({})", item.name, item.version, registry.as_str());
+        if let Some(description) = &item.description {
+            println!("  {description}");
+        }
+        for (label, value) in [
+            ("license", &item.license),
+            ("homepage", &item.homepage),
+            ("repository", &item.repository),
+            ("published", &item.published_at),
+        ] {
+            if let Some(value) = value {
+                println!("  {label}: {value}");
+            }
+        }
+        if let Some(downloads) = item.downloads {
+            println!("  downloads: {downloads}");
+        }
+        println!("  {}", item.url);
+    }
+
+    Ok(())
+}
+
+fn cmd_versions(registry: Registry, args: &VersionsArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    if !(1..=1000).contains(&args.limit) {
+        return Err(AppError::InvalidArgument(
+            "--limit must be between 1 and 1000".to_string(),
+        ));
+    }
+    let mut items = registries::versions(registry, package_name(&args.name)?, out)?;
+    items.truncate(args.limit);
+
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
+    } else {
+        for item in items {
+            let date = item.published_at.as_deref().unwrap_or("-");
+            let date = date.get(..10).unwrap_or(date);
+            if out.quiet {
+                println!("{}", item.version);
+            } else if item.yanked {
+                println!("{:<24} {date}  yanked", item.version);
+            } else {
+                println!("{:<24} {date}", item.version);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_deps(registry: Registry, args: &DepsArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    let version = args
+        .pkg_version
+        .as_deref()
+        .map(str::trim)
+        .filter(|version| !version.is_empty());
+    let items = registries::deps(registry, package_name(&args.name)?, version, out)?;
+
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
+    } else if out.quiet {
+        for item in items {
+            println!("{}", item.name);
+        }
+    } else {
+        for item in items {
+            let mut notes = Vec::new();
+            if item.kind != "normal" {
+                notes.push(item.kind.as_str());
+            }
+            if item.optional {
+                notes.push("optional");
+            }
+            let notes = if notes.is_empty() {
+                String::new()
+            } else {
+                format!("  ({})", notes.join(", "))
+            };
+            println!("{:<32} {}{notes}", item.name, item.requirement);
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_search(registry: Registry, args: &SearchArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    if !(1..=100).contains(&args.limit) {
+        return Err(AppError::InvalidArgument(
+            "--limit must be between 1 and 100".to_string(),
+        ));
+    }
+    let query = args.query.trim();
+    if query.is_empty() {
+        return Err(AppError::InvalidArgument(
+            "query must not be empty".to_string(),
+        ));
+    }
+    let items = registries::search(registry, query, args.limit, out)?;
+
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
+    } else {
+        for item in items {
+            println!("{} {}", item.name, item.version);
+            if let (false, Some(description)) = (out.quiet, &item.description) {
+                println!("  {description}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
+    match &args.command {
+        ConfigCommand::Set(input) => {
+            let mut cfg = config_file().load_file()?;
+            match input.key.as_str() {
+                "registry" => {
+                    cfg.registry = Some(
+                        Registry::from_str(&input.value, true)
+                            .map_err(AppError::InvalidArgument)?,
+                    )
+                }
+                other => return Err(AppError::InvalidConfigKey(other.to_string())),
+            }
+            config_file().save(&cfg)?;
+
+            if input.output.json {
+                print_json(&OkMessage {
+                    ok: true,
+                    message: "Config updated".to_string(),
+                });
+            } else {
+                println!("Config updated");
+            }
+            Ok(())
+        }
+        ConfigCommand::Show(flags) => {
+            let cfg = config_file().load()?;
+            if flags.json {
+                print_json(&OkItem {
+                    ok: true,
+                    item: cfg,
+                });
+            } else {
+                println!("registry: {}", cfg.registry.unwrap_or_default().as_str());
+            }
+            Ok(())
+        }
+        ConfigCommand::Path => {
+            println!("{}", config_file().path().display());
+            Ok(())
+        }
+    }
+}
+
+fn package_name(name: &str) -> Result<&str, AppError> {
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(AppError::InvalidArgument(format!(
+            "not a package name: '{name}'"
+        )));
+    }
+    Ok(name)
+}
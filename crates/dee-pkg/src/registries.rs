@@ -0,0 +1,722 @@
+//! Registry APIs, each answer normalized into the shared item shapes.
+
+use std::collections::BTreeMap;
+
+use dee_core::cache::Cache;
+use dee_core::http::Http;
+use dee_core::ratelimit;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::{AppError, Dependency, GlobalArgs, Package, Registry, Version};
+
+const CRATES_BASE: &str = "https://crates.io";
+const NPM_BASE: &str = "https://registry.npmjs.org";
+const PYPI_BASE: &str = "https://pypi.org";
+/// Published metadata rarely changes within the hour
+const CACHE_TTL_SECS: u64 = 60 * 60;
+
+#[derive(Debug, Deserialize)]
+struct CrateResponse {
+    #[serde(rename = "crate")]
+    krate: CrateDetails,
+    #[serde(default)]
+    versions: Vec<CrateVersion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateDetails {
+    name: String,
+    #[serde(default)]
+    max_version: String,
+    #[serde(default)]
+    max_stable_version: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    homepage: Option<String>,
+    #[serde(default)]
+    repository: Option<String>,
+    #[serde(default)]
+    downloads: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateVersion {
+    num: String,
+    #[serde(default)]
+    yanked: bool,
+    #[serde(default)]
+    license: Option<String>,
+    #[serde(default)]
+    created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateSearch {
+    #[serde(default)]
+    crates: Vec<CrateDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateDependencies {
+    #[serde(default)]
+    dependencies: Vec<CrateDependency>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateDependency {
+    crate_id: String,
+    #[serde(default)]
+    req: String,
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    optional: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct NpmPackument {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default, rename = "dist-tags")]
+    dist_tags: BTreeMap<String, String>,
+    #[serde(default)]
+    versions: BTreeMap<String, NpmVersion>,
+    /// Publish time per version, plus `created` and `modified`
+    #[serde(default)]
+    time: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NpmVersion {
+    #[serde(default)]
+    description: Option<String>,
+    /// A string, or `{ "type": "MIT" }` in old packages
+    #[serde(default)]
+    license: Option<serde_json::Value>,
+    #[serde(default)]
+    homepage: Option<String>,
+    /// A string, or `{ "type": "git", "url": "..." }`
+    #[serde(default)]
+    repository: Option<serde_json::Value>,
+    #[serde(default)]
+    deprecated: Option<serde_json::Value>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, String>,
+    #[serde(default)]
+    dev_dependencies: BTreeMap<String, String>,
+    #[serde(default)]
+    peer_dependencies: BTreeMap<String, String>,
+    #[serde(default)]
+    optional_dependencies: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NpmSearch {
+    #[serde(default)]
+    objects: Vec<NpmSearchObject>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NpmSearchObject {
+    package: NpmSearchPackage,
+}
+
+#[derive(Debug, Deserialize)]
+struct NpmSearchPackage {
+    name: String,
+    version: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    license: Option<String>,
+    #[serde(default)]
+    date: Option<String>,
+    #[serde(default)]
+    links: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PypiProject {
+    info: PypiInfo,
+    /// Files of every release; only in the unversioned answer
+    #[serde(default)]
+    releases: BTreeMap<String, Vec<PypiFile>>,
+    /// Files of this release
+    #[serde(default)]
+    urls: Vec<PypiFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PypiInfo {
+    name: String,
+    version: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    license: Option<String>,
+    #[serde(default)]
+    license_expression: Option<String>,
+    #[serde(default)]
+    home_page: Option<String>,
+    #[serde(default)]
+    project_urls: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    requires_dist: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PypiFile {
+    #[serde(default)]
+    upload_time_iso_8601: Option<String>,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// `PKG_TEST_BASE_URL` replaces every registry's host, for tests.
+fn base(default: &str) -> String {
+    std::env::var("PKG_TEST_BASE_URL").unwrap_or_else(|_| default.to_string())
+}
+
+pub fn show(registry: Registry, name: &str, out: &GlobalArgs) -> Result<Package, AppError> {
+    match registry {
+        Registry::Crates => {
+            let response = crate_response(name, out)?;
+            let version = crate_latest(&response.krate);
+            let published = response.versions.iter().find(|v| v.num == version);
+            Ok(crate_package(
+                response.krate,
+                version.clone(),
+                published.and_then(|v| v.license.clone()),
+                published.and_then(|v| v.created_at.clone()),
+            ))
+        }
+        Registry::Npm => {
+            let packument = npm_packument(name, out)?;
+            let version = npm_latest(&packument, name)?;
+            let meta = &packument.versions[&version];
+            Ok(Package {
+                registry,
+                url: format!("https://www.npmjs.com/package/{}", packument.name),
+                description: meta
+                    .description
+                    .clone()
+                    .or_else(|| packument.description.clone())
+                    .filter(|x| !x.is_empty()),
+                license: meta.license.as_ref().and_then(npm_license),
+                homepage: meta.homepage.clone().filter(|x| !x.is_empty()),
+                repository: meta.repository.as_ref().and_then(npm_repository),
+                downloads: None,
+                published_at: packument.time.get(&version).cloned(),
+                name: packument.name,
+                version,
+            })
+        }
+        Registry::Pypi => {
+            let project = pypi_project(name, None, out)?;
+            let published_at = earliest_upload(&project.urls);
+            let info = project.info;
+            let repository = pypi_url(
+                &info.project_urls,
+                &["source", "source code", "repository", "code", "github"],
+            );
+            Ok(Package {
+                registry,
+                url: format!("https://pypi.org/project/{}/", info.name),
+                description: info.summary.filter(|x| !x.is_empty()),
+                license: pypi_license(info.license_expression.as_deref())
+                    .or_else(|| pypi_license(info.license.as_deref())),
+                homepage: info
+                    .home_page
+                    .clone()
+                    .filter(|x| !x.is_empty())
+                    .or_else(|| pypi_url(&info.project_urls, &["homepage", "home"])),
+                repository,
+                downloads: None,
+                published_at,
+                name: info.name,
+                version: info.version,
+            })
+        }
+    }
+}
+
+/// Every published version, newest first.
+pub fn versions(
+    registry: Registry,
+    name: &str,
+    out: &GlobalArgs,
+) -> Result<Vec<Version>, AppError> {
+    let mut items: Vec<Version> = match registry {
+        Registry::Crates => {
+            let response = crate_response(name, out)?;
+            response
+                .versions
+                .into_iter()
+                .map(|v| Version {
+                    registry,
+                    name: response.krate.name.clone(),
+                    version: v.num,
+                    published_at: v.created_at,
+                    yanked: v.yanked,
+                })
+                .collect()
+        }
+        Registry::Npm => {
+            let packument = npm_packument(name, out)?;
+            packument
+                .versions
+                .iter()
+                .map(|(version, meta)| Version {
+                    registry,
+                    name: packument.name.clone(),
+                    version: version.clone(),
+                    published_at: packument.time.get(version).cloned(),
+                    yanked: meta.deprecated.is_some(),
+                })
+                .collect()
+        }
+        Registry::Pypi => {
+            let project = pypi_project(name, None, out)?;
+            project
+                .releases
+                .iter()
+                // A release with no files was never installable
+                .filter(|(_, files)| !files.is_empty())
+                .map(|(version, files)| Version {
+                    registry,
+                    name: project.info.name.clone(),
+                    version: version.clone(),
+                    published_at: earliest_upload(files),
+                    yanked: files.iter().all(|file| file.yanked),
+                })
+                .collect()
+        }
+    };
+    // RFC 3339 timestamps sort as text; undated versions go last
+    items.sort_by(|a, b| b.published_at.cmp(&a.published_at));
+    Ok(items)
+}
+
+/// What `version` (default: the latest stable) depends on, in the registry's order.
+pub fn deps(
+    registry: Registry,
+    name: &str,
+    version: Option<&str>,
+    out: &GlobalArgs,
+) -> Result<Vec<Dependency>, AppError> {
+    match registry {
+        Registry::Crates => {
+            let response = crate_response(name, out)?;
+            let version = match version {
+                Some(version) => version.to_string(),
+                None => crate_latest(&response.krate),
+            };
+            let package = response.krate.name;
+            let url = format!(
+                "{}/api/v1/crates/{}/{}/dependencies",
+                base(CRATES_BASE),
+                urlencoding::encode(&package),
+                urlencoding::encode(&version)
+            );
+            let not_found = format!("{package}@{version}");
+            let body: CrateDependencies = fetch(registry, &url, &not_found, out)?;
+            Ok(body
+                .dependencies
+                .into_iter()
+                .map(|dep| Dependency {
+                    registry,
+                    package: package.clone(),
+                    version: version.clone(),
+                    name: dep.crate_id,
+                    requirement: dep.req,
+                    kind: dep.kind.unwrap_or_else(|| "normal".to_string()),
+                    optional: dep.optional,
+                })
+                .collect())
+        }
+        Registry::Npm => {
+            let packument = npm_packument(name, out)?;
+            let version = match version {
+                Some(version) => version.to_string(),
+                None => npm_latest(&packument, name)?,
+            };
+            let meta = packument
+                .versions
+                .get(&version)
+                .ok_or_else(|| AppError::NotFound {
+                    registry,
+                    name: format!("{}@{version}", packument.name),
+                })?;
+            let groups = [
+                (&meta.dependencies, "normal", false),
+                (&meta.optional_dependencies, "normal", true),
+                (&meta.peer_dependencies, "peer", false),
+                (&meta.dev_dependencies, "dev", false),
+            ];
+            let mut items = Vec::new();
+            for (group, kind, optional) in groups {
+                for (dep, requirement) in group {
+                    // npm also lists optional dependencies under `dependencies`
+                    if !optional && kind == "normal" && meta.optional_dependencies.contains_key(dep)
+                    {
+                        continue;
+                    }
+                    items.push(Dependency {
+                        registry,
+                        package: packument.name.clone(),
+                        version: version.clone(),
+                        name: dep.clone(),
+                        requirement: requirement.clone(),
+                        kind: kind.to_string(),
+                        optional,
+                    });
+                }
+            }
+            Ok(items)
+        }
+        Registry::Pypi => {
+            let project = pypi_project(name, version, out)?;
+            let info = project.info;
+            Ok(info
+                .requires_dist
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|spec| parse_requirement(spec))
+                .map(|(dep, requirement, optional)| Dependency {
+                    registry,
+                    package: info.name.clone(),
+                    version: info.version.clone(),
+                    name: dep,
+                    requirement,
+                    kind: "normal".to_string(),
+                    optional,
+                })
+                .collect())
+        }
+    }
+}
+
+pub fn search(
+    registry: Registry,
+    query: &str,
+    limit: usize,
+    out: &GlobalArgs,
+) -> Result<Vec<Package>, AppError> {
+    match registry {
+        Registry::Crates => {
+            let url = format!(
+                "{}/api/v1/crates?q={}&per_page={limit}",
+                base(CRATES_BASE),
+                urlencoding::encode(query)
+            );
+            let body: CrateSearch = fetch(registry, &url, query, out)?;
+            Ok(body
+                .crates
+                .into_iter()
+                .map(|krate| {
+                    let version = crate_latest(&krate);
+                    crate_package(krate, version, None, None)
+                })
+                .collect())
+        }
+        Registry::Npm => {
+            let url = format!(
+                "{}/-/v1/search?text={}&size={limit}",
+                base(NPM_BASE),
+                urlencoding::encode(query)
+            );
+            let body: NpmSearch = fetch(registry, &url, query, out)?;
+            Ok(body
+                .objects
+                .into_iter()
+                .map(|object| {
+                    let package = object.package;
+                    let link = |key: &str| package.links.get(key).cloned();
+                    Package {
+                        registry,
+                        url: link("npm").unwrap_or_else(|| {
+                            format!("https://www.npmjs.com/package/{}", package.name)
+                        }),
+                        homepage: link("homepage"),
+                        repository: link("repository").map(|url| clean_repository(&url)),
+                        name: package.name,
+                        version: package.version,
+                        description: package.description.filter(|x| !x.is_empty()),
+                        license: package.license,
+                        downloads: None,
+                        published_at: package.date,
+                    }
+                })
+                .collect())
+        }
+        Registry::Pypi => Err(AppError::InvalidArgument(
+            "PyPI has no search API; look a package up by its exact name with `show`".to_string(),
+        )),
+    }
+}
+
+fn crate_response(name: &str, out: &GlobalArgs) -> Result<CrateResponse, AppError> {
+    let url = format!(
+        "{}/api/v1/crates/{}",
+        base(CRATES_BASE),
+        urlencoding::encode(name)
+    );
+    fetch(Registry::Crates, &url, name, out)
+}
+
+/// The highest stable version, else the highest at all (crates with only
+/// pre-releases).
+fn crate_latest(krate: &CrateDetails) -> String {
+    krate
+        .max_stable_version
+        .clone()
+        .filter(|version| !version.is_empty())
+        .unwrap_or_else(|| krate.max_version.clone())
+}
+
+fn crate_package(
+    krate: CrateDetails,
+    version: String,
+    license: Option<String>,
+    published_at: Option<String>,
+) -> Package {
+    Package {
+        registry: Registry::Crates,
+        url: format!("https://crates.io/crates/{}", krate.name),
+        name: krate.name,
+        version,
+        description: krate
+            .description
+            .map(|x| x.trim().to_string())
+            .filter(|x| !x.is_empty()),
+        license,
+        homepage: krate.homepage.filter(|x| !x.is_empty()),
+        repository: krate.repository.filter(|x| !x.is_empty()),
+        downloads: krate.downloads,
+        published_at,
+    }
+}
+
+fn npm_packument(name: &str, out: &GlobalArgs) -> Result<NpmPackument, AppError> {
+    // Scoped names keep the `@` but escape the slash: `@types%2Fnode`
+    let url = format!("{}/{}", base(NPM_BASE), name.replace('/', "%2F"));
+    fetch(Registry::Npm, &url, name, out)
+}
+
+fn npm_latest(packument: &NpmPackument, name: &str) -> Result<String, AppError> {
+    packument
+        .dist_tags
+        .get("latest")
+        .filter(|version| packument.versions.contains_key(*version))
+        .cloned()
+        .ok_or_else(|| AppError::NotFound {
+            registry: Registry::Npm,
+            name: name.to_string(),
+        })
+}
+
+fn npm_license(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(license) => Some(license.clone()),
+        serde_json::Value::Object(map) => map.get("type")?.as_str().map(str::to_string),
+        _ => None,
+    }
+    .filter(|license| !license.is_empty())
+}
+
+fn npm_repository(value: &serde_json::Value) -> Option<String> {
+    let url = match value {
+        serde_json::Value::String(url) => url.as_str(),
+        serde_json::Value::Object(map) => map.get("url")?.as_str()?,
+        _ => return None,
+    };
+    (!url.is_empty()).then(|| clean_repository(url))
+}
+
+/// `git+https://github.com/a/b.git` as `https://github.com/a/b`; `github:a/b`
+/// shorthands as GitHub URLs.
+fn clean_repository(url: &str) -> String {
+    let url = url.trim();
+    let url = url.strip_prefix("git+").unwrap_or(url);
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    if let Some(rest) = url.strip_prefix("git://") {
+        return format!("https://{rest}");
+    }
+    if let Some(rest) = url.strip_prefix("git@github.com:") {
+        return format!("https://github.com/{rest}");
+    }
+    if let Some(rest) = url.strip_prefix("github:") {
+        return format!("https://github.com/{rest}");
+    }
+    url.to_string()
+}
+
+fn pypi_project(
+    name: &str,
+    version: Option<&str>,
+    out: &GlobalArgs,
+) -> Result<PypiProject, AppError> {
+    let name_part = urlencoding::encode(name);
+    let (url, not_found) = match version {
+        Some(version) => (
+            format!(
+                "{}/pypi/{name_part}/{}/json",
+                base(PYPI_BASE),
+                urlencoding::encode(version)
+            ),
+            format!("{name}=={version}"),
+        ),
+        None => (
+            format!("{}/pypi/{name_part}/json", base(PYPI_BASE)),
+            name.to_string(),
+        ),
+    };
+    fetch(Registry::Pypi, &url, &not_found, out)
+}
+
+/// Older packages put the whole license text in `license`; keep only short
+/// identifiers.
+fn pypi_license(license: Option<&str>) -> Option<String> {
+    license
+        .map(str::trim)
+        .filter(|x| !x.is_empty() && x.len() <= 64 && !x.contains('\n'))
+        .map(str::to_string)
+}
+
+/// The first project URL whose label is one of `labels`, ignoring case.
+fn pypi_url(urls: &Option<BTreeMap<String, String>>, labels: &[&str]) -> Option<String> {
+    let urls = urls.as_ref()?;
+    labels.iter().find_map(|label| {
+        urls.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(label))
+            .map(|(_, url)| url.clone())
+    })
+}
+
+fn earliest_upload(files: &[PypiFile]) -> Option<String> {
+    files
+        .iter()
+        .filter_map(|file| file.upload_time_iso_8601.clone())
+        .min()
+}
+
+/// A PEP 508 requirement such as `urllib3 (<3,>=1.21.1)` or
+/// `pytest>=7; extra == "test"`, as (name, requirement, only with an extra).
+fn parse_requirement(spec: &str) -> Option<(String, String, bool)> {
+    let (requirement, marker) = spec.split_once(';').unwrap_or((spec, ""));
+    let requirement = requirement.trim();
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    let name = &requirement[..end];
+    if name.is_empty() {
+        return None;
+    }
+    let mut rest = requirement[end..].trim();
+    // Extras of the dependency itself: `requests[socks]>=2`
+    if rest.starts_with('[') {
+        rest = rest.split_once(']').map_or("", |(_, rest)| rest).trim();
+    }
+    let rest = rest
+        .strip_prefix('(')
+        .and_then(|rest| rest.strip_suffix(')'))
+        .unwrap_or(rest)
+        .trim();
+    Some((name.to_string(), rest.to_string(), marker.contains("extra")))
+}
+
+/// The JSON at `url` as `T`, from the cache when fresh. A 404 is `NotFound`
+/// for `name`.
+fn fetch<T: DeserializeOwned>(
+    registry: Registry,
+    url: &str,
+    name: &str,
+    out: &GlobalArgs,
+) -> Result<T, AppError> {
+    let cache = Cache::new("dee-pkg").ttl_secs(CACHE_TTL_SECS);
+    if let Some(hit) = cache.lookup::<serde_json::Value>(url, &out.cache)? {
+        tracing::debug!("cached {url} ({}s old)", hit.age_secs);
+        return serde_json::from_value(hit.value).map_err(|_| AppError::ParseFailed);
+    }
+
+    let wait = ratelimit::reserve(url)?;
+    if !wait.is_zero() {
+        tracing::debug!(
+            "waiting {:.1}s before calling {}",
+            wait.as_secs_f64(),
+            registry.as_str()
+        );
+        std::thread::sleep(wait);
+    }
+    tracing::debug!("GET {url}");
+
+    let client = Http::new("dee-pkg", env!("CARGO_PKG_VERSION"))
+        .args(&out.http)
+        .blocking()
+        .map_err(|_| AppError::RequestFailed)?;
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|_| AppError::RequestFailed)?;
+    match response.status() {
+        StatusCode::NOT_FOUND => {
+            return Err(AppError::NotFound {
+                registry,
+                name: name.to_string(),
+            })
+        }
+        StatusCode::TOO_MANY_REQUESTS => return Err(AppError::RateLimited(registry)),
+        status if !status.is_success() => return Err(AppError::HttpStatus(status.as_u16())),
+        _ => {}
+    }
+    let raw: serde_json::Value = response.json().map_err(|_| AppError::ParseFailed)?;
+    let value = serde_json::from_value(raw.clone()).map_err(|_| AppError::ParseFailed)?;
+    cache.put(url, &raw);
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pep_508_requirements() {
+        assert_eq!(
+            parse_requirement("urllib3 (<3,>=1.21.1)"),
+            Some(("urllib3".to_string(), "<3,>=1.21.1".to_string(), false))
+        );
+        assert_eq!(
+            parse_requirement("PySocks!=1.5.7,>=1.5.6; extra == \"socks\""),
+            Some(("PySocks".to_string(), "!=1.5.7,>=1.5.6".to_string(), true))
+        );
+        assert_eq!(
+            parse_requirement("requests[socks]>=2"),
+            Some(("requests".to_string(), ">=2".to_string(), false))
+        );
+        assert_eq!(
+            parse_requirement("idna"),
+            Some(("idna".to_string(), String::new(), false))
+        );
+    }
+
+    #[test]
+    fn cleans_repository_urls() {
+        assert_eq!(
+            clean_repository("git+https://github.com/expressjs/express.git"),
+            "https://github.com/expressjs/express"
+        );
+        assert_eq!(
+            clean_repository("github:facebook/react"),
+            "https://github.com/facebook/react"
+        );
+        assert_eq!(
+            clean_repository("git://github.com/a/b.git"),
+            "https://github.com/a/b"
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use assert_cmd::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+
+fn bin(home: &Path, url: &str) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-pkg"));
+    cmd.env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("DEE_RATELIMIT_DIR", home.join("ratelimit"))
+        .env("PKG_TEST_BASE_URL", url)
+        .env_remove("DEE_PKG_REGISTRY");
+    cmd
+}
+
+const CRATE: &str = r#"{
+  "crate": {"name": "serde", "max_version": "2.0.0-rc.1", "max_stable_version": "1.0.228",
+            "description": "A serialization framework\n", "homepage": "https://serde.rs",
+            "repository": "https://github.com/serde-rs/serde", "downloads": 700000000},
+  "versions": [
+    {"num": "2.0.0-rc.1", "yanked": false, "license": "MIT OR Apache-2.0", "created_at": "2026-09-01T00:00:00Z"},
+    {"num": "1.0.228", "yanked": false, "license": "MIT OR Apache-2.0", "created_at": "2026-08-01T00:00:00Z"},
+    {"num": "1.0.227", "yanked": true, "license": "MIT OR Apache-2.0", "created_at": "2026-07-01T00:00:00Z"}
+  ]
+}"#;
+
+const CRATE_DEPS: &str = r#"{"dependencies": [
+  {"crate_id": "serde_derive", "req": "=1.0.228", "kind": "normal", "optional": true},
+  {"crate_id": "serde_json", "req": "^1.0", "kind": "dev", "optional": false}
+]}"#;
+
+const NPM: &str = r#"{
+  "name": "left-pad",
+  "dist-tags": {"latest": "1.3.0"},
+  "versions": {
+    "1.2.0": {"license": "WTFPL"},
+    "1.3.0": {"description": "String left pad", "license": "WTFPL",
+              "repository": {"type": "git", "url": "git+https://github.com/stevemao/left-pad.git"},
+              "deprecated": "use String.prototype.padStart()",
+              "dependencies": {"a": "^1.0.0", "b": "~2.0.0"},
+              "optionalDependencies": {"b": "~2.0.0"},
+              "peerDependencies": {"c": ">=3"}}
+  },
+  "time": {"created": "2016-01-01T00:00:00.000Z", "1.2.0": "2017-11-01T00:00:00.000Z", "1.3.0": "2018-04-09T00:00:00.000Z"}
+}"#;
+
+const PYPI: &str = r#"{
+  "info": {"name": "requests", "version": "2.32.5", "summary": "Python HTTP for Humans.",
+           "license": "Apache-2.0", "home_page": "",
+           "project_urls": {"Homepage": "https://requests.readthedocs.io", "Source": "https://github.com/psf/requests"},
+           "requires_dist": ["charset_normalizer<4,>=2", "idna (<4,>=2.5)", "PySocks!=1.5.7,>=1.5.6; extra == \"socks\""]},
+  "releases": {
+    "2.32.4": [{"upload_time_iso_8601": "2025-06-09T00:00:00.000000Z", "yanked": false}],
+    "2.32.5": [{"upload_time_iso_8601": "2025-08-18T00:00:00.000000Z", "yanked": false}],
+    "0.0.1": []
+  },
+  "urls": [{"upload_time_iso_8601": "2025-08-18T00:00:00.000000Z", "yanked": false}]
+}"#;
+
+/// Canned answers from each registry at the paths the real APIs use.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let path = line.split_whitespace().nth(1).unwrap_or("").to_string();
+            let body = match path.as_str() {
+                "/api/v1/crates/serde" => CRATE,
+                "/api/v1/crates/serde/1.0.228/dependencies" => CRATE_DEPS,
+                "/api/v1/crates?q=serde&per_page=2" => {
+                    r#"{"crates":[{"name":"serde","max_version":"1.0.228","max_stable_version":"1.0.228","downloads":700000000}]}"#
+                }
+                "/left-pad" => NPM,
+                "/pypi/requests/json" => PYPI,
+                _ => "",
+            };
+            let status = if body.is_empty() {
+                "404 Not Found"
+            } else {
+                "200 OK"
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}")
+}
+
+fn json(cmd: &mut Command) -> (Option<i32>, serde_json::Value) {
+    let out = cmd.output().unwrap();
+    let parsed = serde_json::from_slice(&out.stdout).expect("valid json on stdout");
+    (out.status.code(), parsed)
+}
+
+#[test]
+fn show_normalizes_every_registry() {
+    let url = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    let (code, parsed) = json(bin(home.path(), &url).args(["show", "serde", "--json"]));
+    assert_eq!(code, Some(0));
+    let item = &parsed["item"];
+    assert_eq!(item["registry"], "crates");
+    assert_eq!(item["version"], "1.0.228");
+    assert_eq!(item["description"], "A serialization framework");
+    assert_eq!(item["license"], "MIT OR Apache-2.0");
+    assert_eq!(item["published_at"], "2026-08-01T00:00:00Z");
+    assert_eq!(item["url"], "https://crates.io/crates/serde");
+
+    let (_, parsed) =
+        json(bin(home.path(), &url).args(["show", "left-pad", "--registry", "npm", "--json"]));
+    let item = &parsed["item"];
+    assert_eq!(item["registry"], "npm");
+    assert_eq!(item["version"], "1.3.0");
+    assert_eq!(item["repository"], "https://github.com/stevemao/left-pad");
+    assert_eq!(item["published_at"], "2018-04-09T00:00:00.000Z");
+
+    let (_, parsed) =
+        json(bin(home.path(), &url).args(["show", "requests", "--registry", "pypi", "--json"]));
+    let item = &parsed["item"];
+    assert_eq!(item["registry"], "pypi");
+    assert_eq!(item["version"], "2.32.5");
+    assert_eq!(item["homepage"], "https://requests.readthedocs.io");
+    assert_eq!(item["repository"], "https://github.com/psf/requests");
+    assert_eq!(item["url"], "https://pypi.org/project/requests/");
+}
+
+#[test]
+fn versions_are_newest_first() {
+    let url = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    let (_, parsed) = json(bin(home.path(), &url).args(["versions", "serde", "--json"]));
+    assert_eq!(parsed["count"], 3);
+    assert_eq!(parsed["items"][0]["version"], "2.0.0-rc.1");
+    assert_eq!(parsed["items"][2]["yanked"], true);
+
+    // Deprecated counts as yanked on npm
+    let (_, parsed) = json(bin(home.path(), &url).args([
+        "versions", "left-pad", "-r", "npm", "--limit", "1", "--json",
+    ]));
+    assert_eq!(parsed["count"], 1);
+    assert_eq!(parsed["items"][0]["version"], "1.3.0");
+    assert_eq!(parsed["items"][0]["yanked"], true);
+
+    // Releases without files are left out
+    let (_, parsed) =
+        json(bin(home.path(), &url).args(["versions", "requests", "-r", "pypi", "--json"]));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][0]["version"], "2.32.5");
+}
+
+#[test]
+fn deps_of_the_latest_version() {
+    let url = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    let (_, parsed) = json(bin(home.path(), &url).args(["deps", "serde", "--json"]));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][0]["name"], "serde_derive");
+    assert_eq!(parsed["items"][0]["version"], "1.0.228");
+    assert_eq!(parsed["items"][0]["optional"], true);
+    assert_eq!(parsed["items"][1]["kind"], "dev");
+
+    let (_, parsed) =
+        json(bin(home.path(), &url).args(["deps", "left-pad", "-r", "npm", "--json"]));
+    let names: Vec<_> = parsed["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|dep| {
+            (
+                dep["name"].as_str().unwrap(),
+                dep["kind"].as_str().unwrap(),
+                dep["optional"].as_bool().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        names,
+        [
+            ("a", "normal", false),
+            ("b", "normal", true),
+            ("c", "peer", false)
+        ]
+    );
+
+    let (code, parsed) = json(bin(home.path(), &url).args([
+        "deps",
+        "left-pad",
+        "-r",
+        "npm",
+        "--version",
+        "9.9.9",
+        "--json",
+    ]));
+    assert_eq!(code, Some(3));
+    assert_eq!(parsed["code"], "NOT_FOUND");
+
+    let (_, parsed) =
+        json(bin(home.path(), &url).args(["deps", "requests", "-r", "pypi", "--json"]));
+    assert_eq!(parsed["count"], 3);
+    assert_eq!(parsed["items"][1]["name"], "idna");
+    assert_eq!(parsed["items"][1]["requirement"], "<4,>=2.5");
+    assert_eq!(parsed["items"][2]["optional"], true);
+}
+
+#[test]
+fn search_and_missing_packages() {
+    let url = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    let (_, parsed) =
+        json(bin(home.path(), &url).args(["search", "serde", "--limit", "2", "--json"]));
+    assert_eq!(parsed["count"], 1);
+    assert_eq!(parsed["items"][0]["name"], "serde");
+    assert_eq!(parsed["items"][0]["downloads"], 700_000_000);
+
+    let (code, parsed) =
+        json(bin(home.path(), &url).args(["search", "http", "-r", "pypi", "--json"]));
+    assert_eq!(code, Some(2));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    let (code, parsed) = json(bin(home.path(), &url).args(["show", "no-such-crate", "--json"]));
+    assert_eq!(code, Some(3));
+    assert_eq!(parsed["code"], "NOT_FOUND");
+}
+
+#[test]
+fn config_registry_is_the_default_and_offline_serves_the_cache() {
+    let url = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    bin(home.path(), &url)
+        .args(["config", "set", "registry", "npm"])
+        .assert()
+        .success();
+    let (_, parsed) = json(bin(home.path(), &url).args(["show", "left-pad", "--json"]));
+    assert_eq!(parsed["item"]["registry"], "npm");
+
+    let (_, parsed) =
+        json(bin(home.path(), &url).args(["show", "left-pad", "--offline", "--json"]));
+    assert_eq!(parsed["stale"], true);
+    assert_eq!(parsed["item"]["version"], "1.3.0");
+
+    let (code, parsed) =
+        json(bin(home.path(), &url).args(["show", "express", "--offline", "--json"]));
+    assert_eq!(code, Some(3));
+    assert_eq!(parsed["code"], "OFFLINE_MISS");
+
+    let (_, parsed) =
+        json(bin(home.path(), &url).args(["config", "set", "registry", "maven", "--json"]));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
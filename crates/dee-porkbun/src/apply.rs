@@ -0,0 +1,437 @@
+//! `dns apply`: bring a domain's records in line with a manifest.
+//!
+//! Records are matched per (type, name). Exact matches are left alone; the rest
+//! of a group is paired off into edits, and what is left over is created or
+//! deleted. Deletes run first so a CNAME can replace other records at a name.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use dee_core::{style, OkItem};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    call_api, dns_body_from_common, enc, print_json, require_auth_config, require_confirm,
+    validate_domain, validate_record_type, AppError, DnsApplyArgs, OutputFlags,
+};
+
+/// Porkbun's default, and its minimum
+const DEFAULT_TTL: u32 = 600;
+
+/// The desired zone, from TOML or YAML.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    domain: String,
+    #[serde(default)]
+    records: Vec<ManifestRecord>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestRecord {
+    #[serde(rename = "type")]
+    record_type: String,
+    /// Subdomain; empty or `@` for the apex
+    #[serde(default)]
+    name: String,
+    content: String,
+    #[serde(default)]
+    ttl: Option<u32>,
+    #[serde(default)]
+    prio: Option<u32>,
+    /// Compared only when set
+    #[serde(default)]
+    notes: Option<String>,
+}
+
+/// One record, normalized so manifest and API records compare equal.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Record {
+    #[serde(rename = "type")]
+    record_type: String,
+    /// Subdomain, empty for the apex
+    name: String,
+    content: String,
+    ttl: u32,
+    prio: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Create,
+    Edit,
+    Delete,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Change {
+    pub(crate) action: Action,
+    /// Porkbun record id; absent for creates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) id: Option<String>,
+    #[serde(flatten)]
+    pub(crate) record: Record,
+    /// The record being replaced, for edits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) before: Option<Record>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ApplyPlan {
+    domain: String,
+    /// True when nothing was sent
+    dry_run: bool,
+    /// Records already as the manifest wants them
+    unchanged: usize,
+    changes: Vec<Change>,
+}
+
+pub async fn handle(args: &DnsApplyArgs, output: &OutputFlags) -> Result<()> {
+    let manifest = read_manifest(&args.file)?;
+    validate_domain(&manifest.domain)?;
+    let desired = manifest
+        .records
+        .iter()
+        .map(|record| desired_record(record, &manifest.domain))
+        .collect::<Result<Vec<_>>>()?;
+    if !args.dry_run {
+        require_confirm(args.confirm)?;
+    }
+
+    let cfg = require_auth_config()?;
+    let path = format!("/dns/retrieve/{}", enc(&manifest.domain));
+    let value = call_api(&path, Map::new(), &cfg, output).await?;
+    let existing = value
+        .get("records")
+        .and_then(Value::as_array)
+        .map(|records| {
+            records
+                .iter()
+                .filter_map(|record| existing_record(record, &manifest.domain))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let (changes, unchanged) = plan(desired, existing);
+    let plan = ApplyPlan {
+        domain: manifest.domain,
+        dry_run: args.dry_run,
+        unchanged,
+        changes,
+    };
+
+    if !args.dry_run {
+        let total = plan.changes.len();
+        for (done, change) in plan.changes.iter().enumerate() {
+            execute(&plan.domain, change, &cfg, output)
+                .await
+                .with_context(|| format!("dns apply stopped after {done} of {total} change(s)"))?;
+        }
+    }
+
+    if output.json {
+        print_json(&OkItem {
+            ok: true,
+            item: plan,
+        })
+    } else if output.quiet {
+        println!("{}", plan.changes.len());
+        Ok(())
+    } else {
+        print_plan(&plan);
+        Ok(())
+    }
+}
+
+/// `.yaml`/`.yml` files are YAML, anything else TOML.
+fn read_manifest(file: &Path) -> Result<Manifest> {
+    let text = std::fs::read_to_string(file).map_err(|err| {
+        AppError::InvalidArgument(format!("cannot read {}: {err}", file.display()))
+    })?;
+    let yaml = file
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"));
+    let manifest = if yaml {
+        serde_yaml::from_str(&text).map_err(|err| err.to_string())
+    } else {
+        toml::from_str(&text).map_err(|err| err.to_string())
+    };
+    manifest.map_err(|err| {
+        AppError::InvalidArgument(format!("invalid manifest {}: {err}", file.display())).into()
+    })
+}
+
+fn desired_record(record: &ManifestRecord, domain: &str) -> Result<Record> {
+    validate_record_type(&record.record_type)?;
+    if record.content.trim().is_empty() {
+        return Err(AppError::InvalidArgument(format!(
+            "{} record `{}` has no content",
+            record.record_type, record.name
+        ))
+        .into());
+    }
+    crate::content::validate(&record.record_type, &record.content)?;
+    Ok(Record {
+        record_type: record.record_type.to_ascii_uppercase(),
+        name: subdomain(&record.name, domain),
+        content: record.content.trim().to_string(),
+        ttl: record.ttl.unwrap_or(DEFAULT_TTL),
+        prio: record.prio.unwrap_or(0),
+        notes: record.notes.clone(),
+    })
+}
+
+/// A `dns retrieve` record with its id, or `None` for records `apply` leaves
+/// alone: the apex NS records Porkbun manages.
+pub(crate) fn existing_record(value: &Value, domain: &str) -> Option<(String, Record)> {
+    let text = |key: &str| value.get(key).and_then(Value::as_str).unwrap_or_default();
+    let number = |key: &str| text(key).trim().parse::<u32>().ok();
+    let record = Record {
+        record_type: text("type").to_ascii_uppercase(),
+        name: subdomain(text("name"), domain),
+        content: text("content").to_string(),
+        ttl: number("ttl").unwrap_or(DEFAULT_TTL),
+        prio: number("prio").unwrap_or(0),
+        notes: Some(text("notes").to_string()),
+    };
+    if record.record_type == "NS" && record.name.is_empty() {
+        return None;
+    }
+    Some((text("id").to_string(), record))
+}
+
+/// `www.example.com`, `www` and `www.example.com.` are all `www`; `@` and the
+/// domain itself are the apex.
+pub(crate) fn subdomain(name: &str, domain: &str) -> String {
+    let name = name.trim().trim_end_matches('.').to_ascii_lowercase();
+    let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+    if name == "@" || name == domain {
+        return String::new();
+    }
+    name.strip_suffix(&format!(".{domain}"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+/// Hostnames compare without case or a trailing dot; other content exactly.
+fn same_content(record_type: &str, a: &str, b: &str) -> bool {
+    if matches!(record_type, "CNAME" | "ALIAS" | "MX" | "NS") {
+        a.trim_end_matches('.')
+            .eq_ignore_ascii_case(b.trim_end_matches('.'))
+    } else {
+        a == b
+    }
+}
+
+fn matches(desired: &Record, existing: &Record) -> bool {
+    same_content(&desired.record_type, &desired.content, &existing.content)
+        && desired.ttl == existing.ttl
+        && desired.prio == existing.prio
+        && desired
+            .notes
+            .as_ref()
+            .is_none_or(|notes| Some(notes) == existing.notes.as_ref())
+}
+
+/// The desired records and the existing `(id, record)`s at one (type, name)
+type Group = (Vec<Record>, Vec<(String, Record)>);
+
+/// Deletes, then edits, then creates; and how many records already match.
+pub(crate) fn plan(desired: Vec<Record>, existing: Vec<(String, Record)>) -> (Vec<Change>, usize) {
+    let mut groups: BTreeMap<(String, String), Group> = BTreeMap::new();
+    for record in desired {
+        let key = (record.record_type.clone(), record.name.clone());
+        groups.entry(key).or_default().0.push(record);
+    }
+    for (id, record) in existing {
+        let key = (record.record_type.clone(), record.name.clone());
+        groups.entry(key).or_default().1.push((id, record));
+    }
+
+    let (mut deletes, mut edits, mut creates) = (Vec::new(), Vec::new(), Vec::new());
+    let mut unchanged = 0;
+    for (_, (mut want, mut have)) in groups {
+        want.retain(|record| {
+            match have
+                .iter()
+                .position(|(_, current)| matches(record, current))
+            {
+                Some(found) => {
+                    have.remove(found);
+                    unchanged += 1;
+                    false
+                }
+                None => true,
+            }
+        });
+
+        let mut have = have.into_iter();
+        for record in want {
+            match have.next() {
+                Some((id, before)) => edits.push(Change {
+                    action: Action::Edit,
+                    id: Some(id),
+                    record,
+                    before: Some(before),
+                }),
+                None => creates.push(Change {
+                    action: Action::Create,
+                    id: None,
+                    record,
+                    before: None,
+                }),
+            }
+        }
+        deletes.extend(have.map(|(id, record)| Change {
+            action: Action::Delete,
+            id: Some(id),
+            record,
+            before: None,
+        }));
+    }
+
+    deletes.append(&mut edits);
+    deletes.append(&mut creates);
+    (deletes, unchanged)
+}
+
+async fn execute(
+    domain: &str,
+    change: &Change,
+    cfg: &crate::AppConfig,
+    output: &OutputFlags,
+) -> Result<()> {
+    let record = &change.record;
+    let id = change.id.as_deref().unwrap_or_default();
+    let (path, body) = match change.action {
+        Action::Delete => (
+            format!("/dns/delete/{}/{}", enc(domain), enc(id)),
+            Map::new(),
+        ),
+        Action::Edit | Action::Create => {
+            let body = dns_body_from_common(
+                &record.record_type,
+                &record.name,
+                &record.content,
+                Some(record.ttl),
+                Some(record.prio),
+                record.notes.clone(),
+            )?;
+            let path = if change.action == Action::Edit {
+                format!("/dns/edit/{}/{}", enc(domain), enc(id))
+            } else {
+                format!("/dns/create/{}", enc(domain))
+            };
+            (path, body)
+        }
+    };
+    call_api(&path, body, cfg, output).await.map(|_| ())
+}
+
+/// `A www 203.0.113.10 ttl=600`, with `@` for the apex and `prio=` when set.
+pub(crate) fn record_line(record: &Record) -> String {
+    let name = if record.name.is_empty() {
+        "@"
+    } else {
+        &record.name
+    };
+    format!(
+        "{} {name} {} ttl={}{}",
+        record.record_type,
+        record.content,
+        record.ttl,
+        if record.prio > 0 {
+            format!(" prio={}", record.prio)
+        } else {
+            String::new()
+        }
+    )
+}
+
+fn print_plan(plan: &ApplyPlan) {
+    for change in &plan.changes {
+        let mark = match change.action {
+            Action::Create => "+",
+            Action::Edit => "~",
+            Action::Delete => "-",
+        };
+        println!("{mark} {}", record_line(&change.record));
+    }
+    let summary = match (plan.dry_run, plan.changes.len()) {
+        (_, 0) => format!("{} is up to date", plan.domain),
+        (true, n) => format!("{n} change(s) planned for {} (dry run)", plan.domain),
+        (false, n) => format!("Applied {n} change(s) to {}", plan.domain),
+    };
+    println!("{}", style::header(summary));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(record_type: &str, name: &str, content: &str) -> Record {
+        Record {
+            record_type: record_type.to_string(),
+            name: name.to_string(),
+            content: content.to_string(),
+            ttl: DEFAULT_TTL,
+            prio: 0,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn names_are_relative_to_the_domain() {
+        assert_eq!(subdomain("www.example.com", "example.com"), "www");
+        assert_eq!(subdomain("WWW.example.com.", "example.com"), "www");
+        assert_eq!(subdomain("example.com", "example.com"), "");
+        assert_eq!(subdomain("@", "example.com"), "");
+        assert_eq!(subdomain("mail", "example.com"), "mail");
+    }
+
+    #[test]
+    fn plan_is_minimal_and_deletes_first() {
+        let desired = vec![
+            record("A", "", "203.0.113.10"),
+            record("A", "www", "203.0.113.20"),
+            record("TXT", "", "v=spf1 -all"),
+        ];
+        let existing = vec![
+            ("1".to_string(), record("A", "", "203.0.113.10")),
+            ("2".to_string(), record("A", "www", "203.0.113.11")),
+            ("3".to_string(), record("CNAME", "old", "example.com.")),
+        ];
+
+        let (changes, unchanged) = plan(desired, existing);
+        assert_eq!(unchanged, 1);
+        let actions: Vec<_> = changes
+            .iter()
+            .map(|c| (c.action, c.id.as_deref(), c.record.record_type.as_str()))
+            .collect();
+        assert_eq!(
+            actions,
+            [
+                (Action::Delete, Some("3"), "CNAME"),
+                (Action::Edit, Some("2"), "A"),
+                (Action::Create, None, "TXT"),
+            ]
+        );
+    }
+
+    #[test]
+    fn hostnames_match_without_case_or_trailing_dot() {
+        let desired = vec![record("CNAME", "www", "Example.com.")];
+        let existing = vec![("9".to_string(), record("CNAME", "www", "example.com"))];
+        let (changes, unchanged) = plan(desired, existing);
+        assert!(changes.is_empty());
+        assert_eq!(unchanged, 1);
+    }
+}
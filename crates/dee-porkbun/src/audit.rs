@@ -0,0 +1,205 @@
+//! The audit log: one JSON line per mutating Porkbun call, appended to
+//! `<data dir>/dee-porkbun/audit.jsonl`, and `audit list` to read it back.
+//!
+//! Calls are recorded after Porkbun answers, successful or not. The API keys
+//! are never written; reads (retrieve, list, check, pricing) are not logged.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, SecondsFormat, TimeDelta, Utc};
+use dee_core::profile;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{output_value_list, AppError, AuditListArgs, OutputFlags, TOOL};
+
+/// Endpoints that change nothing, by path prefix.
+const READ_ONLY: &[&str] = &[
+    "/ping",
+    "/pricing/get",
+    "/domain/listAll",
+    "/domain/checkDomain/",
+    "/domain/getNs/",
+    "/domain/getUrlForwarding/",
+    "/domain/getGlue/",
+    "/dns/retrieve",
+    "/dns/getDnssecRecords/",
+    "/ssl/retrieve/",
+];
+
+const SECRET_FIELDS: &[&str] = &["apikey", "secretapikey"];
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AuditEntry {
+    /// When Porkbun answered, RFC 3339 UTC
+    pub ts: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// API path, e.g. `/dns/create/example.com`
+    pub path: String,
+    /// Request body without the API keys
+    pub body: Map<String, Value>,
+    pub ok: bool,
+    /// Porkbun's answer when `ok`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// The error when not `ok`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn file() -> PathBuf {
+    dee_core::config::data_dir(TOOL).join("audit.jsonl")
+}
+
+/// Whether a call to `path` is logged.
+pub fn mutating(path: &str) -> bool {
+    !READ_ONLY.iter().any(|prefix| path.starts_with(prefix))
+}
+
+/// Append the outcome of a call to `path`. The change has happened either
+/// way, so a log that cannot be written is a warning, not an error.
+pub fn record(path: &str, mut body: Map<String, Value>, outcome: &Result<Value>) {
+    for field in SECRET_FIELDS {
+        body.remove(*field);
+    }
+    let entry = AuditEntry {
+        ts: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        profile: profile::active(),
+        path: path.to_string(),
+        body,
+        ok: outcome.is_ok(),
+        result: outcome.as_ref().ok().cloned(),
+        error: outcome.as_ref().err().map(|err| format!("{err:#}")),
+    };
+    let file = file();
+    if let Err(err) = append(&file, &entry) {
+        tracing::warn!("audit log {} not written: {err}", file.display());
+    }
+}
+
+fn append(file: &std::path::Path, entry: &AuditEntry) -> std::io::Result<()> {
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    // bodies name domains, addresses and forward targets
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    // one write per line, so concurrent runs do not interleave
+    options.open(file)?.write_all(line.as_bytes())
+}
+
+pub fn list(args: &AuditListArgs, output: &OutputFlags) -> Result<()> {
+    let since = args
+        .since
+        .as_deref()
+        .map(|since| parse_since(since, Utc::now()))
+        .transpose()?;
+    let mut items = Vec::new();
+    for entry in entries()? {
+        let at = DateTime::parse_from_rfc3339(&entry.ts).map(|ts| ts.with_timezone(&Utc));
+        if since.is_some_and(|since| at.map_or(true, |at| at < since)) {
+            continue;
+        }
+        items.push(serde_json::to_value(entry)?);
+    }
+    output_value_list(output, items)
+}
+
+/// Every readable entry, oldest first; none when nothing was logged yet.
+pub fn entries() -> Result<Vec<AuditEntry>, AppError> {
+    let file = file();
+    let text = match fs::read_to_string(&file) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(AppError::InvalidArgument(format!(
+                "cannot read {}: {err}",
+                file.display()
+            )))
+        }
+    };
+    Ok(text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(number, line)| {
+            serde_json::from_str(line)
+                .map_err(|_| {
+                    tracing::warn!(line = number + 1, "unreadable audit log line; skipped")
+                })
+                .ok()
+        })
+        .collect())
+}
+
+/// `2026-10-01T12:00:00Z`, a UTC date `2026-10-01`, or an age such as `90m`,
+/// `24h` or `7d`.
+fn parse_since(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, AppError> {
+    let value = value.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    let split = value.char_indices().last().map_or(0, |(at, _)| at);
+    let (amount, unit) = value.split_at(split);
+    let age = amount.parse::<i64>().ok().and_then(|amount| match unit {
+        "m" => TimeDelta::try_minutes(amount),
+        "h" => TimeDelta::try_hours(amount),
+        "d" => TimeDelta::try_days(amount),
+        _ => None,
+    });
+    match age.filter(|age| *age >= TimeDelta::zero()) {
+        Some(age) => now.checked_sub_signed(age).ok_or_else(|| {
+            AppError::InvalidArgument(format!("--since `{value}` reaches too far back"))
+        }),
+        None => Err(AppError::InvalidArgument(format!(
+            "--since `{value}`: expected an RFC 3339 time, a date (YYYY-MM-DD) or an age like 24h or 7d"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changes_are_logged() {
+        assert!(mutating("/dns/create/example.com"));
+        assert!(mutating("/domain/updateNs/example.com"));
+        assert!(mutating("/dns/deleteDnssecRecord/example.com/64087"));
+        assert!(!mutating("/dns/retrieveByNameType/example.com/A/www"));
+        assert!(!mutating("/domain/listAll"));
+        assert!(!mutating("/ssl/retrieve/example.com"));
+    }
+
+    #[test]
+    fn since_takes_times_dates_and_ages() {
+        let now = DateTime::parse_from_rfc3339("2026-10-16T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let at = |value| {
+            parse_since(value, now)
+                .unwrap()
+                .to_rfc3339_opts(SecondsFormat::Secs, true)
+        };
+        assert_eq!(at("2026-10-15T08:30:00+02:00"), "2026-10-15T06:30:00Z");
+        assert_eq!(at("2026-10-01"), "2026-10-01T00:00:00Z");
+        assert_eq!(at("90m"), "2026-10-16T10:30:00Z");
+        assert_eq!(at("7d"), "2026-10-09T12:00:00Z");
+        assert!(parse_since("yesterday", now).is_err());
+        assert!(parse_since("-3h", now).is_err());
+        assert!(parse_since("", now).is_err());
+        assert!(parse_since("99999999999d", now).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! Record content checks, run before anything is sent so a typo is an
+//! `INVALID_ARGUMENT` instead of a half-applied change.
+//!
+//! Only the types with a fixed shape are checked; TLSA, HTTPS, SVCB and SSHFP
+//! content goes to Porkbun as given.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::AppError;
+
+/// Longest TXT content accepted. Porkbun splits long values into 255-byte
+/// strings itself; this keeps the record inside a single DNS answer.
+const TXT_MAX: usize = 4000;
+
+const HOSTNAME_MAX: usize = 253;
+const LABEL_MAX: usize = 63;
+
+/// Check `content` against what a `record_type` record holds. The type is
+/// assumed valid (`validate_record_type`).
+pub fn validate(record_type: &str, content: &str) -> Result<(), AppError> {
+    let content = content.trim();
+    let problem = match record_type.to_ascii_uppercase().as_str() {
+        "A" => content
+            .parse::<Ipv4Addr>()
+            .err()
+            .map(|_| "not an IPv4 address".to_string()),
+        "AAAA" => content
+            .parse::<Ipv6Addr>()
+            .err()
+            .map(|_| "not an IPv6 address".to_string()),
+        "CNAME" | "ALIAS" | "MX" | "NS" => hostname(content).err(),
+        "TXT" => {
+            (content.chars().count() > TXT_MAX).then(|| format!("longer than {TXT_MAX} characters"))
+        }
+        "SRV" => srv(content).err(),
+        "CAA" => caa(content).err(),
+        _ => None,
+    };
+    match problem {
+        Some(problem) => Err(AppError::InvalidArgument(format!(
+            "{} content `{content}`: {problem}",
+            record_type.to_ascii_uppercase()
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// A fully qualified host name, with or without the trailing dot.
+fn hostname(value: &str) -> Result<(), String> {
+    let name = value.strip_suffix('.').unwrap_or(value);
+    if name.len() > HOSTNAME_MAX {
+        return Err(format!("host name longer than {HOSTNAME_MAX} characters"));
+    }
+    let labels: Vec<&str> = name.split('.').collect();
+    if labels.len() < 2 {
+        return Err("not a fully qualified host name".to_string());
+    }
+    for label in &labels {
+        if label.is_empty() || label.len() > LABEL_MAX {
+            return Err(format!("label `{label}` must be 1-{LABEL_MAX} characters"));
+        }
+        // `_` appears in service names such as `_dmarc` targets
+        if !label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            || label.starts_with('-')
+            || label.ends_with('-')
+        {
+            return Err(format!("label `{label}` is not a valid host name label"));
+        }
+    }
+    if labels
+        .last()
+        .is_some_and(|tld| tld.chars().all(|c| c.is_ascii_digit()))
+    {
+        return Err("an IP address is not a host name".to_string());
+    }
+    Ok(())
+}
+
+/// Porkbun takes SRV content as `weight port target`; priority is `--prio`.
+fn srv(value: &str) -> Result<(), String> {
+    let fields: Vec<&str> = value.split_whitespace().collect();
+    let [weight, port, target] = fields[..] else {
+        return Err("expected `weight port target`, e.g. `5 5060 sip.example.com`".to_string());
+    };
+    if weight.parse::<u16>().is_err() {
+        return Err(format!("weight `{weight}` is not 0-65535"));
+    }
+    if port.parse::<u16>().is_err() {
+        return Err(format!("port `{port}` is not 0-65535"));
+    }
+    // `.` means the service is not offered at this name
+    if target == "." {
+        return Ok(());
+    }
+    hostname(target)
+}
+
+/// `flags tag value`, e.g. `0 issue "letsencrypt.org"`.
+fn caa(value: &str) -> Result<(), String> {
+    let mut fields = value.splitn(3, char::is_whitespace);
+    let (Some(flags), Some(tag), Some(rest)) = (fields.next(), fields.next(), fields.next()) else {
+        return Err("expected `flags tag value`, e.g. `0 issue \"letsencrypt.org\"`".to_string());
+    };
+    if flags.parse::<u8>().is_err() {
+        return Err(format!("flags `{flags}` is not 0-255"));
+    }
+    if tag.is_empty() || tag.len() > 15 || !tag.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("tag `{tag}` must be 1-15 letters or digits"));
+    }
+    if rest.trim().is_empty() {
+        return Err("missing value".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok(record_type: &str, content: &str) -> bool {
+        validate(record_type, content).is_ok()
+    }
+
+    #[test]
+    fn addresses_match_the_family() {
+        assert!(ok("A", "203.0.113.10"));
+        assert!(!ok("A", "203.0.113"));
+        assert!(!ok("a", "2001:db8::1"));
+        assert!(ok("AAAA", "2001:db8::1"));
+        assert!(!ok("AAAA", "203.0.113.10"));
+    }
+
+    #[test]
+    fn targets_are_host_names() {
+        assert!(ok("CNAME", "example.com"));
+        assert!(ok("MX", "mx1.example.net."));
+        assert!(ok("NS", "curitiba.ns.porkbun.com"));
+        assert!(ok("CNAME", "s1._domainkey.example.net"));
+        assert!(!ok("CNAME", "localhost"));
+        assert!(!ok("MX", "203.0.113.10"));
+        assert!(!ok("NS", "bad..example.com"));
+        assert!(!ok("ALIAS", "-edge.example.com"));
+        assert!(!ok("CNAME", "ex ample.com"));
+        assert!(!ok("CNAME", &format!("{}.com", "a".repeat(64))));
+    }
+
+    #[test]
+    fn txt_has_a_length_limit() {
+        assert!(ok("TXT", "v=spf1 include:_spf.porkbun.com ~all"));
+        assert!(ok("TXT", &"a".repeat(TXT_MAX)));
+        assert!(!ok("TXT", &"a".repeat(TXT_MAX + 1)));
+    }
+
+    #[test]
+    fn srv_and_caa_follow_their_syntax() {
+        assert!(ok("SRV", "5 5060 sip.example.com"));
+        assert!(ok("SRV", "0 0 ."));
+        assert!(!ok("SRV", "10 5 5060 sip.example.com"));
+        assert!(!ok("SRV", "5 70000 sip.example.com"));
+        assert!(ok("CAA", "0 issue \"letsencrypt.org\""));
+        assert!(ok("CAA", "128 iodef \"mailto:ops@example.com\""));
+        assert!(!ok("CAA", "0 issue"));
+        assert!(!ok("CAA", "256 issue \"letsencrypt.org\""));
+        assert!(!ok("CAA", "0 is-sue \"letsencrypt.org\""));
+    }
+
+    #[test]
+    fn errors_name_the_type_and_content() {
+        let err = validate("a", "example.com").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid argument: A content `example.com`: not an IPv4 address"
+        );
+        assert!(ok("TLSA", "3 1 1 abcdef"));
+    }
+}
@@ -0,0 +1,330 @@
+//! `domains update-ns --verify`: wait until the parent zone delegates to the
+//! new nameservers.
+//!
+//! The parent's servers (the TLD's, or `co.uk`'s for `example.co.uk`) are
+//! asked directly over UDP with recursion off, so the answer is the referral
+//! the registry publishes rather than whatever a resolver has cached. They are
+//! found with DNS-over-HTTPS unless `--parent-server` names one.
+
+use std::collections::BTreeSet;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use dee_core::{style, OkItem};
+use schemars::JsonSchema;
+use serde::Serialize;
+use tokio::net::UdpSocket;
+
+use crate::dnssec::owner_name;
+use crate::{doh, print_json, AppError, OutputFlags, UpdateNsArgs};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const NS_TYPE: u16 = 2;
+const IN_CLASS: u16 = 1;
+const NXDOMAIN: u16 = 3;
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct NsVerified {
+    domain: String,
+    /// As requested, lower case without the trailing dot
+    nameservers: Vec<String>,
+    /// Whether the parent zone delegates to exactly these nameservers
+    propagated: bool,
+    /// NS names in the parent's last answer
+    observed: Vec<String>,
+    /// The parent zone server asked
+    parent_server: String,
+    /// Queries sent
+    checks: u32,
+    waited_secs: u64,
+}
+
+/// Poll the parent zone after a successful update and print the outcome.
+pub async fn handle(args: &UpdateNsArgs, output: &OutputFlags) -> Result<()> {
+    let (server, addr) = parent_server(&args.domain, args, output).await?;
+    let expected: BTreeSet<String> = args.nameservers.iter().map(|ns| normalize(ns)).collect();
+    let deadline = Duration::from_secs(args.wait);
+    let started = Instant::now();
+    let mut checks = 0;
+    let mut observed = Vec::new();
+    loop {
+        checks += 1;
+        match query(addr, &args.domain).await {
+            Ok(names) => observed = names,
+            Err(err) => tracing::warn!(server, "parent zone query failed: {err}"),
+        }
+        let propagated = observed.iter().cloned().collect::<BTreeSet<_>>() == expected;
+        let elapsed = started.elapsed();
+        if propagated || elapsed >= deadline {
+            break;
+        }
+        tracing::info!(
+            observed = observed.join(" "),
+            "delegation not updated yet; asking again"
+        );
+        tokio::time::sleep(POLL_INTERVAL.min(deadline - elapsed)).await;
+    }
+
+    let verified = NsVerified {
+        domain: args.domain.clone(),
+        propagated: observed.iter().cloned().collect::<BTreeSet<_>>() == expected,
+        nameservers: expected.into_iter().collect(),
+        observed,
+        parent_server: server,
+        checks,
+        waited_secs: started.elapsed().as_secs(),
+    };
+    if output.json {
+        print_json(&OkItem {
+            ok: true,
+            item: verified,
+        })
+    } else if output.quiet {
+        println!(
+            "{}",
+            if verified.propagated {
+                "propagated"
+            } else {
+                "pending"
+            }
+        );
+        Ok(())
+    } else {
+        println!("Nameservers updated");
+        if verified.propagated {
+            println!(
+                "{}",
+                style::good(format!(
+                    "{} delegates to them at {}",
+                    verified.domain, verified.parent_server
+                ))
+            );
+        } else {
+            println!(
+                "{}",
+                style::warn(format!(
+                    "{} still lists {} after {}s; the registry may take longer",
+                    verified.parent_server,
+                    if verified.observed.is_empty() {
+                        "nothing".to_string()
+                    } else {
+                        verified.observed.join(", ")
+                    },
+                    verified.waited_secs
+                ))
+            );
+        }
+        Ok(())
+    }
+}
+
+/// `--parent-server`, or the first parent zone nameserver with an IPv4
+/// address, as a label and an address to query.
+async fn parent_server(
+    domain: &str,
+    args: &UpdateNsArgs,
+    output: &OutputFlags,
+) -> Result<(String, SocketAddr)> {
+    if let Some(server) = &args.parent_server {
+        let addr = server
+            .parse::<SocketAddr>()
+            .or_else(|_| server.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 53)))
+            .map_err(|_| {
+                AppError::InvalidArgument(format!(
+                    "--parent-server `{server}` is not an IP address or address:port"
+                ))
+            })?;
+        return Ok((server.clone(), addr));
+    }
+    let resolver = args.resolver.as_deref();
+    let mut labels = domain.trim_end_matches('.').split('.').skip(1);
+    while labels.clone().next().is_some() {
+        let zone = labels.clone().collect::<Vec<_>>().join(".");
+        labels.next();
+        let mut servers = doh::lookup(&zone, doh::NS, resolver, output).await?;
+        servers.sort();
+        for server in servers {
+            let found = doh::lookup(&server, doh::A, resolver, output)
+                .await?
+                .iter()
+                .find_map(|data| data.parse::<Ipv4Addr>().ok());
+            if let Some(ip) = found {
+                let server = normalize(&server);
+                return Ok((format!("{server} ({ip})"), SocketAddr::new(ip.into(), 53)));
+            }
+        }
+    }
+    Err(AppError::NotFound(format!(
+        "no parent zone nameserver found for {domain}; name one with --parent-server"
+    ))
+    .into())
+}
+
+/// The NS names `server` gives for `domain`; empty when it does not know it.
+async fn query(server: SocketAddr, domain: &str) -> Result<Vec<String>, String> {
+    let id = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.subsec_nanos() as u16)
+        .unwrap_or_default();
+    let local: SocketAddr = if server.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(local).await.map_err(|e| e.to_string())?;
+    tracing::debug!("NS {domain} @{server}");
+    socket
+        .send_to(&ns_query(id, domain), server)
+        .await
+        .map_err(|e| e.to_string())?;
+    let answer = async {
+        let mut buf = [0u8; 4096];
+        loop {
+            let (len, from) = socket
+                .recv_from(&mut buf)
+                .await
+                .map_err(|e| e.to_string())?;
+            if from == server && buf[..len].starts_with(&id.to_be_bytes()) {
+                return ns_names(&buf[..len], domain);
+            }
+        }
+    };
+    tokio::time::timeout(QUERY_TIMEOUT, answer)
+        .await
+        .map_err(|_| format!("no answer within {}s", QUERY_TIMEOUT.as_secs()))?
+}
+
+/// A single non-recursive NS question.
+fn ns_query(id: u16, domain: &str) -> Vec<u8> {
+    let mut packet = id.to_be_bytes().to_vec();
+    // flags 0 (standard query, RD off), one question, no records
+    packet.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 0, 0, 0]);
+    packet.extend(owner_name(domain));
+    packet.extend_from_slice(&NS_TYPE.to_be_bytes());
+    packet.extend_from_slice(&IN_CLASS.to_be_bytes());
+    packet
+}
+
+/// NS targets owned by `domain` in the answer and authority sections, sorted.
+fn ns_names(packet: &[u8], domain: &str) -> Result<Vec<String>, String> {
+    let short = || "truncated response".to_string();
+    let word = |at: usize| -> Result<u16, String> {
+        packet
+            .get(at..at + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(short)
+    };
+    let rcode = word(2)? & 0x000f;
+    if rcode == NXDOMAIN {
+        return Ok(Vec::new());
+    }
+    if rcode != 0 {
+        return Err(format!("server answered rcode {rcode}"));
+    }
+    let questions = word(4)?;
+    let records = usize::from(word(6)?) + usize::from(word(8)?);
+    let mut at = 12;
+    for _ in 0..questions {
+        at = read_name(packet, at)?.1 + 4;
+    }
+    let domain = normalize(domain);
+    let mut names = BTreeSet::new();
+    for _ in 0..records {
+        let (owner, next) = read_name(packet, at)?;
+        let rtype = word(next)?;
+        let rdlength = usize::from(word(next + 8)?);
+        let rdata = next + 10;
+        if rdata + rdlength > packet.len() {
+            return Err(short());
+        }
+        if rtype == NS_TYPE && owner == domain {
+            names.insert(read_name(packet, rdata)?.0);
+        }
+        at = rdata + rdlength;
+    }
+    Ok(names.into_iter().collect())
+}
+
+/// The name at `at`, following compression pointers, and the offset just
+/// past it.
+fn read_name(packet: &[u8], mut at: usize) -> Result<(String, usize), String> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    for _ in 0..128 {
+        let len = *packet.get(at).ok_or("truncated name")?;
+        match len {
+            0 => {
+                return Ok((labels.join("."), end.unwrap_or(at + 1)));
+            }
+            len if len & 0xc0 == 0xc0 => {
+                let low = *packet.get(at + 1).ok_or("truncated name")?;
+                end.get_or_insert(at + 2);
+                at = usize::from(u16::from_be_bytes([len & 0x3f, low]));
+            }
+            len => {
+                let label = packet
+                    .get(at + 1..at + 1 + usize::from(len))
+                    .ok_or("truncated name")?;
+                labels.push(String::from_utf8_lossy(label).to_ascii_lowercase());
+                at += 1 + usize::from(len);
+            }
+        }
+    }
+    Err("name compression loop".to_string())
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `.com` referral: owner names point back at the question, targets
+    /// are spelled out, and a glue A record follows in the additional section.
+    fn referral(id: u16) -> Vec<u8> {
+        let mut packet = id.to_be_bytes().to_vec();
+        packet.extend_from_slice(&[0x80, 0, 0, 1, 0, 0, 0, 2, 0, 1]);
+        packet.extend(owner_name("Example.com"));
+        packet.extend_from_slice(&[0, 2, 0, 1]);
+        for target in ["NS1.Host.net.", "ns2.host.net"] {
+            let rdata = owner_name(target);
+            packet.extend_from_slice(&[0xc0, 12, 0, 2, 0, 1, 0, 2, 0xa3, 0]);
+            packet.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+            packet.extend(rdata);
+        }
+        packet.extend_from_slice(&[0xc0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 192, 0, 2, 1]);
+        packet
+    }
+
+    #[test]
+    fn reads_the_referral() {
+        let query = ns_query(7, "example.com");
+        assert_eq!(&query[..4], &[0, 7, 0, 0]);
+        assert_eq!(
+            ns_names(&referral(7), "EXAMPLE.com.").unwrap(),
+            ["ns1.host.net", "ns2.host.net"]
+        );
+        assert!(ns_names(&referral(7), "other.com").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_broken_packets() {
+        let packet = referral(7);
+        assert!(ns_names(&packet[..packet.len() - 20], "example.com").is_err());
+        let mut refused = packet.clone();
+        refused[3] = 5;
+        assert!(ns_names(&refused, "example.com").is_err());
+        let mut overcounted = packet.clone();
+        overcounted[6..10].fill(0xff);
+        assert!(ns_names(&overcounted, "example.com").is_err());
+        let mut unknown = packet;
+        unknown[3] = NXDOMAIN as u8;
+        assert!(ns_names(&unknown, "example.com").unwrap().is_empty());
+        assert!(read_name(&[0xc0, 0], 0).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! `dns diff`: the live zone against a snapshot saved from `dns retrieve`.
+//!
+//! Records are paired the way `dns apply` pairs them, so `changed` lists
+//! exactly the edits that applying the snapshot would make.
+
+use std::path::Path;
+
+use anyhow::Result;
+use dee_core::{style, OkItem};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::apply::{existing_record, plan, record_line, Action, Record};
+use crate::{
+    call_api, enc, print_json, require_auth_config, validate_domain, AppError, DnsDiffArgs,
+    OutputFlags,
+};
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DiffRecord {
+    /// Live record id; absent for removed records
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(flatten)]
+    record: Record,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ChangedRecord {
+    /// Live record id
+    id: String,
+    before: Record,
+    after: Record,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DnsDiff {
+    domain: String,
+    snapshot: String,
+    /// Records the same in both
+    unchanged: usize,
+    /// Live only
+    added: Vec<DiffRecord>,
+    /// Snapshot only
+    removed: Vec<DiffRecord>,
+    /// In both with different content, TTL, priority or notes
+    changed: Vec<ChangedRecord>,
+}
+
+pub async fn handle(args: &DnsDiffArgs, output: &OutputFlags) -> Result<()> {
+    validate_domain(&args.domain)?;
+    let snapshot = read_snapshot(&args.snapshot, &args.domain)?;
+    let cfg = require_auth_config()?;
+    let path = format!("/dns/retrieve/{}", enc(&args.domain));
+    let value = call_api(&path, Map::new(), &cfg, output).await?;
+    let live = records(value.get("records"), &args.domain);
+
+    let diff = diff(&args.domain, &args.snapshot, snapshot, live);
+    if output.json {
+        print_json(&OkItem {
+            ok: true,
+            item: diff,
+        })
+    } else if output.quiet {
+        println!(
+            "{}",
+            diff.added.len() + diff.removed.len() + diff.changed.len()
+        );
+        Ok(())
+    } else {
+        print_diff(&diff);
+        Ok(())
+    }
+}
+
+/// The `dns retrieve --json` output, Porkbun's own `{"records": [...]}`, or a
+/// bare array of records.
+fn read_snapshot(file: &Path, domain: &str) -> Result<Vec<(String, Record)>> {
+    let text = std::fs::read_to_string(file).map_err(|err| {
+        AppError::InvalidArgument(format!("cannot read {}: {err}", file.display()))
+    })?;
+    let value: Value = serde_json::from_str(&text).map_err(|err| {
+        AppError::InvalidArgument(format!("invalid snapshot {}: {err}", file.display()))
+    })?;
+    let list = if value.is_array() {
+        Some(&value)
+    } else {
+        value.get("items").or_else(|| value.get("records"))
+    };
+    if !list.is_some_and(Value::is_array) {
+        return Err(AppError::InvalidArgument(format!(
+            "invalid snapshot {}: expected `dns retrieve --json` output",
+            file.display()
+        ))
+        .into());
+    }
+    Ok(records(list, domain))
+}
+
+fn records(list: Option<&Value>, domain: &str) -> Vec<(String, Record)> {
+    list.and_then(Value::as_array)
+        .map(|records| {
+            records
+                .iter()
+                .filter_map(|record| existing_record(record, domain))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// What restoring `snapshot` would do, read backwards: its creates are
+/// records since removed, its deletes records since added.
+fn diff(
+    domain: &str,
+    snapshot_file: &Path,
+    snapshot: Vec<(String, Record)>,
+    live: Vec<(String, Record)>,
+) -> DnsDiff {
+    let wanted = snapshot.into_iter().map(|(_, record)| record).collect();
+    let (changes, unchanged) = plan(wanted, live);
+    let mut diff = DnsDiff {
+        domain: domain.to_string(),
+        snapshot: snapshot_file.display().to_string(),
+        unchanged,
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+    for change in changes {
+        let id = change.id;
+        match (change.action, change.before) {
+            (Action::Edit, Some(live)) => diff.changed.push(ChangedRecord {
+                id: id.unwrap_or_default(),
+                before: change.record,
+                after: live,
+            }),
+            (Action::Delete, _) => diff.added.push(DiffRecord {
+                id,
+                record: change.record,
+            }),
+            _ => diff.removed.push(DiffRecord {
+                id,
+                record: change.record,
+            }),
+        }
+    }
+    diff
+}
+
+fn print_diff(diff: &DnsDiff) {
+    for added in &diff.added {
+        println!(
+            "{}",
+            style::good(format!("+ {}", record_line(&added.record)))
+        );
+    }
+    for removed in &diff.removed {
+        println!(
+            "{}",
+            style::bad(format!("- {}", record_line(&removed.record)))
+        );
+    }
+    for changed in &diff.changed {
+        println!("~ {}", record_line(&changed.before));
+        println!("  {}", record_line(&changed.after));
+    }
+    let summary = if diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty() {
+        format!("{} matches {}", diff.domain, diff.snapshot)
+    } else {
+        format!(
+            "{}: {} added, {} removed, {} changed since {}",
+            diff.domain,
+            diff.added.len(),
+            diff.removed.len(),
+            diff.changed.len(),
+            diff.snapshot
+        )
+    };
+    println!("{}", style::header(summary));
+}
@@ -0,0 +1,275 @@
+//! `dnssec wizard`: DS data computed from a DNSKEY, then sent to Porkbun.
+//!
+//! The key comes from `--dnskey` or from a DNS-over-HTTPS lookup. Key tag
+//! and digest follow RFC 4034 (appendix B and section 5.1.4); the digest is
+//! SHA-256 (type 2) or SHA-384 (type 4).
+
+use anyhow::Result;
+use base64::Engine;
+use dee_core::{style, OkItem};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256, Sha384};
+
+use crate::{
+    call_api, doh, enc, print_json, require_auth_config, require_confirm, validate_domain,
+    AppError, DnssecWizardArgs, OutputFlags,
+};
+
+/// Zone key flag; every DNSKEY that signs a zone has it
+const ZONE_KEY: u16 = 0x0100;
+/// Secure entry point flag, set on key-signing keys
+const SEP: u16 = 0x0001;
+
+#[derive(Debug, Clone)]
+struct Dnskey {
+    flags: u16,
+    protocol: u8,
+    algorithm: u8,
+    /// Base64 as published
+    public_key: String,
+    key: Vec<u8>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DsRecord {
+    domain: String,
+    key_tag: u16,
+    alg: u8,
+    digest_type: u8,
+    /// Hex, upper case
+    digest: String,
+    /// The DNSKEY's flags, 257 for a key-signing key
+    dnskey_flags: u16,
+    /// True when nothing was sent
+    dry_run: bool,
+}
+
+pub async fn handle(args: &DnssecWizardArgs, output: &OutputFlags) -> Result<()> {
+    validate_domain(&args.domain)?;
+    if !matches!(args.digest_type, 2 | 4) {
+        return Err(AppError::InvalidArgument(format!(
+            "--digest-type {} is not supported; use 2 (SHA-256) or 4 (SHA-384)",
+            args.digest_type
+        ))
+        .into());
+    }
+    if !args.dry_run {
+        require_confirm(args.confirm)?;
+    }
+    let dnskey = match &args.dnskey {
+        Some(text) => {
+            let key = parse_dnskey(text)?;
+            if key.flags & SEP == 0 {
+                tracing::warn!(
+                    flags = key.flags,
+                    "this DNSKEY is not a key-signing key; the parent usually wants the 257 key"
+                );
+            }
+            key
+        }
+        None => published_ksk(&args.domain, args.resolver.as_deref(), output).await?,
+    };
+
+    let ds = DsRecord {
+        domain: args.domain.clone(),
+        key_tag: key_tag(&dnskey),
+        alg: dnskey.algorithm,
+        digest_type: args.digest_type,
+        digest: digest(&args.domain, &dnskey, args.digest_type),
+        dnskey_flags: dnskey.flags,
+        dry_run: args.dry_run,
+    };
+
+    if !args.dry_run {
+        let cfg = require_auth_config()?;
+        let text = |value: &dyn ToString| Value::String(value.to_string());
+        let mut body = Map::new();
+        body.insert("keyTag".to_string(), text(&ds.key_tag));
+        body.insert("alg".to_string(), text(&ds.alg));
+        body.insert("digestType".to_string(), text(&ds.digest_type));
+        body.insert("digest".to_string(), text(&ds.digest));
+        body.insert("maxSigLife".to_string(), text(&""));
+        body.insert("keyDataFlags".to_string(), text(&dnskey.flags));
+        body.insert("keyDataProtocol".to_string(), text(&dnskey.protocol));
+        body.insert("keyDataAlgo".to_string(), text(&dnskey.algorithm));
+        body.insert("keyDataPubKey".to_string(), text(&dnskey.public_key));
+        let path = format!("/dns/createDnssecRecord/{}", enc(&args.domain));
+        call_api(&path, body, &cfg, output).await?;
+    }
+
+    if output.json {
+        print_json(&OkItem { ok: true, item: ds })
+    } else if output.quiet {
+        println!("{}", ds.key_tag);
+        Ok(())
+    } else {
+        println!(
+            "{}. IN DS {} {} {} {}",
+            ds.domain, ds.key_tag, ds.alg, ds.digest_type, ds.digest
+        );
+        let summary = if ds.dry_run {
+            format!("DS record for {} computed (dry run)", ds.domain)
+        } else {
+            format!("DS record for {} sent to Porkbun", ds.domain)
+        };
+        println!("{}", style::header(summary));
+        Ok(())
+    }
+}
+
+/// `257 3 13 <base64>`, or a zone file line ending in one; parentheses and
+/// line breaks inside the key are fine.
+fn parse_dnskey(text: &str) -> Result<Dnskey, AppError> {
+    let invalid = |why: &str| AppError::InvalidArgument(format!("invalid DNSKEY: {why}"));
+    let text = text.replace(['(', ')'], " ");
+    let fields: Vec<&str> = text.split_whitespace().collect();
+    let fields = match fields
+        .iter()
+        .position(|field| field.eq_ignore_ascii_case("DNSKEY"))
+    {
+        Some(at) => &fields[at + 1..],
+        None => &fields[..],
+    };
+    let [flags, protocol, algorithm, key @ ..] = fields else {
+        return Err(invalid("expected `flags protocol algorithm public-key`"));
+    };
+    let flags: u16 = flags
+        .parse()
+        .map_err(|_| invalid("flags is not a number"))?;
+    let protocol: u8 = protocol
+        .parse()
+        .map_err(|_| invalid("protocol is not a number"))?;
+    let algorithm: u8 = algorithm
+        .parse()
+        .map_err(|_| invalid("algorithm is not a number"))?;
+    if protocol != 3 {
+        return Err(invalid("protocol must be 3"));
+    }
+    if flags & ZONE_KEY == 0 {
+        return Err(invalid("not a zone key (flags 256 or 257 expected)"));
+    }
+    let public_key: String = key.concat();
+    let key = base64::engine::general_purpose::STANDARD
+        .decode(&public_key)
+        .map_err(|_| invalid("public key is not base64"))?;
+    if key.is_empty() {
+        return Err(invalid("public key is empty"));
+    }
+    Ok(Dnskey {
+        flags,
+        protocol,
+        algorithm,
+        public_key,
+        key,
+    })
+}
+
+/// The single key-signing key `domain` publishes.
+async fn published_ksk(
+    domain: &str,
+    resolver: Option<&str>,
+    output: &OutputFlags,
+) -> Result<Dnskey> {
+    let keys = doh::lookup(domain, doh::DNSKEY, resolver, output)
+        .await?
+        .iter()
+        .map(|data| parse_dnskey(data))
+        .collect::<Result<Vec<_>, _>>()?;
+    let ksks: Vec<Dnskey> = keys
+        .into_iter()
+        .filter(|key| key.flags & SEP != 0)
+        .collect();
+    match ksks.as_slice() {
+        [] => Err(AppError::NotFound(format!(
+            "no key-signing DNSKEY published for {domain}; pass one with --dnskey"
+        ))
+        .into()),
+        [key] => Ok(key.clone()),
+        many => Err(AppError::InvalidArgument(format!(
+            "{domain} publishes {} key-signing keys (key tags {}); pick one with --dnskey",
+            many.len(),
+            many.iter()
+                .map(|key| key_tag(key).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+        .into()),
+    }
+}
+
+/// DNSKEY RDATA: flags, protocol, algorithm, key.
+fn rdata(key: &Dnskey) -> Vec<u8> {
+    let mut rdata = key.flags.to_be_bytes().to_vec();
+    rdata.push(key.protocol);
+    rdata.push(key.algorithm);
+    rdata.extend_from_slice(&key.key);
+    rdata
+}
+
+/// RFC 4034 appendix B, for every algorithm but the retired RSA/MD5.
+fn key_tag(key: &Dnskey) -> u16 {
+    let mut sum: u32 = 0;
+    for (i, byte) in rdata(key).iter().enumerate() {
+        sum += if i % 2 == 0 {
+            u32::from(*byte) << 8
+        } else {
+            u32::from(*byte)
+        };
+    }
+    sum += sum >> 16;
+    (sum & 0xffff) as u16
+}
+
+/// The owner name in canonical wire form: lower case, length-prefixed labels.
+pub(crate) fn owner_name(domain: &str) -> Vec<u8> {
+    let mut wire = Vec::new();
+    for label in domain.trim_end_matches('.').split('.') {
+        wire.push(label.len() as u8);
+        wire.extend(label.to_ascii_lowercase().bytes());
+    }
+    wire.push(0);
+    wire
+}
+
+fn digest(domain: &str, key: &Dnskey, digest_type: u8) -> String {
+    let mut data = owner_name(domain);
+    data.extend(rdata(key));
+    let hash = if digest_type == 4 {
+        Sha384::digest(&data).to_vec()
+    } else {
+        Sha256::digest(&data).to_vec()
+    };
+    hash.iter().map(|byte| format!("{byte:02X}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 4034 section 5.4 and RFC 4509 section 2.3
+    const RFC_KEY: &str = "dskey.example.com. 86400 IN DNSKEY 256 3 5 ( AQOeiiR0GOMYkDshWoSKz9XzfwJr1AYtsmx3TGkJaNXVbfi/2pHm822aJ5iI9BMzNXxeYCmZDRD99WYwYqUSdjMmmAphXdvxegXd/M5+X7OrzKBaMbCVdFLUUh6DhweJBjEVv5f2wwjM9XzcnOf+EPbtG9DMBmADjFDc2w/rljwvFw== )";
+
+    #[test]
+    fn matches_the_rfc_examples() {
+        let key = parse_dnskey(RFC_KEY).unwrap();
+        assert_eq!(key.flags, 256);
+        assert_eq!(key.algorithm, 5);
+        assert_eq!(key_tag(&key), 60485);
+        assert_eq!(
+            digest("DSKEY.example.com.", &key, 2),
+            "D4B7D520E7BB5F0F67674A0CCEB1E3E0614B93C4F9E99B8383F6A1E4469DA50A"
+        );
+        assert_eq!(digest("dskey.example.com", &key, 4).len(), 96);
+    }
+
+    #[test]
+    fn rejects_what_is_not_a_zone_key() {
+        assert!(parse_dnskey("257 3 13").is_err());
+        assert!(parse_dnskey("257 2 13 AQOe").is_err());
+        assert!(parse_dnskey("0 3 13 AQOe").is_err());
+        assert!(parse_dnskey("257 3 13 not*base64").is_err());
+        assert!(parse_dnskey("257 3 13 mdsswUyr 3DPW132mOi8V").is_ok());
+    }
+}
@@ -0,0 +1,58 @@
+//! DNS-over-HTTPS lookups in the `application/dns-json` format, for the
+//! commands that need to see what DNS publishes.
+
+use anyhow::Result;
+use dee_core::http::{base_url, Http};
+use serde_json::Value;
+
+use crate::{enc, AppError, OutputFlags};
+
+/// Resolver answering `application/dns-json` queries
+const DOH_DEFAULT: &str = "https://cloudflare-dns.com/dns-query";
+
+pub const A: (&str, u64) = ("A", 1);
+pub const NS: (&str, u64) = ("NS", 2);
+pub const DNSKEY: (&str, u64) = ("DNSKEY", 48);
+
+/// The `data` of every `rtype` record in the answer for `name`; signatures
+/// and CNAMEs along the way are left out.
+pub async fn lookup(
+    name: &str,
+    (rtype, code): (&str, u64),
+    resolver: Option<&str>,
+    output: &OutputFlags,
+) -> Result<Vec<String>> {
+    let url = format!(
+        "{}?name={}&type={rtype}",
+        base_url(resolver, DOH_DEFAULT),
+        enc(name)
+    );
+    let client = Http::new("dee-porkbun", env!("CARGO_PKG_VERSION"))
+        .args(&output.http)
+        .client()
+        .map_err(|e| AppError::RequestFailed(e.to_string()))?;
+    tracing::debug!("GET {url}");
+    let response = client
+        .get(&url)
+        .header(reqwest::header::ACCEPT, "application/dns-json")
+        .send()
+        .await
+        .map_err(|e| AppError::RequestFailed(format!("{rtype} lookup: {e}")))?;
+    if !response.status().is_success() {
+        return Err(AppError::RequestFailed(format!(
+            "{rtype} lookup: resolver answered HTTP {}",
+            response.status().as_u16()
+        ))
+        .into());
+    }
+    let answer: Value = response.json().await.map_err(|_| AppError::ParseFailed)?;
+    Ok(answer
+        .get("Answer")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|record| record.get("type").and_then(Value::as_u64) == Some(code))
+        .filter_map(|record| record.get("data").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect())
+}
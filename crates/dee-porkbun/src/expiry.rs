@@ -0,0 +1,103 @@
+//! `domains expiring`: which domains lapse within a window, soonest first.
+//!
+//! Expiry dates come from `listAll`, which Porkbun returns in UTC as
+//! `YYYY-MM-DD HH:MM:SS`. Domains already past their date are included, so a
+//! report never hides a lapse it missed.
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    list_all_domains, output_value_list, parse_boolish, require_auth_config, ExpiringArgs,
+    OutputFlags,
+};
+
+const EXPIRE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ExpiringDomain {
+    pub domain: String,
+    /// As Porkbun reports it, UTC
+    pub expire_date: String,
+    /// Whole days until expiry; negative once lapsed
+    pub days_left: i64,
+    pub auto_renew: bool,
+    pub status: String,
+}
+
+pub async fn handle(args: &ExpiringArgs, output: &OutputFlags) -> Result<()> {
+    let cfg = require_auth_config()?;
+    let domains = list_all_domains(&cfg, false, output).await?;
+    let items = due(&domains, Utc::now(), args.days)
+        .into_iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()?;
+    output_value_list(output, items)
+}
+
+/// The domains in `domains` (`listAll` entries) expiring by `now + days`,
+/// soonest first. Entries without a readable `expireDate` are left out.
+fn due(domains: &[Value], now: DateTime<Utc>, days: u32) -> Vec<ExpiringDomain> {
+    let cutoff = now + TimeDelta::days(days.into());
+    let mut due: Vec<(DateTime<Utc>, ExpiringDomain)> = domains
+        .iter()
+        .filter_map(|entry| {
+            let domain = entry.get("domain").and_then(Value::as_str)?;
+            let expire_date = entry.get("expireDate").and_then(Value::as_str)?;
+            let Ok(expires) = NaiveDateTime::parse_from_str(expire_date, EXPIRE_FORMAT) else {
+                tracing::warn!(domain, expire_date, "unreadable expireDate; skipped");
+                return None;
+            };
+            let expires = expires.and_utc();
+            (expires <= cutoff).then(|| {
+                let item = ExpiringDomain {
+                    domain: domain.to_string(),
+                    expire_date: expire_date.to_string(),
+                    days_left: (expires - now).num_days(),
+                    auto_renew: entry.get("autoRenew").is_some_and(parse_boolish),
+                    status: entry
+                        .get("status")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                };
+                (expires, item)
+            })
+        })
+        .collect();
+    due.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.domain.cmp(&b.1.domain)));
+    due.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn keeps_the_window_and_lapsed_domains_soonest_first() {
+        let now = NaiveDateTime::parse_from_str("2026-10-01 00:00:00", EXPIRE_FORMAT)
+            .unwrap()
+            .and_utc();
+        let domains = [
+            json!({"domain": "later.com", "expireDate": "2026-11-20 12:00:00", "autoRenew": 0}),
+            json!({"domain": "far.com", "expireDate": "2027-10-01 00:00:00", "autoRenew": 1}),
+            json!({"domain": "gone.com", "expireDate": "2026-09-28 00:00:00", "status": "EXPIRED"}),
+            json!({"domain": "soon.com", "expireDate": "2026-10-11 00:00:00", "autoRenew": "1"}),
+            json!({"domain": "odd.com", "expireDate": "next year"}),
+        ];
+
+        let due = due(&domains, now, 60);
+        let names: Vec<_> = due.iter().map(|item| item.domain.as_str()).collect();
+        assert_eq!(names, ["gone.com", "soon.com", "later.com"]);
+        assert_eq!(due[0].days_left, -3);
+        assert_eq!(due[0].status, "EXPIRED");
+        assert_eq!(due[1].days_left, 10);
+        assert!(due[1].auto_renew);
+        assert_eq!(due[2].days_left, 50);
+        assert!(!due[2].auto_renew);
+    }
+}
@@ -0,0 +1,152 @@
+//! `dns find`: the zone's records filtered here, so scripts get the ids to
+//! edit or delete without parsing `dns retrieve` themselves.
+//!
+//! Names compare relative to the domain, as in `dns apply`: `www`,
+//! `www.example.com` and `www.example.com.` are the same name and `@` is the
+//! apex. `*` and `?` in `--name` are wildcards, which also makes `*.dev` match
+//! a literal `*.dev` record.
+
+use anyhow::Result;
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::apply::subdomain;
+use crate::{
+    call_api, enc, output_value_list, require_auth_config, validate_domain, validate_record_type,
+    AppError, DnsFindArgs, OutputFlags,
+};
+
+struct Filter {
+    name: Option<Regex>,
+    record_type: Option<String>,
+    content: Option<Regex>,
+}
+
+pub async fn handle(args: &DnsFindArgs, output: &OutputFlags) -> Result<()> {
+    validate_domain(&args.domain)?;
+    let filter = Filter::new(args)?;
+    let cfg = require_auth_config()?;
+    let path = format!("/dns/retrieve/{}", enc(&args.domain));
+    let value = call_api(&path, Map::new(), &cfg, output).await?;
+    let items = value
+        .get("records")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|record| filter.matches(record, &args.domain))
+        .cloned()
+        .collect();
+    output_value_list(output, items)
+}
+
+impl Filter {
+    fn new(args: &DnsFindArgs) -> Result<Self> {
+        if let Some(record_type) = &args.record_type {
+            validate_record_type(record_type)?;
+        }
+        let content = args
+            .content_regex
+            .as_deref()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|err| {
+                    AppError::InvalidArgument(format!("--content-regex `{pattern}`: {err}"))
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            name: args
+                .name
+                .as_deref()
+                .map(|name| glob(&subdomain(name, &args.domain))),
+            record_type: args.record_type.as_deref().map(str::to_ascii_uppercase),
+            content,
+        })
+    }
+
+    fn matches(&self, record: &Value, domain: &str) -> bool {
+        let text = |key: &str| record.get(key).and_then(Value::as_str).unwrap_or_default();
+        self.record_type
+            .as_ref()
+            .is_none_or(|wanted| text("type").eq_ignore_ascii_case(wanted))
+            && self
+                .name
+                .as_ref()
+                .is_none_or(|name| name.is_match(&subdomain(text("name"), domain)))
+            && self
+                .content
+                .as_ref()
+                .is_none_or(|content| content.is_match(text("content")))
+    }
+}
+
+/// `*` for any run of characters, `?` for one; everything else literal.
+fn glob(pattern: &str) -> Regex {
+    let body: String = pattern
+        .chars()
+        .map(|c| match c {
+            '*' => ".*".to_string(),
+            '?' => ".".to_string(),
+            c => regex::escape(&c.to_string()),
+        })
+        .collect();
+    Regex::new(&format!("^{body}$")).expect("escaped glob is a valid regex")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn filter(name: Option<&str>, record_type: Option<&str>, content: Option<&str>) -> Filter {
+        Filter::new(&DnsFindArgs {
+            domain: "example.com".to_string(),
+            name: name.map(str::to_string),
+            record_type: record_type.map(str::to_string),
+            content_regex: content.map(str::to_string),
+        })
+        .unwrap()
+    }
+
+    fn names(filter: &Filter) -> Vec<&'static str> {
+        [
+            "example.com",
+            "www.example.com",
+            "api.dev.example.com",
+            "*.dev.example.com",
+            "dev.example.com",
+        ]
+        .into_iter()
+        .filter(|name| filter.matches(&json!({"name": name, "type": "A"}), "example.com"))
+        .collect()
+    }
+
+    #[test]
+    fn names_are_relative_and_may_be_wildcards() {
+        assert_eq!(names(&filter(Some("@"), None, None)), ["example.com"]);
+        assert_eq!(
+            names(&filter(Some("WWW.example.com."), None, None)),
+            ["www.example.com"]
+        );
+        assert_eq!(
+            names(&filter(Some("*.dev"), None, None)),
+            ["api.dev.example.com", "*.dev.example.com"]
+        );
+        assert_eq!(names(&filter(Some("?ev"), None, None)), ["dev.example.com"]);
+        assert_eq!(names(&filter(None, None, None)).len(), 5);
+    }
+
+    #[test]
+    fn type_and_content_narrow_further() {
+        let spf = json!({"name": "example.com", "type": "TXT", "content": "v=spf1 include:_spf.porkbun.com ~all"});
+        assert!(filter(None, Some("txt"), Some("^v=spf1 ")).matches(&spf, "example.com"));
+        assert!(!filter(None, Some("A"), None).matches(&spf, "example.com"));
+        assert!(!filter(None, None, Some("google")).matches(&spf, "example.com"));
+        assert!(Filter::new(&DnsFindArgs {
+            domain: "example.com".to_string(),
+            name: None,
+            record_type: None,
+            content_regex: Some("(".to_string()),
+        })
+        .is_err());
+    }
+}
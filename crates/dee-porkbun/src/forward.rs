@@ -0,0 +1,219 @@
+//! URL forwards. Porkbun can add, list and delete them but not edit one, so
+//! `edit-url-forward` deletes the forward and adds it back changed; the
+//! replacement gets a new record id.
+
+use anyhow::{Context, Result};
+use dee_core::OkItem;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::apply::subdomain;
+use crate::{
+    call_api, enc, output_value_list, print_json, require_auth_config, require_confirm, to_yes_no,
+    validate_domain, AppConfig, AppError, EditUrlForwardArgs, GetUrlForwardingArgs, OutputFlags,
+};
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UrlForward {
+    /// Empty for the apex
+    pub subdomain: String,
+    pub location: String,
+    /// `temporary` or `permanent`
+    #[serde(rename = "type")]
+    pub forward_type: String,
+    /// `yes` or `no`
+    pub include_path: String,
+    /// `yes` or `no`
+    pub wildcard: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct UrlForwardEdited {
+    domain: String,
+    /// Id of the forward that was deleted; the replacement has a new one
+    replaced_id: String,
+    #[serde(flatten)]
+    forward: UrlForward,
+}
+
+impl UrlForward {
+    /// Checked and normalized flag values.
+    pub fn new(
+        subdomain: &str,
+        location: &str,
+        forward_type: &str,
+        include_path: &str,
+        wildcard: &str,
+    ) -> Result<Self> {
+        let forward_type = match forward_type.to_ascii_lowercase().as_str() {
+            kind @ ("temporary" | "permanent") => kind.to_string(),
+            _ => {
+                return Err(AppError::InvalidArgument(
+                    "--type must be temporary or permanent".to_string(),
+                )
+                .into())
+            }
+        };
+        if !location.starts_with("http://") && !location.starts_with("https://") {
+            return Err(AppError::InvalidArgument(
+                "--location must start with http:// or https://".to_string(),
+            )
+            .into());
+        }
+        Ok(Self {
+            subdomain: subdomain.to_string(),
+            location: location.to_string(),
+            forward_type,
+            include_path: to_yes_no(include_path)?.to_string(),
+            wildcard: to_yes_no(wildcard)?.to_string(),
+        })
+    }
+
+    /// The `addUrlForward` request body.
+    pub fn body(&self) -> Map<String, Value> {
+        let mut body = Map::new();
+        for (key, value) in [
+            ("subdomain", &self.subdomain),
+            ("location", &self.location),
+            ("type", &self.forward_type),
+            ("includePath", &self.include_path),
+            ("wildcard", &self.wildcard),
+        ] {
+            body.insert(key.to_string(), Value::String(value.clone()));
+        }
+        body
+    }
+}
+
+pub async fn add(
+    domain: &str,
+    forward: &UrlForward,
+    cfg: &AppConfig,
+    output: &OutputFlags,
+) -> Result<()> {
+    let path = format!("/domain/addUrlForward/{}", enc(domain));
+    call_api(&path, forward.body(), cfg, output).await.map(drop)
+}
+
+async fn forwards(domain: &str, cfg: &AppConfig, output: &OutputFlags) -> Result<Vec<Value>> {
+    let path = format!("/domain/getUrlForwarding/{}", enc(domain));
+    let value = call_api(&path, Map::new(), cfg, output).await?;
+    Ok(value
+        .get("forwards")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// `get-url-forwarding`, narrowed to one `--subdomain` when given.
+pub async fn list(args: &GetUrlForwardingArgs, output: &OutputFlags) -> Result<()> {
+    validate_domain(&args.domain)?;
+    let cfg = require_auth_config()?;
+    let mut items = forwards(&args.domain, &cfg, output).await?;
+    if let Some(wanted) = &args.subdomain {
+        let wanted = subdomain(wanted, &args.domain);
+        items.retain(|item| {
+            let name = item
+                .get("subdomain")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            subdomain(name, &args.domain) == wanted
+        });
+    }
+    output_value_list(output, items)
+}
+
+pub async fn edit(args: &EditUrlForwardArgs, output: &OutputFlags) -> Result<()> {
+    require_confirm(args.confirm)?;
+    validate_domain(&args.domain)?;
+    if args.record_id.trim().is_empty() {
+        return Err(AppError::InvalidArgument("record_id is required".to_string()).into());
+    }
+    if args.subdomain.is_none()
+        && args.location.is_none()
+        && args.r#type.is_none()
+        && args.include_path.is_none()
+        && args.wildcard.is_none()
+    {
+        return Err(AppError::InvalidArgument(
+            "nothing to change; pass --subdomain, --location, --type, --include-path or --wildcard"
+                .to_string(),
+        )
+        .into());
+    }
+    let cfg = require_auth_config()?;
+    let current = forwards(&args.domain, &cfg, output)
+        .await?
+        .into_iter()
+        .find(|item| id_of(item) == args.record_id.trim())
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "URL forward {} on {}",
+                args.record_id.trim(),
+                args.domain
+            ))
+        })?;
+    let field = |key: &str| {
+        current
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let pick = |flag: &Option<String>, key: &str| flag.clone().unwrap_or_else(|| field(key));
+    let forward = UrlForward::new(
+        &pick(&args.subdomain, "subdomain"),
+        &pick(&args.location, "location"),
+        &pick(&args.r#type, "type"),
+        &pick(&args.include_path, "includePath"),
+        &pick(&args.wildcard, "wildcard"),
+    )?;
+
+    let path = format!(
+        "/domain/deleteUrlForward/{}/{}",
+        enc(&args.domain),
+        enc(args.record_id.trim())
+    );
+    call_api(&path, Map::new(), &cfg, output).await?;
+    add(&args.domain, &forward, &cfg, output)
+        .await
+        .with_context(|| {
+            format!(
+                "URL forward {} was deleted but its replacement was not added; it was \
+             subdomain `{}` -> {} ({}, include path {}, wildcard {})",
+                args.record_id.trim(),
+                field("subdomain"),
+                field("location"),
+                field("type"),
+                field("includePath"),
+                field("wildcard")
+            )
+        })?;
+
+    if output.json {
+        print_json(&OkItem {
+            ok: true,
+            item: UrlForwardEdited {
+                domain: args.domain.clone(),
+                replaced_id: args.record_id.trim().to_string(),
+                forward,
+            },
+        })
+    } else if output.quiet {
+        println!("ok");
+        Ok(())
+    } else {
+        println!("URL forward updated");
+        Ok(())
+    }
+}
+
+/// Porkbun sends ids as strings; numbers are accepted too.
+fn id_of(item: &Value) -> String {
+    match item.get("id") {
+        Some(Value::String(id)) => id.clone(),
+        Some(Value::Number(id)) => id.to_string(),
+        _ => String::new(),
+    }
+}
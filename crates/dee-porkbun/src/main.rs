@@ -1,12 +1,39 @@
+mod apply;
+mod audit;
+mod content;
+mod delegation;
+mod diff;
+mod dnssec;
+mod doh;
+mod expiry;
+mod find;
+mod forward;
+mod ssl;
+mod status;
+
 use std::collections::BTreeMap;
-use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
+use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use clap::{Args, Parser, Subcommand};
+use dee_core::cache::{Cache, CacheArgs, CacheCommand, CacheError};
+use dee_core::format::{render_columns, FormatError};
+use dee_core::http::{base_url, retry_after, retry_delay, Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    exit, report_code, style, Config, ConfigError, ErrorCode, Format, OkItem, OkMessage,
+    SecretsBackend,
+};
+use dee_core::{profile, ratelimit};
+use futures::StreamExt;
+use reqwest::StatusCode;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
 
+const TOOL: &str = "dee-porkbun";
 const API_BASE: &str = "https://api.porkbun.com/api/json/v3";
 
 #[derive(Debug, Parser)]
@@ -15,7 +42,7 @@ const API_BASE: &str = "https://api.porkbun.com/api/json/v3";
     version,
     about = "Porkbun API CLI",
     long_about = "dee-porkbun - Full Porkbun API wrapper with agent-friendly JSON output.",
-    after_help = "EXAMPLES:\n  dee-porkbun config set api_key pk1_xxx\n  dee-porkbun config set secret_key sk1_xxx\n  dee-porkbun domains pricing --tld com --json\n  dee-porkbun domains list-all --json\n  dee-porkbun dns retrieve dee.ink --json\n  dee-porkbun dns create dee.ink --type A --name www --content 1.1.1.1 --confirm --json\n  dee-porkbun dnssec get dee.ink --json\n  dee-porkbun ssl retrieve dee.ink --json"
+    after_help = "EXAMPLES:\n  dee-porkbun config set api_key pk1_xxx\n  dee-porkbun config set secret_key sk1_xxx\n  dee-porkbun domains pricing --tld com --json\n  dee-porkbun domains list-all --json\n  dee-porkbun domains expiring --days 60 --json\n  dee-porkbun dns retrieve dee.ink --json\n  dee-porkbun dns create dee.ink --type A --name www --content 1.1.1.1 --confirm --json\n  dee-porkbun dns apply dee.ink.toml --dry-run --json\n  dee-porkbun dnssec get dee.ink --json\n  dee-porkbun ssl retrieve dee.ink --json\n  dee-porkbun ssl retrieve dee.ink --out-dir /etc/ssl/dee.ink --reload-cmd 'systemctl reload nginx'"
 )]
 struct Cli {
     #[command(flatten)]
@@ -38,6 +65,33 @@ struct OutputFlags {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long = "output", global = true, value_enum)]
+    format: Option<Format>,
+
+    /// Fields to keep in list output, in order, e.g. domain,expireDate
+    #[arg(long, global = true, value_delimiter = ',')]
+    columns: Vec<String>,
+
+    #[command(flatten)]
+    http: HttpArgs,
+
+    /// First wait before retrying a 429/503 answer; doubles on each further retry
+    #[arg(long, global = true, value_name = "MS", default_value_t = 1000)]
+    retry_delay_ms: u64,
+
+    /// Porkbun requests in flight at once when a command needs several
+    #[arg(long, global = true, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..=16))]
+    concurrency: u16,
+
+    #[command(flatten)]
+    cache: CacheArgs,
+
+    /// Reuse cached pricing and listAll answers up to this age (default 1 day
+    /// for pricing, 5 minutes for listAll)
+    #[arg(long, global = true, value_name = "SECS")]
+    cache_ttl: Option<u64>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -52,6 +106,34 @@ enum Commands {
     Dnssec(DnssecArgs),
     /// SSL endpoints
     Ssl(SslArgs),
+    /// Check keys, connectivity and the last known balance in one probe
+    Status,
+    /// Review the log of mutating calls
+    Audit(AuditArgs),
+    /// Manage cached pricing and listAll answers
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(Debug, Args)]
+struct AuditArgs {
+    #[command(subcommand)]
+    command: AuditCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum AuditCommand {
+    /// Mutating calls made from this machine, oldest first
+    List(AuditListArgs),
+}
+
+#[derive(Debug, Args)]
+struct AuditListArgs {
+    /// Only calls since an RFC 3339 time, a date (YYYY-MM-DD, UTC) or an age like 24h or 7d
+    #[arg(long)]
+    since: Option<String>,
 }
 
 #[derive(Debug, Args)]
@@ -62,17 +144,29 @@ struct ConfigArgs {
 
 #[derive(Debug, Subcommand)]
 enum ConfigCommand {
-    /// Set a config value (api_key|secret_key)
+    /// Set a config value (api_key|secret_key|secrets_backend|base_url); with
+    /// --profile, in that profile
     Set(ConfigSetArgs),
     /// Show current config
     Show,
     /// Print config path
     Path,
+    /// Named account profiles, picked with --profile <name>
+    Profiles {
+        #[command(subcommand)]
+        command: ProfilesCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ProfilesCommand {
+    /// List the profiles that hold Porkbun keys
+    List,
 }
 
 #[derive(Debug, Args)]
 struct ConfigSetArgs {
-    /// Config key: api_key or secret_key
+    /// Config key: api_key, secret_key, secrets_backend or base_url
     key: String,
     /// Config value
     value: String,
@@ -96,6 +190,10 @@ enum DomainsCommand {
     Check(CheckArgs),
     /// Register a domain
     Create(CreateDomainArgs),
+    /// Renew a domain for one more year, paid from the account balance
+    Renew(RenewDomainArgs),
+    /// Domains expiring within --days, soonest first
+    Expiring(ExpiringArgs),
     /// Update nameservers
     UpdateNs(UpdateNsArgs),
     /// Get nameservers
@@ -105,7 +203,9 @@ enum DomainsCommand {
     /// Add URL forward
     AddUrlForward(AddUrlForwardArgs),
     /// Get URL forwarding
-    GetUrlForwarding(GetDomainArgs),
+    GetUrlForwarding(GetUrlForwardingArgs),
+    /// Change a URL forward by record id (deleted and added back with a new id)
+    EditUrlForward(EditUrlForwardArgs),
     /// Delete URL forward by record id
     DeleteUrlForward(DeleteUrlForwardArgs),
     /// Create glue host
@@ -140,6 +240,12 @@ enum DnsCommand {
     Retrieve(DnsRetrieveArgs),
     /// Retrieve DNS records by name/type
     RetrieveByNameType(DnsRetrieveByNameTypeArgs),
+    /// Make a domain's records match a TOML/YAML manifest
+    Apply(DnsApplyArgs),
+    /// Compare the live records with a saved `dns retrieve --json` snapshot
+    Diff(DnsDiffArgs),
+    /// Records matching a name (wildcards allowed), type or content pattern
+    Find(DnsFindArgs),
 }
 
 #[derive(Debug, Args)]
@@ -156,6 +262,8 @@ enum DnssecCommand {
     Get(GetDomainArgs),
     /// Delete DNSSEC record by key tag
     Delete(DnssecDeleteArgs),
+    /// Compute DS data from the domain's DNSKEY and create it
+    Wizard(DnssecWizardArgs),
 }
 
 #[derive(Debug, Args)]
@@ -166,8 +274,8 @@ struct SslArgs {
 
 #[derive(Debug, Subcommand)]
 enum SslCommand {
-    /// Retrieve SSL bundle for a domain
-    Retrieve(GetDomainArgs),
+    /// Retrieve SSL bundle for a domain, or install it with --out-dir
+    Retrieve(SslRetrieveArgs),
 }
 
 #[derive(Debug, Args)]
@@ -180,9 +288,21 @@ struct PricingArgs {
 #[derive(Debug, Args)]
 struct ListAllArgs {
     /// Optional start index (chunked by 1000)
-    #[arg(long)]
+    #[arg(long, conflicts_with = "all")]
     start: Option<u64>,
 
+    /// Fetch every chunk and merge them
+    #[arg(long)]
+    all: bool,
+
+    /// Only domains under this TLD, e.g. com
+    #[arg(long)]
+    tld: Option<String>,
+
+    /// Only domains with auto-renew on|off
+    #[arg(long)]
+    auto_renew: Option<String>,
+
     /// Include domain labels
     #[arg(long)]
     include_labels: bool,
@@ -218,6 +338,41 @@ struct CreateDomainArgs {
     confirm: bool,
 }
 
+#[derive(Debug, Args)]
+struct SslRetrieveArgs {
+    /// Domain name
+    domain: String,
+
+    /// Write certificatechain.pem, privatekey.pem (0600) and publickey.pem here
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+
+    /// Shell command to run after a file changed, e.g. `systemctl reload nginx`
+    #[arg(long, requires = "out_dir")]
+    reload_cmd: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct RenewDomainArgs {
+    /// Domain name
+    domain: String,
+
+    /// Renewal cost in pennies from `domains pricing`
+    #[arg(long)]
+    cost: Option<u64>,
+
+    /// Required for mutating commands
+    #[arg(long)]
+    confirm: bool,
+}
+
+#[derive(Debug, Args)]
+struct ExpiringArgs {
+    /// Window in days; already lapsed domains are always listed
+    #[arg(long, default_value_t = 60)]
+    days: u32,
+}
+
 #[derive(Debug, Args)]
 struct UpdateNsArgs {
     /// Domain name
@@ -230,6 +385,22 @@ struct UpdateNsArgs {
     /// Required for mutating commands
     #[arg(long)]
     confirm: bool,
+
+    /// After updating, poll the parent zone until it delegates to the new set
+    #[arg(long)]
+    verify: bool,
+
+    /// Seconds to keep polling with --verify
+    #[arg(long, default_value_t = 300, requires = "verify")]
+    wait: u64,
+
+    /// Parent zone server to ask, `IP` or `IP:port`; found over DNS-over-HTTPS by default
+    #[arg(long, requires = "verify")]
+    parent_server: Option<String>,
+
+    /// DNS-over-HTTPS endpoint used to find the parent zone servers
+    #[arg(long, requires = "verify")]
+    resolver: Option<String>,
 }
 
 #[derive(Debug, Args)]
@@ -279,6 +450,49 @@ struct AddUrlForwardArgs {
     confirm: bool,
 }
 
+#[derive(Debug, Args)]
+struct GetUrlForwardingArgs {
+    /// Domain name
+    domain: String,
+
+    /// Only forwards for this subdomain (`www`, `www.example.com`; `@` for the root)
+    #[arg(long)]
+    subdomain: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct EditUrlForwardArgs {
+    /// Domain name
+    domain: String,
+
+    /// URL forward record id
+    record_id: String,
+
+    /// New subdomain, empty for root
+    #[arg(long)]
+    subdomain: Option<String>,
+
+    /// New destination URL
+    #[arg(long)]
+    location: Option<String>,
+
+    /// temporary|permanent
+    #[arg(long)]
+    r#type: Option<String>,
+
+    /// yes|no
+    #[arg(long)]
+    include_path: Option<String>,
+
+    /// yes|no
+    #[arg(long)]
+    wildcard: Option<String>,
+
+    /// Required for mutating commands
+    #[arg(long)]
+    confirm: bool,
+}
+
 #[derive(Debug, Args)]
 struct DeleteUrlForwardArgs {
     /// Domain name
@@ -475,6 +689,47 @@ struct DnsRetrieveByNameTypeArgs {
     subdomain: Option<String>,
 }
 
+#[derive(Debug, Args)]
+struct DnsDiffArgs {
+    /// Domain name
+    domain: String,
+
+    /// Snapshot file: saved `dns retrieve --json` output
+    snapshot: PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct DnsFindArgs {
+    /// Domain name
+    domain: String,
+
+    /// Record name: `www`, `www.example.com`, `@` for the apex; `*` and `?` match anything
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Record type
+    #[arg(long = "type")]
+    record_type: Option<String>,
+
+    /// Regular expression the content must match
+    #[arg(long)]
+    content_regex: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct DnsApplyArgs {
+    /// Manifest: `domain` and `[[records]]` with type, name, content, ttl, prio, notes
+    file: PathBuf,
+
+    /// Print the planned changes without sending them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Required unless --dry-run
+    #[arg(long)]
+    confirm: bool,
+}
+
 #[derive(Debug, Args)]
 struct DnssecCreateArgs {
     /// Domain name
@@ -505,48 +760,53 @@ struct DnssecCreateArgs {
 }
 
 #[derive(Debug, Args)]
-struct DnssecDeleteArgs {
+struct DnssecWizardArgs {
     /// Domain name
     domain: String,
 
-    /// DNSSEC key tag
-    key_tag: String,
+    /// DNSKEY as `257 3 13 <base64>` (or a zone file line); looked up over
+    /// DNS-over-HTTPS when omitted
+    #[arg(long)]
+    dnskey: Option<String>,
+
+    /// 2 (SHA-256) or 4 (SHA-384)
+    #[arg(long, default_value_t = 2)]
+    digest_type: u8,
+
+    /// DNS-over-HTTPS endpoint answering `application/dns-json`
+    #[arg(long)]
+    resolver: Option<String>,
+
+    /// Print the DS data without sending it
+    #[arg(long)]
+    dry_run: bool,
 
     /// Required for mutating commands
     #[arg(long)]
     confirm: bool,
 }
 
-#[derive(Debug, Serialize)]
-struct SuccessList<T: Serialize> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct SuccessItem<T: Serialize> {
-    ok: bool,
-    item: T,
-}
+#[derive(Debug, Args)]
+struct DnssecDeleteArgs {
+    /// Domain name
+    domain: String,
 
-#[derive(Debug, Serialize)]
-struct SuccessMessage {
-    ok: bool,
-    message: String,
-}
+    /// DNSSEC key tag
+    key_tag: String,
 
-#[derive(Debug, Serialize)]
-struct ErrorJson {
-    ok: bool,
-    error: String,
-    code: String,
+    /// Required for mutating commands
+    #[arg(long)]
+    confirm: bool,
 }
 
 #[derive(Debug, thiserror::Error)]
 enum AppError {
-    #[error("Config directory is unavailable")]
-    ConfigDirUnavailable,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+    #[error(transparent)]
+    Format(#[from] FormatError),
     #[error("Config file not found. Run `dee-porkbun config set api_key <value>` and `dee-porkbun config set secret_key <value>`")]
     ConfigMissing,
     #[error(
@@ -561,121 +821,368 @@ enum AppError {
     RequestFailed(String),
     #[error("Porkbun API error: {0}")]
     ApiError(String),
+    #[error("Rate limited by Porkbun: {0}")]
+    RateLimited(String),
     #[error("Not found: {0}")]
     NotFound(String),
     #[error("Failed to parse API response")]
     ParseFailed,
+    #[error("Could not write {0}")]
+    WriteFailed(String),
+    #[error("Reload command failed: {0}")]
+    ReloadFailed(String),
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
-            Self::ConfigDirUnavailable | Self::ConfigMissing => "CONFIG_MISSING",
+            Self::Config(err) => err.code(),
+            Self::Cache(err) => err.code(),
+            Self::Format(err) => err.code(),
+            Self::ConfigMissing => "CONFIG_MISSING",
             Self::AuthMissing => "AUTH_MISSING",
             Self::InvalidArgument(_) => "INVALID_ARGUMENT",
             Self::ConfirmRequired => "CONFIRM_REQUIRED",
             Self::RequestFailed(_) => "REQUEST_FAILED",
             Self::ApiError(_) => "API_ERROR",
+            Self::RateLimited(_) => "RATE_LIMITED",
             Self::NotFound(_) => "NOT_FOUND",
             Self::ParseFailed => "PARSE_FAILED",
+            Self::WriteFailed(_) => "WRITE_FAILED",
+            Self::ReloadFailed(_) => "RELOAD_FAILED",
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 struct AppConfig {
     #[serde(default)]
     api_key: String,
     #[serde(default)]
     secret_key: String,
+    /// `plaintext` (default) or `keyring`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secrets_backend: Option<SecretsBackend>,
+    /// API root instead of `API_BASE`, e.g. a mock server in tests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_url: Option<String>,
+}
+
+/// `config show`: whether each key is set, never the keys themselves
+#[derive(Debug, Serialize, JsonSchema)]
+struct ConfigView {
+    /// The active `--profile`, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    api_key_set: bool,
+    secret_key_set: bool,
+    secrets_backend: SecretsBackend,
+    base_url: String,
+}
+
+/// `config profiles list`: one account in `profiles.toml`
+#[derive(Debug, Serialize, JsonSchema)]
+struct ProfileView {
+    name: String,
+    active: bool,
+    api_key_set: bool,
+    secret_key_set: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base_url: Option<String>,
 }
 
-fn main() {
-    let cli = parse_cli();
-    if let Err(err) = run(&cli) {
-        if cli.global.json {
-            let payload = ErrorJson {
-                ok: false,
-                error: err.to_string(),
-                code: classify_error_code(&err).to_string(),
-            };
-            if let Ok(out) = serde_json::to_string(&payload) {
-                println!("{out}");
-            } else {
-                println!("{{\"ok\":false,\"error\":\"Internal serialization error\",\"code\":\"INTERNAL_ERROR\"}}");
-            }
-        } else {
-            eprintln!("error: {err:#}");
-        }
-        std::process::exit(1);
+#[derive(Debug, Serialize, JsonSchema)]
+struct ConfigPath {
+    path: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct Pong {
+    status: String,
+    /// The caller's IP as Porkbun sees it
+    message: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct DomainCheck {
+    domain: String,
+    available: bool,
+    price: String,
+    currency: String,
+    /// Porkbun's `response` object as returned
+    response: Value,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct DomainOrder {
+    domain: String,
+    cost: Value,
+    order_id: Value,
+    balance: Value,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct AutoRenewUpdated {
+    status: Value,
+    /// Per-domain results as returned by Porkbun
+    results: Value,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct DnsCreated {
+    id: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct SslBundle {
+    certificatechain: String,
+    privatekey: String,
+    publickey: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct TldPricing {
+    tld: String,
+    registration: String,
+    renewal: String,
+    transfer: String,
+}
+
+/// `ssl retrieve` prints the bundle, or with `--out-dir` what was written.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)] // only described; the variants are printed directly
+enum SslRetrieveOutput {
+    Bundle(OkItem<SslBundle>),
+    Installed(OkItem<ssl::SslInstalled>),
+}
+
+/// `domains update-ns` prints a message, or with `--verify` what the parent
+/// zone delegates to.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)] // only described; the variants are printed directly
+enum UpdateNsOutput {
+    Updated(OkMessage),
+    Verified(OkItem<delegation::NsVerified>),
+}
+
+/// What each command prints under `--json`, for `--schema`. Lists passed
+/// through from the Porkbun API are described as arbitrary JSON.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .message("config set")
+        .item::<ConfigView>("config show")
+        .item::<ConfigPath>("config path")
+        .item::<status::Status>("status")
+        .list::<audit::AuditEntry>("audit list")
+        .cache()
+        .list::<ProfileView>("config profiles list")
+        .item::<Pong>("domains ping")
+        .list::<TldPricing>("domains pricing")
+        .list::<Value>("domains list-all")
+        .item::<DomainCheck>("domains check")
+        .item::<DomainOrder>("domains create")
+        .item::<DomainOrder>("domains renew")
+        .list::<expiry::ExpiringDomain>("domains expiring")
+        .custom::<UpdateNsOutput>("domains update-ns")
+        .list::<String>("domains get-ns")
+        .item::<AutoRenewUpdated>("domains update-auto-renew")
+        .message("domains add-url-forward")
+        .list::<Value>("domains get-url-forwarding")
+        .item::<forward::UrlForwardEdited>("domains edit-url-forward")
+        .message("domains delete-url-forward")
+        .message("domains create-glue")
+        .message("domains update-glue")
+        .message("domains delete-glue")
+        .list::<Value>("domains get-glue")
+        .item::<DnsCreated>("dns create")
+        .message("dns edit")
+        .message("dns edit-by-name-type")
+        .message("dns delete")
+        .message("dns delete-by-name-type")
+        .list::<Value>("dns retrieve")
+        .list::<Value>("dns retrieve-by-name-type")
+        .item::<apply::ApplyPlan>("dns apply")
+        .item::<diff::DnsDiff>("dns diff")
+        .list::<Value>("dns find")
+        .message("dnssec create")
+        .item::<Value>("dnssec get")
+        .message("dnssec delete")
+        .item::<dnssec::DsRecord>("dnssec wizard")
+        .custom::<SslRetrieveOutput>("ssl retrieve")
+}
+
+#[tokio::main]
+async fn main() {
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
+    if let Err(err) = run(&cli).await {
+        let code = classify_error_code(&err);
+        report_code(
+            &format!("{err:#}"),
+            code,
+            Format::json_errors(cli.global.format, cli.global.json),
+        );
+        std::process::exit(exit::for_code(code));
     }
 }
 
-fn run(cli: &Cli) -> Result<()> {
+async fn run(cli: &Cli) -> Result<()> {
     match &cli.command {
         Commands::Config(args) => handle_config(args, &cli.global),
-        Commands::Domains(args) => handle_domains(args, &cli.global),
-        Commands::Dns(args) => handle_dns(args, &cli.global),
-        Commands::Dnssec(args) => handle_dnssec(args, &cli.global),
-        Commands::Ssl(args) => handle_ssl(args, &cli.global),
+        Commands::Domains(args) => handle_domains(args, &cli.global).await,
+        Commands::Dns(args) => handle_dns(args, &cli.global).await,
+        Commands::Dnssec(args) => handle_dnssec(args, &cli.global).await,
+        Commands::Ssl(args) => handle_ssl(args, &cli.global).await,
+        Commands::Status => status::handle(&cli.global).await,
+        Commands::Audit(args) => match &args.command {
+            AuditCommand::List(list_args) => audit::list(list_args, &cli.global),
+        },
+        Commands::Cache { command } => Ok(dee_core::cache::run(
+            &Cache::new(TOOL),
+            *command,
+            cli.global.json,
+        )?),
     }
 }
 
 fn handle_config(args: &ConfigArgs, output: &OutputFlags) -> Result<()> {
     match &args.command {
         ConfigCommand::Set(set_args) => {
-            let mut cfg = load_config_or_default()?;
+            if let Some(name) = profile::active() {
+                return set_profile_key(&name, set_args, output);
+            }
+            let config = config_file();
+            let mut cfg = config.load_file().map_err(AppError::from)?;
             match set_args.key.as_str() {
                 "api_key" => cfg.api_key = set_args.value.clone(),
                 "secret_key" => cfg.secret_key = set_args.value.clone(),
+                "secrets_backend" => {
+                    cfg.secrets_backend =
+                        Some(set_args.value.parse().map_err(AppError::InvalidArgument)?)
+                }
+                "base_url" => cfg.base_url = Some(set_args.value.clone()),
                 other => {
                     return Err(AppError::InvalidArgument(format!(
-                        "unknown config key `{other}`; expected api_key|secret_key"
+                        "unknown config key `{other}`; expected api_key|secret_key|secrets_backend|base_url"
                     ))
                     .into())
                 }
             }
-            save_config(&cfg)?;
+            config.save(&cfg).map_err(AppError::from)?;
             output_action(output, &format!("Set {}", set_args.key))
         }
         ConfigCommand::Show => {
-            let cfg = load_config_or_default()?;
-            let item = serde_json::json!({
-                "api_key_set": !cfg.api_key.is_empty(),
-                "secret_key_set": !cfg.secret_key.is_empty(),
-            });
+            let cfg = config_file().load().map_err(AppError::from)?;
+            let item = ConfigView {
+                profile: profile::active(),
+                api_key_set: !cfg.api_key.is_empty(),
+                secret_key_set: !cfg.secret_key.is_empty(),
+                secrets_backend: cfg.secrets_backend.unwrap_or_default(),
+                base_url: base_url(cfg.base_url.as_deref(), API_BASE),
+            };
             if output.json {
-                print_json(&SuccessItem { ok: true, item })
+                print_json(&OkItem { ok: true, item })
             } else {
+                if let Some(name) = &item.profile {
+                    println!("profile={name}");
+                }
                 println!("api_key_set={}", !cfg.api_key.is_empty());
                 println!("secret_key_set={}", !cfg.secret_key.is_empty());
+                println!(
+                    "secrets_backend={}",
+                    cfg.secrets_backend.unwrap_or_default().as_str()
+                );
+                println!("base_url={}", base_url(cfg.base_url.as_deref(), API_BASE));
                 Ok(())
             }
         }
         ConfigCommand::Path => {
-            let path = config_path()?;
+            let path = config_file().path();
             if output.json {
-                let item = serde_json::json!({ "path": path.display().to_string() });
-                print_json(&SuccessItem { ok: true, item })
+                let item = ConfigPath {
+                    path: path.display().to_string(),
+                };
+                print_json(&OkItem { ok: true, item })
             } else {
                 println!("{}", path.display());
                 Ok(())
             }
         }
+        ConfigCommand::Profiles {
+            command: ProfilesCommand::List,
+        } => {
+            let active = profile::active();
+            let items = profile::for_tool(TOOL)
+                .map_err(AppError::from)?
+                .into_iter()
+                .map(|(name, keys)| {
+                    let set = |key: &str| {
+                        keys.get(key)
+                            .and_then(|value| value.as_str())
+                            .is_some_and(|value| !value.is_empty())
+                    };
+                    serde_json::to_value(ProfileView {
+                        active: active.as_deref() == Some(name.as_str()),
+                        api_key_set: set("api_key"),
+                        secret_key_set: set("secret_key"),
+                        base_url: keys
+                            .get("base_url")
+                            .and_then(|value| value.as_str())
+                            .map(str::to_string),
+                        name,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            output_value_list(output, items)
+        }
     }
 }
 
-fn handle_domains(args: &DomainsArgs, output: &OutputFlags) -> Result<()> {
+/// `config set` under `--profile`: the key goes to `[<name>.dee-porkbun]` in
+/// `profiles.toml`, in plain text.
+fn set_profile_key(name: &str, set_args: &ConfigSetArgs, output: &OutputFlags) -> Result<()> {
+    match set_args.key.as_str() {
+        "api_key" | "secret_key" | "base_url" => {}
+        "secrets_backend" => {
+            return Err(AppError::InvalidArgument(
+                "secrets_backend belongs to config.toml; set it without --profile".to_string(),
+            )
+            .into())
+        }
+        other => {
+            return Err(AppError::InvalidArgument(format!(
+                "unknown config key `{other}`; expected api_key|secret_key|base_url"
+            ))
+            .into())
+        }
+    }
+    profile::set_key(
+        name,
+        TOOL,
+        &set_args.key,
+        toml::Value::String(set_args.value.clone()),
+    )
+    .map_err(AppError::from)?;
+    output_action(output, &format!("Set {} in profile {name}", set_args.key))
+}
+
+async fn handle_domains(args: &DomainsArgs, output: &OutputFlags) -> Result<()> {
     match &args.command {
         DomainsCommand::Ping => {
             let cfg = require_auth_config()?;
-            let value = call_api("/ping", Map::new(), Some(&cfg), output.verbose)?;
-            let item = serde_json::json!({
-                "status": "ok",
-                "message": value.get("yourIp").and_then(Value::as_str).unwrap_or("pong")
-            });
+            let value = call_api("/ping", Map::new(), &cfg, output).await?;
+            let item = Pong {
+                status: "ok".to_string(),
+                message: value
+                    .get("yourIp")
+                    .and_then(Value::as_str)
+                    .unwrap_or("pong")
+                    .to_string(),
+            };
             if output.json {
-                print_json(&SuccessItem { ok: true, item })
+                print_json(&OkItem { ok: true, item })
             } else if output.quiet {
                 println!("ok");
                 Ok(())
@@ -684,65 +1191,73 @@ fn handle_domains(args: &DomainsArgs, output: &OutputFlags) -> Result<()> {
                 Ok(())
             }
         }
-        DomainsCommand::Pricing(pricing_args) => handle_pricing(pricing_args, output),
+        DomainsCommand::Pricing(pricing_args) => handle_pricing(pricing_args, output).await,
         DomainsCommand::ListAll(list_args) => {
+            let auto_renew = list_args
+                .auto_renew
+                .as_deref()
+                .map(to_on_off)
+                .transpose()?
+                .map(|status| status == "on");
+            let tld = list_args
+                .tld
+                .as_deref()
+                .map(|tld| tld.trim().trim_start_matches('.').to_ascii_lowercase());
             let cfg = require_auth_config()?;
-            let mut body = Map::new();
-            if let Some(start) = list_args.start {
-                body.insert("start".to_string(), Value::String(start.to_string()));
-            }
-            if list_args.include_labels {
-                body.insert(
-                    "includeLabels".to_string(),
-                    Value::String("yes".to_string()),
-                );
-            }
-            let value = call_api("/domain/listAll", body, Some(&cfg), output.verbose)?;
-            let items = value
-                .get("domains")
-                .and_then(Value::as_array)
-                .cloned()
-                .unwrap_or_default();
+            let mut items = if list_args.all {
+                list_all_domains(&cfg, list_args.include_labels, output).await?
+            } else {
+                list_domains(
+                    &cfg,
+                    list_args.start.unwrap_or(0),
+                    list_args.include_labels,
+                    output,
+                )
+                .await?
+            };
+            items.retain(|domain| {
+                tld.as_ref().is_none_or(|tld| domain_tld(domain) == *tld)
+                    && auto_renew
+                        .is_none_or(|on| domain.get("autoRenew").is_some_and(parse_boolish) == on)
+            });
             output_value_list(output, items)
         }
         DomainsCommand::Check(check_args) => {
             validate_domain(&check_args.domain)?;
             let cfg = require_auth_config()?;
             let path = format!("/domain/checkDomain/{}", enc(&check_args.domain));
-            let value = call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
+            let value = call_api(&path, Map::new(), &cfg, output).await?;
             let response = value
                 .get("response")
                 .cloned()
                 .unwrap_or_else(|| serde_json::json!({}));
-            let item = serde_json::json!({
-                "domain": check_args.domain,
-                "available": parse_available(&value),
-                "price": find_first_string(&value, &["price", "cost", "priceAmount"]),
-                "currency": find_first_string(&value, &["currency", "currencySymbol"]),
-                "response": response,
-            });
+            let item = DomainCheck {
+                domain: check_args.domain.clone(),
+                available: parse_available(&value),
+                price: find_first_string(&value, &["price", "cost", "priceAmount"]),
+                currency: find_first_string(&value, &["currency", "currencySymbol"]),
+                response,
+            };
             if output.json {
-                print_json(&SuccessItem { ok: true, item })
+                print_json(&OkItem { ok: true, item })
             } else if output.quiet {
-                println!(
-                    "{}",
-                    item.get("available")
-                        .and_then(Value::as_bool)
-                        .unwrap_or(false)
-                );
+                println!("{}", item.available);
                 Ok(())
             } else {
-                println!("domain: {}", check_args.domain);
+                let available = item.available;
                 println!(
-                    "available: {}",
-                    item.get("available")
-                        .and_then(Value::as_bool)
-                        .unwrap_or(false)
+                    "{}",
+                    style::header(format!("domain: {}", check_args.domain))
                 );
                 println!(
-                    "price: {}",
-                    item.get("price").and_then(Value::as_str).unwrap_or("")
+                    "available: {}",
+                    if available {
+                        style::good(available)
+                    } else {
+                        style::bad(available)
+                    }
                 );
+                println!("price: {}", item.price);
                 Ok(())
             }
         }
@@ -763,19 +1278,33 @@ fn handle_domains(args: &DomainsArgs, output: &OutputFlags) -> Result<()> {
             body.insert("cost".to_string(), Value::Number(cost.into()));
             body.insert("agreeToTerms".to_string(), Value::String("yes".to_string()));
             let path = format!("/domain/create/{}", enc(&create_args.domain));
-            let value = call_api(&path, body, Some(&cfg), output.verbose)?;
-            let item = serde_json::json!({
-                "domain": value.get("domain").and_then(Value::as_str).unwrap_or(create_args.domain.as_str()),
-                "cost": value.get("cost").cloned().unwrap_or(Value::Number(cost.into())),
-                "order_id": value.get("orderId").cloned().unwrap_or_else(|| Value::String(String::new())),
-                "balance": value.get("balance").cloned().unwrap_or_else(|| Value::String(String::new())),
-            });
+            let value = call_api(&path, body, &cfg, output).await?;
+            let item = domain_order(&value, &create_args.domain, cost);
             if output.json {
-                print_json(&SuccessItem { ok: true, item })
+                print_json(&OkItem { ok: true, item })
             } else {
                 output_action(output, "Domain create request accepted")
             }
         }
+        DomainsCommand::Renew(renew_args) => {
+            require_confirm(renew_args.confirm)?;
+            validate_domain(&renew_args.domain)?;
+            let cost = renew_args
+                .cost
+                .ok_or_else(|| AppError::InvalidArgument("--cost is required".to_string()))?;
+            let cfg = require_auth_config()?;
+            let mut body = Map::new();
+            body.insert("cost".to_string(), Value::Number(cost.into()));
+            let path = format!("/domain/renew/{}", enc(&renew_args.domain));
+            let value = call_api(&path, body, &cfg, output).await?;
+            let item = domain_order(&value, &renew_args.domain, cost);
+            if output.json {
+                print_json(&OkItem { ok: true, item })
+            } else {
+                output_action(output, "Domain renew request accepted")
+            }
+        }
+        DomainsCommand::Expiring(expiring_args) => expiry::handle(expiring_args, output).await,
         DomainsCommand::UpdateNs(update_args) => {
             require_confirm(update_args.confirm)?;
             validate_domain(&update_args.domain)?;
@@ -797,14 +1326,18 @@ fn handle_domains(args: &DomainsArgs, output: &OutputFlags) -> Result<()> {
                 ),
             );
             let path = format!("/domain/updateNs/{}", enc(&update_args.domain));
-            call_api(&path, body, Some(&cfg), output.verbose)?;
-            output_action(output, "Nameservers updated")
+            call_api(&path, body, &cfg, output).await?;
+            if update_args.verify {
+                delegation::handle(update_args, output).await
+            } else {
+                output_action(output, "Nameservers updated")
+            }
         }
         DomainsCommand::GetNs(get_args) => {
             validate_domain(&get_args.domain)?;
             let cfg = require_auth_config()?;
             let path = format!("/domain/getNs/{}", enc(&get_args.domain));
-            let value = call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
+            let value = call_api(&path, Map::new(), &cfg, output).await?;
             let items = value
                 .get("ns")
                 .and_then(Value::as_array)
@@ -821,92 +1354,86 @@ fn handle_domains(args: &DomainsArgs, output: &OutputFlags) -> Result<()> {
                 .into());
             }
             let status = to_on_off(&auto_args.status)?;
-            let cfg = require_auth_config()?;
-            let mut body = Map::new();
-            body.insert("status".to_string(), Value::String(status.to_string()));
-            if !auto_args.domains.is_empty() {
-                body.insert(
-                    "domains".to_string(),
-                    Value::Array(
-                        auto_args
-                            .domains
-                            .iter()
-                            .map(|x| Value::String(x.clone()))
-                            .collect(),
-                    ),
-                );
+            let mut domains: Vec<String> = auto_args.domain.iter().cloned().collect();
+            for domain in &auto_args.domains {
+                if !domains.contains(domain) {
+                    domains.push(domain.clone());
+                }
             }
-            let path = if let Some(domain) = &auto_args.domain {
+            for domain in &domains {
                 validate_domain(domain)?;
-                format!("/domain/updateAutoRenew/{}", enc(domain))
+            }
+            let cfg = require_auth_config()?;
+            let update = |domain: &str| {
+                let mut body = Map::new();
+                body.insert("status".to_string(), Value::String(status.to_string()));
+                let path = format!("/domain/updateAutoRenew/{}", enc(domain));
+                let cfg = &cfg;
+                async move { call_api(&path, body, cfg, output).await }
+            };
+            let item = if let [domain] = domains.as_slice() {
+                let value = update(domain).await?;
+                AutoRenewUpdated {
+                    status: value
+                        .get("status")
+                        .cloned()
+                        .unwrap_or(Value::String("SUCCESS".to_string())),
+                    results: value
+                        .get("results")
+                        .cloned()
+                        .unwrap_or_else(|| serde_json::json!({})),
+                }
             } else {
-                "/domain/updateAutoRenew".to_string()
+                // One call per domain so a domain Porkbun refuses does not hold up the rest
+                let mut results = Map::new();
+                for (domain, outcome) in per_domain(&domains, output, update).await {
+                    let result = match outcome {
+                        Ok(_) => serde_json::json!({"status": "SUCCESS", "message": ""}),
+                        Err(err) => {
+                            serde_json::json!({"status": "ERROR", "message": err.to_string()})
+                        }
+                    };
+                    results.insert(domain.to_string(), result);
+                }
+                AutoRenewUpdated {
+                    status: Value::String("SUCCESS".to_string()),
+                    results: Value::Object(results),
+                }
             };
-            let value = call_api(&path, body, Some(&cfg), output.verbose)?;
-            let item = serde_json::json!({
-                "status": value.get("status").cloned().unwrap_or(Value::String("SUCCESS".to_string())),
-                "results": value.get("results").cloned().unwrap_or_else(|| serde_json::json!({}))
-            });
             if output.json {
-                print_json(&SuccessItem { ok: true, item })
+                print_json(&OkItem { ok: true, item })
+            } else if output.quiet {
+                println!("ok");
+                Ok(())
             } else {
-                output_action(output, "Auto-renew updated")
+                println!("Auto-renew updated");
+                if let Value::Object(results) = &item.results {
+                    for (domain, result) in results {
+                        if result.get("status").and_then(Value::as_str) == Some("ERROR") {
+                            let message = result.get("message").and_then(Value::as_str);
+                            eprintln!("{domain}: {}", message.unwrap_or_default());
+                        }
+                    }
+                }
+                Ok(())
             }
         }
         DomainsCommand::AddUrlForward(forward_args) => {
             require_confirm(forward_args.confirm)?;
             validate_domain(&forward_args.domain)?;
-            let forward_type = match forward_args.r#type.to_ascii_lowercase().as_str() {
-                "temporary" | "permanent" => forward_args.r#type.to_ascii_lowercase(),
-                _ => {
-                    return Err(AppError::InvalidArgument(
-                        "--type must be temporary or permanent".to_string(),
-                    )
-                    .into())
-                }
-            };
-            let include_path = to_yes_no(&forward_args.include_path)?;
-            let wildcard = to_yes_no(&forward_args.wildcard)?;
-            if !forward_args.location.starts_with("http://")
-                && !forward_args.location.starts_with("https://")
-            {
-                return Err(AppError::InvalidArgument(
-                    "--location must start with http:// or https://".to_string(),
-                )
-                .into());
-            }
+            let forward = forward::UrlForward::new(
+                &forward_args.subdomain,
+                &forward_args.location,
+                &forward_args.r#type,
+                &forward_args.include_path,
+                &forward_args.wildcard,
+            )?;
             let cfg = require_auth_config()?;
-            let mut body = Map::new();
-            body.insert(
-                "subdomain".to_string(),
-                Value::String(forward_args.subdomain.clone()),
-            );
-            body.insert(
-                "location".to_string(),
-                Value::String(forward_args.location.clone()),
-            );
-            body.insert("type".to_string(), Value::String(forward_type));
-            body.insert(
-                "includePath".to_string(),
-                Value::String(include_path.to_string()),
-            );
-            body.insert("wildcard".to_string(), Value::String(wildcard.to_string()));
-            let path = format!("/domain/addUrlForward/{}", enc(&forward_args.domain));
-            call_api(&path, body, Some(&cfg), output.verbose)?;
+            forward::add(&forward_args.domain, &forward, &cfg, output).await?;
             output_action(output, "URL forward added")
         }
-        DomainsCommand::GetUrlForwarding(get_args) => {
-            validate_domain(&get_args.domain)?;
-            let cfg = require_auth_config()?;
-            let path = format!("/domain/getUrlForwarding/{}", enc(&get_args.domain));
-            let value = call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
-            let items = value
-                .get("forwards")
-                .and_then(Value::as_array)
-                .cloned()
-                .unwrap_or_default();
-            output_value_list(output, items)
-        }
+        DomainsCommand::GetUrlForwarding(get_args) => forward::list(get_args, output).await,
+        DomainsCommand::EditUrlForward(edit_args) => forward::edit(edit_args, output).await,
         DomainsCommand::DeleteUrlForward(delete_args) => {
             require_confirm(delete_args.confirm)?;
             validate_domain(&delete_args.domain)?;
@@ -919,11 +1446,11 @@ fn handle_domains(args: &DomainsArgs, output: &OutputFlags) -> Result<()> {
                 enc(&delete_args.domain),
                 enc(&delete_args.record_id)
             );
-            call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
+            call_api(&path, Map::new(), &cfg, output).await?;
             output_action(output, "URL forward deleted")
         }
-        DomainsCommand::CreateGlue(glue_args) => handle_glue_upsert(glue_args, output, true),
-        DomainsCommand::UpdateGlue(glue_args) => handle_glue_upsert(glue_args, output, false),
+        DomainsCommand::CreateGlue(glue_args) => handle_glue_upsert(glue_args, output, true).await,
+        DomainsCommand::UpdateGlue(glue_args) => handle_glue_upsert(glue_args, output, false).await,
         DomainsCommand::DeleteGlue(delete_args) => {
             require_confirm(delete_args.confirm)?;
             validate_domain(&delete_args.domain)?;
@@ -934,14 +1461,14 @@ fn handle_domains(args: &DomainsArgs, output: &OutputFlags) -> Result<()> {
                 enc(&delete_args.domain),
                 enc(&delete_args.host)
             );
-            call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
+            call_api(&path, Map::new(), &cfg, output).await?;
             output_action(output, "Glue record deleted")
         }
         DomainsCommand::GetGlue(get_args) => {
             validate_domain(&get_args.domain)?;
             let cfg = require_auth_config()?;
             let path = format!("/domain/getGlue/{}", enc(&get_args.domain));
-            let value = call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
+            let value = call_api(&path, Map::new(), &cfg, output).await?;
             let hosts = value
                 .get("hosts")
                 .and_then(Value::as_array)
@@ -952,7 +1479,7 @@ fn handle_domains(args: &DomainsArgs, output: &OutputFlags) -> Result<()> {
     }
 }
 
-fn handle_dns(args: &DnsArgs, output: &OutputFlags) -> Result<()> {
+async fn handle_dns(args: &DnsArgs, output: &OutputFlags) -> Result<()> {
     match &args.command {
         DnsCommand::Create(create_args) => {
             require_confirm(create_args.confirm)?;
@@ -967,12 +1494,16 @@ fn handle_dns(args: &DnsArgs, output: &OutputFlags) -> Result<()> {
                 create_args.notes.clone(),
             )?;
             let path = format!("/dns/create/{}", enc(&create_args.domain));
-            let value = call_api(&path, std::mem::take(&mut body), Some(&cfg), output.verbose)?;
-            let item = serde_json::json!({
-                "id": value.get("id").and_then(Value::as_str).unwrap_or(""),
-            });
+            let value = call_api(&path, std::mem::take(&mut body), &cfg, output).await?;
+            let item = DnsCreated {
+                id: value
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string(),
+            };
             if output.json {
-                print_json(&SuccessItem { ok: true, item })
+                print_json(&OkItem { ok: true, item })
             } else {
                 output_action(output, "DNS record created")
             }
@@ -995,7 +1526,7 @@ fn handle_dns(args: &DnsArgs, output: &OutputFlags) -> Result<()> {
                 enc(&edit_args.domain),
                 enc(&edit_args.record_id)
             );
-            call_api(&path, std::mem::take(&mut body), Some(&cfg), output.verbose)?;
+            call_api(&path, std::mem::take(&mut body), &cfg, output).await?;
             output_action(output, "DNS record updated")
         }
         DnsCommand::EditByNameType(edit_args) => {
@@ -1023,7 +1554,7 @@ fn handle_dns(args: &DnsArgs, output: &OutputFlags) -> Result<()> {
                 &edit_args.record_type,
                 edit_args.subdomain.as_deref(),
             );
-            call_api(&path, body, Some(&cfg), output.verbose)?;
+            call_api(&path, body, &cfg, output).await?;
             output_action(output, "DNS records updated")
         }
         DnsCommand::Delete(delete_args) => {
@@ -1036,7 +1567,7 @@ fn handle_dns(args: &DnsArgs, output: &OutputFlags) -> Result<()> {
                 enc(&delete_args.domain),
                 enc(&delete_args.record_id)
             );
-            call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
+            call_api(&path, Map::new(), &cfg, output).await?;
             output_action(output, "DNS record deleted")
         }
         DnsCommand::DeleteByNameType(delete_args) => {
@@ -1050,7 +1581,7 @@ fn handle_dns(args: &DnsArgs, output: &OutputFlags) -> Result<()> {
                 &delete_args.record_type,
                 delete_args.subdomain.as_deref(),
             );
-            call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
+            call_api(&path, Map::new(), &cfg, output).await?;
             output_action(output, "DNS records deleted")
         }
         DnsCommand::Retrieve(retrieve_args) => {
@@ -1065,7 +1596,7 @@ fn handle_dns(args: &DnsArgs, output: &OutputFlags) -> Result<()> {
             } else {
                 format!("/dns/retrieve/{}", enc(&retrieve_args.domain))
             };
-            let value = call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
+            let value = call_api(&path, Map::new(), &cfg, output).await?;
             let items = value
                 .get("records")
                 .and_then(Value::as_array)
@@ -1083,7 +1614,7 @@ fn handle_dns(args: &DnsArgs, output: &OutputFlags) -> Result<()> {
                 &retrieve_args.record_type,
                 retrieve_args.subdomain.as_deref(),
             );
-            let value = call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
+            let value = call_api(&path, Map::new(), &cfg, output).await?;
             let items = value
                 .get("records")
                 .and_then(Value::as_array)
@@ -1091,10 +1622,13 @@ fn handle_dns(args: &DnsArgs, output: &OutputFlags) -> Result<()> {
                 .unwrap_or_default();
             output_value_list(output, items)
         }
+        DnsCommand::Apply(apply_args) => apply::handle(apply_args, output).await,
+        DnsCommand::Diff(diff_args) => diff::handle(diff_args, output).await,
+        DnsCommand::Find(find_args) => find::handle(find_args, output).await,
     }
 }
 
-fn handle_dnssec(args: &DnssecArgs, output: &OutputFlags) -> Result<()> {
+async fn handle_dnssec(args: &DnssecArgs, output: &OutputFlags) -> Result<()> {
     match &args.command {
         DnssecCommand::Create(create_args) => {
             require_confirm(create_args.confirm)?;
@@ -1136,21 +1670,21 @@ fn handle_dnssec(args: &DnssecArgs, output: &OutputFlags) -> Result<()> {
             );
 
             let path = format!("/dns/createDnssecRecord/{}", enc(&create_args.domain));
-            call_api(&path, body, Some(&cfg), output.verbose)?;
+            call_api(&path, body, &cfg, output).await?;
             output_action(output, "DNSSEC record created")
         }
         DnssecCommand::Get(get_args) => {
             validate_domain(&get_args.domain)?;
             let cfg = require_auth_config()?;
             let path = format!("/dns/getDnssecRecords/{}", enc(&get_args.domain));
-            let value = call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
+            let value = call_api(&path, Map::new(), &cfg, output).await?;
             let item = value
                 .get("records")
                 .filter(|v| !v.is_null())
                 .cloned()
                 .unwrap_or_else(|| serde_json::json!({}));
             if output.json {
-                print_json(&SuccessItem { ok: true, item })
+                print_json(&OkItem { ok: true, item })
             } else if output.quiet {
                 println!("{}", serde_json::to_string(&item)?);
                 Ok(())
@@ -1159,6 +1693,7 @@ fn handle_dnssec(args: &DnssecArgs, output: &OutputFlags) -> Result<()> {
                 Ok(())
             }
         }
+        DnssecCommand::Wizard(wizard_args) => dnssec::handle(wizard_args, output).await,
         DnssecCommand::Delete(delete_args) => {
             require_confirm(delete_args.confirm)?;
             validate_domain(&delete_args.domain)?;
@@ -1169,50 +1704,73 @@ fn handle_dnssec(args: &DnssecArgs, output: &OutputFlags) -> Result<()> {
                 enc(&delete_args.domain),
                 enc(&delete_args.key_tag)
             );
-            call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
+            call_api(&path, Map::new(), &cfg, output).await?;
             output_action(output, "DNSSEC record deleted")
         }
     }
 }
 
-fn handle_ssl(args: &SslArgs, output: &OutputFlags) -> Result<()> {
+async fn handle_ssl(args: &SslArgs, output: &OutputFlags) -> Result<()> {
     match &args.command {
         SslCommand::Retrieve(retrieve_args) => {
             validate_domain(&retrieve_args.domain)?;
             let cfg = require_auth_config()?;
             let path = format!("/ssl/retrieve/{}", enc(&retrieve_args.domain));
-            let value = call_api(&path, Map::new(), Some(&cfg), output.verbose)?;
-            let item = serde_json::json!({
-                "certificatechain": value.get("certificatechain").and_then(Value::as_str).unwrap_or(""),
-                "privatekey": value.get("privatekey").and_then(Value::as_str).unwrap_or(""),
-                "publickey": value.get("publickey").and_then(Value::as_str).unwrap_or(""),
-            });
+            let value = call_api(&path, Map::new(), &cfg, output).await?;
+            let field = |key: &str| {
+                value
+                    .get(key)
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string()
+            };
+            let item = SslBundle {
+                certificatechain: field("certificatechain"),
+                privatekey: field("privatekey"),
+                publickey: field("publickey"),
+            };
+            if let Some(dir) = &retrieve_args.out_dir {
+                let item = ssl::install(
+                    &retrieve_args.domain,
+                    &item,
+                    dir,
+                    retrieve_args.reload_cmd.as_deref(),
+                )?;
+                return if output.json {
+                    print_json(&OkItem { ok: true, item })
+                } else if output.quiet {
+                    println!("{}", item.dir);
+                    Ok(())
+                } else {
+                    let state = if item.changed { "updated" } else { "unchanged" };
+                    println!("SSL bundle for {} {state} in {}", item.domain, item.dir);
+                    if item.reloaded {
+                        println!("reload command ran");
+                    }
+                    Ok(())
+                };
+            }
             if output.json {
-                print_json(&SuccessItem { ok: true, item })
+                print_json(&OkItem { ok: true, item })
             } else if output.quiet {
                 println!("{}", retrieve_args.domain);
                 Ok(())
             } else {
                 println!("SSL bundle retrieved for {}", retrieve_args.domain);
-                println!(
-                    "certificatechain: {} bytes",
-                    item["certificatechain"].as_str().unwrap_or("").len()
-                );
-                println!(
-                    "privatekey: {} bytes",
-                    item["privatekey"].as_str().unwrap_or("").len()
-                );
-                println!(
-                    "publickey: {} bytes",
-                    item["publickey"].as_str().unwrap_or("").len()
-                );
+                println!("certificatechain: {} bytes", item.certificatechain.len());
+                println!("privatekey: {} bytes", item.privatekey.len());
+                println!("publickey: {} bytes", item.publickey.len());
                 Ok(())
             }
         }
     }
 }
 
-fn handle_glue_upsert(args: &GlueUpsertArgs, output: &OutputFlags, create: bool) -> Result<()> {
+async fn handle_glue_upsert(
+    args: &GlueUpsertArgs,
+    output: &OutputFlags,
+    create: bool,
+) -> Result<()> {
     require_confirm(args.confirm)?;
     validate_domain(&args.domain)?;
     validate_non_empty("host", &args.host)?;
@@ -1232,7 +1790,7 @@ fn handle_glue_upsert(args: &GlueUpsertArgs, output: &OutputFlags, create: bool)
         enc(&args.domain),
         enc(&args.host)
     );
-    call_api(&path, body, Some(&cfg), output.verbose)?;
+    call_api(&path, body, &cfg, output).await?;
     if create {
         output_action(output, "Glue record created")
     } else {
@@ -1240,15 +1798,10 @@ fn handle_glue_upsert(args: &GlueUpsertArgs, output: &OutputFlags, create: bool)
     }
 }
 
-fn handle_pricing(args: &PricingArgs, output: &OutputFlags) -> Result<()> {
-    let cfg = load_config_or_default()?;
-    let auth = if cfg.api_key.is_empty() || cfg.secret_key.is_empty() {
-        None
-    } else {
-        Some(cfg)
-    };
-
-    let value = call_api("/pricing/get", Map::new(), auth.as_ref(), output.verbose)?;
+async fn handle_pricing(args: &PricingArgs, output: &OutputFlags) -> Result<()> {
+    // Pricing works without keys; they are sent when set
+    let cfg = config_file().load().map_err(AppError::from)?;
+    let value = call_api_cached("/pricing/get", Map::new(), &cfg, output, PRICING_TTL_SECS).await?;
     let pricing = value
         .get("pricing")
         .and_then(Value::as_object)
@@ -1257,55 +1810,54 @@ fn handle_pricing(args: &PricingArgs, output: &OutputFlags) -> Result<()> {
     let mut items = Vec::new();
     for (tld, row) in pricing {
         let map = row.as_object().cloned().unwrap_or_default();
-        items.push(serde_json::json!({
-            "tld": tld,
-            "registration": map.get("registration").and_then(Value::as_str).unwrap_or(""),
-            "renewal": map.get("renewal").and_then(Value::as_str).unwrap_or(""),
-            "transfer": map.get("transfer").and_then(Value::as_str).unwrap_or(""),
-        }));
+        let field = |key: &str| {
+            map.get(key)
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string()
+        };
+        items.push(TldPricing {
+            tld: tld.clone(),
+            registration: field("registration"),
+            renewal: field("renewal"),
+            transfer: field("transfer"),
+        });
     }
-    items.sort_by(|a, b| {
-        let at = a.get("tld").and_then(Value::as_str).unwrap_or("");
-        let bt = b.get("tld").and_then(Value::as_str).unwrap_or("");
-        at.cmp(bt)
-    });
+    items.sort_by(|a, b| a.tld.cmp(&b.tld));
 
     if let Some(filter_tld) = args.tld.as_deref() {
         let filter_tld = filter_tld.trim_start_matches('.').to_ascii_lowercase();
         let item = items
             .into_iter()
-            .find(|x| x.get("tld").and_then(Value::as_str) == Some(filter_tld.as_str()))
+            .find(|x| x.tld == filter_tld)
             .ok_or_else(|| AppError::NotFound(format!("pricing for .{}", filter_tld)))?;
 
         if output.json {
-            print_json(&SuccessItem { ok: true, item })
+            print_json(&OkItem { ok: true, item })
         } else if output.quiet {
             println!(".{}", filter_tld);
             Ok(())
         } else {
             println!(
                 ".{} registration={} renewal={} transfer={}",
-                filter_tld,
-                item.get("registration")
-                    .and_then(Value::as_str)
-                    .unwrap_or(""),
-                item.get("renewal").and_then(Value::as_str).unwrap_or(""),
-                item.get("transfer").and_then(Value::as_str).unwrap_or("")
+                filter_tld, item.registration, item.renewal, item.transfer
             );
             Ok(())
         }
     } else {
+        let items = items
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
         output_value_list(output, items)
     }
 }
 
 fn output_value_list(output: &OutputFlags, items: Vec<Value>) -> Result<()> {
-    if output.json {
-        print_json(&SuccessList {
-            ok: true,
-            count: items.len(),
-            items,
-        })
+    if let Some(format) = Format::resolve(output.format, output.json) {
+        let text = render_columns(&items, format, &output.columns).map_err(AppError::from)?;
+        print!("{text}");
+        Ok(())
     } else if output.quiet {
         for item in &items {
             if let Some(s) = item.as_str() {
@@ -1314,26 +1866,28 @@ fn output_value_list(output: &OutputFlags, items: Vec<Value>) -> Result<()> {
                 println!("{domain}");
             } else if let Some(id) = item.get("id").and_then(Value::as_str) {
                 println!("{id}");
+            } else if let Some(name) = item.get("name").and_then(Value::as_str) {
+                println!("{name}");
             } else {
                 println!("{}", serde_json::to_string(item)?);
             }
         }
         Ok(())
     } else {
-        println!("Found {} item(s)", items.len());
-        for item in &items {
-            println!("{}", serde_json::to_string(item)?);
-        }
+        let text =
+            render_columns(&items, Format::Table, &output.columns).map_err(AppError::from)?;
+        println!(
+            "{}",
+            style::header(format!("Found {} item(s)", items.len()))
+        );
+        print!("{text}");
         Ok(())
     }
 }
 
 fn output_action(output: &OutputFlags, message: &str) -> Result<()> {
     if output.json {
-        print_json(&SuccessMessage {
-            ok: true,
-            message: message.to_string(),
-        })
+        print_json(&OkMessage::new(message))
     } else if output.quiet {
         println!("ok");
         Ok(())
@@ -1407,6 +1961,7 @@ fn dns_body_from_common(
 ) -> Result<Map<String, Value>> {
     validate_record_type(record_type)?;
     validate_non_empty("content", content)?;
+    content::validate(record_type, content)?;
     let mut body = Map::new();
     body.insert(
         "type".to_string(),
@@ -1445,58 +2000,89 @@ fn enc(input: &str) -> String {
     urlencoding::encode(input).to_string()
 }
 
-fn config_path() -> Result<PathBuf> {
-    let dir = dirs::config_dir().ok_or(AppError::ConfigDirUnavailable)?;
-    Ok(dir.join("dee-porkbun").join("config.toml"))
-}
-
-fn load_config_or_default() -> Result<AppConfig> {
-    let path = config_path()?;
-    if !path.exists() {
-        return Ok(AppConfig::default());
-    }
-    let raw = fs::read_to_string(&path)
-        .with_context(|| format!("failed reading config file {}", path.display()))?;
-    let cfg = toml::from_str::<AppConfig>(&raw)
-        .with_context(|| format!("failed parsing config file {}", path.display()))?;
-    Ok(cfg)
+/// `DEE_PORKBUN_API_KEY`, `DEE_PORKBUN_SECRET_KEY` and `DEE_PORKBUN_BASE_URL`
+/// override the file. With `secrets_backend = "keyring"` both keys live in the
+/// OS keyring instead.
+fn config_file() -> Config<AppConfig> {
+    Config::new(TOOL)
+        .env_override("api_key")
+        .env_override("secret_key")
+        .env_override("base_url")
+        .secret("api_key")
+        .secret("secret_key")
 }
 
 fn require_auth_config() -> Result<AppConfig> {
-    let path = config_path()?;
-    if !path.exists() {
+    let config = config_file();
+    let from_env = std::env::var_os("DEE_PORKBUN_API_KEY").is_some();
+    if !from_env && profile::active().is_none() && !config.path().exists() {
         return Err(AppError::ConfigMissing.into());
     }
-    let cfg = load_config_or_default()?;
+    let cfg = config.load().map_err(AppError::from)?;
     if cfg.api_key.is_empty() || cfg.secret_key.is_empty() {
         return Err(AppError::AuthMissing.into());
     }
     Ok(cfg)
 }
 
-fn save_config(cfg: &AppConfig) -> Result<()> {
-    let path = config_path()?;
-    ensure_parent_dir(&path)?;
-    let raw = toml::to_string(cfg)?;
-    fs::write(&path, raw)
-        .with_context(|| format!("failed writing config file {}", path.display()))?;
-    Ok(())
+/// How long a `/pricing/get` answer is reused
+const PRICING_TTL_SECS: u64 = 24 * 60 * 60;
+/// How long a `/domain/listAll` chunk is reused
+const LIST_ALL_TTL_SECS: u64 = 5 * 60;
+
+/// `call_api` through the response cache. The key is the URL and the request
+/// body; the account is named by a hash of its API key, never the key itself,
+/// so profiles do not share entries.
+async fn call_api_cached(
+    path: &str,
+    body: Map<String, Value>,
+    cfg: &AppConfig,
+    output: &OutputFlags,
+    ttl_secs: u64,
+) -> Result<Value> {
+    let mut key = format!("{}{path}", base_url(cfg.base_url.as_deref(), API_BASE));
+    for (name, value) in &body {
+        key.push_str(&format!(" {name}={value}"));
+    }
+    if !cfg.api_key.is_empty() {
+        let account = Sha256::digest(cfg.api_key.as_bytes());
+        key.push_str(" account=");
+        key.extend(account[..6].iter().map(|byte| format!("{byte:02x}")));
+    }
+    let cache = Cache::new(TOOL).ttl_secs(output.cache_ttl.unwrap_or(ttl_secs));
+    if let Some(hit) = cache
+        .lookup::<Value>(&key, &output.cache)
+        .map_err(AppError::from)?
+    {
+        tracing::debug!("{path} from cache, {}s old", hit.age_secs);
+        return Ok(hit.value);
+    }
+    let value = call_api(path, body, cfg, output).await?;
+    cache.put(&key, &value);
+    Ok(value)
 }
 
-fn ensure_parent_dir(path: &Path) -> Result<()> {
-    let parent = path.parent().ok_or(AppError::ConfigDirUnavailable)?;
-    fs::create_dir_all(parent)
-        .with_context(|| format!("failed creating config directory {}", parent.display()))?;
-    Ok(())
+async fn call_api(
+    path: &str,
+    body: Map<String, Value>,
+    cfg: &AppConfig,
+    output: &OutputFlags,
+) -> Result<Value> {
+    if !audit::mutating(path) {
+        return send_api(path, body, cfg, output).await;
+    }
+    let outcome = send_api(path, body.clone(), cfg, output).await;
+    audit::record(path, body, &outcome);
+    outcome
 }
 
-fn call_api(
+async fn send_api(
     path: &str,
     mut body: Map<String, Value>,
-    cfg: Option<&AppConfig>,
-    verbose: bool,
+    cfg: &AppConfig,
+    output: &OutputFlags,
 ) -> Result<Value> {
-    if let Some(cfg) = cfg {
+    if !cfg.api_key.is_empty() && !cfg.secret_key.is_empty() {
         body.insert("apikey".to_string(), Value::String(cfg.api_key.clone()));
         body.insert(
             "secretapikey".to_string(),
@@ -1504,24 +2090,60 @@ fn call_api(
         );
     }
 
-    let url = format!("{}{}", API_BASE, path);
-    if verbose {
-        eprintln!("debug: POST {url}");
-    }
-
-    let client = reqwest::blocking::Client::builder()
-        .user_agent("dee-porkbun/0.2.0 (https://dee.ink)")
-        .build()
+    let url = format!("{}{path}", base_url(cfg.base_url.as_deref(), API_BASE));
+    let settings = Http::new("dee-porkbun", env!("CARGO_PKG_VERSION")).args(&output.http);
+    let retries = settings.retries();
+    let client = settings
+        .client()
         .map_err(|e| AppError::RequestFailed(e.to_string()))?;
 
-    let response = client
-        .post(url)
-        .json(&body)
-        .send()
-        .map_err(|e| AppError::RequestFailed(e.to_string()))?;
+    let mut attempt = 0;
+    let response = loop {
+        let wait = ratelimit::reserve(&url).map_err(AppError::from)?;
+        if !wait.is_zero() {
+            tracing::debug!("waiting {:.1}s before calling Porkbun", wait.as_secs_f64());
+            tokio::time::sleep(wait).await;
+        }
+        tracing::debug!("POST {url} (attempt {}/{})", attempt + 1, retries + 1);
+
+        let response = client
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AppError::RequestFailed(e.to_string()))?;
+        let status = response.status();
+        if !matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+        ) {
+            break response;
+        }
+        if attempt == retries {
+            return Err(AppError::RateLimited(format!(
+                "HTTP {} after {} attempt(s); try a larger --retries or --retry-delay-ms",
+                status.as_u16(),
+                attempt + 1
+            ))
+            .into());
+        }
+        let delay = retry_delay(
+            Duration::from_millis(output.retry_delay_ms),
+            attempt,
+            retry_after(response.headers()),
+        );
+        tracing::debug!(
+            "Porkbun answered {}, retrying in {}ms",
+            status.as_u16(),
+            delay.as_millis()
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    };
     let status_code = response.status();
     let response_text = response
         .text()
+        .await
         .map_err(|e| AppError::RequestFailed(e.to_string()))?;
 
     let value: Value = serde_json::from_str(&response_text).map_err(|_| {
@@ -1568,6 +2190,111 @@ fn parse_available(value: &Value) -> bool {
     false
 }
 
+/// `listAll` returns domains in chunks of this many
+const LIST_ALL_CHUNK: usize = 1000;
+
+/// One `listAll` chunk, from index `start`.
+async fn list_domains(
+    cfg: &AppConfig,
+    start: u64,
+    include_labels: bool,
+    output: &OutputFlags,
+) -> Result<Vec<Value>> {
+    let mut body = Map::new();
+    if start > 0 {
+        body.insert("start".to_string(), Value::String(start.to_string()));
+    }
+    if include_labels {
+        body.insert(
+            "includeLabels".to_string(),
+            Value::String("yes".to_string()),
+        );
+    }
+    let value = call_api_cached("/domain/listAll", body, cfg, output, LIST_ALL_TTL_SECS).await?;
+    Ok(value
+        .get("domains")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// Every domain on the account, one `listAll` chunk at a time until a short
+/// one. The total is not known up front, and each call is rate limited, so
+/// chunks are not requested ahead.
+async fn list_all_domains(
+    cfg: &AppConfig,
+    include_labels: bool,
+    output: &OutputFlags,
+) -> Result<Vec<Value>> {
+    let mut domains = Vec::new();
+    loop {
+        let chunk = list_domains(cfg, domains.len() as u64, include_labels, output).await?;
+        let full = chunk.len() == LIST_ALL_CHUNK;
+        domains.extend(chunk);
+        if !full {
+            return Ok(domains);
+        }
+    }
+}
+
+/// Run `task` for each of `domains`, up to `--concurrency` at once, and
+/// return each outcome in the order given.
+async fn per_domain<'a, T, F>(
+    domains: &'a [String],
+    output: &OutputFlags,
+    task: impl Fn(&'a str) -> F,
+) -> Vec<(&'a str, Result<T>)>
+where
+    F: std::future::Future<Output = Result<T>>,
+{
+    futures::stream::iter(domains)
+        .map(|domain| {
+            let outcome = task(domain);
+            async move { (domain.as_str(), outcome.await) }
+        })
+        .buffered(usize::from(output.concurrency))
+        .collect()
+        .await
+}
+
+/// `tld` as Porkbun reports it, else the last label of `domain`.
+fn domain_tld(domain: &Value) -> String {
+    domain
+        .get("tld")
+        .and_then(Value::as_str)
+        .or_else(|| {
+            domain
+                .get("domain")
+                .and_then(Value::as_str)
+                .and_then(|name| name.rsplit('.').next())
+        })
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// The order Porkbun answers a create or renew with; `cost` is what was sent.
+fn domain_order(value: &Value, domain: &str, cost: u64) -> DomainOrder {
+    DomainOrder {
+        domain: value
+            .get("domain")
+            .and_then(Value::as_str)
+            .unwrap_or(domain)
+            .to_string(),
+        cost: value
+            .get("cost")
+            .cloned()
+            .unwrap_or(Value::Number(cost.into())),
+        order_id: value
+            .get("orderId")
+            .cloned()
+            .unwrap_or_else(|| Value::String(String::new())),
+        balance: value
+            .get("balance")
+            .cloned()
+            .unwrap_or_else(|| Value::String(String::new())),
+    }
+}
+
 fn parse_boolish(v: &Value) -> bool {
     match v {
         Value::Bool(b) => *b,
@@ -1609,35 +2336,3 @@ fn classify_error_code(err: &anyhow::Error) -> &'static str {
 fn stable_map(value: &Map<String, Value>) -> BTreeMap<String, Value> {
     value.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
 }
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -0,0 +1,180 @@
+//! `ssl retrieve --out-dir`: install the bundle as PEM files.
+//!
+//! Each file is written to a temporary name and renamed into place, so a
+//! server reloading mid-run never reads half a key. Files whose content is
+//! already current are left untouched, and `--reload-cmd` only runs when
+//! something changed, which keeps a daily cron job quiet.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{AppError, SslBundle};
+
+/// What `ssl retrieve --out-dir` reports; the key itself is never printed.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SslInstalled {
+    pub domain: String,
+    pub dir: String,
+    /// Paths of the three PEM files
+    pub files: Vec<String>,
+    /// Whether any file was (re)written
+    pub changed: bool,
+    /// Whether `--reload-cmd` ran
+    pub reloaded: bool,
+}
+
+/// Write `bundle` into `dir` and run `reload_cmd` if a file changed.
+pub fn install(
+    domain: &str,
+    bundle: &SslBundle,
+    dir: &Path,
+    reload_cmd: Option<&str>,
+) -> Result<SslInstalled, AppError> {
+    if bundle.certificatechain.is_empty() || bundle.privatekey.is_empty() {
+        return Err(AppError::ApiError(format!(
+            "SSL bundle for {domain} is incomplete; nothing written"
+        )));
+    }
+    create_dir(dir)?;
+    let mut files = Vec::new();
+    let mut changed = false;
+    for (name, content, private) in [
+        ("certificatechain.pem", &bundle.certificatechain, false),
+        ("privatekey.pem", &bundle.privatekey, true),
+        ("publickey.pem", &bundle.publickey, false),
+    ] {
+        let path = dir.join(name);
+        changed |= write_if_changed(&path, content, private)?;
+        files.push(path.display().to_string());
+    }
+    let reloaded = match reload_cmd {
+        Some(cmd) if changed => {
+            reload(cmd, domain, dir)?;
+            true
+        }
+        _ => false,
+    };
+    Ok(SslInstalled {
+        domain: domain.to_string(),
+        dir: dir.display().to_string(),
+        files,
+        changed,
+        reloaded,
+    })
+}
+
+fn create_dir(dir: &Path) -> Result<(), AppError> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder
+        .create(dir)
+        .map_err(|e| AppError::WriteFailed(format!("{}: {e}", dir.display())))
+}
+
+/// `true` when `path` was written; the private key is `0600`, the rest `0644`.
+fn write_if_changed(path: &Path, content: &str, private: bool) -> Result<bool, AppError> {
+    if fs::read(path).is_ok_and(|current| current == content.as_bytes()) {
+        return Ok(false);
+    }
+    let failed = |e: std::io::Error| AppError::WriteFailed(format!("{}: {e}", path.display()));
+    let tmp = temp_path(path);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, if private { 0o600 } else { 0o644 });
+    #[cfg(not(unix))]
+    let _ = private;
+    let written = options.open(&tmp).and_then(|mut file| {
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|()| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(failed(e));
+    }
+    Ok(true)
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{name}.tmp"))
+}
+
+fn reload(cmd: &str, domain: &str, dir: &Path) -> Result<(), AppError> {
+    tracing::debug!("running reload command: {cmd}");
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .env("DEE_PORKBUN_DOMAIN", domain)
+        .env("DEE_PORKBUN_SSL_DIR", dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .status()
+        .map_err(|e| AppError::ReloadFailed(format!("failed to start: {e}")))?;
+    if !status.success() {
+        return Err(AppError::ReloadFailed(format!(
+            "exited with {status}; the new files are in place, reload by hand"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(key: &str) -> SslBundle {
+        SslBundle {
+            certificatechain: "-----BEGIN CERTIFICATE-----\n".to_string(),
+            privatekey: key.to_string(),
+            publickey: "-----BEGIN PUBLIC KEY-----\n".to_string(),
+        }
+    }
+
+    #[test]
+    fn rewrites_only_what_changed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("example.com");
+
+        let first = install("example.com", &bundle("key one"), &dir, None).unwrap();
+        assert!(first.changed);
+        assert_eq!(first.files.len(), 3);
+        assert_eq!(
+            fs::read_to_string(dir.join("privatekey.pem")).unwrap(),
+            "key one"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let key = fs::metadata(dir.join("privatekey.pem")).unwrap();
+            assert_eq!(key.permissions().mode() & 0o777, 0o600);
+        }
+
+        let again = install("example.com", &bundle("key one"), &dir, Some("exit 1")).unwrap();
+        assert!(!again.changed);
+        assert!(!again.reloaded);
+
+        let err = install("example.com", &bundle("key two"), &dir, Some("exit 1")).unwrap_err();
+        assert!(matches!(err, AppError::ReloadFailed(_)));
+        assert_eq!(
+            fs::read_to_string(dir.join("privatekey.pem")).unwrap(),
+            "key two"
+        );
+        assert!(!dir.join(".privatekey.pem.tmp").exists());
+    }
+
+    #[test]
+    fn refuses_an_empty_bundle() {
+        let tmp = tempfile::tempdir().unwrap();
+        let err = install("example.com", &bundle(""), tmp.path(), None).unwrap_err();
+        assert!(matches!(err, AppError::ApiError(_)));
+        assert!(!tmp.path().join("certificatechain.pem").exists());
+    }
+}
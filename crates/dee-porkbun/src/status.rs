@@ -0,0 +1,198 @@
+//! `status`: one probe to run before a workflow. It loads the keys, pings
+//! Porkbun with them and reports what it learned.
+//!
+//! Porkbun has no balance endpoint. The balance shown is the one Porkbun sent
+//! with the last domain order (create or renew) on this account, taken from
+//! the audit log, with the time it was seen.
+
+use std::time::Instant;
+
+use anyhow::Result;
+use dee_core::http::base_url;
+use dee_core::{exit, profile, style, OkItem};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    audit, call_api, classify_error_code, config_file, print_json, require_auth_config,
+    OutputFlags, API_BASE,
+};
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Status {
+    /// Keys accepted and the API answering
+    ready: bool,
+    /// API root in use
+    api: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    /// `ok`, `missing` or `rejected`; `unknown` when Porkbun was not reached
+    credentials: String,
+    /// Whether Porkbun answered; absent when there were no keys to ping with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reachable: Option<bool>,
+    /// The caller's IP as Porkbun sees it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    your_ip: Option<String>,
+    /// Round trip of the ping
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_ms: Option<u64>,
+    /// Last balance Porkbun reported; absent until an order was placed from here
+    #[serde(skip_serializing_if = "Option::is_none")]
+    balance: Option<Balance>,
+    /// What kept the account from being ready
+    #[serde(skip_serializing_if = "Option::is_none")]
+    problem: Option<Problem>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Balance {
+    /// As Porkbun sent it
+    value: Value,
+    /// When that order was placed, RFC 3339 UTC
+    as_of: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Problem {
+    code: String,
+    message: String,
+}
+
+pub async fn handle(output: &OutputFlags) -> Result<()> {
+    let loaded = config_file().load().ok();
+    let mut status = Status {
+        ready: false,
+        api: base_url(
+            loaded.as_ref().and_then(|cfg| cfg.base_url.as_deref()),
+            API_BASE,
+        ),
+        profile: profile::active(),
+        credentials: "missing".to_string(),
+        reachable: None,
+        your_ip: None,
+        latency_ms: None,
+        balance: last_balance(),
+        problem: None,
+    };
+    match require_auth_config() {
+        Err(err) => status.problem = Some(problem(&err)),
+        Ok(cfg) => {
+            let started = Instant::now();
+            match call_api("/ping", Map::new(), &cfg, output).await {
+                Ok(value) => {
+                    status.ready = true;
+                    status.credentials = "ok".to_string();
+                    status.reachable = Some(true);
+                    status.latency_ms = Some(started.elapsed().as_millis() as u64);
+                    status.your_ip = value
+                        .get("yourIp")
+                        .and_then(Value::as_str)
+                        .map(str::to_string);
+                }
+                Err(err) => {
+                    let mut problem = problem(&err);
+                    let (credentials, reachable) = match problem.code.as_str() {
+                        // an API error from /ping is Porkbun turning the keys down
+                        "API_ERROR" => {
+                            problem.code = "AUTH_REJECTED".to_string();
+                            ("rejected", true)
+                        }
+                        "RATE_LIMITED" => ("unknown", true),
+                        _ => ("unknown", false),
+                    };
+                    status.credentials = credentials.to_string();
+                    status.reachable = Some(reachable);
+                    status.problem = Some(problem);
+                }
+            }
+        }
+    }
+
+    let code = status.problem.as_ref().map(|problem| problem.code.clone());
+    if output.json {
+        print_json(&OkItem {
+            ok: true,
+            item: status,
+        })?;
+    } else if output.quiet {
+        println!("{}", if status.ready { "ready" } else { "not ready" });
+    } else {
+        print_status(&status);
+    }
+    match code {
+        Some(code) => std::process::exit(exit::for_code(&code)),
+        None => Ok(()),
+    }
+}
+
+fn problem(err: &anyhow::Error) -> Problem {
+    Problem {
+        code: classify_error_code(err).to_string(),
+        message: format!("{err:#}"),
+    }
+}
+
+/// The balance in the newest successful order logged for this profile.
+fn last_balance() -> Option<Balance> {
+    let profile = profile::active();
+    audit::entries()
+        .ok()?
+        .into_iter()
+        .rev()
+        .filter(|entry| entry.ok && entry.profile == profile)
+        .filter(|entry| {
+            entry.path.starts_with("/domain/create/") || entry.path.starts_with("/domain/renew/")
+        })
+        .find_map(|entry| {
+            let value = entry.result?.get("balance")?.clone();
+            Some(Balance {
+                value,
+                as_of: entry.ts,
+            })
+        })
+}
+
+fn print_status(status: &Status) {
+    let mark = |good: bool, text: &str| {
+        if good {
+            style::good(text)
+        } else {
+            style::bad(text)
+        }
+    };
+    println!("api          {}", status.api);
+    if let Some(profile) = &status.profile {
+        println!("profile      {profile}");
+    }
+    println!(
+        "credentials  {}",
+        mark(status.credentials == "ok", &status.credentials)
+    );
+    if let Some(reachable) = status.reachable {
+        println!(
+            "reachable    {}",
+            mark(reachable, if reachable { "yes" } else { "no" })
+        );
+    }
+    if let (Some(ip), Some(ms)) = (&status.your_ip, status.latency_ms) {
+        println!("your ip      {ip} {}", style::dim(format!("({ms} ms)")));
+    }
+    if let Some(balance) = &status.balance {
+        let value = match &balance.value {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        println!(
+            "balance      {value} {}",
+            style::dim(format!("(as of {})", balance.as_of))
+        );
+    }
+    if let Some(problem) = &status.problem {
+        println!(
+            "{}",
+            style::bad(format!("{}: {}", problem.code, problem.message))
+        );
+    }
+}
@@ -89,3 +89,69 @@ fn domains_check_without_config_returns_config_missing() {
         .failure()
         .stdout(contains("\"code\":\"CONFIG_MISSING\""));
 }
+
+/// A `secret-tool` stand-in keeping entries as files in `$FAKE_KEYRING`.
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn keyring_backend_keeps_keys_out_of_the_config_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let bin = dir.path().join("bin");
+    let store = dir.path().join("keyring");
+    std::fs::create_dir_all(&bin).unwrap();
+    std::fs::create_dir_all(&store).unwrap();
+    let script = bin.join("secret-tool");
+    std::fs::write(
+        &script,
+        "#!/bin/sh\ncase \"$1\" in\n  lookup) cat \"$FAKE_KEYRING/$3.$5\" 2>/dev/null || exit 1 ;;\n  store) cat > \"$FAKE_KEYRING/$5.$7\" ;;\nesac\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let config = dir.path().join("config.toml");
+    let path = std::env::join_paths(std::iter::once(bin.clone()).chain(std::env::split_paths(
+        &std::env::var_os("PATH").unwrap_or_default(),
+    )))
+    .unwrap();
+    let porkbun = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-porkbun"));
+        cmd.env("DEE_PORKBUN_CONFIG", &config)
+            .env("FAKE_KEYRING", &store)
+            .env("PATH", &path)
+            .args(args);
+        cmd
+    };
+
+    porkbun(&["config", "set", "api_key", "pk1_plain"])
+        .assert()
+        .success();
+    porkbun(&["config", "set", "secrets_backend", "keyring"])
+        .assert()
+        .success();
+    porkbun(&["config", "set", "secret_key", "sk1_secret"])
+        .assert()
+        .success();
+
+    let file = std::fs::read_to_string(&config).unwrap();
+    assert!(file.contains("secrets_backend = \"keyring\""), "{file}");
+    assert!(
+        !file.contains("pk1_plain") && !file.contains("sk1_secret"),
+        "{file}"
+    );
+    assert_eq!(
+        std::fs::read_to_string(store.join("dee-porkbun.api_key")).unwrap(),
+        "pk1_plain"
+    );
+
+    porkbun(&["config", "show", "--json"])
+        .assert()
+        .success()
+        .stdout(contains("\"api_key_set\":true"))
+        .stdout(contains("\"secret_key_set\":true"))
+        .stdout(contains("\"secrets_backend\":\"keyring\""));
+
+    porkbun(&["config", "set", "secrets_backend", "vault", "--json"])
+        .assert()
+        .failure()
+        .stdout(contains("\"code\":\"INVALID_ARGUMENT\""));
+}
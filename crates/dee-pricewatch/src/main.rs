@@ -1,13 +1,17 @@
 use std::fs;
 use std::path::PathBuf;
-use std::time::Duration;
 
 use chrono::{SecondsFormat, Utc};
 use clap::{Args, Parser, Subcommand};
+use dee_core::format::render_list;
+use dee_core::http::{Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{report, ErrorCode, Format};
 use regex::Regex;
 use reqwest::blocking::Client;
 use reqwest::Url;
 use rusqlite::{params, Connection, OptionalExtension};
+use schemars::JsonSchema;
 use scraper::{Html, Selector};
 use serde::Serialize;
 
@@ -40,6 +44,13 @@ struct GlobalArgs {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -81,9 +92,6 @@ struct AddArgs {
 struct CheckArgs {
     /// Optional watch id or label; defaults to all
     watch: Option<String>,
-
-    #[arg(long, default_value_t = 20)]
-    timeout_secs: u64,
 }
 
 #[derive(Debug, Clone, Args)]
@@ -91,14 +99,7 @@ struct WatchArg {
     watch: String,
 }
 
-#[derive(Debug, Serialize)]
-struct OkList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct OkMessage {
     ok: bool,
     message: String,
@@ -106,13 +107,6 @@ struct OkMessage {
     id: Option<i64>,
 }
 
-#[derive(Debug, Serialize)]
-struct ErrorJson {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
 #[derive(Debug, Clone)]
 struct WatchRecord {
     id: i64,
@@ -127,7 +121,7 @@ struct WatchRecord {
     updated_at: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct WatchItem {
     id: i64,
     url: String,
@@ -146,7 +140,7 @@ struct WatchItem {
     updated_at: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct CheckItem {
     id: i64,
     url: String,
@@ -190,7 +184,7 @@ enum AppError {
     Serialize,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
             Self::DataDirMissing => "CONFIG_MISSING",
@@ -206,31 +200,25 @@ impl AppError {
 
 type AppResult<T> = Result<T, AppError>;
 
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .custom::<OkMessage>("add")
+        .list::<WatchItem>("list")
+        .list::<CheckItem>("check")
+        .custom::<OkMessage>("delete")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = dispatch(&cli) {
-        if cli.global.json {
-            let payload = ErrorJson {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            };
-
-            match serde_json::to_string(&payload) {
-                Ok(rendered) => println!("{rendered}"),
-                Err(_) => {
-                    let escaped = escape_json(&err.to_string());
-                    println!(
-                        "{{\"ok\":false,\"error\":\"{escaped}\",\"code\":\"{}\"}}",
-                        err.code()
-                    );
-                }
-            }
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -240,9 +228,7 @@ fn dispatch(cli: &Cli) -> AppResult<()> {
         fs::create_dir_all(parent).map_err(|_| AppError::Database)?;
     }
 
-    if cli.global.verbose {
-        eprintln!("[dee-pricewatch] db_path={}", db_path.display());
-    }
+    tracing::debug!("db_path={}", db_path.display());
 
     let conn = Connection::open(db_path).map_err(|_| AppError::Database)?;
     initialize_db(&conn)?;
@@ -318,12 +304,8 @@ fn cmd_list(conn: &Connection, global: &GlobalArgs) -> AppResult<()> {
         })
         .collect::<Vec<_>>();
 
-    if global.json {
-        write_json(&OkList {
-            ok: true,
-            count: items.len(),
-            items,
-        })
+    if let Some(format) = Format::resolve(global.output, global.json) {
+        write_list(&items, format)
     } else if global.quiet {
         println!("{}", items.len());
         Ok(())
@@ -352,7 +334,7 @@ fn cmd_check(conn: &Connection, args: &CheckArgs, global: &GlobalArgs) -> AppRes
         None => list_watches(conn)?,
     };
 
-    let client = build_http_client(args.timeout_secs)?;
+    let client = build_http_client(global)?;
     let mut items = Vec::with_capacity(watches.len());
 
     for watch in watches {
@@ -376,12 +358,8 @@ fn cmd_check(conn: &Connection, args: &CheckArgs, global: &GlobalArgs) -> AppRes
         }
     }
 
-    if global.json {
-        write_json(&OkList {
-            ok: true,
-            count: items.len(),
-            items,
-        })
+    if let Some(format) = Format::resolve(global.output, global.json) {
+        write_list(&items, format)
     } else if global.quiet {
         let success_count = items.iter().filter(|item| item.ok).count();
         println!("{success_count}");
@@ -432,12 +410,10 @@ fn cmd_delete(conn: &Connection, args: &WatchArg, global: &GlobalArgs) -> AppRes
     }
 }
 
-fn build_http_client(timeout_secs: u64) -> AppResult<Client> {
-    let timeout = if timeout_secs == 0 { 20 } else { timeout_secs };
-    Client::builder()
-        .timeout(Duration::from_secs(timeout))
-        .user_agent("dee-pricewatch/0.1")
-        .build()
+fn build_http_client(global: &GlobalArgs) -> AppResult<Client> {
+    Http::new("dee-pricewatch", env!("CARGO_PKG_VERSION"))
+        .args(&global.http)
+        .blocking()
         .map_err(|err| AppError::RequestFailed(err.to_string()))
 }
 
@@ -788,46 +764,12 @@ fn write_json<T: Serialize>(value: &T) -> AppResult<()> {
     Ok(())
 }
 
-fn now_timestamp() -> String {
-    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
-}
-
-fn escape_json(value: &str) -> String {
-    value
-        .replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace('\n', "\\n")
-        .replace('\r', "\\r")
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
+fn write_list<T: Serialize>(items: &[T], format: Format) -> AppResult<()> {
+    let rendered = render_list(items, format).map_err(|_| AppError::Serialize)?;
+    print!("{rendered}");
+    Ok(())
 }
 
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
+fn now_timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
 }
@@ -0,0 +1,324 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::split::part_path;
+use crate::{
+    absolute_path, build_symbol, classify_error, load_image, render, render_image, scan_image,
+    verify_output, AppError, DecodeDirArgs, GenerateArgs, GenerateBatchArgs, GlobalFlags,
+    ModuleStyle, OutputFormat,
+};
+
+/// Extensions `decode` accepts; other files in the directory are ignored.
+const IMAGE_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "gif", "bmp", "webp", "tiff", "tif"];
+
+#[derive(Serialize, JsonSchema)]
+struct DirItem {
+    path: String,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub(crate) struct DirReport {
+    ok: bool,
+    count: usize,
+    decoded: usize,
+    failed: usize,
+    items: Vec<DirItem>,
+}
+
+pub fn handle_decode_dir(args: DecodeDirArgs, global: &GlobalFlags) -> Result<()> {
+    if !args.dir.exists() {
+        return Err(AppError::DirNotFound(args.dir.display().to_string()).into());
+    }
+    if !args.dir.is_dir() {
+        return Err(AppError::InvalidArgument(format!(
+            "{} is not a directory",
+            args.dir.display()
+        ))
+        .into());
+    }
+
+    let mut paths = Vec::new();
+    collect_images(&args.dir, args.recursive, &mut paths)?;
+    paths.sort();
+    tracing::debug!("{} image files under {}", paths.len(), args.dir.display());
+
+    let items: Vec<DirItem> = paths.iter().map(|path| decode_one(path)).collect();
+    let decoded = items.iter().filter(|item| item.ok).count();
+    let report = DirReport {
+        ok: true,
+        count: items.len(),
+        decoded,
+        failed: items.len() - decoded,
+        items,
+    };
+
+    if global.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    for item in &report.items {
+        match (&item.data, &item.error) {
+            (Some(data), _) if global.quiet => println!("{}\t{data}", item.path),
+            (Some(data), _) => println!("{}: {data}", item.path),
+            (None, Some(error)) if !global.quiet => println!("{}: error: {error}", item.path),
+            _ => {}
+        }
+    }
+    if !global.quiet {
+        println!(
+            "{} images, {} decoded, {} failed",
+            report.count, report.decoded, report.failed
+        );
+    }
+    Ok(())
+}
+
+/// Gather image files directly in `dir`, descending into subdirectories when `recursive`.
+/// Symlinked directories are not followed.
+fn collect_images(dir: &Path, recursive: bool, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if recursive {
+                collect_images(&path, recursive, out)?;
+            }
+            continue;
+        }
+        let is_image = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+            .unwrap_or(false);
+        if is_image {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Decode the first code in one file; failures are recorded on the item, not returned.
+fn decode_one(path: &Path) -> DirItem {
+    let result = load_image(path).and_then(|image| {
+        let (mut items, failed) = scan_image(&image)?;
+        if items.is_empty() {
+            return Err(if failed > 0 {
+                AppError::DecodeFailed
+            } else {
+                AppError::QrNotFound
+            }
+            .into());
+        }
+        Ok(items.swap_remove(0))
+    });
+
+    let path = path.display().to_string();
+    match result {
+        Ok(item) => DirItem {
+            path,
+            ok: true,
+            data: Some(item.data),
+            format: Some(item.format),
+            version: item.version,
+            error: None,
+            code: None,
+        },
+        Err(err) => {
+            let (message, code) = classify_error(&err);
+            DirItem {
+                path,
+                ok: false,
+                data: None,
+                format: None,
+                version: None,
+                error: Some(message),
+                code: Some(code),
+            }
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+struct BatchItem {
+    /// 1-based position in the input
+    index: usize,
+    data: String,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    /// Image width in pixels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub(crate) struct BatchReport {
+    ok: bool,
+    count: usize,
+    generated: usize,
+    failed: usize,
+    format: OutputFormat,
+    items: Vec<BatchItem>,
+}
+
+pub fn handle_generate_batch(args: GenerateBatchArgs, global: &GlobalFlags) -> Result<()> {
+    let texts = match args.pipe.values("url")? {
+        Some(texts) => texts,
+        None => {
+            let mut buf = String::new();
+            std::io::stdin()
+                .read_to_string(&mut buf)
+                .context("failed to read from stdin")?;
+            buf.lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string)
+                .collect()
+        }
+    };
+
+    let format = args.format;
+    if format == OutputFormat::Terminal {
+        return Err(AppError::InvalidArgument(
+            "generate-batch writes files; pick an image format".to_string(),
+        )
+        .into());
+    }
+    if args.verify && !(format.is_raster() || format == OutputFormat::Svg) {
+        return Err(AppError::UnsupportedOption("--verify", format.as_str()).into());
+    }
+    if args.style != ModuleStyle::Square && format != OutputFormat::Svg {
+        return Err(AppError::UnsupportedOption("--style", format.as_str()).into());
+    }
+    fs::create_dir_all(&args.out_dir)
+        .with_context(|| format!("failed to create {}", args.out_dir.display()))?;
+
+    let layout = render::Layout {
+        scale: args.scale,
+        margin: args.margin,
+        min_size: args.min_size,
+    };
+    let extension = match format {
+        OutputFormat::Jpeg => "jpg",
+        other => other.as_str(),
+    };
+    let template = args.out_dir.join(format!("{}.{extension}", args.prefix));
+    // The single-code renderer reads its options from `generate`'s arguments
+    let generate = GenerateArgs {
+        text: None,
+        stdin: false,
+        out: None,
+        emit: None,
+        format: Some(format),
+        symbology: args.symbology,
+        ec_level: args.ec_level,
+        scale: args.scale,
+        margin: args.margin,
+        min_size: args.min_size,
+        fg: args.fg,
+        bg: args.bg,
+        logo: None,
+        style: args.style,
+        width: None,
+        height: None,
+        split: false,
+        part_size: None,
+        verify: args.verify,
+        invert: false,
+        compact: false,
+        quiet_zone: None,
+    };
+
+    let total = texts.len();
+    let items: Vec<BatchItem> = texts
+        .into_iter()
+        .enumerate()
+        .map(|(idx, data)| {
+            let path = part_path(&template, idx + 1, total);
+            let result = build_symbol(&data, &generate).and_then(|(matrix, _)| {
+                let bytes = render_image(&matrix, format, layout, &generate)?;
+                fs::write(&path, &bytes)?;
+                if generate.verify {
+                    verify_output(&fs::read(&path)?, format, &data, generate.symbology)?;
+                }
+                Ok((absolute_path(&path)?, layout.size_px(&matrix).0))
+            });
+            match result {
+                Ok((path, size)) => BatchItem {
+                    index: idx + 1,
+                    data,
+                    ok: true,
+                    path: Some(path.display().to_string()),
+                    size: Some(size),
+                    error: None,
+                    code: None,
+                },
+                Err(err) => {
+                    let (message, code) = classify_error(&err);
+                    BatchItem {
+                        index: idx + 1,
+                        data,
+                        ok: false,
+                        path: None,
+                        size: None,
+                        error: Some(message),
+                        code: Some(code),
+                    }
+                }
+            }
+        })
+        .collect();
+    let generated = items.iter().filter(|item| item.ok).count();
+    tracing::debug!(
+        "{generated} of {total} codes written to {}",
+        args.out_dir.display()
+    );
+    let report = BatchReport {
+        ok: true,
+        count: items.len(),
+        generated,
+        failed: items.len() - generated,
+        format,
+        items,
+    };
+
+    if global.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    for item in &report.items {
+        match (&item.path, &item.error) {
+            (Some(path), _) if global.quiet => println!("{path}"),
+            (Some(path), _) => println!("{}: {path}", item.data),
+            (None, Some(error)) if !global.quiet => println!("{}: error: {error}", item.data),
+            _ => {}
+        }
+    }
+    if !global.quiet {
+        println!(
+            "{} inputs, {} generated, {} failed",
+            report.count, report.generated, report.failed
+        );
+    }
+    Ok(())
+}
@@ -1,12 +1,22 @@
+mod batch;
+mod render;
+mod split;
+mod symbol;
+mod vector;
+
 use std::fs;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use clap::{Args, Parser, Subcommand, ValueEnum};
-use image::Luma;
-use qrcode::render::svg;
-use qrcode::QrCode;
+use dee_core::cli::wants_json;
+use dee_core::pipe::{PipeArgs, PipeError};
+use dee_core::schema::{self, Schemas};
+use dee_core::{exit, report_code, ErrorCode};
+use image::Rgba;
+use qrcode::{EcLevel, QrCode};
+use schemars::JsonSchema;
 use serde::Serialize;
 use thiserror::Error;
 
@@ -15,7 +25,7 @@ use thiserror::Error;
     name = "dee-qr",
     version,
     about = "dee-qr - QR Code Generate & Decode CLI",
-    after_help = "EXAMPLES:\n  dee-qr generate \"https://example.com\" --out qr.png\n  dee-qr generate \"hello\" --format svg --out qr.svg --json\n  dee-qr generate \"terminal demo\" --format terminal\n  dee-qr decode qr.png\n  dee-qr decode qr.png --json"
+    after_help = "EXAMPLES:\n  dee-qr generate \"https://example.com\" --out qr.png\n  dee-qr generate \"hello\" --format svg --out qr.svg --json\n  dee-qr generate \"terminal demo\" --format terminal\n  dee-qr decode qr.png\n  dee-qr decode qr.png --json\n  dee-qr decode-dir ./labels --recursive --json\n  dee-hn top --json | dee-qr generate-batch --stdin-json --field url --out-dir codes"
 )]
 struct Cli {
     #[command(flatten)]
@@ -41,14 +51,18 @@ struct GlobalFlags {
 enum Commands {
     /// Generate a QR code from text
     Generate(GenerateArgs),
+    /// Generate one code per line or piped JSON item into a directory
+    GenerateBatch(GenerateBatchArgs),
     /// Decode a QR code from an image
     Decode(DecodeArgs),
+    /// Decode every image in a directory and report per-file results
+    DecodeDir(DecodeDirArgs),
 }
 
 #[derive(Args, Debug)]
 #[command(
-    about = "Generate a QR code from text",
-    after_help = "EXAMPLES:\n  dee-qr generate \"https://example.com\" --out qr.png\n  dee-qr generate \"hello\" --format svg --out qr.svg --json\n  dee-qr generate \"scan me\" --format terminal\n  echo \"https://example.com\" | dee-qr generate --stdin --format terminal"
+    about = "Generate a QR code or barcode from text",
+    after_help = "EXAMPLES:\n  dee-qr generate \"https://example.com\" --out qr.png\n  dee-qr generate \"hello\" --format svg --out qr.svg --json\n  dee-qr generate \"hello\" --style rounded --width 40mm --out qr.svg\n  dee-qr generate \"scan me\" --format terminal\n  dee-qr generate \"scan me\" --format terminal --invert --compact\n  dee-qr generate \"print me\" --ec-level h --min-size 1200 --out print.png\n  dee-qr generate \"label\" --out label.pdf\n  dee-qr generate \"batch-17\" --verify --out batch-17.png\n  dee-qr generate --stdin --split --out big.png < notes.txt\n  dee-qr generate 5901234123457 --symbology ean13 --out ean.png\n  echo \"https://example.com\" | dee-qr generate --stdin --format terminal"
 )]
 struct GenerateArgs {
     /// Text content to encode (omit when using --stdin)
@@ -59,38 +73,321 @@ struct GenerateArgs {
     #[arg(long)]
     stdin: bool,
 
-    /// Output path for png/svg (optional for terminal)
+    /// Output file (optional for terminal); `-` writes raw bytes to stdout
     #[arg(long)]
     out: Option<PathBuf>,
 
+    /// Print the image bytes inline as base64 or a data URI (--out becomes optional)
+    #[arg(long, value_enum)]
+    emit: Option<Emit>,
+
+    /// Output format; inferred from the --out extension when omitted, else png
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Barcode type to generate
+    #[arg(long, value_enum, default_value_t = Symbology::Qr)]
+    symbology: Symbology,
+
+    /// QR error correction level: l (7%), m (15%), q (25%), h (30%)
+    #[arg(long, value_enum, default_value_t = EcLevelArg::M)]
+    ec_level: EcLevelArg,
+
+    /// Pixels per module for png/svg
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..=100))]
+    scale: u32,
+
+    /// Quiet zone around the code, in modules
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(0..=64))]
+    margin: u32,
+
+    /// Minimum png/svg width and height in pixels; modules are enlarged to reach it
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..=10000))]
+    min_size: u32,
+
+    /// Module color, as hex (#000000)
+    #[arg(long, default_value = "#000000", value_parser = render::parse_color)]
+    fg: Rgba<u8>,
+
+    /// Background color, as hex (#ffffff; #ffffff00 for transparent)
+    #[arg(long, default_value = "#ffffff", value_parser = render::parse_color)]
+    bg: Rgba<u8>,
+
+    /// Image composited over the center of a png code; size is capped by --ec-level
+    #[arg(long)]
+    logo: Option<PathBuf>,
+
+    /// SVG only: module shape
+    #[arg(long, value_enum, default_value_t = ModuleStyle::Square)]
+    style: ModuleStyle,
+
+    /// SVG only: width attribute (e.g. 512, 40mm, 2in); height follows unless set
+    #[arg(long, value_parser = render::parse_length)]
+    width: Option<render::SvgLength>,
+
+    /// SVG only: height attribute (e.g. 512, 40mm, 2in); width follows unless set
+    #[arg(long, value_parser = render::parse_length)]
+    height: Option<render::SvgLength>,
+
+    /// Split content into a numbered sequence of codes written as <stem>-1.<ext>, ...
+    #[arg(long)]
+    split: bool,
+
+    /// With --split: maximum content bytes per code (default: as much as one code holds)
+    #[arg(long, requires = "split", value_parser = clap::value_parser!(u64).range(1..=2953))]
+    part_size: Option<u64>,
+
+    /// Decode the written output and fail with VERIFY_FAILED unless it matches the input
+    /// (png, jpeg, webp, svg)
+    #[arg(long)]
+    verify: bool,
+
+    /// Terminal only: draw light modules as blocks (for light-on-dark terminals)
+    #[arg(long)]
+    invert: bool,
+
+    /// Terminal only: half-block characters, two module rows per line
+    #[arg(long)]
+    compact: bool,
+
+    /// Terminal only: quiet zone in modules (default 4; 10 for linear barcodes)
+    #[arg(long, value_parser = clap::value_parser!(u32).range(0..=64))]
+    quiet_zone: Option<u32>,
+}
+
+#[derive(Args, Debug)]
+#[command(
+    about = "Generate one code per input into a directory",
+    after_help = "EXAMPLES:\n  dee-hn top --json | dee-qr generate-batch --stdin-json --field url --out-dir codes\n  dee-qr generate-batch --out-dir labels --prefix label --format svg < lines.txt\n  dee-stash list --json | dee-qr generate-batch --stdin-json --field url --verify --json"
+)]
+struct GenerateBatchArgs {
+    // Another dee tool's JSON output (`url` of each item, unless --field);
+    // without --stdin-json, each non-empty stdin line is one code
+    #[command(flatten)]
+    pipe: PipeArgs,
+
+    /// Directory for the codes, created when missing
+    #[arg(long, default_value = ".")]
+    out_dir: PathBuf,
+
+    /// File names are <prefix>-1.<ext>, <prefix>-2.<ext>, ...
+    #[arg(long, default_value = "qr")]
+    prefix: String,
+
+    /// Image format of every code
     #[arg(long, value_enum, default_value_t = OutputFormat::Png)]
     format: OutputFormat,
+
+    /// Barcode type to generate
+    #[arg(long, value_enum, default_value_t = Symbology::Qr)]
+    symbology: Symbology,
+
+    /// QR error correction level: l (7%), m (15%), q (25%), h (30%)
+    #[arg(long, value_enum, default_value_t = EcLevelArg::M)]
+    ec_level: EcLevelArg,
+
+    /// Pixels per module for png/svg
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..=100))]
+    scale: u32,
+
+    /// Quiet zone around the code, in modules
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(0..=64))]
+    margin: u32,
+
+    /// Minimum png/svg width and height in pixels; modules are enlarged to reach it
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..=10000))]
+    min_size: u32,
+
+    /// Module color, as hex (#000000)
+    #[arg(long, default_value = "#000000", value_parser = render::parse_color)]
+    fg: Rgba<u8>,
+
+    /// Background color, as hex (#ffffff; #ffffff00 for transparent)
+    #[arg(long, default_value = "#ffffff", value_parser = render::parse_color)]
+    bg: Rgba<u8>,
+
+    /// SVG only: module shape
+    #[arg(long, value_enum, default_value_t = ModuleStyle::Square)]
+    style: ModuleStyle,
+
+    /// Decode each written file and mark it VERIFY_FAILED unless it matches its input
+    /// (png, jpeg, webp, svg)
+    #[arg(long)]
+    verify: bool,
 }
 
 #[derive(Args, Debug)]
 #[command(
-    about = "Decode a QR code from an image",
-    after_help = "EXAMPLES:\n  dee-qr decode qr.png\n  dee-qr decode qr.png --json\n  dee-qr decode qr.png --quiet"
+    about = "Decode a QR code (or a barcode, best-effort) from an image",
+    after_help = "EXAMPLES:\n  dee-qr decode qr.png\n  dee-qr decode qr.png --json\n  dee-qr decode qr.png --quiet\n  screenshot-tool | dee-qr decode -\n  dee-qr decode --data-uri \"data:image/png;base64,iVBOR...\"\n  dee-qr decode --join big-1.png big-2.png big-3.png"
 )]
 struct DecodeArgs {
-    /// Path to image file containing QR code
-    image: PathBuf,
+    /// Path to image file containing QR code, or `-` to read image bytes from stdin.
+    /// Several images are accepted with --join
+    #[arg(
+        value_name = "IMAGE",
+        required_unless_present = "data_uri",
+        conflicts_with = "data_uri"
+    )]
+    images: Vec<PathBuf>,
+
+    /// Decode an image embedded in a base64 `data:` URI
+    #[arg(long)]
+    data_uri: Option<String>,
+
+    /// Report every code found in the image as a list, ordered top-to-bottom, left-to-right
+    #[arg(long)]
+    all: bool,
+
+    /// Reassemble a `generate --split` sequence from every code in the given images
+    #[arg(long, conflicts_with = "all")]
+    join: bool,
+}
+
+#[derive(Args, Debug)]
+#[command(
+    about = "Decode every image in a directory",
+    after_help = "EXAMPLES:\n  dee-qr decode-dir ./labels\n  dee-qr decode-dir ./assets --recursive --json\n  dee-qr decode-dir ./labels --quiet"
+)]
+struct DecodeDirArgs {
+    /// Directory containing images (png, jpg, gif, bmp, webp, tiff)
+    dir: PathBuf,
+
+    /// Include images in subdirectories
+    #[arg(long, short = 'r')]
+    recursive: bool,
 }
 
-#[derive(Clone, Copy, Debug, ValueEnum, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 enum OutputFormat {
     Png,
     Svg,
+    Jpeg,
+    Webp,
+    Pdf,
+    Eps,
     Terminal,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Symbology {
+    Qr,
+    Code128,
+    Ean13,
+    Datamatrix,
+}
+
+impl Symbology {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Qr => "qr",
+            Self::Code128 => "code128",
+            Self::Ean13 => "ean13",
+            Self::Datamatrix => "datamatrix",
+        }
+    }
+
+    /// Human-readable name used in status messages.
+    fn label(self) -> &'static str {
+        match self {
+            Self::Qr => "QR code",
+            Self::Code128 => "Code 128 barcode",
+            Self::Ean13 => "EAN-13 barcode",
+            Self::Datamatrix => "Data Matrix code",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ModuleStyle {
+    Square,
+    Rounded,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Emit {
+    Base64,
+    DataUri,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum EcLevelArg {
+    L,
+    M,
+    Q,
+    H,
+}
+
+impl EcLevelArg {
+    fn level(self) -> EcLevel {
+        match self {
+            Self::L => EcLevel::L,
+            Self::M => EcLevel::M,
+            Self::Q => EcLevel::Q,
+            Self::H => EcLevel::H,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::L => "l",
+            Self::M => "m",
+            Self::Q => "q",
+            Self::H => "h",
+        }
+    }
+}
+
+impl OutputFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Svg => "svg",
+            Self::Jpeg => "jpeg",
+            Self::Webp => "webp",
+            Self::Pdf => "pdf",
+            Self::Eps => "eps",
+            Self::Terminal => "terminal",
+        }
+    }
+
+    fn mime(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Svg => "image/svg+xml",
+            Self::Jpeg => "image/jpeg",
+            Self::Webp => "image/webp",
+            Self::Pdf => "application/pdf",
+            Self::Eps => "application/postscript",
+            Self::Terminal => "text/plain",
+        }
+    }
+
+    fn from_extension(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "png" => Some(Self::Png),
+            "svg" => Some(Self::Svg),
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "webp" => Some(Self::Webp),
+            "pdf" => Some(Self::Pdf),
+            "eps" | "ps" => Some(Self::Eps),
+            _ => None,
+        }
+    }
+
+    fn is_raster(self) -> bool {
+        matches!(self, Self::Png | Self::Jpeg | Self::Webp)
+    }
+}
+
 #[derive(Debug, Error)]
 enum AppError {
     #[error("Missing required argument: --out for format {0}")]
     MissingOut(String),
 
-    #[error("No QR code found in image")]
+    #[error("No QR code or barcode found in image")]
     QrNotFound,
 
     #[error("Failed to decode QR payload")]
@@ -101,87 +398,167 @@ enum AppError {
 
     #[error("Image file not found: {0}")]
     FileNotFound(String),
-}
 
-#[derive(Serialize)]
-struct JsonError {
-    ok: bool,
-    error: String,
-    code: &'static str,
+    #[error("Directory not found: {0}")]
+    DirNotFound(String),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("Invalid data URI: {0}")]
+    InvalidDataUri(String),
+
+    #[error("No image data received on stdin")]
+    EmptyStdin,
+
+    #[error("{0} is not supported for {1} output")]
+    UnsupportedOption(&'static str, &'static str),
+
+    #[error("Content too long for a QR code at error correction level {0}")]
+    DataTooLong(&'static str),
+
+    #[error("Verification failed: {0}")]
+    VerifyFailed(String),
+
+    #[error("Could not join split codes: {0}")]
+    JoinFailed(String),
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct GenerateJson {
     ok: bool,
     message: String,
-    path: String,
+    /// Absolute path written; omitted when the code was only emitted inline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
     data: String,
     format: OutputFormat,
+    symbology: &'static str,
+    /// QR only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ec_level: Option<&'static str>,
+    /// QR only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<i32>,
+    /// Image width in pixels (omitted for terminal output)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u32>,
+    /// Image height in pixels, when it differs from the width (linear barcodes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    height: Option<u32>,
+    /// Present (true) when --verify decoded the output successfully
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verified: Option<bool>,
+    /// base64 or data URI payload when --emit is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoded: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct DecodeItem {
     data: String,
     format: String,
-    version: i32,
+    /// QR only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<i32>,
+    bounds: Bounds,
 }
 
-#[derive(Serialize)]
+/// Axis-aligned box around a detected code, in image pixels from the top-left corner.
+#[derive(Serialize, JsonSchema)]
+struct Bounds {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    /// Corner points as detected: top-left, top-right, bottom-right, bottom-left of the code
+    corners: [[i32; 2]; 4],
+}
+
+#[derive(Serialize, JsonSchema)]
+struct DecodeListJson {
+    ok: bool,
+    count: usize,
+    items: Vec<DecodeItem>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct JoinedItem {
+    data: String,
+    /// Sequence id from the part headers
+    id: String,
+    parts: usize,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct JoinJson {
+    ok: bool,
+    item: JoinedItem,
+}
+
+#[derive(Serialize, JsonSchema)]
 struct DecodeJson {
     ok: bool,
     item: DecodeItem,
 }
 
+/// `generate` prints one of these: several codes with `--split`.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)] // only described; the variants are printed directly
+enum GenerateOutput {
+    Code(GenerateJson),
+    Split(split::SplitJson),
+}
+
+/// `decode` prints one of these: every code with `--all`, the joined data
+/// with `--join`.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)] // only described; the variants are printed directly
+enum DecodeOutput {
+    Code(DecodeJson),
+    All(DecodeListJson),
+    Joined(JoinJson),
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .custom::<GenerateOutput>("generate")
+        .custom::<batch::BatchReport>("generate-batch")
+        .custom::<DecodeOutput>("decode")
+        .custom::<batch::DirReport>("decode-dir")
+}
+
 fn main() {
+    schema::exit_on_schema(schemas);
     if let Err(err) = run() {
-        // Check if json mode was requested from raw args (run() may have failed during parse)
-        let json = std::env::args().any(|a| a == "--json" || a == "-j");
-        if json {
-            let payload = JsonError {
-                ok: false,
-                error: err.to_string(),
-                code: "INTERNAL_ERROR",
-            };
-            if let Ok(out) = serde_json::to_string_pretty(&payload) {
-                println!("{out}");
-            } else {
-                println!(
-                    "{{\"ok\":false,\"error\":\"internal error\",\"code\":\"INTERNAL_ERROR\"}}"
-                );
-            }
-        } else {
-            eprintln!("{err}");
-        }
-        std::process::exit(1);
+        report_code(&err.to_string(), "INTERNAL_ERROR", wants_json());
+        std::process::exit(exit::FAILURE);
     }
 }
 
 fn run() -> Result<()> {
-    let cli = parse_cli();
+    let cli: Cli = dee_core::cli::parse();
 
-    if cli.global.verbose {
-        eprintln!("debug: parsed command");
-    }
+    tracing::debug!("parsed command");
 
     let result = match cli.command {
         Commands::Generate(args) => handle_generate(args, &cli.global),
+        Commands::GenerateBatch(args) => batch::handle_generate_batch(args, &cli.global),
         Commands::Decode(args) => handle_decode(args, &cli.global),
+        Commands::DecodeDir(args) => batch::handle_decode_dir(args, &cli.global),
     };
 
     if let Err(err) = result {
+        let (message, code) = classify_error(&err);
         if cli.global.json {
-            let (message, code) = classify_error(&err);
-            let payload = JsonError {
-                ok: false,
-                error: message,
-                code,
-            };
-            let json = serde_json::to_string_pretty(&payload)?;
-            println!("{json}");
-            std::process::exit(1);
+            report_code(&message, code, true);
+        } else {
+            report_code(&err.to_string(), code, false);
         }
-
-        return Err(err);
+        std::process::exit(exit::for_code(code));
     }
 
     Ok(())
@@ -195,140 +572,584 @@ fn handle_generate(args: GenerateArgs, global: &GlobalFlags) -> Result<()> {
             .context("failed to read from stdin")?;
         buf.trim_end_matches('\n').to_string()
     } else {
-        args.text.unwrap_or_default()
+        args.text.clone().unwrap_or_default()
     };
 
-    let qr = QrCode::new(text.as_bytes())?;
-
-    match args.format {
-        OutputFormat::Png => {
-            let out = require_out(args.out, "png")?;
-            let img = qr.render::<Luma<u8>>().build();
-            img.save(&out)?;
-            let abs = absolute_path(&out)?;
-            emit_generate_output(&text, OutputFormat::Png, &abs, global)?;
+    let format = resolve_format(&args)?;
+    tracing::debug!("output format {}", format.as_str());
+    if format == OutputFormat::Terminal && args.emit.is_some() {
+        return Err(AppError::InvalidArgument(
+            "--emit needs an image format, not terminal".to_string(),
+        )
+        .into());
+    }
+    if args.logo.is_some() && !format.is_raster() {
+        return Err(AppError::UnsupportedOption("--logo", format.as_str()).into());
+    }
+    if args.logo.is_some() && args.symbology != Symbology::Qr {
+        return Err(AppError::UnsupportedOption("--logo", args.symbology.as_str()).into());
+    }
+    if args.verify && !(format.is_raster() || format == OutputFormat::Svg) {
+        return Err(AppError::UnsupportedOption("--verify", format.as_str()).into());
+    }
+    if format != OutputFormat::Terminal {
+        let terminal_flag = if args.invert {
+            Some("--invert")
+        } else if args.compact {
+            Some("--compact")
+        } else if args.quiet_zone.is_some() {
+            Some("--quiet-zone")
+        } else {
+            None
+        };
+        if let Some(flag) = terminal_flag {
+            return Err(AppError::UnsupportedOption(flag, format.as_str()).into());
         }
-        OutputFormat::Svg => {
-            let out = require_out(args.out, "svg")?;
-            let rendered = qr
-                .render::<svg::Color<'_>>()
-                .min_dimensions(256, 256)
-                .build();
-            fs::write(&out, rendered)?;
-            let abs = absolute_path(&out)?;
-            emit_generate_output(&text, OutputFormat::Svg, &abs, global)?;
+    }
+    if format != OutputFormat::Svg {
+        let svg_flag = if args.style != ModuleStyle::Square {
+            Some("--style")
+        } else if args.width.is_some() || args.height.is_some() {
+            Some("--width/--height")
+        } else {
+            None
+        };
+        if let Some(flag) = svg_flag {
+            return Err(AppError::UnsupportedOption(flag, format.as_str()).into());
         }
+    }
+    let custom_colors = args.fg != Rgba([0, 0, 0, 255]) || args.bg != Rgba([255, 255, 255, 255]);
+    if custom_colors && format == OutputFormat::Terminal {
+        return Err(AppError::UnsupportedOption("--fg/--bg", format.as_str()).into());
+    }
+
+    let layout = render::Layout {
+        scale: args.scale,
+        margin: args.margin,
+        min_size: args.min_size,
+    };
+    if args.split {
+        return split::handle_split(&text, format, layout, &args, global);
+    }
+
+    let (matrix, version) = build_symbol(&text, &args)?;
+    let (width_px, height_px) = layout.size_px(&matrix);
+    tracing::debug!(
+        "{} {}x{} modules, {}px per module, {width_px}x{height_px}px total",
+        args.symbology.as_str(),
+        matrix.width,
+        matrix.height,
+        layout.module_px(&matrix)
+    );
+    let meta = SymbolMeta {
+        symbology: args.symbology,
+        ec_level: version.map(|_| args.ec_level.as_str()),
+        version,
+        size: Some(width_px),
+        height: (height_px != width_px).then_some(height_px),
+    };
+
+    match format {
         OutputFormat::Terminal => {
-            let rendered = qr
-                .render::<qrcode::render::unicode::Dense1x2>()
-                .dark_color(qrcode::render::unicode::Dense1x2::Dark)
-                .light_color(qrcode::render::unicode::Dense1x2::Light)
-                .build();
+            let style = render::TerminalStyle {
+                invert: args.invert,
+                compact: args.compact,
+                quiet_zone: args.quiet_zone,
+            };
+            let rendered = render::terminal(&matrix, style);
 
             if global.json {
                 let payload = GenerateJson {
                     ok: true,
-                    message: "QR code rendered to terminal".to_string(),
-                    path: "terminal".to_string(),
+                    message: format!("{} rendered to terminal", meta.symbology.label()),
+                    path: Some("terminal".to_string()),
                     data: text,
                     format: OutputFormat::Terminal,
+                    symbology: meta.symbology.as_str(),
+                    ec_level: meta.ec_level,
+                    version: meta.version,
+                    size: None,
+                    height: None,
+                    verified: None,
+                    encoded: None,
                 };
                 println!("{}", serde_json::to_string_pretty(&payload)?);
             } else {
                 println!("{rendered}");
             }
         }
+        _ => {
+            let bytes = render_image(&matrix, format, layout, &args)?;
+            deliver_generated(&text, format, &bytes, &args, meta, global)?;
+        }
     }
 
     Ok(())
 }
 
+/// Encode a symbol as file bytes in any image (non-terminal) format.
+fn render_image(
+    matrix: &symbol::Matrix,
+    format: OutputFormat,
+    layout: render::Layout,
+    args: &GenerateArgs,
+) -> Result<Vec<u8>> {
+    let bytes = match format {
+        OutputFormat::Png | OutputFormat::Jpeg | OutputFormat::Webp => {
+            let mut img = render::raster(matrix, layout, args.fg, args.bg);
+            if let Some(logo_path) = &args.logo {
+                let logo = load_image(logo_path)?;
+                let level = args.ec_level.level();
+                let cleared = render::embed_logo(&mut img, matrix, level, layout, &logo, args.bg);
+                tracing::debug!("logo area {cleared}x{cleared}px");
+            }
+            let mut bytes = Vec::new();
+            let mut cursor = std::io::Cursor::new(&mut bytes);
+            match format {
+                // JPEG has no alpha channel
+                OutputFormat::Jpeg => image::DynamicImage::ImageRgba8(img)
+                    .to_rgb8()
+                    .write_to(&mut cursor, image::ImageFormat::Jpeg)?,
+                OutputFormat::Webp => img.write_to(&mut cursor, image::ImageFormat::WebP)?,
+                _ => img.write_to(&mut cursor, image::ImageFormat::Png)?,
+            }
+            bytes
+        }
+        OutputFormat::Svg => {
+            let style = render::SvgStyle {
+                fg: args.fg,
+                bg: args.bg,
+                rounded: args.style == ModuleStyle::Rounded,
+                width: args.width,
+                height: args.height,
+            };
+            render::svg(matrix, layout, style).into_bytes()
+        }
+        OutputFormat::Pdf => vector::pdf(matrix, layout, args.fg, args.bg),
+        OutputFormat::Eps => vector::eps(matrix, layout, args.fg, args.bg).into_bytes(),
+        OutputFormat::Terminal => unreachable!("terminal output is rendered as text"),
+    };
+    Ok(bytes)
+}
+
 fn handle_decode(args: DecodeArgs, global: &GlobalFlags) -> Result<()> {
-    ensure_supported_image(&args.image)?;
+    if args.images.len() > 1 && !args.join {
+        return Err(AppError::InvalidArgument(
+            "pass one image, or use --join to reassemble a split sequence".to_string(),
+        )
+        .into());
+    }
+    let images = match &args.data_uri {
+        Some(uri) => vec![load_image_bytes(&decode_data_uri(uri)?, "data URI")?],
+        None => args
+            .images
+            .iter()
+            .map(|path| load_decode_source(path))
+            .collect::<Result<Vec<_>>>()?,
+    };
+
+    let mut items = Vec::new();
+    let mut failed = 0;
+    for image in &images {
+        tracing::debug!("image {}x{}", image.width(), image.height());
+        let (found, undecoded) = scan_image(image)?;
+        items.extend(found);
+        failed += undecoded;
+    }
+    if failed > 0 {
+        tracing::debug!("{failed} detected grid(s) could not be decoded");
+    }
+    if items.is_empty() {
+        return Err(if failed > 0 {
+            AppError::DecodeFailed
+        } else {
+            AppError::QrNotFound
+        }
+        .into());
+    }
 
-    if !args.image.exists() {
-        return Err(AppError::FileNotFound(args.image.display().to_string()).into());
+    if args.join {
+        let parts = items
+            .iter()
+            .filter_map(|item| split::parse_part(&item.data));
+        let (data, id, total) = split::join(parts).map_err(AppError::JoinFailed)?;
+        if global.json {
+            let payload = JoinJson {
+                ok: true,
+                item: JoinedItem {
+                    data: data.clone(),
+                    id: id.to_string(),
+                    parts: total,
+                },
+            };
+            println!("{}", serde_json::to_string_pretty(&payload)?);
+        } else if global.quiet {
+            println!("{data}");
+        } else {
+            println!("Data: {data}");
+            println!("Sequence: {id} ({total} parts)");
+        }
+        return Ok(());
     }
 
-    let image = image::open(&args.image)?;
-    let gray = image.to_luma8();
-    let mut prepared = rqrr::PreparedImage::prepare(gray);
-    let grids = prepared.detect_grids();
+    if args.all {
+        if global.json {
+            let payload = DecodeListJson {
+                ok: true,
+                count: items.len(),
+                items,
+            };
+            println!("{}", serde_json::to_string_pretty(&payload)?);
+        } else {
+            for (idx, item) in items.iter().enumerate() {
+                if global.quiet {
+                    println!("{}", item.data);
+                    continue;
+                }
+                if idx > 0 {
+                    println!();
+                }
+                print_decode_item(item);
+            }
+        }
+        return Ok(());
+    }
+
+    let item = items.swap_remove(0);
+    if global.json {
+        let payload = DecodeJson { ok: true, item };
+        println!("{}", serde_json::to_string_pretty(&payload)?);
+    } else if global.quiet {
+        println!("{}", item.data);
+    } else {
+        print_decode_item(&item);
+    }
+
+    Ok(())
+}
+
+/// Properties of the generated symbol reported alongside the output path.
+struct SymbolMeta {
+    symbology: Symbology,
+    ec_level: Option<&'static str>,
+    version: Option<i32>,
+    size: Option<u32>,
+    height: Option<u32>,
+}
+
+/// Encode `text` in the requested symbology. Returns the module grid and, for QR, the version.
+fn build_symbol(text: &str, args: &GenerateArgs) -> Result<(symbol::Matrix, Option<i32>)> {
+    let matrix = match args.symbology {
+        Symbology::Qr => {
+            let qr = QrCode::with_error_correction_level(text.as_bytes(), args.ec_level.level())
+                .map_err(|err| match err {
+                    qrcode::types::QrError::DataTooLong => {
+                        anyhow::Error::new(AppError::DataTooLong(args.ec_level.as_str()))
+                    }
+                    other => anyhow::Error::new(other),
+                })?;
+            return Ok((symbol::Matrix::from_qr(&qr), Some(qr_version(&qr))));
+        }
+        Symbology::Code128 => symbol::code128(text),
+        Symbology::Ean13 => symbol::ean13(text),
+        Symbology::Datamatrix => symbol::datamatrix(text),
+    };
+    Ok((matrix.map_err(AppError::InvalidArgument)?, None))
+}
 
-    if grids.is_empty() {
-        return Err(AppError::QrNotFound.into());
+fn qr_version(qr: &QrCode) -> i32 {
+    match qr.version() {
+        qrcode::Version::Normal(v) => i32::from(v),
+        qrcode::Version::Micro(v) => i32::from(v),
     }
+}
 
-    let mut decoded_data = String::new();
-    let mut version = 0;
+/// Decode every QR grid in the image, falling back to a single barcode or Data Matrix.
+/// Returns decoded items in reading order and the number of QR grids that were detected
+/// but failed to decode.
+fn scan_image(image: &image::DynamicImage) -> Result<(Vec<DecodeItem>, usize)> {
+    let gray = image.to_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare(gray.clone());
+    let grids = prepared.detect_grids();
 
+    let mut items = Vec::with_capacity(grids.len());
+    let mut failed = 0;
     for grid in grids {
         match grid.decode() {
-            Ok((meta, content)) => {
-                decoded_data = content;
-                version = i32::try_from(meta.version.0)?;
-                break;
+            Ok((meta, content)) if !content.is_empty() => {
+                let corners = grid.bounds.map(|p| [p.x, p.y]);
+                let xs = corners.map(|c| c[0]);
+                let ys = corners.map(|c| c[1]);
+                let (min_x, max_x) = (xs.iter().min(), xs.iter().max());
+                let (min_y, max_y) = (ys.iter().min(), ys.iter().max());
+                let (Some(&min_x), Some(&max_x), Some(&min_y), Some(&max_y)) =
+                    (min_x, max_x, min_y, max_y)
+                else {
+                    continue;
+                };
+                items.push(DecodeItem {
+                    data: content,
+                    format: "QR_CODE".to_string(),
+                    version: Some(i32::try_from(meta.version.0)?),
+                    bounds: Bounds {
+                        x: min_x,
+                        y: min_y,
+                        width: max_x - min_x,
+                        height: max_y - min_y,
+                        corners,
+                    },
+                });
             }
-            Err(_) => continue,
+            _ => failed += 1,
         }
     }
 
-    if decoded_data.is_empty() {
-        return Err(AppError::DecodeFailed.into());
+    // Other symbologies are only tried when no QR code decodes
+    if items.is_empty() && failed == 0 {
+        if let Some(found) = symbol::scan(&gray) {
+            let (x, y) = (found.x as i32, found.y as i32);
+            let (x1, y1) = (x + found.width as i32, y + found.height as i32);
+            items.push(DecodeItem {
+                data: found.data,
+                format: found.format.to_string(),
+                version: None,
+                bounds: Bounds {
+                    x,
+                    y,
+                    width: x1 - x,
+                    height: y1 - y,
+                    corners: [[x, y], [x1, y], [x1, y1], [x, y1]],
+                },
+            });
+        }
     }
 
-    if global.json {
-        let payload = DecodeJson {
-            ok: true,
-            item: DecodeItem {
-                data: decoded_data,
-                format: "QR_CODE".to_string(),
-                version,
-            },
-        };
-        println!("{}", serde_json::to_string_pretty(&payload)?);
-    } else if global.quiet {
-        println!("{decoded_data}");
-    } else {
-        println!("Data: {decoded_data}");
-        println!("Format: QR_CODE");
+    items.sort_by_key(|item| (item.bounds.y, item.bounds.x));
+    Ok((items, failed))
+}
+
+fn print_decode_item(item: &DecodeItem) {
+    println!("Data: {}", item.data);
+    println!("Format: {}", item.format);
+    if let Some(version) = item.version {
         println!("Version: {version}");
     }
+    println!(
+        "Bounds: x={} y={} {}x{}",
+        item.bounds.x, item.bounds.y, item.bounds.width, item.bounds.height
+    );
+}
 
-    Ok(())
+/// Explicit --format wins; otherwise infer from the --out extension, defaulting to png.
+fn resolve_format(args: &GenerateArgs) -> Result<OutputFormat> {
+    if let Some(format) = args.format {
+        return Ok(format);
+    }
+    match args.out.as_deref() {
+        None => Ok(OutputFormat::Png),
+        Some(out) if out == Path::new("-") => Ok(OutputFormat::Png),
+        Some(out) => OutputFormat::from_extension(out).ok_or_else(|| {
+            AppError::InvalidArgument(format!(
+                "cannot infer format from '{}'; pass --format",
+                out.display()
+            ))
+            .into()
+        }),
+    }
 }
 
-fn emit_generate_output(
+/// Send rendered image bytes to the requested sinks: a file, raw stdout (`--out -`),
+/// and/or an inline base64/data URI payload (`--emit`).
+fn deliver_generated(
     text: &str,
     format: OutputFormat,
-    abs_path: &Path,
+    bytes: &[u8],
+    args: &GenerateArgs,
+    meta: SymbolMeta,
     global: &GlobalFlags,
 ) -> Result<()> {
-    let path_str = abs_path.display().to_string();
+    use base64::Engine;
+
+    if args.out.as_deref() == Some(Path::new("-")) {
+        if global.json || args.emit.is_some() {
+            return Err(AppError::InvalidArgument(
+                "--out - writes raw image bytes; use --emit with --json".to_string(),
+            )
+            .into());
+        }
+        if args.verify {
+            verify_output(bytes, format, text, args.symbology)?;
+        }
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(bytes)?;
+        stdout.flush()?;
+        return Ok(());
+    }
+
+    let written = match (&args.out, args.emit) {
+        (Some(out), _) => {
+            fs::write(out, bytes)?;
+            if args.verify {
+                // Re-read what landed on disk rather than trusting the in-memory copy
+                verify_output(&fs::read(out)?, format, text, args.symbology)?;
+            }
+            Some(absolute_path(out)?)
+        }
+        (None, Some(_)) => {
+            if args.verify {
+                verify_output(bytes, format, text, args.symbology)?;
+            }
+            None
+        }
+        (None, None) => return Err(AppError::MissingOut(format.as_str().to_string()).into()),
+    };
+    let encoded = args.emit.map(|emit| {
+        let b64 = base64::engine::general_purpose::STANDARD.encode(bytes);
+        match emit {
+            Emit::Base64 => b64,
+            Emit::DataUri => format!("data:{};base64,{b64}", format.mime()),
+        }
+    });
+    let path_str = written.as_ref().map(|p| p.display().to_string());
 
     if global.json {
+        let label = meta.symbology.label();
+        let message = match &path_str {
+            Some(path) => format!("{label} saved to {path}"),
+            None => format!("{label} encoded inline"),
+        };
         let payload = GenerateJson {
             ok: true,
-            message: format!("QR code saved to {path_str}"),
+            message,
             path: path_str,
             data: text.to_string(),
             format,
+            symbology: meta.symbology.as_str(),
+            ec_level: meta.ec_level,
+            version: meta.version,
+            size: meta.size,
+            height: meta.height,
+            verified: args.verify.then_some(true),
+            encoded,
         };
         println!("{}", serde_json::to_string_pretty(&payload)?);
-    } else if global.quiet {
-        println!("{}", abs_path.display());
-    } else {
-        println!("Saved {:?} QR to {}", format, abs_path.display());
+    } else if let Some(encoded) = encoded {
+        // stdout carries only the payload so it can be captured directly
+        println!("{encoded}");
+    } else if let Some(path) = path_str {
+        if global.quiet {
+            println!("{path}");
+        } else {
+            println!(
+                "Saved {} {} to {path}",
+                format.as_str(),
+                meta.symbology.label()
+            );
+        }
     }
 
     Ok(())
 }
 
-fn require_out(out: Option<PathBuf>, format_name: &str) -> Result<PathBuf> {
-    match out {
-        Some(path) => Ok(path),
-        None => Err(AppError::MissingOut(format_name.to_string()).into()),
+/// Decode generated output the same way `decode` would and compare it with the input.
+fn verify_output(
+    bytes: &[u8],
+    format: OutputFormat,
+    expected: &str,
+    symbology: Symbology,
+) -> Result<()> {
+    let image = if format == OutputFormat::Svg {
+        image::DynamicImage::ImageRgba8(
+            render::rasterize_svg(bytes).map_err(AppError::VerifyFailed)?,
+        )
+    } else {
+        load_image_bytes(bytes, format.as_str())?
+    };
+    // Transparent backgrounds are judged as they would look on white paper
+    let mut flat =
+        image::RgbaImage::from_pixel(image.width(), image.height(), Rgba([255, 255, 255, 255]));
+    image::imageops::overlay(&mut flat, &image.to_rgba8(), 0, 0);
+
+    let (items, _) = scan_image(&image::DynamicImage::ImageRgba8(flat))?;
+    let Some(item) = items.first() else {
+        return Err(
+            AppError::VerifyFailed("no code could be decoded from the output".to_string()).into(),
+        );
+    };
+    // EAN-13 input may omit the check digit, which the decoder always reports
+    let matches = item.data == expected
+        || (symbology == Symbology::Ean13
+            && expected.len() == 12
+            && item.data.starts_with(expected));
+    if !matches {
+        return Err(AppError::VerifyFailed(format!(
+            "decoded '{}' does not match the input",
+            item.data
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// A `decode` input: an image file, or `-` for image bytes on stdin.
+fn load_decode_source(path: &Path) -> Result<image::DynamicImage> {
+    if path.as_os_str() != "-" {
+        return load_image(path);
+    }
+    let mut buf = Vec::new();
+    std::io::stdin()
+        .read_to_end(&mut buf)
+        .context("failed to read from stdin")?;
+    if buf.is_empty() {
+        return Err(AppError::EmptyStdin.into());
+    }
+    load_image_bytes(&buf, "stdin")
+}
+
+fn load_image(path: &Path) -> Result<image::DynamicImage> {
+    ensure_supported_image(path)?;
+    if !path.exists() {
+        return Err(AppError::FileNotFound(path.display().to_string()).into());
+    }
+    image::open(path).map_err(|_| AppError::UnsupportedImage(path.display().to_string()).into())
+}
+
+fn load_image_bytes(bytes: &[u8], source: &str) -> Result<image::DynamicImage> {
+    image::load_from_memory(bytes)
+        .map_err(|_| AppError::UnsupportedImage(source.to_string()).into())
+}
+
+/// Extract the payload of a `data:[<mime>][;base64],<data>` URI. Only base64 payloads
+/// are accepted since image bytes are never sent percent-encoded in practice.
+fn decode_data_uri(uri: &str) -> Result<Vec<u8>> {
+    use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+    use base64::{alphabet, Engine};
+
+    let rest = uri
+        .trim()
+        .strip_prefix("data:")
+        .ok_or_else(|| AppError::InvalidDataUri("must start with `data:`".to_string()))?;
+    let (header, payload) = rest
+        .split_once(',')
+        .ok_or_else(|| AppError::InvalidDataUri("missing `,` before the payload".to_string()))?;
+    if !header
+        .split(';')
+        .any(|part| part.eq_ignore_ascii_case("base64"))
+    {
+        return Err(
+            AppError::InvalidDataUri("only base64 payloads are supported".to_string()).into(),
+        );
     }
+
+    // Pasted URIs are often wrapped or stripped of padding
+    let engine = GeneralPurpose::new(
+        &alphabet::STANDARD,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+    let cleaned: String = payload.chars().filter(|c| !c.is_whitespace()).collect();
+    engine
+        .decode(cleaned)
+        .map_err(|_| AppError::InvalidDataUri("payload is not valid base64".to_string()).into())
 }
 
 fn absolute_path(path: &Path) -> Result<PathBuf> {
@@ -354,47 +1175,28 @@ fn ensure_supported_image(path: &Path) -> Result<()> {
 }
 
 fn classify_error(err: &anyhow::Error) -> (String, &'static str) {
+    if let Some(err) = err.downcast_ref::<PipeError>() {
+        return (err.to_string(), err.code());
+    }
     if let Some(app) = err.downcast_ref::<AppError>() {
         match app {
             AppError::MissingOut(_) => (app.to_string(), "MISSING_ARGUMENT"),
-            AppError::QrNotFound => ("No QR code found in image".to_string(), "NOT_FOUND"),
+            AppError::QrNotFound => (
+                "No QR code or barcode found in image".to_string(),
+                "NOT_FOUND",
+            ),
             AppError::DecodeFailed => ("Failed to decode QR payload".to_string(), "DECODE_FAILED"),
             AppError::UnsupportedImage(_) => (app.to_string(), "UNSUPPORTED_FORMAT"),
-            AppError::FileNotFound(_) => (app.to_string(), "NOT_FOUND"),
+            AppError::FileNotFound(_) | AppError::DirNotFound(_) => (app.to_string(), "NOT_FOUND"),
+            AppError::VerifyFailed(_) => (app.to_string(), "VERIFY_FAILED"),
+            AppError::JoinFailed(_) => (app.to_string(), "JOIN_FAILED"),
+            AppError::DataTooLong(_)
+            | AppError::UnsupportedOption(..)
+            | AppError::InvalidArgument(_)
+            | AppError::InvalidDataUri(_)
+            | AppError::EmptyStdin => (app.to_string(), "INVALID_ARGUMENT"),
         }
     } else {
         ("Command failed".to_string(), "INTERNAL_ERROR")
     }
 }
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
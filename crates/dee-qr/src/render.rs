@@ -0,0 +1,521 @@
+use std::fmt::Write as _;
+
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, Rgba, RgbaImage};
+use qrcode::EcLevel;
+
+use crate::symbol::Matrix;
+
+/// Geometry shared by every renderer.
+#[derive(Debug, Clone, Copy)]
+pub struct Layout {
+    /// Pixels per module before `min_size` is applied
+    pub scale: u32,
+    /// Quiet zone width in modules
+    pub margin: u32,
+    /// Minimum output width in pixels; the module size grows to reach it
+    pub min_size: u32,
+}
+
+impl Layout {
+    /// Quiet zone in modules on the (left/right, top/bottom) sides.
+    pub fn quiet(&self, m: &Matrix) -> (u32, u32) {
+        (self.margin.max(m.min_quiet_x), self.margin)
+    }
+
+    /// Module size in pixels: `scale`, enlarged uniformly until the image reaches `min_size`.
+    pub fn module_px(&self, m: &Matrix) -> u32 {
+        let across = m.width + 2 * self.quiet(m).0;
+        self.scale.max(self.min_size.div_ceil(across))
+    }
+
+    /// Total image (width, height) in pixels.
+    pub fn size_px(&self, m: &Matrix) -> (u32, u32) {
+        let (qx, qy) = self.quiet(m);
+        let unit = self.module_px(m);
+        ((m.width + 2 * qx) * unit, (m.height + 2 * qy) * unit)
+    }
+}
+
+/// Every dark module as a pixel rectangle `(x, y, size)` with the top-left origin.
+fn dark_cells(m: &Matrix, layout: Layout) -> impl Iterator<Item = (u32, u32, u32)> + '_ {
+    let unit = layout.module_px(m);
+    let (qx, qy) = layout.quiet(m);
+    (0..m.height).flat_map(move |y| {
+        (0..m.width)
+            .filter(move |&x| m.is_dark(x, y))
+            .map(move |x| ((qx + x) * unit, (qy + y) * unit, unit))
+    })
+}
+
+pub fn raster(m: &Matrix, layout: Layout, fg: Rgba<u8>, bg: Rgba<u8>) -> RgbaImage {
+    let (width, height) = layout.size_px(m);
+    let mut img = RgbaImage::from_pixel(width, height, bg);
+    for (x0, y0, unit) in dark_cells(m, layout) {
+        for y in y0..y0 + unit {
+            for x in x0..x0 + unit {
+                img.put_pixel(x, y, fg);
+            }
+        }
+    }
+    img
+}
+
+/// Presentation options for SVG output.
+#[derive(Debug, Clone, Copy)]
+pub struct SvgStyle {
+    pub fg: Rgba<u8>,
+    pub bg: Rgba<u8>,
+    /// Draw modules as rounded squares instead of a crisp grid
+    pub rounded: bool,
+    /// Explicit `width`/`height` attributes; a missing one follows the aspect ratio
+    pub width: Option<SvgLength>,
+    pub height: Option<SvgLength>,
+}
+
+/// A positive SVG length with an optional absolute unit, e.g. `512`, `40mm`, `2in`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgLength {
+    pub value: f64,
+    pub unit: &'static str,
+}
+
+impl SvgLength {
+    fn scaled(self, factor: f64) -> Self {
+        Self {
+            value: self.value * factor,
+            unit: self.unit,
+        }
+    }
+}
+
+impl std::fmt::Display for SvgLength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", trim_number(self.value), self.unit)
+    }
+}
+
+/// Parse a number with an optional `px`, `mm`, `cm`, `in`, or `pt` suffix.
+pub fn parse_length(raw: &str) -> Result<SvgLength, String> {
+    let trimmed = raw.trim();
+    let split = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let unit = match unit {
+        "" | "px" => "",
+        "mm" => "mm",
+        "cm" => "cm",
+        "in" => "in",
+        "pt" => "pt",
+        _ => {
+            return Err(format!(
+                "invalid length '{raw}'; use a number with px, mm, cm, in, or pt"
+            ))
+        }
+    };
+    match number.parse::<f64>() {
+        Ok(value) if value > 0.0 && value.is_finite() => Ok(SvgLength { value, unit }),
+        _ => Err(format!(
+            "invalid length '{raw}'; expected a positive number like 512 or 40mm"
+        )),
+    }
+}
+
+/// At most three decimals, without trailing zeros.
+fn trim_number(value: f64) -> String {
+    let text = format!("{value:.3}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// `fill="#rrggbb"`, plus `fill-opacity` when the color is translucent.
+fn svg_fill(color: Rgba<u8>) -> String {
+    let [r, g, b, a] = color.0;
+    let mut attr = format!(r##"fill="#{r:02x}{g:02x}{b:02x}""##);
+    if a < 255 {
+        let _ = write!(
+            attr,
+            r#" fill-opacity="{}""#,
+            trim_number(f64::from(a) / 255.0)
+        );
+    }
+    attr
+}
+
+pub fn svg(m: &Matrix, layout: Layout, style: SvgStyle) -> String {
+    let (w, h) = layout.size_px(m);
+    let aspect = f64::from(h) / f64::from(w);
+    let (width, height) = match (style.width, style.height) {
+        (Some(width), Some(height)) => (width.to_string(), height.to_string()),
+        (Some(width), None) => (width.to_string(), width.scaled(aspect).to_string()),
+        (None, Some(height)) => (height.scaled(1.0 / aspect).to_string(), height.to_string()),
+        (None, None) => (w.to_string(), h.to_string()),
+    };
+    let rendering = if style.rounded {
+        "geometricPrecision"
+    } else {
+        "crispEdges"
+    };
+
+    let mut out = format!(
+        concat!(
+            r#"<?xml version="1.0" standalone="yes"?>"#,
+            r#"<svg xmlns="http://www.w3.org/2000/svg""#,
+            r#" version="1.1" width="{width}" height="{height}""#,
+            r#" viewBox="0 0 {w} {h}" shape-rendering="{rendering}">"#,
+        ),
+        width = width,
+        height = height,
+        w = w,
+        h = h,
+        rendering = rendering,
+    );
+    // A fully transparent background is left out entirely
+    if style.bg.0[3] > 0 {
+        let _ = write!(
+            out,
+            r#"<rect x="0" y="0" width="{w}" height="{h}" {}/>"#,
+            svg_fill(style.bg)
+        );
+    }
+    let _ = write!(out, r#"<path {} d=""#, svg_fill(style.fg));
+    for (x, y, unit) in dark_cells(m, layout) {
+        if style.rounded {
+            // Corner radius of a third of the module keeps neighbours visually joined
+            // enough for scanners while reading as dots
+            let r = trim_number(f64::from(unit) / 3.0);
+            let edge = trim_number(f64::from(unit) - 2.0 * f64::from(unit) / 3.0);
+            let _ = write!(
+                out,
+                "M{} {y}h{edge}a{r} {r} 0 0 1 {r} {r}v{edge}a{r} {r} 0 0 1 -{r} {r}h-{edge}a{r} {r} 0 0 1 -{r} -{r}v-{edge}a{r} {r} 0 0 1 {r} -{r}z",
+                trim_number(f64::from(x) + f64::from(unit) / 3.0),
+            );
+        } else {
+            let _ = write!(out, "M{x} {y}h{unit}v{unit}H{x}V{y}");
+        }
+    }
+    out.push_str(r#""/></svg>"#);
+    out
+}
+
+/// Options for text rendering.
+#[derive(Debug, Clone, Copy)]
+pub struct TerminalStyle {
+    /// Draw light modules as blocks instead of dark ones, for light-on-dark terminals
+    pub invert: bool,
+    /// Pack two module rows into each line with half-block characters
+    pub compact: bool,
+    /// Quiet zone in modules; `None` uses 4, or more when the symbology needs it
+    pub quiet_zone: Option<u32>,
+}
+
+/// Text rendering with block characters. By default each module is two full blocks wide
+/// and one line tall, which is roughly square in most terminal fonts.
+pub fn terminal(m: &Matrix, style: TerminalStyle) -> String {
+    const HALF_BLOCKS: [&str; 4] = [" ", "\u{2584}", "\u{2580}", "\u{2588}"];
+    let quiet = style.quiet_zone.unwrap_or(4.max(m.min_quiet_x));
+    let width = m.width + 2 * quiet;
+    let height = m.height + 2 * quiet;
+    let filled = |x: u32, y: u32| {
+        let dark = x >= quiet
+            && y >= quiet
+            && x < quiet + m.width
+            && y < quiet + m.height
+            && m.is_dark(x - quiet, y - quiet);
+        dark != style.invert
+    };
+
+    let mut lines = Vec::new();
+    if style.compact {
+        for top in (0..height).step_by(2) {
+            let line: String = (0..width)
+                .map(|x| {
+                    let upper = usize::from(filled(x, top));
+                    // An odd final row is paired with an unfilled one
+                    let lower = usize::from(top + 1 < height && filled(x, top + 1));
+                    HALF_BLOCKS[upper * 2 + lower]
+                })
+                .collect();
+            lines.push(line);
+        }
+    } else {
+        for y in 0..height {
+            let line: String = (0..width)
+                .map(|x| {
+                    if filled(x, y) {
+                        "\u{2588}\u{2588}"
+                    } else {
+                        "  "
+                    }
+                })
+                .collect();
+            lines.push(line);
+        }
+    }
+    lines.join("\n")
+}
+
+/// Rasterize SVG markup at its intrinsic size onto a white canvas.
+pub fn rasterize_svg(bytes: &[u8]) -> Result<RgbaImage, String> {
+    use resvg::{tiny_skia, usvg};
+
+    let tree = usvg::Tree::from_data(bytes, &usvg::Options::default())
+        .map_err(|e| format!("generated SVG could not be parsed: {e}"))?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| "generated SVG has no area".to_string())?;
+    pixmap.fill(tiny_skia::Color::WHITE);
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    // An opaque canvas means premultiplied and straight RGBA are the same bytes
+    RgbaImage::from_raw(size.width(), size.height(), pixmap.take())
+        .ok_or_else(|| "generated SVG could not be rasterized".to_string())
+}
+
+/// Largest share of the symbol width a centered logo may cover at each EC level.
+/// The covered area (the square of this) stays well inside the recoverable share
+/// so finder-adjacent damage and print defects still leave headroom.
+fn logo_fraction(level: EcLevel) -> f32 {
+    match level {
+        EcLevel::L => 0.15,
+        EcLevel::M => 0.20,
+        EcLevel::Q => 0.25,
+        EcLevel::H => 0.30,
+    }
+}
+
+/// Paint `logo` over the center of a rendered code on a `bg` backing square.
+/// Returns the side of the cleared square in pixels.
+pub fn embed_logo(
+    img: &mut RgbaImage,
+    m: &Matrix,
+    level: EcLevel,
+    layout: Layout,
+    logo: &DynamicImage,
+    bg: Rgba<u8>,
+) -> u32 {
+    let unit = layout.module_px(m);
+    let fraction = logo_fraction(level);
+    // Snap the backing square to an odd number of whole modules so it sits on the
+    // module grid and stays centered on the (odd-width) symbol
+    let mut modules = ((m.width as f32 * fraction).floor() as u32).max(1);
+    if modules.is_multiple_of(2) {
+        modules -= 1;
+    }
+    let box_px = modules * unit;
+    let across = img.width() / unit;
+    let box_origin = (across - modules) / 2 * unit;
+    for y in box_origin..box_origin + box_px {
+        for x in box_origin..box_origin + box_px {
+            img.put_pixel(x, y, bg);
+        }
+    }
+
+    // Half a module of backing on each side keeps the logo off neighbouring modules
+    let inner = box_px.saturating_sub(unit).max(1);
+    let fitted = logo.resize(inner, inner, FilterType::Lanczos3).to_rgba8();
+    let x = i64::from(box_origin + (box_px - fitted.width()) / 2);
+    let y = i64::from(box_origin + (box_px - fitted.height()) / 2);
+    imageops::overlay(img, &fitted, x, y);
+    box_px
+}
+
+/// Parse `#rgb`, `#rrggbb`, or `#rrggbbaa` (leading `#` optional).
+pub fn parse_color(raw: &str) -> Result<Rgba<u8>, String> {
+    let hex = raw.trim().trim_start_matches('#');
+    let expanded: String = match hex.len() {
+        3 => hex
+            .chars()
+            .flat_map(|c| [c, c])
+            .chain("ff".chars())
+            .collect(),
+        6 => format!("{hex}ff"),
+        8 => hex.to_string(),
+        _ => String::new(),
+    };
+    if expanded.len() != 8 || !expanded.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "invalid color '{raw}'; expected hex like #000000 or #1a73e8"
+        ));
+    }
+    let byte = |i: usize| u8::from_str_radix(&expanded[i..i + 2], 16).unwrap_or(0);
+    Ok(Rgba([byte(0), byte(2), byte(4), byte(6)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(scale: u32, margin: u32, min_size: u32) -> Layout {
+        Layout {
+            scale,
+            margin,
+            min_size,
+        }
+    }
+
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+    fn qr(data: &[u8], level: EcLevel) -> Matrix {
+        Matrix::from_qr(&qrcode::QrCode::with_error_correction_level(data, level).unwrap())
+    }
+
+    #[test]
+    fn scale_and_margin_set_image_size() {
+        // Version 1 codes are 21 modules across
+        let m = qr(b"hi", EcLevel::M);
+        assert_eq!(m.width, 21);
+
+        let img = raster(&m, layout(10, 4, 0), BLACK, WHITE);
+        assert_eq!(img.width(), (21 + 8) * 10);
+
+        let img = raster(&m, layout(3, 0, 0), BLACK, WHITE);
+        assert_eq!(img.width(), 21 * 3);
+    }
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(
+            parse_color("#1a73e8").unwrap(),
+            Rgba([0x1a, 0x73, 0xe8, 255])
+        );
+        assert_eq!(parse_color("fff").unwrap(), WHITE);
+        assert_eq!(parse_color("#00000080").unwrap(), Rgba([0, 0, 0, 0x80]));
+        assert!(parse_color("red").is_err());
+        assert!(parse_color("#12345").is_err());
+    }
+
+    #[test]
+    fn logo_box_grows_with_ec_level() {
+        let logo = DynamicImage::new_rgba8(50, 50);
+        let l = layout(10, 4, 0);
+
+        let low = qr(b"hi", EcLevel::L);
+        let mut img = raster(&low, l, BLACK, WHITE);
+        let low_box = embed_logo(&mut img, &low, EcLevel::L, l, &logo, WHITE);
+
+        let high = qr(b"hi", EcLevel::H);
+        let mut img = raster(&high, l, BLACK, WHITE);
+        let high_box = embed_logo(&mut img, &high, EcLevel::H, l, &logo, WHITE);
+
+        assert!(low_box < high_box);
+        // 30% of a 21-module symbol is 6.3 modules, snapped down to an odd 5
+        assert_eq!(high_box, 50);
+        // Center of the cleared square is background (the logo is fully transparent)
+        assert_eq!(*img.get_pixel(img.width() / 2, img.height() / 2), WHITE);
+    }
+
+    #[test]
+    fn min_size_enlarges_modules_without_distortion() {
+        let m = qr(b"hi", EcLevel::M);
+        let l = layout(2, 4, 1000);
+        // ceil(1000 / 29) = 35
+        assert_eq!(l.module_px(&m), 35);
+        assert_eq!(l.size_px(&m), (29 * 35, 29 * 35));
+        assert_eq!(raster(&m, l, BLACK, WHITE).width(), 29 * 35);
+    }
+
+    fn svg_style() -> SvgStyle {
+        SvgStyle {
+            fg: BLACK,
+            bg: WHITE,
+            rounded: false,
+            width: None,
+            height: None,
+        }
+    }
+
+    #[test]
+    fn svg_uses_layout_dimensions() {
+        let m = qr(b"hi", EcLevel::M);
+        let out = svg(&m, layout(4, 2, 0), svg_style());
+        assert!(out.contains(r#"width="100""#), "{out}");
+        assert!(out.contains(r##"fill="#000000""##), "{out}");
+    }
+
+    #[test]
+    fn svg_style_sets_colors_size_and_shape() {
+        let m = qr(b"hi", EcLevel::M);
+        let style = SvgStyle {
+            fg: Rgba([0x1a, 0x73, 0xe8, 255]),
+            bg: Rgba([255, 255, 255, 0]),
+            rounded: true,
+            width: Some(parse_length("40mm").unwrap()),
+            height: None,
+        };
+        let out = svg(&m, layout(10, 4, 0), style);
+        assert!(out.contains(r#"width="40mm" height="40mm""#), "{out}");
+        assert!(out.contains(r#"viewBox="0 0 290 290""#), "{out}");
+        assert!(out.contains(r##"<path fill="#1a73e8""##), "{out}");
+        assert!(!out.contains("<rect"), "transparent background is omitted");
+        assert!(out.contains("a3.333 3.333 0 0 1"), "{out}");
+
+        let translucent = SvgStyle {
+            bg: Rgba([255, 255, 255, 128]),
+            ..svg_style()
+        };
+        assert!(svg(&m, layout(1, 0, 0), translucent).contains(r#"fill-opacity="0.502""#));
+    }
+
+    #[test]
+    fn parses_svg_lengths() {
+        assert_eq!(
+            parse_length("512").unwrap(),
+            SvgLength {
+                value: 512.0,
+                unit: ""
+            }
+        );
+        assert_eq!(parse_length("2.5in").unwrap().to_string(), "2.5in");
+        assert_eq!(parse_length("300px").unwrap().to_string(), "300");
+        assert!(parse_length("0").is_err());
+        assert!(parse_length("10em").is_err());
+        assert!(parse_length("wide").is_err());
+    }
+
+    #[test]
+    fn terminal_styles_change_shape_and_polarity() {
+        let m = qr(b"hi", EcLevel::M);
+        let style = TerminalStyle {
+            invert: false,
+            compact: false,
+            quiet_zone: Some(1),
+        };
+
+        let full = terminal(&m, style);
+        let lines: Vec<&str> = full.lines().collect();
+        assert_eq!(lines.len(), 23);
+        assert_eq!(lines[0], " ".repeat(46));
+        // Top-left finder pattern starts one module in
+        assert!(lines[1].starts_with("  \u{2588}\u{2588}"));
+
+        let inverted = terminal(
+            &m,
+            TerminalStyle {
+                invert: true,
+                ..style
+            },
+        );
+        assert_eq!(inverted.lines().next().unwrap(), "\u{2588}".repeat(46));
+
+        let compact = terminal(
+            &m,
+            TerminalStyle {
+                compact: true,
+                ..style
+            },
+        );
+        assert_eq!(compact.lines().count(), 12);
+        assert_eq!(compact.lines().next().unwrap().chars().count(), 23);
+    }
+
+    #[test]
+    fn linear_symbols_keep_a_wide_quiet_zone() {
+        let m = crate::symbol::code128("12345678").unwrap();
+        let l = layout(2, 4, 0);
+        assert_eq!(l.quiet(&m), (10, 4));
+        let (w, h) = l.size_px(&m);
+        assert_eq!(w, (m.width + 20) * 2);
+        assert_eq!(h, (m.height + 8) * 2);
+    }
+}
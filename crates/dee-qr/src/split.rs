@@ -0,0 +1,348 @@
+//! Multi-part QR sequences for payloads larger than one code.
+//!
+//! rqrr cannot read QR structured-append segments, so each part carries a plain-text
+//! header instead: `DQR:<id>:<part>/<total>:<chunk>`. `id` is the FNV-1a 32-bit hash of
+//! the complete payload as 8 lowercase hex digits; it groups the parts and lets the
+//! joiner confirm the reassembled text. Parts are numbered from 1 and chunks are split on
+//! UTF-8 character boundaries, so every part decodes as valid text on its own.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use qrcode::{EcLevel, QrCode};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    absolute_path, qr_version, render_image, symbol, verify_output, AppError, GenerateArgs,
+    GlobalFlags, OutputFormat, Symbology,
+};
+
+const PREFIX: &str = "DQR:";
+
+/// Most parts in one sequence. `join` trusts the total a decoded header claims
+/// only up to this.
+const MAX_PARTS: usize = 1000;
+
+/// Largest byte-mode payload of a version 40 code at each error correction level.
+fn capacity(level: EcLevel) -> usize {
+    match level {
+        EcLevel::L => 2953,
+        EcLevel::M => 2331,
+        EcLevel::Q => 1663,
+        EcLevel::H => 1273,
+    }
+}
+
+fn fnv1a(data: &str) -> u32 {
+    data.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+fn header(id: &str, part: usize, total: usize) -> String {
+    format!("{PREFIX}{id}:{part}/{total}:")
+}
+
+/// Split `text` into headed part payloads. `part_size` caps the chunk bytes per part;
+/// by default each part fills one code at `level`. Returns the sequence id and payloads.
+pub fn split_payload(
+    text: &str,
+    level: EcLevel,
+    part_size: Option<usize>,
+) -> Result<(String, Vec<String>), String> {
+    let id = format!("{:08x}", fnv1a(text));
+    // The header grows with the digit count of `total`, so settle the part count first
+    let mut total = 1;
+    let budget = loop {
+        let room = capacity(level).saturating_sub(header(&id, total, total).len());
+        let budget = match part_size {
+            Some(size) if size > room => {
+                return Err(format!(
+                    "--part-size {size} exceeds the {room} bytes a part can hold at this error correction level"
+                ))
+            }
+            Some(size) => size,
+            None => room,
+        };
+        let needed = text.len().div_ceil(budget).max(1);
+        if needed.to_string().len() <= total.to_string().len() {
+            break budget;
+        }
+        total = needed;
+    };
+
+    // Spread the text evenly so parts end up at similar versions
+    let parts = text.len().div_ceil(budget).max(1);
+    let target = text.len().div_ceil(parts).max(1);
+    let mut chunks = vec![String::new()];
+    for c in text.chars() {
+        let current = chunks.last_mut().expect("chunks is never empty");
+        if !current.is_empty() && current.len() + c.len_utf8() > target {
+            chunks.push(String::new());
+        }
+        chunks.last_mut().expect("chunks is never empty").push(c);
+    }
+
+    let total = chunks.len();
+    if total > MAX_PARTS {
+        return Err(format!(
+            "the text needs {total} parts; a sequence holds at most {MAX_PARTS}"
+        ));
+    }
+    let payloads = chunks
+        .iter()
+        .enumerate()
+        .map(|(idx, chunk)| format!("{}{chunk}", header(&id, idx + 1, total)))
+        .collect();
+    Ok((id, payloads))
+}
+
+/// One decoded part of a sequence.
+#[derive(Debug, PartialEq)]
+pub struct Part<'a> {
+    pub id: &'a str,
+    pub index: usize,
+    pub total: usize,
+    pub chunk: &'a str,
+}
+
+pub fn parse_part(data: &str) -> Option<Part<'_>> {
+    let rest = data.strip_prefix(PREFIX)?;
+    let (id, rest) = rest.split_once(':')?;
+    let (position, chunk) = rest.split_once(':')?;
+    let (index, total) = position.split_once('/')?;
+    let (index, total) = (index.parse().ok()?, total.parse().ok()?);
+    if id.len() != 8 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    if index == 0 || index > total || total > MAX_PARTS {
+        return None;
+    }
+    Some(Part {
+        id,
+        index,
+        total,
+        chunk,
+    })
+}
+
+/// Reassemble a complete sequence. Duplicate parts are ignored; anything missing,
+/// mixed, or failing the checksum is an error describing what went wrong.
+pub fn join<'a>(
+    parts: impl IntoIterator<Item = Part<'a>>,
+) -> Result<(String, &'a str, usize), String> {
+    let mut sequences: BTreeMap<&str, (usize, BTreeMap<usize, &str>)> = BTreeMap::new();
+    for part in parts {
+        let entry = sequences
+            .entry(part.id)
+            .or_insert_with(|| (part.total, BTreeMap::new()));
+        if entry.0 != part.total {
+            return Err(format!("sequence {} has inconsistent part totals", part.id));
+        }
+        entry.1.insert(part.index, part.chunk);
+    }
+
+    let mut iter = sequences.into_iter();
+    let Some((id, (total, chunks))) = iter.next() else {
+        return Err("no dee-qr split parts found".to_string());
+    };
+    if iter.next().is_some() {
+        return Err(
+            "parts from more than one sequence were found; join one sequence at a time".to_string(),
+        );
+    }
+    let missing: Vec<String> = (1..=total)
+        .filter(|idx| !chunks.contains_key(idx))
+        .map(|idx| idx.to_string())
+        .collect();
+    if !missing.is_empty() {
+        let noun = if missing.len() == 1 { "part" } else { "parts" };
+        return Err(format!(
+            "missing {noun} {} of {total} in sequence {id}",
+            missing.join(", ")
+        ));
+    }
+
+    let data: String = chunks.into_values().collect();
+    if format!("{:08x}", fnv1a(&data)) != id {
+        return Err(format!("reassembled data does not match sequence {id}"));
+    }
+    Ok((data, id, total))
+}
+
+/// `label.png` becomes `label-1.png`, zero-padded to the width of `total`.
+pub(crate) fn part_path(out: &Path, part: usize, total: usize) -> PathBuf {
+    let width = total.to_string().len();
+    let stem = out
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "qr".to_string());
+    let name = match out.extension() {
+        Some(ext) => format!("{stem}-{part:0width$}.{}", ext.to_string_lossy()),
+        None => format!("{stem}-{part:0width$}"),
+    };
+    out.with_file_name(name)
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SplitItem {
+    part: usize,
+    total: usize,
+    path: String,
+    version: i32,
+    /// Image width/height in pixels
+    size: u32,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub(crate) struct SplitJson {
+    ok: bool,
+    count: usize,
+    id: String,
+    format: OutputFormat,
+    ec_level: &'static str,
+    /// Present (true) when --verify decoded every part successfully
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verified: Option<bool>,
+    items: Vec<SplitItem>,
+}
+
+pub fn handle_split(
+    text: &str,
+    format: OutputFormat,
+    layout: crate::render::Layout,
+    args: &GenerateArgs,
+    global: &GlobalFlags,
+) -> Result<()> {
+    if args.symbology != Symbology::Qr {
+        return Err(AppError::UnsupportedOption("--split", args.symbology.as_str()).into());
+    }
+    if format == OutputFormat::Terminal || args.emit.is_some() {
+        return Err(AppError::InvalidArgument(
+            "--split writes numbered files; use an image format with --out".to_string(),
+        )
+        .into());
+    }
+    let out = match args.out.as_deref() {
+        Some(out) if out != Path::new("-") => out,
+        Some(_) => {
+            return Err(AppError::InvalidArgument(
+                "--split writes numbered files and cannot use --out -".to_string(),
+            )
+            .into())
+        }
+        None => return Err(AppError::MissingOut(format.as_str().to_string()).into()),
+    };
+
+    let level = args.ec_level.level();
+    let (id, payloads) = split_payload(text, level, args.part_size.map(|n| n as usize))
+        .map_err(AppError::InvalidArgument)?;
+    tracing::debug!("sequence {id}, {} parts", payloads.len());
+
+    let total = payloads.len();
+    let mut items = Vec::with_capacity(total);
+    for (idx, payload) in payloads.iter().enumerate() {
+        let qr = QrCode::with_error_correction_level(payload.as_bytes(), level)
+            .map_err(|_| AppError::DataTooLong(args.ec_level.as_str()))?;
+        let matrix = symbol::Matrix::from_qr(&qr);
+        let bytes = render_image(&matrix, format, layout, args)?;
+        let path = part_path(out, idx + 1, total);
+        fs::write(&path, &bytes)?;
+        if args.verify {
+            verify_output(&fs::read(&path)?, format, payload, Symbology::Qr)?;
+        }
+        items.push(SplitItem {
+            part: idx + 1,
+            total,
+            path: absolute_path(&path)?.display().to_string(),
+            version: qr_version(&qr),
+            size: layout.size_px(&matrix).0,
+        });
+    }
+
+    if global.json {
+        let payload = SplitJson {
+            ok: true,
+            count: items.len(),
+            id,
+            format,
+            ec_level: args.ec_level.as_str(),
+            verified: args.verify.then_some(true),
+            items,
+        };
+        println!("{}", serde_json::to_string_pretty(&payload)?);
+    } else {
+        if !global.quiet {
+            println!(
+                "Saved {total} {} QR code parts (sequence {id}):",
+                format.as_str()
+            );
+        }
+        for item in &items {
+            println!("{}", item.path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_and_join_roundtrip() {
+        let text = "é-data ".repeat(900);
+        let (id, payloads) = split_payload(&text, EcLevel::H, None).unwrap();
+        assert!(payloads.len() > 1);
+        assert!(payloads.iter().all(|p| p.len() <= capacity(EcLevel::H)));
+        assert!(payloads[0].starts_with(&format!("DQR:{id}:1/{}:", payloads.len())));
+
+        // Order and duplicates do not matter
+        let mut parts: Vec<Part> = payloads
+            .iter()
+            .rev()
+            .filter_map(|p| parse_part(p))
+            .collect();
+        parts.push(parse_part(&payloads[0]).unwrap());
+        let (joined, joined_id, total) = join(parts).unwrap();
+        assert_eq!(joined, text);
+        assert_eq!(joined_id, id);
+        assert_eq!(total, payloads.len());
+    }
+
+    #[test]
+    fn part_size_controls_part_count() {
+        let (_, payloads) = split_payload("abcdefghij", EcLevel::M, Some(3)).unwrap();
+        assert_eq!(payloads.len(), 4);
+        assert!(split_payload("abc", EcLevel::H, Some(5000)).is_err());
+        assert!(split_payload(&"x".repeat(2001), EcLevel::M, Some(2)).is_err());
+    }
+
+    #[test]
+    fn join_reports_missing_and_mixed_parts() {
+        let (_, a) = split_payload("first sequence", EcLevel::M, Some(5)).unwrap();
+        let (_, b) = split_payload("second sequence", EcLevel::M, Some(5)).unwrap();
+
+        let err = join([parse_part(&a[0]).unwrap()]).unwrap_err();
+        assert!(err.contains("missing parts 2, 3 of 3"), "{err}");
+
+        let err = join([parse_part(&a[0]).unwrap(), parse_part(&b[0]).unwrap()]).unwrap_err();
+        assert!(err.contains("more than one sequence"), "{err}");
+
+        assert!(parse_part("hello").is_none());
+        assert!(parse_part("DQR:zz:1/2:x").is_none());
+        assert!(parse_part("DQR:0000abcd:1/18446744073709551615:x").is_none());
+    }
+
+    #[test]
+    fn numbered_paths_are_padded() {
+        assert_eq!(
+            part_path(Path::new("out/label.png"), 3, 12),
+            PathBuf::from("out/label-03.png")
+        );
+        assert_eq!(part_path(Path::new("code"), 1, 1), PathBuf::from("code-1"));
+    }
+}
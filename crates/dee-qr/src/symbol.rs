@@ -0,0 +1,424 @@
+//! Barcode symbologies reduced to a module grid the renderers understand, plus best-effort
+//! decoders for clean, axis-aligned images of the non-QR symbologies.
+
+use barcoders::sym::code128::Code128;
+use barcoders::sym::ean13::EAN13;
+use datamatrix::{DataMatrix, SymbolList};
+use image::GrayImage;
+use qrcode::{Color, QrCode};
+
+/// Dark/light modules of a symbol, row-major, without its quiet zone.
+pub struct Matrix {
+    pub width: u32,
+    pub height: u32,
+    dark: Vec<bool>,
+    /// Minimum horizontal quiet zone, in modules, the symbology requires
+    pub min_quiet_x: u32,
+}
+
+impl Matrix {
+    pub fn from_qr(qr: &QrCode) -> Self {
+        let width = qr.width() as u32;
+        Self {
+            width,
+            height: width,
+            dark: qr
+                .to_colors()
+                .into_iter()
+                .map(|c| c == Color::Dark)
+                .collect(),
+            min_quiet_x: 0,
+        }
+    }
+
+    /// Stretch a row of bars (1 = dark) into a block of identical rows.
+    fn linear(bars: &[u8]) -> Self {
+        let width = bars.len() as u32;
+        let height = (width / 3).max(24);
+        let row: Vec<bool> = bars.iter().map(|b| *b == 1).collect();
+        Self {
+            width,
+            height,
+            dark: row.repeat(height as usize),
+            // Linear scanners need a wide clear area on both ends of the bars
+            min_quiet_x: 10,
+        }
+    }
+
+    pub fn is_dark(&self, x: u32, y: u32) -> bool {
+        self.dark[(y * self.width + x) as usize]
+    }
+}
+
+/// Code 128 using character set C for even-length digit strings, else set B (printable ASCII).
+pub fn code128(text: &str) -> Result<Matrix, String> {
+    let all_digits = !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+    let prefixed = if all_digits && text.len().is_multiple_of(2) {
+        format!("\u{0106}{text}")
+    } else {
+        format!("\u{0181}{text}")
+    };
+    let code = Code128::new(prefixed)
+        .map_err(|_| "code128 accepts printable ASCII (space to ~) only".to_string())?;
+    Ok(Matrix::linear(&code.encode()))
+}
+
+/// EAN-13 from 12 digits (check digit appended) or 13 digits (check digit verified).
+pub fn ean13(text: &str) -> Result<Matrix, String> {
+    if !(text.len() == 12 || text.len() == 13) || !text.chars().all(|c| c.is_ascii_digit()) {
+        return Err("ean13 needs 12 digits, or 13 with a valid check digit".to_string());
+    }
+    let code = EAN13::new(text).map_err(|_| "ean13 check digit does not match".to_string())?;
+    Ok(Matrix::linear(&code.encode()))
+}
+
+/// ECC 200 Data Matrix, restricted to square symbol sizes.
+pub fn datamatrix(text: &str) -> Result<Matrix, String> {
+    let code = DataMatrix::encode(text.as_bytes(), SymbolList::default().enforce_square())
+        .map_err(|_| "content too long for a data matrix".to_string())?;
+    let bitmap = code.bitmap();
+    Ok(Matrix {
+        width: bitmap.width() as u32,
+        height: bitmap.height() as u32,
+        dark: bitmap.bits().to_vec(),
+        min_quiet_x: 0,
+    })
+}
+
+/// A non-QR code found by [`scan`]: format name, payload, and pixel bounding box.
+pub struct Found {
+    pub format: &'static str,
+    pub data: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Look for one EAN-13, Code 128, or Data Matrix symbol. Only clean renders (straight,
+/// unblurred, single symbol) are expected to decode.
+pub fn scan(gray: &GrayImage) -> Option<Found> {
+    let (x0, y0, x1, y1) = dark_bounds(gray)?;
+    let found = |format, data| Found {
+        format,
+        data,
+        x: x0,
+        y: y0,
+        width: x1 - x0 + 1,
+        height: y1 - y0 + 1,
+    };
+
+    // Sample a few scanlines through the middle of the marks; damaged rows are common
+    let span = y1 - y0;
+    for frac in [2, 1, 3] {
+        let y = y0 + span * frac / 4;
+        let runs = row_runs(gray, y, x0, x1);
+        if let Some(data) = decode_ean13(&runs) {
+            return Some(found("EAN_13", data));
+        }
+        if let Some(data) = decode_code128(&runs) {
+            return Some(found("CODE_128", data));
+        }
+    }
+    decode_datamatrix(gray, (x0, y0, x1, y1)).map(|data| found("DATA_MATRIX", data))
+}
+
+fn is_dark(gray: &GrayImage, x: u32, y: u32) -> bool {
+    gray.get_pixel(x, y).0[0] < 128
+}
+
+/// Smallest rectangle containing every dark pixel.
+fn dark_bounds(gray: &GrayImage) -> Option<(u32, u32, u32, u32)> {
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+    for (x, y, px) in gray.enumerate_pixels() {
+        if px.0[0] < 128 {
+            bounds = Some(match bounds {
+                None => (x, y, x, y),
+                Some((a, b, c, d)) => (a.min(x), b.min(y), c.max(x), d.max(y)),
+            });
+        }
+    }
+    bounds
+}
+
+/// Alternating run lengths along a row, starting with a dark run at `x0`.
+fn row_runs(gray: &GrayImage, y: u32, x0: u32, x1: u32) -> Vec<u32> {
+    let mut runs = Vec::new();
+    let mut current = true;
+    let mut len = 0;
+    for x in x0..=x1 {
+        if is_dark(gray, x, y) == current {
+            len += 1;
+        } else {
+            runs.push(len);
+            current = !current;
+            len = 1;
+        }
+    }
+    runs.push(len);
+    runs
+}
+
+/// Scale `runs` so they sum to `modules` and round each to whole modules.
+fn normalize(runs: &[u32], modules: u32) -> Vec<u32> {
+    let total: u32 = runs.iter().sum();
+    runs.iter()
+        .map(|r| ((r * modules) as f32 / total as f32).round().max(1.0) as u32)
+        .collect()
+}
+
+/// Widths (space, bar, space, bar) of the EAN "L" digit codes; "R" codes share the widths
+/// with colors swapped and "G" codes are the reversed widths.
+const EAN_L: [[u32; 4]; 10] = [
+    [3, 2, 1, 1],
+    [2, 2, 2, 1],
+    [2, 1, 2, 2],
+    [1, 4, 1, 1],
+    [1, 1, 3, 2],
+    [1, 2, 3, 1],
+    [1, 1, 1, 4],
+    [1, 3, 1, 2],
+    [1, 2, 1, 3],
+    [3, 1, 1, 2],
+];
+
+/// Left-half parity (false = L, true = G) selected by the implied first digit.
+const EAN_PARITY: [[bool; 6]; 10] = [
+    [false, false, false, false, false, false],
+    [false, false, true, false, true, true],
+    [false, false, true, true, false, true],
+    [false, false, true, true, true, false],
+    [false, true, false, false, true, true],
+    [false, true, true, false, false, true],
+    [false, true, true, true, false, false],
+    [false, true, false, true, false, true],
+    [false, true, false, true, true, false],
+    [false, true, true, false, true, false],
+];
+
+fn decode_ean13(runs: &[u32]) -> Option<String> {
+    // 3 guard + 24 left + 5 middle + 24 right + 3 guard runs
+    if runs.len() != 59 {
+        return None;
+    }
+    let mut digits = Vec::with_capacity(13);
+    let mut parity = [false; 6];
+    for (i, chunk) in runs[3..27].chunks(4).enumerate() {
+        let w = normalize(chunk, 7);
+        let reversed = [w[3], w[2], w[1], w[0]];
+        if let Some(d) = EAN_L.iter().position(|p| p[..] == w[..]) {
+            digits.push(d as u32);
+        } else {
+            digits.push(EAN_L.iter().position(|p| *p == reversed)? as u32);
+            parity[i] = true;
+        }
+    }
+    for chunk in runs[32..56].chunks(4) {
+        let w = normalize(chunk, 7);
+        digits.push(EAN_L.iter().position(|p| p[..] == w[..])? as u32);
+    }
+    let first = EAN_PARITY.iter().position(|p| *p == parity)? as u32;
+    digits.insert(0, first);
+
+    let sum: u32 = digits[..12]
+        .iter()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { *d } else { d * 3 })
+        .sum();
+    if (10 - sum % 10) % 10 != digits[12] {
+        return None;
+    }
+    Some(digits.iter().map(|d| d.to_string()).collect())
+}
+
+/// Bar/space widths for each Code 128 symbol value, derived from the encoder so the
+/// tables cannot drift apart. Values 0-101 plus the three start codes (103-105); FNC1 (102)
+/// cannot be produced by the encoder so its widths are spelled out.
+fn code128_patterns() -> Vec<(u32, Vec<u32>)> {
+    fn widths(bits: &[u8]) -> Vec<u32> {
+        let mut out = Vec::new();
+        let mut len = 0;
+        let mut last = bits[0];
+        for &b in bits {
+            if b == last {
+                len += 1;
+            } else {
+                out.push(len);
+                last = b;
+                len = 1;
+            }
+        }
+        out.push(len);
+        out
+    }
+    let symbol = |input: String, index: usize| {
+        Code128::new(input)
+            .ok()
+            .map(|c| widths(&c.encode()[index * 11..(index + 1) * 11]))
+    };
+
+    let mut table = Vec::with_capacity(105);
+    for value in 0..100 {
+        if let Some(w) = symbol(format!("\u{0106}{value:02}"), 1) {
+            table.push((value, w));
+        }
+    }
+    if let Some(w) = symbol("\u{0106}00\u{0181}A".to_string(), 2) {
+        table.push((100, w));
+    }
+    if let Some(w) = symbol("\u{0106}00\u{00C0}A".to_string(), 2) {
+        table.push((101, w));
+    }
+    // Only ever seen as a check symbol here
+    table.push((102, vec![4, 1, 1, 1, 3, 1]));
+    for (value, start) in [(103, '\u{00C0}'), (104, '\u{0181}'), (105, '\u{0106}')] {
+        if let Some(w) = symbol(format!("{start}11"), 0) {
+            table.push((value, w));
+        }
+    }
+    table
+}
+
+fn decode_code128(runs: &[u32]) -> Option<String> {
+    // start + at least one data symbol + check, 6 runs each, then a 7-run stop
+    if runs.len() < 25 || !(runs.len() - 7).is_multiple_of(6) {
+        return None;
+    }
+    let table = code128_patterns();
+    let lookup = |chunk: &[u32]| {
+        let w = normalize(chunk, 11);
+        table.iter().find(|(_, p)| *p == w).map(|(v, _)| *v)
+    };
+
+    let symbols = (runs.len() - 7) / 6;
+    let mut values = Vec::with_capacity(symbols);
+    for chunk in runs[..symbols * 6].chunks(6) {
+        values.push(lookup(chunk)?);
+    }
+    let stop = normalize(&runs[symbols * 6..], 13);
+    if stop != [2, 3, 3, 1, 1, 1, 2] {
+        return None;
+    }
+
+    let (start, rest) = values.split_first()?;
+    let (check, data) = rest.split_last()?;
+    let sum = data
+        .iter()
+        .enumerate()
+        .fold(*start, |acc, (i, v)| acc + (i as u32 + 1) * v);
+    if sum % 103 != *check {
+        return None;
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Set {
+        A,
+        B,
+        C,
+    }
+    let mut set = match start {
+        103 => Set::A,
+        104 => Set::B,
+        105 => Set::C,
+        _ => return None,
+    };
+    let mut out = String::new();
+    for &v in data {
+        match (set, v) {
+            (Set::C, 0..=99) => out.push_str(&format!("{v:02}")),
+            (Set::A | Set::B, 99) => set = Set::C,
+            (Set::A | Set::C, 100) => set = Set::B,
+            (Set::B | Set::C, 101) => set = Set::A,
+            (Set::A, 0..=63) | (Set::B, 0..=95) => out.push(char::from_u32(v + 32)?),
+            (Set::A, 64..=95) => out.push(char::from_u32(v - 64)?),
+            // FNC and shift codes carry no text
+            _ => {}
+        }
+    }
+    Some(out)
+}
+
+/// Sample the module grid of a Data Matrix filling `bounds` and decode it. The top edge
+/// is the alternating timing pattern, so its runs give the number of columns.
+fn decode_datamatrix(gray: &GrayImage, bounds: (u32, u32, u32, u32)) -> Option<String> {
+    let (x0, y0, x1, y1) = bounds;
+    let top = row_runs(gray, y0, x0, x1);
+    let cols = top.len() as u32;
+    let mut rows = 0;
+    let mut last = None;
+    for y in y0..=y1 {
+        let dark = is_dark(gray, x1, y);
+        if last != Some(dark) {
+            rows += 1;
+            last = Some(dark);
+        }
+    }
+    if cols < 10 || rows < 8 {
+        return None;
+    }
+
+    let cell_w = (x1 - x0 + 1) as f32 / cols as f32;
+    let cell_h = (y1 - y0 + 1) as f32 / rows as f32;
+    let mut pixels = Vec::with_capacity((cols * rows) as usize);
+    for row in 0..rows {
+        for col in 0..cols {
+            let x = x0 + ((col as f32 + 0.5) * cell_w) as u32;
+            let y = y0 + ((row as f32 + 0.5) * cell_h) as u32;
+            pixels.push(is_dark(gray, x.min(x1), y.min(y1)));
+        }
+    }
+    let bytes = DataMatrix::decode(&pixels, cols as usize).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render a matrix at `unit` pixels per module with a 10-module white border.
+    fn raster(m: &Matrix, unit: u32) -> GrayImage {
+        let pad = 10 * unit;
+        let mut img = GrayImage::from_pixel(
+            m.width * unit + 2 * pad,
+            m.height * unit + 2 * pad,
+            image::Luma([255]),
+        );
+        for y in 0..m.height * unit {
+            for x in 0..m.width * unit {
+                if m.is_dark(x / unit, y / unit) {
+                    img.put_pixel(x + pad, y + pad, image::Luma([0]));
+                }
+            }
+        }
+        img
+    }
+
+    #[test]
+    fn ean13_roundtrip_appends_check_digit() {
+        let m = ean13("400638133393").unwrap();
+        assert_eq!(m.width, 95);
+        let found = scan(&raster(&m, 3)).unwrap();
+        assert_eq!(found.format, "EAN_13");
+        assert_eq!(found.data, "4006381333931");
+        assert!(ean13("4006381333932").is_err());
+    }
+
+    #[test]
+    fn code128_roundtrip_sets_b_and_c() {
+        for text in ["Hello, World!", "12345678"] {
+            let found = scan(&raster(&code128(text).unwrap(), 2)).unwrap();
+            assert_eq!(found.format, "CODE_128");
+            assert_eq!(found.data, text);
+        }
+        assert!(code128("caf\u{e9}").is_err());
+    }
+
+    #[test]
+    fn datamatrix_roundtrip() {
+        let m = datamatrix("LOT 42 / 2024-07").unwrap();
+        assert_eq!(m.width, m.height);
+        let found = scan(&raster(&m, 6)).unwrap();
+        assert_eq!(found.format, "DATA_MATRIX");
+        assert_eq!(found.data, "LOT 42 / 2024-07");
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt::Write as _;
+
+use crate::render::Layout;
+use crate::symbol::Matrix;
+use image::Rgba;
+
+/// A horizontal run of dark modules, in module units from the top-left of the symbol.
+struct Run {
+    x: u32,
+    y: u32,
+    len: u32,
+}
+
+/// Merge adjacent dark modules on each row so vector output stays small.
+fn dark_runs(m: &Matrix) -> Vec<Run> {
+    let mut runs = Vec::new();
+    for y in 0..m.height {
+        let mut x = 0;
+        while x < m.width {
+            if m.is_dark(x, y) {
+                let start = x;
+                while x < m.width && m.is_dark(x, y) {
+                    x += 1;
+                }
+                runs.push(Run {
+                    x: start,
+                    y,
+                    len: x - start,
+                });
+            } else {
+                x += 1;
+            }
+        }
+    }
+    runs
+}
+
+/// `r g b` components in 0..1 for PDF/PostScript color operators. Alpha is ignored.
+fn rgb(color: Rgba<u8>) -> String {
+    let [r, g, b, _] = color.0;
+    format!(
+        "{:.3} {:.3} {:.3}",
+        f32::from(r) / 255.0,
+        f32::from(g) / 255.0,
+        f32::from(b) / 255.0
+    )
+}
+
+/// Drawing commands shared by PDF and EPS. Both use a bottom-left origin, one point per pixel.
+fn paint_ops(m: &Matrix, layout: Layout, fg: Rgba<u8>, bg: Rgba<u8>, pdf: bool) -> String {
+    let unit = layout.module_px(m);
+    let (width, height) = layout.size_px(m);
+    let (qx, qy) = layout.quiet(m);
+    let (fill_color, rect) = if pdf {
+        ("rg", "re f")
+    } else {
+        ("setrgbcolor", "rectfill")
+    };
+
+    let mut ops = String::new();
+    let _ = writeln!(ops, "{} {fill_color}", rgb(bg));
+    let _ = writeln!(ops, "0 0 {width} {height} {rect}");
+    let _ = writeln!(ops, "{} {fill_color}", rgb(fg));
+    for run in dark_runs(m) {
+        let x = (qx + run.x) * unit;
+        // Flip rows: module row 0 is the top edge of the page
+        let y = height - (qy + run.y + 1) * unit;
+        let _ = writeln!(ops, "{x} {y} {} {unit} {rect}", run.len * unit);
+    }
+    ops
+}
+
+/// Single-page PDF sized to the code, with modules drawn as filled rectangles.
+pub fn pdf(m: &Matrix, layout: Layout, fg: Rgba<u8>, bg: Rgba<u8>) -> Vec<u8> {
+    let (width, height) = layout.size_px(m);
+    let content = paint_ops(m, layout, fg, bg, true);
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width} {height}] /Contents 4 0 R /Resources << >> >>"
+        ),
+        format!(
+            "<< /Length {} >>\nstream\n{content}endstream",
+            content.len()
+        ),
+    ];
+
+    let mut out = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (idx, body) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        let _ = write!(out, "{} 0 obj\n{body}\nendobj\n", idx + 1);
+    }
+    let xref = out.len();
+    let _ = write!(out, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(out, "{offset:010} 00000 n ");
+    }
+    let _ = write!(
+        out,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    );
+    out.into_bytes()
+}
+
+/// Encapsulated PostScript with a tight bounding box, for print and layout tools.
+pub fn eps(m: &Matrix, layout: Layout, fg: Rgba<u8>, bg: Rgba<u8>) -> String {
+    let (width, height) = layout.size_px(m);
+    let mut out = String::new();
+    let _ = writeln!(out, "%!PS-Adobe-3.0 EPSF-3.0");
+    let _ = writeln!(out, "%%Creator: dee-qr");
+    let _ = writeln!(out, "%%BoundingBox: 0 0 {width} {height}");
+    let _ = writeln!(out, "%%Pages: 0");
+    let _ = writeln!(out, "%%EndComments");
+    out.push_str(&paint_ops(m, layout, fg, bg, false));
+    let _ = writeln!(out, "showpage");
+    let _ = writeln!(out, "%%EOF");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+    fn layout() -> Layout {
+        Layout {
+            scale: 10,
+            margin: 4,
+            min_size: 0,
+        }
+    }
+
+    #[test]
+    fn pdf_xref_offsets_point_at_objects() {
+        let m = Matrix::from_qr(&qrcode::QrCode::new(b"hi").unwrap());
+        let bytes = pdf(&m, layout(), BLACK, WHITE);
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.contains("/MediaBox [0 0 290 290]"));
+
+        let xref_at: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(text[xref_at..].starts_with("xref\n"));
+        for (idx, line) in text[xref_at..].lines().skip(3).take(4).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", idx + 1)));
+        }
+    }
+
+    #[test]
+    fn eps_has_bounding_box_and_runs() {
+        let m = Matrix::from_qr(&qrcode::QrCode::new(b"hi").unwrap());
+        let out = eps(&m, layout(), BLACK, WHITE);
+        assert!(out.starts_with("%!PS-Adobe-3.0 EPSF-3.0\n"));
+        assert!(out.contains("%%BoundingBox: 0 0 290 290\n"));
+        // Top-left finder row: 7 dark modules starting at the margin, on the top module row
+        assert!(out.contains("40 240 70 10 rectfill\n"), "{out}");
+    }
+}
@@ -71,3 +71,241 @@ fn decode_missing_file_json_error() {
         .unwrap_or_default()
         .contains("Image file not found"));
 }
+
+/// content beyond a version 40 code at the requested EC level is an invalid argument
+#[test]
+fn generate_too_long_for_ec_level_json_error() {
+    let text = "x".repeat(2000);
+    let out = bin()
+        .args([
+            "generate",
+            "--json",
+            "--format",
+            "terminal",
+            "--ec-level",
+            "h",
+        ])
+        .arg(&text)
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let parsed: serde_json::Value =
+        serde_json::from_str(stdout.trim()).expect("error must be valid JSON on stdout");
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
+/// --scale outside 1-100 is rejected by the parser
+#[test]
+fn generate_zero_scale_is_invalid_argument() {
+    let out = bin()
+        .args(["generate", "--json", "--scale", "0", "--out", "x.png", "hi"])
+        .output()
+        .unwrap();
+
+    assert_eq!(out.status.code(), Some(2));
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
+/// --logo only applies to png output
+#[test]
+fn logo_with_svg_is_invalid_argument() {
+    let out = bin()
+        .args([
+            "generate", "--json", "--format", "svg", "--out", "x.svg", "--logo", "l.png", "hi",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
+/// malformed colors are rejected at parse time
+#[test]
+fn invalid_color_is_invalid_argument() {
+    let out = bin()
+        .args(["generate", "--json", "--fg", "blue", "--out", "x.png", "hi"])
+        .output()
+        .unwrap();
+
+    assert_eq!(out.status.code(), Some(2));
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
+/// a data URI without a base64 payload is an invalid argument
+#[test]
+fn decode_non_base64_data_uri_json_error() {
+    let out = bin()
+        .args(["decode", "--json", "--data-uri", "data:text/plain,hello"])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
+/// bytes on stdin that are not an image report UNSUPPORTED_FORMAT
+#[test]
+fn decode_stdin_garbage_json_error() {
+    let out = bin()
+        .args(["decode", "-", "--json"])
+        .write_stdin("not an image")
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("UNSUPPORTED_FORMAT"));
+}
+
+/// raw stdout output cannot be combined with JSON mode
+#[test]
+fn generate_stdout_with_json_is_invalid_argument() {
+    let out = bin()
+        .args(["generate", "--json", "--out", "-", "hi"])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
+/// an unknown --out extension without --format cannot be inferred
+#[test]
+fn generate_unknown_extension_is_invalid_argument() {
+    let out = bin()
+        .args(["generate", "--json", "--out", "qr.xyz", "hi"])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
+/// EAN-13 only takes 12 or 13 digits
+#[test]
+fn generate_invalid_ean13_is_invalid_argument() {
+    let out = bin()
+        .args([
+            "generate",
+            "--json",
+            "--symbology",
+            "ean13",
+            "--out",
+            "ean.png",
+            "12345",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
+/// terminal styling flags are rejected for image formats
+#[test]
+fn terminal_flags_with_png_are_invalid_argument() {
+    let out = bin()
+        .args(["generate", "--json", "--invert", "--out", "qr.png", "hi"])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
+/// a code that cannot be read back fails --verify with its own code
+#[test]
+fn generate_unreadable_output_is_verify_failed() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("faint.png");
+    let out = bin()
+        .args([
+            "generate",
+            "--json",
+            "--verify",
+            "--fg",
+            "#f4f4f4",
+            "--out",
+            path.to_str().unwrap(),
+            "hi",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("VERIFY_FAILED"));
+}
+
+/// svg styling flags are rejected for other formats
+#[test]
+fn svg_style_with_png_is_invalid_argument() {
+    let out = bin()
+        .args([
+            "generate", "--json", "--style", "rounded", "--out", "qr.png", "hi",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
+/// decode-dir on a missing directory is NOT_FOUND
+#[test]
+fn decode_dir_missing_is_not_found() {
+    let out = bin()
+        .args(["decode-dir", "--json", "/tmp/dee-qr-no-such-dir"])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("NOT_FOUND"));
+}
+
+/// joining an incomplete sequence reports the missing parts
+#[test]
+fn join_with_missing_part_is_join_failed() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let out_path = dir.path().join("seq.png");
+    bin()
+        .args([
+            "generate",
+            "--split",
+            "--part-size",
+            "4",
+            "--out",
+            out_path.to_str().unwrap(),
+            "abcdefgh",
+        ])
+        .assert()
+        .success();
+
+    let out = bin()
+        .args([
+            "decode",
+            "--json",
+            "--join",
+            dir.path().join("seq-1.png").to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("JOIN_FAILED"));
+    assert!(parsed["error"].as_str().unwrap().contains("missing part 2"));
+}
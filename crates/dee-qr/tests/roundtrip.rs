@@ -80,3 +80,498 @@ fn generate_stdin_terminal_format() {
     let stdout = String::from_utf8_lossy(&out.stdout);
     assert!(!stdout.trim().is_empty(), "should produce terminal output");
 }
+
+/// --scale, --margin, and --min-size determine the PNG dimensions reported in JSON
+#[test]
+fn generate_png_respects_size_options() {
+    let dir = TempDir::new().unwrap();
+    let png_path = dir.path().join("sized.png");
+
+    let out = bin()
+        .args([
+            "generate",
+            "--json",
+            "--ec-level",
+            "q",
+            "--scale",
+            "5",
+            "--margin",
+            "2",
+            "--out",
+            png_path.to_str().unwrap(),
+            "hi",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["ec_level"], serde_json::json!("q"));
+    assert_eq!(parsed["version"], serde_json::json!(1));
+    // version 1 is 21 modules, plus 2 margin modules per side, at 5px each
+    assert_eq!(parsed["size"], serde_json::json!(125));
+    let img = image::open(&png_path).unwrap();
+    assert_eq!(img.width(), 125);
+
+    let out = bin()
+        .args([
+            "generate",
+            "--json",
+            "--min-size",
+            "1000",
+            "--out",
+            png_path.to_str().unwrap(),
+            "hi",
+        ])
+        .output()
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert!(parsed["size"].as_u64().unwrap() >= 1000);
+}
+
+/// --fg/--bg recolor the PNG and --logo clears a centered square for the logo
+#[test]
+fn generate_png_with_colors_and_logo() {
+    let dir = TempDir::new().unwrap();
+    let logo_path = dir.path().join("logo.png");
+    image::RgbaImage::from_pixel(40, 40, image::Rgba([255, 0, 0, 255]))
+        .save(&logo_path)
+        .unwrap();
+    let png_path = dir.path().join("branded.png");
+
+    bin()
+        .args([
+            "generate",
+            "--ec-level",
+            "h",
+            "--fg",
+            "#1a73e8",
+            "--bg",
+            "#fffff0",
+            "--logo",
+            logo_path.to_str().unwrap(),
+            "--out",
+            png_path.to_str().unwrap(),
+            "branded",
+        ])
+        .assert()
+        .success();
+
+    let img = image::open(&png_path).unwrap().to_rgba8();
+    assert_eq!(img.get_pixel(0, 0).0, [0xff, 0xff, 0xf0, 0xff]);
+    assert_eq!(
+        img.get_pixel(img.width() / 2, img.height() / 2).0,
+        [255, 0, 0, 255]
+    );
+    assert!(img.pixels().any(|p| p.0 == [0x1a, 0x73, 0xe8, 0xff]));
+}
+
+/// `decode -` reads image bytes from stdin and `--data-uri` decodes an inline image
+#[test]
+fn decode_from_stdin_and_data_uri() {
+    use base64::Engine;
+
+    let dir = TempDir::new().unwrap();
+    let png_path = dir.path().join("piped.png");
+    bin()
+        .args(["generate", "--out", png_path.to_str().unwrap(), "piped"])
+        .assert()
+        .success();
+    let bytes = std::fs::read(&png_path).unwrap();
+
+    let out = bin()
+        .args(["decode", "-", "--quiet"])
+        .write_stdin(bytes.clone())
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "piped");
+
+    let uri = format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(&bytes)
+    );
+    let out = bin()
+        .args(["decode", "--data-uri", &uri, "--quiet"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "piped");
+}
+
+/// --all reports every code in the image with its bounding box, in reading order
+#[test]
+fn decode_all_reports_each_code_with_bounds() {
+    let dir = TempDir::new().unwrap();
+    let left = dir.path().join("left.png");
+    let right = dir.path().join("right.png");
+    for (path, text) in [(&left, "left-code"), (&right, "right-code")] {
+        bin()
+            .args(["generate", "--out", path.to_str().unwrap(), text])
+            .assert()
+            .success();
+    }
+
+    let left_img = image::open(&left).unwrap().to_rgba8();
+    let right_img = image::open(&right).unwrap().to_rgba8();
+    let mut sheet = image::RgbaImage::from_pixel(
+        left_img.width() + right_img.width(),
+        left_img.height().max(right_img.height()),
+        image::Rgba([255, 255, 255, 255]),
+    );
+    image::imageops::overlay(&mut sheet, &left_img, 0, 0);
+    image::imageops::overlay(&mut sheet, &right_img, i64::from(left_img.width()), 0);
+    let sheet_path = dir.path().join("sheet.png");
+    sheet.save(&sheet_path).unwrap();
+
+    let out = bin()
+        .args(["decode", "--all", "--json", sheet_path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["count"], serde_json::json!(2));
+    assert_eq!(parsed["items"][0]["data"], serde_json::json!("left-code"));
+    assert_eq!(parsed["items"][1]["data"], serde_json::json!("right-code"));
+    let second_x = parsed["items"][1]["bounds"]["x"].as_i64().unwrap();
+    assert!(second_x >= i64::from(left_img.width()));
+    assert!(parsed["items"][0]["bounds"]["width"].as_i64().unwrap() > 0);
+}
+
+/// --emit data-uri returns the image inline and --out - streams raw bytes
+#[test]
+fn generate_emit_and_stdout_outputs() {
+    let out = bin()
+        .args(["generate", "--json", "--emit", "data-uri", "inline-me"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert!(parsed.get("path").is_none());
+    let uri = parsed["encoded"].as_str().unwrap();
+    assert!(uri.starts_with("data:image/png;base64,"));
+
+    let decoded = bin()
+        .args(["decode", "--data-uri", uri, "--quiet"])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&decoded.stdout).trim(), "inline-me");
+
+    let out = bin()
+        .args(["generate", "--format", "svg", "--emit", "base64", "x"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let text = String::from_utf8_lossy(&out.stdout);
+    assert_eq!(text.trim().lines().count(), 1);
+
+    let out = bin()
+        .args(["generate", "--out", "-", "raw-bytes"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert!(out.stdout.starts_with(b"\x89PNG"));
+}
+
+/// the format follows the --out extension when --format is omitted
+#[test]
+fn generate_infers_format_from_extension() {
+    let dir = TempDir::new().unwrap();
+    let cases = [
+        ("qr.jpg", "jpeg", &b"\xff\xd8\xff"[..]),
+        ("qr.webp", "webp", &b"RIFF"[..]),
+        ("qr.pdf", "pdf", &b"%PDF-"[..]),
+        ("qr.eps", "eps", &b"%!PS-Adobe"[..]),
+        ("qr.svg", "svg", &b"<?xml"[..]),
+    ];
+    for (name, format, magic) in cases {
+        let path = dir.path().join(name);
+        let out = bin()
+            .args(["generate", "--json", "--out", path.to_str().unwrap(), "x"])
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{name}");
+        let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        assert_eq!(parsed["format"], serde_json::json!(format));
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.starts_with(magic), "{name} starts with {magic:?}");
+    }
+
+    // Raster outputs decode back to the input
+    for name in ["qr.jpg", "qr.webp"] {
+        let out = bin()
+            .args(["decode", "--quiet", dir.path().join(name).to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "x", "{name}");
+    }
+}
+
+/// non-QR symbologies generate and decode back through the fallback scanner
+#[test]
+fn generate_other_symbologies_roundtrip() {
+    let dir = TempDir::new().unwrap();
+    let cases = [
+        ("ean13", "590123412345", "5901234123457", "EAN_13"),
+        ("code128", "SKU-0042 blue", "SKU-0042 blue", "CODE_128"),
+        ("datamatrix", "LOT 42/B", "LOT 42/B", "DATA_MATRIX"),
+    ];
+    for (symbology, input, expected, format) in cases {
+        let path = dir.path().join(format!("{symbology}.png"));
+        let out = bin()
+            .args([
+                "generate",
+                "--json",
+                "--symbology",
+                symbology,
+                "--out",
+                path.to_str().unwrap(),
+                input,
+            ])
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{symbology}");
+        let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        assert_eq!(parsed["symbology"], serde_json::json!(symbology));
+        assert!(parsed.get("version").is_none());
+
+        let out = bin()
+            .args(["decode", "--json", path.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "decode {symbology}");
+        let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        assert_eq!(parsed["item"]["data"], serde_json::json!(expected));
+        assert_eq!(parsed["item"]["format"], serde_json::json!(format));
+    }
+}
+
+/// terminal output is full-block by default; --compact halves the line count
+#[test]
+fn generate_terminal_styles() {
+    let render = |extra: &[&str]| {
+        let mut args = vec!["generate", "--format", "terminal", "hi"];
+        args.extend_from_slice(extra);
+        let out = bin().args(&args).output().unwrap();
+        assert!(out.status.success(), "{extra:?}");
+        String::from_utf8(out.stdout).unwrap()
+    };
+
+    // Version 1 is 21 modules; quiet zone 2 on each side
+    let full = render(&["--quiet-zone", "2"]);
+    assert_eq!(full.lines().count(), 25);
+    assert!(full.lines().next().unwrap().trim().is_empty());
+
+    let inverted = render(&["--quiet-zone", "2", "--invert"]);
+    assert_eq!(inverted.lines().next().unwrap(), "\u{2588}".repeat(50));
+
+    let compact = render(&["--quiet-zone", "2", "--compact"]);
+    assert_eq!(compact.lines().count(), 13);
+}
+
+/// --verify decodes the written file, including rasterized svg
+#[test]
+fn generate_verify_reports_verified() {
+    let dir = TempDir::new().unwrap();
+    for name in ["v.png", "v.svg", "v.webp"] {
+        let path = dir.path().join(name);
+        let out = bin()
+            .args([
+                "generate",
+                "--json",
+                "--verify",
+                "--out",
+                path.to_str().unwrap(),
+                "batch-item-17",
+            ])
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{name}");
+        let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        assert_eq!(parsed["verified"], serde_json::json!(true), "{name}");
+    }
+}
+
+/// styled svg keeps its viewBox, takes explicit sizing, and still decodes
+#[test]
+fn generate_styled_svg() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("styled.svg");
+    let out = bin()
+        .args([
+            "generate",
+            "--json",
+            "--verify",
+            "--style",
+            "rounded",
+            "--fg",
+            "#1a73e8",
+            "--bg",
+            "#ffffff00",
+            "--width",
+            "40mm",
+            "--out",
+            path.to_str().unwrap(),
+            "styled",
+        ])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+
+    let svg = std::fs::read_to_string(&path).unwrap();
+    assert!(svg.contains(r#"width="40mm" height="40mm""#), "{svg}");
+    assert!(svg.contains(r##"fill="#1a73e8""##), "{svg}");
+    assert!(!svg.contains("<rect"), "{svg}");
+}
+
+/// decode-dir reports every image, including failures, with a summary
+#[test]
+fn decode_dir_reports_each_file() {
+    let dir = TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join("nested")).unwrap();
+    for (name, text) in [("a.png", "first"), ("nested/b.png", "second")] {
+        bin()
+            .args([
+                "generate",
+                "--out",
+                dir.path().join(name).to_str().unwrap(),
+                text,
+            ])
+            .assert()
+            .success();
+    }
+    std::fs::write(dir.path().join("broken.png"), b"not an image").unwrap();
+    std::fs::write(dir.path().join("notes.txt"), b"ignored").unwrap();
+
+    let report = |extra: &[&str]| {
+        let mut args = vec!["decode-dir", "--json", dir.path().to_str().unwrap()];
+        args.extend_from_slice(extra);
+        let out = bin().args(&args).output().unwrap();
+        assert!(out.status.success());
+        serde_json::from_slice::<serde_json::Value>(&out.stdout).unwrap()
+    };
+
+    let flat = report(&[]);
+    assert_eq!(flat["count"], serde_json::json!(2));
+    assert_eq!(flat["decoded"], serde_json::json!(1));
+    assert_eq!(flat["failed"], serde_json::json!(1));
+    assert_eq!(flat["items"][0]["data"], serde_json::json!("first"));
+    assert_eq!(flat["items"][0]["version"], serde_json::json!(1));
+    assert_eq!(
+        flat["items"][1]["code"],
+        serde_json::json!("UNSUPPORTED_FORMAT")
+    );
+
+    let deep = report(&["--recursive"]);
+    assert_eq!(deep["count"], serde_json::json!(3));
+    assert_eq!(deep["items"][2]["data"], serde_json::json!("second"));
+}
+
+/// generate-batch writes one code per piped item, or per stdin line
+#[test]
+fn generate_batch_from_piped_json_and_lines() {
+    let dir = TempDir::new().unwrap();
+    let out_dir = dir.path().join("codes");
+    let out = bin()
+        .args([
+            "generate-batch",
+            "--stdin-json",
+            "--field",
+            "url",
+            "--out-dir",
+            out_dir.to_str().unwrap(),
+            "--json",
+        ])
+        .write_stdin(
+            r#"{"ok":true,"count":2,"items":[{"url":"https://a.example"},{"url":"https://b.example"}]}"#,
+        )
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(report["count"], serde_json::json!(2));
+    assert_eq!(report["generated"], serde_json::json!(2));
+    assert!(out_dir.join("qr-1.png").exists());
+    assert!(out_dir.join("qr-2.png").exists());
+
+    let decoded = bin()
+        .args(["decode-dir", "--json", out_dir.to_str().unwrap()])
+        .output()
+        .unwrap();
+    let decoded: serde_json::Value = serde_json::from_slice(&decoded.stdout).unwrap();
+    assert_eq!(
+        decoded["items"][1]["data"],
+        serde_json::json!("https://b.example")
+    );
+
+    // Plain lines; a bad one fails its own item
+    let out = bin()
+        .args([
+            "generate-batch",
+            "--symbology",
+            "ean13",
+            "--prefix",
+            "ean",
+            "--out-dir",
+            out_dir.to_str().unwrap(),
+            "--json",
+        ])
+        .write_stdin("5901234123457\n\nnot-a-number\n")
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(report["count"], serde_json::json!(2));
+    assert_eq!(report["failed"], serde_json::json!(1));
+    assert_eq!(
+        report["items"][1]["code"],
+        serde_json::json!("INVALID_ARGUMENT")
+    );
+    assert!(out_dir.join("ean-1.png").exists());
+}
+
+/// --split writes numbered parts that decode --join reassembles in any order
+#[test]
+fn split_then_join_roundtrip() {
+    let dir = TempDir::new().unwrap();
+    let out_path = dir.path().join("seq.png");
+    let text = "structured payload ".repeat(6);
+
+    let out = bin()
+        .args([
+            "generate",
+            "--json",
+            "--split",
+            "--part-size",
+            "40",
+            "--out",
+            out_path.to_str().unwrap(),
+            &text,
+        ])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["count"], serde_json::json!(3));
+    assert_eq!(parsed["items"][0]["total"], serde_json::json!(3));
+    for part in 1..=3 {
+        assert!(dir.path().join(format!("seq-{part}.png")).exists());
+    }
+
+    let part = |n: u32| dir.path().join(format!("seq-{n}.png"));
+    let out = bin()
+        .args([
+            "decode",
+            "--json",
+            "--join",
+            part(3).to_str().unwrap(),
+            part(1).to_str().unwrap(),
+            part(2).to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["item"]["data"], serde_json::json!(text));
+    assert_eq!(parsed["item"]["parts"], serde_json::json!(3));
+}
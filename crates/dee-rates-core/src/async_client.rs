@@ -0,0 +1,127 @@
+//! Async front for [`RatesClient`]. Each call runs the blocking client on tokio's
+//! blocking pool, so it can be awaited from any tokio runtime.
+
+use crate::client::RatesClient;
+use crate::currency::CurrencyClass;
+use crate::error::RatesError;
+use crate::models::{AlertItem, ConvertItem, DiffItem, GetItem, TableRow};
+use crate::providers::ProviderKind;
+use std::sync::Arc;
+
+/// Cheap to clone; clones share one [`RatesClient`].
+///
+/// ```no_run
+/// use dee_rates_core::{AsyncRatesClient, ProviderKind, RatesClient};
+///
+/// # async fn run() -> Result<(), dee_rates_core::RatesError> {
+/// let client = AsyncRatesClient::new(RatesClient::new(ProviderKind::CurrencyApi, None)?);
+/// let item = client.convert(100.0, "USD", "BTC").await?;
+/// println!("{} BTC", item.result);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AsyncRatesClient {
+    inner: Arc<RatesClient>,
+}
+
+impl AsyncRatesClient {
+    pub fn new(client: RatesClient) -> Self {
+        Self {
+            inner: Arc::new(client),
+        }
+    }
+
+    pub fn provider(&self) -> ProviderKind {
+        self.inner.provider()
+    }
+
+    /// See [`RatesClient::get_rates`].
+    pub async fn get_rates(&self, from: &str, targets: &[&str]) -> Result<GetItem, RatesError> {
+        let from = from.to_string();
+        let targets = owned(targets);
+        self.run(move |client| client.get_rates(&from, &targets))
+            .await
+    }
+
+    /// See [`RatesClient::convert`].
+    pub async fn convert(
+        &self,
+        amount: f64,
+        from: &str,
+        to: &str,
+    ) -> Result<ConvertItem, RatesError> {
+        let (from, to) = (from.to_string(), to.to_string());
+        self.run(move |client| client.convert(amount, &from, &to))
+            .await
+    }
+
+    /// See [`RatesClient::convert_many`].
+    pub async fn convert_many(
+        &self,
+        amount: f64,
+        from: &str,
+        targets: &[&str],
+    ) -> Result<Vec<ConvertItem>, RatesError> {
+        let from = from.to_string();
+        let targets = owned(targets);
+        self.run(move |client| client.convert_many(amount, &from, &targets))
+            .await
+    }
+
+    /// See [`RatesClient::list_currencies`].
+    pub async fn list_currencies(
+        &self,
+        class: Option<CurrencyClass>,
+    ) -> Result<Vec<String>, RatesError> {
+        self.run(move |client| client.list_currencies(class)).await
+    }
+
+    /// See [`RatesClient::table`].
+    pub async fn table(&self, pairs: &[&str], change: bool) -> Result<Vec<TableRow>, RatesError> {
+        let pairs = owned(pairs);
+        self.run(move |client| client.table(&pairs, change)).await
+    }
+
+    /// See [`RatesClient::alert`].
+    pub async fn alert(
+        &self,
+        from: &str,
+        to: &str,
+        above: Option<f64>,
+        below: Option<f64>,
+    ) -> Result<AlertItem, RatesError> {
+        let (from, to) = (from.to_string(), to.to_string());
+        self.run(move |client| client.alert(&from, &to, above, below))
+            .await
+    }
+
+    /// See [`RatesClient::diff`].
+    pub async fn diff(&self, from: &str, to: &str, days: i64) -> Result<DiffItem, RatesError> {
+        let (from, to) = (from.to_string(), to.to_string());
+        self.run(move |client| client.diff(&from, &to, days)).await
+    }
+
+    async fn run<T: Send + 'static>(
+        &self,
+        call: impl FnOnce(&RatesClient) -> Result<T, RatesError> + Send + 'static,
+    ) -> Result<T, RatesError> {
+        let client = Arc::clone(&self.inner);
+        match tokio::task::spawn_blocking(move || call(&client)).await {
+            Ok(result) => result,
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            // The runtime is shutting down
+            Err(_) => Err(RatesError::RequestFailed),
+        }
+    }
+}
+
+impl From<RatesClient> for AsyncRatesClient {
+    fn from(client: RatesClient) -> Self {
+        Self::new(client)
+    }
+}
+
+fn owned(codes: &[&str]) -> Vec<String> {
+    codes.iter().map(|c| c.to_string()).collect()
+}
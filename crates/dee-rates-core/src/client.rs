@@ -0,0 +1,510 @@
+use crate::currency::{class_of, CurrencyClass};
+use crate::error::RatesError;
+use crate::models::{AlertItem, ConvertItem, DiffItem, GetItem, TableRow};
+use crate::providers::{self, Provider, ProviderKind, Snapshot};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use dee_core::cache::{Cache, Cached};
+use dee_core::http::Http;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Cached rates are served for an hour unless set otherwise.
+pub const DEFAULT_CACHE_TTL_SECS: u64 = 3600;
+/// Name of the cache dir shared with the `dee-rates` CLI, for `dee_core::cache::Cache::new`
+pub const CACHE_TOOL: &str = "dee-rates";
+
+/// Blocking client for one provider. Responses are cached under the platform cache
+/// dir, shared with the `dee-rates` CLI.
+///
+/// ```no_run
+/// use dee_rates_core::{ProviderKind, RatesClient};
+///
+/// let client = RatesClient::new(ProviderKind::Ecb, None)?;
+/// let item = client.convert(100.0, "USD", "EUR")?;
+/// println!("{} EUR", item.result);
+/// # Ok::<(), dee_rates_core::RatesError>(())
+/// ```
+pub struct RatesClient {
+    provider: Box<dyn Provider>,
+    /// Built per call when unset: a blocking client may not be created or dropped on
+    /// an async worker thread, where `AsyncRatesClient` keeps this struct.
+    http: Option<reqwest::blocking::Client>,
+    no_cache: bool,
+    offline: bool,
+    cache_ttl_secs: u64,
+}
+
+/// A response and when it was fetched; `cached` is set when it came from the cache.
+struct Fetched<T> {
+    value: T,
+    fetched_at: DateTime<Utc>,
+    cached: bool,
+}
+
+struct BaseRates {
+    date: NaiveDate,
+    rates: HashMap<String, f64>,
+    fetched_at: DateTime<Utc>,
+    cached: bool,
+}
+
+/// ISO 4217 codes have 3 letters; crypto tickers such as USDT or DOGE have 4.
+pub fn validate_currency_code(code: &str) -> Result<(), RatesError> {
+    let trimmed = code.trim();
+    if (3..=4).contains(&trimmed.len()) && trimmed.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(())
+    } else {
+        Err(RatesError::InvalidCurrencyCode(code.to_string()))
+    }
+}
+
+/// `USD/EUR` -> (`USD`, `EUR`).
+pub fn parse_pair(pair: &str) -> Result<(String, String), RatesError> {
+    let (base, quote) = pair
+        .trim()
+        .split_once('/')
+        .ok_or_else(|| RatesError::InvalidPair(pair.to_string()))?;
+    validate_currency_code(base).map_err(|_| RatesError::InvalidPair(pair.to_string()))?;
+    validate_currency_code(quote).map_err(|_| RatesError::InvalidPair(pair.to_string()))?;
+    Ok((normalize_currency(base), normalize_currency(quote)))
+}
+
+impl RatesClient {
+    /// A client for `kind`. `exchangerate_host_key` is required for
+    /// [`ProviderKind::ExchangerateHost`] and ignored otherwise.
+    pub fn new(
+        kind: ProviderKind,
+        exchangerate_host_key: Option<&str>,
+    ) -> Result<Self, RatesError> {
+        Ok(Self {
+            provider: providers::provider(kind, exchangerate_host_key)?,
+            http: None,
+            no_cache: false,
+            offline: false,
+            cache_ttl_secs: DEFAULT_CACHE_TTL_SECS,
+        })
+    }
+
+    /// Send requests through `client`, e.g. one with the caller's User-Agent, timeout
+    /// and retries.
+    pub fn http_client(mut self, client: reqwest::blocking::Client) -> Self {
+        self.http = Some(client);
+        self
+    }
+
+    /// Skip cached copies (fresh responses are still stored).
+    pub fn no_cache(mut self, no_cache: bool) -> Self {
+        self.no_cache = no_cache;
+        self
+    }
+
+    /// Serve cached copies at any age and never touch the network.
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn cache_ttl_secs(mut self, secs: u64) -> Self {
+        self.cache_ttl_secs = secs;
+        self
+    }
+
+    pub fn provider(&self) -> ProviderKind {
+        self.provider.kind()
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Rates for `from` against each of `targets`, or against every currency when
+    /// `targets` is empty.
+    pub fn get_rates<S: AsRef<str>>(
+        &self,
+        from: &str,
+        targets: &[S],
+    ) -> Result<GetItem, RatesError> {
+        validate_currency_code(from)?;
+        for target in targets {
+            validate_currency_code(target.as_ref())?;
+        }
+        let from = normalize_currency(from);
+        let BaseRates {
+            date,
+            rates,
+            fetched_at,
+            cached,
+        } = self.fetch_base(&from, None)?;
+
+        let rates = if targets.is_empty() {
+            rates
+                .into_iter()
+                .map(|(code, rate)| (code.to_uppercase(), rate))
+                .collect()
+        } else {
+            targets
+                .iter()
+                .map(|target| {
+                    let target = normalize_currency(target.as_ref());
+                    rates
+                        .get(&target.to_lowercase())
+                        .copied()
+                        .map(|rate| (target.clone(), rate))
+                        .ok_or(RatesError::TargetCurrencyNotFound(target))
+                })
+                .collect::<Result<_, _>>()?
+        };
+
+        Ok(GetItem {
+            base: from,
+            date: iso_date(date).ok_or(RatesError::InvalidResponse)?,
+            rates,
+            rates_formatted: None,
+            provider: self.provider.kind().name().to_string(),
+            fetched_at: iso_timestamp(fetched_at),
+            cached,
+        })
+    }
+
+    /// `amount` of `from` in `to`.
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Result<ConvertItem, RatesError> {
+        let mut items = self.convert_many(amount, from, &[to])?;
+        Ok(items.remove(0))
+    }
+
+    /// `amount` of `from` in each of `targets`, from a single fetch of the `from` rates.
+    pub fn convert_many<S: AsRef<str>>(
+        &self,
+        amount: f64,
+        from: &str,
+        targets: &[S],
+    ) -> Result<Vec<ConvertItem>, RatesError> {
+        if !amount.is_finite() {
+            return Err(RatesError::InvalidAmount);
+        }
+        let payload = self.get_rates(from, targets)?;
+
+        targets
+            .iter()
+            .map(|target| {
+                let to = normalize_currency(target.as_ref());
+                let rate = payload
+                    .rates
+                    .get(&to)
+                    .copied()
+                    .ok_or_else(|| RatesError::TargetCurrencyNotFound(to.clone()))?;
+                Ok(ConvertItem {
+                    from: payload.base.clone(),
+                    to,
+                    amount,
+                    result: amount * rate,
+                    rate,
+                    amount_formatted: None,
+                    result_formatted: None,
+                    rate_formatted: None,
+                    date: payload.date.clone(),
+                    provider: payload.provider.clone(),
+                    fetched_at: payload.fetched_at.clone(),
+                    cached: payload.cached,
+                })
+            })
+            .collect()
+    }
+
+    /// Every code the provider knows, or only those of `class`.
+    pub fn list_currencies(&self, class: Option<CurrencyClass>) -> Result<Vec<String>, RatesError> {
+        let key = format!("{}/currencies.json", self.provider.kind().name());
+        let mut items: Vec<String> = self
+            .fetch_cached(&key, None, || self.provider.currencies(&self.http()?))?
+            .value;
+        if let Some(class) = class {
+            items.retain(|code| class_of(code) == class);
+        }
+        items.sort();
+        items.dedup();
+        Ok(items)
+    }
+
+    /// One row per pair, in the order given. Each base is fetched once; with `change`,
+    /// the snapshots from 1 and 7 days before the latest are fetched too. A missing
+    /// snapshot leaves that change out rather than failing the table.
+    pub fn table<S: AsRef<str>>(
+        &self,
+        pairs: &[S],
+        change: bool,
+    ) -> Result<Vec<TableRow>, RatesError> {
+        let pairs = pairs
+            .iter()
+            .map(|p| parse_pair(p.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut latest: HashMap<String, BaseRates> = HashMap::new();
+        let mut history: HashMap<(String, i64), Option<HashMap<String, f64>>> = HashMap::new();
+        let mut rows = Vec::with_capacity(pairs.len());
+
+        for (base, quote) in pairs {
+            if !latest.contains_key(&base) {
+                latest.insert(base.clone(), self.fetch_base(&base, None)?);
+            }
+            let current = &latest[&base];
+            let quote_api = quote.to_lowercase();
+            let rate = current
+                .rates
+                .get(&quote_api)
+                .copied()
+                .ok_or_else(|| RatesError::TargetCurrencyNotFound(quote.clone()))?;
+
+            let mut past = |days: i64| -> Option<f64> {
+                let snapshot = history.entry((base.clone(), days)).or_insert_with(|| {
+                    match self.fetch_base(&base, Some(current.date - Duration::days(days))) {
+                        Ok(past) => Some(past.rates),
+                        Err(err) => {
+                            tracing::debug!("no {base} rates {days} day(s) back: {err}");
+                            None
+                        }
+                    }
+                });
+                let then = snapshot.as_ref()?.get(&quote_api).copied()?;
+                percent_change(then, rate)
+            };
+            let (change_24h_pct, change_7d_pct) = if change {
+                (past(1), past(7))
+            } else {
+                (None, None)
+            };
+
+            rows.push(TableRow {
+                pair: format!("{base}/{quote}"),
+                base: base.clone(),
+                quote,
+                rate,
+                rate_formatted: None,
+                date: iso_date(current.date).ok_or(RatesError::InvalidResponse)?,
+                change_24h_pct,
+                change_7d_pct,
+                provider: self.provider.kind().name().to_string(),
+                fetched_at: iso_timestamp(current.fetched_at),
+                cached: current.cached,
+            });
+        }
+
+        Ok(rows)
+    }
+
+    /// Compare the latest `from`/`to` rate with the thresholds. The alert triggers when
+    /// the rate is at or above `above`, or at or below `below`.
+    pub fn alert(
+        &self,
+        from: &str,
+        to: &str,
+        above: Option<f64>,
+        below: Option<f64>,
+    ) -> Result<AlertItem, RatesError> {
+        for threshold in [above, below].into_iter().flatten() {
+            if !threshold.is_finite() || threshold <= 0.0 {
+                return Err(RatesError::InvalidArgument(format!(
+                    "threshold must be a positive number: {threshold}"
+                )));
+            }
+        }
+        let (base, quote) = parse_pair(&format!("{from}/{to}"))?;
+        let current = self.fetch_base(&base, None)?;
+        let rate = current
+            .rates
+            .get(&quote.to_lowercase())
+            .copied()
+            .ok_or_else(|| RatesError::TargetCurrencyNotFound(quote.clone()))?;
+        let triggered = above.is_some_and(|a| rate >= a) || below.is_some_and(|b| rate <= b);
+
+        Ok(AlertItem {
+            pair: format!("{base}/{quote}"),
+            base,
+            quote,
+            rate,
+            rate_formatted: None,
+            above,
+            below,
+            triggered,
+            date: iso_date(current.date).ok_or(RatesError::InvalidResponse)?,
+            provider: self.provider.kind().name().to_string(),
+            fetched_at: iso_timestamp(current.fetched_at),
+            cached: current.cached,
+        })
+    }
+
+    /// Change between the latest `from`/`to` rate and the snapshot `days` before it.
+    pub fn diff(&self, from: &str, to: &str, days: i64) -> Result<DiffItem, RatesError> {
+        let (base, quote) = parse_pair(&format!("{from}/{to}"))?;
+        let quote_api = quote.to_lowercase();
+        let current = self.fetch_base(&base, None)?;
+        let past = self.fetch_base(&base, Some(current.date - Duration::days(days)))?;
+
+        let rate_of = |rates: &HashMap<String, f64>| {
+            rates
+                .get(&quote_api)
+                .copied()
+                .ok_or_else(|| RatesError::TargetCurrencyNotFound(quote.clone()))
+        };
+        let rate = rate_of(&current.rates)?;
+        let past_rate = rate_of(&past.rates)?;
+
+        Ok(DiffItem {
+            pair: format!("{base}/{quote}"),
+            base: base.clone(),
+            quote: quote.clone(),
+            days,
+            rate,
+            date: iso_date(current.date).ok_or(RatesError::InvalidResponse)?,
+            past_rate,
+            past_date: iso_date(past.date).ok_or(RatesError::InvalidResponse)?,
+            change: rate - past_rate,
+            change_pct: percent_change(past_rate, rate),
+            rate_formatted: None,
+            past_rate_formatted: None,
+            change_formatted: None,
+            provider: self.provider.kind().name().to_string(),
+            fetched_at: iso_timestamp(current.fetched_at),
+            cached: current.cached,
+        })
+    }
+
+    fn http(&self) -> Result<reqwest::blocking::Client, RatesError> {
+        match &self.http {
+            Some(client) => Ok(client.clone()),
+            None => Http::new(CACHE_TOOL, env!("CARGO_PKG_VERSION"))
+                .blocking()
+                .map_err(|_| RatesError::RequestFailed),
+        }
+    }
+
+    /// Rates quoted against `base` (lowercase codes, as the API serves them) and the
+    /// date they were published.
+    fn fetch_base(&self, base: &str, date: Option<NaiveDate>) -> Result<BaseRates, RatesError> {
+        let key = format!(
+            "{}/{}.json",
+            self.provider.kind().name(),
+            base.to_lowercase()
+        );
+        let fetched: Fetched<Snapshot> = self.fetch_cached(&key, date, || {
+            self.provider
+                .rates(&self.http()?, &base.to_uppercase(), date)
+        })?;
+        Ok(BaseRates {
+            date: fetched.value.date,
+            rates: fetched.value.rates,
+            fetched_at: fetched.fetched_at,
+            cached: fetched.cached,
+        })
+    }
+
+    /// `key` from the cache when fresh enough, else from `fetch`. When the provider
+    /// cannot be reached, a stale cached copy is served instead of an error. Copies
+    /// served offline or as that fallback mark the output stale
+    /// ([`dee_core::cache::served_stale`]).
+    fn fetch_cached<T: Serialize + DeserializeOwned>(
+        &self,
+        key: &str,
+        date: Option<NaiveDate>,
+        fetch: impl FnOnce() -> Result<T, RatesError>,
+    ) -> Result<Fetched<T>, RatesError> {
+        // Only the latest rates change; dated snapshots are always fetched
+        let cache = Cache::new(CACHE_TOOL);
+        let mut cached = if date.is_none() && !self.no_cache {
+            cache.get_stale::<T>(key)
+        } else {
+            None
+        };
+
+        if let Some(hit) = cached.take_if(|hit| self.offline || hit.age_secs < self.cache_ttl_secs)
+        {
+            tracing::debug!("cached {key} ({}s old)", hit.age_secs);
+            if self.offline {
+                dee_core::cache::served_stale(hit.age_secs);
+            }
+            return Ok(from_cache(hit));
+        }
+        if self.offline {
+            return Err(RatesError::OfflineMiss(key.to_string()));
+        }
+
+        match fetch() {
+            Ok(value) => {
+                if date.is_none() {
+                    cache.put(key, &value);
+                }
+                Ok(Fetched {
+                    value,
+                    fetched_at: Utc::now(),
+                    cached: false,
+                })
+            }
+            Err(err) if err.is_transient() => match cached {
+                Some(hit) => {
+                    tracing::debug!("{err}; using cached {key}");
+                    dee_core::cache::served_stale(hit.age_secs);
+                    Ok(from_cache(hit))
+                }
+                None => Err(err),
+            },
+            Err(err) => Err(err),
+        }
+    }
+}
+
+fn percent_change(then: f64, now: f64) -> Option<f64> {
+    if then == 0.0 || !then.is_finite() {
+        None
+    } else {
+        Some((now - then) / then * 100.0)
+    }
+}
+
+fn from_cache<T>(hit: Cached<T>) -> Fetched<T> {
+    Fetched {
+        value: hit.value,
+        fetched_at: DateTime::from_timestamp(hit.fetched_at, 0).unwrap_or_default(),
+        cached: true,
+    }
+}
+
+fn normalize_currency(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+fn iso_timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+fn iso_date(parsed: NaiveDate) -> Option<String> {
+    let dt = Utc
+        .from_local_datetime(&parsed.and_hms_opt(0, 0, 0)?)
+        .single()?;
+    Some(dt.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pairs() {
+        assert_eq!(
+            parse_pair(" usd/eur ").unwrap(),
+            ("USD".to_string(), "EUR".to_string())
+        );
+        assert!(parse_pair("USDEUR").is_err());
+        assert_eq!(
+            parse_pair("btc/usdt").unwrap(),
+            ("BTC".to_string(), "USDT".to_string())
+        );
+        assert!(parse_pair("USD/EUROS").is_err());
+        assert!(parse_pair("US/EUR").is_err());
+    }
+
+    #[test]
+    fn percent_change_skips_zero_baseline() {
+        assert_eq!(percent_change(2.0, 2.5), Some(25.0));
+        assert_eq!(percent_change(0.0, 1.0), None);
+    }
+}
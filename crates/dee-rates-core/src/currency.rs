@@ -0,0 +1,62 @@
+//! Currency classes, for `list --class` in the CLI.
+//!
+//! Providers do not label their codes, so the class comes from the code itself:
+//! precious metals are the ISO 4217 `X..` metal codes, fiat is every other ISO 4217
+//! code (plus a few widely quoted non-ISO ones such as GGP), and anything else is
+//! treated as crypto.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum CurrencyClass {
+    /// Government-issued currencies (USD, EUR, JPY, ...)
+    Fiat,
+    /// Cryptocurrencies and stablecoins (BTC, ETH, USDT, ...)
+    Crypto,
+    /// Precious metals, priced per troy ounce (XAU, XAG, XPT, XPD)
+    Metal,
+}
+
+const METALS: &[&str] = &["XAG", "XAU", "XPD", "XPT"];
+
+const FIAT: &[&str] = &[
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT",
+    "BGN", "BHD", "BIF", "BMD", "BND", "BOB", "BRL", "BSD", "BTN", "BWP", "BYN", "BYR", "BZD",
+    "CAD", "CDF", "CHF", "CLF", "CLP", "CNH", "CNY", "COP", "CRC", "CUC", "CUP", "CVE", "CZK",
+    "DJF", "DKK", "DOP", "DZD", "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GGP",
+    "GHS", "GIP", "GMD", "GNF", "GTQ", "GYD", "HKD", "HNL", "HRK", "HTG", "HUF", "IDR", "ILS",
+    "IMP", "INR", "IQD", "IRR", "ISK", "JEP", "JMD", "JOD", "JPY", "KES", "KGS", "KHR", "KMF",
+    "KPW", "KRW", "KWD", "KYD", "KZT", "LAK", "LBP", "LKR", "LRD", "LSL", "LTL", "LVL", "LYD",
+    "MAD", "MDL", "MGA", "MKD", "MMK", "MNT", "MOP", "MRO", "MRU", "MUR", "MVR", "MWK", "MXN",
+    "MYR", "MZN", "NAD", "NGN", "NIO", "NOK", "NPR", "NZD", "OMR", "PAB", "PEN", "PGK", "PHP",
+    "PKR", "PLN", "PYG", "QAR", "RON", "RSD", "RUB", "RWF", "SAR", "SBD", "SCR", "SDG", "SEK",
+    "SGD", "SHP", "SLE", "SLL", "SOS", "SRD", "SSP", "STD", "STN", "SVC", "SYP", "SZL", "THB",
+    "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TVD", "TWD", "TZS", "UAH", "UGX", "USD", "UYU",
+    "UZS", "VEF", "VES", "VND", "VUV", "WST", "XAF", "XCD", "XCG", "XDR", "XOF", "XPF", "YER",
+    "ZAR", "ZMK", "ZMW", "ZWG", "ZWL",
+];
+
+/// The class of a currency code, in any case.
+pub fn class_of(code: &str) -> CurrencyClass {
+    let upper = code.trim().to_ascii_uppercase();
+    if METALS.contains(&upper.as_str()) {
+        CurrencyClass::Metal
+    } else if FIAT.contains(&upper.as_str()) {
+        CurrencyClass::Fiat
+    } else {
+        CurrencyClass::Crypto
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_codes() {
+        assert_eq!(class_of("usd"), CurrencyClass::Fiat);
+        assert_eq!(class_of("GGP"), CurrencyClass::Fiat);
+        assert_eq!(class_of("xau"), CurrencyClass::Metal);
+        assert_eq!(class_of("BTC"), CurrencyClass::Crypto);
+        assert_eq!(class_of("usdt"), CurrencyClass::Crypto);
+    }
+}
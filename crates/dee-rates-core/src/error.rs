@@ -0,0 +1,71 @@
+//! Every failure the client (and the `dee-rates` CLI on top of it) can report, each
+//! with a stable machine-readable code.
+
+use dee_core::secrets::SecretsError;
+use dee_core::ErrorCode;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RatesError {
+    #[error("Currency not found: {0}")]
+    CurrencyNotFound(String),
+    #[error("Target currency not found: {0}")]
+    TargetCurrencyNotFound(String),
+    #[error("Request failed")]
+    RequestFailed,
+    #[error("Invalid API response")]
+    InvalidResponse,
+    #[error("Amount must be finite")]
+    InvalidAmount,
+    #[error("Invalid currency code (must be 3 or 4 letters): {0}")]
+    InvalidCurrencyCode(String),
+    #[error("Invalid currency pair (expected BASE/QUOTE, e.g. USD/EUR): {0}")]
+    InvalidPair(String),
+    #[error("No pairs given and no watchlist in {0}")]
+    EmptyWatchlist(String),
+    #[error("Config file could not be read: {0}")]
+    ConfigInvalid(String),
+    #[error("No cached copy of {0} (run once without --offline)")]
+    OfflineMiss(String),
+    #[error("Missing API key: set {0} in the config file")]
+    AuthMissing(String),
+    #[error("Provider error: {0}")]
+    Api(String),
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("Config file could not be written: {0}")]
+    ConfigWrite(String),
+    #[error("{0}")]
+    ProfileNotFound(String),
+    #[error(transparent)]
+    Secrets(#[from] SecretsError),
+}
+
+impl RatesError {
+    /// The provider could not be reached or answered garbage; worth retrying later.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::RequestFailed | Self::InvalidResponse | Self::Api(_)
+        )
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::CurrencyNotFound(_) => "NOT_FOUND",
+            Self::TargetCurrencyNotFound(_) => "NOT_FOUND",
+            Self::RequestFailed => "REQUEST_FAILED",
+            Self::InvalidResponse => "BAD_RESPONSE",
+            Self::InvalidAmount => "INVALID_ARGUMENT",
+            Self::InvalidCurrencyCode(_) => "INVALID_ARGUMENT",
+            Self::InvalidPair(_) | Self::EmptyWatchlist(_) => "INVALID_ARGUMENT",
+            Self::ConfigInvalid(_) => "CONFIG_INVALID",
+            Self::OfflineMiss(_) => "OFFLINE_MISS",
+            Self::AuthMissing(_) => "AUTH_MISSING",
+            Self::Api(_) => "API_ERROR",
+            Self::InvalidArgument(_) => "INVALID_ARGUMENT",
+            Self::ConfigWrite(_) => "CONFIG_WRITE_FAILED",
+            Self::ProfileNotFound(_) => "PROFILE_NOT_FOUND",
+            Self::Secrets(err) => err.code(),
+        }
+    }
+}
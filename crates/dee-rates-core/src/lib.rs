@@ -0,0 +1,23 @@
+//! Currency rates and conversion behind the `dee-rates` CLI, as a library.
+//!
+//! [`RatesClient`] is blocking; with the default `async` feature,
+//! [`AsyncRatesClient`] offers the same calls as futures. Both share the CLI's
+//! cache, `~/.cache/dee-rates/` on Linux.
+
+#[cfg(feature = "async")]
+mod async_client;
+mod client;
+pub mod currency;
+mod error;
+pub mod models;
+pub mod providers;
+
+#[cfg(feature = "async")]
+pub use async_client::AsyncRatesClient;
+pub use client::{
+    parse_pair, validate_currency_code, RatesClient, CACHE_TOOL, DEFAULT_CACHE_TTL_SECS,
+};
+pub use currency::{class_of, CurrencyClass};
+pub use error::RatesError;
+pub use models::{AlertItem, ConvertItem, DiffItem, GetItem, TableRow};
+pub use providers::{ProviderKind, Snapshot};
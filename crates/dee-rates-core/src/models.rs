@@ -0,0 +1,115 @@
+//! Results of each client call, serialized as-is in the CLI's JSON output. The
+//! `*_formatted` fields are left empty here; the CLI fills them for `--precision` and
+//! `--locale`.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GetItem {
+    pub base: String,
+    pub date: String,
+    pub rates: HashMap<String, f64>,
+    /// `rates` as formatted strings, with `--precision` or `--locale`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rates_formatted: Option<HashMap<String, String>>,
+    /// Source of the rates, e.g. `ecb`
+    pub provider: String,
+    /// When the rates were fetched from the API
+    pub fetched_at: String,
+    /// Served from the local cache rather than a fresh request
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ConvertItem {
+    pub from: String,
+    pub to: String,
+    pub amount: f64,
+    pub result: f64,
+    pub rate: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_formatted: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_formatted: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_formatted: Option<String>,
+    pub date: String,
+    /// Source of the rates, e.g. `ecb`
+    pub provider: String,
+    pub fetched_at: String,
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AlertItem {
+    pub pair: String,
+    pub base: String,
+    pub quote: String,
+    pub rate: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_formatted: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub above: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub below: Option<f64>,
+    /// The rate has reached a threshold
+    pub triggered: bool,
+    pub date: String,
+    pub provider: String,
+    pub fetched_at: String,
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DiffItem {
+    pub pair: String,
+    pub base: String,
+    pub quote: String,
+    pub days: i64,
+    pub rate: f64,
+    pub date: String,
+    pub past_rate: f64,
+    /// Date of the past snapshot; may be earlier than `date` minus `days` for
+    /// providers that skip weekends
+    pub past_date: String,
+    /// `rate - past_rate`
+    pub change: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_pct: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_formatted: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub past_rate_formatted: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_formatted: Option<String>,
+    pub provider: String,
+    pub fetched_at: String,
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TableRow {
+    pub pair: String,
+    pub base: String,
+    pub quote: String,
+    pub rate: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_formatted: Option<String>,
+    pub date: String,
+    /// Percent change since the previous day's snapshot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_24h_pct: Option<f64>,
+    /// Percent change since the snapshot 7 days earlier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_7d_pct: Option<f64>,
+    /// Source of the rates, e.g. `ecb`
+    pub provider: String,
+    pub fetched_at: String,
+    pub cached: bool,
+}
@@ -0,0 +1,432 @@
+//! Rate sources behind one trait. Every provider answers with rates quoted against the
+//! requested base, keyed by lowercase currency code.
+
+use crate::error::RatesError;
+use chrono::{DateTime, NaiveDate};
+use reqwest::blocking::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const CURRENCY_API_PRIMARY: &str =
+    "https://cdn.jsdelivr.net/npm/@fawazahmed0/currency-api@latest/v1";
+const CURRENCY_API_FALLBACK: &str = "https://latest.currency-api.pages.dev/v1";
+const ECB_BASE: &str = "https://www.ecb.europa.eu/stats/eurofxref";
+const EXCHANGERATE_HOST_BASE: &str = "https://api.exchangerate.host";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum ProviderKind {
+    /// fawazahmed0/currency-api on jsDelivr, with a Cloudflare Pages mirror
+    #[default]
+    CurrencyApi,
+    /// European Central Bank reference rates (business days, about 30 currencies)
+    Ecb,
+    /// exchangerate.host (needs `exchangerate_host_key` in the config)
+    ExchangerateHost,
+}
+
+impl ProviderKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::CurrencyApi => "currency-api",
+            Self::Ecb => "ecb",
+            Self::ExchangerateHost => "exchangerate-host",
+        }
+    }
+}
+
+/// Rates quoted against one base, as published on `date`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub date: NaiveDate,
+    pub rates: HashMap<String, f64>,
+}
+
+pub trait Provider: Send + Sync {
+    fn kind(&self) -> ProviderKind;
+    /// Rates for `base` (uppercase code): the latest, or the last published on or
+    /// before `date`.
+    fn rates(
+        &self,
+        client: &Client,
+        base: &str,
+        date: Option<NaiveDate>,
+    ) -> Result<Snapshot, RatesError>;
+    /// Every currency code the provider quotes, uppercase.
+    fn currencies(&self, client: &Client) -> Result<Vec<String>, RatesError>;
+}
+
+pub fn provider(
+    kind: ProviderKind,
+    exchangerate_host_key: Option<&str>,
+) -> Result<Box<dyn Provider>, RatesError> {
+    Ok(match kind {
+        ProviderKind::CurrencyApi => Box::new(CurrencyApi),
+        ProviderKind::Ecb => Box::new(Ecb),
+        ProviderKind::ExchangerateHost => {
+            let key = exchangerate_host_key
+                .filter(|k| !k.trim().is_empty())
+                .ok_or_else(|| RatesError::AuthMissing("exchangerate_host_key".to_string()))?;
+            Box::new(ExchangerateHost {
+                access_key: key.trim().to_string(),
+            })
+        }
+    })
+}
+
+/// `RATES_TEST_BASE_URL` replaces every provider's host, for tests.
+fn test_base_url() -> Option<String> {
+    std::env::var("RATES_TEST_BASE_URL").ok()
+}
+
+/// The body at `url`; `shown` is the same URL without credentials, for the log.
+fn get_text(client: &Client, url: &str, shown: &str) -> Result<String, RatesError> {
+    tracing::debug!("fetching {shown}");
+    let resp = client.get(url).send().map_err(|err| {
+        tracing::debug!("request error from {shown}: {err}");
+        RatesError::RequestFailed
+    })?;
+    if !resp.status().is_success() {
+        tracing::debug!("non-success {} from {shown}", resp.status());
+        return Err(RatesError::RequestFailed);
+    }
+    resp.text().map_err(|_| RatesError::RequestFailed)
+}
+
+fn get_json<T: DeserializeOwned>(client: &Client, url: &str, shown: &str) -> Result<T, RatesError> {
+    serde_json::from_str(&get_text(client, url, shown)?).map_err(|_| RatesError::InvalidResponse)
+}
+
+/// Requote rates given per unit of some pivot currency (EUR for the ECB, USD for
+/// exchangerate.host) against `base`.
+fn rebase(
+    per_pivot: &HashMap<String, f64>,
+    base: &str,
+) -> Result<HashMap<String, f64>, RatesError> {
+    let base_rate = per_pivot
+        .get(base)
+        .copied()
+        .filter(|r| *r > 0.0)
+        .ok_or_else(|| RatesError::CurrencyNotFound(base.to_string()))?;
+    Ok(per_pivot
+        .iter()
+        .map(|(code, rate)| (code.to_lowercase(), rate / base_rate))
+        .collect())
+}
+
+pub struct CurrencyApi;
+
+#[derive(Debug, Deserialize)]
+struct CurrencyApiRates {
+    date: String,
+    #[serde(flatten)]
+    rates_by_base: HashMap<String, HashMap<String, f64>>,
+}
+
+impl CurrencyApi {
+    /// Mirrors for the latest rates, or for the snapshot published on `date`.
+    fn base_urls(date: Option<NaiveDate>) -> [String; 2] {
+        if let Some(url) = test_base_url() {
+            let url = match date {
+                Some(date) => format!("{url}/{date}"),
+                None => url,
+            };
+            [url.clone(), url]
+        } else if let Some(date) = date {
+            [
+                format!("https://cdn.jsdelivr.net/npm/@fawazahmed0/currency-api@{date}/v1"),
+                format!("https://{date}.currency-api.pages.dev/v1"),
+            ]
+        } else {
+            [
+                CURRENCY_API_PRIMARY.to_string(),
+                CURRENCY_API_FALLBACK.to_string(),
+            ]
+        }
+    }
+
+    fn fetch_json_with_fallback<T: DeserializeOwned>(
+        client: &Client,
+        path: &str,
+        date: Option<NaiveDate>,
+    ) -> Result<T, RatesError> {
+        let mut last_err = RatesError::RequestFailed;
+        for base in Self::base_urls(date) {
+            let url = format!("{base}/{path}");
+            match get_json(client, &url, &url) {
+                Ok(parsed) => return Ok(parsed),
+                Err(err) => last_err = err,
+            }
+        }
+        Err(last_err)
+    }
+}
+
+impl Provider for CurrencyApi {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::CurrencyApi
+    }
+
+    fn rates(
+        &self,
+        client: &Client,
+        base: &str,
+        date: Option<NaiveDate>,
+    ) -> Result<Snapshot, RatesError> {
+        let base_api = base.to_lowercase();
+        let payload: CurrencyApiRates =
+            Self::fetch_json_with_fallback(client, &format!("currencies/{base_api}.json"), date)?;
+        let rates = payload
+            .rates_by_base
+            .get(&base_api)
+            .cloned()
+            .ok_or_else(|| RatesError::CurrencyNotFound(base.to_string()))?;
+        let date = NaiveDate::parse_from_str(&payload.date, "%Y-%m-%d")
+            .map_err(|_| RatesError::InvalidResponse)?;
+        Ok(Snapshot { date, rates })
+    }
+
+    fn currencies(&self, client: &Client) -> Result<Vec<String>, RatesError> {
+        let payload: HashMap<String, String> =
+            Self::fetch_json_with_fallback(client, "currencies.json", None)?;
+        Ok(payload.keys().map(|k| k.to_uppercase()).collect())
+    }
+}
+
+/// One day of ECB rates, per euro
+type EcbRates = (NaiveDate, HashMap<String, f64>);
+
+/// Daily reference rates from the ECB, quoted per euro. History comes from the
+/// 90-day file, so `table --change` works but older dates do not.
+pub struct Ecb;
+
+#[derive(Debug, Deserialize)]
+struct EcbEnvelope {
+    #[serde(rename = "Cube")]
+    cube: EcbCube,
+}
+
+#[derive(Debug, Deserialize)]
+struct EcbCube {
+    #[serde(rename = "Cube", default)]
+    days: Vec<EcbDay>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EcbDay {
+    #[serde(rename = "@time")]
+    time: String,
+    #[serde(rename = "Cube", default)]
+    rates: Vec<EcbRate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EcbRate {
+    #[serde(rename = "@currency")]
+    currency: String,
+    #[serde(rename = "@rate")]
+    rate: f64,
+}
+
+impl Ecb {
+    /// Days in the file, each with its rates per euro (EUR itself included).
+    fn fetch_days(client: &Client, date: Option<NaiveDate>) -> Result<Vec<EcbRates>, RatesError> {
+        let base = test_base_url().unwrap_or_else(|| ECB_BASE.to_string());
+        let file = if date.is_some() {
+            "eurofxref-hist-90d.xml"
+        } else {
+            "eurofxref-daily.xml"
+        };
+        let url = format!("{base}/{file}");
+        let xml = get_text(client, &url, &url)?;
+        parse_ecb(&xml)
+    }
+}
+
+fn parse_ecb(xml: &str) -> Result<Vec<EcbRates>, RatesError> {
+    let envelope: EcbEnvelope =
+        quick_xml::de::from_str(xml).map_err(|_| RatesError::InvalidResponse)?;
+    envelope
+        .cube
+        .days
+        .into_iter()
+        .map(|day| {
+            let date = NaiveDate::parse_from_str(&day.time, "%Y-%m-%d")
+                .map_err(|_| RatesError::InvalidResponse)?;
+            let mut rates: HashMap<String, f64> = day
+                .rates
+                .into_iter()
+                .map(|r| (r.currency.to_uppercase(), r.rate))
+                .collect();
+            rates.insert("EUR".to_string(), 1.0);
+            Ok((date, rates))
+        })
+        .collect()
+}
+
+impl Provider for Ecb {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Ecb
+    }
+
+    fn rates(
+        &self,
+        client: &Client,
+        base: &str,
+        date: Option<NaiveDate>,
+    ) -> Result<Snapshot, RatesError> {
+        // No rates on weekends and holidays: take the last day on or before `date`
+        let (day, per_eur) = Self::fetch_days(client, date)?
+            .into_iter()
+            .filter(|(day, _)| date.is_none_or(|d| *day <= d))
+            .max_by_key(|(day, _)| *day)
+            .ok_or(RatesError::InvalidResponse)?;
+        Ok(Snapshot {
+            date: day,
+            rates: rebase(&per_eur, base)?,
+        })
+    }
+
+    fn currencies(&self, client: &Client) -> Result<Vec<String>, RatesError> {
+        let days = Self::fetch_days(client, None)?;
+        Ok(days
+            .into_iter()
+            .flat_map(|(_, rates)| rates.into_keys())
+            .collect())
+    }
+}
+
+/// exchangerate.host, always asked for USD quotes (the only source on the free plan)
+/// and requoted locally.
+pub struct ExchangerateHost {
+    access_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExchangerateHostResponse {
+    success: bool,
+    #[serde(default)]
+    error: Option<ExchangerateHostError>,
+    #[serde(default)]
+    timestamp: Option<i64>,
+    #[serde(default)]
+    date: Option<String>,
+    #[serde(default)]
+    quotes: HashMap<String, f64>,
+    #[serde(default)]
+    currencies: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExchangerateHostError {
+    #[serde(default)]
+    info: Option<String>,
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
+}
+
+impl ExchangerateHost {
+    fn call(
+        &self,
+        client: &Client,
+        endpoint: &str,
+        query: &str,
+    ) -> Result<ExchangerateHostResponse, RatesError> {
+        let base = test_base_url().unwrap_or_else(|| EXCHANGERATE_HOST_BASE.to_string());
+        let url = format!("{base}/{endpoint}?access_key={}{query}", self.access_key);
+        // Keep the key out of debug logs
+        let payload: ExchangerateHostResponse =
+            get_json(client, &url, &format!("{base}/{endpoint}"))?;
+        if !payload.success {
+            let error = payload.error.unwrap_or(ExchangerateHostError {
+                info: None,
+                kind: None,
+            });
+            return Err(RatesError::Api(
+                error
+                    .info
+                    .or(error.kind)
+                    .unwrap_or_else(|| "unknown error".to_string()),
+            ));
+        }
+        Ok(payload)
+    }
+}
+
+impl Provider for ExchangerateHost {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::ExchangerateHost
+    }
+
+    fn rates(
+        &self,
+        client: &Client,
+        base: &str,
+        date: Option<NaiveDate>,
+    ) -> Result<Snapshot, RatesError> {
+        let payload = match date {
+            Some(date) => self.call(client, "historical", &format!("&source=USD&date={date}"))?,
+            None => self.call(client, "live", "&source=USD")?,
+        };
+        let date = match (payload.date.as_deref(), payload.timestamp) {
+            (Some(date), _) => NaiveDate::parse_from_str(date, "%Y-%m-%d").ok(),
+            (None, Some(ts)) => DateTime::from_timestamp(ts, 0).map(|dt| dt.date_naive()),
+            (None, None) => None,
+        }
+        .ok_or(RatesError::InvalidResponse)?;
+
+        // Quotes are keyed `USDEUR`
+        let mut per_usd: HashMap<String, f64> = payload
+            .quotes
+            .into_iter()
+            .filter_map(|(pair, rate)| Some((pair.strip_prefix("USD")?.to_string(), rate)))
+            .collect();
+        per_usd.insert("USD".to_string(), 1.0);
+        Ok(Snapshot {
+            date,
+            rates: rebase(&per_usd, base)?,
+        })
+    }
+
+    fn currencies(&self, client: &Client) -> Result<Vec<String>, RatesError> {
+        let payload = self.call(client, "list", "")?;
+        Ok(payload.currencies.into_keys().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ecb_reference_rates() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+  <gesmes:subject>Reference rates</gesmes:subject>
+  <Cube>
+    <Cube time='2026-03-10'>
+      <Cube currency='USD' rate='1.25'/>
+      <Cube currency='GBP' rate='0.5'/>
+    </Cube>
+  </Cube>
+</gesmes:Envelope>"#;
+        let days = parse_ecb(xml).unwrap();
+        assert_eq!(days.len(), 1);
+        let rates = rebase(&days[0].1, "USD").unwrap();
+        assert_eq!(rates["eur"], 0.8);
+        assert_eq!(rates["gbp"], 0.4);
+        assert_eq!(rates["usd"], 1.0);
+    }
+
+    #[test]
+    fn rebase_rejects_unknown_base() {
+        let rates = HashMap::from([("EUR".to_string(), 1.0)]);
+        assert!(matches!(
+            rebase(&rates, "XYZ"),
+            Err(RatesError::CurrencyNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+use dee_rates_core::{AsyncRatesClient, CurrencyClass, ProviderKind, RatesClient, RatesError};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+
+/// Serve USD rates and a small currency list.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let body = match line.split_whitespace().nth(1).unwrap_or("") {
+                "/currencies.json" => r#"{"usd":"US Dollar","eur":"Euro","btc":"Bitcoin"}"#,
+                "/currencies/usd.json" => {
+                    r#"{"date":"2026-03-10","usd":{"eur":0.9,"btc":0.00001}}"#
+                }
+                _ => "",
+            };
+            let status = if body.is_empty() {
+                "404 Not Found"
+            } else {
+                "200 OK"
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}")
+}
+
+// One test, since the client reads its host and cache dir from the process environment
+#[tokio::test(flavor = "multi_thread")]
+async fn blocking_and_async_clients() {
+    let data = tempfile::tempdir().unwrap();
+    std::env::set_var("XDG_CACHE_HOME", data.path());
+    std::env::set_var("RATES_TEST_BASE_URL", serve());
+
+    let client = RatesClient::new(ProviderKind::CurrencyApi, None)
+        .unwrap()
+        .no_cache(true);
+    let client = AsyncRatesClient::new(client);
+
+    let item = client.convert(100.0, "usd", "EUR").await.unwrap();
+    assert_eq!(item.from, "USD");
+    assert!((item.result - 90.0).abs() < 1e-9);
+    assert_eq!(item.provider, "currency-api");
+
+    let crypto = client
+        .list_currencies(Some(CurrencyClass::Crypto))
+        .await
+        .unwrap();
+    assert_eq!(crypto, ["BTC"]);
+
+    let err = client.convert(1.0, "USD", "GBP").await.unwrap_err();
+    assert!(matches!(err, RatesError::TargetCurrencyNotFound(_)));
+
+    // The blocking client must not run on an async worker thread
+    let items = tokio::task::spawn_blocking(|| {
+        let client = RatesClient::new(ProviderKind::CurrencyApi, None).unwrap();
+        client.convert_many(2.0, "USD", &["EUR", "BTC"])
+    })
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(items.len(), 2);
+    assert!((items[1].result - 0.00002).abs() < 1e-12);
+    // Served from the copy the async client stored, despite its own `no_cache`
+    assert!(items[0].cached);
+
+    assert!(matches!(
+        RatesClient::new(ProviderKind::ExchangerateHost, None),
+        Err(RatesError::AuthMissing(_))
+    ));
+}
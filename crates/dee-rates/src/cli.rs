@@ -1,11 +1,16 @@
-use clap::{Args, Parser, Subcommand};
+use crate::format::Rounding;
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use dee_core::cache::{CacheArgs, CacheCommand};
+use dee_core::http::HttpArgs;
+use dee_core::Format;
+use dee_rates_core::{CurrencyClass, ProviderKind};
 
 #[derive(Parser, Debug)]
 #[command(
     name = "dee-rates",
     version,
     about = "Currency exchange rates and conversions",
-    after_help = "EXAMPLES:\n  dee-rates get USD\n  dee-rates get USD EUR --json\n  dee-rates convert 100 USD EUR\n  dee-rates convert 100 USD EUR --json\n  dee-rates list --json"
+    after_help = "EXAMPLES:\n  dee-rates get USD\n  dee-rates get USD EUR --json\n  dee-rates convert 100 USD EUR\n  dee-rates convert 100 USD EUR --json\n  dee-rates list --json\n  dee-rates list --class crypto\n  dee-rates table --change\n  dee-rates table USD/EUR GBP/USD --json\n  dee-rates get USD EUR --offline\n  dee-rates convert 100 EUR USD --provider ecb\n  dee-rates alert USD EUR --above 0.95\n  dee-rates alert USD EUR --below 0.9 --watch --interval 1h\n  dee-rates diff USD EUR --days 7 --json\n  dee-rates convert 1234.5 USD EUR --precision 2 --locale de\n  dee-rates convert 1000 USD BTC --precision 2\n  dee-rates config set base EUR\n  dee-rates config set favorites USD,GBP,JPY\n  dee-rates cache clear"
 )]
 pub struct Cli {
     #[command(subcommand)]
@@ -25,26 +30,119 @@ pub struct GlobalFlags {
 
     #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    #[command(flatten)]
+    pub cache: CacheArgs,
+
+    /// Rate source (default: `provider` in the config file, else currency-api)
+    #[arg(long, global = true, value_enum)]
+    pub provider: Option<ProviderKind>,
+
+    /// Round numbers to N decimal places (adds `*_formatted` fields to JSON)
+    #[arg(long, global = true, value_parser = clap::value_parser!(u8).range(0..=12))]
+    pub precision: Option<u8>,
+
+    /// Thousands and decimal separators, e.g. en, de, fr, de-CH (adds `*_formatted` fields to JSON)
+    #[arg(long, global = true)]
+    pub locale: Option<String>,
+
+    /// How --precision breaks ties (default: half-up)
+    #[arg(long, global = true, value_enum)]
+    pub round: Option<Rounding>,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    pub output: Option<Format>,
+
+    #[command(flatten)]
+    pub http: HttpArgs,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Get rates for a base currency, optionally only one target currency
     Get {
-        /// Base currency code, e.g. USD
-        from: String,
-        /// Optional target currency code, e.g. EUR
+        /// Base currency code, e.g. USD (default: `base` in the config file)
+        from: Option<String>,
+        /// Optional target currency code, e.g. EUR (default: `favorites` in the config file)
         to: Option<String>,
+        /// Every rate, even when favorites are configured
+        #[arg(long, conflicts_with = "to")]
+        all: bool,
     },
     /// Convert amount between currencies
     Convert {
         /// Amount to convert
         amount: f64,
-        /// Source currency code
+        /// Source currency code (default: `base` in the config file)
+        from: Option<String>,
+        /// Target currency code (default: each of `favorites` in the config file)
+        to: Option<String>,
+    },
+    /// List all available currency codes
+    List {
+        /// Only codes of this class
+        #[arg(long, value_enum)]
+        class: Option<CurrencyClass>,
+    },
+    /// Rates for a watchlist of pairs from the config file (or the pairs given)
+    Table {
+        /// Pairs such as USD/EUR (default: `watchlist` in the config file)
+        pairs: Vec<String>,
+        /// Add percent change over the last day and the last 7 days
+        #[arg(long)]
+        change: bool,
+    },
+    /// Change in a rate over the last N days
+    Diff {
+        /// Base currency code, e.g. USD
         from: String,
-        /// Target currency code
+        /// Target currency code, e.g. EUR
         to: String,
+        /// How many days back to compare against
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(i64).range(1..=365))]
+        days: i64,
     },
-    /// List all available currency codes
-    List,
+    /// Show or change defaults in the config file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Check a rate against thresholds; exits 0 when triggered, 3 when not
+    #[command(group(ArgGroup::new("threshold").required(true).multiple(true).args(["above", "below"])))]
+    Alert {
+        /// Base currency code, e.g. USD
+        from: String,
+        /// Target currency code, e.g. EUR
+        to: String,
+        /// Trigger when the rate is at or above this value
+        #[arg(long)]
+        above: Option<f64>,
+        /// Trigger when the rate is at or below this value
+        #[arg(long)]
+        below: Option<f64>,
+        /// Keep checking until the alert triggers
+        #[arg(long)]
+        watch: bool,
+        /// Time between checks with --watch (s, m, h or d; at least 1m)
+        #[arg(long, default_value = "1h", requires = "watch")]
+        interval: String,
+    },
+    /// Manage cached rates
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Set a key: base, favorites, precision, watchlist, provider, cache_ttl_secs,
+    /// exchangerate_host_key, secrets_backend (an empty value clears it; lists are
+    /// comma-separated)
+    Set { key: String, value: String },
+    /// Show the settings in effect
+    Show,
+    /// Print the config file path
+    Path,
 }
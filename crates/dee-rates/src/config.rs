@@ -0,0 +1,192 @@
+use clap::ValueEnum;
+use dee_core::{Config, ConfigError, SecretsBackend};
+use dee_rates_core::{
+    parse_pair, validate_currency_code, ProviderKind, RatesError, DEFAULT_CACHE_TTL_SECS,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Keys accepted by `config set`
+pub const CONFIG_KEYS: &[&str] = &[
+    "base",
+    "favorites",
+    "precision",
+    "watchlist",
+    "provider",
+    "cache_ttl_secs",
+    "exchangerate_host_key",
+    "secrets_backend",
+];
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AppConfig {
+    /// Base currency when `get` or `convert` is run without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Targets for `get` and `convert` when none is given
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub favorites: Vec<String>,
+    /// Decimal places when `--precision` is not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precision: Option<u8>,
+    /// Pairs shown by `table`, written as `BASE/QUOTE`
+    #[serde(default)]
+    pub watchlist: Vec<String>,
+    /// Seconds cached rates stay fresh (default one hour)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_secs: Option<u64>,
+    /// Rate source when `--provider` is not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderKind>,
+    /// Access key for the exchangerate.host provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exchangerate_host_key: Option<String>,
+    /// `plaintext` (default) or `keyring`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secrets_backend: Option<SecretsBackend>,
+}
+
+/// `config show`: the settings in effect, with the access key masked.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConfigView {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    pub favorites: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precision: Option<u8>,
+    pub watchlist: Vec<String>,
+    pub provider: ProviderKind,
+    pub cache_ttl_secs: u64,
+    pub exchangerate_host_key_set: bool,
+    pub secrets_backend: SecretsBackend,
+}
+
+impl AppConfig {
+    pub fn cache_ttl_secs(&self) -> u64 {
+        self.cache_ttl_secs.unwrap_or(DEFAULT_CACHE_TTL_SECS)
+    }
+
+    pub fn view(&self) -> ConfigView {
+        ConfigView {
+            base: self.base.clone(),
+            favorites: self.favorites.clone(),
+            precision: self.precision,
+            watchlist: self.watchlist.clone(),
+            provider: self.provider.unwrap_or_default(),
+            cache_ttl_secs: self.cache_ttl_secs(),
+            exchangerate_host_key_set: self.exchangerate_host_key.is_some(),
+            secrets_backend: self.secrets_backend.unwrap_or_default(),
+        }
+    }
+
+    /// Validate and store one `config set` value. An empty value clears the key; lists
+    /// are comma-separated.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), RatesError> {
+        let value = value.trim();
+        let list = || -> Vec<String> {
+            value
+                .split(',')
+                .map(|v| v.trim().to_uppercase())
+                .filter(|v| !v.is_empty())
+                .collect()
+        };
+        let invalid = |what: &str| RatesError::InvalidArgument(format!("invalid {key}: {what}"));
+
+        match key {
+            "base" if value.is_empty() => self.base = None,
+            "base" => {
+                validate_currency_code(value)?;
+                self.base = Some(value.to_uppercase());
+            }
+            "favorites" => {
+                let codes = list();
+                for code in &codes {
+                    validate_currency_code(code)?;
+                }
+                self.favorites = codes;
+            }
+            "precision" if value.is_empty() => self.precision = None,
+            "precision" => {
+                let precision: u8 = value
+                    .parse()
+                    .ok()
+                    .filter(|p| *p <= 12)
+                    .ok_or_else(|| invalid("expected 0-12"))?;
+                self.precision = Some(precision);
+            }
+            "watchlist" => {
+                let pairs = list();
+                for pair in &pairs {
+                    parse_pair(pair)?;
+                }
+                self.watchlist = pairs;
+            }
+            "provider" if value.is_empty() => self.provider = None,
+            "provider" => {
+                let provider = ProviderKind::from_str(value, true)
+                    .map_err(|_| invalid("expected currency-api, ecb or exchangerate-host"))?;
+                self.provider = Some(provider);
+            }
+            "cache_ttl_secs" if value.is_empty() => self.cache_ttl_secs = None,
+            "cache_ttl_secs" => {
+                let secs = value.parse().map_err(|_| invalid("expected seconds"))?;
+                self.cache_ttl_secs = Some(secs);
+            }
+            "exchangerate_host_key" => {
+                self.exchangerate_host_key = (!value.is_empty()).then(|| value.to_string());
+            }
+            "secrets_backend" if value.is_empty() => self.secrets_backend = None,
+            "secrets_backend" => {
+                self.secrets_backend = Some(value.parse().map_err(RatesError::InvalidArgument)?);
+            }
+            other => {
+                return Err(RatesError::InvalidArgument(format!(
+                    "unknown config key '{other}' (expected one of: {})",
+                    CONFIG_KEYS.join(", ")
+                )))
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `DEE_RATES_<KEY>` overrides any key in the file, e.g. `DEE_RATES_BASE=EUR` or
+/// `DEE_RATES_FAVORITES=USD,GBP`. With `secrets_backend = "keyring"` the access key
+/// lives in the OS keyring instead of the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-rates")
+        .env_overrides(CONFIG_KEYS)
+        .secret("exchangerate_host_key")
+}
+
+pub fn config_path() -> PathBuf {
+    config_file().path()
+}
+
+/// The settings in effect, env overrides included.
+pub fn load_config() -> Result<AppConfig, RatesError> {
+    config_file().load().map_err(rates_error)
+}
+
+/// The file as written, for `config set` to edit and save back.
+pub fn load_config_file() -> Result<AppConfig, RatesError> {
+    config_file().load_file().map_err(rates_error)
+}
+
+pub fn save_config(cfg: &AppConfig) -> Result<(), RatesError> {
+    config_file().save(cfg).map_err(rates_error)
+}
+
+fn rates_error(err: ConfigError) -> RatesError {
+    match err {
+        ConfigError::Invalid { path, message } => {
+            RatesError::ConfigInvalid(format!("{}: {message}", path.display()))
+        }
+        ConfigError::Write { path, message } => {
+            RatesError::ConfigWrite(format!("{}: {message}", path.display()))
+        }
+        err @ ConfigError::UnknownProfile { .. } => RatesError::ProfileNotFound(err.to_string()),
+        ConfigError::Secret(err) => RatesError::Secrets(err),
+    }
+}
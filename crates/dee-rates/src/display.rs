@@ -1,4 +1,6 @@
-use crate::models::{ConvertItem, GetItem};
+use crate::config::ConfigView;
+use dee_core::style;
+use dee_rates_core::{AlertItem, ConvertItem, DiffItem, GetItem, TableRow};
 
 pub fn print_get(item: &GetItem, quiet: bool) {
     if quiet {
@@ -7,33 +9,95 @@ pub fn print_get(item: &GetItem, quiet: bool) {
         return;
     }
 
-    println!("Base: {}", item.base.to_uppercase());
+    println!(
+        "{}",
+        style::header(format!("Base: {}", item.base.to_uppercase()))
+    );
     println!("Date: {}", item.date);
+    println!("{}", fetched(&item.provider, &item.fetched_at, item.cached));
 
     let mut entries: Vec<_> = item.rates.iter().collect();
     entries.sort_by(|a, b| a.0.cmp(b.0));
 
     for (code, rate) in entries {
-        println!("{}: {}", code.to_uppercase(), rate);
+        let formatted = item.rates_formatted.as_ref().and_then(|f| f.get(code));
+        println!(
+            "{}: {}",
+            code.to_uppercase(),
+            formatted.cloned().unwrap_or_else(|| rate.to_string())
+        );
     }
 }
 
 pub fn print_convert(item: &ConvertItem, quiet: bool) {
     if quiet {
         // Minimal output: result amount and target currency
-        println!("{} {}", item.result, item.to.to_uppercase());
+        println!(
+            "{} {}",
+            num(item.result, &item.result_formatted),
+            item.to.to_uppercase()
+        );
         return;
     }
 
     println!(
         "{} {} = {} {} (rate: {}, date: {})",
-        item.amount,
+        num(item.amount, &item.amount_formatted),
         item.from.to_uppercase(),
-        item.result,
+        num(item.result, &item.result_formatted),
         item.to.to_uppercase(),
-        item.rate,
+        num(item.rate, &item.rate_formatted),
         item.date
     );
+    println!("{}", fetched(&item.provider, &item.fetched_at, item.cached));
+}
+
+/// One conversion per line, for `convert` into each favorite.
+pub fn print_conversions(items: &[ConvertItem], quiet: bool) {
+    for item in items {
+        if quiet {
+            println!(
+                "{} {}",
+                num(item.result, &item.result_formatted),
+                item.to.to_uppercase()
+            );
+        } else {
+            println!(
+                "{} {} = {} {} (rate: {})",
+                num(item.amount, &item.amount_formatted),
+                item.from.to_uppercase(),
+                num(item.result, &item.result_formatted),
+                item.to.to_uppercase(),
+                num(item.rate, &item.rate_formatted)
+            );
+        }
+    }
+    if let (false, Some(item)) = (quiet, items.first()) {
+        println!("Date: {}", item.date);
+        println!("{}", fetched(&item.provider, &item.fetched_at, item.cached));
+    }
+}
+
+pub fn print_config(view: &ConfigView) {
+    let or_unset = |v: Option<String>| v.unwrap_or_else(|| "(not set)".to_string());
+    println!("base: {}", or_unset(view.base.clone()));
+    println!("favorites: {}", view.favorites.join(", "));
+    println!(
+        "precision: {}",
+        or_unset(view.precision.map(|p| p.to_string()))
+    );
+    println!("watchlist: {}", view.watchlist.join(", "));
+    println!("provider: {}", view.provider.name());
+    println!("cache_ttl_secs: {}", view.cache_ttl_secs);
+    println!(
+        "exchangerate_host_key: {}",
+        if view.exchangerate_host_key_set {
+            "set"
+        } else {
+            "(not set)"
+        }
+    );
+    println!("secrets_backend: {}", view.secrets_backend.as_str());
 }
 
 pub fn print_list(items: &[String], quiet: bool) {
@@ -45,3 +109,162 @@ pub fn print_list(items: &[String], quiet: bool) {
         println!("(no currencies found)");
     }
 }
+
+pub fn print_table(rows: &[TableRow], quiet: bool) {
+    if quiet {
+        // Minimal output: pair and rate
+        for row in rows {
+            println!("{} {}", row.pair, num(row.rate, &row.rate_formatted));
+        }
+        return;
+    }
+
+    let with_change = rows
+        .iter()
+        .any(|r| r.change_24h_pct.is_some() || r.change_7d_pct.is_some());
+    // Padded before colouring so escape codes do not skew the columns
+    let change = |pct: Option<f64>| match pct {
+        Some(p) => movement(format!("{:>8}", format!("{p:+.2}%")), p),
+        None => format!("{:>8}", "-"),
+    };
+
+    if with_change {
+        println!(
+            "{}",
+            style::header(format!(
+                "{:<9} {:>14} {:>8} {:>8}",
+                "PAIR", "RATE", "24H", "7D"
+            ))
+        );
+    } else {
+        println!("{}", style::header(format!("{:<9} {:>14}", "PAIR", "RATE")));
+    }
+    for row in rows {
+        if with_change {
+            println!(
+                "{:<9} {:>14} {} {}",
+                row.pair,
+                num(row.rate, &row.rate_formatted),
+                change(row.change_24h_pct),
+                change(row.change_7d_pct)
+            );
+        } else {
+            println!("{:<9} {:>14}", row.pair, num(row.rate, &row.rate_formatted));
+        }
+    }
+    if let Some(row) = rows.first() {
+        println!("Date: {}", row.date);
+    }
+    // Bases may have been fetched at different times; show the oldest
+    if let Some(oldest) = rows.iter().min_by(|a, b| a.fetched_at.cmp(&b.fetched_at)) {
+        println!(
+            "{}",
+            fetched(
+                &oldest.provider,
+                &oldest.fetched_at,
+                rows.iter().any(|r| r.cached)
+            )
+        );
+    }
+}
+
+pub fn print_diff(item: &DiffItem, quiet: bool) {
+    let pct = item
+        .change_pct
+        .map(|p| format!("{p:+.2}%"))
+        .unwrap_or_else(|| "-".into());
+    if quiet {
+        // Minimal output: pair, absolute and percent change
+        println!(
+            "{} {} {pct}",
+            item.pair,
+            num(item.change, &item.change_formatted)
+        );
+        return;
+    }
+
+    println!(
+        "{} {} -> {} over {} day(s): {}",
+        item.pair,
+        num(item.past_rate, &item.past_rate_formatted),
+        num(item.rate, &item.rate_formatted),
+        item.days,
+        movement(
+            format!("{} ({pct})", signed(item.change, &item.change_formatted)),
+            item.change
+        )
+    );
+    println!("From: {}", item.past_date);
+    println!("To: {}", item.date);
+    println!("{}", fetched(&item.provider, &item.fetched_at, item.cached));
+}
+
+pub fn print_alert(item: &AlertItem, quiet: bool) {
+    let state = if item.triggered {
+        "triggered"
+    } else {
+        "not triggered"
+    };
+    if quiet {
+        // Minimal output: pair, rate and verdict
+        println!(
+            "{} {} {state}",
+            item.pair,
+            num(item.rate, &item.rate_formatted)
+        );
+        return;
+    }
+
+    let condition = [
+        item.above.map(|a| format!("above {a}")),
+        item.below.map(|b| format!("below {b}")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" or ");
+    let state = if item.triggered {
+        style::warn(state)
+    } else {
+        style::dim(state)
+    };
+    println!(
+        "{} {}: {state} ({condition})",
+        item.pair,
+        num(item.rate, &item.rate_formatted)
+    );
+    println!("{}", fetched(&item.provider, &item.fetched_at, item.cached));
+}
+
+/// The `--precision`/`--locale` form when there is one, else the raw number.
+fn num(raw: f64, formatted: &Option<String>) -> String {
+    formatted.clone().unwrap_or_else(|| raw.to_string())
+}
+
+fn signed(raw: f64, formatted: &Option<String>) -> String {
+    let text = num(raw, formatted);
+    if raw > 0.0 {
+        format!("+{text}")
+    } else {
+        text
+    }
+}
+
+fn fetched(provider: &str, at: &str, cached: bool) -> String {
+    if cached {
+        style::dim(format!("Fetched: {at} from {provider} (cached)"))
+    } else {
+        style::dim(format!("Fetched: {at} from {provider}"))
+    }
+}
+
+/// Green when the rate went up, red when it went down.
+fn movement(text: String, change: f64) -> String {
+    if change > 0.0 {
+        style::good(text)
+    } else if change < 0.0 {
+        style::bad(text)
+    } else {
+        text
+    }
+}
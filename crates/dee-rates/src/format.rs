@@ -0,0 +1,299 @@
+//! Number formatting for `--precision`, `--locale` and `--round`.
+//!
+//! Rounding works on the shortest decimal form of the float, so `2.675` rounds
+//! half-up to `2.68` as a person would expect, not to `2.67` as its binary value
+//! would suggest.
+//!
+//! Rates and conversion results below one whose leading zeros fill every decimal
+//! place, such as USD/BTC at `--precision 2`, keep that many significant digits
+//! instead (`0.0000156` -> `0.000016`).
+
+use clap::ValueEnum;
+use dee_rates_core::{AlertItem, ConvertItem, DiffItem, GetItem, RatesError, TableRow};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rounding {
+    /// Ties go away from zero (2.5 -> 3)
+    #[default]
+    HalfUp,
+    /// Ties go to the even digit (2.5 -> 2, 3.5 -> 4)
+    Bankers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Separators {
+    group: Option<char>,
+    decimal: char,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NumberFormat {
+    /// Digits after the decimal point; `None` keeps every digit
+    precision: Option<usize>,
+    separators: Separators,
+    rounding: Rounding,
+}
+
+impl NumberFormat {
+    /// `None` when no formatting was asked for, so outputs keep their raw numbers only.
+    pub fn new(
+        precision: Option<usize>,
+        locale: Option<&str>,
+        rounding: Rounding,
+    ) -> Result<Option<Self>, RatesError> {
+        if precision.is_none() && locale.is_none() {
+            return Ok(None);
+        }
+        let separators = match locale {
+            Some(locale) => separators(locale)?,
+            None => Separators {
+                group: None,
+                decimal: '.',
+            },
+        };
+        Ok(Some(Self {
+            precision,
+            separators,
+            rounding,
+        }))
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        self.format_digits(value, false)
+    }
+
+    /// Like `format`, but a tiny value keeps `precision` significant digits rather
+    /// than rounding to (nearly) zero. Used for rates and conversion results.
+    pub fn format_rate(&self, value: f64) -> String {
+        self.format_digits(value, true)
+    }
+
+    fn format_digits(&self, value: f64, significant: bool) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+        let raw = value.abs().to_string();
+        let (int, frac) = raw.split_once('.').unwrap_or((&raw, ""));
+        let (int, frac) = match self.precision {
+            Some(precision) => {
+                let zeros = frac.bytes().take_while(|&b| b == b'0').count();
+                let tiny = int == "0" && zeros < frac.len() && zeros >= precision;
+                let precision = if significant && tiny {
+                    zeros + precision.max(1)
+                } else {
+                    precision
+                };
+                round(int, frac, precision, self.rounding)
+            }
+            None => (int.to_string(), frac.to_string()),
+        };
+
+        let negative = value < 0.0 && (int.bytes().chain(frac.bytes())).any(|b| b != b'0');
+        let mut out = String::new();
+        if negative {
+            out.push('-');
+        }
+        out.push_str(&group(&int, self.separators.group));
+        if !frac.is_empty() {
+            out.push(self.separators.decimal);
+            out.push_str(&frac);
+        }
+        out
+    }
+}
+
+/// Fill in the `*_formatted` fields next to the raw numbers.
+pub trait Formatted {
+    fn format_with(&mut self, fmt: &NumberFormat);
+}
+
+impl Formatted for GetItem {
+    fn format_with(&mut self, fmt: &NumberFormat) {
+        self.rates_formatted = Some(
+            self.rates
+                .iter()
+                .map(|(code, rate)| (code.clone(), fmt.format_rate(*rate)))
+                .collect(),
+        );
+    }
+}
+
+impl Formatted for ConvertItem {
+    fn format_with(&mut self, fmt: &NumberFormat) {
+        self.amount_formatted = Some(fmt.format(self.amount));
+        self.result_formatted = Some(fmt.format_rate(self.result));
+        self.rate_formatted = Some(fmt.format_rate(self.rate));
+    }
+}
+
+impl Formatted for TableRow {
+    fn format_with(&mut self, fmt: &NumberFormat) {
+        self.rate_formatted = Some(fmt.format_rate(self.rate));
+    }
+}
+
+impl Formatted for DiffItem {
+    fn format_with(&mut self, fmt: &NumberFormat) {
+        self.rate_formatted = Some(fmt.format_rate(self.rate));
+        self.past_rate_formatted = Some(fmt.format_rate(self.past_rate));
+        self.change_formatted = Some(fmt.format(self.change));
+    }
+}
+
+impl Formatted for AlertItem {
+    fn format_with(&mut self, fmt: &NumberFormat) {
+        self.rate_formatted = Some(fmt.format_rate(self.rate));
+    }
+}
+
+/// Separators by language tag: `en-US`, `de`, `fr_FR`, `de-CH`, or `none` for plain
+/// `1234.5`.
+fn separators(locale: &str) -> Result<Separators, RatesError> {
+    let tag = locale.trim().to_ascii_lowercase().replace('_', "-");
+    let lang = tag.split('-').next().unwrap_or_default();
+    let (group, decimal) = match (lang, tag.as_str()) {
+        (_, "de-ch" | "fr-ch" | "it-ch") => (Some('\''), '.'),
+        ("none" | "c" | "posix", _) => (None, '.'),
+        ("en" | "ja" | "zh" | "ko" | "he" | "th", _) => (Some(','), '.'),
+        ("de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el", _) => (Some('.'), ','),
+        ("fr" | "ru" | "pl" | "sv" | "nb" | "no" | "fi" | "cs" | "sk" | "uk" | "hu", _) => {
+            (Some(' '), ',')
+        }
+        _ => {
+            return Err(RatesError::InvalidArgument(format!(
+                "unknown --locale '{locale}' (try en, de, fr, de-CH or none)"
+            )))
+        }
+    };
+    Ok(Separators { group, decimal })
+}
+
+/// Round the decimal digits `int.frac` to `precision` fraction digits.
+fn round(int: &str, frac: &str, precision: usize, rounding: Rounding) -> (String, String) {
+    let mut digits: Vec<u8> = int.bytes().chain(frac.bytes()).map(|b| b - b'0').collect();
+    let frac_len = frac.len().max(precision);
+    digits.resize(int.len() + frac_len, 0);
+    let keep = int.len() + precision;
+
+    let round_up = match digits.get(keep) {
+        None => false,
+        Some(&first) => {
+            let rest_nonzero = digits[keep + 1..].iter().any(|&d| d != 0);
+            match first.cmp(&5) {
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Less => false,
+                std::cmp::Ordering::Equal => match rounding {
+                    Rounding::HalfUp => true,
+                    Rounding::Bankers => {
+                        rest_nonzero || keep.checked_sub(1).is_some_and(|i| digits[i] % 2 == 1)
+                    }
+                },
+            }
+        }
+    };
+    digits.truncate(keep);
+
+    if round_up {
+        let mut i = digits.len();
+        loop {
+            if i == 0 {
+                digits.insert(0, 1);
+                break;
+            }
+            i -= 1;
+            if digits[i] == 9 {
+                digits[i] = 0;
+            } else {
+                digits[i] += 1;
+                break;
+            }
+        }
+    }
+
+    let split = digits.len() - precision;
+    let text = |ds: &[u8]| ds.iter().map(|d| char::from(b'0' + d)).collect::<String>();
+    let int = text(&digits[..split]);
+    let int = if int.is_empty() { "0".to_string() } else { int };
+    (int, text(&digits[split..]))
+}
+
+fn group(int: &str, sep: Option<char>) -> String {
+    let Some(sep) = sep else {
+        return int.to_string();
+    };
+    let mut out = String::with_capacity(int.len() + int.len() / 3);
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i).is_multiple_of(3) {
+            out.push(sep);
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(precision: Option<usize>, locale: Option<&str>, rounding: Rounding) -> NumberFormat {
+        NumberFormat::new(precision, locale, rounding)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn rounds_on_the_decimal_form() {
+        let half_up = fmt(Some(2), None, Rounding::HalfUp);
+        let bankers = fmt(Some(2), None, Rounding::Bankers);
+        assert_eq!(half_up.format(2.675), "2.68");
+        assert_eq!(bankers.format(2.665), "2.66");
+        assert_eq!(bankers.format(2.675), "2.68");
+        assert_eq!(bankers.format(2.6651), "2.67");
+        assert_eq!(half_up.format(9.999), "10.00");
+        assert_eq!(half_up.format(-0.001), "0.00");
+        assert_eq!(fmt(Some(0), None, Rounding::Bankers).format(2.5), "2");
+        assert_eq!(fmt(Some(3), None, Rounding::HalfUp).format(1.5), "1.500");
+    }
+
+    #[test]
+    fn tiny_rates_keep_significant_digits() {
+        let fmt2 = fmt(Some(2), None, Rounding::HalfUp);
+        assert_eq!(fmt2.format_rate(0.0000156), "0.000016");
+        assert_eq!(fmt2.format(0.0000156), "0.00");
+        assert_eq!(fmt2.format_rate(0.0096), "0.0096");
+        assert_eq!(fmt2.format_rate(0.05), "0.05");
+        assert_eq!(fmt2.format_rate(2.0000156), "2.00");
+        assert_eq!(fmt2.format_rate(0.0), "0.00");
+        assert_eq!(fmt(Some(0), None, Rounding::HalfUp).format_rate(0.4), "0.4");
+        assert_eq!(
+            fmt(Some(3), Some("de"), Rounding::HalfUp).format_rate(0.000012345),
+            "0,0000123"
+        );
+    }
+
+    #[test]
+    fn applies_locale_separators() {
+        assert_eq!(
+            fmt(Some(2), Some("en-US"), Rounding::HalfUp).format(1234567.891),
+            "1,234,567.89"
+        );
+        assert_eq!(
+            fmt(Some(2), Some("de_DE"), Rounding::HalfUp).format(-1234.5),
+            "-1.234,50"
+        );
+        assert_eq!(
+            fmt(None, Some("fr"), Rounding::HalfUp).format(1234.5),
+            "1 234,5"
+        );
+        assert_eq!(
+            fmt(None, Some("de-CH"), Rounding::HalfUp).format(1234.5),
+            "1'234.5"
+        );
+        assert!(NumberFormat::new(None, Some("xx"), Rounding::HalfUp).is_err());
+        assert!(NumberFormat::new(None, None, Rounding::Bankers)
+            .unwrap()
+            .is_none());
+    }
+}
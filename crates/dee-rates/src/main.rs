@@ -1,99 +1,313 @@
 mod cli;
-mod commands;
+mod config;
 mod display;
-mod models;
+mod format;
 
-use clap::Parser;
-use cli::{Cli, Commands};
-use models::{ErrorResponse, ListResponse, SingleResponse};
+use cli::{Cli, Commands, ConfigCommand};
+use dee_core::cache::Cache;
+use dee_core::http::Http;
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    exit, print_json, print_list, report, report_code, ErrorCode, Format, OkItem, OkMessage,
+};
+use dee_rates_core::{
+    AlertItem, ConvertItem, DiffItem, GetItem, RatesClient, RatesError, TableRow, CACHE_TOOL,
+};
+use format::{Formatted, NumberFormat};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// `config path`
+#[derive(Debug, Serialize, JsonSchema)]
+struct ConfigPath {
+    path: String,
+}
+
+/// What each command prints under `--json`, for `--schema`. `convert` without
+/// a target prints a list, one conversion per favorite.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .item::<GetItem>("get")
+        .item::<ConvertItem>("convert")
+        .list::<String>("list")
+        .list::<TableRow>("table")
+        .item::<DiffItem>("diff")
+        .item::<AlertItem>("alert")
+        .message("config set")
+        .item::<config::ConfigView>("config show")
+        .item::<ConfigPath>("config path")
+        .cache()
+}
 
 fn main() {
-    let cli = parse_cli();
-    let json = cli.global.json;
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
+    let json = Format::json_errors(cli.global.output, cli.global.json);
 
-    let result = match cli.command {
-        Commands::Get { from, to } => commands::get_rates(&from, to.as_deref(), cli.global.verbose)
-            .map(|item| {
-                if json {
-                    print_json(&SingleResponse { ok: true, item });
-                } else {
-                    display::print_get(&item, cli.global.quiet);
-                }
-            }),
-        Commands::Convert { amount, from, to } => {
-            commands::convert(amount, &from, &to, cli.global.verbose).map(|item| {
-                if json {
-                    print_json(&SingleResponse { ok: true, item });
-                } else {
-                    display::print_convert(&item, cli.global.quiet);
-                }
-            })
+    // Config commands must work even when the saved provider cannot be built
+    if let Commands::Config { command } = &cli.command {
+        exit_on_error(config_command(command, json), json);
+        return;
+    }
+    if let Commands::Cache { command } = &cli.command {
+        if let Err(err) = dee_core::cache::run(&Cache::new(CACHE_TOOL), *command, cli.global.json) {
+            report(&err, json);
+            std::process::exit(err.exit_code());
         }
-        Commands::List => commands::list_currencies(cli.global.verbose).map(|items| {
-            if json {
-                print_json(&ListResponse {
-                    ok: true,
-                    count: items.len(),
-                    items,
-                });
-            } else {
-                display::print_list(&items, cli.global.quiet);
-            }
-        }),
-    };
+        return;
+    }
+
+    let result = config::load_config().and_then(|config| {
+        let provider = cli.global.provider.or(config.provider).unwrap_or_default();
+        let http = Http::new("dee-rates", env!("CARGO_PKG_VERSION"))
+            .args(&cli.global.http)
+            .blocking()
+            .map_err(|_| RatesError::RequestFailed)?;
+        let client = RatesClient::new(provider, config.exchangerate_host_key.as_deref())?
+            .http_client(http)
+            .no_cache(cli.global.cache.no_cache)
+            .offline(cli.global.cache.offline)
+            .cache_ttl_secs(config.cache_ttl_secs());
+        let precision = cli.global.precision.or(config.precision);
+        if cli.global.round.is_some() && precision.is_none() {
+            return Err(RatesError::InvalidArgument(
+                "--round needs --precision (or `precision` in the config file)".to_string(),
+            ));
+        }
+        let out = Output {
+            json: cli.global.json,
+            list: Format::resolve(cli.global.output, cli.global.json),
+            quiet: cli.global.quiet,
+            format: NumberFormat::new(
+                precision.map(usize::from),
+                cli.global.locale.as_deref(),
+                cli.global.round.unwrap_or_default(),
+            )?,
+        };
+        run(cli.command, config, client, &out)
+    });
+    exit_on_error(result, json);
+}
 
+fn exit_on_error(result: Result<(), RatesError>, json: bool) {
     if let Err(err) = result {
-        if json {
-            print_json(&ErrorResponse {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
+        report_code(&err.to_string(), err.code(), json);
+        std::process::exit(exit::for_code(err.code()));
+    }
+}
+
+/// How results are printed: JSON or text, and with which number format.
+struct Output {
+    json: bool,
+    /// `--output`, or `json` under `--json`; lists only
+    list: Option<Format>,
+    quiet: bool,
+    format: Option<NumberFormat>,
+}
+
+impl Output {
+    fn item<T: Serialize + Formatted>(&self, mut item: T, print: fn(&T, bool)) {
+        if let Some(fmt) = &self.format {
+            item.format_with(fmt);
+        }
+        if self.json {
+            print_json(&OkItem { ok: true, item });
         } else {
-            eprintln!("error: {err}");
+            print(&item, self.quiet);
         }
-        std::process::exit(1);
     }
-}
 
-fn print_json<T: serde::Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(out) => println!("{out}"),
-        Err(err) => {
-            eprintln!("error: failed to serialize JSON output: {err}");
-            std::process::exit(1);
+    fn items<T: Serialize + Formatted>(&self, mut items: Vec<T>, print: fn(&[T], bool)) {
+        if let Some(fmt) = &self.format {
+            items.iter_mut().for_each(|item| item.format_with(fmt));
+        }
+        if let Some(format) = self.list {
+            print_list(&items, format);
+        } else {
+            print(&items, self.quiet);
         }
     }
 }
 
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
+fn run(
+    command: Commands,
+    config: config::AppConfig,
+    mut client: RatesClient,
+    out: &Output,
+) -> Result<(), RatesError> {
+    // Each watch check must see fresh rates
+    if matches!(command, Commands::Alert { watch: true, .. }) {
+        client = client.no_cache(true);
+    }
+    let client = &client;
+    match command {
+        Commands::Get { from, to, all } => {
+            let from = base_or_default(from, &config)?;
+            let targets = match to {
+                Some(to) => vec![to],
+                None if all => Vec::new(),
+                None => config.favorites,
+            };
+            client
+                .get_rates(&from, &targets)
+                .map(|item| out.item(item, display::print_get))
+        }
+        Commands::Convert { amount, from, to } => {
+            let from = base_or_default(from, &config)?;
+            match to {
+                Some(to) => client
+                    .convert(amount, &from, &to)
+                    .map(|item| out.item(item, display::print_convert)),
+                // One conversion per favorite
+                None if !config.favorites.is_empty() => client
+                    .convert_many(amount, &from, &config.favorites)
+                    .map(|items| out.items(items, display::print_conversions)),
+                None => Err(RatesError::InvalidArgument(
+                    "no target currency given and no favorites in the config file".to_string(),
+                )),
+            }
+        }
+        Commands::List { class } => client.list_currencies(class).map(|items| {
+            if let Some(format) = out.list {
+                print_list(&items, format);
+            } else {
+                display::print_list(&items, out.quiet);
+            }
+        }),
+        Commands::Table { pairs, change } => {
+            // Pairs from the command line, else the configured watchlist
+            let pairs = if pairs.is_empty() {
+                config.watchlist
+            } else {
+                pairs
+            };
+            if pairs.is_empty() {
+                return Err(RatesError::EmptyWatchlist(
+                    config::config_path().display().to_string(),
+                ));
+            }
+            client
+                .table(&pairs, change)
+                .map(|rows| out.items(rows, display::print_table))
+        }
+        Commands::Diff { from, to, days } => client
+            .diff(&from, &to, days)
+            .map(|item| out.item(item, display::print_diff)),
+        Commands::Config { .. } | Commands::Cache { .. } => {
+            unreachable!("handled before the provider is built")
+        }
+        Commands::Alert {
+            from,
+            to,
+            above,
+            below,
+            watch,
+            interval,
+        } => {
+            let interval = if watch {
+                if client.is_offline() {
+                    return Err(RatesError::InvalidArgument(
+                        "--watch needs the network; drop --offline".to_string(),
+                    ));
+                }
+                let interval = dee_core::interval::parse(&interval)
+                    .map_err(|err| RatesError::InvalidArgument(err.to_string()))?;
+                Some(interval)
+            } else {
+                None
+            };
+            alert(&from, &to, above, below, interval, client, out)
+        }
     }
 }
 
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
+/// The positional base, else `base` from the config file.
+fn base_or_default(from: Option<String>, config: &config::AppConfig) -> Result<String, RatesError> {
+    from.or_else(|| config.base.clone()).ok_or_else(|| {
+        RatesError::InvalidArgument(format!(
+            "no base currency given and no `base` in {}",
+            config::config_path().display()
+        ))
+    })
+}
 
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
+fn config_command(command: &ConfigCommand, json: bool) -> Result<(), RatesError> {
+    match command {
+        ConfigCommand::Set { key, value } => {
+            let mut config = config::load_config_file()?;
+            config.set(key, value)?;
+            config::save_config(&config)?;
+            if json {
+                print_json(&OkMessage {
+                    ok: true,
+                    message: format!("{key} updated"),
+                });
+            } else {
+                println!("{key} updated");
+            }
+        }
+        ConfigCommand::Show => {
+            let view = config::load_config()?.view();
+            if json {
+                print_json(&OkItem {
+                    ok: true,
+                    item: view,
                 });
-                println!("{payload}");
             } else {
-                let _ = err.print();
+                display::print_config(&view);
+            }
+        }
+        ConfigCommand::Path => {
+            let path = config::config_path().display().to_string();
+            if json {
+                print_json(&OkItem {
+                    ok: true,
+                    item: ConfigPath { path },
+                });
+            } else {
+                println!("{path}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// One check, or with `interval` a check per interval until the alert triggers. Exits 3
+/// when a single check does not trigger.
+fn alert(
+    from: &str,
+    to: &str,
+    above: Option<f64>,
+    below: Option<f64>,
+    interval: Option<std::time::Duration>,
+    client: &RatesClient,
+    out: &Output,
+) -> Result<(), RatesError> {
+    let Some(interval) = interval else {
+        let item = client.alert(from, to, above, below)?;
+        let triggered = item.triggered;
+        out.item(item, display::print_alert);
+        if !triggered {
+            std::process::exit(exit::THRESHOLD);
+        }
+        return Ok(());
+    };
+
+    loop {
+        match client.alert(from, to, above, below) {
+            Ok(item) => {
+                let triggered = item.triggered;
+                out.item(item, display::print_alert);
+                if triggered {
+                    return Ok(());
+                }
+            }
+            // An outage should not end the watch; report it and try again next time
+            Err(err) if err.is_transient() => {
+                report_code(&err.to_string(), err.code(), out.json);
             }
-            std::process::exit(2);
+            Err(err) => return Err(err),
         }
+        std::thread::sleep(interval);
     }
 }
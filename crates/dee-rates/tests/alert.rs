@@ -0,0 +1,146 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+
+fn bin(data: &Path, url: &str) -> Command {
+    let mut cmd = Command::cargo_bin("dee-rates").unwrap();
+    cmd.env("XDG_CACHE_HOME", data)
+        .env("XDG_CONFIG_HOME", data)
+        .env("RATES_TEST_BASE_URL", url);
+    cmd
+}
+
+/// Serve USD rates with EUR at 0.9.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let body = r#"{"date":"2026-03-10","usd":{"eur":0.9}}"#;
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}")
+}
+
+fn run(cmd: &mut Command, code: i32) -> serde_json::Value {
+    let out = cmd.assert().code(code).get_output().stdout.clone();
+    serde_json::from_slice(&out).expect("valid json on stdout")
+}
+
+#[test]
+fn exit_code_reflects_the_verdict() {
+    let data = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    let hit = run(
+        bin(data.path(), &url).args(["alert", "usd", "eur", "--above", "0.85", "--json"]),
+        0,
+    );
+    assert_eq!(hit["ok"], true);
+    assert_eq!(hit["item"]["pair"], "USD/EUR");
+    assert_eq!(hit["item"]["triggered"], true);
+    assert_eq!(hit["item"]["above"], 0.85);
+    assert!(hit["item"].get("below").is_none());
+
+    let miss = run(
+        bin(data.path(), &url).args([
+            "alert", "USD", "EUR", "--above", "0.95", "--below", "0.5", "--json",
+        ]),
+        6,
+    );
+    assert_eq!(miss["ok"], true);
+    assert_eq!(miss["item"]["triggered"], false);
+    assert_eq!(miss["item"]["rate"], 0.9);
+}
+
+#[test]
+fn watch_stops_once_triggered() {
+    let data = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    let out = bin(data.path(), &url)
+        .args([
+            "alert",
+            "USD",
+            "EUR",
+            "--below",
+            "0.95",
+            "--watch",
+            "--interval",
+            "1m",
+            "--json",
+        ])
+        .timeout(std::time::Duration::from_secs(30))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let lines: Vec<serde_json::Value> = String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["item"]["triggered"], true);
+    assert_eq!(lines[0]["item"]["cached"], false);
+}
+
+#[test]
+fn rejects_missing_threshold_and_bad_interval() {
+    let data = tempfile::tempdir().unwrap();
+    let url = "http://127.0.0.1:1";
+
+    let parsed = run(
+        bin(data.path(), url).args(["alert", "USD", "EUR", "--json"]),
+        2,
+    );
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    let parsed = run(
+        bin(data.path(), url).args([
+            "alert",
+            "USD",
+            "EUR",
+            "--above",
+            "1",
+            "--interval",
+            "1h",
+            "--json",
+        ]),
+        2,
+    );
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    let parsed = run(
+        bin(data.path(), url).args([
+            "alert",
+            "USD",
+            "EUR",
+            "--above",
+            "1",
+            "--watch",
+            "--interval",
+            "10s",
+            "--json",
+        ]),
+        2,
+    );
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    let parsed = run(
+        bin(data.path(), url).args(["alert", "USD", "EUR", "--below", "0", "--json"]),
+        2,
+    );
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
@@ -0,0 +1,137 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+
+const UNREACHABLE: &str = "http://127.0.0.1:1";
+
+fn bin(data: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("dee-rates").unwrap();
+    cmd.env("XDG_CACHE_HOME", data).env("XDG_CONFIG_HOME", data);
+    cmd
+}
+
+/// Serve the latest USD rates; anything else is a 404.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let body = if line.split_whitespace().nth(1) == Some("/currencies/usd.json") {
+                r#"{"date":"2026-03-10","usd":{"eur":0.9}}"#
+            } else {
+                ""
+            };
+            let status = if body.is_empty() {
+                "404 Not Found"
+            } else {
+                "200 OK"
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}")
+}
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let out = cmd.output().unwrap();
+    serde_json::from_slice(&out.stdout).expect("valid json on stdout")
+}
+
+#[test]
+fn cached_rates_are_served_during_an_outage() {
+    let data = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    let first = json(
+        bin(data.path())
+            .env("RATES_TEST_BASE_URL", &url)
+            .args(["get", "USD", "EUR", "--json"]),
+    );
+    assert_eq!(first["ok"], true);
+    assert_eq!(first["item"]["cached"], false);
+
+    // Still fresh: answered from the cache without a request
+    let fresh = json(
+        bin(data.path())
+            .env("RATES_TEST_BASE_URL", UNREACHABLE)
+            .args(["convert", "10", "USD", "EUR", "--json"]),
+    );
+    assert_eq!(fresh["item"]["cached"], true);
+    assert_eq!(fresh["item"]["fetched_at"], first["item"]["fetched_at"]);
+    assert!(fresh.get("stale").is_none());
+
+    // Expired but the network is down: the stale copy beats an error
+    std::fs::create_dir_all(data.path().join("dee-rates")).unwrap();
+    std::fs::write(
+        data.path().join("dee-rates").join("config.toml"),
+        "cache_ttl_secs = 0\n",
+    )
+    .unwrap();
+    let stale = json(
+        bin(data.path())
+            .env("RATES_TEST_BASE_URL", UNREACHABLE)
+            .args(["get", "USD", "EUR", "--json"]),
+    );
+    assert_eq!(stale["ok"], true);
+    assert_eq!(stale["item"]["cached"], true);
+    assert_eq!(stale["item"]["rates"]["EUR"], 0.9);
+    assert_eq!(stale["stale"], true);
+
+    // --no-cache never falls back
+    let bypass = json(
+        bin(data.path())
+            .env("RATES_TEST_BASE_URL", UNREACHABLE)
+            .args(["get", "USD", "--no-cache", "--json"]),
+    );
+    assert_eq!(bypass["code"], "REQUEST_FAILED");
+}
+
+#[test]
+fn offline_uses_only_the_cache() {
+    let data = tempfile::tempdir().unwrap();
+
+    let missing = json(bin(data.path()).args(["get", "USD", "--offline", "--json"]));
+    assert_eq!(missing["ok"], false);
+    assert_eq!(missing["code"], "OFFLINE_MISS");
+
+    let url = serve();
+    bin(data.path())
+        .env("RATES_TEST_BASE_URL", &url)
+        .args(["get", "USD", "--json"])
+        .assert()
+        .success();
+
+    let cached = json(
+        bin(data.path())
+            .env("RATES_TEST_BASE_URL", UNREACHABLE)
+            .args(["table", "USD/EUR", "--offline", "--json"]),
+    );
+    assert_eq!(cached["ok"], true);
+    assert_eq!(cached["items"][0]["rate"], 0.9);
+    assert_eq!(cached["items"][0]["cached"], true);
+    assert_eq!(cached["stale"], true);
+    assert!(cached["cache_age_secs"].is_u64());
+}
+
+#[test]
+fn offline_and_no_cache_conflict() {
+    let data = tempfile::tempdir().unwrap();
+    let out = bin(data.path())
+        .args(["get", "USD", "--offline", "--no-cache", "--json"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
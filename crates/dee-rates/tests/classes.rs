@@ -0,0 +1,97 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+
+fn bin(data: &Path, url: &str) -> Command {
+    let mut cmd = Command::cargo_bin("dee-rates").unwrap();
+    cmd.env("XDG_CACHE_HOME", data)
+        .env("XDG_CONFIG_HOME", data)
+        .env("RATES_TEST_BASE_URL", url);
+    cmd
+}
+
+/// Serve a currency list mixing fiat, crypto and metals, and USD rates including BTC.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let body = match line.split_whitespace().nth(1).unwrap_or("") {
+                "/currencies.json" => {
+                    r#"{"usd":"US Dollar","eur":"Euro","btc":"Bitcoin","usdt":"Tether","xau":"Gold"}"#
+                }
+                "/currencies/usd.json" => {
+                    r#"{"date":"2026-03-10","usd":{"eur":0.9,"btc":0.0000156,"usdt":1.0002}}"#
+                }
+                _ => "",
+            };
+            let status = if body.is_empty() {
+                "404 Not Found"
+            } else {
+                "200 OK"
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}")
+}
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let out = cmd.output().unwrap();
+    serde_json::from_slice(&out.stdout).expect("valid json on stdout")
+}
+
+#[test]
+fn list_filters_by_class() {
+    let data = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    for (class, expected) in [
+        ("fiat", vec!["EUR", "USD"]),
+        ("crypto", vec!["BTC", "USDT"]),
+        ("metal", vec!["XAU"]),
+    ] {
+        let parsed = json(bin(data.path(), &url).args(["list", "--class", class, "--json"]));
+        assert_eq!(parsed["items"], serde_json::json!(expected), "{class}");
+        assert_eq!(parsed["count"], expected.len());
+    }
+
+    let parsed = json(bin(data.path(), &url).args(["list", "--json"]));
+    assert_eq!(parsed["count"], 5);
+}
+
+#[test]
+fn four_letter_codes_and_tiny_rates() {
+    let data = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    let parsed = json(bin(data.path(), &url).args(["convert", "100", "USD", "USDT", "--json"]));
+    assert_eq!(parsed["ok"], true);
+    assert_eq!(parsed["item"]["to"], "USDT");
+
+    let parsed = json(bin(data.path(), &url).args([
+        "convert",
+        "1",
+        "USD",
+        "BTC",
+        "--precision",
+        "2",
+        "--json",
+    ]));
+    let item = &parsed["item"];
+    assert_eq!(item["rate_formatted"], "0.000016");
+    assert_eq!(item["result_formatted"], "0.000016");
+    assert_eq!(item["amount_formatted"], "1.00");
+
+    let parsed = json(bin(data.path(), &url).args(["get", "USD", "DOGECOIN", "--json"]));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
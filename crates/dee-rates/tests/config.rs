@@ -0,0 +1,172 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+
+fn bin(home: &Path, url: &str) -> Command {
+    let mut cmd = Command::cargo_bin("dee-rates").unwrap();
+    cmd.env("XDG_CACHE_HOME", home)
+        .env("XDG_CONFIG_HOME", home)
+        .env("RATES_TEST_BASE_URL", url);
+    cmd
+}
+
+/// Serve EUR rates.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let body = if line.split_whitespace().nth(1) == Some("/currencies/eur.json") {
+                r#"{"date":"2026-03-10","eur":{"usd":1.25,"gbp":0.85,"jpy":160.0}}"#
+            } else {
+                ""
+            };
+            let status = if body.is_empty() {
+                "404 Not Found"
+            } else {
+                "200 OK"
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}")
+}
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let out = cmd.output().unwrap();
+    serde_json::from_slice(&out.stdout).expect("valid json on stdout")
+}
+
+#[test]
+fn defaults_fill_in_omitted_arguments() {
+    let home = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    let parsed = json(bin(home.path(), &url).args(["get", "--json"]));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    for (key, value) in [
+        ("base", "eur"),
+        ("favorites", "usd, GBP"),
+        ("precision", "2"),
+    ] {
+        let parsed = json(bin(home.path(), &url).args(["config", "set", key, value, "--json"]));
+        assert_eq!(parsed["ok"], true, "{key}");
+    }
+
+    let parsed = json(bin(home.path(), &url).args(["get", "--json"]));
+    let item = &parsed["item"];
+    assert_eq!(item["base"], "EUR");
+    assert_eq!(item["rates"].as_object().unwrap().len(), 2);
+    assert_eq!(item["rates_formatted"]["USD"], "1.25");
+
+    let parsed = json(bin(home.path(), &url).args(["get", "--all", "--json"]));
+    assert_eq!(parsed["item"]["rates"].as_object().unwrap().len(), 3);
+
+    let parsed = json(bin(home.path(), &url).args(["convert", "10", "--json"]));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][0]["to"], "USD");
+    assert_eq!(parsed["items"][1]["result_formatted"], "8.50");
+
+    let parsed = json(bin(home.path(), &url).args(["convert", "10", "EUR", "JPY", "--json"]));
+    assert_eq!(parsed["item"]["result"], 1600.0);
+}
+
+#[test]
+fn show_path_and_validation() {
+    let home = tempfile::tempdir().unwrap();
+    let url = "http://127.0.0.1:1";
+
+    bin(home.path(), url)
+        .args(["config", "set", "exchangerate_host_key", "hunter2"])
+        .assert()
+        .success();
+    let parsed = json(bin(home.path(), url).args(["config", "show", "--json"]));
+    let item = &parsed["item"];
+    assert_eq!(item["provider"], "currency-api");
+    assert_eq!(item["cache_ttl_secs"], 3600);
+    assert_eq!(item["exchangerate_host_key_set"], true);
+    assert_eq!(item["secrets_backend"], "plaintext");
+    assert!(!parsed.to_string().contains("hunter2"));
+
+    let parsed = json(bin(home.path(), url).args(["config", "path", "--json"]));
+    assert!(parsed["item"]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("config.toml"));
+
+    for (key, value) in [
+        ("colour", "blue"),
+        ("base", "EUROS"),
+        ("precision", "20"),
+        ("watchlist", "USDEUR"),
+        ("provider", "oanda"),
+        ("secrets_backend", "vault"),
+    ] {
+        let parsed = json(bin(home.path(), url).args(["config", "set", key, value, "--json"]));
+        assert_eq!(parsed["code"], "INVALID_ARGUMENT", "{key}");
+    }
+}
+
+#[test]
+fn env_overrides_any_key() {
+    let home = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    let parsed = json(
+        bin(home.path(), &url)
+            .env("DEE_RATES_BASE", "eur")
+            .env("DEE_RATES_FAVORITES", "usd,GBP")
+            .env("DEE_RATES_PRECISION", "2")
+            .args(["get", "--json"]),
+    );
+    let item = &parsed["item"];
+    assert_eq!(item["base"], "EUR");
+    assert_eq!(item["rates"].as_object().unwrap().len(), 2);
+    assert_eq!(item["rates_formatted"]["USD"], "1.25");
+
+    let parsed = json(
+        bin(home.path(), &url)
+            .env("DEE_RATES_PRECISION", "many")
+            .args(["get", "--json"]),
+    );
+    assert_eq!(parsed["code"], "CONFIG_INVALID");
+}
+
+#[test]
+fn profiles_switch_defaults() {
+    let home = tempfile::tempdir().unwrap();
+    let url = serve();
+    std::fs::create_dir_all(home.path().join("dee")).unwrap();
+    std::fs::write(
+        home.path().join("dee").join("profiles.toml"),
+        "[travel.dee-rates]\nbase = \"eur\"\nfavorites = [\"gbp\"]\n",
+    )
+    .unwrap();
+
+    let parsed = json(bin(home.path(), &url).args(["get", "--profile", "travel", "--json"]));
+    assert_eq!(parsed["item"]["base"], "EUR");
+    assert_eq!(parsed["item"]["rates"].as_object().unwrap().len(), 1);
+
+    let parsed = json(
+        bin(home.path(), &url)
+            .env("DEE_PROFILE", "travel")
+            .args(["config", "show", "--json"]),
+    );
+    assert_eq!(parsed["item"]["favorites"][0], "gbp");
+
+    bin(home.path(), &url)
+        .args(["--profile", "work", "get", "--json"])
+        .assert()
+        .code(3)
+        .stdout(predicates::str::contains("PROFILE_NOT_FOUND"));
+}
@@ -0,0 +1,84 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+
+fn bin(data: &Path, url: &str) -> Command {
+    let mut cmd = Command::cargo_bin("dee-rates").unwrap();
+    cmd.env("XDG_CACHE_HOME", data)
+        .env("XDG_CONFIG_HOME", data)
+        .env("RATES_TEST_BASE_URL", url);
+    cmd
+}
+
+/// Latest USD rates plus the snapshot a week earlier.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let body = match line.split_whitespace().nth(1).unwrap_or("") {
+                "/currencies/usd.json" => r#"{"date":"2026-03-10","usd":{"eur":0.9}}"#,
+                "/2026-03-03/currencies/usd.json" => r#"{"date":"2026-03-03","usd":{"eur":0.8}}"#,
+                _ => "",
+            };
+            let status = if body.is_empty() {
+                "404 Not Found"
+            } else {
+                "200 OK"
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}")
+}
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let out = cmd.output().unwrap();
+    serde_json::from_slice(&out.stdout).expect("valid json on stdout")
+}
+
+#[test]
+fn diff_compares_with_the_past_snapshot() {
+    let data = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    let parsed = json(bin(data.path(), &url).args(["diff", "USD", "EUR", "--days", "7", "--json"]));
+    assert_eq!(parsed["ok"], true);
+    let item = &parsed["item"];
+    assert_eq!(item["pair"], "USD/EUR");
+    assert_eq!(item["days"], 7);
+    assert_eq!(item["rate"], 0.9);
+    assert_eq!(item["past_rate"], 0.8);
+    assert_eq!(item["past_date"], "2026-03-03T00:00:00Z");
+    assert!((item["change"].as_f64().unwrap() - 0.1).abs() < 1e-9);
+    assert!((item["change_pct"].as_f64().unwrap() - 12.5).abs() < 1e-9);
+}
+
+#[test]
+fn diff_reports_missing_history_and_bad_days() {
+    let data = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    let parsed = json(bin(data.path(), &url).args(["diff", "USD", "EUR", "--days", "2", "--json"]));
+    assert_eq!(parsed["ok"], false);
+    assert_eq!(parsed["code"], "REQUEST_FAILED");
+
+    let out = bin(data.path(), &url)
+        .args(["diff", "USD", "EUR", "--days", "0", "--json"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
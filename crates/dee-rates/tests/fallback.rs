@@ -9,8 +9,10 @@ fn bin() -> Command {
 /// get exits non-zero with a JSON error on stdout (not stderr).
 #[test]
 fn unreachable_base_url_gives_json_error() {
+    let data = tempfile::tempdir().unwrap();
     let out = bin()
         .env("RATES_TEST_BASE_URL", "http://127.0.0.1:1") // refused connection
+        .env("XDG_CACHE_HOME", data.path()) // no cached copy to fall back on
         .args(["get", "--json", "USD"])
         .output()
         .unwrap();
@@ -29,8 +31,10 @@ fn unreachable_base_url_gives_json_error() {
 /// Same for list command
 #[test]
 fn list_unreachable_gives_json_error() {
+    let data = tempfile::tempdir().unwrap();
     let out = bin()
         .env("RATES_TEST_BASE_URL", "http://127.0.0.1:1")
+        .env("XDG_CACHE_HOME", data.path())
         .args(["list", "--json"])
         .output()
         .unwrap();
@@ -0,0 +1,108 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+
+fn bin(data: &Path, url: &str) -> Command {
+    let mut cmd = Command::cargo_bin("dee-rates").unwrap();
+    cmd.env("XDG_CACHE_HOME", data)
+        .env("XDG_CONFIG_HOME", data)
+        .env("RATES_TEST_BASE_URL", url);
+    cmd
+}
+
+/// Serve USD rates with EUR at 0.9.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let body = r#"{"date":"2026-03-10","usd":{"eur":0.9}}"#;
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}")
+}
+
+#[test]
+fn formatted_fields_sit_next_to_raw_numbers() {
+    let data = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    let out = bin(data.path(), &url)
+        .args([
+            "convert",
+            "1234.5",
+            "USD",
+            "EUR",
+            "--precision",
+            "2",
+            "--locale",
+            "de",
+            "--json",
+        ])
+        .output()
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let item = &parsed["item"];
+    assert!((item["result"].as_f64().unwrap() - 1111.05).abs() < 1e-9);
+    assert_eq!(item["result_formatted"], "1.111,05");
+    assert_eq!(item["amount_formatted"], "1.234,50");
+    assert_eq!(item["rate_formatted"], "0,90");
+
+    // Without formatting flags the JSON keeps raw numbers only
+    let out = bin(data.path(), &url)
+        .args(["convert", "1234.5", "USD", "EUR", "--json"])
+        .output()
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert!(parsed["item"].get("result_formatted").is_none());
+
+    bin(data.path(), &url)
+        .args([
+            "convert",
+            "1234.5",
+            "USD",
+            "EUR",
+            "--precision",
+            "0",
+            "--quiet",
+        ])
+        .assert()
+        .success()
+        .stdout("1111 EUR\n");
+}
+
+#[test]
+fn rejects_unknown_locale_and_round_without_precision() {
+    let data = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    let out = bin(data.path(), &url)
+        .args(["get", "USD", "--locale", "klingon", "--json"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    let out = bin(data.path(), &url)
+        .args(["get", "USD", "--round", "bankers", "--json"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
@@ -0,0 +1,121 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+
+const ECB_DAILY: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+<gesmes:Envelope xmlns:gesmes=\"http://www.gesmes.org/xml/2002-08-01\" xmlns=\"http://www.ecb.int/vocabulary/2002-08-01/eurofxref\">\
+<gesmes:subject>Reference rates</gesmes:subject>\
+<Cube><Cube time='2026-03-10'><Cube currency='USD' rate='1.25'/><Cube currency='GBP' rate='0.5'/></Cube></Cube>\
+</gesmes:Envelope>";
+
+const EXCHANGERATE_HOST_LIVE: &str = r#"{"success":true,"timestamp":1773100800,"source":"USD","quotes":{"USDEUR":0.8,"USDGBP":0.4}}"#;
+
+fn bin(data: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("dee-rates").unwrap();
+    cmd.env("XDG_CACHE_HOME", data).env("XDG_CONFIG_HOME", data);
+    cmd
+}
+
+/// Serve the ECB daily file and the exchangerate.host `live` endpoint.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let path = line.split_whitespace().nth(1).unwrap_or("").to_string();
+            let body = if path == "/eurofxref-daily.xml" {
+                ECB_DAILY
+            } else if path.starts_with("/live?access_key=secret&") {
+                EXCHANGERATE_HOST_LIVE
+            } else {
+                ""
+            };
+            let status = if body.is_empty() {
+                "404 Not Found"
+            } else {
+                "200 OK"
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}")
+}
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let out = cmd.output().unwrap();
+    serde_json::from_slice(&out.stdout).expect("valid json on stdout")
+}
+
+#[test]
+fn ecb_rates_are_requoted_against_the_base() {
+    let data = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    let parsed = json(bin(data.path()).env("RATES_TEST_BASE_URL", &url).args([
+        "convert",
+        "10",
+        "USD",
+        "GBP",
+        "--provider",
+        "ecb",
+        "--json",
+    ]));
+    assert_eq!(parsed["ok"], true);
+    assert_eq!(parsed["item"]["provider"], "ecb");
+    assert_eq!(parsed["item"]["date"], "2026-03-10T00:00:00Z");
+    let rate = parsed["item"]["rate"].as_f64().unwrap();
+    assert!((rate - 0.4).abs() < 1e-12, "{rate}");
+}
+
+#[test]
+fn exchangerate_host_uses_the_configured_key() {
+    let data = tempfile::tempdir().unwrap();
+    let url = serve();
+
+    let missing = json(bin(data.path()).env("RATES_TEST_BASE_URL", &url).args([
+        "get",
+        "EUR",
+        "--provider",
+        "exchangerate-host",
+        "--json",
+    ]));
+    assert_eq!(missing["code"], "AUTH_MISSING");
+
+    std::fs::create_dir_all(data.path().join("dee-rates")).unwrap();
+    std::fs::write(
+        data.path().join("dee-rates").join("config.toml"),
+        "provider = \"exchangerate-host\"\nexchangerate_host_key = \"secret\"\n",
+    )
+    .unwrap();
+    let parsed = json(
+        bin(data.path())
+            .env("RATES_TEST_BASE_URL", &url)
+            .args(["get", "EUR", "GBP", "--json"]),
+    );
+    assert_eq!(parsed["ok"], true);
+    assert_eq!(parsed["item"]["provider"], "exchangerate-host");
+    assert_eq!(parsed["item"]["rates"]["GBP"], 0.5);
+}
+
+#[test]
+fn unknown_provider_is_invalid() {
+    let data = tempfile::tempdir().unwrap();
+    let out = bin(data.path())
+        .args(["get", "USD", "--provider", "oanda", "--json"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
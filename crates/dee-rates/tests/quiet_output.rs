@@ -52,7 +52,7 @@ fn convert_invalid_from_currency() {
 #[test]
 fn convert_invalid_to_currency() {
     let out = bin()
-        .args(["convert", "--json", "100", "USD", "NOPE"])
+        .args(["convert", "--json", "100", "USD", "NOPES"])
         .output()
         .unwrap();
 
@@ -0,0 +1,104 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+
+fn bin() -> Command {
+    Command::cargo_bin("dee-rates").unwrap()
+}
+
+/// Serve canned rate snapshots: latest at `/currencies/usd.json`, history under `/<date>/`.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let path = line.split_whitespace().nth(1).unwrap_or("").to_string();
+            let body = match path.as_str() {
+                "/currencies/usd.json" => r#"{"date":"2026-03-10","usd":{"eur":0.9,"jpy":150.0}}"#,
+                "/2026-03-09/currencies/usd.json" => {
+                    r#"{"date":"2026-03-09","usd":{"eur":0.8,"jpy":150.0}}"#
+                }
+                _ => "",
+            };
+            let status = if body.is_empty() {
+                "404 Not Found"
+            } else {
+                "200 OK"
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}")
+}
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let out = cmd.output().unwrap();
+    serde_json::from_slice(&out.stdout).expect("valid json on stdout")
+}
+
+#[test]
+fn table_shows_pairs_with_change() {
+    let url = serve();
+    let data = tempfile::tempdir().unwrap();
+    let parsed = json(
+        bin()
+            .env("RATES_TEST_BASE_URL", &url)
+            .env("XDG_CACHE_HOME", data.path())
+            .args(["table", "usd/eur", "USD/JPY", "--change", "--json"]),
+    );
+
+    assert_eq!(parsed["ok"], true);
+    assert_eq!(parsed["count"], 2);
+    let eur = &parsed["items"][0];
+    assert_eq!(eur["pair"], "USD/EUR");
+    assert_eq!(eur["rate"], 0.9);
+    assert_eq!(eur["date"], "2026-03-10T00:00:00Z");
+    let pct = eur["change_24h_pct"].as_f64().unwrap();
+    assert!((pct - 12.5).abs() < 1e-9, "{pct}");
+    // No snapshot a week back: the field is left out rather than failing
+    assert!(eur.get("change_7d_pct").is_none());
+    assert_eq!(parsed["items"][1]["change_24h_pct"], 0.0);
+}
+
+#[test]
+fn table_reads_watchlist_from_config() {
+    let url = serve();
+    let home = tempfile::tempdir().unwrap();
+    let dir = home.path().join("dee-rates");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("config.toml"), "watchlist = [\"USD/JPY\"]\n").unwrap();
+
+    let parsed = json(
+        bin()
+            .env("RATES_TEST_BASE_URL", &url)
+            .env("XDG_CACHE_HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path())
+            .args(["table", "--json"]),
+    );
+    assert_eq!(parsed["count"], 1);
+    assert_eq!(parsed["items"][0]["pair"], "USD/JPY");
+    assert!(parsed["items"][0].get("change_24h_pct").is_none());
+}
+
+#[test]
+fn table_without_pairs_or_watchlist_is_invalid() {
+    let home = tempfile::tempdir().unwrap();
+    let parsed = json(
+        bin()
+            .env("XDG_CONFIG_HOME", home.path())
+            .args(["table", "--json"]),
+    );
+    assert_eq!(parsed["ok"], false);
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    let parsed = json(bin().args(["table", "USDEUR", "--json"]));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
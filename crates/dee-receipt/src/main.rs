@@ -4,9 +4,15 @@ use std::path::{Path, PathBuf};
 use base64::Engine as _;
 use chrono::{SecondsFormat, Utc};
 use clap::{Args, Parser, Subcommand};
-use reqwest::blocking::Client;
+use dee_core::http::{Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{print_json, report, Config, ConfigError, ErrorCode, OkItem, OkList, OkMessage};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// Vision requests take longer than the shared default
+const RECEIPT_TIMEOUT_SECS: u64 = 60;
+
 #[derive(Debug, Parser)]
 #[command(
     name = "dee-receipt",
@@ -36,6 +42,9 @@ struct GlobalArgs {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -84,33 +93,7 @@ struct ShowFlags {
     json: bool,
 }
 
-#[derive(Debug, Serialize)]
-struct OkList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct OkItem<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
-struct OkMessage {
-    ok: bool,
-    message: String,
-}
-
-#[derive(Debug, Serialize)]
-struct ErrorJson {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
 struct AppConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     openai_api_key: Option<String>,
@@ -118,7 +101,7 @@ struct AppConfig {
     openai_base_url: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ReceiptItem {
     merchant: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -136,7 +119,7 @@ struct ReceiptItem {
     parsed_at: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct LineItem {
     name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -164,8 +147,8 @@ struct OpenAiMessage {
 
 #[derive(Debug, thiserror::Error)]
 enum AppError {
-    #[error("Configuration directory not found")]
-    ConfigMissing,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
     #[error("Missing OpenAI API key. Set openai.api-key via config set")]
     AuthMissing,
     #[error("Unknown config key: {0}")]
@@ -178,40 +161,46 @@ enum AppError {
     ApiError(String),
     #[error("Response parse failed: {0}")]
     ParseFailed(String),
-    #[error("JSON serialization failed")]
-    Serialize,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
-            Self::ConfigMissing => "CONFIG_MISSING",
+            Self::Config(err) => err.code(),
             Self::AuthMissing => "AUTH_MISSING",
             Self::InvalidConfigKey(_) | Self::InvalidArgument(_) => "INVALID_ARGUMENT",
             Self::RequestFailed => "REQUEST_FAILED",
             Self::ApiError(_) => "API_ERROR",
             Self::ParseFailed(_) => "PARSE_FAILED",
-            Self::Serialize => "SERIALIZE",
         }
     }
 }
 
 type AppResult<T> = Result<T, AppError>;
 
+/// `DEE_RECEIPT_OPENAI_API_KEY` and `DEE_RECEIPT_OPENAI_BASE_URL` override the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-receipt")
+        .env_override("openai_api_key")
+        .env_override("openai_base_url")
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .item::<ReceiptItem>("scan")
+        .message("config set")
+        .item::<AppConfig>("config show")
+        .list::<String>("config path")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = dispatch(&cli) {
-        if cli.global.json {
-            print_json(&ErrorJson {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(&err, cli.global.json);
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -244,7 +233,7 @@ fn cmd_scan(args: &ScanArgs, global: &GlobalArgs) -> AppResult<()> {
         return Err(AppError::InvalidArgument("image file is empty".to_string()));
     }
 
-    let config = load_config_or_default()?;
+    let config = config_file().load()?;
     let api_key = config.openai_api_key.ok_or(AppError::AuthMissing)?;
     let base_url = config
         .openai_base_url
@@ -270,7 +259,11 @@ fn cmd_scan(args: &ScanArgs, global: &GlobalArgs) -> AppResult<()> {
 
     let endpoint = format!("{}/chat/completions", base_url.trim_end_matches('/'));
 
-    let client = Client::new();
+    let client = Http::new("dee-receipt", env!("CARGO_PKG_VERSION"))
+        .timeout_secs(RECEIPT_TIMEOUT_SECS)
+        .args(&global.http)
+        .blocking()
+        .map_err(|_| AppError::RequestFailed)?;
     let response = client
         .post(endpoint)
         .bearer_auth(api_key)
@@ -336,7 +329,7 @@ fn cmd_config(args: &ConfigArgs, global: &GlobalArgs) -> AppResult<()> {
 }
 
 fn cmd_config_set(args: &ConfigSetArgs, global: &GlobalArgs) -> AppResult<()> {
-    let mut config = load_config_or_default()?;
+    let mut config = config_file().load_file()?;
 
     match args.key.as_str() {
         "openai.api-key" => {
@@ -358,7 +351,7 @@ fn cmd_config_set(args: &ConfigSetArgs, global: &GlobalArgs) -> AppResult<()> {
         other => return Err(AppError::InvalidConfigKey(other.to_string())),
     }
 
-    save_config(&config)?;
+    config_file().save(&config)?;
 
     if global.json || args.output.json {
         print_json(&OkMessage {
@@ -375,7 +368,7 @@ fn cmd_config_set(args: &ConfigSetArgs, global: &GlobalArgs) -> AppResult<()> {
 }
 
 fn cmd_config_show(json: bool) -> AppResult<()> {
-    let config = load_config_or_default()?;
+    let config = config_file().load()?;
 
     if json {
         print_json(&OkItem {
@@ -397,7 +390,7 @@ fn cmd_config_show(json: bool) -> AppResult<()> {
 }
 
 fn cmd_config_path(json: bool) -> AppResult<()> {
-    let path = config_path()?;
+    let path = config_file().path();
     let rendered = path.display().to_string();
 
     if json {
@@ -494,96 +487,6 @@ fn infer_mime_type(path: &Path) -> &'static str {
     }
 }
 
-fn config_path() -> AppResult<PathBuf> {
-    let base = dirs::config_dir().ok_or(AppError::ConfigMissing)?;
-    Ok(base.join("dee-receipt").join("config.toml"))
-}
-
-fn load_config_or_default() -> AppResult<AppConfig> {
-    match load_config() {
-        Ok(config) => Ok(config),
-        Err(AppError::InvalidArgument(_)) => Ok(AppConfig::default()),
-        Err(other) => Err(other),
-    }
-}
-
-fn load_config() -> AppResult<AppConfig> {
-    let path = config_path()?;
-    if !path.exists() {
-        return Err(AppError::InvalidArgument(
-            "config file not found".to_string(),
-        ));
-    }
-
-    let raw = fs::read_to_string(path)
-        .map_err(|_| AppError::InvalidArgument("failed to read config".to_string()))?;
-    toml::from_str(&raw).map_err(|_| AppError::InvalidArgument("invalid config TOML".to_string()))
-}
-
-fn save_config(config: &AppConfig) -> AppResult<()> {
-    let path = config_path()?;
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|_| AppError::InvalidArgument("failed to create config dir".to_string()))?;
-    }
-
-    let raw = toml::to_string(config)
-        .map_err(|_| AppError::InvalidArgument("failed to encode config".to_string()))?;
-    fs::write(path, raw)
-        .map_err(|_| AppError::InvalidArgument("failed to write config".to_string()))
-}
-
-fn print_json<T: Serialize>(value: &T) {
-    if let Ok(rendered) = serde_json::to_string(value) {
-        println!("{rendered}");
-    } else {
-        let payload = ErrorJson {
-            ok: false,
-            error: "JSON serialization failed".to_string(),
-            code: AppError::Serialize.code().to_string(),
-        };
-        if let Ok(rendered) = serde_json::to_string(&payload) {
-            println!("{rendered}");
-        } else {
-            println!(
-                "{{\"ok\":false,\"error\":\"JSON serialization failed\",\"code\":\"SERIALIZE\"}}"
-            );
-        }
-    }
-}
-
 fn now_timestamp() -> String {
     Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
 }
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
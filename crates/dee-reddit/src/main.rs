@@ -1,10 +1,14 @@
-use std::fs;
-use std::path::PathBuf;
-
 use base64::Engine as _;
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::http::{Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, report, Config, ConfigError, ErrorCode, Format, OkItem, OkList,
+    OkMessage,
+};
 use reqwest::blocking::Client;
-use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Parser)]
@@ -31,6 +35,11 @@ struct GlobalArgs {
     quiet: bool,
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -110,7 +119,7 @@ struct ShowFlags {
     json: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
 struct AppConfig {
     #[serde(default)]
     client_id: Option<String>,
@@ -124,33 +133,7 @@ struct AppConfig {
     api_base_url: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct OkList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct OkItem<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
-struct OkMessage {
-    ok: bool,
-    message: String,
-}
-
-#[derive(Debug, Serialize)]
-struct ErrorJson {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct PostItem {
     id: String,
     title: String,
@@ -212,8 +195,8 @@ struct PostData {
 
 #[derive(Debug, thiserror::Error)]
 enum AppError {
-    #[error("Configuration directory not found")]
-    ConfigMissing,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
     #[error("Missing Reddit credentials. Set reddit.client-id and reddit.client-secret")]
     AuthMissing,
     #[error("Missing Reddit user-agent. Set reddit.user-agent")]
@@ -228,40 +211,53 @@ enum AppError {
     ApiError(String),
     #[error("Response parse failed")]
     ParseFailed,
-    #[error("JSON serialization failed")]
-    Serialize,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
-            Self::ConfigMissing => "CONFIG_MISSING",
+            Self::Config(err) => err.code(),
             Self::AuthMissing | Self::UserAgentMissing => "AUTH_MISSING",
             Self::InvalidConfigKey(_) | Self::InvalidArgument(_) => "INVALID_ARGUMENT",
             Self::RequestFailed => "REQUEST_FAILED",
             Self::ApiError(_) => "API_ERROR",
             Self::ParseFailed => "PARSE_FAILED",
-            Self::Serialize => "SERIALIZE",
         }
     }
 }
 
 type AppResult<T> = Result<T, AppError>;
 
+/// `DEE_REDDIT_<KEY>` overrides any key in the file, e.g. `DEE_REDDIT_CLIENT_ID`.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-reddit")
+        .env_override("client_id")
+        .env_override("client_secret")
+        .env_override("user_agent")
+        .env_override("oauth_base_url")
+        .env_override("api_base_url")
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<PostItem>("search")
+        .list::<PostItem>("subreddit")
+        .message("config set")
+        .item::<AppConfig>("config show")
+        .list::<String>("config path")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = dispatch(&cli) {
-        if cli.global.json {
-            print_json(&ErrorJson {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -277,10 +273,11 @@ fn cmd_search(args: &SearchArgs, global: &GlobalArgs) -> AppResult<()> {
     validate_query(&args.query)?;
     validate_limit(args.limit)?;
 
-    let config = load_config_or_default()?;
+    let config = config_file().load()?;
     let auth = read_auth_from_config(&config)?;
 
-    let token = fetch_access_token(&auth)?;
+    let client = http_client(&auth, global)?;
+    let token = fetch_access_token(&client, &auth)?;
 
     let url = format!(
         "{}/search.json?q={}&sort={}&limit={}&type=link",
@@ -290,7 +287,7 @@ fn cmd_search(args: &SearchArgs, global: &GlobalArgs) -> AppResult<()> {
         args.limit
     );
 
-    let items = fetch_listing(&url, &token, &auth.user_agent)?;
+    let items = fetch_listing(&client, &url, &token)?;
     render_items(items, global)
 }
 
@@ -298,10 +295,11 @@ fn cmd_subreddit(args: &SubredditArgs, global: &GlobalArgs) -> AppResult<()> {
     validate_query(&args.name)?;
     validate_limit(args.limit)?;
 
-    let config = load_config_or_default()?;
+    let config = config_file().load()?;
     let auth = read_auth_from_config(&config)?;
 
-    let token = fetch_access_token(&auth)?;
+    let client = http_client(&auth, global)?;
+    let token = fetch_access_token(&client, &auth)?;
 
     let endpoint = match args.sort {
         SortArg::Top => "top",
@@ -318,22 +316,18 @@ fn cmd_subreddit(args: &SubredditArgs, global: &GlobalArgs) -> AppResult<()> {
         args.limit
     );
 
-    let mut items = fetch_listing(&url, &token, &auth.user_agent)?;
+    let mut items = fetch_listing(&client, &url, &token)?;
 
     if matches!(args.sort, SortArg::Comments) {
-        items.sort_by(|a, b| b.comments.cmp(&a.comments));
+        items.sort_by_key(|item| std::cmp::Reverse(item.comments));
     }
 
     render_items(items, global)
 }
 
 fn render_items(items: Vec<PostItem>, global: &GlobalArgs) -> AppResult<()> {
-    if global.json {
-        print_json(&OkList {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(global.output, global.json) {
+        print_list(&items, format);
         return Ok(());
     }
 
@@ -392,7 +386,17 @@ fn read_auth_from_config(config: &AppConfig) -> AppResult<AuthConfig> {
     })
 }
 
-fn fetch_access_token(auth: &AuthConfig) -> AppResult<String> {
+/// Reddit asks every client to send its own User-Agent.
+fn http_client(auth: &AuthConfig, global: &GlobalArgs) -> AppResult<Client> {
+    Http::new("dee-reddit", env!("CARGO_PKG_VERSION"))
+        .args(&global.http)
+        .blocking_builder()
+        .user_agent(auth.user_agent.as_str())
+        .build()
+        .map_err(|_| AppError::RequestFailed)
+}
+
+fn fetch_access_token(client: &Client, auth: &AuthConfig) -> AppResult<String> {
     let basic = base64::engine::general_purpose::STANDARD.encode(format!(
         "{}:{}",
         auth.client_id.trim(),
@@ -401,11 +405,9 @@ fn fetch_access_token(auth: &AuthConfig) -> AppResult<String> {
 
     let endpoint = format!("{}/access_token", auth.oauth_base.trim_end_matches('/'));
 
-    let client = Client::new();
     let response = client
         .post(endpoint)
         .header(AUTHORIZATION, format!("Basic {basic}"))
-        .header(USER_AGENT, auth.user_agent.clone())
         .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
         .body("grant_type=client_credentials")
         .send()
@@ -429,12 +431,10 @@ fn fetch_access_token(auth: &AuthConfig) -> AppResult<String> {
     Ok(token.access_token)
 }
 
-fn fetch_listing(url: &str, token: &str, user_agent: &str) -> AppResult<Vec<PostItem>> {
-    let client = Client::new();
+fn fetch_listing(client: &Client, url: &str, token: &str) -> AppResult<Vec<PostItem>> {
     let response = client
         .get(url)
         .header(AUTHORIZATION, format!("Bearer {token}"))
-        .header(USER_AGENT, user_agent)
         .send()
         .map_err(|_| AppError::RequestFailed)?;
 
@@ -496,7 +496,7 @@ fn cmd_config(args: &ConfigArgs, global: &GlobalArgs) -> AppResult<()> {
 }
 
 fn cmd_config_set(args: &ConfigSetArgs, global: &GlobalArgs) -> AppResult<()> {
-    let mut config = load_config_or_default()?;
+    let mut config = config_file().load_file()?;
 
     match args.key.as_str() {
         "reddit.client-id" => {
@@ -522,7 +522,7 @@ fn cmd_config_set(args: &ConfigSetArgs, global: &GlobalArgs) -> AppResult<()> {
         other => return Err(AppError::InvalidConfigKey(other.to_string())),
     }
 
-    save_config(&config)?;
+    config_file().save(&config)?;
 
     if global.json || args.output.json {
         print_json(&OkMessage {
@@ -548,7 +548,7 @@ fn ensure_not_empty(value: &str) -> AppResult<()> {
 }
 
 fn cmd_config_show(json: bool) -> AppResult<()> {
-    let config = load_config_or_default()?;
+    let config = config_file().load()?;
 
     if json {
         print_json(&OkItem {
@@ -570,7 +570,7 @@ fn cmd_config_show(json: bool) -> AppResult<()> {
 }
 
 fn cmd_config_path(json: bool) -> AppResult<()> {
-    let path = config_path()?;
+    let path = config_file().path();
     let rendered = path.display().to_string();
 
     if json {
@@ -585,93 +585,3 @@ fn cmd_config_path(json: bool) -> AppResult<()> {
 
     Ok(())
 }
-
-fn config_path() -> AppResult<PathBuf> {
-    let base = dirs::config_dir().ok_or(AppError::ConfigMissing)?;
-    Ok(base.join("dee-reddit").join("config.toml"))
-}
-
-fn load_config_or_default() -> AppResult<AppConfig> {
-    match load_config() {
-        Ok(config) => Ok(config),
-        Err(AppError::InvalidArgument(_)) => Ok(AppConfig::default()),
-        Err(other) => Err(other),
-    }
-}
-
-fn load_config() -> AppResult<AppConfig> {
-    let path = config_path()?;
-    if !path.exists() {
-        return Err(AppError::InvalidArgument(
-            "config file not found".to_string(),
-        ));
-    }
-
-    let raw = fs::read_to_string(path)
-        .map_err(|_| AppError::InvalidArgument("failed to read config".to_string()))?;
-    toml::from_str(&raw).map_err(|_| AppError::InvalidArgument("invalid config TOML".to_string()))
-}
-
-fn save_config(config: &AppConfig) -> AppResult<()> {
-    let path = config_path()?;
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|_| AppError::InvalidArgument("failed to create config dir".to_string()))?;
-    }
-
-    let raw = toml::to_string(config)
-        .map_err(|_| AppError::InvalidArgument("failed to encode config".to_string()))?;
-    fs::write(path, raw)
-        .map_err(|_| AppError::InvalidArgument("failed to write config".to_string()))
-}
-
-fn print_json<T: Serialize>(value: &T) {
-    if let Ok(rendered) = serde_json::to_string(value) {
-        println!("{rendered}");
-    } else {
-        let payload = ErrorJson {
-            ok: false,
-            error: "JSON serialization failed".to_string(),
-            code: AppError::Serialize.code().to_string(),
-        };
-        if let Ok(rendered) = serde_json::to_string(&payload) {
-            println!("{rendered}");
-        } else {
-            println!(
-                "{{\"ok\":false,\"error\":\"JSON serialization failed\",\"code\":\"SERIALIZE\"}}"
-            );
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
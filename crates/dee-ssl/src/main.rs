@@ -6,9 +6,14 @@ use std::time::Duration;
 use anyhow::Result;
 use chrono::{DateTime, SecondsFormat, Utc};
 use clap::{ArgAction, Args, Parser, Subcommand};
+use dee_core::format::render_list;
+use dee_core::pipe::{PipeArgs, PipeError};
+use dee_core::schema::{self, Schemas};
+use dee_core::{report, style, ErrorCode, Format, OkItem, OkList};
 use rustls::client::ClientConnection;
 use rustls::pki_types::{CertificateDer, ServerName};
 use rustls::{ClientConfig, RootCertStore, StreamOwned};
+use schemars::JsonSchema;
 use serde::Serialize;
 use thiserror::Error;
 use x509_parser::extensions::ParsedExtension;
@@ -19,7 +24,7 @@ use x509_parser::prelude::FromDer;
     name = "dee-ssl",
     version,
     about = "SSL certificate checker for domains",
-    after_help = "EXAMPLES:\n  dee-ssl check example.com\n  dee-ssl check example.com --chain\n  dee-ssl check example.com --warn-days 30\n  dee-ssl check example.com --json\n  dee-ssl check example.com --port 8443\n  dee-ssl check example.com --timeout-secs 5"
+    after_help = "EXAMPLES:\n  dee-ssl check example.com\n  dee-ssl check example.com --chain\n  dee-ssl check example.com --warn-days 30\n  dee-ssl check example.com --json\n  dee-ssl check example.com --port 8443\n  dee-ssl check example.com --timeout-secs 5\n  dee-porkbun domains list-all --json | dee-ssl check --stdin-json --field domain"
 )]
 struct Cli {
     #[command(subcommand)]
@@ -33,6 +38,10 @@ struct Cli {
 
     #[arg(short = 'v', long, global = true, action = ArgAction::SetTrue)]
     verbose: bool,
+
+    /// List format for `--chain`: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -43,15 +52,20 @@ enum Commands {
 
 #[derive(Args, Debug)]
 struct CheckArgs {
-    /// Domain to check
-    domain: String,
+    /// Domain to check (omit with --stdin-json)
+    #[arg(required_unless_present = "stdin_json")]
+    domain: Option<String>,
+
+    // Check every domain piped in (`domain` of each item, unless --field)
+    #[command(flatten)]
+    pipe: PipeArgs,
 
     /// TLS port
     #[arg(long, default_value_t = 443)]
     port: u16,
 
     /// Show full certificate chain
-    #[arg(long, action = ArgAction::SetTrue)]
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "stdin_json")]
     chain: bool,
 
     /// Exit with code 1 when cert expires in N days or less
@@ -86,7 +100,7 @@ enum AppError {
     },
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
             Self::ResolveAddress { .. } => "RESOLVE_FAILED",
@@ -98,14 +112,7 @@ impl AppError {
     }
 }
 
-#[derive(Debug, Serialize)]
-struct JsonError<'a> {
-    ok: bool,
-    error: &'a str,
-    code: &'a str,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct CertItem {
     domain: String,
     port: u16,
@@ -118,7 +125,7 @@ struct CertItem {
     chain_depth: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ChainCertItem {
     index: usize,
     subject: String,
@@ -127,24 +134,52 @@ struct ChainCertItem {
     not_after: String,
 }
 
-#[derive(Debug, Serialize)]
-struct SingleOk<T> {
+/// One piped domain: its certificate's expiry, or why it could not be checked.
+#[derive(Debug, Serialize, JsonSchema)]
+struct BatchItem {
+    domain: String,
+    port: u16,
     ok: bool,
-    item: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    valid: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    days_until_expiry: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issuer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
 }
 
-#[derive(Debug, Serialize)]
-struct ListOk<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
+/// `check` prints one of these: the chain with `--chain`, one item per domain
+/// with `--stdin-json`.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)] // only described; the variants are printed directly
+enum CheckOutput {
+    Leaf(OkItem<CertItem>),
+    Chain(OkList<ChainCertItem>),
+    Batch(OkList<BatchItem>),
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new().custom::<CheckOutput>("check")
 }
 
 fn main() {
+    schema::exit_on_schema(schemas);
     let _ = rustls::crypto::ring::default_provider().install_default();
-    let cli = parse_cli();
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = run(&cli) {
+        if let Some(err) = err.downcast_ref::<PipeError>() {
+            report(err, Format::json_errors(cli.output, cli.json));
+            std::process::exit(err.exit_code());
+        }
         let app_err =
             err.downcast_ref::<AppError>()
                 .cloned()
@@ -152,21 +187,8 @@ fn main() {
                     reason: err.to_string(),
                 });
 
-        if cli.json {
-            let payload = JsonError {
-                ok: false,
-                error: &app_err.to_string(),
-                code: app_err.code(),
-            };
-            if let Ok(serialized) = serde_json::to_string(&payload) {
-                println!("{serialized}");
-            } else {
-                println!("{{\"ok\":false,\"error\":\"serialization failed\",\"code\":\"SERIALIZE_ERROR\"}}");
-            }
-        } else {
-            eprintln!("error: {app_err}");
-        }
-        std::process::exit(1);
+        report(&app_err, Format::json_errors(cli.output, cli.json));
+        std::process::exit(app_err.exit_code());
     }
 }
 
@@ -177,25 +199,11 @@ fn run(cli: &Cli) -> Result<()> {
 }
 
 fn handle_check(cli: &Cli, args: &CheckArgs) -> Result<()> {
-    let certs = fetch_cert_chain(&args.domain, args.port, cli.verbose, args.timeout_secs)?;
-    let leaf = certs.first().ok_or_else(|| AppError::MissingCertificate {
-        domain: args.domain.clone(),
-        port: args.port,
-    })?;
-
-    let parsed = parse_cert(leaf)?;
-    let expires = parsed.not_after.clone();
-    let expires_dt = parse_rfc3339_utc(&expires)?;
-    let now = Utc::now();
-    let days_until_expiry = expires_dt.signed_duration_since(now).num_days();
-
-    if args.warn_days > 0 && days_until_expiry <= args.warn_days {
-        return Err(AppError::ExpiringSoon {
-            days_until_expiry,
-            warn_days: args.warn_days,
-        }
-        .into());
+    if let Some(domains) = args.pipe.values("domain")? {
+        return handle_batch(cli, args, domains);
     }
+    let domain = args.domain.as_deref().unwrap_or_default();
+    let (item, certs) = inspect(domain, args)?;
 
     if args.chain {
         let items = certs
@@ -204,22 +212,23 @@ fn handle_check(cli: &Cli, args: &CheckArgs) -> Result<()> {
             .map(|(index, cert)| cert_to_chain_item(index, cert))
             .collect::<Result<Vec<_>>>()?;
 
-        if cli.json {
-            let payload = ListOk {
-                ok: true,
-                count: items.len(),
-                items,
-            };
-            println!("{}", serde_json::to_string(&payload)?);
+        if let Some(format) = Format::resolve(cli.output, cli.json) {
+            print!("{}", render_list(&items, format)?);
             return Ok(());
         }
 
         if !cli.quiet {
-            println!("Certificate chain for {}:{}", args.domain, args.port);
+            println!(
+                "{}",
+                style::header(format!("Certificate chain for {domain}:{}", args.port))
+            );
             for item in &items {
                 println!(
-                    "[{}] {}\n     issuer: {}\n     valid: {} → {}",
-                    item.index, item.subject, item.issuer, item.not_before, item.not_after
+                    "[{}] {}\n     {}\n     {}",
+                    item.index,
+                    item.subject,
+                    style::dim(format!("issuer: {}", item.issuer)),
+                    style::dim(format!("valid: {} → {}", item.not_before, item.not_after))
                 );
             }
         } else {
@@ -230,8 +239,66 @@ fn handle_check(cli: &Cli, args: &CheckArgs) -> Result<()> {
         return Ok(());
     }
 
+    if cli.json {
+        let payload = OkItem { ok: true, item };
+        println!("{}", serde_json::to_string(&payload)?);
+        return Ok(());
+    }
+
+    if cli.quiet {
+        println!("{}", item.expires);
+    } else {
+        let valid = if item.valid {
+            style::good("true")
+        } else {
+            style::bad("false")
+        };
+        println!(
+            "{}",
+            style::header(format!("Domain: {}:{}", item.domain, item.port))
+        );
+        println!("Valid now: {valid}");
+        println!(
+            "Expires: {}",
+            style::expiry_within(
+                format!("{} ({} days)", item.expires, item.days_until_expiry),
+                item.days_until_expiry,
+                args.warn_days.max(style::EXPIRY_WARNING_DAYS)
+            )
+        );
+        println!("Issuer: {}", item.issuer);
+        println!("Subject: {}", item.subject);
+        println!("SANs: {}", style::dim(item.sans.join(", ")));
+        println!("Chain depth: {}", item.chain_depth);
+    }
+
+    Ok(())
+}
+
+/// Fetch and parse `domain`'s certificate; `EXPIRING_SOON` inside `--warn-days`.
+fn inspect(domain: &str, args: &CheckArgs) -> Result<(CertItem, Vec<CertificateDer<'static>>)> {
+    let certs = fetch_cert_chain(domain, args.port, args.timeout_secs)?;
+    let leaf = certs.first().ok_or_else(|| AppError::MissingCertificate {
+        domain: domain.to_string(),
+        port: args.port,
+    })?;
+
+    let parsed = parse_cert(leaf)?;
+    let expires = parsed.not_after.clone();
+    let expires_dt = parse_rfc3339_utc(&expires)?;
+    let now = Utc::now();
+    let days_until_expiry = expires_dt.signed_duration_since(now).num_days();
+
+    if args.warn_days > 0 && days_until_expiry <= args.warn_days {
+        return Err(AppError::ExpiringSoon {
+            days_until_expiry,
+            warn_days: args.warn_days,
+        }
+        .into());
+    }
+
     let item = CertItem {
-        domain: args.domain.clone(),
+        domain: domain.to_string(),
         port: args.port,
         valid: parsed
             .x509
@@ -244,35 +311,77 @@ fn handle_check(cli: &Cli, args: &CheckArgs) -> Result<()> {
         sans: parsed.sans,
         chain_depth: certs.len(),
     };
+    Ok((item, certs))
+}
 
-    if cli.json {
-        let payload = SingleOk { ok: true, item };
-        println!("{}", serde_json::to_string(&payload)?);
+/// Check each piped domain in turn; a failure is recorded on its item, not returned.
+fn handle_batch(cli: &Cli, args: &CheckArgs, domains: Vec<String>) -> Result<()> {
+    let items: Vec<BatchItem> = domains
+        .into_iter()
+        .map(|domain| match inspect(&domain, args) {
+            Ok((cert, _)) => BatchItem {
+                domain,
+                port: args.port,
+                ok: true,
+                valid: Some(cert.valid),
+                expires: Some(cert.expires),
+                days_until_expiry: Some(cert.days_until_expiry),
+                issuer: Some(cert.issuer),
+                error: None,
+                code: None,
+            },
+            Err(err) => {
+                let err = err
+                    .downcast::<AppError>()
+                    .unwrap_or_else(|err| AppError::ParseCert {
+                        reason: err.to_string(),
+                    });
+                BatchItem {
+                    domain,
+                    port: args.port,
+                    ok: false,
+                    valid: None,
+                    expires: None,
+                    days_until_expiry: None,
+                    issuer: None,
+                    error: Some(err.to_string()),
+                    code: Some(err.code()),
+                }
+            }
+        })
+        .collect();
+
+    if let Some(format) = Format::resolve(cli.output, cli.json) {
+        print!("{}", render_list(&items, format)?);
         return Ok(());
     }
-
-    if cli.quiet {
-        println!("{}", item.expires);
-    } else {
-        println!("Domain: {}:{}", item.domain, item.port);
-        println!("Valid now: {}", item.valid);
-        println!(
-            "Expires: {} ({} days)",
-            item.expires, item.days_until_expiry
-        );
-        println!("Issuer: {}", item.issuer);
-        println!("Subject: {}", item.subject);
-        println!("SANs: {}", item.sans.join(", "));
-        println!("Chain depth: {}", item.chain_depth);
+    for item in &items {
+        match (&item.expires, &item.error) {
+            (Some(expires), _) if cli.quiet => println!("{}\t{expires}", item.domain),
+            (Some(expires), _) => {
+                let days = item.days_until_expiry.unwrap_or_default();
+                println!(
+                    "{}: {}",
+                    item.domain,
+                    style::expiry_within(
+                        format!("{expires} ({days} days)"),
+                        days,
+                        args.warn_days.max(style::EXPIRY_WARNING_DAYS)
+                    )
+                );
+            }
+            (None, Some(error)) if !cli.quiet => {
+                println!("{}: {}", item.domain, style::bad(format!("error: {error}")))
+            }
+            _ => {}
+        }
     }
-
     Ok(())
 }
 
 fn fetch_cert_chain(
     domain: &str,
     port: u16,
-    verbose: bool,
     timeout_secs: u64,
 ) -> Result<Vec<CertificateDer<'static>>> {
     let timeout = Duration::from_secs(timeout_secs);
@@ -314,9 +423,7 @@ fn fetch_cert_chain(
     let cert_result = rustls_native_certs::load_native_certs();
     for cert in cert_result.certs {
         if let Err(error) = roots.add(cert) {
-            if verbose {
-                eprintln!("warning: failed to add root cert: {error}");
-            }
+            tracing::debug!("warning: failed to add root cert: {error}");
         }
     }
 
@@ -439,35 +546,3 @@ fn parse_rfc3339_utc(input: &str) -> Result<DateTime<Utc>> {
     })?;
     Ok(parsed.with_timezone(&Utc))
 }
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -0,0 +1,58 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use serde_json::Value;
+
+fn bin() -> Command {
+    Command::cargo_bin("dee-ssl").unwrap()
+}
+
+/// Each piped domain gets an item; port 1 refuses connections, so both fail fast.
+#[test]
+fn piped_domains_are_checked_one_by_one() {
+    let out = bin()
+        .args([
+            "check",
+            "--stdin-json",
+            "--field",
+            "name",
+            "--port",
+            "1",
+            "--timeout-secs",
+            "2",
+            "--json",
+        ])
+        .write_stdin(r#"{"ok":true,"count":2,"items":[{"name":"127.0.0.1"},{"name":"127.0.0.1"}]}"#)
+        .output()
+        .unwrap();
+
+    assert!(out.status.success());
+    let parsed: Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["ok"], Value::Bool(true));
+    assert_eq!(parsed["count"], 2);
+    let item = &parsed["items"][0];
+    assert_eq!(item["domain"], "127.0.0.1");
+    assert_eq!(item["ok"], Value::Bool(false));
+    assert!(item["code"].is_string());
+}
+
+#[test]
+fn a_failed_upstream_command_fails_the_check() {
+    let out = bin()
+        .args(["check", "--stdin-json", "--json"])
+        .write_stdin(r#"{"ok":false,"error":"Auth missing","code":"AUTH_MISSING"}"#)
+        .output()
+        .unwrap();
+
+    assert_eq!(out.status.code(), Some(1));
+    let parsed: Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], "UPSTREAM_FAILED");
+}
+
+#[test]
+fn a_domain_is_required_without_stdin_json() {
+    bin().args(["check", "--json"]).assert().code(2);
+    bin()
+        .args(["check", "example.com", "--field", "domain"])
+        .assert()
+        .code(2);
+}
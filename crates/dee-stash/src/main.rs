@@ -3,7 +3,10 @@ use std::path::PathBuf;
 
 use chrono::{SecondsFormat, Utc};
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::schema::{self, Schemas};
+use dee_core::{print_json, report, ErrorCode, Format, OkItem, OkList};
 use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Parser)]
@@ -35,6 +38,10 @@ struct GlobalFlags {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -148,7 +155,7 @@ struct ExportArgs {
     format: TransferFormat,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct BookmarkItem {
     id: i64,
     url: String,
@@ -163,20 +170,7 @@ struct BookmarkItem {
     updated_at: String,
 }
 
-#[derive(Debug, Serialize)]
-struct ListResponse<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct ItemResponse<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ActionResponse {
     ok: bool,
     message: String,
@@ -186,14 +180,7 @@ struct ActionResponse {
     count: Option<usize>,
 }
 
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct CsvItem {
     format: String,
     data: String,
@@ -218,7 +205,7 @@ enum AppError {
     Parse,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
             Self::DataDirMissing => "CONFIG_MISSING",
@@ -234,20 +221,40 @@ impl AppError {
 
 type AppResult<T> = Result<T, AppError>;
 
+/// `export` prints one of these: the CSV text with `--format csv`.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)] // only described; the variants are printed directly
+enum ExportOutput {
+    Json(OkList<BookmarkItem>),
+    Csv(OkItem<CsvItem>),
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .custom::<ActionResponse>("add")
+        .list::<BookmarkItem>("list")
+        .list::<BookmarkItem>("search")
+        .item::<BookmarkItem>("show")
+        .custom::<ActionResponse>("edit")
+        .custom::<ActionResponse>("delete")
+        .custom::<ActionResponse>("archive")
+        .custom::<ActionResponse>("unarchive")
+        .custom::<ActionResponse>("import")
+        .custom::<ExportOutput>("export")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = run(&cli) {
-        if cli.global.json {
-            print_json(&ErrorResponse {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -258,9 +265,7 @@ fn run(cli: &Cli) -> AppResult<()> {
     if let Some(parent) = db_path.parent() {
         fs::create_dir_all(parent).map_err(|_| AppError::Database)?;
     }
-    if cli.global.verbose {
-        eprintln!("[dee-stash] db_path={}", db_path.display());
-    }
+    tracing::debug!("db_path={}", db_path.display());
 
     let conn = Connection::open(db_path).map_err(|_| AppError::Database)?;
     init_db(&conn)?;
@@ -461,7 +466,7 @@ fn cmd_show(conn: &Connection, id: i64, global: &GlobalFlags) -> AppResult<()> {
     let item = bookmark_by_id(conn, id)?.ok_or(AppError::NotFound)?;
 
     if global.json {
-        print_json(&ItemResponse { ok: true, item });
+        print_json(&OkItem { ok: true, item });
     } else if global.quiet {
         println!("{id}");
     } else {
@@ -550,7 +555,7 @@ fn cmd_export(conn: &Connection, args: &ExportArgs, global: &GlobalFlags) -> App
     match args.format {
         TransferFormat::Json => {
             if global.json {
-                print_json(&ListResponse {
+                print_json(&OkList {
                     ok: true,
                     count: items.len(),
                     items,
@@ -565,7 +570,7 @@ fn cmd_export(conn: &Connection, args: &ExportArgs, global: &GlobalFlags) -> App
         TransferFormat::Csv => {
             let csv = bookmarks_to_csv(&items);
             if global.json {
-                print_json(&ItemResponse {
+                print_json(&OkItem {
                     ok: true,
                     item: CsvItem {
                         format: "csv".to_string(),
@@ -633,12 +638,8 @@ fn parse_bookmark_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<BookmarkItem>
 }
 
 fn print_list(items: Vec<BookmarkItem>, global: &GlobalFlags) -> AppResult<()> {
-    if global.json {
-        print_json(&ListResponse {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(global.output, global.json) {
+        dee_core::print_list(&items, format);
     } else if global.quiet {
         println!("{}", items.len());
     } else if items.is_empty() {
@@ -822,47 +823,3 @@ fn empty_to_none(value: &str) -> Option<&str> {
         Some(value)
     }
 }
-
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(text) => println!("{text}"),
-        Err(_) => {
-            println!(
-                "{{\"ok\":false,\"error\":\"JSON serialization failed\",\"code\":\"SERIALIZE\"}}"
-            );
-            std::process::exit(1);
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -0,0 +1,158 @@
+use crate::error::StocksError;
+use crate::providers::ProviderKind;
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use clap::{Args, Parser, Subcommand};
+use dee_core::cache::{CacheArgs, CacheCommand};
+use dee_core::http::HttpArgs;
+use dee_core::Format;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "dee-stocks",
+    version,
+    about = "Stock quotes, price history and watchlists",
+    after_help = "EXAMPLES:\n  dee-stocks quote AAPL\n  dee-stocks quote AAPL --json\n  dee-stocks quote VOD.UK\n  dee-stocks history AAPL --range 6m\n  dee-stocks history MSFT --range ytd --output csv\n  dee-stocks watchlist\n  dee-stocks watchlist AAPL MSFT NVDA --json\n  dee-stocks quote AAPL --offline\n  dee-stocks quote IBM --provider alpha-vantage\n  dee-stocks config set watchlist AAPL,MSFT,NVDA\n  dee-stocks config set alpha_vantage_key <KEY>\n  dee-stocks cache clear"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Commands,
+
+    #[command(flatten)]
+    pub global: GlobalFlags,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct GlobalFlags {
+    #[arg(short, long, global = true)]
+    pub json: bool,
+
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    #[command(flatten)]
+    pub cache: CacheArgs,
+
+    /// Price source (default: `provider` in the config file, else stooq)
+    #[arg(long, global = true, value_enum)]
+    pub provider: Option<ProviderKind>,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    pub output: Option<Format>,
+
+    #[command(flatten)]
+    pub http: HttpArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Latest price for a symbol, with the change since the previous close
+    Quote {
+        /// Ticker, e.g. AAPL; other markets with a suffix (VOD.UK) on Stooq
+        symbol: String,
+    },
+    /// Daily prices over a range, oldest first
+    History {
+        /// Ticker, e.g. AAPL
+        symbol: String,
+        /// How far back: a number with d, w, m or y (5d, 6m, 1y), or ytd
+        #[arg(long, default_value = "1m")]
+        range: String,
+    },
+    /// Quotes for the watchlist in the config file (or the symbols given)
+    Watchlist {
+        /// Tickers (default: `watchlist` in the config file)
+        symbols: Vec<String>,
+    },
+    /// Show or change defaults in the config file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Manage cached prices
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Set a key: provider, watchlist, cache_ttl_secs, alpha_vantage_key,
+    /// secrets_backend (an empty value clears it; lists are comma-separated)
+    Set { key: String, value: String },
+    /// Show the settings in effect
+    Show,
+    /// Print the config file path
+    Path,
+}
+
+/// `aapl` -> `AAPL`. Letters, digits and the `.`, `-`, `^`, `=` used by
+/// exchange suffixes, share classes and indices.
+pub fn normalize_symbol(raw: &str) -> Result<String, StocksError> {
+    let symbol = raw.trim().to_uppercase();
+    let valid = (1..=20).contains(&symbol.len())
+        && symbol
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '^' | '='));
+    if valid {
+        Ok(symbol)
+    } else {
+        Err(StocksError::InvalidSymbol(raw.to_string()))
+    }
+}
+
+/// First day of `--range` ending `today`: `5d`, `2w`, `6m`, `1y` or `ytd`.
+pub fn range_start(raw: &str, today: NaiveDate) -> Result<NaiveDate, StocksError> {
+    let invalid = || StocksError::InvalidRange(raw.to_string());
+    let trimmed = raw.trim().to_ascii_lowercase();
+    if trimmed == "ytd" {
+        return NaiveDate::from_ymd_opt(today.year(), 1, 1).ok_or_else(invalid);
+    }
+    let unit_at = trimmed.char_indices().last().map_or(0, |(index, _)| index);
+    let (number, unit) = trimmed.split_at(unit_at);
+    let count: u32 = number
+        .parse()
+        .ok()
+        .filter(|n| (1..=3650).contains(n))
+        .ok_or_else(invalid)?;
+    match unit {
+        "d" => today.checked_sub_signed(Duration::days(count.into())),
+        "w" => today.checked_sub_signed(Duration::weeks(count.into())),
+        "m" => today.checked_sub_months(Months::new(count)),
+        "y" => today.checked_sub_months(Months::new(count.saturating_mul(12))),
+        _ => None,
+    }
+    .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ranges() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let date = |raw| range_start(raw, today).unwrap().to_string();
+        assert_eq!(date("5d"), "2026-10-11");
+        assert_eq!(date("2w"), "2026-10-02");
+        assert_eq!(date("6m"), "2026-04-16");
+        assert_eq!(date("1Y"), "2025-10-16");
+        assert_eq!(date("ytd"), "2026-01-01");
+        assert!(range_start("0d", today).is_err());
+        assert!(range_start("6", today).is_err());
+        assert!(range_start("3q", today).is_err());
+    }
+
+    #[test]
+    fn normalizes_symbols() {
+        assert_eq!(normalize_symbol(" aapl ").unwrap(), "AAPL");
+        assert_eq!(normalize_symbol("brk-b").unwrap(), "BRK-B");
+        assert_eq!(normalize_symbol("^spx").unwrap(), "^SPX");
+        assert!(normalize_symbol("").is_err());
+        assert!(normalize_symbol("AA PL").is_err());
+    }
+}
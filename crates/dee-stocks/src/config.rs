@@ -0,0 +1,134 @@
+use crate::cli::normalize_symbol;
+use crate::error::StocksError;
+use crate::providers::ProviderKind;
+use clap::ValueEnum;
+use dee_core::{Config, SecretsBackend};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Keys accepted by `config set`
+pub const CONFIG_KEYS: &[&str] = &[
+    "provider",
+    "watchlist",
+    "cache_ttl_secs",
+    "alpha_vantage_key",
+    "secrets_backend",
+];
+
+/// Prices are delayed on the free tiers anyway; a quarter of an hour saves calls
+pub const DEFAULT_CACHE_TTL_SECS: u64 = 15 * 60;
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AppConfig {
+    /// Price source when `--provider` is not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderKind>,
+    /// Symbols shown by `watchlist`
+    #[serde(default)]
+    pub watchlist: Vec<String>,
+    /// Seconds cached prices stay fresh (default 15 minutes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_secs: Option<u64>,
+    /// API key for the alpha-vantage provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alpha_vantage_key: Option<String>,
+    /// `plaintext` (default) or `keyring`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secrets_backend: Option<SecretsBackend>,
+}
+
+/// `config show`: the settings in effect, with the API key masked.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConfigView {
+    pub provider: ProviderKind,
+    pub watchlist: Vec<String>,
+    pub cache_ttl_secs: u64,
+    pub alpha_vantage_key_set: bool,
+    pub secrets_backend: SecretsBackend,
+}
+
+impl AppConfig {
+    pub fn cache_ttl_secs(&self) -> u64 {
+        self.cache_ttl_secs.unwrap_or(DEFAULT_CACHE_TTL_SECS)
+    }
+
+    pub fn view(&self) -> ConfigView {
+        ConfigView {
+            provider: self.provider.unwrap_or_default(),
+            watchlist: self.watchlist.clone(),
+            cache_ttl_secs: self.cache_ttl_secs(),
+            alpha_vantage_key_set: self.alpha_vantage_key.is_some(),
+            secrets_backend: self.secrets_backend.unwrap_or_default(),
+        }
+    }
+
+    /// Validate and store one `config set` value. An empty value clears the key; lists
+    /// are comma-separated.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), StocksError> {
+        let value = value.trim();
+        let invalid = |what: &str| StocksError::InvalidArgument(format!("invalid {key}: {what}"));
+
+        match key {
+            "provider" if value.is_empty() => self.provider = None,
+            "provider" => {
+                let provider = ProviderKind::from_str(value, true)
+                    .map_err(|_| invalid("expected stooq or alpha-vantage"))?;
+                self.provider = Some(provider);
+            }
+            "watchlist" => {
+                self.watchlist = value
+                    .split(',')
+                    .filter(|v| !v.trim().is_empty())
+                    .map(normalize_symbol)
+                    .collect::<Result<_, _>>()?;
+            }
+            "cache_ttl_secs" if value.is_empty() => self.cache_ttl_secs = None,
+            "cache_ttl_secs" => {
+                let secs = value.parse().map_err(|_| invalid("expected seconds"))?;
+                self.cache_ttl_secs = Some(secs);
+            }
+            "alpha_vantage_key" => {
+                self.alpha_vantage_key = (!value.is_empty()).then(|| value.to_string());
+            }
+            "secrets_backend" if value.is_empty() => self.secrets_backend = None,
+            "secrets_backend" => {
+                self.secrets_backend = Some(value.parse().map_err(StocksError::InvalidArgument)?);
+            }
+            other => {
+                return Err(StocksError::InvalidArgument(format!(
+                    "unknown config key '{other}' (expected one of: {})",
+                    CONFIG_KEYS.join(", ")
+                )))
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `DEE_STOCKS_<KEY>` overrides any key in the file, e.g. `DEE_STOCKS_PROVIDER=stooq`
+/// or `DEE_STOCKS_WATCHLIST=AAPL,MSFT`. With `secrets_backend = "keyring"` the API
+/// key lives in the OS keyring instead of the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-stocks")
+        .env_overrides(CONFIG_KEYS)
+        .secret("alpha_vantage_key")
+}
+
+pub fn config_path() -> PathBuf {
+    config_file().path()
+}
+
+/// The settings in effect, env overrides included.
+pub fn load_config() -> Result<AppConfig, StocksError> {
+    Ok(config_file().load()?)
+}
+
+/// The file as written, for `config set` to edit and save back.
+pub fn load_config_file() -> Result<AppConfig, StocksError> {
+    Ok(config_file().load_file()?)
+}
+
+pub fn save_config(cfg: &AppConfig) -> Result<(), StocksError> {
+    Ok(config_file().save(cfg)?)
+}
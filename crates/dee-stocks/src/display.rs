@@ -0,0 +1,152 @@
+use crate::config::ConfigView;
+use crate::models::{Bar, Quote};
+use dee_core::style;
+
+pub fn print_quote(quote: &Quote, quiet: bool) {
+    if quiet {
+        // Minimal output: symbol and price
+        println!("{} {}", quote.symbol, price(quote.price));
+        return;
+    }
+
+    println!(
+        "{}",
+        style::header(format!("{}  {}", quote.symbol, price(quote.price)))
+    );
+    if let (Some(change), Some(pct)) = (quote.change, quote.change_percent) {
+        println!(
+            "Change: {}",
+            movement(format!("{change:+.2} ({pct:+.2}%)"), change)
+        );
+    }
+    println!(
+        "Open: {}  High: {}  Low: {}",
+        price(quote.open),
+        price(quote.high),
+        price(quote.low)
+    );
+    if let Some(previous) = quote.previous_close {
+        println!("Previous close: {}", price(previous));
+    }
+    if let Some(volume) = quote.volume {
+        println!("Volume: {volume}");
+    }
+    println!(
+        "{}",
+        style::dim(format!("Date: {} from {}", quote.date, quote.provider))
+    );
+}
+
+pub fn print_history(bars: &[Bar], quiet: bool) {
+    if quiet {
+        // Minimal output: date and close
+        for bar in bars {
+            println!("{} {}", bar.date, price(bar.close));
+        }
+        return;
+    }
+    if bars.is_empty() {
+        println!("(no trading days in range)");
+        return;
+    }
+
+    println!(
+        "{}",
+        style::header(format!(
+            "{:<10} {:>12} {:>12} {:>12} {:>12} {:>14}",
+            "DATE", "OPEN", "HIGH", "LOW", "CLOSE", "VOLUME"
+        ))
+    );
+    for bar in bars {
+        println!(
+            "{:<10} {:>12} {:>12} {:>12} {:>12} {:>14}",
+            bar.date,
+            price(bar.open),
+            price(bar.high),
+            price(bar.low),
+            price(bar.close),
+            bar.volume.map(|v| v.to_string()).unwrap_or_default()
+        );
+    }
+    if let (Some(first), Some(last)) = (bars.first(), bars.last()) {
+        let change = last.close - first.close;
+        let pct = if first.close != 0.0 {
+            change / first.close * 100.0
+        } else {
+            0.0
+        };
+        println!(
+            "{} over {} trading days",
+            movement(format!("{change:+.2} ({pct:+.2}%)"), change),
+            bars.len()
+        );
+    }
+}
+
+pub fn print_watchlist(quotes: &[Quote], quiet: bool) {
+    if quiet {
+        // Minimal output: symbol and price
+        for quote in quotes {
+            println!("{} {}", quote.symbol, price(quote.price));
+        }
+        return;
+    }
+
+    println!(
+        "{}",
+        style::header(format!(
+            "{:<10} {:>12} {:>10} {:>9} {}",
+            "SYMBOL", "PRICE", "CHANGE", "CHANGE%", "DATE"
+        ))
+    );
+    // Padded before colouring so escape codes do not skew the columns
+    for quote in quotes {
+        let (change, pct) = match (quote.change, quote.change_percent) {
+            (Some(change), Some(pct)) => (
+                movement(format!("{:>10}", format!("{change:+.2}")), change),
+                movement(format!("{:>9}", format!("{pct:+.2}%")), change),
+            ),
+            _ => (format!("{:>10}", "-"), format!("{:>9}", "-")),
+        };
+        println!(
+            "{:<10} {:>12} {change} {pct} {}",
+            quote.symbol,
+            price(quote.price),
+            quote.date
+        );
+    }
+}
+
+pub fn print_config(view: &ConfigView) {
+    println!("provider: {}", view.provider.name());
+    println!("watchlist: {}", view.watchlist.join(", "));
+    println!("cache_ttl_secs: {}", view.cache_ttl_secs);
+    println!(
+        "alpha_vantage_key: {}",
+        if view.alpha_vantage_key_set {
+            "set"
+        } else {
+            "(not set)"
+        }
+    );
+    println!("secrets_backend: {}", view.secrets_backend.as_str());
+}
+
+/// Two decimals, four below one unit (penny stocks).
+fn price(value: f64) -> String {
+    if value.abs() < 1.0 {
+        format!("{value:.4}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+fn movement(text: String, change: f64) -> String {
+    if change > 0.0 {
+        style::good(text)
+    } else if change < 0.0 {
+        style::bad(text)
+    } else {
+        text
+    }
+}
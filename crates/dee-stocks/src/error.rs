@@ -0,0 +1,51 @@
+//! Every failure `dee-stocks` can report, each with a stable machine-readable code.
+
+use dee_core::cache::CacheError;
+use dee_core::{ConfigError, ErrorCode};
+
+#[derive(Debug, thiserror::Error)]
+pub enum StocksError {
+    #[error("Symbol not found: {0}")]
+    NotFound(String),
+    #[error("Invalid symbol (letters, digits, '.', '-', '^' or '='): {0}")]
+    InvalidSymbol(String),
+    #[error("Invalid --range '{0}' (a number with d, w, m or y, e.g. 6m, or ytd)")]
+    InvalidRange(String),
+    #[error("No symbols given and no watchlist in {0}")]
+    EmptyWatchlist(String),
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("Missing API key: set {0} in the config file")]
+    AuthMissing(String),
+    #[error("Rate limited by {0}; try again later")]
+    RateLimited(&'static str),
+    #[error("Request failed")]
+    RequestFailed,
+    #[error("Invalid API response")]
+    InvalidResponse,
+    #[error("Provider error: {0}")]
+    Api(String),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+}
+
+impl ErrorCode for StocksError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::InvalidSymbol(_)
+            | Self::InvalidRange(_)
+            | Self::EmptyWatchlist(_)
+            | Self::InvalidArgument(_) => "INVALID_ARGUMENT",
+            Self::AuthMissing(_) => "AUTH_MISSING",
+            Self::RateLimited(_) => "RATE_LIMITED",
+            Self::RequestFailed => "REQUEST_FAILED",
+            Self::InvalidResponse => "BAD_RESPONSE",
+            Self::Api(_) => "API_ERROR",
+            Self::Config(err) => err.code(),
+            Self::Cache(err) => err.code(),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+mod cli;
+mod config;
+mod display;
+mod error;
+mod models;
+mod providers;
+
+use chrono::Utc;
+use cli::{Cli, Commands, ConfigCommand};
+use dee_core::cache::Cache;
+use dee_core::http::Http;
+use dee_core::schema::{self, Schemas};
+use dee_core::{print_json, print_list, report, ErrorCode, Format, OkItem, OkMessage};
+use error::StocksError;
+use models::{Bar, Quote};
+use providers::{Fetcher, Provider};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+const CACHE_TOOL: &str = "dee-stocks";
+
+/// `config path`
+#[derive(Debug, Serialize, JsonSchema)]
+struct ConfigPath {
+    path: String,
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .item::<Quote>("quote")
+        .list::<Bar>("history")
+        .list::<Quote>("watchlist")
+        .message("config set")
+        .item::<config::ConfigView>("config show")
+        .item::<ConfigPath>("config path")
+        .cache()
+}
+
+fn main() {
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
+    let json = Format::json_errors(cli.global.output, cli.global.json);
+
+    // Config commands must work even when the saved provider cannot be built
+    if let Commands::Config { command } = &cli.command {
+        exit_on_error(config_command(command, json), json);
+        return;
+    }
+    if let Commands::Cache { command } = &cli.command {
+        if let Err(err) = dee_core::cache::run(&Cache::new(CACHE_TOOL), *command, cli.global.json) {
+            report(&err, json);
+            std::process::exit(err.exit_code());
+        }
+        return;
+    }
+
+    let result = config::load_config().and_then(|config| {
+        let kind = cli.global.provider.or(config.provider).unwrap_or_default();
+        let provider = providers::provider(kind, config.alpha_vantage_key.as_deref())?;
+        let http = Http::new(CACHE_TOOL, env!("CARGO_PKG_VERSION"))
+            .args(&cli.global.http)
+            .blocking()
+            .map_err(|_| StocksError::RequestFailed)?;
+        let fetcher = Fetcher::new(
+            http,
+            Cache::new(CACHE_TOOL).ttl_secs(config.cache_ttl_secs()),
+            cli.global.cache,
+        );
+        let out = Output {
+            json: cli.global.json,
+            list: Format::resolve(cli.global.output, cli.global.json),
+            quiet: cli.global.quiet,
+        };
+        run(cli.command, config, provider.as_ref(), &fetcher, &out)
+    });
+    exit_on_error(result, json);
+}
+
+fn exit_on_error(result: Result<(), StocksError>, json: bool) {
+    if let Err(err) = result {
+        report(&err, json);
+        std::process::exit(err.exit_code());
+    }
+}
+
+/// How results are printed: JSON or text.
+struct Output {
+    json: bool,
+    /// `--output`, or `json` under `--json`; lists only
+    list: Option<Format>,
+    quiet: bool,
+}
+
+impl Output {
+    fn item<T: Serialize>(&self, item: T, print: fn(&T, bool)) {
+        if self.json {
+            print_json(&OkItem { ok: true, item });
+        } else {
+            print(&item, self.quiet);
+        }
+    }
+
+    fn items<T: Serialize>(&self, items: Vec<T>, print: fn(&[T], bool)) {
+        if let Some(format) = self.list {
+            print_list(&items, format);
+        } else {
+            print(&items, self.quiet);
+        }
+    }
+}
+
+fn run(
+    command: Commands,
+    config: config::AppConfig,
+    provider: &dyn Provider,
+    fetcher: &Fetcher,
+    out: &Output,
+) -> Result<(), StocksError> {
+    match command {
+        Commands::Quote { symbol } => {
+            let symbol = cli::normalize_symbol(&symbol)?;
+            provider
+                .quote(fetcher, &symbol)
+                .map(|quote| out.item(quote, display::print_quote))
+        }
+        Commands::History { symbol, range } => {
+            let symbol = cli::normalize_symbol(&symbol)?;
+            let today = Utc::now().date_naive();
+            let from = cli::range_start(&range, today)?;
+            provider
+                .history(fetcher, &symbol, from, today)
+                .map(|bars| out.items(bars, display::print_history))
+        }
+        Commands::Watchlist { symbols } => {
+            // Symbols from the command line, else the configured watchlist
+            let symbols = if symbols.is_empty() {
+                config.watchlist
+            } else {
+                symbols
+            };
+            if symbols.is_empty() {
+                return Err(StocksError::EmptyWatchlist(
+                    config::config_path().display().to_string(),
+                ));
+            }
+            let quotes = symbols
+                .iter()
+                .map(|symbol| provider.quote(fetcher, &cli::normalize_symbol(symbol)?))
+                .collect::<Result<Vec<_>, _>>()?;
+            out.items(quotes, display::print_watchlist);
+            Ok(())
+        }
+        Commands::Config { .. } | Commands::Cache { .. } => {
+            unreachable!("handled before the provider is built")
+        }
+    }
+}
+
+fn config_command(command: &ConfigCommand, json: bool) -> Result<(), StocksError> {
+    match command {
+        ConfigCommand::Set { key, value } => {
+            let mut config = config::load_config_file()?;
+            config.set(key, value)?;
+            config::save_config(&config)?;
+            if json {
+                print_json(&OkMessage {
+                    ok: true,
+                    message: format!("{key} updated"),
+                });
+            } else {
+                println!("{key} updated");
+            }
+        }
+        ConfigCommand::Show => {
+            let view = config::load_config()?.view();
+            if json {
+                print_json(&OkItem {
+                    ok: true,
+                    item: view,
+                });
+            } else {
+                display::print_config(&view);
+            }
+        }
+        ConfigCommand::Path => {
+            let path = config::config_path().display().to_string();
+            if json {
+                print_json(&OkItem {
+                    ok: true,
+                    item: ConfigPath { path },
+                });
+            } else {
+                println!("{path}");
+            }
+        }
+    }
+    Ok(())
+}
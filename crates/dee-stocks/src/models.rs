@@ -0,0 +1,70 @@
+//! What each command prints, serialized as-is under `--json`.
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The latest daily price for one symbol. Prices are in the listing's currency.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Quote {
+    /// As given, uppercase, e.g. `AAPL`
+    pub symbol: String,
+    /// Trading day of the price, `YYYY-MM-DD`
+    pub date: String,
+    /// Last price (the close once the day is over)
+    pub price: f64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub volume: Option<u64>,
+    /// Close of the trading day before `date`
+    pub previous_close: Option<f64>,
+    /// `price - previous_close`
+    pub change: Option<f64>,
+    /// `change` as a percentage of `previous_close`
+    pub change_percent: Option<f64>,
+    /// Source of the price, e.g. `stooq`
+    pub provider: String,
+}
+
+/// One trading day of `history`, oldest first.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Bar {
+    pub symbol: String,
+    pub date: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Missing for indices
+    pub volume: Option<u64>,
+}
+
+impl Quote {
+    /// The last bar, with the change against the one before it.
+    pub fn from_bars(bars: &[Bar], provider: &str) -> Option<Self> {
+        let (last, earlier) = bars.split_last()?;
+        let previous_close = earlier.last().map(|bar| bar.close);
+        Some(Self::new(last, previous_close, provider))
+    }
+
+    pub fn new(bar: &Bar, previous_close: Option<f64>, provider: &str) -> Self {
+        let change = previous_close.map(|previous| bar.close - previous);
+        let change_percent = previous_close
+            .zip(change)
+            .filter(|(previous, _)| *previous != 0.0)
+            .map(|(previous, change)| change / previous * 100.0);
+        Self {
+            symbol: bar.symbol.clone(),
+            date: bar.date.clone(),
+            price: bar.close,
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            volume: bar.volume,
+            previous_close,
+            change,
+            change_percent,
+            provider: provider.to_string(),
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Price sources behind one trait. Symbols reach a provider uppercase (`AAPL`,
+//! `VOD.UK`, `^SPX`); each maps them to its own spelling.
+
+use crate::error::StocksError;
+use crate::models::{Bar, Quote};
+use chrono::{Duration, NaiveDate, Utc};
+use clap::ValueEnum;
+use dee_core::cache::{Cache, CacheArgs};
+use dee_core::ratelimit;
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const STOOQ_BASE: &str = "https://stooq.com";
+const ALPHA_VANTAGE_BASE: &str = "https://www.alphavantage.co";
+/// Days of history fetched for a quote: enough to span a long weekend and a holiday
+const QUOTE_LOOKBACK_DAYS: i64 = 14;
+/// Alpha Vantage's `compact` series holds the last 100 trading days, about 140
+/// calendar days; anything older needs `full`
+const COMPACT_DAYS: i64 = 140;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, ValueEnum,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProviderKind {
+    /// Stooq daily CSV (free, no key; US tickers get `.us` appended)
+    #[default]
+    Stooq,
+    /// Alpha Vantage (needs `alpha_vantage_key` in the config)
+    AlphaVantage,
+}
+
+impl ProviderKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Stooq => "stooq",
+            Self::AlphaVantage => "alpha-vantage",
+        }
+    }
+}
+
+pub trait Provider {
+    fn kind(&self) -> ProviderKind;
+    /// The latest price for `symbol`, with the change since the previous close.
+    fn quote(&self, fetcher: &Fetcher, symbol: &str) -> Result<Quote, StocksError>;
+    /// Daily bars for `symbol` from `from` through `to`, oldest first.
+    fn history(
+        &self,
+        fetcher: &Fetcher,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Bar>, StocksError>;
+}
+
+pub fn provider(
+    kind: ProviderKind,
+    alpha_vantage_key: Option<&str>,
+) -> Result<Box<dyn Provider>, StocksError> {
+    Ok(match kind {
+        ProviderKind::Stooq => Box::new(Stooq),
+        ProviderKind::AlphaVantage => {
+            let key = alpha_vantage_key
+                .filter(|k| !k.trim().is_empty())
+                .ok_or_else(|| StocksError::AuthMissing("alpha_vantage_key".to_string()))?;
+            Box::new(AlphaVantage {
+                api_key: key.trim().to_string(),
+            })
+        }
+    })
+}
+
+/// `STOCKS_TEST_BASE_URL` replaces every provider's host, for tests.
+fn base_url(default: &str) -> String {
+    std::env::var("STOCKS_TEST_BASE_URL").unwrap_or_else(|_| default.to_string())
+}
+
+/// HTTP with the cache, `--offline` and the rate limiter in front.
+pub struct Fetcher {
+    http: Client,
+    cache: Cache,
+    cache_args: CacheArgs,
+}
+
+impl Fetcher {
+    pub fn new(http: Client, cache: Cache, cache_args: CacheArgs) -> Self {
+        Self {
+            http,
+            cache,
+            cache_args,
+        }
+    }
+
+    /// The body at `url` run through `parse`; `key` is the same URL without
+    /// credentials, for the cache and debug output. Only bodies that parse are
+    /// cached, so a rate-limit notice is never served later as data.
+    fn get<T>(
+        &self,
+        provider: ProviderKind,
+        url: &str,
+        key: &str,
+        parse: impl Fn(&str) -> Result<T, StocksError>,
+    ) -> Result<T, StocksError> {
+        if let Some(hit) = self.cache.lookup::<String>(key, &self.cache_args)? {
+            tracing::debug!("cached {key} ({}s old)", hit.age_secs);
+            return parse(&hit.value);
+        }
+
+        let wait = ratelimit::reserve(url)?;
+        if !wait.is_zero() {
+            tracing::debug!(
+                "waiting {:.1}s before calling {}",
+                wait.as_secs_f64(),
+                provider.name()
+            );
+            std::thread::sleep(wait);
+        }
+        tracing::debug!("GET {key}");
+
+        let response = self.http.get(url).send().map_err(|err| {
+            tracing::debug!("request error from {key}: {err}");
+            StocksError::RequestFailed
+        })?;
+        match response.status() {
+            StatusCode::TOO_MANY_REQUESTS => return Err(StocksError::RateLimited(provider.name())),
+            status if !status.is_success() => {
+                return Err(StocksError::Api(format!("HTTP {}", status.as_u16())))
+            }
+            _ => {}
+        }
+        let body = response.text().map_err(|_| StocksError::RequestFailed)?;
+        let value = parse(&body)?;
+        self.cache.put(key, &body);
+        Ok(value)
+    }
+}
+
+pub struct Stooq;
+
+impl Stooq {
+    /// Stooq lists US shares as `aapl.us`; other markets and indices (`vod.uk`,
+    /// `^spx`) are spelled out already.
+    fn symbol(symbol: &str) -> String {
+        let symbol = symbol.to_lowercase();
+        if symbol.contains('.') || symbol.starts_with('^') {
+            symbol
+        } else {
+            format!("{symbol}.us")
+        }
+    }
+}
+
+impl Provider for Stooq {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Stooq
+    }
+
+    /// Stooq's quote endpoint has no previous close, so a quote is the tail of
+    /// the daily history; the current day's bar updates while the market is open.
+    fn quote(&self, fetcher: &Fetcher, symbol: &str) -> Result<Quote, StocksError> {
+        let today = Utc::now().date_naive();
+        let bars = self.history(
+            fetcher,
+            symbol,
+            today - Duration::days(QUOTE_LOOKBACK_DAYS),
+            today,
+        )?;
+        Quote::from_bars(&bars, self.kind().name())
+            .ok_or_else(|| StocksError::NotFound(symbol.to_string()))
+    }
+
+    fn history(
+        &self,
+        fetcher: &Fetcher,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Bar>, StocksError> {
+        let url = format!(
+            "{}/q/d/l/?s={}&i=d&d1={}&d2={}",
+            base_url(STOOQ_BASE),
+            Self::symbol(symbol),
+            from.format("%Y%m%d"),
+            to.format("%Y%m%d")
+        );
+        fetcher.get(self.kind(), &url, &url, |body| {
+            parse_stooq_csv(symbol, body)
+        })
+    }
+}
+
+/// `Date,Open,High,Low,Close,Volume` rows; indices have no `Volume` column.
+fn parse_stooq_csv(symbol: &str, body: &str) -> Result<Vec<Bar>, StocksError> {
+    let body = body.trim();
+    if body.starts_with("No data") {
+        return Err(StocksError::NotFound(symbol.to_string()));
+    }
+    if body.contains("Exceeded the daily hits limit") {
+        return Err(StocksError::RateLimited(ProviderKind::Stooq.name()));
+    }
+
+    let mut lines = body.lines();
+    let header: Vec<&str> = lines
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .collect();
+    let column = |name: &str| header.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let (Some(date), Some(open), Some(high), Some(low), Some(close)) = (
+        column("Date"),
+        column("Open"),
+        column("High"),
+        column("Low"),
+        column("Close"),
+    ) else {
+        return Err(StocksError::InvalidResponse);
+    };
+    let volume = column("Volume");
+
+    lines
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let number = |index: usize| -> Result<f64, StocksError> {
+                fields
+                    .get(index)
+                    .and_then(|field| field.parse().ok())
+                    .ok_or(StocksError::InvalidResponse)
+            };
+            Ok(Bar {
+                symbol: symbol.to_string(),
+                date: fields
+                    .get(date)
+                    .ok_or(StocksError::InvalidResponse)?
+                    .to_string(),
+                open: number(open)?,
+                high: number(high)?,
+                low: number(low)?,
+                close: number(close)?,
+                volume: volume
+                    .and_then(|index| number(index).ok())
+                    .map(|v| v as u64),
+            })
+        })
+        .collect()
+}
+
+pub struct AlphaVantage {
+    api_key: String,
+}
+
+impl AlphaVantage {
+    /// The request URL and the same URL without the key, for the cache.
+    fn urls(&self, query: &str) -> (String, String) {
+        let key = format!("{}/query?{query}", base_url(ALPHA_VANTAGE_BASE));
+        (format!("{key}&apikey={}", self.api_key), key)
+    }
+}
+
+impl Provider for AlphaVantage {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::AlphaVantage
+    }
+
+    fn quote(&self, fetcher: &Fetcher, symbol: &str) -> Result<Quote, StocksError> {
+        let (url, key) = self.urls(&format!("function=GLOBAL_QUOTE&symbol={symbol}"));
+        fetcher.get(self.kind(), &url, &key, |body| {
+            let root = alpha_vantage_json(symbol, body)?;
+            let quote = root
+                .get("Global Quote")
+                .and_then(|quote| quote.as_object())
+                .filter(|quote| !quote.is_empty())
+                .ok_or_else(|| StocksError::NotFound(symbol.to_string()))?;
+            let field = |name: &str| quote.get(name).and_then(|value| value.as_str());
+            let number = |name: &str| -> Result<f64, StocksError> {
+                field(name)
+                    .and_then(|value| value.parse().ok())
+                    .ok_or(StocksError::InvalidResponse)
+            };
+            let bar = Bar {
+                symbol: symbol.to_string(),
+                date: field("07. latest trading day")
+                    .ok_or(StocksError::InvalidResponse)?
+                    .to_string(),
+                open: number("02. open")?,
+                high: number("03. high")?,
+                low: number("04. low")?,
+                close: number("05. price")?,
+                volume: number("06. volume").ok().map(|v| v as u64),
+            };
+            Ok(Quote::new(
+                &bar,
+                number("08. previous close").ok(),
+                self.kind().name(),
+            ))
+        })
+    }
+
+    fn history(
+        &self,
+        fetcher: &Fetcher,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Bar>, StocksError> {
+        let size = if (Utc::now().date_naive() - from).num_days() > COMPACT_DAYS {
+            "full"
+        } else {
+            "compact"
+        };
+        let (url, key) = self.urls(&format!(
+            "function=TIME_SERIES_DAILY&symbol={symbol}&outputsize={size}"
+        ));
+        let bars = fetcher.get(self.kind(), &url, &key, |body| {
+            let root = alpha_vantage_json(symbol, body)?;
+            let series: BTreeMap<String, BTreeMap<String, String>> = root
+                .get("Time Series (Daily)")
+                .cloned()
+                .map(serde_json::from_value)
+                .ok_or_else(|| StocksError::NotFound(symbol.to_string()))?
+                .map_err(|_| StocksError::InvalidResponse)?;
+            // Dates sort as text, so the map is oldest first
+            series
+                .into_iter()
+                .map(|(date, day)| {
+                    let number = |name: &str| -> Result<f64, StocksError> {
+                        day.get(name)
+                            .and_then(|value| value.parse().ok())
+                            .ok_or(StocksError::InvalidResponse)
+                    };
+                    Ok(Bar {
+                        symbol: symbol.to_string(),
+                        open: number("1. open")?,
+                        high: number("2. high")?,
+                        low: number("3. low")?,
+                        close: number("4. close")?,
+                        volume: number("5. volume").ok().map(|v| v as u64),
+                        date,
+                    })
+                })
+                .collect::<Result<Vec<_>, StocksError>>()
+        })?;
+        let (from, to) = (from.to_string(), to.to_string());
+        Ok(bars
+            .into_iter()
+            .filter(|bar| bar.date >= from && bar.date <= to)
+            .collect())
+    }
+}
+
+/// Alpha Vantage answers 200 with a JSON note when something is wrong.
+fn alpha_vantage_json(symbol: &str, body: &str) -> Result<serde_json::Value, StocksError> {
+    let root: serde_json::Value =
+        serde_json::from_str(body).map_err(|_| StocksError::InvalidResponse)?;
+    let text = |name: &str| root.get(name).and_then(|value| value.as_str());
+    if text("Error Message").is_some() {
+        return Err(StocksError::NotFound(symbol.to_string()));
+    }
+    if text("Note").is_some() {
+        return Err(StocksError::RateLimited(ProviderKind::AlphaVantage.name()));
+    }
+    if let Some(info) = text("Information") {
+        return Err(if info.contains("rate limit") {
+            StocksError::RateLimited(ProviderKind::AlphaVantage.name())
+        } else {
+            StocksError::Api(info.to_string())
+        });
+    }
+    Ok(root)
+}
@@ -0,0 +1,63 @@
+use assert_cmd::Command;
+use std::path::Path;
+
+fn bin(home: &Path) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-stocks"));
+    cmd.env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env_remove("DEE_STOCKS_PROVIDER")
+        .env_remove("DEE_STOCKS_WATCHLIST")
+        .env_remove("DEE_STOCKS_ALPHA_VANTAGE_KEY");
+    cmd
+}
+
+fn json(cmd: &mut Command) -> (Option<i32>, serde_json::Value) {
+    let out = cmd.output().unwrap();
+    let parsed = serde_json::from_slice(&out.stdout).expect("valid json on stdout");
+    (out.status.code(), parsed)
+}
+
+#[test]
+fn config_set_validates_and_show_masks_the_key() {
+    let home = tempfile::tempdir().unwrap();
+
+    let (code, parsed) =
+        json(bin(home.path()).args(["config", "set", "provider", "yahoo", "--json"]));
+    assert_eq!(code, Some(2));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    let (_, parsed) =
+        json(bin(home.path()).args(["config", "set", "watchlist", "AAPL,BAD SYM", "--json"]));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    let (_, parsed) = json(bin(home.path()).args(["config", "set", "colour", "red", "--json"]));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    for (key, value) in [
+        ("provider", "alpha-vantage"),
+        ("watchlist", "nvda,brk-b"),
+        ("alpha_vantage_key", "av-key-123"),
+    ] {
+        bin(home.path())
+            .args(["config", "set", key, value])
+            .assert()
+            .success();
+    }
+    let (code, parsed) = json(bin(home.path()).args(["config", "show", "--json"]));
+    assert_eq!(code, Some(0));
+    let item = &parsed["item"];
+    assert_eq!(item["provider"], "alpha-vantage");
+    assert_eq!(item["watchlist"], serde_json::json!(["NVDA", "BRK-B"]));
+    assert_eq!(item["cache_ttl_secs"], 900);
+    assert_eq!(item["alpha_vantage_key_set"], true);
+    assert!(!parsed.to_string().contains("av-key-123"));
+}
+
+#[test]
+fn bad_symbols_are_rejected_before_any_request() {
+    let home = tempfile::tempdir().unwrap();
+    let (code, parsed) = json(bin(home.path()).args(["quote", "AA/PL", "--json"]));
+    assert_eq!(code, Some(2));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
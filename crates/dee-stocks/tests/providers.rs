@@ -0,0 +1,182 @@
+use assert_cmd::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+
+fn bin(home: &Path, url: &str) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-stocks"));
+    cmd.env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("DEE_RATELIMIT_DIR", home.join("ratelimit"))
+        .env("STOCKS_TEST_BASE_URL", url)
+        .env_remove("DEE_STOCKS_PROVIDER")
+        .env_remove("DEE_STOCKS_WATCHLIST")
+        .env_remove("DEE_STOCKS_ALPHA_VANTAGE_KEY");
+    cmd
+}
+
+/// Serve canned Stooq CSV for `aapl.us` and `msft.us` and Alpha Vantage JSON for
+/// `IBM`; any other symbol gets Stooq's `No data` and `LIMIT` a rate-limit note.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let path = line.split_whitespace().nth(1).unwrap_or("").to_string();
+            let body = if path.starts_with("/q/d/l/?s=aapl.us&") {
+                "Date,Open,High,Low,Close,Volume\n2026-10-14,240.1,243.0,239.5,242.0,51000000\n2026-10-15,242.5,246.2,241.8,245.63,48210000\n"
+            } else if path.starts_with("/q/d/l/?s=msft.us&") {
+                "Date,Open,High,Low,Close,Volume\n2026-10-14,512.0,515.0,505.0,510.0,20000000\n2026-10-15,509.0,510.0,498.1,500.0,25000000\n"
+            } else if path.starts_with("/q/d/l/") {
+                "No data"
+            } else if path.contains("function=GLOBAL_QUOTE&symbol=IBM&apikey=demo") {
+                r#"{"Global Quote":{"01. symbol":"IBM","02. open":"250.00","03. high":"254.10","04. low":"249.20","05. price":"253.00","06. volume":"3100000","07. latest trading day":"2026-10-15","08. previous close":"250.00","09. change":"3.00","10. change percent":"1.2000%"}}"#
+            } else if path.contains("symbol=LIMIT") {
+                r#"{"Note":"Thank you for using Alpha Vantage! Our standard API call frequency is 5 calls per minute."}"#
+            } else {
+                r#"{"Global Quote":{}}"#
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}")
+}
+
+fn json(cmd: &mut Command) -> (Option<i32>, serde_json::Value) {
+    let out = cmd.output().unwrap();
+    let parsed = serde_json::from_slice(&out.stdout).expect("valid json on stdout");
+    (out.status.code(), parsed)
+}
+
+#[test]
+fn stooq_quote_compares_with_the_previous_close() {
+    let url = serve();
+    let home = tempfile::tempdir().unwrap();
+    let (code, parsed) = json(bin(home.path(), &url).args(["quote", "aapl", "--json"]));
+
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["ok"], true);
+    let item = &parsed["item"];
+    assert_eq!(item["symbol"], "AAPL");
+    assert_eq!(item["date"], "2026-10-15");
+    assert_eq!(item["price"], 245.63);
+    assert_eq!(item["previous_close"], 242.0);
+    assert_eq!(item["volume"], 48_210_000);
+    assert_eq!(item["provider"], "stooq");
+    let change = item["change_percent"].as_f64().unwrap();
+    assert!((change - 1.5).abs() < 0.01, "{change}");
+}
+
+#[test]
+fn history_lists_bars_oldest_first() {
+    let url = serve();
+    let home = tempfile::tempdir().unwrap();
+    let (code, parsed) =
+        json(bin(home.path(), &url).args(["history", "AAPL", "--range", "6m", "--json"]));
+
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][0]["date"], "2026-10-14");
+    assert_eq!(parsed["items"][1]["close"], 245.63);
+
+    let (code, parsed) =
+        json(bin(home.path(), &url).args(["history", "AAPL", "--range", "6q", "--json"]));
+    assert_eq!(code, Some(2));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn watchlist_quotes_the_configured_symbols() {
+    let url = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    let (code, parsed) = json(bin(home.path(), &url).args(["watchlist", "--json"]));
+    assert_eq!(code, Some(2));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    bin(home.path(), &url)
+        .args(["config", "set", "watchlist", "aapl, msft"])
+        .assert()
+        .success();
+    let (code, parsed) = json(bin(home.path(), &url).args(["watchlist", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][0]["symbol"], "AAPL");
+    assert_eq!(parsed["items"][1]["symbol"], "MSFT");
+    assert_eq!(parsed["items"][1]["change"], -10.0);
+
+    // Symbols on the command line replace the watchlist
+    let (_, parsed) = json(bin(home.path(), &url).args(["watchlist", "MSFT", "--json"]));
+    assert_eq!(parsed["count"], 1);
+}
+
+#[test]
+fn unknown_symbol_is_not_found() {
+    let url = serve();
+    let home = tempfile::tempdir().unwrap();
+    let (code, parsed) = json(bin(home.path(), &url).args(["quote", "NOPE", "--json"]));
+
+    assert_eq!(code, Some(3));
+    assert_eq!(parsed["code"], "NOT_FOUND");
+}
+
+#[test]
+fn alpha_vantage_needs_a_key() {
+    let url = serve();
+    let home = tempfile::tempdir().unwrap();
+    let (code, parsed) = json(bin(home.path(), &url).args([
+        "quote",
+        "IBM",
+        "--provider",
+        "alpha-vantage",
+        "--json",
+    ]));
+    assert_eq!(code, Some(4));
+    assert_eq!(parsed["code"], "AUTH_MISSING");
+
+    let (code, parsed) = json(
+        bin(home.path(), &url)
+            .env("DEE_STOCKS_ALPHA_VANTAGE_KEY", "demo")
+            .args(["quote", "IBM", "--provider", "alpha-vantage", "--json"]),
+    );
+    assert_eq!(code, Some(0));
+    let item = &parsed["item"];
+    assert_eq!(item["price"], 253.0);
+    assert_eq!(item["change"], 3.0);
+    assert_eq!(item["change_percent"], 1.2);
+    assert_eq!(item["provider"], "alpha-vantage");
+
+    let (_, parsed) = json(
+        bin(home.path(), &url)
+            .env("DEE_STOCKS_ALPHA_VANTAGE_KEY", "demo")
+            .args(["quote", "LIMIT", "--provider", "alpha-vantage", "--json"]),
+    );
+    assert_eq!(parsed["code"], "RATE_LIMITED");
+}
+
+#[test]
+fn offline_serves_the_cached_quote() {
+    let url = serve();
+    let home = tempfile::tempdir().unwrap();
+
+    let (_, parsed) = json(bin(home.path(), &url).args(["quote", "AAPL", "--offline", "--json"]));
+    assert_eq!(parsed["code"], "OFFLINE_MISS");
+
+    bin(home.path(), &url)
+        .args(["quote", "AAPL", "--json"])
+        .assert()
+        .success();
+    let (code, parsed) =
+        json(bin(home.path(), &url).args(["quote", "AAPL", "--offline", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["stale"], true);
+    assert_eq!(parsed["item"]["price"], 245.63);
+}
@@ -0,0 +1,170 @@
+//! What the tool crates' integration tests share: a mock API server, the
+//! canned API answers in `fixtures/`, and a tool command that cannot touch the
+//! real home directory.
+//!
+//! ```no_run
+//! use dee_test_support::{fixture, json, MockApi, Sandbox};
+//!
+//! # let dee_hn = "target/debug/dee-hn";
+//! // in dee-hn's tests: `let dee_hn = assert_cmd::cargo::cargo_bin!("dee-hn");`
+//! let api = MockApi::start();
+//! api.get_json("/v0/item/8863.json", &fixture("hn/item_8863.json"));
+//! let sandbox = Sandbox::new();
+//! let (code, parsed) = json(
+//!     sandbox
+//!         .command(dee_hn)
+//!         .env("DEE_HN_BASE_URL", api.url("/v0"))
+//!         .args(["item", "8863", "--json"]),
+//! );
+//! assert_eq!(code, Some(0));
+//! assert_eq!(parsed["item"]["id"], 8863);
+//! ```
+//!
+//! Tools are pointed at the server through their `*_base_url` config keys,
+//! `DEE_<TOOL>_<API>_BASE_URL` in the env.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+use dee_core::cache::Cache;
+use tempfile::TempDir;
+use tokio::runtime::Runtime;
+use wiremock::matchers::{method, path};
+use wiremock::MockServer;
+
+pub use wiremock::{matchers, Mock, Request, ResponseTemplate};
+
+/// A local HTTP server answering with mounted [`Mock`]s; anything else is a
+/// 404. It stops when dropped.
+pub struct MockApi {
+    runtime: Runtime,
+    server: MockServer,
+}
+
+impl MockApi {
+    pub fn start() -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+        let server = runtime.block_on(MockServer::start());
+        Self { runtime, server }
+    }
+
+    /// `http://127.0.0.1:<port>`
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    /// The server address followed by `path`, e.g. `url("/v3")` for a base URL.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.uri())
+    }
+
+    /// Answer with `mock` from now on. Earlier mounts win when two match.
+    pub fn mount(&self, mock: Mock) -> &Self {
+        self.runtime.block_on(mock.mount(&self.server));
+        self
+    }
+
+    /// Answer `GET <route>` with `body` as JSON; the query is not matched.
+    pub fn get_json(&self, route: &str, body: &str) -> &Self {
+        self.mount(
+            Mock::given(method("GET"))
+                .and(path(route))
+                .respond_with(json_body(200, body)),
+        )
+    }
+
+    /// Answer `POST <route>` with `body` as JSON.
+    pub fn post_json(&self, route: &str, body: &str) -> &Self {
+        self.mount(
+            Mock::given(method("POST"))
+                .and(path(route))
+                .respond_with(json_body(200, body)),
+        )
+    }
+
+    /// Every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<Request> {
+        self.runtime
+            .block_on(self.server.received_requests())
+            .unwrap_or_default()
+    }
+}
+
+/// A `status` answer with `body` as `application/json`.
+pub fn json_body(status: u16, body: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_raw(body.to_string(), "application/json")
+}
+
+/// The text of `fixtures/<name>`, e.g. `fixture("hn/item_8863.json")`.
+pub fn fixture(name: &str) -> String {
+    let file = fixtures_dir().join(name);
+    std::fs::read_to_string(&file).unwrap_or_else(|err| panic!("fixture {}: {err}", file.display()))
+}
+
+pub fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures")
+}
+
+/// A temporary home for one test. Config, cache, data and rate-limit state
+/// all live under it; it is removed when dropped.
+pub struct Sandbox {
+    home: TempDir,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sandbox {
+    pub fn new() -> Self {
+        Self {
+            home: tempfile::tempdir().expect("temp dir"),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.home.path()
+    }
+
+    /// Run the binary at `program` (`assert_cmd::cargo::cargo_bin!("dee-x")`)
+    /// in this sandbox, clear of the caller's profile and log settings.
+    pub fn command(&self, program: impl AsRef<OsStr>) -> Command {
+        let home = self.path();
+        let mut cmd = Command::new(program);
+        cmd.env("HOME", home)
+            .env("XDG_CONFIG_HOME", home.join("config"))
+            .env("XDG_CACHE_HOME", home.join("cache"))
+            .env("XDG_DATA_HOME", home.join("data"))
+            .env("DEE_RATELIMIT_DIR", home.join("ratelimit"))
+            .env_remove("DEE_PROFILE")
+            .env_remove("DEE_LOG_LEVEL")
+            .env_remove("DEE_LOG_FORMAT");
+        cmd
+    }
+}
+
+/// Cache `body` under `key` for `tool` in the cache dir `cache_home`
+/// (`XDG_CACHE_HOME`), as fetched at the Unix epoch: past any TTL, so only
+/// `--offline` serves it.
+pub fn seed_cache(cache_home: &Path, tool: &str, key: &str, body: &serde_json::Value) {
+    Cache::in_dir(cache_home.join(tool)).put_at(key, body, 0);
+}
+
+/// Run `cmd`; its exit code and stdout as JSON.
+pub fn json(cmd: &mut Command) -> (Option<i32>, serde_json::Value) {
+    let out = cmd.output().expect("run the tool");
+    let parsed = serde_json::from_slice(&out.stdout).unwrap_or_else(|err| {
+        panic!(
+            "stdout is not JSON ({err}): {}\nstderr: {}",
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        )
+    });
+    (out.status.code(), parsed)
+}
@@ -4,7 +4,10 @@ use std::path::PathBuf;
 
 use chrono::{DateTime, Duration, SecondsFormat, Utc};
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::schema::{self, Schemas};
+use dee_core::{print_json, print_list, report, ErrorCode, Format, OkItem};
 use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use schemars::JsonSchema;
 use serde::Serialize;
 
 #[derive(Debug, Parser)]
@@ -36,6 +39,10 @@ struct GlobalFlags {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -119,7 +126,7 @@ struct ReportArgs {
     project: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 struct SessionItem {
     id: i64,
     task: String,
@@ -137,27 +144,14 @@ struct SessionItem {
     pomodoro: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ReportGroup {
     project: String,
     total_sec: i64,
     session_count: usize,
 }
 
-#[derive(Debug, Serialize)]
-struct ListResponse<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct ItemResponse<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ActionResponse {
     ok: bool,
     message: String,
@@ -165,14 +159,7 @@ struct ActionResponse {
     id: Option<i64>,
 }
 
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct StatusItem {
     active: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -199,7 +186,7 @@ enum AppError {
     Database,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
             Self::DataDirMissing => "CONFIG_MISSING",
@@ -214,20 +201,28 @@ impl AppError {
 
 type AppResult<T> = Result<T, AppError>;
 
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .custom::<ActionResponse>("start")
+        .custom::<ActionResponse>("stop")
+        .item::<StatusItem>("status")
+        .item::<SessionItem>("show")
+        .list::<SessionItem>("list")
+        .list::<ReportGroup>("report")
+        .custom::<ActionResponse>("delete")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = run(&cli) {
-        if cli.global.json {
-            print_json(&ErrorResponse {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -239,9 +234,7 @@ fn run(cli: &Cli) -> AppResult<()> {
         fs::create_dir_all(parent).map_err(|_| AppError::Database)?;
     }
 
-    if cli.global.verbose {
-        eprintln!("[dee-timer] db_path={}", db_path.display());
-    }
+    tracing::debug!("db_path={}", db_path.display());
 
     let conn = Connection::open(db_path).map_err(|_| AppError::Database)?;
     initialize_db(&conn)?;
@@ -394,7 +387,7 @@ fn cmd_status(conn: &Connection, global: &GlobalFlags) -> AppResult<()> {
     };
 
     if global.json {
-        print_json(&ItemResponse {
+        print_json(&OkItem {
             ok: true,
             item: status,
         });
@@ -422,7 +415,7 @@ fn cmd_show(conn: &Connection, id: i64, global: &GlobalFlags) -> AppResult<()> {
     let item = get_session_by_id(conn, id)?.ok_or(AppError::NotFound)?;
 
     if global.json {
-        print_json(&ItemResponse { ok: true, item });
+        print_json(&OkItem { ok: true, item });
         return Ok(());
     }
 
@@ -488,12 +481,8 @@ fn cmd_report(conn: &Connection, args: &ReportArgs, global: &GlobalFlags) -> App
         })
         .collect();
 
-    if global.json {
-        print_json(&ListResponse {
-            ok: true,
-            count: groups.len(),
-            items: groups,
-        });
+    if let Some(format) = Format::resolve(global.output, global.json) {
+        print_list(&groups, format);
         return Ok(());
     }
 
@@ -550,12 +539,8 @@ fn print_action_result(message: &str, id: i64, global: &GlobalFlags) -> AppResul
 }
 
 fn print_session_list(items: Vec<SessionItem>, global: &GlobalFlags) -> AppResult<()> {
-    if global.json {
-        print_json(&ListResponse {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(global.output, global.json) {
+        print_list(&items, format);
         return Ok(());
     }
 
@@ -711,45 +696,3 @@ fn parse_tags(input: Option<&str>) -> Vec<String> {
 fn now_iso() -> String {
     Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
 }
-
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(text) => println!("{text}"),
-        Err(_) => {
-            println!(r#"{{"ok":false,"error":"JSON serialization failed","code":"SERIALIZE"}}"#);
-            std::process::exit(1);
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
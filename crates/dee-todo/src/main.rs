@@ -3,7 +3,10 @@ use std::path::PathBuf;
 
 use chrono::{SecondsFormat, Utc};
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::schema::{self, Schemas};
+use dee_core::{print_json, print_list, report, ErrorCode, Format, OkItem};
 use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use schemars::JsonSchema;
 use serde::Serialize;
 
 #[derive(Debug, Parser)]
@@ -35,6 +38,10 @@ struct GlobalFlags {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -144,20 +151,7 @@ struct EditArgs {
     tags: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct ListResponse<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct ItemResponse<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ActionResponse {
     ok: bool,
     message: String,
@@ -165,14 +159,7 @@ struct ActionResponse {
     id: Option<i64>,
 }
 
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 struct TodoItem {
     id: i64,
     title: String,
@@ -205,7 +192,7 @@ enum AppError {
     Serialize,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
             Self::DataDirMissing => "CONFIG_MISSING",
@@ -219,22 +206,32 @@ impl AppError {
 
 type AppResult<T> = Result<T, AppError>;
 
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .custom::<ActionResponse>("add")
+        .list::<TodoItem>("list")
+        .list::<TodoItem>("project")
+        .list::<TodoItem>("search")
+        .custom::<ActionResponse>("done")
+        .custom::<ActionResponse>("undone")
+        .item::<TodoItem>("show")
+        .custom::<ActionResponse>("edit")
+        .custom::<ActionResponse>("delete")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     let result = run(&cli);
 
     if let Err(err) = result {
-        if cli.global.json {
-            print_json(&ErrorResponse {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -246,9 +243,7 @@ fn run(cli: &Cli) -> AppResult<()> {
         fs::create_dir_all(parent).map_err(|_| AppError::Database)?;
     }
 
-    if cli.global.verbose {
-        eprintln!("[dee-todo] db_path={}", db_path.display());
-    }
+    tracing::debug!("db_path={}", db_path.display());
 
     let conn = Connection::open(db_path).map_err(|_| AppError::Database)?;
     initialize_db(&conn)?;
@@ -489,7 +484,7 @@ fn cmd_show(conn: &Connection, id: i64, global: &GlobalFlags) -> AppResult<()> {
     let item = get_todo_by_id(conn, id)?.ok_or(AppError::NotFound)?;
 
     if global.json {
-        print_json(&ItemResponse { ok: true, item });
+        print_json(&OkItem { ok: true, item });
         return Ok(());
     }
 
@@ -543,12 +538,8 @@ fn cmd_edit(conn: &Connection, args: &EditArgs, global: &GlobalFlags) -> AppResu
 }
 
 fn print_list_result(items: Vec<TodoItem>, global: &GlobalFlags) -> AppResult<()> {
-    if global.json {
-        print_json(&ListResponse {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(global.output, global.json) {
+        print_list(&items, format);
         return Ok(());
     }
 
@@ -693,45 +684,3 @@ fn parse_tags(input: Option<&str>) -> Vec<String> {
         .map(ToOwned::to_owned)
         .collect()
 }
-
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(text) => println!("{text}"),
-        Err(_) => {
-            println!(r#"{{"ok":false,"error":"JSON serialization failed","code":"SERIALIZE"}}"#);
-            std::process::exit(1);
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -36,6 +36,37 @@ fn add_and_list_json_contract() {
         .stdout(predicate::str::contains("Write tests"));
 }
 
+#[test]
+fn list_output_formats() {
+    let home = TempDir::new().expect("temp dir");
+
+    let mut add = cmd();
+    with_temp_home(&mut add, &home);
+    add.args(["add", "Ship, then rest", "--quiet"])
+        .assert()
+        .success();
+
+    let mut csv = cmd();
+    with_temp_home(&mut csv, &home);
+    csv.args(["list", "--output", "csv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("id,"))
+        .stdout(predicate::str::contains("\"Ship, then rest\""));
+
+    let mut jsonl = cmd();
+    with_temp_home(&mut jsonl, &home);
+    jsonl
+        .args(["list", "--output", "jsonl"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("{\"id\":1,"));
+
+    let mut bad = cmd();
+    with_temp_home(&mut bad, &home);
+    bad.args(["list", "--output", "xml"]).assert().code(2);
+}
+
 #[test]
 fn json_error_for_missing_todo_uses_stdout() {
     let home = TempDir::new().expect("temp dir");
@@ -1,6 +1,10 @@
 use clap::{Args, Parser, Subcommand};
+use dee_core::http::{Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{print_list, report, ErrorCode, Format};
 use reqwest::blocking::Client;
 use reqwest::StatusCode;
+use schemars::JsonSchema;
 use serde::Serialize;
 use serde_json::{json, Value};
 
@@ -35,6 +39,13 @@ struct GlobalFlags {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
+
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -69,28 +80,14 @@ struct QueryArgs {
     tz: i32,
 }
 
-#[derive(Debug, Serialize)]
-struct ListResponse<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct InterestPoint {
     timestamp: String,
     formatted_time: String,
     value: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct RelatedQuery {
     query: String,
     query_type: String,
@@ -100,7 +97,7 @@ struct RelatedQuery {
     formatted_value: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct WidgetSummary {
     id: String,
     title: String,
@@ -121,7 +118,7 @@ enum AppError {
     NotFound,
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
             Self::InvalidArgument(_) => "INVALID_ARGUMENT",
@@ -133,21 +130,25 @@ impl AppError {
     }
 }
 
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<InterestPoint>("interest")
+        .list::<RelatedQuery>("related")
+        .list::<WidgetSummary>("explore")
+}
+
 fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
     let result = run(&cli);
 
     if let Err(err) = result {
-        if cli.global.json {
-            print_json(&ErrorResponse {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
@@ -158,12 +159,12 @@ fn run(cli: &Cli) -> Result<(), AppError> {
 
     validate_args(args)?;
 
-    let client = Client::builder()
-        .timeout(std::time::Duration::from_secs(20))
-        .build()
+    let client = Http::new("dee-trends", env!("CARGO_PKG_VERSION"))
+        .args(&cli.global.http)
+        .blocking()
         .map_err(|_| AppError::RequestFailed)?;
 
-    let api = TrendsApi::new(client, base_url(), cli.global.verbose);
+    let api = TrendsApi::new(client, base_url());
 
     match &cli.command {
         Commands::Explore(a) => {
@@ -177,12 +178,8 @@ fn run(cli: &Cli) -> Result<(), AppError> {
                 })
                 .collect::<Vec<_>>();
 
-            if cli.global.json {
-                print_json(&ListResponse {
-                    ok: true,
-                    count: items.len(),
-                    items,
-                });
+            if let Some(format) = Format::resolve(cli.global.output, cli.global.json) {
+                print_list(&items, format);
             } else if cli.global.quiet {
                 println!("{}", items.len());
             } else {
@@ -193,12 +190,8 @@ fn run(cli: &Cli) -> Result<(), AppError> {
         }
         Commands::Interest(a) => {
             let points = api.interest_points(a)?;
-            if cli.global.json {
-                print_json(&ListResponse {
-                    ok: true,
-                    count: points.len(),
-                    items: points,
-                });
+            if let Some(format) = Format::resolve(cli.global.output, cli.global.json) {
+                print_list(&points, format);
             } else if cli.global.quiet {
                 println!("{}", points.len());
             } else if points.is_empty() {
@@ -211,12 +204,8 @@ fn run(cli: &Cli) -> Result<(), AppError> {
         }
         Commands::Related(a) => {
             let items = api.related_queries(a)?;
-            if cli.global.json {
-                print_json(&ListResponse {
-                    ok: true,
-                    count: items.len(),
-                    items,
-                });
+            if let Some(format) = Format::resolve(cli.global.output, cli.global.json) {
+                print_list(&items, format);
             } else if cli.global.quiet {
                 println!("{}", items.len());
             } else if items.is_empty() {
@@ -264,16 +253,11 @@ struct Widget {
 struct TrendsApi {
     client: Client,
     base_url: String,
-    verbose: bool,
 }
 
 impl TrendsApi {
-    fn new(client: Client, base_url: String, verbose: bool) -> Self {
-        Self {
-            client,
-            base_url,
-            verbose,
-        }
+    fn new(client: Client, base_url: String) -> Self {
+        Self { client, base_url }
     }
 
     fn explore(&self, args: &QueryArgs) -> Result<Vec<Widget>, AppError> {
@@ -473,9 +457,7 @@ impl TrendsApi {
     }
 
     fn get_text(&self, url: &str) -> Result<String, AppError> {
-        if self.verbose {
-            eprintln!("[dee-trends] GET {url}");
-        }
+        tracing::debug!("GET {url}");
 
         let response = self
             .client
@@ -501,47 +483,3 @@ fn strip_xssi_prefix(input: &str) -> &str {
         .trim_start_matches('\n')
         .trim_start()
 }
-
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string(value) {
-        Ok(text) => println!("{text}"),
-        Err(_) => {
-            println!(
-                "{{\"ok\":false,\"error\":\"JSON serialization failed\",\"code\":\"SERIALIZE\"}}"
-            );
-            std::process::exit(1);
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
-    }
-}
@@ -0,0 +1,925 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::cache::{Cache, CacheArgs, CacheCommand, CacheError};
+use dee_core::http::{Http, HttpArgs};
+use dee_core::ratelimit;
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, report, Config, ConfigError, ErrorCode, Format, OkItem, OkMessage,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const GEOCODE_BASE: &str = "https://geocoding-api.open-meteo.com/v1/search";
+const FORECAST_BASE: &str = "https://api.open-meteo.com/v1/forecast";
+const ALERTS_BASE: &str = "https://api.weather.gov/alerts/active";
+/// Open-Meteo refreshes its models every 15 minutes at best
+const WEATHER_TTL_SECS: u64 = 15 * 60;
+const ALERTS_TTL_SECS: u64 = 5 * 60;
+/// Places do not move
+const GEOCODE_TTL_SECS: u64 = 30 * 24 * 60 * 60;
+const MAX_FORECAST_DAYS: u32 = 16;
+const MAX_GEOCODE_RESULTS: u32 = 100;
+/// Country codes NWS issues alerts for: the states and the territories
+const NWS_COUNTRIES: &[&str] = &["US", "PR", "VI", "GU", "AS", "MP"];
+
+const CURRENT_FIELDS: &str = "temperature_2m,apparent_temperature,relative_humidity_2m,precipitation,weather_code,wind_speed_10m,wind_direction_10m,is_day";
+const DAILY_FIELDS: &str = "weather_code,temperature_2m_max,temperature_2m_min,precipitation_sum,precipitation_probability_max,wind_speed_10m_max,sunrise,sunset";
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "dee-weather",
+    version,
+    about = "Current weather, forecasts and alerts",
+    after_help = "EXAMPLES:\n  dee-weather current Berlin --json\n  dee-weather current 40.71,-74.01 --units imperial\n  dee-weather forecast \"Portland, Oregon\" --days 3 --json\n  dee-weather alerts Miami --json\n  dee-weather geocode Springfield --limit 5 --output table\n  dee-weather config set location \"Oslo\"\n  dee-weather cache clear"
+)]
+struct Cli {
+    #[command(flatten)]
+    global: GlobalArgs,
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, Clone, Args)]
+struct GlobalArgs {
+    #[arg(short = 'j', long, global = true)]
+    json: bool,
+    #[arg(short = 'q', long, global = true)]
+    quiet: bool,
+    #[arg(short = 'v', long, global = true)]
+    verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    /// metric (°C, km/h, mm) or imperial (°F, mph, in); defaults to config `units`
+    #[arg(long, global = true, value_enum)]
+    units: Option<Units>,
+    #[command(flatten)]
+    http: HttpArgs,
+    #[command(flatten)]
+    cache: CacheArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// Conditions right now
+    Current(LocationArgs),
+    /// Daily forecast
+    Forecast(ForecastArgs),
+    /// Active weather alerts from the US National Weather Service
+    Alerts(LocationArgs),
+    /// Look up places by name
+    Geocode(GeocodeArgs),
+    Config(ConfigArgs),
+    /// Manage cached Open-Meteo and NWS responses
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+enum Units {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+#[derive(Debug, Args)]
+struct LocationArgs {
+    /// Place name ("Berlin", "Portland, Oregon") or "lat,lon"; defaults to config `location`
+    location: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct ForecastArgs {
+    /// Place name ("Berlin", "Portland, Oregon") or "lat,lon"; defaults to config `location`
+    location: Option<String>,
+    /// Days to forecast, today included (1-16)
+    #[arg(long, default_value_t = 5)]
+    days: u32,
+}
+
+#[derive(Debug, Args)]
+struct GeocodeArgs {
+    /// Place name; a qualifier after a comma narrows by country, country code or region
+    name: String,
+    #[arg(long, default_value_t = 5)]
+    limit: u32,
+}
+
+#[derive(Debug, Args)]
+struct ConfigArgs {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    Set(ConfigSetArgs),
+    Show(ShowFlags),
+    Path,
+}
+
+#[derive(Debug, Args)]
+struct ConfigSetArgs {
+    key: String,
+    value: String,
+    #[command(flatten)]
+    output: ShowFlags,
+}
+
+#[derive(Debug, Args)]
+struct ShowFlags {
+    #[arg(short = 'j', long)]
+    json: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
+struct AppConfig {
+    /// `metric` (default) or `imperial`
+    #[serde(default)]
+    units: Option<Units>,
+    /// Used when a command is given no location
+    #[serde(default)]
+    location: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+struct Place {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    country: Option<String>,
+    country_code: Option<String>,
+    /// State, province or region
+    admin1: Option<String>,
+    timezone: Option<String>,
+    population: Option<u64>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct CurrentWeather {
+    location: String,
+    latitude: f64,
+    longitude: f64,
+    timezone: Option<String>,
+    /// Local time of the observation
+    time: String,
+    conditions: String,
+    /// WMO weather interpretation code
+    weather_code: Option<u8>,
+    temperature: Option<f64>,
+    feels_like: Option<f64>,
+    /// Relative humidity, percent
+    humidity: Option<f64>,
+    precipitation: Option<f64>,
+    wind_speed: Option<f64>,
+    /// Degrees, the direction the wind comes from
+    wind_direction: Option<f64>,
+    is_day: bool,
+    units: Units,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct DayForecast {
+    location: String,
+    date: String,
+    conditions: String,
+    /// WMO weather interpretation code
+    weather_code: Option<u8>,
+    temp_max: Option<f64>,
+    temp_min: Option<f64>,
+    precipitation: Option<f64>,
+    /// Highest chance of precipitation in the day, percent
+    precipitation_probability: Option<f64>,
+    wind_speed_max: Option<f64>,
+    sunrise: Option<String>,
+    sunset: Option<String>,
+    units: Units,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct Alert {
+    id: String,
+    event: String,
+    headline: Option<String>,
+    /// Extreme, Severe, Moderate, Minor or Unknown
+    severity: Option<String>,
+    urgency: Option<String>,
+    certainty: Option<String>,
+    area: Option<String>,
+    sender: Option<String>,
+    effective: Option<String>,
+    expires: Option<String>,
+    description: Option<String>,
+    instruction: Option<String>,
+}
+
+/// `DEE_WEATHER_UNITS` and `DEE_WEATHER_LOCATION` override the file.
+fn config_file() -> Config<AppConfig> {
+    Config::new("dee-weather").env_overrides(&["units", "location"])
+}
+
+#[derive(Debug, thiserror::Error)]
+enum AppError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+    #[error("Unknown config key: {0}")]
+    InvalidConfigKey(String),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("No place found for {0}")]
+    NotFound(String),
+    #[error("HTTP request failed")]
+    RequestFailed,
+    #[error("Weather API returned an error: {0}")]
+    ApiError(String),
+    #[error("Response parse failed")]
+    ParseFailed,
+}
+
+impl ErrorCode for AppError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Config(err) => err.code(),
+            Self::Cache(err) => err.code(),
+            Self::InvalidConfigKey(_) | Self::InvalidArgument(_) => "INVALID_ARGUMENT",
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::RequestFailed => "REQUEST_FAILED",
+            Self::ApiError(_) => "API_ERROR",
+            Self::ParseFailed => "PARSE_FAILED",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GeoRoot {
+    #[serde(default)]
+    results: Vec<GeoResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeoResult {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    #[serde(default)]
+    country: Option<String>,
+    #[serde(default)]
+    country_code: Option<String>,
+    #[serde(default)]
+    admin1: Option<String>,
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    population: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastRoot {
+    #[serde(default)]
+    timezone: Option<String>,
+    #[serde(default)]
+    current: Option<RawCurrent>,
+    #[serde(default)]
+    daily: Option<RawDaily>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawCurrent {
+    time: String,
+    #[serde(default)]
+    temperature_2m: Option<f64>,
+    #[serde(default)]
+    apparent_temperature: Option<f64>,
+    #[serde(default)]
+    relative_humidity_2m: Option<f64>,
+    #[serde(default)]
+    precipitation: Option<f64>,
+    #[serde(default)]
+    weather_code: Option<u8>,
+    #[serde(default)]
+    wind_speed_10m: Option<f64>,
+    #[serde(default)]
+    wind_direction_10m: Option<f64>,
+    #[serde(default)]
+    is_day: Option<u8>,
+}
+
+/// One array per field, one entry per day; any entry may be null.
+#[derive(Debug, Deserialize)]
+struct RawDaily {
+    time: Vec<String>,
+    #[serde(default)]
+    weather_code: Vec<Option<u8>>,
+    #[serde(default)]
+    temperature_2m_max: Vec<Option<f64>>,
+    #[serde(default)]
+    temperature_2m_min: Vec<Option<f64>>,
+    #[serde(default)]
+    precipitation_sum: Vec<Option<f64>>,
+    #[serde(default)]
+    precipitation_probability_max: Vec<Option<f64>>,
+    #[serde(default)]
+    wind_speed_10m_max: Vec<Option<f64>>,
+    #[serde(default)]
+    sunrise: Vec<Option<String>>,
+    #[serde(default)]
+    sunset: Vec<Option<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NwsRoot {
+    #[serde(default)]
+    features: Vec<NwsFeature>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NwsFeature {
+    properties: NwsAlert,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NwsAlert {
+    id: String,
+    event: String,
+    #[serde(default)]
+    headline: Option<String>,
+    #[serde(default)]
+    severity: Option<String>,
+    #[serde(default)]
+    urgency: Option<String>,
+    #[serde(default)]
+    certainty: Option<String>,
+    #[serde(default)]
+    area_desc: Option<String>,
+    #[serde(default)]
+    sender_name: Option<String>,
+    #[serde(default)]
+    effective: Option<String>,
+    #[serde(default)]
+    expires: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    instruction: Option<String>,
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .item::<CurrentWeather>("current")
+        .list::<DayForecast>("forecast")
+        .list::<Alert>("alerts")
+        .list::<Place>("geocode")
+        .message("config set")
+        .item::<AppConfig>("config show")
+        .cache()
+}
+
+fn main() {
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
+
+    let result = dispatch(&cli);
+    if let Err(err) = result {
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
+    }
+}
+
+fn dispatch(cli: &Cli) -> Result<(), AppError> {
+    match &cli.command {
+        Commands::Current(args) => cmd_current(args, &cli.global),
+        Commands::Forecast(args) => cmd_forecast(args, &cli.global),
+        Commands::Alerts(args) => cmd_alerts(args, &cli.global),
+        Commands::Geocode(args) => cmd_geocode(args, &cli.global),
+        Commands::Config(args) => cmd_config(args),
+        Commands::Cache { command } => Ok(dee_core::cache::run(
+            &Cache::new("dee-weather"),
+            *command,
+            cli.global.json,
+        )?),
+    }
+}
+
+fn cmd_current(args: &LocationArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    let config = config_file().load()?;
+    let units = out.units.or(config.units).unwrap_or_default();
+    let place = resolve_place(args.location.as_deref(), &config, out)?;
+
+    let url = forecast_url(&place, units, &format!("current={CURRENT_FIELDS}"));
+    let raw = fetch_json(&url, WEATHER_TTL_SECS, out)?;
+    let body: ForecastRoot = serde_json::from_value(raw).map_err(|_| AppError::ParseFailed)?;
+    let current = body.current.ok_or(AppError::ParseFailed)?;
+
+    let item = CurrentWeather {
+        location: display_name(&place),
+        latitude: place.latitude,
+        longitude: place.longitude,
+        timezone: body.timezone,
+        time: current.time,
+        conditions: conditions(current.weather_code).to_string(),
+        weather_code: current.weather_code,
+        temperature: current.temperature_2m,
+        feels_like: current.apparent_temperature,
+        humidity: current.relative_humidity_2m,
+        precipitation: current.precipitation,
+        wind_speed: current.wind_speed_10m,
+        wind_direction: current.wind_direction_10m,
+        is_day: current.is_day == Some(1),
+        units,
+    };
+
+    let symbols = Symbols::of(units);
+    if out.json {
+        print_json(&OkItem { ok: true, item });
+    } else if out.quiet {
+        println!("{}", number(item.temperature));
+    } else {
+        println!(
+            "{}: {}{}, feels like {}{}, {}",
+            item.location,
+            number(item.temperature),
+            symbols.temperature,
+            number(item.feels_like),
+            symbols.temperature,
+            item.conditions
+        );
+        println!(
+            "humidity {}%, wind {} {} from {}°, precipitation {} {} ({})",
+            number(item.humidity),
+            number(item.wind_speed),
+            symbols.wind_speed,
+            number(item.wind_direction),
+            number(item.precipitation),
+            symbols.precipitation,
+            item.time
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_forecast(args: &ForecastArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    if args.days == 0 || args.days > MAX_FORECAST_DAYS {
+        return Err(AppError::InvalidArgument(format!(
+            "--days must be between 1 and {MAX_FORECAST_DAYS}"
+        )));
+    }
+
+    let config = config_file().load()?;
+    let units = out.units.or(config.units).unwrap_or_default();
+    let place = resolve_place(args.location.as_deref(), &config, out)?;
+
+    let url = forecast_url(
+        &place,
+        units,
+        &format!("daily={DAILY_FIELDS}&forecast_days={}", args.days),
+    );
+    let raw = fetch_json(&url, WEATHER_TTL_SECS, out)?;
+    let body: ForecastRoot = serde_json::from_value(raw).map_err(|_| AppError::ParseFailed)?;
+    let daily = body.daily.ok_or(AppError::ParseFailed)?;
+
+    let location = display_name(&place);
+    let items: Vec<DayForecast> = daily
+        .time
+        .iter()
+        .enumerate()
+        .map(|(day, date)| {
+            let weather_code = at(&daily.weather_code, day);
+            DayForecast {
+                location: location.clone(),
+                date: date.clone(),
+                conditions: conditions(weather_code).to_string(),
+                weather_code,
+                temp_max: at(&daily.temperature_2m_max, day),
+                temp_min: at(&daily.temperature_2m_min, day),
+                precipitation: at(&daily.precipitation_sum, day),
+                precipitation_probability: at(&daily.precipitation_probability_max, day),
+                wind_speed_max: at(&daily.wind_speed_10m_max, day),
+                sunrise: daily.sunrise.get(day).cloned().flatten(),
+                sunset: daily.sunset.get(day).cloned().flatten(),
+                units,
+            }
+        })
+        .collect();
+
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
+    } else if out.quiet {
+        println!("{}", items.len());
+    } else {
+        let symbols = Symbols::of(units);
+        println!("{location}");
+        for item in items {
+            println!(
+                "{}  {}..{}{}  {}  precipitation {} {} ({}%)",
+                item.date,
+                number(item.temp_min),
+                number(item.temp_max),
+                symbols.temperature,
+                item.conditions,
+                number(item.precipitation),
+                symbols.precipitation,
+                number(item.precipitation_probability)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_alerts(args: &LocationArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    let config = config_file().load()?;
+    let place = resolve_place(args.location.as_deref(), &config, out)?;
+    // Geocoded places say where they are; bare coordinates are left to NWS
+    if let Some(code) = &place.country_code {
+        if !NWS_COUNTRIES.contains(&code.to_uppercase().as_str()) {
+            return Err(AppError::InvalidArgument(format!(
+                "alerts come from the US National Weather Service; {} is outside the US",
+                display_name(&place)
+            )));
+        }
+    }
+
+    let url = format!(
+        "{ALERTS_BASE}?point={:.4},{:.4}",
+        place.latitude, place.longitude
+    );
+    let raw = fetch_json(&url, ALERTS_TTL_SECS, out)?;
+    let body: NwsRoot = serde_json::from_value(raw).map_err(|_| AppError::ParseFailed)?;
+    let items: Vec<Alert> = body
+        .features
+        .into_iter()
+        .map(|feature| {
+            let alert = feature.properties;
+            Alert {
+                id: alert.id,
+                event: alert.event,
+                headline: alert.headline,
+                severity: alert.severity,
+                urgency: alert.urgency,
+                certainty: alert.certainty,
+                area: alert.area_desc,
+                sender: alert.sender_name,
+                effective: alert.effective,
+                expires: alert.expires,
+                description: alert.description,
+                instruction: alert.instruction,
+            }
+        })
+        .collect();
+
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
+    } else if out.quiet {
+        println!("{}", items.len());
+    } else if items.is_empty() {
+        println!("No active alerts for {}", display_name(&place));
+    } else {
+        for item in items {
+            println!(
+                "{} {}: {}",
+                item.severity.as_deref().unwrap_or("Unknown"),
+                item.event,
+                item.headline.as_deref().unwrap_or("")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_geocode(args: &GeocodeArgs, out: &GlobalArgs) -> Result<(), AppError> {
+    if args.limit == 0 || args.limit > MAX_GEOCODE_RESULTS {
+        return Err(AppError::InvalidArgument(format!(
+            "--limit must be between 1 and {MAX_GEOCODE_RESULTS}"
+        )));
+    }
+
+    let items = search_places(&args.name, args.limit, out)?;
+    if items.is_empty() {
+        return Err(AppError::NotFound(args.name.clone()));
+    }
+
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
+    } else if out.quiet {
+        println!("{}", items.len());
+    } else {
+        for item in items {
+            println!(
+                "{} ({:.4}, {:.4}) {}",
+                display_name(&item),
+                item.latitude,
+                item.longitude,
+                item.timezone.as_deref().unwrap_or("")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The place for a command: `lat,lon` as given, else the best geocoding match.
+fn resolve_place(
+    location: Option<&str>,
+    config: &AppConfig,
+    out: &GlobalArgs,
+) -> Result<Place, AppError> {
+    let query = location
+        .or(config.location.as_deref())
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .ok_or_else(|| {
+            AppError::InvalidArgument(
+                "give a location or save one with config set location <PLACE>".to_string(),
+            )
+        })?;
+
+    if let Some((latitude, longitude)) = parse_coordinates(query)? {
+        return Ok(Place {
+            name: format!("{latitude:.4},{longitude:.4}"),
+            latitude,
+            longitude,
+            country: None,
+            country_code: None,
+            admin1: None,
+            timezone: None,
+            population: None,
+        });
+    }
+
+    search_places(query, 1, out)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::NotFound(query.to_string()))
+}
+
+/// `Some` when `text` is two numbers separated by a comma, checked for range.
+fn parse_coordinates(text: &str) -> Result<Option<(f64, f64)>, AppError> {
+    let Some((lat, lon)) = text.split_once(',') else {
+        return Ok(None);
+    };
+    let (Ok(latitude), Ok(longitude)) = (lat.trim().parse::<f64>(), lon.trim().parse::<f64>())
+    else {
+        return Ok(None);
+    };
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return Err(AppError::InvalidArgument(format!(
+            "coordinates out of range: {text}"
+        )));
+    }
+    Ok(Some((latitude, longitude)))
+}
+
+/// Open-Meteo matches on the name alone, so `Portland, Oregon` looks up
+/// `Portland` and keeps the results whose country, country code or region is
+/// `Oregon`.
+fn search_places(query: &str, limit: u32, out: &GlobalArgs) -> Result<Vec<Place>, AppError> {
+    let (name, qualifier) = match query.split_once(',') {
+        Some((name, qualifier)) => (name.trim(), Some(qualifier.trim().to_lowercase())),
+        None => (query.trim(), None),
+    };
+    if name.is_empty() {
+        return Err(AppError::InvalidArgument(
+            "location name is empty".to_string(),
+        ));
+    }
+    // Fetch extra candidates when some will be filtered out
+    let count = if qualifier.is_some() {
+        MAX_GEOCODE_RESULTS
+    } else {
+        limit
+    };
+
+    let url = format!(
+        "{GEOCODE_BASE}?name={}&count={count}&language=en&format=json",
+        urlencoding::encode(name)
+    );
+    let raw = fetch_json(&url, GEOCODE_TTL_SECS, out)?;
+    let body: GeoRoot = serde_json::from_value(raw).map_err(|_| AppError::ParseFailed)?;
+
+    Ok(body
+        .results
+        .into_iter()
+        .filter(|result| match &qualifier {
+            Some(qualifier) => [&result.country, &result.country_code, &result.admin1]
+                .into_iter()
+                .flatten()
+                .any(|field| field.to_lowercase() == *qualifier),
+            None => true,
+        })
+        .take(limit as usize)
+        .map(|result| Place {
+            name: result.name,
+            latitude: result.latitude,
+            longitude: result.longitude,
+            country: result.country,
+            country_code: result.country_code,
+            admin1: result.admin1,
+            timezone: result.timezone,
+            population: result.population,
+        })
+        .collect())
+}
+
+fn forecast_url(place: &Place, units: Units, fields: &str) -> String {
+    let mut url = format!(
+        "{FORECAST_BASE}?latitude={:.4}&longitude={:.4}&{fields}&timezone=auto",
+        place.latitude, place.longitude
+    );
+    if units == Units::Imperial {
+        url.push_str("&temperature_unit=fahrenheit&wind_speed_unit=mph&precipitation_unit=inch");
+    }
+    url
+}
+
+/// The JSON at `url`, from the cache when it is younger than `ttl_secs`.
+fn fetch_json(url: &str, ttl_secs: u64, out: &GlobalArgs) -> Result<serde_json::Value, AppError> {
+    let cache = Cache::new("dee-weather").ttl_secs(ttl_secs);
+    if let Some(hit) = cache.lookup::<serde_json::Value>(url, &out.cache)? {
+        tracing::debug!("cached {url} ({}s old)", hit.age_secs);
+        return Ok(hit.value);
+    }
+
+    let wait = ratelimit::reserve(url)?;
+    if !wait.is_zero() {
+        tracing::debug!(
+            "waiting {:.1}s before calling {}",
+            wait.as_secs_f64(),
+            ratelimit::host_of(url)
+        );
+        std::thread::sleep(wait);
+    }
+    tracing::debug!("GET {url}");
+
+    let client = Http::new("dee-weather", env!("CARGO_PKG_VERSION"))
+        .args(&out.http)
+        .blocking()
+        .map_err(|_| AppError::RequestFailed)?;
+    let response = client
+        .get(url)
+        // NWS answers GeoJSON; Open-Meteo ignores the header
+        .header("Accept", "application/geo+json, application/json")
+        .send()
+        .map_err(|_| AppError::RequestFailed)?;
+    let status = response.status();
+    let raw: serde_json::Value = response.json().map_err(|_| {
+        if status.is_success() {
+            AppError::ParseFailed
+        } else {
+            AppError::RequestFailed
+        }
+    })?;
+    if !status.is_success() {
+        // Open-Meteo says why in `reason`, NWS in `detail`
+        let reason = ["reason", "detail"]
+            .into_iter()
+            .find_map(|key| raw.get(key).and_then(|x| x.as_str()))
+            .unwrap_or_else(|| status.as_str());
+        return Err(AppError::ApiError(reason.to_string()));
+    }
+
+    cache.put(url, &raw);
+    Ok(raw)
+}
+
+/// `Berlin, Land Berlin, Germany`
+fn display_name(place: &Place) -> String {
+    let mut parts = vec![place.name.as_str()];
+    if let Some(admin1) = place.admin1.as_deref().filter(|x| *x != place.name) {
+        parts.push(admin1);
+    }
+    if let Some(country) = place.country.as_deref() {
+        parts.push(country);
+    }
+    parts.join(", ")
+}
+
+fn at<T: Copy>(values: &[Option<T>], day: usize) -> Option<T> {
+    values.get(day).copied().flatten()
+}
+
+fn number(value: Option<f64>) -> String {
+    value
+        .map(|x| format!("{x:.1}"))
+        .unwrap_or_else(|| "?".to_string())
+}
+
+struct Symbols {
+    temperature: &'static str,
+    wind_speed: &'static str,
+    precipitation: &'static str,
+}
+
+impl Symbols {
+    fn of(units: Units) -> Self {
+        match units {
+            Units::Metric => Self {
+                temperature: "°C",
+                wind_speed: "km/h",
+                precipitation: "mm",
+            },
+            Units::Imperial => Self {
+                temperature: "°F",
+                wind_speed: "mph",
+                precipitation: "in",
+            },
+        }
+    }
+}
+
+/// WMO weather interpretation codes, as Open-Meteo documents them.
+fn conditions(code: Option<u8>) -> &'static str {
+    match code {
+        Some(0) => "Clear sky",
+        Some(1) => "Mainly clear",
+        Some(2) => "Partly cloudy",
+        Some(3) => "Overcast",
+        Some(45) => "Fog",
+        Some(48) => "Depositing rime fog",
+        Some(51) => "Light drizzle",
+        Some(53) => "Drizzle",
+        Some(55) => "Dense drizzle",
+        Some(56) => "Light freezing drizzle",
+        Some(57) => "Dense freezing drizzle",
+        Some(61) => "Slight rain",
+        Some(63) => "Rain",
+        Some(65) => "Heavy rain",
+        Some(66) => "Light freezing rain",
+        Some(67) => "Heavy freezing rain",
+        Some(71) => "Slight snow",
+        Some(73) => "Snow",
+        Some(75) => "Heavy snow",
+        Some(77) => "Snow grains",
+        Some(80) => "Slight rain showers",
+        Some(81) => "Rain showers",
+        Some(82) => "Violent rain showers",
+        Some(85) => "Slight snow showers",
+        Some(86) => "Heavy snow showers",
+        Some(95) => "Thunderstorm",
+        Some(96) => "Thunderstorm with slight hail",
+        Some(99) => "Thunderstorm with heavy hail",
+        _ => "Unknown",
+    }
+}
+
+fn cmd_config(args: &ConfigArgs) -> Result<(), AppError> {
+    match &args.command {
+        ConfigCommand::Set(input) => {
+            let mut cfg = config_file().load_file()?;
+            match input.key.as_str() {
+                "units" => {
+                    cfg.units = Some(
+                        Units::from_str(&input.value, true).map_err(AppError::InvalidArgument)?,
+                    )
+                }
+                "location" => cfg.location = Some(input.value.clone()),
+                other => return Err(AppError::InvalidConfigKey(other.to_string())),
+            }
+            config_file().save(&cfg)?;
+
+            if input.output.json {
+                print_json(&OkMessage {
+                    ok: true,
+                    message: "Config updated".to_string(),
+                });
+            } else {
+                println!("Config updated");
+            }
+            Ok(())
+        }
+        ConfigCommand::Show(flags) => {
+            let cfg = config_file().load()?;
+            if flags.json {
+                print_json(&OkItem {
+                    ok: true,
+                    item: cfg,
+                });
+            } else {
+                let units = match cfg.units.unwrap_or_default() {
+                    Units::Metric => "metric",
+                    Units::Imperial => "imperial",
+                };
+                println!("units: {units}");
+                println!("location: {}", cfg.location.as_deref().unwrap_or("(unset)"));
+            }
+            Ok(())
+        }
+        ConfigCommand::Path => {
+            println!("{}", config_file().path().display());
+            Ok(())
+        }
+    }
+}
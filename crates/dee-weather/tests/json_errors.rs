@@ -0,0 +1,94 @@
+use assert_cmd::Command;
+use dee_test_support::seed_cache;
+use std::path::Path;
+
+fn bin(home: &Path) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("dee-weather"));
+    cmd.env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env_remove("DEE_WEATHER_LOCATION")
+        .env_remove("DEE_WEATHER_UNITS");
+    cmd
+}
+
+fn json(cmd: &mut Command) -> (Option<i32>, serde_json::Value) {
+    let out = cmd.output().unwrap();
+    let parsed = serde_json::from_slice(&out.stdout).expect("valid json");
+    (out.status.code(), parsed)
+}
+
+#[test]
+fn emits_json_errors_for_bad_arguments() {
+    let home = tempfile::tempdir().unwrap();
+
+    let (code, parsed) =
+        json(bin(home.path()).args(["forecast", "Berlin", "--days", "0", "--json"]));
+    assert_eq!(code, Some(2));
+    assert_eq!(parsed["ok"], false);
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    let (code, parsed) = json(bin(home.path()).args(["current", "95,10", "--json"]));
+    assert_eq!(code, Some(2));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+
+    // No location given and none saved
+    let (code, parsed) = json(bin(home.path()).args(["current", "--json"]));
+    assert_eq!(code, Some(2));
+    assert!(parsed["error"]
+        .as_str()
+        .unwrap()
+        .contains("config set location"));
+
+    let (_, parsed) = json(bin(home.path()).args(["config", "set", "units", "kelvin", "--json"]));
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn offline_serves_cached_weather_for_the_saved_location() {
+    let home = tempfile::tempdir().unwrap();
+
+    bin(home.path())
+        .args(["config", "set", "location", "52.52, 13.41"])
+        .assert()
+        .success();
+
+    let (_, parsed) = json(bin(home.path()).args(["current", "--offline", "--json"]));
+    assert_eq!(parsed["code"], "OFFLINE_MISS");
+
+    let key = "https://api.open-meteo.com/v1/forecast?latitude=52.5200&longitude=13.4100&current=temperature_2m,apparent_temperature,relative_humidity_2m,precipitation,weather_code,wind_speed_10m,wind_direction_10m,is_day&timezone=auto";
+    let body = serde_json::json!({
+        "timezone": "Europe/Berlin",
+        "current": {
+            "time": "2026-10-16T14:00",
+            "temperature_2m": 12.5,
+            "apparent_temperature": 10.9,
+            "relative_humidity_2m": 71,
+            "precipitation": 0.0,
+            "weather_code": 3,
+            "wind_speed_10m": 14.2,
+            "wind_direction_10m": 250,
+            "is_day": 1
+        }
+    });
+    seed_cache(&home.path().join("cache"), "dee-weather", key, &body);
+
+    let (code, parsed) = json(bin(home.path()).args(["current", "--offline", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["ok"], true);
+    assert_eq!(parsed["stale"], true);
+    let item = &parsed["item"];
+    assert_eq!(item["location"], "52.5200,13.4100");
+    assert_eq!(item["temperature"], 12.5);
+    assert_eq!(item["conditions"], "Overcast");
+    assert_eq!(item["is_day"], true);
+    assert_eq!(item["units"], "metric");
+
+    // Imperial asks Open-Meteo for other units, so it is another entry
+    let (_, parsed) = json(bin(home.path()).env("DEE_WEATHER_UNITS", "imperial").args([
+        "current",
+        "--offline",
+        "--json",
+    ]));
+    assert_eq!(parsed["code"], "OFFLINE_MISS");
+}
@@ -1,18 +1,16 @@
 use clap::{Args, Parser, Subcommand};
-use reqwest::blocking::{Client, Response};
+use dee_core::http::{Http, HttpArgs};
+use dee_core::schema::{self, Schemas};
+use dee_core::{print_json, print_list, report, ErrorCode, Format, OkItem};
+use reqwest::blocking::Response;
 use reqwest::header::CONTENT_TYPE;
 use reqwest::{StatusCode, Url};
+use schemars::JsonSchema;
 use scraper::{ElementRef, Html, Selector};
 use serde::Serialize;
 use sha2::{Digest, Sha256};
 use std::io::Read;
-use std::time::Duration;
 
-const USER_AGENT: &str = concat!(
-    "dee-webpage/",
-    env!("CARGO_PKG_VERSION"),
-    " (https://dee.ink)"
-);
 const DEFAULT_MAX_BYTES: usize = 2_000_000;
 const DEFAULT_MAX_CHARS: usize = 20_000;
 const DEFAULT_LINK_LIMIT: usize = 200;
@@ -39,6 +37,11 @@ struct GlobalArgs {
     quiet: bool,
     #[arg(short = 'v', long, global = true, help = "Debug output to stderr")]
     verbose: bool,
+    /// List format: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+    #[command(flatten)]
+    http: HttpArgs,
 }
 
 #[derive(Debug, Subcommand)]
@@ -57,9 +60,6 @@ enum Commands {
 struct FetchArgs {
     /// URL to fetch
     url: String,
-    /// Request timeout in seconds
-    #[arg(long, default_value_t = 20)]
-    timeout_secs: u64,
     /// Maximum response body size in bytes
     #[arg(long, default_value_t = DEFAULT_MAX_BYTES)]
     max_bytes: usize,
@@ -75,9 +75,6 @@ struct TextArgs {
     /// Maximum text characters to return
     #[arg(long, default_value_t = DEFAULT_MAX_CHARS)]
     max_chars: usize,
-    /// Request timeout in seconds
-    #[arg(long, default_value_t = 20)]
-    timeout_secs: u64,
     /// Maximum response body size in bytes
     #[arg(long, default_value_t = DEFAULT_MAX_BYTES)]
     max_bytes: usize,
@@ -96,35 +93,12 @@ struct LinksArgs {
     /// Only show links on a different host
     #[arg(long)]
     external: bool,
-    /// Request timeout in seconds
-    #[arg(long, default_value_t = 20)]
-    timeout_secs: u64,
     /// Maximum response body size in bytes
     #[arg(long, default_value_t = DEFAULT_MAX_BYTES)]
     max_bytes: usize,
 }
 
-#[derive(Debug, Serialize)]
-struct JsonItem<T> {
-    ok: bool,
-    item: T,
-}
-
-#[derive(Debug, Serialize)]
-struct JsonList<T> {
-    ok: bool,
-    count: usize,
-    items: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-struct JsonError {
-    ok: bool,
-    error: String,
-    code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct MetadataItem {
     url: String,
     final_url: String,
@@ -144,13 +118,13 @@ struct MetadataItem {
     images_count: usize,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 struct HeadingItem {
     level: u8,
     text: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct TextItem {
     url: String,
     final_url: String,
@@ -162,7 +136,7 @@ struct TextItem {
     content_sha256: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct MarkdownItem {
     url: String,
     final_url: String,
@@ -174,7 +148,7 @@ struct MarkdownItem {
     content_sha256: String,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 struct LinkItem {
     source_url: String,
     url: String,
@@ -210,7 +184,7 @@ enum AppError {
     Internal(String),
 }
 
-impl AppError {
+impl ErrorCode for AppError {
     fn code(&self) -> &'static str {
         match self {
             Self::InvalidArgument(_) => "INVALID_ARGUMENT",
@@ -223,47 +197,28 @@ impl AppError {
     }
 }
 
-fn main() {
-    let cli = match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => {
-            if err.kind() == clap::error::ErrorKind::DisplayHelp
-                || err.kind() == clap::error::ErrorKind::DisplayVersion
-            {
-                err.exit();
-            }
-
-            if wants_json_from_args() {
-                print_json(&JsonError {
-                    ok: false,
-                    error: err.to_string().trim().to_string(),
-                    code: "INVALID_ARGUMENT".to_string(),
-                });
-                std::process::exit(1);
-            }
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .item::<MetadataItem>("metadata")
+        .item::<TextItem>("text")
+        .item::<MarkdownItem>("markdown")
+        .list::<LinkItem>("links")
+}
 
-            err.exit();
-        }
-    };
+fn main() {
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = run(&cli) {
-        if cli.global.json {
-            print_json(&JsonError {
-                ok: false,
-                error: err.to_string(),
-                code: err.code().to_string(),
-            });
-        } else {
-            eprintln!("error: {err}");
-        }
-        std::process::exit(1);
+        report(
+            &err,
+            Format::json_errors(cli.global.output, cli.global.json),
+        );
+        std::process::exit(err.exit_code());
     }
 }
 
-fn wants_json_from_args() -> bool {
-    std::env::args_os().any(|arg| arg == "--json" || arg == "-j")
-}
-
 fn run(cli: &Cli) -> Result<(), AppError> {
     match &cli.command {
         Commands::Metadata(args) => cmd_metadata(args, &cli.global),
@@ -274,13 +229,13 @@ fn run(cli: &Cli) -> Result<(), AppError> {
 }
 
 fn cmd_metadata(args: &FetchArgs, out: &GlobalArgs) -> Result<(), AppError> {
-    validate_fetch_args(&args.url, args.timeout_secs, args.max_bytes)?;
-    let page = fetch_page(&args.url, args.timeout_secs, args.max_bytes, out)?;
+    validate_fetch_args(&args.url, args.max_bytes)?;
+    let page = fetch_page(&args.url, args.max_bytes, out)?;
     let html = page.html()?;
     let item = build_metadata(&page, &html)?;
 
     if out.json {
-        print_json(&JsonItem { ok: true, item });
+        print_json(&OkItem { ok: true, item });
     } else if out.quiet {
         println!("{}", non_empty(&item.title, &item.final_url));
     } else {
@@ -299,14 +254,14 @@ fn cmd_metadata(args: &FetchArgs, out: &GlobalArgs) -> Result<(), AppError> {
 }
 
 fn cmd_text(args: &TextArgs, out: &GlobalArgs) -> Result<(), AppError> {
-    validate_fetch_args(&args.url, args.timeout_secs, args.max_bytes)?;
+    validate_fetch_args(&args.url, args.max_bytes)?;
     if args.max_chars == 0 || args.max_chars > 1_000_000 {
         return Err(AppError::InvalidArgument(
             "--max-chars must be between 1 and 1000000".to_string(),
         ));
     }
 
-    let page = fetch_page(&args.url, args.timeout_secs, args.max_bytes, out)?;
+    let page = fetch_page(&args.url, args.max_bytes, out)?;
     let html = page.html()?;
     let title = first_text(&html, "title")?;
     let (selector, text) = extract_text(&html, args.selector.as_deref())?;
@@ -324,7 +279,7 @@ fn cmd_text(args: &TextArgs, out: &GlobalArgs) -> Result<(), AppError> {
     };
 
     if out.json {
-        print_json(&JsonItem { ok: true, item });
+        print_json(&OkItem { ok: true, item });
     } else if out.quiet {
         println!("{}", item.text);
     } else {
@@ -340,14 +295,14 @@ fn cmd_text(args: &TextArgs, out: &GlobalArgs) -> Result<(), AppError> {
 }
 
 fn cmd_markdown(args: &TextArgs, out: &GlobalArgs) -> Result<(), AppError> {
-    validate_fetch_args(&args.url, args.timeout_secs, args.max_bytes)?;
+    validate_fetch_args(&args.url, args.max_bytes)?;
     if args.max_chars == 0 || args.max_chars > 1_000_000 {
         return Err(AppError::InvalidArgument(
             "--max-chars must be between 1 and 1000000".to_string(),
         ));
     }
 
-    let page = fetch_page(&args.url, args.timeout_secs, args.max_bytes, out)?;
+    let page = fetch_page(&args.url, args.max_bytes, out)?;
     let html = page.html()?;
     let title = first_text(&html, "title")?;
     let (selector, markdown) = extract_markdown(&html, args.selector.as_deref())?;
@@ -365,7 +320,7 @@ fn cmd_markdown(args: &TextArgs, out: &GlobalArgs) -> Result<(), AppError> {
     };
 
     if out.json {
-        print_json(&JsonItem { ok: true, item });
+        print_json(&OkItem { ok: true, item });
     } else if out.quiet {
         println!("{}", item.markdown);
     } else {
@@ -381,7 +336,7 @@ fn cmd_markdown(args: &TextArgs, out: &GlobalArgs) -> Result<(), AppError> {
 }
 
 fn cmd_links(args: &LinksArgs, out: &GlobalArgs) -> Result<(), AppError> {
-    validate_fetch_args(&args.url, args.timeout_secs, args.max_bytes)?;
+    validate_fetch_args(&args.url, args.max_bytes)?;
     if args.limit == 0 || args.limit > 10_000 {
         return Err(AppError::InvalidArgument(
             "--limit must be between 1 and 10000".to_string(),
@@ -393,7 +348,7 @@ fn cmd_links(args: &LinksArgs, out: &GlobalArgs) -> Result<(), AppError> {
         ));
     }
 
-    let page = fetch_page(&args.url, args.timeout_secs, args.max_bytes, out)?;
+    let page = fetch_page(&args.url, args.max_bytes, out)?;
     let html = page.html()?;
     let mut items = extract_links(&html, &page.final_url)?;
     if args.internal {
@@ -404,12 +359,8 @@ fn cmd_links(args: &LinksArgs, out: &GlobalArgs) -> Result<(), AppError> {
     }
     items.truncate(args.limit);
 
-    if out.json {
-        print_json(&JsonList {
-            ok: true,
-            count: items.len(),
-            items,
-        });
+    if let Some(format) = Format::resolve(out.output, out.json) {
+        print_list(&items, format);
     } else if out.quiet {
         for item in items {
             println!("{}", item.url);
@@ -426,7 +377,7 @@ fn cmd_links(args: &LinksArgs, out: &GlobalArgs) -> Result<(), AppError> {
     Ok(())
 }
 
-fn validate_fetch_args(url: &str, timeout_secs: u64, max_bytes: usize) -> Result<(), AppError> {
+fn validate_fetch_args(url: &str, max_bytes: usize) -> Result<(), AppError> {
     let parsed = Url::parse(url)
         .map_err(|_| AppError::InvalidArgument("url must be a valid absolute URL".to_string()))?;
     if !matches!(parsed.scheme(), "http" | "https") {
@@ -434,11 +385,6 @@ fn validate_fetch_args(url: &str, timeout_secs: u64, max_bytes: usize) -> Result
             "url scheme must be http or https".to_string(),
         ));
     }
-    if timeout_secs == 0 || timeout_secs > 300 {
-        return Err(AppError::InvalidArgument(
-            "--timeout-secs must be between 1 and 300".to_string(),
-        ));
-    }
     if max_bytes == 0 || max_bytes > 50_000_000 {
         return Err(AppError::InvalidArgument(
             "--max-bytes must be between 1 and 50000000".to_string(),
@@ -447,21 +393,16 @@ fn validate_fetch_args(url: &str, timeout_secs: u64, max_bytes: usize) -> Result
     Ok(())
 }
 
-fn fetch_page(
-    url: &str,
-    timeout_secs: u64,
-    max_bytes: usize,
-    out: &GlobalArgs,
-) -> Result<FetchedPage, AppError> {
-    let client = Client::builder()
-        .user_agent(USER_AGENT)
-        .timeout(Duration::from_secs(timeout_secs))
+fn fetch_page(url: &str, max_bytes: usize, out: &GlobalArgs) -> Result<FetchedPage, AppError> {
+    let client = Http::new("dee-webpage", env!("CARGO_PKG_VERSION"))
+        .args(&out.http)
+        .blocking_builder()
         .redirect(reqwest::redirect::Policy::limited(10))
         .build()
         .map_err(|err| AppError::Internal(err.to_string()))?;
 
-    if out.verbose && !out.quiet {
-        eprintln!("debug: GET {url}");
+    if !out.quiet {
+        tracing::debug!("GET {url}");
     }
 
     let response = client
@@ -791,15 +732,6 @@ fn non_empty<'a>(value: &'a str, fallback: &'a str) -> &'a str {
     }
 }
 
-fn print_json<T: Serialize>(value: &T) {
-    match serde_json::to_string_pretty(value) {
-        Ok(json) => println!("{json}"),
-        Err(_) => {
-            println!("{{\"ok\":false,\"error\":\"serialization failure\",\"code\":\"INTERNAL\"}}")
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,14 @@
+CREATE TABLE lookups (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  domain TEXT NOT NULL,
+  looked_up_at TEXT NOT NULL,
+  registrar TEXT NOT NULL,
+  created TEXT NOT NULL,
+  expires TEXT NOT NULL,
+  updated TEXT NOT NULL,
+  name_servers TEXT NOT NULL,
+  status TEXT NOT NULL,
+  whois_server TEXT NOT NULL
+);
+
+CREATE INDEX idx_lookups_domain ON lookups(domain, id);
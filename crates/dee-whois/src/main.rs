@@ -1,10 +1,20 @@
 use anyhow::Result;
 use chrono::{DateTime, NaiveDate, TimeZone, Utc};
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
+use dee_core::format::render_list;
+use dee_core::pipe::{PipeArgs, PipeError};
+use dee_core::schema::{self, Schemas};
+use dee_core::{exit, report_code, style, ErrorCode, Format, OkItem, OkList};
 use regex::Regex;
+use rusqlite::{params, Connection};
+use rusqlite_migration::{Migrations, M};
+use schemars::JsonSchema;
 use serde::Serialize;
 use std::collections::BTreeSet;
+use std::fs;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 
@@ -13,12 +23,20 @@ use tokio::net::TcpStream;
     name = "dee-whois",
     version,
     about = "WHOIS lookup for domains and IPs",
-    long_about = "dee-whois - WHOIS lookup for domains and IPs\n\nUSAGE:\n  dee-whois <domain-or-ip> [options]",
-    after_help = "EXAMPLES:\n  dee-whois example.com\n  dee-whois example.com --json\n  dee-whois example.com --raw\n  dee-whois example.com --expires --json\n  dee-whois 8.8.8.8 --json"
+    long_about = "dee-whois - WHOIS lookup for domains and IPs\n\nUSAGE:\n  dee-whois <domain-or-ip> [options]\n  dee-whois history <domain> [options]\n  dee-whois diff <domain> [options]",
+    after_help = "EXAMPLES:\n  dee-whois example.com\n  dee-whois example.com --json\n  dee-whois example.com --raw\n  dee-whois example.com --expires --json\n  dee-whois 8.8.8.8 --json\n  dee-whois example.com --timeout-secs 5 --retries 3\n  dee-whois history example.com --json\n  dee-whois diff example.com --json\n  dee-porkbun domains list-all --json | dee-whois --stdin-json --field domain --json",
+    args_conflicts_with_subcommands = true
 )]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Domain or IP to look up
-    target: String,
+    target: Option<String>,
+
+    // Look up every domain piped in (`domain` of each item, unless --field)
+    #[command(flatten)]
+    pipe: PipeArgs,
 
     /// Output raw WHOIS text
     #[arg(long)]
@@ -28,20 +46,56 @@ struct Cli {
     #[arg(long)]
     expires: bool,
 
+    /// Per-attempt connect/read timeout in seconds
+    #[arg(long, default_value_t = 10, global = true)]
+    timeout_secs: u64,
+
+    /// Retry failed queries N times with exponential backoff
+    #[arg(long, default_value_t = 2, global = true)]
+    retries: u32,
+
     /// Output as JSON
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     json: bool,
 
     /// Suppress decorative output
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     quiet: bool,
 
     /// Debug output to stderr
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     verbose: bool,
+
+    /// List format for `history`: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    output: Option<Format>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show stored lookups for a domain, newest first
+    History(HistoryArgs),
+    /// Look up a domain and report what changed since the previous stored lookup
+    Diff(DiffArgs),
+}
+
+#[derive(Args, Debug)]
+struct HistoryArgs {
+    /// Domain to show history for
+    domain: String,
+
+    /// Maximum number of lookups to show
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// Domain to look up and compare
+    domain: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 struct WhoisItem {
     domain: String,
     registrar: String,
@@ -54,7 +108,7 @@ struct WhoisItem {
     whois_server: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ExpiresItem {
     domain: String,
     expires: String,
@@ -62,17 +116,81 @@ struct ExpiresItem {
     expired: bool,
 }
 
-#[derive(Debug, Serialize)]
-struct JsonSuccessItem<T: Serialize> {
-    ok: bool,
-    item: T,
+#[derive(Debug, Serialize, JsonSchema)]
+struct HistoryItem {
+    id: i64,
+    domain: String,
+    looked_up_at: String,
+    registrar: String,
+    created: String,
+    expires: String,
+    updated: String,
+    name_servers: Vec<String>,
+    status: Vec<String>,
+    whois_server: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct DiffItem {
+    domain: String,
+    previous_lookup: String,
+    current_lookup: String,
+    first_lookup: bool,
+    changed: bool,
+    changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct FieldChange {
+    field: String,
+    before: String,
+    after: String,
 }
 
-#[derive(Debug, Serialize)]
-struct JsonError {
+#[derive(Debug, Serialize, JsonSchema)]
+struct RawItem<'a> {
+    target: &'a str,
+    raw: &'a str,
+}
+
+/// One piped domain: its registration, or why the lookup failed.
+#[derive(Debug, Serialize, JsonSchema)]
+struct BatchItem {
+    domain: String,
     ok: bool,
-    error: String,
-    code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    registrar: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    days_until_expiry: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name_servers: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+}
+
+/// A lookup prints one of these: just the expiry with `--expires`, the
+/// server's reply with `--raw`, one item per domain with `--stdin-json`.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)] // only described; the variants are printed directly
+enum LookupOutput {
+    Parsed(OkItem<WhoisItem>),
+    Expires(OkItem<ExpiresItem>),
+    Raw(OkItem<RawItem<'static>>),
+    Batch(OkList<BatchItem>),
+}
+
+/// What each command prints under `--json`, for `--schema`. The lookup
+/// itself has no subcommand.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .custom::<LookupOutput>("")
+        .list::<HistoryItem>("history")
+        .item::<DiffItem>("diff")
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -82,85 +200,99 @@ enum WhoisError {
     #[allow(dead_code)]
     #[error("WHOIS lookup failed: {0}")]
     LookupFailed(String),
-    #[allow(dead_code)]
     #[error("Connection to WHOIS server failed: {0}")]
     ConnectionFailed(String),
+    #[error("History database error: {0}")]
+    Database(String),
 }
 
-impl WhoisError {
+impl ErrorCode for WhoisError {
     fn code(&self) -> &'static str {
         match self {
             Self::InvalidArgument(_) => "INVALID_ARGUMENT",
             Self::LookupFailed(_) => "WHOIS_LOOKUP_FAILED",
             Self::ConnectionFailed(_) => "NETWORK_ERROR",
+            Self::Database(_) => "DATABASE_ERROR",
         }
     }
 }
 
 #[tokio::main]
 async fn main() {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     if let Err(err) = run(&cli).await {
-        let message = format!("{err:#}");
-        if cli.json {
-            let code = err
-                .downcast_ref::<WhoisError>()
-                .map(WhoisError::code)
-                .unwrap_or("WHOIS_LOOKUP_FAILED");
-            let payload = JsonError {
-                ok: false,
-                error: message,
-                code: code.to_string(),
-            };
-            let _ = print_json(&payload);
-        } else {
-            eprintln!("error: {message}");
-        }
-        std::process::exit(1);
+        let code = error_code(&err);
+        report_code(
+            &format!("{err:#}"),
+            code,
+            Format::json_errors(cli.output, cli.json),
+        );
+        std::process::exit(exit::for_code(code));
     }
 }
 
+fn error_code(err: &anyhow::Error) -> &'static str {
+    err.downcast_ref::<WhoisError>()
+        .map(WhoisError::code)
+        .or_else(|| err.downcast_ref::<PipeError>().map(PipeError::code))
+        .unwrap_or("WHOIS_LOOKUP_FAILED")
+}
+
 async fn run(cli: &Cli) -> Result<()> {
-    if cli.raw && cli.expires {
+    if cli.timeout_secs == 0 || cli.timeout_secs > 300 {
         anyhow::bail!(WhoisError::InvalidArgument(
-            "--raw and --expires cannot be used together".to_string()
+            "--timeout-secs must be between 1 and 300".to_string()
         ));
     }
-
-    let server = whois_server_for_target(&cli.target);
-    if cli.verbose {
-        eprintln!("querying {} via {}", cli.target, server);
+    if cli.retries > 10 {
+        anyhow::bail!(WhoisError::InvalidArgument(
+            "--retries must be between 0 and 10".to_string()
+        ));
     }
 
-    let raw = query_whois(&server, &cli.target).await?;
-
-    // For .com/.net, attempt a two-step referral lookup if the response includes a Whois Server
-    let (raw, final_server) = if should_try_referral(&server) {
-        if let Some(referral) = extract_referral_server(&raw) {
-            if referral != server && !referral.is_empty() {
-                if cli.verbose {
-                    eprintln!("referral: re-querying via {referral}");
-                }
-                match query_whois(&referral, &cli.target).await {
-                    Ok(referral_raw) => (referral_raw, referral),
-                    Err(_) => (raw, server), // fall back to registry response
-                }
-            } else {
-                (raw, server)
+    match &cli.command {
+        Some(Command::History(args)) => cmd_history(cli, args),
+        Some(Command::Diff(args)) => cmd_diff(cli, args).await,
+        None if cli.pipe.stdin_json => {
+            if cli.target.is_some() {
+                anyhow::bail!(WhoisError::InvalidArgument(
+                    "Give a domain or --stdin-json, not both".to_string()
+                ));
             }
-        } else {
-            (raw, server)
+            if cli.raw {
+                anyhow::bail!(WhoisError::InvalidArgument(
+                    "--raw cannot be used with --stdin-json".to_string()
+                ));
+            }
+            let targets = cli.pipe.values("domain")?.unwrap_or_default();
+            cmd_batch(cli, &targets).await
         }
-    } else {
-        (raw, server)
-    };
+        None => {
+            let target = cli.target.as_deref().ok_or_else(|| {
+                WhoisError::InvalidArgument("Missing required argument: domain-or-ip".to_string())
+            })?;
+            cmd_lookup(cli, target).await
+        }
+    }
+}
+
+async fn cmd_lookup(cli: &Cli, target: &str) -> Result<()> {
+    if cli.raw && cli.expires {
+        anyhow::bail!(WhoisError::InvalidArgument(
+            "--raw and --expires cannot be used together".to_string()
+        ));
+    }
+
+    let (raw, final_server) = lookup(cli, target).await?;
 
     if cli.raw {
-        return output_raw(cli, &raw);
+        return output_raw(cli, target, &raw);
     }
 
-    let parsed = parse_whois(&cli.target, &final_server, &raw);
+    let parsed = parse_whois(target, &final_server, &raw);
+    record_lookup_best_effort(&parsed);
 
     if cli.expires {
         let expires = ExpiresItem {
@@ -175,6 +307,192 @@ async fn run(cli: &Cli) -> Result<()> {
     output_item(cli, &parsed)
 }
 
+/// Look up each piped domain in turn; a failure is recorded on its item, not
+/// returned. Every item carries the expiry, so `--expires` changes nothing.
+async fn cmd_batch(cli: &Cli, targets: &[String]) -> Result<()> {
+    let mut items = Vec::with_capacity(targets.len());
+    for target in targets {
+        let item = match lookup(cli, target).await {
+            Ok((raw, final_server)) => {
+                let parsed = parse_whois(target, &final_server, &raw);
+                record_lookup_best_effort(&parsed);
+                BatchItem {
+                    domain: parsed.domain,
+                    ok: true,
+                    registrar: Some(parsed.registrar),
+                    expires: Some(parsed.expires),
+                    days_until_expiry: Some(parsed.days_until_expiry),
+                    name_servers: Some(parsed.name_servers),
+                    error: None,
+                    code: None,
+                }
+            }
+            Err(err) => BatchItem {
+                domain: target.clone(),
+                ok: false,
+                registrar: None,
+                expires: None,
+                days_until_expiry: None,
+                name_servers: None,
+                error: Some(format!("{err:#}")),
+                code: Some(error_code(&err)),
+            },
+        };
+        items.push(item);
+    }
+
+    if let Some(format) = Format::resolve(cli.output, cli.json) {
+        print!("{}", render_list(&items, format)?);
+        return Ok(());
+    }
+    for item in &items {
+        match (&item.expires, &item.error) {
+            (Some(expires), _) if cli.quiet => println!("{}\t{expires}", item.domain),
+            (Some(expires), _) => println!(
+                "{}: {}  {}",
+                item.domain,
+                expiry(expires, item.days_until_expiry.unwrap_or_default()),
+                style::dim(item.registrar.as_deref().unwrap_or_default())
+            ),
+            (None, Some(error)) if !cli.quiet => {
+                println!("{}: {}", item.domain, style::bad(format!("error: {error}")))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn cmd_history(cli: &Cli, args: &HistoryArgs) -> Result<()> {
+    if args.limit == 0 {
+        anyhow::bail!(WhoisError::InvalidArgument(
+            "--limit must be greater than 0".to_string()
+        ));
+    }
+
+    let conn = open_db()?;
+    let items = load_history(&conn, &normalize_domain(&args.domain), args.limit)?;
+
+    if let Some(format) = cli.output {
+        print!("{}", render_list(&items, format)?);
+        return Ok(());
+    }
+    if cli.json {
+        return print_json(&OkList {
+            ok: true,
+            count: items.len(),
+            items,
+        });
+    }
+
+    if items.is_empty() {
+        if !cli.quiet {
+            println!("No stored lookups for {}", args.domain);
+        }
+        return Ok(());
+    }
+
+    for item in &items {
+        if cli.quiet {
+            println!("{}", item.looked_up_at);
+        } else {
+            println!(
+                "{}  registrar={}  expires={}  ns={}",
+                style::dim(&item.looked_up_at),
+                item.registrar,
+                item.expires,
+                item.name_servers.join(",")
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_diff(cli: &Cli, args: &DiffArgs) -> Result<()> {
+    let domain = normalize_domain(&args.domain);
+    let conn = open_db()?;
+    let previous = load_history(&conn, &domain, 1)?.into_iter().next();
+
+    let (raw, final_server) = lookup(cli, &args.domain).await?;
+    let parsed = parse_whois(&args.domain, &final_server, &raw);
+    let current = save_lookup(&conn, &parsed)?;
+
+    let item = match &previous {
+        Some(prev) => {
+            let changes = diff_lookups(prev, &current);
+            DiffItem {
+                domain,
+                previous_lookup: prev.looked_up_at.clone(),
+                current_lookup: current.looked_up_at.clone(),
+                first_lookup: false,
+                changed: !changes.is_empty(),
+                changes,
+            }
+        }
+        None => DiffItem {
+            domain,
+            previous_lookup: String::new(),
+            current_lookup: current.looked_up_at.clone(),
+            first_lookup: true,
+            changed: false,
+            changes: Vec::new(),
+        },
+    };
+
+    output_diff(cli, &item)
+}
+
+/// Resolve the WHOIS server for `target`, query it, and follow a registrar referral when present.
+/// Returns the raw response and the server that produced it.
+async fn lookup(cli: &Cli, target: &str) -> Result<(String, String)> {
+    let server = whois_server_for_target(target);
+    tracing::debug!("querying {target} via {server}");
+
+    let raw = query_whois_with_retry(cli, &server, target).await?;
+
+    // For .com/.net, attempt a two-step referral lookup if the response includes a Whois Server
+    if should_try_referral(&server) {
+        if let Some(referral) = extract_referral_server(&raw) {
+            if referral != server && !referral.is_empty() {
+                tracing::debug!("referral: re-querying via {referral}");
+                if let Ok(referral_raw) = query_whois_with_retry(cli, &referral, target).await {
+                    return Ok((referral_raw, referral));
+                }
+                // fall back to registry response
+            }
+        }
+    }
+
+    Ok((raw, server))
+}
+
+fn diff_lookups(prev: &HistoryItem, current: &HistoryItem) -> Vec<FieldChange> {
+    let fields = [
+        (
+            "registrar",
+            prev.registrar.clone(),
+            current.registrar.clone(),
+        ),
+        ("expires", prev.expires.clone(), current.expires.clone()),
+        (
+            "name_servers",
+            prev.name_servers.join(", "),
+            current.name_servers.join(", "),
+        ),
+        ("status", prev.status.join(", "), current.status.join(", ")),
+    ];
+
+    fields
+        .into_iter()
+        .filter(|(_, before, after)| before != after)
+        .map(|(field, before, after)| FieldChange {
+            field: field.to_string(),
+            before,
+            after,
+        })
+        .collect()
+}
+
 fn should_try_referral(server: &str) -> bool {
     // Verisign is the registry server for .com/.net; their response includes referral info
     server.contains("verisign") || server.contains("iana.org")
@@ -191,19 +509,11 @@ fn extract_referral_server(raw: &str) -> Option<String> {
     })
 }
 
-fn output_raw(cli: &Cli, raw: &str) -> Result<()> {
+fn output_raw(cli: &Cli, target: &str, raw: &str) -> Result<()> {
     if cli.json {
-        #[derive(Serialize)]
-        struct RawItem<'a> {
-            target: &'a str,
-            raw: &'a str,
-        }
-        let payload = JsonSuccessItem {
+        let payload = OkItem {
             ok: true,
-            item: RawItem {
-                target: &cli.target,
-                raw,
-            },
+            item: RawItem { target, raw },
         };
         print_json(&payload)
     } else {
@@ -214,14 +524,14 @@ fn output_raw(cli: &Cli, raw: &str) -> Result<()> {
 
 fn output_expires(cli: &Cli, item: &ExpiresItem) -> Result<()> {
     if cli.json {
-        let payload = JsonSuccessItem { ok: true, item };
+        let payload = OkItem { ok: true, item };
         print_json(&payload)
     } else if cli.quiet {
         println!("{}", item.expires);
         Ok(())
     } else {
-        println!("Domain: {}", item.domain);
-        println!("Expires: {}", item.expires);
+        println!("{}", style::header(format!("Domain: {}", item.domain)));
+        println!("Expires: {}", expiry(&item.expires, item.days_until_expiry));
         println!("Days until expiry: {}", item.days_until_expiry);
         Ok(())
     }
@@ -229,25 +539,72 @@ fn output_expires(cli: &Cli, item: &ExpiresItem) -> Result<()> {
 
 fn output_item(cli: &Cli, item: &WhoisItem) -> Result<()> {
     if cli.json {
-        let payload = JsonSuccessItem { ok: true, item };
+        let payload = OkItem { ok: true, item };
         print_json(&payload)
     } else if cli.quiet {
         println!("{}", item.domain);
         Ok(())
     } else {
-        println!("Domain: {}", item.domain);
+        println!("{}", style::header(format!("Domain: {}", item.domain)));
         println!("Registrar: {}", item.registrar);
-        println!("Created: {}", item.created);
-        println!("Updated: {}", item.updated);
-        println!("Expires: {}", item.expires);
+        println!("Created: {}", style::dim(&item.created));
+        println!("Updated: {}", style::dim(&item.updated));
+        println!("Expires: {}", expiry(&item.expires, item.days_until_expiry));
         println!("Days until expiry: {}", item.days_until_expiry);
-        println!("WHOIS server: {}", item.whois_server);
+        println!("WHOIS server: {}", style::dim(&item.whois_server));
         println!("Name servers: {}", item.name_servers.join(", "));
-        println!("Status: {}", item.status.join(", "));
+        println!("Status: {}", style::dim(item.status.join(", ")));
         Ok(())
     }
 }
 
+/// The expiry date coloured by how close it is; an unknown date stays plain.
+fn expiry(expires: &str, days_until_expiry: i64) -> String {
+    if expires == "unknown" {
+        expires.to_string()
+    } else {
+        style::expiry(expires, days_until_expiry)
+    }
+}
+
+fn output_diff(cli: &Cli, item: &DiffItem) -> Result<()> {
+    if cli.json {
+        let payload = OkItem { ok: true, item };
+        return print_json(&payload);
+    }
+
+    if item.first_lookup {
+        if !cli.quiet {
+            println!(
+                "No previous lookup stored for {}; saved current state as baseline",
+                item.domain
+            );
+        }
+        return Ok(());
+    }
+
+    if !item.changed {
+        if !cli.quiet {
+            println!(
+                "No changes for {} since {}",
+                item.domain, item.previous_lookup
+            );
+        }
+        return Ok(());
+    }
+
+    if !cli.quiet {
+        println!(
+            "Changes for {} since {}:",
+            item.domain, item.previous_lookup
+        );
+    }
+    for change in &item.changes {
+        println!("{}: {} -> {}", change.field, change.before, change.after);
+    }
+    Ok(())
+}
+
 fn print_json<T: Serialize>(value: &T) -> Result<()> {
     let stdout = io::stdout();
     let mut lock = stdout.lock();
@@ -256,20 +613,73 @@ fn print_json<T: Serialize>(value: &T) -> Result<()> {
     Ok(())
 }
 
-async fn query_whois(server: &str, query: &str) -> Result<String> {
-    let mut stream = TcpStream::connect((server, 43)).await.map_err(|e| {
-        WhoisError::ConnectionFailed(format!("failed to connect to WHOIS server {server}: {e}"))
-    })?;
-    stream
-        .write_all(format!("{query}\r\n").as_bytes())
+async fn query_whois_with_retry(cli: &Cli, server: &str, query: &str) -> Result<String> {
+    let timeout = Duration::from_secs(cli.timeout_secs);
+    let mut attempt = 0;
+    loop {
+        match query_whois(server, query, timeout).await {
+            Ok(raw) => return Ok(raw),
+            Err(err) if attempt < cli.retries && is_retryable(&err) => {
+                let delay = retry_delay(attempt);
+                attempt += 1;
+                tracing::debug!(
+                    "attempt {attempt} against {server} failed: {err:#}; retrying in {}ms",
+                    delay.as_millis()
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn is_retryable(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<WhoisError>(),
+        Some(WhoisError::ConnectionFailed(_))
+    )
+}
+
+fn retry_delay(attempt: u32) -> Duration {
+    // 500ms, 1s, 2s, ... capped at 8s
+    Duration::from_millis(500u64.saturating_mul(1 << attempt.min(4)))
+}
+
+async fn query_whois(server: &str, query: &str, timeout: Duration) -> Result<String> {
+    let secs = timeout.as_secs();
+    let mut stream = tokio::time::timeout(timeout, TcpStream::connect((server, 43)))
         .await
-        .map_err(|e| WhoisError::ConnectionFailed(format!("failed to send WHOIS query: {e}")))?;
+        .map_err(|_| {
+            WhoisError::ConnectionFailed(format!(
+                "timed out after {secs}s connecting to WHOIS server {server}"
+            ))
+        })?
+        .map_err(|e| {
+            WhoisError::ConnectionFailed(format!("failed to connect to WHOIS server {server}: {e}"))
+        })?;
+
+    let exchange = async {
+        stream
+            .write_all(format!("{query}\r\n").as_bytes())
+            .await
+            .map_err(|e| {
+                WhoisError::ConnectionFailed(format!("failed to send WHOIS query: {e}"))
+            })?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await.map_err(|e| {
+            WhoisError::ConnectionFailed(format!("failed to read WHOIS response: {e}"))
+        })?;
+        Ok::<_, WhoisError>(response)
+    };
 
-    let mut response = Vec::new();
-    stream
-        .read_to_end(&mut response)
+    let response = tokio::time::timeout(timeout, exchange)
         .await
-        .map_err(|e| WhoisError::ConnectionFailed(format!("failed to read WHOIS response: {e}")))?;
+        .map_err(|_| {
+            WhoisError::ConnectionFailed(format!(
+                "timed out after {secs}s waiting for WHOIS response from {server}"
+            ))
+        })??;
 
     String::from_utf8(response).map_err(|e| {
         WhoisError::LookupFailed(format!("WHOIS response was not valid UTF-8: {e}")).into()
@@ -422,34 +832,108 @@ fn parse_any_date(input: &str) -> Option<DateTime<Utc>> {
         .map(|naive| Utc.from_utc_datetime(&naive))
 }
 
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
+fn normalize_domain(domain: &str) -> String {
+    domain.trim().trim_end_matches('.').to_ascii_lowercase()
 }
 
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
+fn db_path() -> Result<PathBuf> {
+    let base = dirs::data_dir()
+        .ok_or_else(|| WhoisError::Database("could not resolve data directory".to_string()))?;
+    Ok(base.join("dee-whois").join("whois.db"))
+}
 
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
-        }
+fn migrations() -> Migrations<'static> {
+    Migrations::new(vec![M::up(include_str!("../migrations/001_initial.sql"))])
+}
+
+fn open_db() -> Result<Connection> {
+    let path = db_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            WhoisError::Database(format!("failed to create {}: {e}", parent.display()))
+        })?;
     }
+    let mut conn = Connection::open(&path).map_err(|e| WhoisError::Database(e.to_string()))?;
+    migrations()
+        .to_latest(&mut conn)
+        .map_err(|e| WhoisError::Database(e.to_string()))?;
+    Ok(conn)
+}
+
+/// History is a side effect of plain lookups; a broken database must not fail the lookup itself.
+fn record_lookup_best_effort(item: &WhoisItem) {
+    let result = open_db().and_then(|conn| save_lookup(&conn, item));
+    if let Err(err) = result {
+        tracing::debug!("history: failed to record lookup: {err:#}");
+    }
+}
+
+fn save_lookup(conn: &Connection, item: &WhoisItem) -> Result<HistoryItem> {
+    let domain = normalize_domain(&item.domain);
+    let looked_up_at = Utc::now().to_rfc3339();
+    let name_servers = serde_json::to_string(&item.name_servers)?;
+    let status = serde_json::to_string(&item.status)?;
+
+    conn.execute(
+        "INSERT INTO lookups
+         (domain, looked_up_at, registrar, created, expires, updated, name_servers, status, whois_server)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            domain,
+            looked_up_at,
+            item.registrar,
+            item.created,
+            item.expires,
+            item.updated,
+            name_servers,
+            status,
+            item.whois_server
+        ],
+    )
+    .map_err(|e| WhoisError::Database(e.to_string()))?;
+
+    Ok(HistoryItem {
+        id: conn.last_insert_rowid(),
+        domain,
+        looked_up_at,
+        registrar: item.registrar.clone(),
+        created: item.created.clone(),
+        expires: item.expires.clone(),
+        updated: item.updated.clone(),
+        name_servers: item.name_servers.clone(),
+        status: item.status.clone(),
+        whois_server: item.whois_server.clone(),
+    })
+}
+
+fn load_history(conn: &Connection, domain: &str, limit: usize) -> Result<Vec<HistoryItem>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, domain, looked_up_at, registrar, created, expires, updated,
+                    name_servers, status, whois_server
+             FROM lookups WHERE domain = ?1 ORDER BY id DESC LIMIT ?2",
+        )
+        .map_err(|e| WhoisError::Database(e.to_string()))?;
+
+    let rows = stmt
+        .query_map(params![domain, limit as i64], |row| {
+            let name_servers: String = row.get(7)?;
+            let status: String = row.get(8)?;
+            Ok(HistoryItem {
+                id: row.get(0)?,
+                domain: row.get(1)?,
+                looked_up_at: row.get(2)?,
+                registrar: row.get(3)?,
+                created: row.get(4)?,
+                expires: row.get(5)?,
+                updated: row.get(6)?,
+                name_servers: serde_json::from_str(&name_servers).unwrap_or_default(),
+                status: serde_json::from_str(&status).unwrap_or_default(),
+                whois_server: row.get(9)?,
+            })
+        })
+        .map_err(|e| WhoisError::Database(e.to_string()))?;
+
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| WhoisError::Database(e.to_string()).into())
 }
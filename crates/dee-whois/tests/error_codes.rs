@@ -51,3 +51,27 @@ fn invalid_tld_lookup_classifies_network_error() {
     assert_eq!(parsed["ok"], serde_json::json!(false));
     assert_eq!(parsed["code"], serde_json::json!("NETWORK_ERROR"));
 }
+
+#[test]
+fn zero_timeout_gives_invalid_argument() {
+    let out = bin()
+        .args(["--json", "--timeout-secs", "0", "example.com"])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let parsed: serde_json::Value =
+        serde_json::from_str(stdout.trim()).expect("error must be valid JSON on stdout");
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
+#[test]
+fn help_lists_timeout_and_retry_flags() {
+    bin()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--timeout-secs"))
+        .stdout(predicate::str::contains("--retries"));
+}
@@ -0,0 +1,54 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn bin() -> Command {
+    Command::cargo_bin("dee-whois").unwrap()
+}
+
+fn with_home(dir: &TempDir) -> Command {
+    let mut cmd = bin();
+    cmd.env("HOME", dir.path());
+    cmd.env("XDG_DATA_HOME", dir.path().join("data"));
+    cmd
+}
+
+/// history on a fresh database is an empty list, not an error
+#[test]
+fn history_empty_is_ok_list() {
+    let home = TempDir::new().unwrap();
+    let out = with_home(&home)
+        .args(["history", "example.com", "--json"])
+        .output()
+        .unwrap();
+
+    assert!(out.status.success());
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&out.stdout).expect("history must be valid JSON");
+    assert_eq!(parsed["ok"], serde_json::json!(true));
+    assert_eq!(parsed["count"], serde_json::json!(0));
+    assert!(parsed["items"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn history_zero_limit_gives_invalid_argument() {
+    let home = TempDir::new().unwrap();
+    let out = with_home(&home)
+        .args(["history", "example.com", "--limit", "0", "--json"])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
+#[test]
+fn missing_target_gives_invalid_argument() {
+    let out = bin().arg("--json").output().unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["ok"], serde_json::json!(false));
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
@@ -0,0 +1,53 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+fn bin() -> Command {
+    Command::cargo_bin("dee-whois").unwrap()
+}
+
+fn stdout_json(out: &std::process::Output) -> Value {
+    serde_json::from_slice(&out.stdout).expect("stdout must be JSON")
+}
+
+/// A domain that cannot be looked up fails its own item, not the command
+#[test]
+fn piped_domains_get_one_item_each() {
+    let out = bin()
+        .args(["--stdin-json", "--json", "--retries", "0"])
+        .write_stdin("{\"domain\":\"no-such-domain-deedee-zzzz.invalid\"}\n")
+        .output()
+        .unwrap();
+
+    assert!(out.status.success());
+    let parsed = stdout_json(&out);
+    assert_eq!(parsed["count"], json!(1));
+    let item = &parsed["items"][0];
+    assert_eq!(item["domain"], json!("no-such-domain-deedee-zzzz.invalid"));
+    assert_eq!(item["ok"], json!(false));
+    assert_eq!(item["code"], json!("NETWORK_ERROR"));
+}
+
+#[test]
+fn a_failed_upstream_command_fails_the_lookup() {
+    let out = bin()
+        .args(["--stdin-json", "--json"])
+        .write_stdin(r#"{"ok":false,"error":"Auth missing","code":"AUTH_MISSING"}"#)
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    assert_eq!(stdout_json(&out)["code"], json!("UPSTREAM_FAILED"));
+}
+
+#[test]
+fn raw_output_is_not_piped() {
+    let out = bin()
+        .args(["--stdin-json", "--raw", "--json"])
+        .write_stdin(r#"["example.com"]"#)
+        .output()
+        .unwrap();
+
+    assert_eq!(out.status.code(), Some(2));
+    assert_eq!(stdout_json(&out)["code"], json!("INVALID_ARGUMENT"));
+}
@@ -1,4 +1,10 @@
-use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use dee_core::cache::{CacheArgs, CacheCommand};
+use dee_core::http::HttpArgs;
+use dee_core::Format;
 
 #[derive(Debug, Clone, Args)]
 pub struct GlobalArgs {
@@ -13,6 +19,16 @@ pub struct GlobalArgs {
     /// Debug output to stderr
     #[arg(short = 'v', long, global = true)]
     pub verbose: bool,
+
+    #[command(flatten)]
+    pub cache: CacheArgs,
+
+    /// List format for `search` and `history`: json, jsonl, yaml, csv or table
+    #[arg(long, global = true, value_enum)]
+    pub output: Option<Format>,
+
+    #[command(flatten)]
+    pub http: HttpArgs,
 }
 
 #[derive(Debug, Parser)]
@@ -21,7 +37,7 @@ pub struct GlobalArgs {
     version,
     about = "Wikipedia lookup CLI",
     long_about = "dee-wiki - Search Wikipedia and fetch article summaries.",
-    after_help = "EXAMPLES:\n  dee-wiki search \"rust programming\" --limit 5\n  dee-wiki search \"tokio\" --lang en --json\n  dee-wiki get \"Rust (programming language)\" --lang en --json\n  dee-wiki summary \"Berlin\" --lang de\n  dee-wiki summary \"Taylor Swift\" -j"
+    after_help = "EXAMPLES:\n  dee-wiki search \"rust programming\" --limit 5\n  dee-wiki search \"tokio\" --lang en --json\n  dee-wiki get \"Rust (programming language)\" --lang en --json\n  dee-wiki summary \"Berlin\" --lang de\n  dee-wiki summary \"Taylor Swift\" -j\n  dee-wiki get \"Mercury\" --pick 2\n  dee-wiki summary \"Berlin\" --offline\n  dee-wiki article \"Rust (programming language)\"\n  dee-wiki article \"Berlin\" --format text --json\n  dee-wiki image \"Berlin\" --out ./images --width 800\n  dee-wiki history \"Berlin\" --limit 10 --since 7d\n  dee-wiki cache clear"
 )]
 pub struct Cli {
     #[command(flatten)]
@@ -39,6 +55,17 @@ pub enum Commands {
     Get(GetArgs),
     /// Get concise summary payload
     Summary(GetArgs),
+    /// Get the full article as markdown, plain text or HTML
+    Article(ArticleArgs),
+    /// Download the page's lead image with its license and attribution
+    Image(ImageArgs),
+    /// List a page's recent revisions
+    History(HistoryArgs),
+    /// Manage cached summaries and searches
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
 }
 
 #[derive(Debug, Clone, Args)]
@@ -63,4 +90,101 @@ pub struct GetArgs {
     /// Wikipedia language code
     #[arg(long, default_value = "en")]
     pub lang: String,
+
+    /// On a disambiguation page, fetch the Nth candidate (1-based) instead of listing them
+    #[arg(long)]
+    pub pick: Option<usize>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ArticleArgs {
+    /// Exact page title
+    pub title: String,
+
+    /// Wikipedia language code
+    #[arg(long, default_value = "en")]
+    pub lang: String,
+
+    /// Output format of the article body
+    #[arg(long, value_enum, default_value_t = ArticleFormat::Md)]
+    pub format: ArticleFormat,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ImageArgs {
+    /// Exact page title
+    pub title: String,
+
+    /// Wikipedia language code
+    #[arg(long, default_value = "en")]
+    pub lang: String,
+
+    /// Directory to save the image in (created if missing)
+    #[arg(long, default_value = ".")]
+    pub out: PathBuf,
+
+    /// Download a thumbnail this many pixels wide instead of the original
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub width: Option<u32>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct HistoryArgs {
+    /// Exact page title
+    pub title: String,
+
+    /// Wikipedia language code
+    #[arg(long, default_value = "en")]
+    pub lang: String,
+
+    /// Maximum number of revisions, newest first
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..=500))]
+    pub limit: u32,
+
+    /// Only revisions at or after this time: 2024-01-31, an RFC 3339 timestamp, or 36h / 7d ago
+    #[arg(long, value_parser = parse_since)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// `--since` as an instant: a date (midnight UTC), an RFC 3339 timestamp, or `36h`/`7d`
+/// before now.
+pub fn parse_since(value: &str) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    // Relative: hours or days before now
+    let hours = match value.char_indices().last() {
+        Some((idx, 'h')) => value[..idx].parse::<u32>().ok().map(i64::from),
+        Some((idx, 'd')) => value[..idx].parse::<u32>().ok().map(|n| i64::from(n) * 24),
+        _ => None,
+    };
+    if let Some(hours) = hours {
+        return Ok(Utc::now() - Duration::hours(hours));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| {
+            "expected a date like 2024-01-31, an RFC 3339 timestamp, or 36h / 7d".to_string()
+        })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ArticleFormat {
+    /// Markdown with `#` headings
+    Md,
+    /// Plain text
+    Text,
+    /// Parsoid HTML as served by Wikipedia
+    Html,
+}
+
+impl ArticleFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Md => "md",
+            Self::Text => "text",
+            Self::Html => "html",
+        }
+    }
 }
@@ -1,48 +1,46 @@
 use std::borrow::Cow;
 
+use dee_core::cache::{Cache, CacheCommand, CacheError};
+use dee_core::http::{base_url, Http};
+use dee_core::{print_json, print_list, style, OkItem};
 use reqwest::Url;
 use serde_json::Value;
 
 use crate::{
-    cli::{GetArgs, SearchArgs},
+    cli::{ArticleArgs, GetArgs, HistoryArgs, ImageArgs, SearchArgs},
     models::{
-        AppError, ItemResponse, OutputMode, SearchItem, SearchResponse, SummaryApi, WikiItem,
+        AppError, ArticleItem, ArticleResponse, Candidate, DisambiguationResponse, HistoryResponse,
+        ImageItem, ImageResponse, OutputMode, Revision, SearchItem, SearchResponse, SummaryApi,
+        WikiItem,
     },
+    render,
 };
 
+/// How long a cached summary or search is served before it is fetched again
+const CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+/// `{lang}` is replaced with the `--lang` code
+const WIKI_BASE: &str = "https://{lang}.wikipedia.org";
+const WIKI_BASE_ENV: &str = "DEE_WIKI_BASE_URL";
+
 pub fn search(args: &SearchArgs, mode: &OutputMode) -> Result<(), AppError> {
     validate_lang(&args.lang)?;
 
-    if mode.verbose {
-        eprintln!(
-            "debug: searching query='{}' lang='{}' limit={}",
-            args.query, args.lang, args.limit
-        );
-    }
-
-    let mut url = Url::parse(&format!("https://{}.wikipedia.org/w/api.php", args.lang))
-        .map_err(|_| AppError::Request)?;
-    {
-        let mut pairs = url.query_pairs_mut();
-        pairs
-            .append_pair("action", "opensearch")
-            .append_pair("search", args.query.as_str())
-            .append_pair("limit", &args.limit.to_string())
-            .append_pair("format", "json");
-    }
-
-    let client = reqwest::blocking::Client::builder()
-        .user_agent("dee-wiki/0.1.0 (https://dee.ink)")
-        .build()
-        .map_err(|_| AppError::Request)?;
-    let value: Value = client
-        .get(url)
-        .send()
-        .map_err(|_| AppError::Request)?
-        .error_for_status()
-        .map_err(|_| AppError::Request)?
-        .json()
-        .map_err(|_| AppError::Parse)?;
+    tracing::debug!(
+        "searching query='{}' lang='{}' limit={}",
+        args.query,
+        args.lang,
+        args.limit
+    );
+
+    let url = api_url(
+        &args.lang,
+        &[
+            ("action", "opensearch"),
+            ("search", args.query.as_str()),
+            ("limit", &args.limit.to_string()),
+        ],
+    )?;
+    let (value, cached) = cached_json(&url, mode, || api_json(url.clone(), mode))?;
 
     let titles = as_array_ref(&value, 1)?;
     let descriptions = as_array_ref(&value, 2)?;
@@ -68,11 +66,14 @@ pub fn search(args: &SearchArgs, mode: &OutputMode) -> Result<(), AppError> {
     let response = SearchResponse {
         ok: true,
         count: items.len(),
+        cached: mode.verbose.then_some(cached),
         items,
     };
 
-    if mode.json {
-        print_json(&response).map_err(|_| AppError::Parse)?;
+    if let Some(format) = mode.output {
+        print_list(&response.items, format);
+    } else if mode.json {
+        print_json(&response);
     } else {
         print_search_human(&response, mode.quiet);
     }
@@ -91,40 +92,35 @@ pub fn summary(args: &GetArgs, mode: &OutputMode) -> Result<(), AppError> {
 fn fetch_summary(args: &GetArgs, mode: &OutputMode, concise: bool) -> Result<(), AppError> {
     validate_lang(&args.lang)?;
 
-    if mode.verbose {
-        eprintln!(
-            "debug: fetching title='{}' lang='{}'",
-            args.title, args.lang
-        );
-    }
-
-    let mut url = Url::parse(&format!("https://{}.wikipedia.org/api/rest_v1", args.lang))
-        .map_err(|_| AppError::Request)?;
-    {
-        let mut segments = url.path_segments_mut().map_err(|_| AppError::Request)?;
-        segments.extend(["page", "summary", args.title.as_str()]);
-    }
-
-    if mode.verbose {
-        eprintln!("debug: request_url={url}");
-    }
-
-    let client = reqwest::blocking::Client::builder()
-        .user_agent("dee-wiki/0.1.0 (https://dee.ink)")
-        .build()
-        .map_err(|_| AppError::Request)?;
-
-    let response = client.get(url).send().map_err(|_| AppError::Request)?;
-    let status = response.status();
-    if status.as_u16() == 404 {
-        return Err(AppError::NotFound);
-    }
-    if !status.is_success() {
-        return Err(AppError::Request);
+    tracing::debug!("fetching title='{}' lang='{}'", args.title, args.lang);
+
+    let (mut response, mut cached) = summary_api(&args.title, &args.lang, mode)?;
+
+    if response.kind.as_deref() == Some("disambiguation") {
+        let candidates = disambiguation_candidates(&args.title, &args.lang, mode)?;
+        let Some(pick) = args.pick else {
+            let out = DisambiguationResponse {
+                ok: true,
+                disambiguation: true,
+                title: response.title.unwrap_or_else(|| args.title.clone()),
+                count: candidates.len(),
+                items: candidates,
+            };
+            if mode.json {
+                print_json(&out);
+            } else {
+                print_disambiguation_human(&out, mode.quiet);
+            }
+            return Ok(());
+        };
+        let candidate = pick
+            .checked_sub(1)
+            .and_then(|idx| candidates.get(idx))
+            .ok_or(AppError::InvalidPick(pick, candidates.len()))?;
+        tracing::debug!("picked '{}'", candidate.title);
+        (response, cached) = summary_api(&candidate.title, &args.lang, mode)?;
     }
 
-    let response: SummaryApi = response.json().map_err(|_| AppError::Parse)?;
-
     let title = response.title.unwrap_or_default();
     let mut extract = response.extract.unwrap_or_default();
     if concise {
@@ -152,12 +148,13 @@ fn fetch_summary(args: &GetArgs, mode: &OutputMode, concise: bool) -> Result<(),
         url: page_url,
         thumbnail,
         lang: args.lang.clone(),
+        cached: mode.verbose.then_some(cached),
     };
 
-    let out = ItemResponse { ok: true, item };
+    let out = OkItem::new(item);
 
     if mode.json {
-        print_json(&out).map_err(|_| AppError::Parse)?;
+        print_json(&out);
     } else {
         print_item_human(&out, mode.quiet);
     }
@@ -165,6 +162,435 @@ fn fetch_summary(args: &GetArgs, mode: &OutputMode, concise: bool) -> Result<(),
     Ok(())
 }
 
+/// The summary payload and whether it came from the cache.
+fn summary_api(title: &str, lang: &str, mode: &OutputMode) -> Result<(SummaryApi, bool), AppError> {
+    let url = rest_url(lang, &["page", "summary", title])?;
+    let (value, cached) = cached_json(&url, mode, || {
+        rest_response(send(url.clone(), mode)?)?
+            .json()
+            .map_err(|_| AppError::Parse)
+    })?;
+    let summary = serde_json::from_value(value).map_err(|_| AppError::Parse)?;
+    Ok((summary, cached))
+}
+
+/// `url`'s response from the cache when younger than the TTL (any age with
+/// `--offline`), else from `fetch`, storing the fresh response.
+fn cached_json(
+    url: &Url,
+    mode: &OutputMode,
+    fetch: impl FnOnce() -> Result<Value, AppError>,
+) -> Result<(Value, bool), AppError> {
+    let cache = response_cache();
+    if let Some(hit) = cache.lookup::<Value>(url.as_str(), &mode.cache)? {
+        tracing::debug!("cached {url} ({}s old)", hit.age_secs);
+        return Ok((hit.value, true));
+    }
+
+    let body = fetch()?;
+    cache.put(url.as_str(), &body);
+    Ok((body, false))
+}
+
+fn response_cache() -> Cache {
+    Cache::new("dee-wiki").ttl_secs(CACHE_TTL_SECS)
+}
+
+pub fn cache(command: CacheCommand, mode: &OutputMode) -> Result<(), AppError> {
+    Ok(dee_core::cache::run(&response_cache(), command, mode.json)?)
+}
+
+fn client(mode: &OutputMode) -> Result<reqwest::blocking::Client, AppError> {
+    Http::new("dee-wiki", env!("CARGO_PKG_VERSION"))
+        .args(&mode.http)
+        .blocking()
+        .map_err(|_| AppError::Request)
+}
+
+/// The pages a disambiguation page points to, in page order.
+fn disambiguation_candidates(
+    title: &str,
+    lang: &str,
+    mode: &OutputMode,
+) -> Result<Vec<Candidate>, AppError> {
+    let html = rest_get(lang, &["page", "html", title], mode)?
+        .text()
+        .map_err(|_| AppError::Parse)?;
+    Ok(render::candidate_links(&html)
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (title, description))| Candidate {
+            index: idx + 1,
+            url: page_url(lang, &title),
+            title,
+            description,
+        })
+        .collect())
+}
+
+/// The wiki for `lang`: `https://<lang>.wikipedia.org` unless `DEE_WIKI_BASE_URL`
+/// says otherwise, with the same `{lang}` placeholder.
+fn wiki_root(lang: &str) -> String {
+    let configured = std::env::var(WIKI_BASE_ENV).ok();
+    base_url(configured.as_deref(), WIKI_BASE).replace("{lang}", lang)
+}
+
+/// A REST API path under `https://<lang>.wikipedia.org/api/rest_v1`.
+fn rest_url(lang: &str, segments: &[&str]) -> Result<Url, AppError> {
+    let mut url =
+        Url::parse(&format!("{}/api/rest_v1", wiki_root(lang))).map_err(|_| AppError::Request)?;
+    url.path_segments_mut()
+        .map_err(|_| AppError::Request)?
+        .extend(segments);
+    Ok(url)
+}
+
+/// `https://<lang>.wikipedia.org/w/api.php` with `params`, as JSON.
+fn api_url(lang: &str, params: &[(&str, &str)]) -> Result<Url, AppError> {
+    let mut url =
+        Url::parse(&format!("{}/w/api.php", wiki_root(lang))).map_err(|_| AppError::Request)?;
+    url.query_pairs_mut()
+        .extend_pairs(params)
+        .append_pair("format", "json");
+    Ok(url)
+}
+
+/// GET `url`; never with `--offline`.
+fn send(url: Url, mode: &OutputMode) -> Result<reqwest::blocking::Response, AppError> {
+    if mode.cache.offline {
+        return Err(CacheError::OfflineMiss { key: url.into() }.into());
+    }
+    tracing::debug!("request_url={url}");
+    client(mode)?.get(url).send().map_err(|_| AppError::Request)
+}
+
+/// A REST API answer; 404 is `NotFound`.
+fn rest_response(
+    response: reqwest::blocking::Response,
+) -> Result<reqwest::blocking::Response, AppError> {
+    let status = response.status();
+    if status.as_u16() == 404 {
+        return Err(AppError::NotFound);
+    }
+    if !status.is_success() {
+        return Err(AppError::Request);
+    }
+    Ok(response)
+}
+
+/// GET a REST API path under `https://<lang>.wikipedia.org/api/rest_v1`; 404 is
+/// `NotFound`.
+fn rest_get(
+    lang: &str,
+    segments: &[&str],
+    mode: &OutputMode,
+) -> Result<reqwest::blocking::Response, AppError> {
+    rest_response(send(rest_url(lang, segments)?, mode)?)
+}
+
+/// GET `https://<lang>.wikipedia.org/w/api.php` with `params`, as JSON.
+fn api_get(lang: &str, params: &[(&str, &str)], mode: &OutputMode) -> Result<Value, AppError> {
+    api_json(api_url(lang, params)?, mode)
+}
+
+fn api_json(url: Url, mode: &OutputMode) -> Result<Value, AppError> {
+    send(url, mode)?
+        .error_for_status()
+        .map_err(|_| AppError::Request)?
+        .json()
+        .map_err(|_| AppError::Parse)
+}
+
+fn page_url(lang: &str, title: &str) -> String {
+    format!("{}/wiki/{}", wiki_root(lang), title.replace(' ', "_"))
+}
+
+pub fn article(args: &ArticleArgs, mode: &OutputMode) -> Result<(), AppError> {
+    validate_lang(&args.lang)?;
+
+    tracing::debug!(
+        "fetching article title='{}' lang='{}' format={}",
+        args.title,
+        args.lang,
+        args.format.as_str()
+    );
+
+    // Parsoid HTML of the whole page; redirects are followed to the target page
+    let html = rest_get(&args.lang, &["page", "html", args.title.as_str()], mode)?
+        .text()
+        .map_err(|_| AppError::Parse)?;
+
+    let rendered = render::render(&html, args.format);
+    let title = if rendered.title.is_empty() {
+        args.title.clone()
+    } else {
+        rendered.title
+    };
+
+    let item = ArticleItem {
+        url: page_url(&args.lang, &title),
+        title,
+        lang: args.lang.clone(),
+        format: args.format.as_str().to_string(),
+        sections: rendered.sections,
+        content: rendered.content,
+    };
+
+    let out = ArticleResponse { ok: true, item };
+
+    if mode.json {
+        print_json(&out);
+    } else {
+        print_article_human(&out, mode.quiet);
+    }
+
+    Ok(())
+}
+
+pub fn image(args: &ImageArgs, mode: &OutputMode) -> Result<(), AppError> {
+    validate_lang(&args.lang)?;
+
+    tracing::debug!(
+        "fetching lead image title='{}' lang='{}' width={:?}",
+        args.title,
+        args.lang,
+        args.width
+    );
+
+    // The lead image is the one the page summary and search previews use
+    let value = api_get(
+        &args.lang,
+        &[
+            ("action", "query"),
+            ("prop", "pageimages"),
+            ("piprop", "name"),
+            ("redirects", "1"),
+            ("formatversion", "2"),
+            ("titles", args.title.as_str()),
+        ],
+        mode,
+    )?;
+    let page = value.pointer("/query/pages/0").ok_or(AppError::Parse)?;
+    if page.get("missing").is_some() || page.get("invalid").is_some() {
+        return Err(AppError::NotFound);
+    }
+    let title = page
+        .get("title")
+        .map(to_string_or_empty)
+        .unwrap_or_else(|| args.title.clone());
+    let name = page
+        .get("pageimage")
+        .and_then(Value::as_str)
+        .ok_or(AppError::NoImage)?
+        .to_string();
+
+    let file = format!("File:{name}");
+    let width = args.width.map(|w| w.to_string());
+    let mut params = vec![
+        ("action", "query"),
+        ("prop", "imageinfo"),
+        ("iiprop", "url|size|mime|extmetadata"),
+        (
+            "iiextmetadatafilter",
+            "LicenseShortName|LicenseUrl|Artist|Credit|AttributionRequired",
+        ),
+        ("formatversion", "2"),
+        ("titles", file.as_str()),
+    ];
+    if let Some(width) = &width {
+        params.push(("iiurlwidth", width.as_str()));
+    }
+    let value = api_get(&args.lang, &params, mode)?;
+    let info = value
+        .pointer("/query/pages/0/imageinfo/0")
+        .ok_or(AppError::NoImage)?;
+
+    let text = |key: &str| info.get(key).map(to_string_or_empty).unwrap_or_default();
+    let number = |key: &str| info.get(key).and_then(Value::as_u64).unwrap_or_default();
+    let meta = |key: &str| {
+        info.pointer(&format!("/extmetadata/{key}/value"))
+            .map(to_string_or_empty)
+            .map(|html| render::fragment_text(&html))
+            .unwrap_or_default()
+    };
+
+    // MediaWiki answers with the original when the width is not smaller
+    let thumb = text("thumburl");
+    let (url, width, height) = if args.width.is_some() && !thumb.is_empty() {
+        (thumb, number("thumbwidth"), number("thumbheight"))
+    } else {
+        (text("url"), number("width"), number("height"))
+    };
+    if url.is_empty() {
+        return Err(AppError::Parse);
+    }
+
+    tracing::debug!("downloading {url}");
+    let bytes = client(mode)?
+        .get(url.as_str())
+        .send()
+        .map_err(|_| AppError::Request)?
+        .error_for_status()
+        .map_err(|_| AppError::Request)?
+        .bytes()
+        .map_err(|_| AppError::Request)?;
+
+    std::fs::create_dir_all(&args.out).map_err(|e| AppError::Io(e.to_string()))?;
+    let path = args.out.join(image_file_name(&name, &url));
+    std::fs::write(&path, &bytes).map_err(|e| AppError::Io(e.to_string()))?;
+
+    let item = ImageItem {
+        title,
+        file,
+        path: path.display().to_string(),
+        url,
+        description_url: text("descriptionurl"),
+        mime: text("mime"),
+        width,
+        height,
+        bytes: bytes.len() as u64,
+        license: meta("LicenseShortName"),
+        license_url: meta("LicenseUrl"),
+        artist: meta("Artist"),
+        credit: meta("Credit"),
+        attribution_required: meta("AttributionRequired") == "true",
+    };
+
+    let out = ImageResponse { ok: true, item };
+
+    if mode.json {
+        print_json(&out);
+    } else {
+        print_image_human(&out, mode.quiet);
+    }
+
+    Ok(())
+}
+
+/// The file name to save as: the file's own name, or for a thumbnail the name
+/// Wikimedia gives it (`800px-Berlin.jpg`, `800px-Logo.svg.png`).
+fn image_file_name(name: &str, url: &str) -> String {
+    let last = url.rsplit('/').next().unwrap_or_default();
+    let saved = match last.split_once("px-") {
+        Some((px, _)) if !px.is_empty() && px.bytes().all(|b| b.is_ascii_digit()) => {
+            let ext = |s: &str| s.rsplit_once('.').map(|(_, e)| e.to_ascii_lowercase());
+            let mut saved = format!("{px}px-{name}");
+            if let Some(thumb_ext) = ext(last).filter(|e| Some(e) != ext(name).as_ref()) {
+                saved.push('.');
+                saved.push_str(&thumb_ext);
+            }
+            saved
+        }
+        _ => name.to_string(),
+    };
+    saved.replace(['/', '\\'], "_").replace(' ', "_")
+}
+
+pub fn history(args: &HistoryArgs, mode: &OutputMode) -> Result<(), AppError> {
+    validate_lang(&args.lang)?;
+
+    tracing::debug!(
+        "fetching history title='{}' lang='{}' limit={} since={:?}",
+        args.title,
+        args.lang,
+        args.limit,
+        args.since
+    );
+
+    // One extra revision, so the oldest listed one has a size to diff against
+    let rvlimit = (args.limit + 1).to_string();
+    let value = api_get(
+        &args.lang,
+        &[
+            ("action", "query"),
+            ("prop", "revisions"),
+            ("rvprop", "ids|timestamp|user|comment|size|flags"),
+            ("rvlimit", rvlimit.as_str()),
+            ("redirects", "1"),
+            ("formatversion", "2"),
+            ("titles", args.title.as_str()),
+        ],
+        mode,
+    )?;
+    let page = value.pointer("/query/pages/0").ok_or(AppError::Parse)?;
+    if page.get("missing").is_some() || page.get("invalid").is_some() {
+        return Err(AppError::NotFound);
+    }
+    let title = page
+        .get("title")
+        .map(to_string_or_empty)
+        .unwrap_or_else(|| args.title.clone());
+
+    // Timestamps are all `YYYY-MM-DDTHH:MM:SSZ`, so they compare as strings
+    let since = args
+        .since
+        .map(|dt| dt.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+    let items: Vec<Revision> = revisions(page)
+        .into_iter()
+        .filter(|rev| since.as_ref().is_none_or(|since| rev.timestamp >= *since))
+        .take(args.limit as usize)
+        .collect();
+
+    let out = HistoryResponse {
+        ok: true,
+        url: page_url(&args.lang, &title),
+        title,
+        count: items.len(),
+        items,
+    };
+
+    if let Some(format) = mode.output {
+        print_list(&out.items, format);
+    } else if mode.json {
+        print_json(&out);
+    } else {
+        print_history_human(&out, mode.quiet);
+    }
+
+    Ok(())
+}
+
+/// A page's revisions, newest first, each with its size change against the next
+/// older one. The page's first revision counts as adding its whole size.
+fn revisions(page: &Value) -> Vec<Revision> {
+    let raw = page
+        .get("revisions")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let size = |rev: &Value| rev.get("size").and_then(Value::as_u64).unwrap_or_default();
+
+    raw.iter()
+        .enumerate()
+        .map(|(idx, rev)| {
+            let older = match raw.get(idx + 1) {
+                Some(older) => size(older),
+                // The first revision, or an older one left out of the response
+                None if rev.get("parentid").and_then(Value::as_u64) == Some(0) => 0,
+                None => size(rev),
+            };
+            Revision {
+                revid: rev.get("revid").and_then(Value::as_u64).unwrap_or_default(),
+                timestamp: rev
+                    .get("timestamp")
+                    .map(to_string_or_empty)
+                    .unwrap_or_default(),
+                user: rev.get("user").map(to_string_or_empty).unwrap_or_default(),
+                comment: rev
+                    .get("comment")
+                    .map(to_string_or_empty)
+                    .unwrap_or_default(),
+                size: size(rev),
+                size_delta: size(rev) as i64 - older as i64,
+                minor: rev
+                    .get("minor")
+                    .and_then(Value::as_bool)
+                    .unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
 fn validate_lang(lang: &str) -> Result<(), AppError> {
     let valid = !lang.is_empty() && lang.chars().all(|ch| ch.is_ascii_alphabetic() || ch == '-');
     if valid {
@@ -215,41 +641,180 @@ fn first_sentence(input: &str) -> Cow<'_, str> {
     }
 }
 
-fn print_json<T: serde::Serialize>(value: &T) -> anyhow::Result<()> {
-    println!("{}", serde_json::to_string(value)?);
-    Ok(())
-}
-
 fn print_search_human(response: &SearchResponse, quiet: bool) {
     if !quiet {
         println!("Found {} results", response.count);
     }
 
     for item in &response.items {
-        println!("{}", item.title);
+        if quiet {
+            println!("{}", item.title);
+        } else {
+            println!("{}", style::header(&item.title));
+        }
         if !item.description.is_empty() {
             println!("  {}", item.description);
         }
         if !item.url.is_empty() {
-            println!("  {}", item.url);
+            if quiet {
+                println!("  {}", item.url);
+            } else {
+                println!("  {}", style::dim(&item.url));
+            }
         }
     }
 }
 
-fn print_item_human(response: &ItemResponse, quiet: bool) {
+fn print_item_human(response: &OkItem<WikiItem>, quiet: bool) {
     let item = &response.item;
 
-    println!("{}", item.title);
+    if quiet {
+        println!("{}", item.title);
+    } else {
+        println!("{}", style::header(&item.title));
+    }
     if !item.extract.is_empty() {
         println!("{}", item.extract);
     }
     if !quiet {
         if !item.url.is_empty() {
-            println!("{}", item.url);
+            println!("{}", style::dim(&item.url));
         }
         if !item.thumbnail.is_empty() {
-            println!("thumbnail: {}", item.thumbnail);
+            println!("{}", style::dim(format!("thumbnail: {}", item.thumbnail)));
+        }
+        println!("{}", style::dim(format!("lang: {}", item.lang)));
+    }
+}
+
+fn print_article_human(response: &ArticleResponse, quiet: bool) {
+    let item = &response.item;
+
+    if !quiet && item.format != "html" {
+        if item.format == "md" {
+            println!("# {}", item.title);
+        } else {
+            println!("{}", item.title);
         }
-        println!("lang: {}", item.lang);
+        println!();
+    }
+    println!("{}", item.content);
+    if !quiet {
+        println!();
+        println!("{}", item.url);
+    }
+}
+
+fn print_image_human(response: &ImageResponse, quiet: bool) {
+    let item = &response.item;
+
+    println!("{}", item.path);
+    if quiet {
+        return;
+    }
+    println!(
+        "{} ({}x{}, {} bytes)",
+        item.file, item.width, item.height, item.bytes
+    );
+    if !item.license.is_empty() {
+        println!("license: {}", item.license);
+    }
+    if !item.artist.is_empty() {
+        println!("author: {}", item.artist);
+    }
+    if !item.credit.is_empty() {
+        println!("credit: {}", item.credit);
+    }
+    if item.attribution_required {
+        println!("attribution required");
+    }
+    println!("{}", item.description_url);
+}
+
+fn print_history_human(response: &HistoryResponse, quiet: bool) {
+    if !quiet {
+        println!("{} — {} revisions", response.title, response.count);
+    }
+    for rev in &response.items {
+        let when = rev.timestamp.replacen('T', " ", 1).replace('Z', "");
+        let minor = if rev.minor { " m" } else { "" };
+        let mut line = format!("{when}  {:>+7}{minor}  {}", rev.size_delta, rev.user);
+        if !rev.comment.is_empty() {
+            line.push_str("  ");
+            line.push_str(&rev.comment);
+        }
+        println!("{line}");
+    }
+    if !quiet {
+        println!("{}", response.url);
+    }
+}
+
+fn print_disambiguation_human(response: &DisambiguationResponse, quiet: bool) {
+    if !quiet {
+        println!(
+            "\"{}\" may refer to ({} pages):",
+            response.title, response.count
+        );
+    }
+    for item in &response.items {
+        if quiet {
+            println!("{}", item.title);
+            continue;
+        }
+        if item.description.is_empty() || item.description == item.title {
+            println!("{:>3}. {}", item.index, item.title);
+        } else {
+            println!("{:>3}. {} — {}", item.index, item.title, item.description);
+        }
+    }
+    if !quiet {
+        println!("Use --pick N to fetch one.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revision_size_deltas() {
+        let page = serde_json::json!({"revisions": [
+            {"revid": 3, "parentid": 2, "timestamp": "2024-02-01T00:00:00Z", "user": "B", "size": 90, "minor": true},
+            {"revid": 2, "parentid": 1, "timestamp": "2024-01-02T00:00:00Z", "user": "A", "comment": "more", "size": 100},
+            {"revid": 1, "parentid": 0, "timestamp": "2024-01-01T00:00:00Z", "user": "A", "size": 40}
+        ]});
+        let revs = revisions(&page);
+        let deltas: Vec<i64> = revs.iter().map(|r| r.size_delta).collect();
+        assert_eq!(deltas, [-10, 60, 40]);
+        assert!(revs[0].minor);
+        assert_eq!(revs[1].comment, "more");
+        assert_eq!(revs[0].comment, "");
+    }
+
+    #[test]
+    fn thumbnail_file_names() {
+        let base = "https://upload.wikimedia.org/wikipedia/commons";
+        assert_eq!(
+            image_file_name(
+                "Berlin Skyline.jpg",
+                &format!("{base}/a/ab/Berlin_Skyline.jpg")
+            ),
+            "Berlin_Skyline.jpg"
+        );
+        assert_eq!(
+            image_file_name(
+                "Berlin Skyline.jpg",
+                &format!("{base}/thumb/a/ab/Berlin_Skyline.jpg/800px-Berlin_Skyline.jpg")
+            ),
+            "800px-Berlin_Skyline.jpg"
+        );
+        assert_eq!(
+            image_file_name(
+                "Logo.svg",
+                &format!("{base}/thumb/1/1c/Logo.svg/640px-Logo.svg.png")
+            ),
+            "640px-Logo.svg.png"
+        );
     }
 }
@@ -1,82 +1,75 @@
 mod cli;
 mod commands;
 mod models;
+mod render;
 
 use std::process::ExitCode;
 
-use clap::Parser;
+use dee_core::schema::{self, Schemas};
+use dee_core::{report, ErrorCode, Format, OkItem};
+use schemars::JsonSchema;
 
 use crate::{
     cli::{Cli, Commands},
-    models::{ErrorJson, OutputMode},
+    models::{
+        ArticleResponse, DisambiguationResponse, HistoryResponse, ImageResponse, OutputMode,
+        SearchResponse, WikiItem,
+    },
 };
 
+/// `get` and `summary` print one of these: the candidates when the title is
+/// a disambiguation page and `--pick` is not given.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)] // only described; the variants are printed directly
+enum SummaryOutput {
+    Page(OkItem<WikiItem>),
+    Disambiguation(DisambiguationResponse),
+}
+
+/// What each command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .custom::<SearchResponse>("search")
+        .custom::<SummaryOutput>("get")
+        .custom::<SummaryOutput>("summary")
+        .custom::<ArticleResponse>("article")
+        .custom::<ImageResponse>("image")
+        .custom::<HistoryResponse>("history")
+        .cache()
+}
+
 fn main() -> ExitCode {
-    let cli = parse_cli();
+    schema::exit_on_schema(schemas);
+    let cli: Cli = dee_core::cli::parse();
 
     let output_mode = OutputMode {
         json: cli.global.json,
         quiet: cli.global.quiet,
         verbose: cli.global.verbose,
+        cache: cli.global.cache,
+        output: cli.global.output,
+        http: cli.global.http,
     };
 
     let result = match cli.command {
         Commands::Search(args) => commands::search(&args, &output_mode),
         Commands::Get(args) => commands::get(&args, &output_mode),
         Commands::Summary(args) => commands::summary(&args, &output_mode),
+        Commands::Article(args) => commands::article(&args, &output_mode),
+        Commands::Image(args) => commands::image(&args, &output_mode),
+        Commands::History(args) => commands::history(&args, &output_mode),
+        Commands::Cache { command } => commands::cache(command, &output_mode),
     };
 
     match result {
         Ok(()) => ExitCode::SUCCESS,
         Err(err) => {
-            if output_mode.json {
-                let body = ErrorJson {
-                    ok: false,
-                    error: err.to_string(),
-                    code: err.code().to_string(),
-                };
-                match serde_json::to_string(&body) {
-                    Ok(text) => println!("{text}"),
-                    Err(_) => println!(
-                        r#"{{"ok":false,"error":"Internal serialization error","code":"SERIALIZE"}}"#
-                    ),
-                }
-            } else {
-                eprintln!("error: {err}");
-            }
-            ExitCode::from(1)
-        }
-    }
-}
-
-fn parse_cli() -> Cli {
-    match Cli::try_parse() {
-        Ok(cli) => cli,
-        Err(err) => handle_clap_parse_error(err),
-    }
-}
-
-fn handle_clap_parse_error(err: clap::Error) -> ! {
-    use clap::error::ErrorKind;
-
-    match err.kind() {
-        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-            let _ = err.print();
-            std::process::exit(0);
-        }
-        _ => {
-            let wants_json = std::env::args().any(|arg| arg == "--json" || arg == "-j");
-            if wants_json {
-                let payload = serde_json::json!({
-                    "ok": false,
-                    "error": err.to_string().trim(),
-                    "code": "INVALID_ARGUMENT"
-                });
-                println!("{payload}");
-            } else {
-                let _ = err.print();
-            }
-            std::process::exit(2);
+            report(
+                &err,
+                Format::json_errors(output_mode.output, output_mode.json),
+            );
+            ExitCode::from(err.exit_code() as u8)
         }
     }
 }
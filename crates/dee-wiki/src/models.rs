@@ -1,3 +1,7 @@
+use dee_core::cache::{CacheArgs, CacheError};
+use dee_core::http::HttpArgs;
+use dee_core::{ErrorCode, Format};
+use schemars::JsonSchema;
 use serde::Serialize;
 use thiserror::Error;
 
@@ -6,6 +10,12 @@ pub struct OutputMode {
     pub json: bool,
     pub quiet: bool,
     pub verbose: bool,
+    /// `--no-cache` and `--offline`
+    pub cache: CacheArgs,
+    /// `--output`: the list's items alone, in this format
+    pub output: Option<Format>,
+    /// `--timeout-secs` and `--retries`
+    pub http: HttpArgs,
 }
 
 #[derive(Debug, Error)]
@@ -18,27 +28,32 @@ pub enum AppError {
     NotFound,
     #[error("Invalid language code")]
     InvalidLanguage,
+    #[error("The page has no lead image")]
+    NoImage,
+    #[error("Could not save the image: {0}")]
+    Io(String),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+    #[error("--pick {0} is out of range (the page lists {1} candidates)")]
+    InvalidPick(usize, usize),
 }
 
-impl AppError {
-    pub fn code(&self) -> &'static str {
+impl ErrorCode for AppError {
+    fn code(&self) -> &'static str {
         match self {
             Self::Request => "REQUEST_FAILED",
             Self::Parse => "PARSE_FAILED",
             Self::NotFound => "NOT_FOUND",
             Self::InvalidLanguage => "INVALID_LANGUAGE",
+            Self::InvalidPick(..) => "INVALID_ARGUMENT",
+            Self::NoImage => "NO_IMAGE",
+            Self::Io(_) => "IO_ERROR",
+            Self::Cache(err) => err.code(),
         }
     }
 }
 
-#[derive(Debug, Serialize)]
-pub struct ErrorJson {
-    pub ok: bool,
-    pub error: String,
-    pub code: String,
-}
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct SearchItem {
     pub title: String,
     pub description: String,
@@ -46,30 +61,131 @@ pub struct SearchItem {
     pub lang: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct SearchResponse {
     pub ok: bool,
     pub count: usize,
+    /// Served from the local cache; only with `--verbose`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached: Option<bool>,
     pub items: Vec<SearchItem>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct WikiItem {
     pub title: String,
     pub extract: String,
     pub url: String,
     pub thumbnail: String,
     pub lang: String,
+    /// Served from the local cache; only with `--verbose`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct ItemResponse {
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ArticleItem {
+    pub title: String,
+    pub url: String,
+    pub lang: String,
+    /// `md`, `text` or `html`
+    pub format: String,
+    /// Section headings in order
+    pub sections: Vec<String>,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ArticleResponse {
     pub ok: bool,
-    pub item: WikiItem,
+    pub item: ArticleItem,
+}
+
+/// A saved lead image and the file page's license metadata.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ImageItem {
+    /// Page the image leads
+    pub title: String,
+    /// File page title, e.g. "File:Rust programming language black logo.svg"
+    pub file: String,
+    /// Where the image was saved
+    pub path: String,
+    /// URL the image was downloaded from
+    pub url: String,
+    /// The file's description page
+    pub description_url: String,
+    pub mime: String,
+    /// Size of the saved image
+    pub width: u64,
+    pub height: u64,
+    pub bytes: u64,
+    /// e.g. "CC BY-SA 4.0" or "Public domain"
+    pub license: String,
+    pub license_url: String,
+    /// Author as plain text
+    pub artist: String,
+    pub credit: String,
+    pub attribution_required: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ImageResponse {
+    pub ok: bool,
+    pub item: ImageItem,
+}
+
+/// One edit of a page.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Revision {
+    pub revid: u64,
+    /// RFC 3339, UTC
+    pub timestamp: String,
+    /// `""` when hidden
+    pub user: String,
+    /// Edit summary; `""` when empty or hidden
+    pub comment: String,
+    /// Page size in bytes after the edit
+    pub size: u64,
+    /// Bytes added (positive) or removed (negative) by the edit
+    pub size_delta: i64,
+    pub minor: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct HistoryResponse {
+    pub ok: bool,
+    pub title: String,
+    pub url: String,
+    pub count: usize,
+    pub items: Vec<Revision>,
+}
+
+/// One page a disambiguation page points to.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Candidate {
+    /// 1-based, for `--pick`
+    pub index: usize,
+    pub title: String,
+    /// The list entry's text, e.g. "Rust (fungus), a plant disease"
+    pub description: String,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DisambiguationResponse {
+    pub ok: bool,
+    pub disambiguation: bool,
+    /// Title of the disambiguation page
+    pub title: String,
+    pub count: usize,
+    pub items: Vec<Candidate>,
 }
 
 #[derive(Debug, serde::Deserialize)]
 pub struct SummaryApi {
+    /// `standard`, `disambiguation`, ...
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
     pub title: Option<String>,
     pub extract: Option<String>,
     pub content_urls: Option<ContentUrls>,
@@ -0,0 +1,497 @@
+//! Parsoid article HTML to readable markdown or plain text.
+//!
+//! Keeps headings, paragraphs, lists, quotes and code; drops tables (infoboxes,
+//! navboxes), figures, citation markers and the reference list.
+
+use scraper::{ElementRef, Html, Node, Selector};
+
+use crate::cli::ArticleFormat;
+
+/// Elements and classes that carry no prose.
+const SKIP_TAGS: &[&str] = &[
+    "head", "style", "script", "link", "meta", "table", "figure", "img", "audio", "video",
+    "noscript",
+];
+const SKIP_CLASSES: &[&str] = &[
+    "reference",
+    "mw-ref",
+    "mw-references-wrap",
+    "references",
+    "reflist",
+    "hatnote",
+    "navbox",
+    "infobox",
+    "ambox",
+    "metadata",
+    "noprint",
+    "shortdescription",
+    "mw-empty-elt",
+    "thumb",
+];
+
+pub struct Rendered {
+    pub title: String,
+    pub content: String,
+    /// Section headings in document order
+    pub sections: Vec<String>,
+}
+
+pub fn render(html: &str, format: ArticleFormat) -> Rendered {
+    let doc = Html::parse_document(html);
+    let title = Selector::parse("title")
+        .ok()
+        .and_then(|sel| doc.select(&sel).next())
+        .map(|el| collapse(&el.text().collect::<String>()))
+        .unwrap_or_default();
+
+    let mut renderer = Renderer {
+        markdown: format == ArticleFormat::Md,
+        blocks: Vec::new(),
+        sections: Vec::new(),
+    };
+    let body = Selector::parse("body")
+        .ok()
+        .and_then(|sel| doc.select(&sel).next())
+        .unwrap_or_else(|| doc.root_element());
+    renderer.container(body);
+
+    let content = match format {
+        ArticleFormat::Html => html.to_string(),
+        _ => renderer.blocks.join("\n\n"),
+    };
+    Rendered {
+        title,
+        content,
+        sections: renderer.sections,
+    }
+}
+
+/// Namespaces whose links are never candidate articles.
+const NON_ARTICLE_NAMESPACES: &[&str] = &[
+    "Category",
+    "File",
+    "Help",
+    "Portal",
+    "Special",
+    "Template",
+    "Wikipedia",
+    "Wiktionary",
+];
+
+/// (title, description) for each list entry of a disambiguation page: the entry's
+/// first article link, and the entry's text. Red links and duplicates are skipped.
+pub fn candidate_links(html: &str) -> Vec<(String, String)> {
+    let doc = Html::parse_document(html);
+    let Ok(items) = Selector::parse("li") else {
+        return Vec::new();
+    };
+    let Ok(links) = Selector::parse("a[rel~='mw:WikiLink'][title]") else {
+        return Vec::new();
+    };
+
+    let mut seen = std::collections::HashSet::new();
+    let mut out = Vec::new();
+    for li in doc.select(&items) {
+        let hidden = skipped(li) || li.ancestors().filter_map(ElementRef::wrap).any(skipped);
+        if hidden {
+            continue;
+        }
+        // Links in nested lists belong to their own entries
+        let Some(link) = li.select(&links).find(|a| {
+            a.ancestors()
+                .filter_map(ElementRef::wrap)
+                .take_while(|el| el.id() != li.id())
+                .all(|el| !matches!(el.value().name(), "ul" | "ol"))
+        }) else {
+            continue;
+        };
+        if has_class(link, "new") {
+            continue;
+        }
+        let Some(title) = link.attr("title").map(collapse) else {
+            continue;
+        };
+        let namespaced = title
+            .split_once(':')
+            .is_some_and(|(ns, _)| NON_ARTICLE_NAMESPACES.contains(&ns));
+        if title.is_empty() || namespaced || !seen.insert(title.clone()) {
+            continue;
+        }
+        let description = li
+            .children()
+            .filter_map(|child| match child.value() {
+                Node::Text(text) => Some(text.to_string()),
+                Node::Element(_) => ElementRef::wrap(child)
+                    .filter(|el| !matches!(el.value().name(), "ul" | "ol"))
+                    .map(plain_text),
+                _ => None,
+            })
+            .collect::<String>();
+        out.push((title, collapse(&description)));
+    }
+    out
+}
+
+struct Renderer {
+    markdown: bool,
+    blocks: Vec<String>,
+    sections: Vec<String>,
+}
+
+impl Renderer {
+    /// Children of a block container: block elements become blocks, runs of inline
+    /// content become paragraphs.
+    fn container(&mut self, el: ElementRef) {
+        let mut pending = String::new();
+        for child in el.children() {
+            match child.value() {
+                Node::Text(text) => pending.push_str(text),
+                Node::Element(_) => {
+                    let Some(child) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    if skipped(child) {
+                        continue;
+                    }
+                    if is_block(child.value().name()) {
+                        self.paragraph(&pending);
+                        pending.clear();
+                        self.block(child);
+                    } else {
+                        pending.push_str(&self.inline(child));
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.paragraph(&pending);
+    }
+
+    fn block(&mut self, el: ElementRef) {
+        match el.value().name() {
+            name @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+                let text = collapse(&plain_text(el));
+                if text.is_empty() {
+                    return;
+                }
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                self.sections.push(text.clone());
+                self.blocks.push(if self.markdown {
+                    format!("{} {text}", "#".repeat(level))
+                } else {
+                    text
+                });
+            }
+            "p" => {
+                let text = self.inline_children(el);
+                self.paragraph(&text);
+            }
+            "ul" | "ol" => {
+                let mut lines = Vec::new();
+                self.list(el, 0, &mut lines);
+                if !lines.is_empty() {
+                    self.blocks.push(lines.join("\n"));
+                }
+            }
+            "dl" => {
+                let mut lines = Vec::new();
+                for item in el.child_elements().filter(|c| !skipped(*c)) {
+                    let text = collapse(&self.inline_children(item));
+                    if text.is_empty() {
+                        continue;
+                    }
+                    match item.value().name() {
+                        "dt" if self.markdown => lines.push(format!("**{text}**")),
+                        "dd" => lines.push(format!("  {text}")),
+                        _ => lines.push(text),
+                    }
+                }
+                if !lines.is_empty() {
+                    self.blocks.push(lines.join("\n"));
+                }
+            }
+            "pre" => {
+                let code = plain_text(el);
+                let code = code.trim_end();
+                if !code.is_empty() {
+                    self.blocks.push(if self.markdown {
+                        format!("```\n{code}\n```")
+                    } else {
+                        code.to_string()
+                    });
+                }
+            }
+            "blockquote" => {
+                let mut inner = Renderer {
+                    markdown: self.markdown,
+                    blocks: Vec::new(),
+                    sections: Vec::new(),
+                };
+                inner.container(el);
+                let quoted = inner.blocks.join("\n\n");
+                if !quoted.is_empty() {
+                    self.blocks.push(if self.markdown {
+                        quoted
+                            .lines()
+                            .map(|line| format!("> {line}").trim_end().to_string())
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    } else {
+                        quoted
+                    });
+                }
+            }
+            _ => self.container(el),
+        }
+    }
+
+    fn list(&self, el: ElementRef, depth: usize, lines: &mut Vec<String>) {
+        let ordered = el.value().name() == "ol";
+        let indent = "  ".repeat(depth);
+        let items = el
+            .child_elements()
+            .filter(|c| c.value().name() == "li" && !skipped(*c));
+        for (idx, item) in items.enumerate() {
+            let mut text = String::new();
+            let mut nested = Vec::new();
+            for child in item.children() {
+                match child.value() {
+                    Node::Text(t) => text.push_str(t),
+                    Node::Element(_) => {
+                        let Some(child) = ElementRef::wrap(child) else {
+                            continue;
+                        };
+                        match child.value().name() {
+                            _ if skipped(child) => {}
+                            "ul" | "ol" => nested.push(child),
+                            _ => text.push_str(&self.inline(child)),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let text = collapse(&text);
+            if !text.is_empty() {
+                let marker = if ordered {
+                    format!("{}.", idx + 1)
+                } else {
+                    "-".to_string()
+                };
+                lines.push(format!("{indent}{marker} {text}"));
+            }
+            for list in nested {
+                self.list(list, depth + 1, lines);
+            }
+        }
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        let text = collapse(text);
+        if !text.is_empty() {
+            self.blocks.push(text);
+        }
+    }
+
+    fn inline_children(&self, el: ElementRef) -> String {
+        let mut out = String::new();
+        for child in el.children() {
+            match child.value() {
+                Node::Text(text) => out.push_str(text),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        out.push_str(&self.inline(child));
+                    }
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    fn inline(&self, el: ElementRef) -> String {
+        if skipped(el) {
+            return String::new();
+        }
+        // Formulas: the TeX source reads better than the MathML fallback
+        if has_class(el, "mwe-math-element") {
+            let tex = Selector::parse("annotation")
+                .ok()
+                .and_then(|sel| el.select(&sel).next())
+                .map(|a| collapse(&a.text().collect::<String>()))
+                .unwrap_or_default();
+            return match (tex.is_empty(), self.markdown) {
+                (true, _) => String::new(),
+                (false, true) => format!("${tex}$"),
+                (false, false) => tex,
+            };
+        }
+        let inner = self.inline_children(el);
+        let wrap = match el.value().name() {
+            "b" | "strong" if self.markdown => "**",
+            "i" | "em" if self.markdown => "*",
+            "code" | "kbd" | "samp" if self.markdown => "`",
+            "br" => return " ".to_string(),
+            _ => return inner,
+        };
+        // Keep surrounding spaces outside the markers
+        let trimmed = inner.trim();
+        if trimmed.is_empty() {
+            return inner;
+        }
+        let lead = if inner.starts_with(char::is_whitespace) {
+            " "
+        } else {
+            ""
+        };
+        let trail = if inner.ends_with(char::is_whitespace) {
+            " "
+        } else {
+            ""
+        };
+        format!("{lead}{wrap}{trimmed}{wrap}{trail}")
+    }
+}
+
+fn is_block(name: &str) -> bool {
+    matches!(
+        name,
+        "section"
+            | "div"
+            | "p"
+            | "h1"
+            | "h2"
+            | "h3"
+            | "h4"
+            | "h5"
+            | "h6"
+            | "ul"
+            | "ol"
+            | "dl"
+            | "pre"
+            | "blockquote"
+            | "center"
+            | "main"
+            | "article"
+            | "aside"
+            | "body"
+    )
+}
+
+fn skipped(el: ElementRef) -> bool {
+    SKIP_TAGS.contains(&el.value().name())
+        || el
+            .value()
+            .classes()
+            .any(|class| SKIP_CLASSES.contains(&class))
+        || el.attr("role") == Some("navigation")
+        || el
+            .attr("style")
+            .is_some_and(|s| s.replace(' ', "").contains("display:none"))
+}
+
+/// Text of an HTML snippet, such as an image's `Artist` metadata, on one line.
+pub fn fragment_text(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    collapse(&fragment.root_element().text().collect::<String>())
+}
+
+fn has_class(el: ElementRef, class: &str) -> bool {
+    el.value().classes().any(|c| c == class)
+}
+
+/// Text of an element, without skipped descendants.
+fn plain_text(el: ElementRef) -> String {
+    let mut out = String::new();
+    for child in el.children() {
+        match child.value() {
+            Node::Text(text) => out.push_str(text),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child).filter(|c| !skipped(*c)) {
+                    out.push_str(&plain_text(child));
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r##"<!DOCTYPE html>
+<html><head><title>Rust (programming language)</title><style>.x{}</style></head>
+<body>
+<section data-mw-section-id="0">
+  <div class="hatnote">For the fungus, see Rust (fungus).</div>
+  <table class="infobox"><tr><td>Paradigm</td></tr></table>
+  <p><b>Rust</b> is a <a href="./Programming_language">language</a> focused on
+  <i>safety</i>.<sup class="mw-ref reference"><a href="#cite_note-1">[1]</a></sup></p>
+</section>
+<section data-mw-section-id="1">
+  <h2 id="History">History</h2>
+  <p>Work began in 2006.</p>
+  <ul><li>Stable<ul><li>1.0 in 2015</li></ul></li><li>Editions</li></ul>
+  <figure><figcaption>Logo</figcaption></figure>
+  <pre>fn main() {}</pre>
+</section>
+<section data-mw-section-id="2">
+  <h2 id="References">References</h2>
+  <div class="mw-references-wrap"><ol class="references"><li>A source</li></ol></div>
+</section>
+</body></html>"##;
+
+    #[test]
+    fn renders_markdown_with_headings() {
+        let out = render(PAGE, ArticleFormat::Md);
+        assert_eq!(out.title, "Rust (programming language)");
+        assert_eq!(out.sections, ["History", "References"]);
+        assert_eq!(
+            out.content,
+            "**Rust** is a language focused on *safety*.\n\n\
+             ## History\n\n\
+             Work began in 2006.\n\n\
+             - Stable\n  - 1.0 in 2015\n- Editions\n\n\
+             ```\nfn main() {}\n```\n\n\
+             ## References"
+        );
+    }
+
+    #[test]
+    fn lists_disambiguation_candidates() {
+        let html = r#"<html><body><section>
+<p><b>Mercury</b> may refer to:</p>
+<ul>
+  <li><a rel="mw:WikiLink" href="./Mercury_(planet)" title="Mercury (planet)">Mercury (planet)</a>, the closest planet to the Sun</li>
+  <li><a rel="mw:WikiLink" href="./Mercury_(element)" title="Mercury (element)">Mercury (element)</a>, a chemical element
+    <ul><li><a rel="mw:WikiLink" href="./Mercury_poisoning" title="Mercury poisoning">Mercury poisoning</a></li></ul></li>
+  <li><a rel="mw:WikiLink" class="new" href="./Mercury_(band)?action=edit" title="Mercury (band)">Mercury (band)</a></li>
+  <li><a rel="mw:WikiLink" href="./Mercury_(planet)" title="Mercury (planet)">again</a></li>
+</ul>
+<div role="navigation" class="navbox"><ul><li><a rel="mw:WikiLink" href="./Venus" title="Venus">Venus</a></li></ul></div>
+<ul><li><a rel="mw:WikiLink" href="./Help:Disambiguation" title="Help:Disambiguation">help</a></li></ul>
+</section></body></html>"#;
+        let candidates = candidate_links(html);
+        let titles: Vec<_> = candidates.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(
+            titles,
+            ["Mercury (planet)", "Mercury (element)", "Mercury poisoning"]
+        );
+        assert_eq!(candidates[1].1, "Mercury (element), a chemical element");
+    }
+
+    #[test]
+    fn renders_plain_text() {
+        let out = render(PAGE, ArticleFormat::Text);
+        assert!(out
+            .content
+            .starts_with("Rust is a language focused on safety.\n\nHistory\n\n"));
+        assert!(!out.content.contains("fungus"));
+        assert!(!out.content.contains("[1]"));
+    }
+}
@@ -0,0 +1,47 @@
+use assert_cmd::Command;
+use dee_test_support::{fixture, json, MockApi, Sandbox};
+
+fn bin(sandbox: &Sandbox, api: &MockApi) -> Command {
+    let mut cmd = sandbox.command(assert_cmd::cargo::cargo_bin!("dee-wiki"));
+    cmd.env("DEE_WIKI_BASE_URL", api.uri());
+    cmd
+}
+
+#[test]
+fn summary_and_search_use_the_configured_wiki() {
+    let api = MockApi::start();
+    api.get_json(
+        "/api/rest_v1/page/summary/Rust",
+        &fixture("wiki/summary_rust.json"),
+    )
+    .get_json("/w/api.php", &fixture("wiki/opensearch_rust.json"));
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["summary", "Rust", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["item"]["title"], "Rust (programming language)");
+    assert_eq!(
+        parsed["item"]["extract"],
+        "Rust is a general-purpose programming language emphasizing performance, type safety, and concurrency."
+    );
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["search", "rust", "--json"]));
+    assert_eq!(code, Some(0));
+    assert_eq!(parsed["count"], 2);
+    assert_eq!(parsed["items"][1]["title"], "Rust (programming language)");
+    let query = api.requests()[1].url.query().unwrap().to_string();
+    assert!(
+        query.starts_with("action=opensearch&search=rust"),
+        "{query}"
+    );
+}
+
+#[test]
+fn missing_page_is_not_found() {
+    let api = MockApi::start();
+    let sandbox = Sandbox::new();
+
+    let (code, parsed) = json(bin(&sandbox, &api).args(["get", "Nope", "--json"]));
+    assert_eq!(code, Some(3));
+    assert_eq!(parsed["code"], "NOT_FOUND");
+}
@@ -0,0 +1,103 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use dee_test_support::seed_cache;
+use std::path::Path;
+
+fn bin(cache: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("dee-wiki").unwrap();
+    cmd.env("XDG_CACHE_HOME", cache);
+    cmd
+}
+
+#[test]
+fn offline_without_cache_is_a_miss() {
+    let data = tempfile::tempdir().unwrap();
+    let out = bin(data.path())
+        .args(["get", "Rust", "--offline", "--json"])
+        .output()
+        .unwrap();
+
+    assert!(!out.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["ok"], false);
+    assert_eq!(parsed["code"], "OFFLINE_MISS");
+}
+
+#[test]
+fn offline_serves_cached_summary() {
+    let data = tempfile::tempdir().unwrap();
+    // Older than the TTL: still served with --offline
+    seed_cache(
+        data.path(),
+        "dee-wiki",
+        "https://en.wikipedia.org/api/rest_v1/page/summary/Rust",
+        &serde_json::json!({
+            "type": "standard",
+            "title": "Rust",
+            "extract": "Rust is an iron oxide.",
+            "content_urls": { "desktop": { "page": "https://en.wikipedia.org/wiki/Rust" } }
+        }),
+    );
+
+    let out = bin(data.path())
+        .args(["get", "Rust", "--offline", "--verbose", "--json"])
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["item"]["title"], "Rust");
+    assert_eq!(parsed["item"]["cached"], true);
+    assert_eq!(parsed["stale"], true);
+    assert!(parsed["cache_age_secs"].as_u64().unwrap() > 1_000_000);
+
+    // `cached` is only reported with --verbose
+    let out = bin(data.path())
+        .args(["get", "Rust", "--offline", "--json"])
+        .output()
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert!(parsed["item"].get("cached").is_none());
+}
+
+#[test]
+fn no_cache_conflicts_with_offline() {
+    let data = tempfile::tempdir().unwrap();
+    bin(data.path())
+        .args(["get", "Rust", "--offline", "--no-cache", "--json"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn cache_path_and_clear() {
+    let data = tempfile::tempdir().unwrap();
+    seed_cache(
+        data.path(),
+        "dee-wiki",
+        "https://en.wikipedia.org/api/rest_v1/page/summary/Rust",
+        &serde_json::json!({}),
+    );
+
+    let out = bin(data.path())
+        .args(["cache", "path", "--json"])
+        .output()
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(
+        parsed["item"]["path"],
+        data.path().join("dee-wiki").display().to_string()
+    );
+
+    let out = bin(data.path())
+        .args(["cache", "clear", "--json"])
+        .output()
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["item"]["removed"], 1);
+    let left = std::fs::read_dir(data.path().join("dee-wiki")).unwrap();
+    assert_eq!(left.count(), 0);
+}
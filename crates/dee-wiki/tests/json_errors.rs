@@ -59,7 +59,44 @@ fn search_invalid_lang_json_error_on_stdout() {
     assert_eq!(parsed["ok"], serde_json::json!(false));
 }
 
+#[test]
+fn pick_must_be_a_number() {
+    let out = bin()
+        .args(["get", "--json", "--pick", "first", "Mercury"])
+        .output()
+        .unwrap();
+
+    assert_eq!(out.status.code(), Some(2));
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], serde_json::json!("INVALID_ARGUMENT"));
+}
+
 #[test]
 fn version_flag_succeeds() {
     bin().arg("--version").assert().success();
 }
+
+#[test]
+fn image_width_must_be_positive() {
+    let out = bin()
+        .args(["image", "Rust", "--width", "0", "--json"])
+        .output()
+        .unwrap();
+
+    assert_eq!(out.status.code(), Some(2));
+    let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+}
+
+#[test]
+fn history_rejects_bad_since_and_limit() {
+    for args in [
+        ["history", "Rust", "--since", "last week", "--json"],
+        ["history", "Rust", "--limit", "0", "--json"],
+    ] {
+        let out = bin().args(args).output().unwrap();
+        assert_eq!(out.status.code(), Some(2), "{args:?}");
+        let parsed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+        assert_eq!(parsed["code"], "INVALID_ARGUMENT");
+    }
+}
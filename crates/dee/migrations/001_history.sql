@@ -0,0 +1,12 @@
+CREATE TABLE invocations (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  tool TEXT NOT NULL,
+  -- JSON array of the arguments after the tool name, secrets redacted
+  args TEXT NOT NULL,
+  redacted INTEGER NOT NULL DEFAULT 0,
+  exit_code INTEGER NOT NULL,
+  duration_ms INTEGER NOT NULL,
+  started_at TEXT NOT NULL
+);
+
+CREATE INDEX invocations_tool ON invocations (tool);
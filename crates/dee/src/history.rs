@@ -0,0 +1,237 @@
+//! The opt-in log of `dee <tool>` runs: which tool, its arguments (secrets
+//! redacted), exit code and duration, in `<data dir>/dee/history.db`.
+//!
+//! Off until `history = true` in `~/.config/dee/config.toml` (`dee history
+//! enable`) or `DEE_HISTORY=true`. Recording is best effort: a run never fails
+//! because its log entry could not be written.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use dee_core::Config;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use rusqlite_migration::{Migrations, M};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::AppError;
+
+/// Written in place of a secret argument
+pub const REDACTED: &str = "<redacted>";
+
+/// Flags and config keys whose value is a secret when their name ends in one of
+/// these words: `--api-key`, `secret_key`, `access_token`, but not `--author`
+const SECRET_WORDS: &[&str] = &["key", "secret", "token", "password", "passwd"];
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct HistoryConfig {
+    /// Record every `dee <tool>` run
+    #[serde(default)]
+    pub history: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Entry {
+    pub id: i64,
+    pub tool: String,
+    pub args: Vec<String>,
+    /// Some arguments were secrets, so the entry cannot be rerun
+    pub redacted: bool,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+    pub started_at: String,
+}
+
+pub fn config() -> Config<HistoryConfig> {
+    Config::new("dee").env_override("history")
+}
+
+pub fn enabled() -> bool {
+    config().load().is_ok_and(|config| config.history)
+}
+
+fn db_path() -> PathBuf {
+    dee_core::config::data_dir("dee").join("history.db")
+}
+
+fn migrations() -> Migrations<'static> {
+    Migrations::new(vec![M::up(include_str!("../migrations/001_history.sql"))])
+}
+
+pub fn open() -> Result<Connection, AppError> {
+    let path = db_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|_| AppError::Database)?;
+    }
+    let mut conn = Connection::open(path).map_err(|_| AppError::Database)?;
+    migrations()
+        .to_latest(&mut conn)
+        .map_err(|_| AppError::Database)?;
+    Ok(conn)
+}
+
+/// Log a finished run when history is on.
+pub fn record(
+    tool: &str,
+    args: &[OsString],
+    exit_code: i32,
+    started_at: DateTime<Utc>,
+    took: Duration,
+) {
+    if !enabled() {
+        return;
+    }
+    let args: Vec<String> = args
+        .iter()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let (args, redacted) = redact(&args);
+    let Ok(args) = serde_json::to_string(&args) else {
+        return;
+    };
+    let Ok(conn) = open() else {
+        return;
+    };
+    let _ = conn.execute(
+        "INSERT INTO invocations (tool, args, redacted, exit_code, duration_ms, started_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            tool,
+            args,
+            redacted,
+            exit_code,
+            took.as_millis() as i64,
+            started_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        ],
+    );
+}
+
+/// Newest first, optionally for one tool.
+pub fn list(conn: &Connection, tool: Option<&str>, limit: usize) -> Result<Vec<Entry>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, tool, args, redacted, exit_code, duration_ms, started_at
+             FROM invocations
+             WHERE ?1 IS NULL OR tool = ?1
+             ORDER BY id DESC
+             LIMIT ?2",
+        )
+        .map_err(|_| AppError::Database)?;
+    let rows = stmt
+        .query_map(params![tool, limit as i64], entry)
+        .map_err(|_| AppError::Database)?;
+    rows.collect::<Result<_, _>>()
+        .map_err(|_| AppError::Database)
+}
+
+pub fn get(conn: &Connection, id: i64) -> Result<Entry, AppError> {
+    conn.query_row(
+        "SELECT id, tool, args, redacted, exit_code, duration_ms, started_at
+         FROM invocations WHERE id = ?1",
+        params![id],
+        entry,
+    )
+    .optional()
+    .map_err(|_| AppError::Database)?
+    .ok_or(AppError::HistoryNotFound(id))
+}
+
+/// Delete every entry; returns how many there were.
+pub fn clear(conn: &Connection) -> Result<usize, AppError> {
+    conn.execute("DELETE FROM invocations", [])
+        .map_err(|_| AppError::Database)
+}
+
+fn entry(row: &Row) -> rusqlite::Result<Entry> {
+    let args: String = row.get(2)?;
+    Ok(Entry {
+        id: row.get(0)?,
+        tool: row.get(1)?,
+        args: serde_json::from_str(&args).unwrap_or_default(),
+        redacted: row.get(3)?,
+        exit_code: row.get(4)?,
+        duration_ms: row.get::<_, i64>(5)?.max(0) as u64,
+        started_at: row.get(6)?,
+    })
+}
+
+/// `args` with secret values replaced by [`REDACTED`]: the value of a flag
+/// such as `--api-key`, and of `config set <key> <value>` for a secret key.
+pub fn redact(args: &[String]) -> (Vec<String>, bool) {
+    let mut out = Vec::with_capacity(args.len());
+    let mut redacted = false;
+    let mut secret_next = false;
+    for (idx, arg) in args.iter().enumerate() {
+        if secret_next {
+            out.push(REDACTED.to_string());
+            redacted = true;
+            secret_next = false;
+            continue;
+        }
+        if let Some((flag, _)) = arg.split_once('=').filter(|_| arg.starts_with('-')) {
+            if is_secret(flag) {
+                out.push(format!("{flag}={REDACTED}"));
+                redacted = true;
+                continue;
+            }
+        } else if arg.starts_with('-') && is_secret(arg) {
+            secret_next = true;
+        } else if idx >= 2 && args[idx - 2] == "config" && args[idx - 1] == "set" {
+            secret_next = is_secret(arg);
+        }
+        out.push(arg.clone());
+    }
+    (out, redacted)
+}
+
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.rsplit(['-', '_'])
+        .next()
+        .is_some_and(|word| SECRET_WORDS.contains(&word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn redacts_secret_flags_and_config_values() {
+        let (args, redacted) = redact(&strings(&[
+            "search",
+            "rust",
+            "--api-key",
+            "pk1",
+            "--token=abc",
+            "--author",
+            "knuth",
+        ]));
+        assert!(redacted);
+        assert_eq!(
+            args,
+            [
+                "search",
+                "rust",
+                "--api-key",
+                REDACTED,
+                "--token=<redacted>",
+                "--author",
+                "knuth"
+            ]
+        );
+
+        let (args, redacted) = redact(&strings(&["config", "set", "secret_key", "sk1"]));
+        assert!(redacted);
+        assert_eq!(args, ["config", "set", "secret_key", REDACTED]);
+
+        let (args, redacted) = redact(&strings(&["config", "set", "base", "EUR"]));
+        assert!(!redacted);
+        assert_eq!(args, ["config", "set", "base", "EUR"]);
+    }
+}
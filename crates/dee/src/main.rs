@@ -0,0 +1,462 @@
+mod history;
+mod mcp;
+mod plugins;
+mod tools;
+
+use std::ffi::OsString;
+use std::process::Command;
+use std::time::Instant;
+
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use dee_core::log;
+use dee_core::schema::{self, Schemas};
+use dee_core::{
+    print_json, print_list, profile, report, style, ConfigError, ErrorCode, Format, OkItem,
+    OkMessage,
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::tools::{Tool, TOOLS};
+
+const EXAMPLES: &str = "EXAMPLES:\n  dee hn top --limit 10\n  dee whois example.com --json\n  dee rates convert 100 USD EUR\n  dee tools --json\n  dee mcp-serve --tool hn --tool wiki\n  dee plugins list\n  dee history enable\n  dee history list --tool hn --json\n  dee history rerun 12\n  dee --profile work porkbun domains list\n  dee help rates";
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "dee",
+    version,
+    disable_help_subcommand = true,
+    about = "Run any dee.ink tool: `dee <tool> <args>` runs `dee-<tool> <args>`"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// List the tools and whether each one is installed
+    Tools(ToolsArgs),
+    /// Serve the installed tools' read-only commands over MCP on stdio
+    McpServe(McpServeArgs),
+    /// Past `dee <tool>` runs (off until `dee history enable`)
+    History(HistoryArgs),
+    /// Third-party `dee-<name>` tools found on PATH
+    Plugins {
+        #[command(subcommand)]
+        command: PluginsCommand,
+    },
+    /// A tool and its arguments, passed through unchanged
+    #[command(external_subcommand)]
+    Run(Vec<OsString>),
+}
+
+#[derive(Debug, Args)]
+struct ToolsArgs {
+    #[arg(short, long, help = "Output as JSON")]
+    json: bool,
+
+    #[arg(short, long, help = "Only print installed tool names")]
+    quiet: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "List format: json, jsonl, yaml, csv or table"
+    )]
+    output: Option<Format>,
+}
+
+#[derive(Debug, Subcommand)]
+enum PluginsCommand {
+    /// Each plugin with its version and description
+    List(PluginsListArgs),
+}
+
+#[derive(Debug, Args)]
+struct PluginsListArgs {
+    #[arg(short, long, help = "Output as JSON")]
+    json: bool,
+
+    #[arg(short, long, help = "Only print plugin names")]
+    quiet: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "List format: json, jsonl, yaml, csv or table"
+    )]
+    output: Option<Format>,
+}
+
+#[derive(Debug, Args)]
+struct McpServeArgs {
+    #[arg(
+        long = "tool",
+        value_name = "TOOL",
+        help = "Only expose this tool (repeatable); default: every installed tool"
+    )]
+    tools: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+struct HistoryArgs {
+    #[arg(short, long, global = true, help = "Output as JSON")]
+    json: bool,
+
+    #[command(subcommand)]
+    command: HistoryCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum HistoryCommand {
+    /// Recorded runs, newest first
+    List(HistoryListArgs),
+    /// Run entry <ID> again, with the same tool and arguments
+    Rerun {
+        /// Entry id from `dee history list`
+        id: i64,
+    },
+    /// Delete every entry
+    Clear,
+    /// Start recording runs
+    Enable,
+    /// Stop recording runs (entries are kept)
+    Disable,
+}
+
+#[derive(Debug, Args)]
+struct HistoryListArgs {
+    #[arg(long, help = "Only runs of this tool")]
+    tool: Option<String>,
+
+    #[arg(long, default_value_t = 20, help = "Entries to show")]
+    limit: usize,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "List format: json, jsonl, yaml, csv or table"
+    )]
+    output: Option<Format>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct ToolItem {
+    name: &'static str,
+    binary: String,
+    about: &'static str,
+    installed: bool,
+    /// Omitted when the tool is not installed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+}
+
+/// `history clear`
+#[derive(Debug, Serialize, JsonSchema)]
+struct HistoryCleared {
+    removed: usize,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum AppError {
+    #[error("Unknown tool `{name}`{hint}. Run `dee tools` or `dee plugins list` for the list", hint = suggestion(name))]
+    UnknownTool { name: String },
+    #[error("`{binary}` is not installed. Install it with `cargo install {binary}`")]
+    NotInstalled { binary: String },
+    #[error("Could not run `{binary}`: {message}")]
+    Spawn { binary: String, message: String },
+    #[error("MCP server stopped: {message}")]
+    Serve { message: String },
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error("History entry {0} not found")]
+    HistoryNotFound(i64),
+    #[error("History entry {0} had secrets redacted; run it yourself")]
+    Redacted(i64),
+    #[error("History database operation failed")]
+    Database,
+}
+
+impl ErrorCode for AppError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::UnknownTool { .. } => "UNKNOWN_TOOL",
+            Self::NotInstalled { .. } => "NOT_INSTALLED",
+            Self::Spawn { .. } => "SPAWN_FAILED",
+            Self::Serve { .. } => "MCP_SERVE_FAILED",
+            Self::Config(err) => err.code(),
+            Self::HistoryNotFound(_) => "NOT_FOUND",
+            Self::Redacted(_) => "INVALID_ARGUMENT",
+            Self::Database => "DATABASE_ERROR",
+        }
+    }
+}
+
+fn suggestion(name: &str) -> String {
+    tools::suggest(name)
+        .map(|tool| format!(" (did you mean `{tool}`?)"))
+        .unwrap_or_default()
+}
+
+/// What each `dee` command prints under `--json`, for `--schema`.
+fn schemas() -> Schemas {
+    Schemas::new()
+        .list::<ToolItem>("tools")
+        .list::<plugins::Plugin>("plugins list")
+        .list::<history::Entry>("history list")
+        .item::<HistoryCleared>("history clear")
+        .message("history enable")
+        .message("history disable")
+}
+
+/// Whether the command line runs a tool or plugin rather than a `dee` command.
+fn runs_tool(args: &[String]) -> bool {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            args.next();
+        } else if !arg.starts_with('-') {
+            return !plugins::RESERVED.contains(&arg.as_str());
+        }
+    }
+    false
+}
+
+fn main() {
+    dee_core::cli::exit_on_generate_man(command);
+    // `dee hn top --schema` is the tool's to answer
+    if !runs_tool(&std::env::args().skip(1).collect::<Vec<_>>()) {
+        schema::exit_on_schema(schemas);
+    }
+    let matches = command()
+        .try_get_matches()
+        .unwrap_or_else(|err| dee_core::cli::exit_on_parse_error(err));
+    let cli = Cli::from_arg_matches(&matches)
+        .unwrap_or_else(|err| dee_core::cli::exit_on_parse_error(err));
+    // `dee --profile work hn top`: the tools read it from the environment
+    dee_core::cli::select_profile(&matches);
+    if let Some(name) = profile::active() {
+        std::env::set_var(profile::PROFILE_ENV, name);
+    }
+    // Likewise `dee --log-level debug hn top`
+    log::init(&matches);
+    for (arg, env) in [
+        (log::LOG_LEVEL_ARG, log::LOG_LEVEL_ENV),
+        (log::LOG_FORMAT_ARG, log::LOG_FORMAT_ENV),
+    ] {
+        if let Some(value) = matches.get_one::<String>(arg) {
+            std::env::set_var(env, value);
+        }
+    }
+
+    let result = match cli.command {
+        Commands::Tools(args) => {
+            list_tools(&args);
+            Ok(0)
+        }
+        Commands::McpServe(args) => mcp_serve(&args),
+        Commands::History(args) => run_history(&args),
+        Commands::Plugins {
+            command: PluginsCommand::List(args),
+        } => {
+            list_plugins(&args);
+            Ok(0)
+        }
+        Commands::Run(args) => run_tool(args),
+    };
+    match result {
+        Ok(code) => std::process::exit(code),
+        Err(err) => {
+            // The tool's own `--json` decides the error format, as it would for the tool
+            report(&err, dee_core::cli::wants_json());
+            std::process::exit(err.exit_code());
+        }
+    }
+}
+
+/// The clap command, with the tool list in `--help`.
+fn command() -> clap::Command {
+    let width = TOOLS.iter().map(|tool| tool.name.len()).max().unwrap_or(0);
+    let listing: String = TOOLS
+        .iter()
+        .map(|tool| format!("  {:width$}  {}\n", tool.name, tool.about))
+        .collect();
+    log::with_logging(dee_core::cli::with_profile(Cli::command()))
+        .after_help(format!("TOOLS:\n{listing}\n{EXAMPLES}"))
+}
+
+fn list_tools(args: &ToolsArgs) {
+    let items: Vec<ToolItem> = TOOLS.iter().map(tool_item).collect();
+    if let Some(format) = Format::resolve(args.output, args.json) {
+        print_list(&items, format);
+    } else if args.quiet {
+        for item in items.iter().filter(|item| item.installed) {
+            println!("{}", item.name);
+        }
+    } else {
+        let width = TOOLS.iter().map(|tool| tool.name.len()).max().unwrap_or(0);
+        for item in &items {
+            let mark = if item.installed {
+                String::new()
+            } else {
+                format!("  {}", style::dim("(not installed)"))
+            };
+            let name = format!("{:width$}", item.name);
+            println!("{}  {}{mark}", style::header(name), item.about);
+        }
+    }
+}
+
+fn tool_item(tool: &Tool) -> ToolItem {
+    let path = tool.locate();
+    ToolItem {
+        name: tool.name,
+        binary: tool.binary(),
+        about: tool.about,
+        installed: path.is_some(),
+        path: path.map(|path| path.display().to_string()),
+    }
+}
+
+fn list_plugins(args: &PluginsListArgs) {
+    let items = plugins::discover();
+    if let Some(format) = Format::resolve(args.output, args.json) {
+        print_list(&items, format);
+    } else if args.quiet {
+        for item in &items {
+            println!("{}", item.name);
+        }
+    } else if items.is_empty() {
+        println!("No plugins. Any `dee-<name>` executable on PATH runs as `dee <name>`");
+    } else {
+        let width = items.iter().map(|item| item.name.len()).max().unwrap_or(0);
+        for item in &items {
+            let name = format!("{:width$}", item.name);
+            println!(
+                "{}  {}  {}",
+                style::header(name),
+                item.about,
+                style::dim(&item.version)
+            );
+        }
+    }
+}
+
+fn mcp_serve(args: &McpServeArgs) -> Result<i32, AppError> {
+    let selected: Vec<&Tool> = if args.tools.is_empty() {
+        TOOLS.iter().collect()
+    } else {
+        args.tools
+            .iter()
+            .map(|name| {
+                tools::find(name).ok_or_else(|| AppError::UnknownTool { name: name.clone() })
+            })
+            .collect::<Result<_, _>>()?
+    };
+    mcp::serve(&selected).map_err(|err| AppError::Serve {
+        message: err.to_string(),
+    })?;
+    Ok(0)
+}
+
+/// Run `dee-<tool>` with the rest of the arguments and return its exit code.
+/// `dee help <tool>` is `dee-<tool> --help`; `dee help` alone is `dee --help`.
+fn run_tool(mut args: Vec<OsString>) -> Result<i32, AppError> {
+    let mut name = args.remove(0).to_string_lossy().into_owned();
+    if name == "help" {
+        if args.is_empty() {
+            let _ = command().print_long_help();
+            return Ok(0);
+        }
+        name = args.remove(0).to_string_lossy().into_owned();
+        args.push("--help".into());
+    }
+    let path = match tools::find(&name) {
+        Some(tool) => tool.locate().ok_or_else(|| AppError::NotInstalled {
+            binary: tool.binary(),
+        })?,
+        None => {
+            plugins::locate(&name).ok_or_else(|| AppError::UnknownTool { name: name.clone() })?
+        }
+    };
+    let started_at = chrono::Utc::now();
+    let clock = Instant::now();
+    let status = Command::new(&path)
+        .args(&args)
+        .status()
+        .map_err(|err| AppError::Spawn {
+            binary: format!("dee-{name}"),
+            message: err.to_string(),
+        })?;
+    // A tool killed by a signal has no code
+    let code = status.code().unwrap_or(1);
+    history::record(&name, &args, code, started_at, clock.elapsed());
+    Ok(code)
+}
+
+fn run_history(args: &HistoryArgs) -> Result<i32, AppError> {
+    match &args.command {
+        HistoryCommand::List(list) => {
+            let conn = history::open()?;
+            let entries = history::list(&conn, list.tool.as_deref(), list.limit)?;
+            if let Some(format) = Format::resolve(list.output, args.json) {
+                print_list(&entries, format);
+            } else if entries.is_empty() && !history::enabled() {
+                println!("No history. Turn it on with `dee history enable`");
+            } else {
+                for entry in &entries {
+                    let exit = format!("exit {:<3}", entry.exit_code);
+                    let exit = if entry.exit_code == 0 {
+                        style::good(exit)
+                    } else {
+                        style::bad(exit)
+                    };
+                    println!(
+                        "{:>5}  {}  {exit} {:>7}ms  dee {} {}",
+                        entry.id,
+                        style::dim(&entry.started_at),
+                        entry.duration_ms,
+                        entry.tool,
+                        entry.args.join(" ")
+                    );
+                }
+            }
+        }
+        HistoryCommand::Rerun { id } => {
+            let entry = history::get(&history::open()?, *id)?;
+            if entry.redacted {
+                return Err(AppError::Redacted(entry.id));
+            }
+            let mut argv: Vec<OsString> = vec![entry.tool.into()];
+            argv.extend(entry.args.into_iter().map(OsString::from));
+            return run_tool(argv);
+        }
+        HistoryCommand::Clear => {
+            let removed = history::clear(&history::open()?)?;
+            if args.json {
+                print_json(&OkItem::new(HistoryCleared { removed }));
+            } else {
+                println!("Removed {removed} history entries");
+            }
+        }
+        HistoryCommand::Enable | HistoryCommand::Disable => {
+            let on = matches!(args.command, HistoryCommand::Enable);
+            let config = history::config();
+            let mut settings = config.load_file()?;
+            settings.history = on;
+            config.save(&settings)?;
+            let message = if on {
+                "History enabled"
+            } else {
+                "History disabled"
+            };
+            if args.json {
+                print_json(&OkMessage::new(message));
+            } else {
+                println!("{message}");
+            }
+        }
+    }
+    Ok(0)
+}
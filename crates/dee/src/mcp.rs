@@ -0,0 +1,187 @@
+//! `dee mcp-serve`: the installed tools' read-only commands as MCP tools.
+//!
+//! JSON-RPC 2.0 over stdio, one message per line. Each tool lists its commands
+//! with `dee-<tool> --mcp-tools` (see [`dee_core::mcp`]); a call runs the binary
+//! with `--json` and returns what it printed.
+
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use dee_core::mcp::McpTool;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::tools::Tool;
+
+/// Used when the client does not say which version it speaks
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// One MCP tool: `hn_top` runs `dee-hn top`.
+struct Exposed {
+    name: String,
+    binary: PathBuf,
+    tool: McpTool,
+}
+
+#[derive(Deserialize)]
+struct Listing {
+    items: Vec<McpTool>,
+}
+
+/// Answer requests on stdin until it closes (or stdout does).
+pub fn serve(tools: &[&Tool]) -> std::io::Result<()> {
+    let exposed: Vec<Exposed> = tools.iter().flat_map(|tool| discover(tool)).collect();
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = handle(&exposed, &line) {
+            writeln!(stdout, "{reply}")?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The tool's commands, or none when it is missing or too old to list them.
+fn discover(tool: &Tool) -> Vec<Exposed> {
+    let Some(binary) = tool.locate() else {
+        return Vec::new();
+    };
+    let listing = Command::new(&binary)
+        .arg(dee_core::cli::MCP_TOOLS_FLAG)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| serde_json::from_slice::<Listing>(&out.stdout).ok());
+    listing
+        .map(|listing| listing.items)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|mcp_tool| Exposed {
+            name: format!("{}_{}", tool.name, mcp_tool.name),
+            binary: binary.clone(),
+            tool: mcp_tool,
+        })
+        .collect()
+}
+
+/// The reply to one message, or `None` for a notification.
+fn handle(exposed: &[Exposed], line: &str) -> Option<Value> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(err) => return Some(error(Value::Null, PARSE_ERROR, &err.to_string())),
+    };
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        let id = message.get("id").cloned().unwrap_or(Value::Null);
+        return Some(error(id, INVALID_REQUEST, "Missing method"));
+    };
+    // Notifications (no id) get no reply, whatever they are
+    let id = message.get("id")?.clone();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let result = match method {
+        "initialize" => Ok(initialize(&params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(list(exposed)),
+        "tools/call" => call(exposed, &params),
+        _ => Err((METHOD_NOT_FOUND, format!("Unknown method `{method}`"))),
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error(id, code, &message),
+    })
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn initialize(params: &Value) -> Value {
+    let version = params
+        .get("protocolVersion")
+        .and_then(Value::as_str)
+        .unwrap_or(PROTOCOL_VERSION);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {} },
+        "serverInfo": { "name": "dee", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+fn list(exposed: &[Exposed]) -> Value {
+    let tools: Vec<Value> = exposed
+        .iter()
+        .map(|item| {
+            json!({
+                "name": item.name,
+                "description": item.tool.description,
+                "inputSchema": item.tool.input_schema,
+            })
+        })
+        .collect();
+    json!({ "tools": tools })
+}
+
+fn call(exposed: &[Exposed], params: &Value) -> Result<Value, (i64, String)> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| (INVALID_PARAMS, "Missing tool name".to_string()))?;
+    let item = exposed
+        .iter()
+        .find(|item| item.name == name)
+        .ok_or_else(|| (INVALID_PARAMS, format!("Unknown tool `{name}`")))?;
+    let empty = Map::new();
+    let arguments = match params.get("arguments") {
+        None | Some(Value::Null) => &empty,
+        Some(Value::Object(arguments)) => arguments,
+        Some(_) => return Err((INVALID_PARAMS, "`arguments` must be an object".to_string())),
+    };
+    let argv = item
+        .tool
+        .argv(arguments)
+        .map_err(|message| (INVALID_PARAMS, message))?;
+
+    // Null stdin: the tool must not read the client's messages
+    let output = Command::new(&item.binary)
+        .args(&argv)
+        .stdin(Stdio::null())
+        .output();
+    let (text, failed) = match output {
+        Ok(out) if out.stdout.is_empty() => (
+            String::from_utf8_lossy(&out.stderr).into_owned(),
+            !out.status.success(),
+        ),
+        Ok(out) => (
+            String::from_utf8_lossy(&out.stdout).into_owned(),
+            !out.status.success(),
+        ),
+        Err(err) => (
+            format!("Could not run `{}`: {err}", item.binary.display()),
+            true,
+        ),
+    };
+    let mut result = json!({
+        "content": [{ "type": "text", "text": text.trim_end() }],
+        "isError": failed,
+    });
+    if let Ok(Value::Object(structured)) = serde_json::from_str::<Value>(&text) {
+        result["structuredContent"] = Value::Object(structured);
+    }
+    Ok(result)
+}
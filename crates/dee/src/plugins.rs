@@ -0,0 +1,142 @@
+//! Third-party tools: any `dee-<name>` executable on `PATH` that is not one of
+//! [`crate::tools::TOOLS`] runs as `dee <name>`, the way git finds `git-<name>`.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::tools;
+
+/// `dee` subcommands a plugin cannot take over
+pub const RESERVED: &[&str] = &["help", "history", "mcp-serve", "plugins", "tools"];
+
+/// How long `--version` and `--help` get before the plugin is skipped
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Plugin {
+    /// Subcommand name: `foo` runs `dee-foo`
+    pub name: String,
+    pub binary: String,
+    pub path: String,
+    /// First line of `--version`, empty when it printed nothing
+    pub version: String,
+    /// First line of `--help` above the usage, empty when there is none
+    pub about: String,
+}
+
+/// Every plugin on `PATH`, by name; the first directory wins, as for a shell.
+pub fn discover() -> Vec<Plugin> {
+    let mut found: BTreeMap<String, PathBuf> = BTreeMap::new();
+    for dir in path_dirs() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file = entry.file_name().to_string_lossy().into_owned();
+            let Some(name) = plugin_name(&file) else {
+                continue;
+            };
+            let path = entry.path();
+            if is_executable(&path) {
+                found.entry(name.to_string()).or_insert(path);
+            }
+        }
+    }
+    found
+        .into_iter()
+        .map(|(name, path)| describe(name, path))
+        .collect()
+}
+
+/// The plugin run by `dee <name>`, when there is one.
+pub fn locate(name: &str) -> Option<PathBuf> {
+    if RESERVED.contains(&name) || tools::find(name).is_some() {
+        return None;
+    }
+    let file = format!("dee-{name}{}", std::env::consts::EXE_SUFFIX);
+    path_dirs()
+        .into_iter()
+        .map(|dir| dir.join(&file))
+        .find(|path| is_executable(path))
+}
+
+fn path_dirs() -> Vec<PathBuf> {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default()
+}
+
+/// `foo` for `dee-foo` (`dee-foo.exe` on Windows), unless that is a built-in.
+fn plugin_name(file: &str) -> Option<&str> {
+    let name = file
+        .strip_prefix("dee-")?
+        .strip_suffix(std::env::consts::EXE_SUFFIX)?;
+    let builtin = RESERVED.contains(&name) || tools::find(name).is_some();
+    (!name.is_empty() && !builtin).then_some(name)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+fn describe(name: String, path: PathBuf) -> Plugin {
+    let version = probe(&path, "--version")
+        .and_then(|out| out.lines().next().map(|line| line.trim().to_string()))
+        .unwrap_or_default();
+    let about = probe(&path, "--help")
+        .and_then(|out| {
+            out.lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .filter(|line| !line.starts_with("Usage:"))
+                .map(str::to_string)
+        })
+        .unwrap_or_default();
+    Plugin {
+        binary: format!("dee-{name}"),
+        name,
+        path: path.display().to_string(),
+        version,
+        about,
+    }
+}
+
+/// Stdout of `<path> <arg>`, or `None` when it fails or takes too long.
+fn probe(path: &Path, arg: &str) -> Option<String> {
+    let mut child = Command::new(path)
+        .arg(arg)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait().ok()? {
+            Some(status) => break status,
+            None if started.elapsed() > PROBE_TIMEOUT => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            None => std::thread::sleep(Duration::from_millis(10)),
+        }
+    };
+    let mut out = String::new();
+    child.stdout.take()?.read_to_string(&mut out).ok()?;
+    status.success().then_some(out)
+}
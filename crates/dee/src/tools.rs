@@ -0,0 +1,137 @@
+//! The tools `dee` knows about, and where their binaries are.
+
+use std::path::{Path, PathBuf};
+
+pub struct Tool {
+    /// Subcommand name: `hn` runs `dee-hn`
+    pub name: &'static str,
+    pub about: &'static str,
+}
+
+pub const TOOLS: &[Tool] = &[
+    Tool::new("amazon", "Search Amazon products"),
+    Tool::new("arxiv", "Search academic papers on arXiv"),
+    Tool::new(
+        "contacts",
+        "Personal CRM with interactions and import/export",
+    ),
+    Tool::new(
+        "crosspost",
+        "Cross-post and schedule posts across social platforms",
+    ),
+    Tool::new("ebay", "Search eBay listings"),
+    Tool::new("events", "Find local events by location and date"),
+    Tool::new("feed", "Read RSS and Atom feeds"),
+    Tool::new("food", "Find restaurants and food spots"),
+    Tool::new("gas", "Check gas prices by location"),
+    Tool::new("github", "GitHub repositories, issues, releases and search"),
+    Tool::new("habit", "Track habits and streaks locally"),
+    Tool::new("hn", "Browse Hacker News"),
+    Tool::new("invoice", "Generate invoice PDFs from JSON or YAML"),
+    Tool::new("ip", "IP geolocation, ASN lookup and CIDR math"),
+    Tool::new("llmfit", "Find local LLMs that fit your hardware"),
+    Tool::new("mentions", "Track mentions across public sources"),
+    Tool::new("openrouter", "Compare LLM models and pricing"),
+    Tool::new("package", "Look up package metadata and versions"),
+    Tool::new("parking", "Find parking spots by location"),
+    Tool::new("ph", "Browse Product Hunt launches"),
+    Tool::new("pkg", "Package lookup across crates.io, npm and PyPI"),
+    Tool::new("porkbun", "Manage domains via Porkbun API"),
+    Tool::new("pricewatch", "Monitor webpage prices and detect drops"),
+    Tool::new("qr", "Generate and decode QR codes"),
+    Tool::new("rates", "Currency exchange rates"),
+    Tool::new("receipt", "Extract structured receipt data from images"),
+    Tool::new("reddit", "Search Reddit posts and subreddits"),
+    Tool::new("ssl", "Check SSL certs and TLS info"),
+    Tool::new("stash", "Bookmark and read-later manager"),
+    Tool::new("stocks", "Stock quotes, price history and watchlists"),
+    Tool::new("timer", "Time tracking and pomodoro sessions"),
+    Tool::new("todo", "Local task list with JSON output"),
+    Tool::new("transit", "Route and transit directions"),
+    Tool::new("trends", "Google Trends interest and related queries"),
+    Tool::new("weather", "Current weather, forecasts and alerts"),
+    Tool::new("webpage", "Extract webpage metadata, text, and links"),
+    Tool::new("whois", "Domain WHOIS lookups"),
+    Tool::new("wiki", "Wikipedia article lookup"),
+];
+
+pub fn find(name: &str) -> Option<&'static Tool> {
+    TOOLS.iter().find(|tool| tool.name == name)
+}
+
+impl Tool {
+    const fn new(name: &'static str, about: &'static str) -> Self {
+        Self { name, about }
+    }
+
+    pub fn binary(&self) -> String {
+        format!("dee-{}", self.name)
+    }
+
+    /// The installed binary: first on `PATH`, then next to `dee` itself (a
+    /// workspace `cargo build` puts every tool in the same target dir).
+    pub fn locate(&self) -> Option<PathBuf> {
+        let file = format!("{}{}", self.binary(), std::env::consts::EXE_SUFFIX);
+        let path_dirs = std::env::var_os("PATH")
+            .map(|path| std::env::split_paths(&path).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let own_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+        path_dirs
+            .into_iter()
+            .chain(own_dir)
+            .map(|dir| dir.join(&file))
+            .find(|candidate| candidate.is_file())
+    }
+}
+
+/// The closest tool name, for "did you mean" hints.
+pub fn suggest(name: &str) -> Option<&'static str> {
+    TOOLS
+        .iter()
+        .map(|tool| (edit_distance(name, tool.name), tool.name))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(row[j + 1])
+            };
+            prev = current;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_close_names_only() {
+        assert_eq!(suggest("hm"), Some("hn"));
+        assert_eq!(suggest("whios"), Some("whois"));
+        assert_eq!(suggest("kubernetes"), None);
+    }
+
+    #[test]
+    fn tools_are_sorted_and_unique() {
+        let names: Vec<_> = TOOLS.iter().map(|tool| tool.name).collect();
+        let mut sorted = names.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(names, sorted);
+    }
+}
@@ -0,0 +1,350 @@
+#![allow(deprecated)]
+use assert_cmd::Command;
+use predicates::str::contains;
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let out = cmd.output().unwrap();
+    serde_json::from_slice(&out.stdout).expect("valid json on stdout")
+}
+
+#[test]
+fn tools_lists_every_tool() {
+    let parsed = json(Command::cargo_bin("dee").unwrap().args(["tools", "--json"]));
+    assert_eq!(parsed["ok"], true);
+    let items = parsed["items"].as_array().unwrap();
+    assert_eq!(parsed["count"], items.len());
+    let hn = items.iter().find(|item| item["name"] == "hn").unwrap();
+    assert_eq!(hn["binary"], "dee-hn");
+    assert!(hn["installed"].is_boolean());
+
+    Command::cargo_bin("dee")
+        .unwrap()
+        .args(["tools", "--output", "csv"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("name,binary,about,installed"));
+}
+
+#[test]
+fn help_lists_tools() {
+    Command::cargo_bin("dee")
+        .unwrap()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(contains("TOOLS:"))
+        .stdout(contains("whois"));
+}
+
+#[test]
+fn unknown_tool_suggests_a_name() {
+    Command::cargo_bin("dee")
+        .unwrap()
+        .args(["hm", "top"])
+        .assert()
+        .code(3)
+        .stderr(contains("did you mean `hn`"));
+
+    let parsed = json(
+        Command::cargo_bin("dee")
+            .unwrap()
+            .args(["kubernetes", "--json"]),
+    );
+    assert_eq!(parsed["ok"], false);
+    assert_eq!(parsed["code"], "UNKNOWN_TOOL");
+}
+
+#[cfg(unix)]
+#[test]
+fn profile_reaches_the_tool() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = tempfile::tempdir().unwrap();
+    let script = bin.path().join("dee-whois");
+    std::fs::write(
+        &script,
+        "#!/bin/sh\necho \"profile: $DEE_PROFILE args: $*\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    Command::cargo_bin("dee")
+        .unwrap()
+        .env("PATH", bin.path())
+        .env_remove("DEE_PROFILE")
+        .args(["--profile", "work", "whois", "example.com"])
+        .assert()
+        .success()
+        .stdout("profile: work args: example.com\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_the_tool_with_its_arguments_and_exit_code() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = tempfile::tempdir().unwrap();
+    let script = bin.path().join("dee-whois");
+    std::fs::write(&script, "#!/bin/sh\necho \"args: $*\"\nexit 3\n").unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    Command::cargo_bin("dee")
+        .unwrap()
+        .env("PATH", bin.path())
+        .args(["whois", "example.com", "--json"])
+        .assert()
+        .code(3)
+        .stdout("args: example.com --json\n");
+
+    Command::cargo_bin("dee")
+        .unwrap()
+        .env("PATH", bin.path())
+        .args(["help", "whois"])
+        .assert()
+        .stdout("args: --help\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn plugins_on_path_run_and_list() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = tempfile::tempdir().unwrap();
+    let plugin = bin.path().join("dee-hello");
+    std::fs::write(
+        &plugin,
+        "#!/bin/sh\ncase \"$1\" in\n  --version) echo \"dee-hello 0.3.1\" ;;\n  --help) printf 'Say hello\\n\\nUsage: dee-hello [NAME]\\n' ;;\n  *) echo \"hello $*\"; exit 4 ;;\nesac\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&plugin, std::fs::Permissions::from_mode(0o755)).unwrap();
+    // Built-ins and files without the executable bit are not plugins
+    let builtin = bin.path().join("dee-whois");
+    std::fs::write(&builtin, "#!/bin/sh\n").unwrap();
+    std::fs::set_permissions(&builtin, std::fs::Permissions::from_mode(0o755)).unwrap();
+    std::fs::write(bin.path().join("dee-notes"), "#!/bin/sh\n").unwrap();
+
+    Command::cargo_bin("dee")
+        .unwrap()
+        .env("PATH", bin.path())
+        .args(["hello", "world"])
+        .assert()
+        .code(4)
+        .stdout("hello world\n");
+
+    let parsed = json(
+        Command::cargo_bin("dee")
+            .unwrap()
+            .env("PATH", bin.path())
+            .args(["plugins", "list", "--json"]),
+    );
+    assert_eq!(parsed["count"], 1);
+    let item = &parsed["items"][0];
+    assert_eq!(item["name"], "hello");
+    assert_eq!(item["binary"], "dee-hello");
+    assert_eq!(item["version"], "dee-hello 0.3.1");
+    assert_eq!(item["about"], "Say hello");
+
+    Command::cargo_bin("dee")
+        .unwrap()
+        .env("PATH", bin.path())
+        .args(["notes"])
+        .assert()
+        .code(3)
+        .stderr(contains("Unknown tool `notes`"));
+}
+
+#[cfg(unix)]
+#[test]
+fn schema_describes_dee_commands_and_leaves_tools_theirs() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let listed = json(Command::cargo_bin("dee").unwrap().arg("--schema"));
+    let commands: Vec<&str> = listed["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["command"].as_str().unwrap())
+        .collect();
+    assert!(commands.contains(&"tools"), "{commands:?}");
+    assert!(commands.contains(&"history list"), "{commands:?}");
+
+    let plugins = json(
+        Command::cargo_bin("dee")
+            .unwrap()
+            .args(["plugins", "list", "--schema"]),
+    );
+    assert!(plugins["item"]["success"]["$defs"]["Plugin"].is_object());
+
+    let bin = tempfile::tempdir().unwrap();
+    let script = bin.path().join("dee-whois");
+    std::fs::write(&script, "#!/bin/sh\necho \"args: $*\"\n").unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    Command::cargo_bin("dee")
+        .unwrap()
+        .env("PATH", bin.path())
+        .args(["--profile", "work", "whois", "history", "--schema"])
+        .assert()
+        .success()
+        .stdout("args: history --schema\n");
+}
+
+#[test]
+fn generate_man_prints_a_man_page() {
+    Command::cargo_bin("dee")
+        .unwrap()
+        .arg("--generate-man")
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with(".TH \"DEE\" \"1\""))
+        .stdout(predicates::str::contains(".SH TOOLS"))
+        .stdout(predicates::str::contains(".SS \"dee tools\""));
+}
+
+#[cfg(unix)]
+#[test]
+fn mcp_serve_lists_and_calls_tool_commands() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = tempfile::tempdir().unwrap();
+    let script = bin.path().join("dee-whois");
+    let listing = serde_json::json!({
+        "ok": true,
+        "count": 1,
+        "items": [{
+            "name": "lookup",
+            "command": ["lookup"],
+            "description": "Look up a domain",
+            "input_schema": {
+                "type": "object",
+                "properties": { "domain": { "type": "string" } },
+                "required": ["domain"],
+                "additionalProperties": false
+            },
+            "args": [{ "name": "domain", "kind": "value" }]
+        }]
+    });
+    std::fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\nif [ \"$1\" = --mcp-tools ]; then echo '{listing}'; exit 0; fi\necho \"{{\\\"ok\\\":true,\\\"args\\\":\\\"$*\\\"}}\"\n"
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26"}}"#,
+        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"whois_lookup","arguments":{"domain":"example.com"}}}"#,
+        r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"whois_lookup","arguments":{"page":2}}}"#,
+        r#"{"jsonrpc":"2.0","id":5,"method":"resources/list"}"#,
+    ];
+    let out = Command::cargo_bin("dee")
+        .unwrap()
+        .env("PATH", bin.path())
+        .args(["mcp-serve", "--tool", "whois"])
+        .write_stdin(requests.join("\n"))
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let replies: Vec<serde_json::Value> = String::from_utf8(out.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(replies.len(), 5);
+
+    assert_eq!(replies[0]["result"]["protocolVersion"], "2025-03-26");
+    assert_eq!(replies[0]["result"]["serverInfo"]["name"], "dee");
+
+    let tools = replies[1]["result"]["tools"].as_array().unwrap();
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0]["name"], "whois_lookup");
+    assert_eq!(tools[0]["inputSchema"]["required"][0], "domain");
+
+    let called = &replies[2]["result"];
+    assert_eq!(called["isError"], false);
+    assert_eq!(
+        called["structuredContent"]["args"],
+        "lookup --json -- example.com"
+    );
+
+    assert_eq!(replies[3]["id"], 4);
+    assert_eq!(replies[3]["error"]["code"], -32602);
+    assert_eq!(replies[4]["error"]["code"], -32601);
+}
+
+#[test]
+fn mcp_serve_rejects_unknown_tools() {
+    Command::cargo_bin("dee")
+        .unwrap()
+        .args(["mcp-serve", "--tool", "hm"])
+        .assert()
+        .code(3)
+        .stderr(contains("did you mean `hn`"));
+}
+
+#[cfg(unix)]
+#[test]
+fn history_records_runs_once_enabled_and_reruns_them() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let home = tempfile::tempdir().unwrap();
+    let bin = home.path().join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    let script = bin.join("dee-whois");
+    std::fs::write(&script, "#!/bin/sh\necho \"args: $*\"\nexit 3\n").unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let dee = || {
+        let mut cmd = Command::cargo_bin("dee").unwrap();
+        cmd.env("PATH", &bin)
+            .env("HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path().join("config"))
+            .env("XDG_DATA_HOME", home.path().join("data"))
+            .env_remove("DEE_HISTORY");
+        cmd
+    };
+
+    // Off by default
+    dee().args(["whois", "example.com"]).assert().code(3);
+    let listed = json(dee().args(["history", "list", "--json"]));
+    assert_eq!(listed["count"], 0);
+
+    dee().args(["history", "enable"]).assert().success();
+    dee()
+        .args(["whois", "example.com", "--json"])
+        .assert()
+        .code(3);
+    dee()
+        .args(["whois", "config", "set", "api_key", "pk1_secret"])
+        .assert()
+        .code(3);
+
+    let listed = json(dee().args(["history", "list", "--tool", "whois", "--json"]));
+    assert_eq!(listed["count"], 2);
+    let newest = &listed["items"][0];
+    assert_eq!(
+        newest["args"],
+        serde_json::json!(["config", "set", "api_key", "<redacted>"])
+    );
+    assert_eq!(newest["redacted"], true);
+    let first = &listed["items"][1];
+    assert_eq!(first["tool"], "whois");
+    assert_eq!(first["exit_code"], 3);
+    assert!(first["duration_ms"].is_u64());
+
+    let id = first["id"].as_i64().unwrap().to_string();
+    dee()
+        .args(["history", "rerun", &id])
+        .assert()
+        .code(3)
+        .stdout("args: example.com --json\n");
+
+    let secret_id = newest["id"].as_i64().unwrap().to_string();
+    let refused = json(dee().args(["history", "rerun", &secret_id, "--json"]));
+    assert_eq!(refused["code"], "INVALID_ARGUMENT");
+
+    let cleared = json(dee().args(["history", "clear", "--json"]));
+    assert_eq!(cleared["item"]["removed"], 3);
+}
@@ -0,0 +1,138 @@
+//! Workspace tasks, run as `cargo xtask <task>`.
+//!
+//! `man [<package>...]` builds the tool binaries and writes each one's
+//! `--generate-man` output to `target/man/<binary>.1`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::Value;
+
+const USAGE: &str = "Usage: cargo xtask man [<package>...]\n\nEXAMPLES:\n  cargo xtask man\n  cargo xtask man dee-hn dee-todo";
+
+struct Binary {
+    package: String,
+    name: String,
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let result = match args.next().as_deref() {
+        Some("man") => man(&args.collect::<Vec<_>>()),
+        Some("-h" | "--help") => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        _ => Err(USAGE.to_string()),
+    };
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn man(packages: &[String]) -> Result<(), String> {
+    let metadata = cargo_metadata()?;
+    let target_dir = metadata["target_directory"]
+        .as_str()
+        .map(PathBuf::from)
+        .ok_or("cargo metadata has no target_directory")?;
+    let binaries: Vec<Binary> = binaries(&metadata)
+        .into_iter()
+        .filter(|bin| packages.is_empty() || packages.contains(&bin.package))
+        .collect();
+    if let Some(unknown) = packages
+        .iter()
+        .find(|package| !binaries.iter().any(|bin| &bin.package == *package))
+    {
+        return Err(format!("No binaries in package `{unknown}`"));
+    }
+
+    let mut build = Command::new(cargo());
+    build.args(["build", "--quiet"]);
+    for bin in &binaries {
+        build.args(["--package", &bin.package, "--bin", &bin.name]);
+    }
+    run(&mut build)?;
+
+    let out_dir = target_dir.join("man");
+    std::fs::create_dir_all(&out_dir)
+        .map_err(|err| format!("Could not create {}: {err}", out_dir.display()))?;
+    for bin in &binaries {
+        let page = generate_man(&target_dir, &bin.name)?;
+        let path = out_dir.join(format!("{}.1", bin.name));
+        std::fs::write(&path, page)
+            .map_err(|err| format!("Could not write {}: {err}", path.display()))?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+fn cargo() -> String {
+    std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string())
+}
+
+fn cargo_metadata() -> Result<Value, String> {
+    let output = Command::new(cargo())
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .output()
+        .map_err(|err| format!("Could not run cargo metadata: {err}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).into_owned());
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|err| format!("Could not parse cargo metadata: {err}"))
+}
+
+/// Every `bin` target in the workspace except this one.
+fn binaries(metadata: &Value) -> Vec<Binary> {
+    let mut binaries = Vec::new();
+    for package in metadata["packages"].as_array().into_iter().flatten() {
+        let Some(package_name) = package["name"].as_str() else {
+            continue;
+        };
+        if package_name == "xtask" {
+            continue;
+        }
+        for target in package["targets"].as_array().into_iter().flatten() {
+            let is_bin = target["kind"]
+                .as_array()
+                .is_some_and(|kinds| kinds.iter().any(|kind| kind == "bin"));
+            if let (true, Some(name)) = (is_bin, target["name"].as_str()) {
+                binaries.push(Binary {
+                    package: package_name.to_string(),
+                    name: name.to_string(),
+                });
+            }
+        }
+    }
+    binaries.sort_by(|a, b| a.name.cmp(&b.name));
+    binaries
+}
+
+fn generate_man(target_dir: &Path, binary: &str) -> Result<Vec<u8>, String> {
+    let exe = target_dir
+        .join("debug")
+        .join(format!("{binary}{}", std::env::consts::EXE_SUFFIX));
+    let output = Command::new(&exe)
+        .arg("--generate-man")
+        .output()
+        .map_err(|err| format!("Could not run {}: {err}", exe.display()))?;
+    if !output.status.success() || !output.stdout.starts_with(b".TH") {
+        return Err(format!(
+            "`{binary} --generate-man` did not print a man page"
+        ));
+    }
+    Ok(output.stdout)
+}
+
+fn run(command: &mut Command) -> Result<(), String> {
+    let status = command
+        .status()
+        .map_err(|err| format!("Could not run cargo: {err}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("cargo exited with {status}"))
+    }
+}